@@ -0,0 +1,9 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // The Yellowstone client is only compiled when the `geyser` feature is enabled.
+    #[cfg(feature = "geyser")]
+    tonic_build::configure().build_server(false).compile(
+        &["proto/geyser.proto"], // The file to compile
+        &["proto"],              // The directory to search in
+    )?;
+    Ok(())
+}
@@ -0,0 +1,96 @@
+//! Splitting large payloads across several `log_action` records.
+//!
+//! The `metadata` of a single `log_action` is capped at `MAX_METADATA_SIZE` (128) bytes.
+//! Larger blobs can still be attached to a session by logging them in chunks: every chunk
+//! is a separate `log_action` with the same `session_id`, and its `metadata` starts with a
+//! small header followed by a slice of the payload:
+//!
+//! `version (1 byte) | sequence (u16 LE) | total (u16 LE) | data`
+//!
+//! The chunks of a payload may land in different transactions, so their events can be
+//! observed in any order. [`reassemble_payload`] sorts them by sequence number and checks
+//! that every chunk is present exactly once. Nothing about chunks is enforced on-chain.
+
+use crate::error::ConnectorError;
+use w3b2_solana_program::{events::OffChainActionLogged, instructions::MAX_METADATA_SIZE};
+
+/// The version of the chunk header described in the [module docs](self).
+pub const CHUNK_FORMAT_VERSION: u8 = 1;
+
+/// The length of the chunk header at the start of each chunk's `metadata`.
+pub const CHUNK_HEADER_LEN: usize = 5;
+
+/// The number of payload bytes carried by each chunk.
+pub const CHUNK_DATA_LEN: usize = MAX_METADATA_SIZE - CHUNK_HEADER_LEN;
+
+/// The largest payload that can be chunked, limited by the `u16` chunk count.
+pub const MAX_CHUNKED_PAYLOAD_LEN: usize = u16::MAX as usize * CHUNK_DATA_LEN;
+
+/// Splits `payload` into the `metadata` of consecutive `log_action` records.
+///
+/// An empty payload produces a single, empty chunk. Returns
+/// [`ConnectorError::PayloadTooLarge`] if the payload exceeds [`MAX_CHUNKED_PAYLOAD_LEN`].
+pub fn chunk_payload(payload: &[u8]) -> Result<Vec<Vec<u8>>, ConnectorError> {
+    if payload.len() > MAX_CHUNKED_PAYLOAD_LEN {
+        return Err(ConnectorError::PayloadTooLarge(payload.len()));
+    }
+    let total = payload.len().div_ceil(CHUNK_DATA_LEN).max(1) as u16;
+    Ok((0..total)
+        .map(|sequence| {
+            let start = sequence as usize * CHUNK_DATA_LEN;
+            let data = &payload[start..payload.len().min(start + CHUNK_DATA_LEN)];
+            let mut metadata = Vec::with_capacity(CHUNK_HEADER_LEN + data.len());
+            metadata.push(CHUNK_FORMAT_VERSION);
+            metadata.extend_from_slice(&sequence.to_le_bytes());
+            metadata.extend_from_slice(&total.to_le_bytes());
+            metadata.extend_from_slice(data);
+            metadata
+        })
+        .collect())
+}
+
+/// Reassembles a payload from the events of its `log_action` chunks.
+///
+/// The events may be given in any order, but must all belong to the same `session_id`
+/// and together hold every chunk of the payload exactly once.
+pub fn reassemble_payload(events: &[OffChainActionLogged]) -> Result<Vec<u8>, ConnectorError> {
+    let Some(first) = events.first() else {
+        return Err(ConnectorError::MissingChunk(0));
+    };
+
+    let mut chunks: Vec<Option<&[u8]>> = Vec::new();
+    for (position, event) in events.iter().enumerate() {
+        if event.session_id != first.session_id {
+            return Err(ConnectorError::ChunkSessionMismatch {
+                expected: first.session_id,
+                found: event.session_id,
+            });
+        }
+        let (sequence, total, data) =
+            parse_chunk(&event.metadata).ok_or(ConnectorError::MalformedChunk(position))?;
+        if chunks.is_empty() {
+            chunks.resize(total as usize, None);
+        } else if chunks.len() != total as usize {
+            return Err(ConnectorError::MalformedChunk(position));
+        }
+        if chunks[sequence as usize].replace(data).is_some() {
+            return Err(ConnectorError::DuplicateChunk(sequence));
+        }
+    }
+
+    let mut payload = Vec::with_capacity(chunks.len() * CHUNK_DATA_LEN);
+    for (sequence, data) in chunks.into_iter().enumerate() {
+        payload.extend_from_slice(data.ok_or(ConnectorError::MissingChunk(sequence as u16))?);
+    }
+    Ok(payload)
+}
+
+/// Splits a chunk's `metadata` into its sequence number, chunk count and data.
+fn parse_chunk(metadata: &[u8]) -> Option<(u16, u16, &[u8])> {
+    if metadata.len() < CHUNK_HEADER_LEN || metadata[0] != CHUNK_FORMAT_VERSION {
+        return None;
+    }
+    let sequence = u16::from_le_bytes([metadata[1], metadata[2]]);
+    let total = u16::from_le_bytes([metadata[3], metadata[4]]);
+    (sequence < total).then_some((sequence, total, &metadata[CHUNK_HEADER_LEN..]))
+}
@@ -25,19 +25,328 @@
 //!   PDAs and composing multi-instruction transactions (like the `Ed25519`
 //!   verification required for `user_dispatch_command`).
 
-use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
 use async_trait::async_trait;
-use solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient};
+use solana_address_lookup_table_interface::instruction::{
+    create_lookup_table, extend_lookup_table,
+};
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_client::rpc_custom_error::{
+    JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+};
+use solana_client::rpc_request::RpcError;
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
 use solana_ed25519_program::new_ed25519_instruction_with_signature;
+use solana_sdk::account::Account;
 use solana_sdk::instruction::Instruction;
+use solana_sdk::message::{v0, AddressLookupTableAccount, Message, VersionedMessage};
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::short_vec;
 use solana_sdk::sysvar;
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::{Transaction, TransactionError};
 use solana_sdk::{hash::Hash, signature::Signature};
+use solana_transaction_status::TransactionStatus;
+use std::collections::HashSet;
+use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
+use w3b2_solana_program::instructions::{
+    MAX_TIMESTAMP_VALIDITY_SECONDS, MIN_TIMESTAMP_VALIDITY_SECONDS,
+};
 use w3b2_solana_program::{accounts, instruction};
 
+use crate::chunking::chunk_payload;
+use crate::error::{ConnectorError, SIGNATURE_LENGTH};
+use crate::oracle::build_price_message;
+use crate::pda::{
+    find_admin_profile_pda_for, find_allowance_pda_for, find_user_profile_pda_for,
+    find_user_wallet_pda_for,
+};
+use crate::simulation::SimulationReport;
+
 pub use crate::dispatcher::UserDispatchCommandArgs;
+/// Whether the sender of a dispatched command expects a response.
+pub use w3b2_solana_program::protocols::CommandMode;
+/// The account returned by [`TransactionBuilder::fetch_admin_profile`].
+pub use w3b2_solana_program::state::AdminProfile;
+/// The account returned by [`TransactionBuilder::fetch_user_profiles`].
+pub use w3b2_solana_program::state::UserProfile;
+
+/// The estimated compute units consumed by an `admin_dispatch_command` with an empty payload.
+pub const ADMIN_DISPATCH_BASE_COMPUTE_UNITS: u32 = 15_000;
+/// The estimated compute units an `admin_dispatch_command` consumes per payload byte. The
+/// payload is deserialized, re-serialized into the event and logged, so the cost grows
+/// linearly with its size.
+pub const ADMIN_DISPATCH_COMPUTE_UNITS_PER_PAYLOAD_BYTE: u32 = 4;
+/// The compute units consumed by the `set_compute_unit_limit` instruction itself.
+const SET_COMPUTE_UNIT_LIMIT_UNITS: u32 = 150;
+/// The maximum compute units a single transaction may request.
+pub const MAX_COMPUTE_UNITS_PER_TRANSACTION: u32 = 1_400_000;
+/// The `command_id` of the notifications sent by [`TransactionBuilder::prepare_admin_shutdown`],
+/// reserved for telling users that their admin's service is closing.
+pub const ADMIN_SHUTDOWN_COMMAND_ID: u64 = u64::MAX;
+/// The maximum size of a serialized transaction, as sent over the wire.
+const MAX_TRANSACTION_SIZE: usize = 1232;
+/// The maximum number of accounts a single `getMultipleAccounts` request may ask for.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Estimates the compute units consumed by an `admin_dispatch_command` carrying a payload
+/// of `payload_len` bytes.
+pub fn estimate_admin_dispatch_compute_units(payload_len: usize) -> u32 {
+    let payload_len = u32::try_from(payload_len).unwrap_or(u32::MAX);
+    ADMIN_DISPATCH_BASE_COMPUTE_UNITS
+        .saturating_add(payload_len.saturating_mul(ADMIN_DISPATCH_COMPUTE_UNITS_PER_PAYLOAD_BYTE))
+}
+
+/// Returns whether `message`, once signed by all of its signers, stays within
+/// [`MAX_TRANSACTION_SIZE`].
+fn fits_in_transaction(message: &Message) -> bool {
+    // A compact-u16 signature count of one byte, followed by the signatures.
+    let signatures_len = 1 + 64 * message.header.num_required_signatures as usize;
+    signatures_len + message.serialize().len() <= MAX_TRANSACTION_SIZE
+}
+
+/// Like [`fits_in_transaction`], for a v0 message.
+fn fits_in_versioned_transaction(message: &v0::Message) -> bool {
+    let signatures_len = 1 + 64 * message.header.num_required_signatures as usize;
+    let message = VersionedMessage::V0(message.clone());
+    signatures_len + message.serialize().len() <= MAX_TRANSACTION_SIZE
+}
+
+/// Returns the byte offset of the `recent_blockhash` in a message serialized by the
+/// [`TransactionBuilder`], or `None` if `message` is too short to be one.
+///
+/// Every `prepare_*` method leaves the 32 bytes at this offset zeroed. Clients overwrite
+/// them with a recent blockhash right before signing, without decoding the message.
+pub fn blockhash_placeholder_offset(message: &[u8]) -> Option<usize> {
+    // The message header is followed by the short-vec encoded list of account keys.
+    const HEADER_LEN: usize = 3;
+    let (key_count, len_bytes) = short_vec::decode_shortu16_len(message.get(HEADER_LEN..)?).ok()?;
+    let offset = HEADER_LEN + len_bytes + key_count * 32;
+    (message.len() >= offset + 32).then_some(offset)
+}
+
+/// Writes `blockhash` into the placeholder of a message prepared by the
+/// [`TransactionBuilder`]. Returns `false` if `message` is too short to be one.
+#[must_use]
+pub fn set_message_blockhash(message: &mut [u8], blockhash: &Hash) -> bool {
+    let Some(offset) = blockhash_placeholder_offset(message) else {
+        return false;
+    };
+    message[offset..offset + 32].copy_from_slice(blockhash.as_ref());
+    true
+}
+
+/// A message serialized by one of the `prepare_*` methods of the [`TransactionBuilder`],
+/// along with its decoded form.
+///
+/// Lets tests and tooling inspect the accounts and instruction data of a prepared
+/// transaction without decoding the bytes themselves:
+///
+/// ```ignore
+/// let prepared = PreparedMessage::decode(builder.prepare_user_deposit(authority, admin, 1_000))?;
+/// assert_eq!(prepared.message.account_keys[0], authority);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedMessage {
+    /// The decoded message. Its `recent_blockhash` is still the zeroed placeholder.
+    pub message: Message,
+    /// The serialized message, exactly as returned by the `prepare_*` method.
+    pub bytes: Vec<u8>,
+}
+
+impl PreparedMessage {
+    /// Decodes `bytes` as returned by a `prepare_*` method. Fails if they are not a
+    /// serialized legacy `Message`, such as the v0 messages of
+    /// [`TransactionBuilder::prepare_admin_broadcast_with_lookup_table`].
+    pub fn decode(bytes: Vec<u8>) -> Result<Self, bincode::error::DecodeError> {
+        let (message, _) = bincode::serde::decode_from_slice(&bytes, bincode::config::standard())?;
+        Ok(Self { message, bytes })
+    }
+
+    /// Returns the program ids of the message's instructions, in order.
+    pub fn program_ids(&self) -> Vec<Pubkey> {
+        self.message
+            .instructions
+            .iter()
+            .map(|ix| *ix.program_id(&self.message.account_keys))
+            .collect()
+    }
+}
+
+/// Controls how [`TransactionBuilder::prepare_admin_broadcast`] packs its instructions
+/// into transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BroadcastOptions {
+    /// The maximum number of distinct accounts referenced by one transaction.
+    pub max_accounts_per_tx: usize,
+    /// If set, a transaction is also closed once the estimated compute units of its
+    /// instructions would exceed this budget, and every transaction starts with a
+    /// `set_compute_unit_limit` instruction requesting its estimate.
+    pub pack_by_compute: Option<u32>,
+}
+
+impl Default for BroadcastOptions {
+    fn default() -> Self {
+        Self {
+            max_accounts_per_tx: 64,
+            pack_by_compute: None,
+        }
+    }
+}
+
+/// Compute budget instructions prepended to a prepared transaction, see
+/// [`TransactionBuilder::prepare_user_dispatch_command_with_compute_budget`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ComputeBudget {
+    /// If set, requests this many compute units with `set_compute_unit_limit`.
+    pub unit_limit: Option<u32>,
+    /// If set, pays this priority fee, in micro-lamports per compute unit, with
+    /// `set_compute_unit_price`.
+    pub unit_price: Option<u64>,
+}
+
+impl ComputeBudget {
+    fn instructions(&self) -> Vec<Instruction> {
+        let limit = self
+            .unit_limit
+            .map(ComputeBudgetInstruction::set_compute_unit_limit);
+        let price = self
+            .unit_price
+            .map(ComputeBudgetInstruction::set_compute_unit_price);
+        limit.into_iter().chain(price).collect()
+    }
+}
+
+/// Controls how [`TransactionBuilder::submit_transaction_with_retry`] retries transient
+/// failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SubmitRetryPolicy {
+    /// How many times a failed submission is retried. `0` submits exactly once.
+    pub max_retries: u32,
+    /// The delay before the first retry. Every further retry waits twice as long as the
+    /// previous one.
+    pub base_delay: Duration,
+}
+
+impl SubmitRetryPolicy {
+    /// Returns the delay before retry number `retry`, counted from zero.
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(retry.min(16)))
+    }
+}
+
+/// The error returned by [`TransactionBuilder::submit_transaction_with_retry`] once it
+/// gives up.
+#[derive(Debug)]
+pub struct SubmitError {
+    /// How many times the transaction was submitted, including the first attempt.
+    pub attempts: u32,
+    /// The error of the last attempt.
+    pub source: ClientError,
+    /// Whether the blockhash of the transaction had expired. Such a transaction can never
+    /// land, and has to be signed again with a fresh blockhash.
+    pub blockhash_expired: bool,
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.blockhash_expired {
+            return write!(
+                f,
+                "the blockhash of the transaction expired after {} attempt(s): {}",
+                self.attempts, self.source
+            );
+        }
+        write!(
+            f,
+            "transaction submission failed after {} attempt(s): {}",
+            self.attempts, self.source
+        )
+    }
+}
+
+impl std::error::Error for SubmitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Converts the raw bytes of an oracle signature, e.g. received over the wire, into the
+/// fixed-size array expected by [`UserDispatchCommandArgs`].
+pub fn oracle_signature_from_slice(bytes: &[u8]) -> Result<[u8; SIGNATURE_LENGTH], ConnectorError> {
+    bytes
+        .try_into()
+        .map_err(|_| ConnectorError::InvalidSignatureLength(bytes.len()))
+}
+
+/// Returns `true` if a failed submission may have been caused by an expired blockhash:
+/// either the node does not know the blockhash, or the transaction could not be confirmed
+/// before its blockhash became too old.
+fn may_be_blockhash_expiry(err: &ClientError) -> bool {
+    err.get_transaction_error() == Some(TransactionError::BlockhashNotFound)
+        || matches!(err.kind(), ClientErrorKind::RpcError(RpcError::ForUser(_)))
+}
+
+/// Returns `true` if a failed submission may succeed when the same signed transaction is
+/// sent again.
+///
+/// Transport failures, an unhealthy node and an unknown blockhash (usually a node lagging
+/// behind the cluster) are transient. Program errors, an already processed transaction and
+/// every other transaction error are not: resending would fail the same way.
+pub fn is_transient_submit_error(err: &ClientError) -> bool {
+    if let Some(tx_err) = err.get_transaction_error() {
+        return tx_err == TransactionError::BlockhashNotFound;
+    }
+    match err.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => matches!(
+            *code,
+            JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY | JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE
+        ),
+        _ => false,
+    }
+}
+
+/// Decodes the account fetched at a `UserProfile` PDA. Anyone can fund an address that
+/// holds no profile, so an account not owned by the program is treated as missing.
+#[allow(clippy::result_large_err)]
+fn decode_user_profile(
+    program_id: &Pubkey,
+    pda: &Pubkey,
+    account: Option<Account>,
+) -> Result<Option<UserProfile>, ClientError> {
+    let Some(account) = account.filter(|account| account.owner == *program_id) else {
+        return Ok(None);
+    };
+    UserProfile::try_deserialize(&mut account.data.as_slice())
+        .map(Some)
+        .map_err(|e| {
+            ClientErrorKind::Custom(format!("account {pda} is not a UserProfile: {e}")).into()
+        })
+}
+
+/// Decodes a fetched account as an `AdminProfile`. Accounts that do not exist or are not
+/// owned by the program decode to `None`.
+#[allow(clippy::result_large_err)]
+fn decode_admin_profile(
+    program_id: &Pubkey,
+    pda: &Pubkey,
+    account: Option<Account>,
+) -> Result<Option<AdminProfile>, ClientError> {
+    let Some(account) = account.filter(|account| account.owner == *program_id) else {
+        return Ok(None);
+    };
+    AdminProfile::try_deserialize(&mut account.data.as_slice())
+        .map(Some)
+        .map_err(|e| {
+            ClientErrorKind::Custom(format!("account {pda} is not an AdminProfile: {e}")).into()
+        })
+}
 
 /// A trait abstracting over the asynchronous RPC client functionality.
 ///
@@ -52,6 +361,51 @@ pub trait AsyncRpcClient: Send + Sync {
         &self,
         transaction: &Transaction,
     ) -> Result<Signature, ClientError>;
+    /// Simulates a transaction without verifying its signatures, replacing its recent
+    /// blockhash with the latest one.
+    ///
+    /// The default implementation reports simulations as unsupported.
+    async fn simulate_unsigned_transaction(
+        &self,
+        _transaction: &Transaction,
+    ) -> Result<RpcSimulateTransactionResult, ClientError> {
+        Err(ClientErrorKind::Custom("transaction simulation is not supported".to_string()).into())
+    }
+
+    /// Checks whether a blockhash can still be used by new transactions.
+    ///
+    /// The default implementation treats every blockhash as valid.
+    async fn is_blockhash_valid(&self, _blockhash: &Hash) -> Result<bool, ClientError> {
+        Ok(true)
+    }
+
+    /// Fetches up to 100 accounts in a single request, returning `None` for the addresses
+    /// that hold no account.
+    ///
+    /// The default implementation reports batched fetches as unsupported.
+    async fn get_multiple_accounts(
+        &self,
+        _pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, ClientError> {
+        Err(
+            ClientErrorKind::Custom("fetching multiple accounts is not supported".to_string())
+                .into(),
+        )
+    }
+
+    /// Fetches the statuses of up to 256 transactions, returning `None` for the signatures
+    /// the node does not know about.
+    ///
+    /// The default implementation reports status queries as unsupported.
+    async fn get_signature_statuses(
+        &self,
+        _signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>, ClientError> {
+        Err(
+            ClientErrorKind::Custom("fetching signature statuses is not supported".to_string())
+                .into(),
+        )
+    }
 }
 
 #[async_trait]
@@ -66,6 +420,40 @@ impl AsyncRpcClient for RpcClient {
     ) -> Result<Signature, ClientError> {
         self.send_and_confirm_transaction(transaction).await
     }
+
+    async fn simulate_unsigned_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<RpcSimulateTransactionResult, ClientError> {
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.commitment()),
+            ..Default::default()
+        };
+        Ok(self
+            .simulate_transaction_with_config(transaction, config)
+            .await?
+            .value)
+    }
+
+    async fn is_blockhash_valid(&self, blockhash: &Hash) -> Result<bool, ClientError> {
+        self.is_blockhash_valid(blockhash, self.commitment()).await
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, ClientError> {
+        self.get_multiple_accounts(pubkeys).await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>, ClientError> {
+        Ok(self.get_signature_statuses(signatures).await?.value)
+    }
 }
 impl<C> TransactionBuilder<C>
 where
@@ -76,19 +464,292 @@ where
         self.rpc_client.send_and_confirm_transaction(tx).await
     }
 
+    /// Submits a signed transaction, retrying transient failures according to `policy`.
+    ///
+    /// Only errors classified by [`is_transient_submit_error`] are retried, with an
+    /// exponential backoff between attempts. When a failure may be caused by an old
+    /// blockhash, the blockhash of the transaction is checked with
+    /// [`AsyncRpcClient::is_blockhash_valid`]: if it has expired, the transaction can never
+    /// land, so the error is returned right away with [`SubmitError::blockhash_expired`] set.
+    pub async fn submit_transaction_with_retry(
+        &self,
+        tx: &Transaction,
+        policy: &SubmitRetryPolicy,
+    ) -> Result<Signature, SubmitError> {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let err = match self.rpc_client.send_and_confirm_transaction(tx).await {
+                Ok(signature) => return Ok(signature),
+                Err(err) => err,
+            };
+            // If the validity check itself fails, assume the node is lagging behind.
+            let blockhash_expired = may_be_blockhash_expiry(&err)
+                && !self
+                    .rpc_client
+                    .is_blockhash_valid(&tx.message.recent_blockhash)
+                    .await
+                    .unwrap_or(true);
+            let retries = attempts - 1;
+            if blockhash_expired
+                || retries >= policy.max_retries
+                || !is_transient_submit_error(&err)
+            {
+                return Err(SubmitError {
+                    attempts,
+                    source: err,
+                    blockhash_expired,
+                });
+            }
+            tracing::debug!(
+                "Submission attempt {} failed with a transient error, retrying: {}",
+                attempts,
+                err
+            );
+            tokio::time::sleep(policy.delay_for(retries)).await;
+        }
+    }
+
+    /// Fetches the `UserProfile` accounts of many users in as few RPC requests as possible.
+    ///
+    /// Each pair is a user's wallet (`authority`) and the `AdminProfile` PDA the profile
+    /// belongs to. The profile PDAs are fetched with `getMultipleAccounts`, in chunks of 100.
+    /// The returned vector has one entry per pair, in the same order, which is `None` if the
+    /// profile does not exist.
+    pub async fn fetch_user_profiles(
+        &self,
+        pairs: &[(Pubkey, Pubkey)],
+    ) -> Result<Vec<Option<UserProfile>>, ClientError> {
+        let pdas: Vec<Pubkey> = pairs
+            .iter()
+            .map(|(authority, admin_profile_pda)| {
+                find_user_profile_pda_for(&self.program_id, authority, admin_profile_pda).0
+            })
+            .collect();
+        let mut profiles = Vec::with_capacity(pdas.len());
+        for chunk in pdas.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let accounts = self.rpc_client.get_multiple_accounts(chunk).await?;
+            for (pda, account) in chunk.iter().zip(accounts) {
+                profiles.push(decode_user_profile(&self.program_id, pda, account)?);
+            }
+        }
+        Ok(profiles)
+    }
+
+    /// Fetches the `AdminProfile` account at `admin_profile_pda`, or `None` if it does not
+    /// exist.
+    pub async fn fetch_admin_profile(
+        &self,
+        admin_profile_pda: &Pubkey,
+    ) -> Result<Option<AdminProfile>, ClientError> {
+        let accounts = self
+            .rpc_client
+            .get_multiple_accounts(std::slice::from_ref(admin_profile_pda))
+            .await?;
+        decode_admin_profile(
+            &self.program_id,
+            admin_profile_pda,
+            accounts.into_iter().next().flatten(),
+        )
+    }
+
+    /// Simulates a message returned by one of the `prepare_*` methods, before it is signed.
+    ///
+    /// The simulation skips signature verification and uses the latest blockhash, so the
+    /// zeroed placeholder does not need to be filled in. A transaction that would fail is
+    /// reported through [`SimulationReport::error`], with custom program errors (such as a
+    /// `BridgeError`) decoded from the program logs.
+    pub async fn simulate_message(
+        &self,
+        message: Message,
+    ) -> Result<SimulationReport, ClientError> {
+        let transaction = Transaction::new_unsigned(message);
+        let result = self
+            .rpc_client
+            .simulate_unsigned_transaction(&transaction)
+            .await?;
+        Ok(SimulationReport::new(
+            result.err,
+            result.logs.unwrap_or_default(),
+            result.units_consumed,
+        ))
+    }
+
+    /// Returns the fee payer of the messages signed by `authority`: the builder's
+    /// [`fee_payer`](TransactionBuilder::with_fee_payer) if one is set, `authority` otherwise.
+    fn payer(&self, authority: Pubkey) -> Pubkey {
+        self.fee_payer.unwrap_or(authority)
+    }
+
     /// A private helper to create a message from a vector of instructions.
     ///
     /// This function encapsulates the boilerplate of creating a new message
-    /// with a specified fee payer.
-    fn create_message_with_instructions(payer: &Pubkey, instructions: Vec<Instruction>) -> Vec<u8> {
-        // Using `Message::new` is more robust as it correctly deduces the fee payer
-        // from the first account in the first instruction that is a signer.
-        // We ensure the payer is the first signer account for clarity.
-        let msg = solana_sdk::message::Message::new(&instructions, Some(payer));
+    /// with the fee payer of `authority`. Its `recent_blockhash` is left zeroed, as a
+    /// placeholder at [`blockhash_placeholder_offset`].
+    fn create_message_with_instructions(
+        &self,
+        authority: &Pubkey,
+        instructions: Vec<Instruction>,
+    ) -> Vec<u8> {
+        // `Message::new` puts the payer first among the signers, ahead of the authority
+        // when the two differ.
+        let msg = Message::new(&instructions, Some(&self.payer(*authority)));
         bincode::serde::encode_to_vec(&msg, bincode::config::standard()).unwrap()
     }
+
+    /// Returns the instructions of one batch of a broadcast, prepending a
+    /// `set_compute_unit_limit` instruction when packing by compute units.
+    fn broadcast_instructions(
+        instructions: &[Instruction],
+        compute_units: u32,
+        options: &BroadcastOptions,
+    ) -> Vec<Instruction> {
+        let mut all_instructions = Vec::with_capacity(instructions.len() + 1);
+        if options.pack_by_compute.is_some() {
+            let limit = compute_units
+                .saturating_add(SET_COMPUTE_UNIT_LIMIT_UNITS)
+                .min(MAX_COMPUTE_UNITS_PER_TRANSACTION);
+            all_instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+        }
+        all_instructions.extend_from_slice(instructions);
+        all_instructions
+    }
+
+    /// Builds the message for one batch of a broadcast.
+    fn broadcast_message(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        compute_units: u32,
+        options: &BroadcastOptions,
+    ) -> Message {
+        let instructions = Self::broadcast_instructions(instructions, compute_units, options);
+        Message::new(&instructions, Some(payer))
+    }
+
+    /// Builds the v0 message for one batch of a broadcast. Accounts found in `lookup_table`
+    /// are loaded from it instead of being listed in the message.
+    fn broadcast_message_v0(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        compute_units: u32,
+        options: &BroadcastOptions,
+        lookup_table: &AddressLookupTableAccount,
+    ) -> Option<v0::Message> {
+        let instructions = Self::broadcast_instructions(instructions, compute_units, options);
+        v0::Message::try_compile(
+            payer,
+            &instructions,
+            std::slice::from_ref(lookup_table),
+            Hash::default(),
+        )
+        .ok()
+    }
+
+    /// Returns `true` if a broadcast batch stays within the compute budget of `options`.
+    fn broadcast_within_budget(compute_units: u32, options: &BroadcastOptions) -> bool {
+        options.pack_by_compute.is_none_or(|budget| {
+            compute_units.saturating_add(SET_COMPUTE_UNIT_LIMIT_UNITS) <= budget
+        })
+    }
+
+    /// Returns `true` if a broadcast batch stays within the compute budget and the account
+    /// limit of `options` and, once signed, within the maximum transaction size.
+    fn broadcast_batch_fits(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        compute_units: u32,
+        options: &BroadcastOptions,
+    ) -> bool {
+        if !Self::broadcast_within_budget(compute_units, options) {
+            return false;
+        }
+        let message = Self::broadcast_message(payer, instructions, compute_units, options);
+        message.account_keys.len() <= options.max_accounts_per_tx && fits_in_transaction(&message)
+    }
+
+    /// Like [`broadcast_batch_fits`](Self::broadcast_batch_fits), for a v0 batch. The
+    /// accounts loaded from `lookup_table` count against the account limit as well.
+    fn broadcast_batch_fits_v0(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        compute_units: u32,
+        options: &BroadcastOptions,
+        lookup_table: &AddressLookupTableAccount,
+    ) -> bool {
+        if !Self::broadcast_within_budget(compute_units, options) {
+            return false;
+        }
+        let Some(message) =
+            Self::broadcast_message_v0(payer, instructions, compute_units, options, lookup_table)
+        else {
+            return false;
+        };
+        let loaded: usize = message
+            .address_table_lookups
+            .iter()
+            .map(|lookup| lookup.writable_indexes.len() + lookup.readonly_indexes.len())
+            .sum();
+        message.account_keys.len() + loaded <= options.max_accounts_per_tx
+            && fits_in_versioned_transaction(&message)
+    }
+
+    /// Builds one `admin_dispatch_command` instruction per target and packs them, in order,
+    /// into batches accepted by `fits`. Returns each batch with its estimated compute units.
+    fn pack_broadcast(
+        &self,
+        authority: Pubkey,
+        admin_index: u16,
+        target_user_profile_pdas: &[Pubkey],
+        command_id: u64,
+        payload: Vec<u8>,
+        fits: impl Fn(&[Instruction], u32) -> bool,
+    ) -> Vec<(Vec<Instruction>, u32)> {
+        let (admin_pda, _) = find_admin_profile_pda_for(&self.program_id, &authority, admin_index);
+        let units_per_ix = estimate_admin_dispatch_compute_units(payload.len());
+
+        let mut batches = Vec::new();
+        let mut batch: Vec<Instruction> = Vec::new();
+        let mut batch_units = 0u32;
+        for &user_profile in target_user_profile_pdas {
+            let ix = Instruction {
+                program_id: self.program_id,
+                accounts: accounts::AdminDispatchCommand {
+                    admin_authority: authority,
+                    admin_profile: admin_pda,
+                    user_profile,
+                }
+                .to_account_metas(None),
+                data: instruction::AdminDispatchCommand {
+                    command_id,
+                    payload: payload.clone(),
+                    mode: CommandMode::OneWay,
+                }
+                .data(),
+            };
+
+            batch.push(ix);
+            let units = batch_units.saturating_add(units_per_ix);
+            if batch.len() > 1 && !fits(&batch, units) {
+                let ix = batch
+                    .pop()
+                    .expect("the batch holds at least two instructions");
+                batches.push((std::mem::replace(&mut batch, vec![ix]), batch_units));
+                batch_units = units_per_ix;
+            } else {
+                batch_units = units;
+            }
+        }
+        if !batch.is_empty() {
+            batches.push((batch, batch_units));
+        }
+        batches
+    }
 }
 
+/// Checks an application payload before it is dispatched, returning a description of the
+/// problem if it is malformed. See [`TransactionBuilder::with_payload_validator`].
+pub type PayloadValidator = Arc<dyn Fn(&[u8]) -> Result<(), String> + Send + Sync>;
+
 /// A builder for preparing unsigned on-chain transactions.
 ///
 /// This struct provides `prepare_` methods to construct unsigned transactions for
@@ -98,6 +759,12 @@ where
 pub struct TransactionBuilder<C: AsyncRpcClient + ?Sized> {
     /// A shared, thread-safe reference to a Solana JSON RPC client.
     rpc_client: Arc<C>,
+    /// The account paying the fees of the prepared transactions, if not their authority.
+    fee_payer: Option<Pubkey>,
+    /// Checks the payloads of dispatched commands before they are built into a transaction.
+    payload_validator: Option<PayloadValidator>,
+    /// The id of the program deployment the prepared instructions are addressed to.
+    program_id: Pubkey,
 }
 
 impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
@@ -107,7 +774,55 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
     ///
     /// * `rpc_client` - A shared client that implements [`AsyncRpcClient`] (e.g., `Arc<RpcClient>`).
     pub fn new(rpc_client: Arc<C>) -> Self {
-        Self { rpc_client }
+        Self {
+            rpc_client,
+            fee_payer: None,
+            payload_validator: None,
+            program_id: w3b2_solana_program::ID,
+        }
+    }
+
+    /// Addresses the prepared instructions, and derives their PDAs, under `program_id`
+    /// instead of the id the connector is built against, e.g. the `program_id` of the
+    /// [`Solana`](crate::config::Solana) config of another deployment.
+    pub fn with_program_id(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    /// The id of the program deployment this builder prepares instructions for.
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    /// Makes `fee_payer` pay the fees of every transaction prepared by this builder.
+    ///
+    /// By default the authority of an instruction also pays for its transaction. With a
+    /// separate fee payer, e.g. a relayer, the prepared messages require two signatures:
+    /// the fee payer's first, then the authority's.
+    pub fn with_fee_payer(mut self, fee_payer: Pubkey) -> Self {
+        self.fee_payer = Some(fee_payer);
+        self
+    }
+
+    /// Makes [`prepare_admin_dispatch_command`](Self::prepare_admin_dispatch_command) and
+    /// [`prepare_user_dispatch_command`](Self::prepare_user_dispatch_command) check every
+    /// payload with `validator` before building the transaction.
+    ///
+    /// The program treats payloads as opaque bytes, so without a validator a malformed
+    /// payload is only noticed by whoever processes the command. A validator lets a service
+    /// enforce its payload schema, e.g. that it decodes as a protobuf message, up front.
+    pub fn with_payload_validator(mut self, validator: PayloadValidator) -> Self {
+        self.payload_validator = Some(validator);
+        self
+    }
+
+    /// Runs the payload validator, if any, on `payload`.
+    fn validate_payload(&self, payload: &[u8]) -> Result<(), ConnectorError> {
+        match &self.payload_validator {
+            Some(validator) => validator(payload).map_err(ConnectorError::InvalidPayload),
+            None => Ok(()),
+        }
     }
 
     // --- Admin Transaction Preparations ---
@@ -117,17 +832,19 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
     /// # Arguments
     ///
     /// * `authority` - The public key of the admin's wallet that will sign the transaction.
+    /// * `admin_index` - The index of the new profile among the authority's profiles. `0` is
+    ///   the profile at the original, index-free address.
     /// * `communication_pubkey` - The public key for secure off-chain communication.
     pub fn prepare_admin_register_profile(
         &self,
         authority: Pubkey,
+        admin_index: u16,
         communication_pubkey: Pubkey,
     ) -> Vec<u8> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_solana_program::ID);
+        let (admin_pda, _) = find_admin_profile_pda_for(&self.program_id, &authority, admin_index);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::AdminRegisterProfile {
                 authority,
                 admin_profile: admin_pda,
@@ -136,11 +853,12 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             .to_account_metas(None),
             data: instruction::AdminRegisterProfile {
                 communication_pubkey,
+                index: Some(admin_index),
             }
             .data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     /// Prepares an `admin_ban_user` transaction.
@@ -148,17 +866,18 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
     /// # Arguments
     ///
     /// * `authority` - The public key of the admin's wallet.
+    /// * `admin_index` - The index of the authority's `AdminProfile` to act on.
     /// * `target_user_profile_pda` - The PDA of the `UserProfile` to be banned.
     pub fn prepare_admin_ban_user(
         &self,
         authority: Pubkey,
+        admin_index: u16,
         target_user_profile_pda: Pubkey,
     ) -> Vec<u8> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_solana_program::ID);
+        let (admin_pda, _) = find_admin_profile_pda_for(&self.program_id, &authority, admin_index);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::AdminBanUser {
                 authority,
                 admin_profile: admin_pda,
@@ -168,7 +887,7 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             data: instruction::AdminBanUser {}.data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     /// Prepares an `admin_unban_user` transaction.
@@ -176,17 +895,18 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
     /// # Arguments
     ///
     /// * `authority` - The public key of the admin's wallet.
+    /// * `admin_index` - The index of the authority's `AdminProfile` to act on.
     /// * `target_user_profile_pda` - The PDA of the `UserProfile` to be unbanned.
     pub fn prepare_admin_unban_user(
         &self,
         authority: Pubkey,
+        admin_index: u16,
         target_user_profile_pda: Pubkey,
     ) -> Vec<u8> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_solana_program::ID);
+        let (admin_pda, _) = find_admin_profile_pda_for(&self.program_id, &authority, admin_index);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::AdminUnbanUser {
                 authority,
                 admin_profile: admin_pda,
@@ -196,7 +916,7 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             data: instruction::AdminUnbanUser {}.data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     /// Prepares an `admin_set_config` transaction.
@@ -204,23 +924,50 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
     /// # Arguments
     ///
     /// * `authority` - The public key of the admin's wallet.
+    /// * `admin_index` - The index of the authority's `AdminProfile` to act on.
     /// * `new_oracle_authority` - An optional new `Pubkey` for the oracle.
     /// * `new_timestamp_validity` - An optional new duration in seconds for signature validity.
     /// * `new_communication_pubkey` - An optional new `Pubkey` for off-chain communication.
     /// * `new_unban_fee` - An optional new fee in lamports for unban requests.
+    /// * `new_max_payload_size` - An optional new payload limit in bytes for dispatch commands.
+    ///   Must not exceed the program's global `MAX_PAYLOAD_SIZE`.
+    /// * `new_unban_fee_multiplier_bps` - An optional new per-ban increase of the unban fee,
+    ///   in basis points of the `unban_fee`.
+    /// * `new_max_unban_fee_multiple` - An optional new cap of the escalated unban fee, as a
+    ///   multiple of the `unban_fee` (`0` for no cap).
+    /// * `new_require_distinct_oracle` - Optionally requires the oracle authority to differ
+    ///   from the admin's `authority`, or lifts that requirement.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConnectorError::InvalidTimestampValidity`] if `new_timestamp_validity` is
+    /// outside `MIN_TIMESTAMP_VALIDITY_SECONDS..=MAX_TIMESTAMP_VALIDITY_SECONDS`, which the
+    /// program would reject.
+    #[allow(clippy::too_many_arguments)]
     pub fn prepare_admin_set_config(
         &self,
         authority: Pubkey,
+        admin_index: u16,
         new_oracle_authority: Option<Pubkey>,
         new_timestamp_validity: Option<i64>,
         new_communication_pubkey: Option<Pubkey>,
         new_unban_fee: Option<u64>,
-    ) -> Vec<u8> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_solana_program::ID);
+        new_max_payload_size: Option<u16>,
+        new_unban_fee_multiplier_bps: Option<u16>,
+        new_max_unban_fee_multiple: Option<u16>,
+        new_require_distinct_oracle: Option<bool>,
+    ) -> Result<Vec<u8>, ConnectorError> {
+        if let Some(validity) = new_timestamp_validity {
+            if !(MIN_TIMESTAMP_VALIDITY_SECONDS..=MAX_TIMESTAMP_VALIDITY_SECONDS)
+                .contains(&validity)
+            {
+                return Err(ConnectorError::InvalidTimestampValidity(validity));
+            }
+        }
+        let (admin_pda, _) = find_admin_profile_pda_for(&self.program_id, &authority, admin_index);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::AdminSetConfig {
                 authority,
                 admin_profile: admin_pda,
@@ -231,11 +978,15 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
                 new_timestamp_validity,
                 new_communication_pubkey,
                 new_unban_fee,
+                new_max_payload_size,
+                new_unban_fee_multiplier_bps,
+                new_max_unban_fee_multiple,
+                new_require_distinct_oracle,
             }
             .data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        Ok(self.create_message_with_instructions(&authority, vec![ix]))
     }
 
     /// Prepares an `admin_withdraw` transaction.
@@ -243,19 +994,20 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
     /// # Arguments
     ///
     /// * `authority` - The public key of the admin's wallet.
+    /// * `admin_index` - The index of the authority's `AdminProfile` to act on.
     /// * `amount` - The amount of lamports to withdraw from the `AdminProfile` balance.
     /// * `destination` - The public key of the account to receive the funds.
     pub fn prepare_admin_withdraw(
         &self,
         authority: Pubkey,
+        admin_index: u16,
         amount: u64,
         destination: Pubkey,
     ) -> Vec<u8> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_solana_program::ID);
+        let (admin_pda, _) = find_admin_profile_pda_for(&self.program_id, &authority, admin_index);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::AdminWithdraw {
                 authority,
                 admin_profile: admin_pda,
@@ -265,7 +1017,37 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             data: instruction::AdminWithdraw { amount }.data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        self.create_message_with_instructions(&authority, vec![ix])
+    }
+
+    /// Prepares an `admin_withdraw_all` transaction, which withdraws the entire internal
+    /// balance of the `AdminProfile`.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The public key of the admin's wallet.
+    /// * `admin_index` - The index of the authority's `AdminProfile` to act on.
+    /// * `destination` - The public key of the account to receive the funds.
+    pub fn prepare_admin_withdraw_all(
+        &self,
+        authority: Pubkey,
+        admin_index: u16,
+        destination: Pubkey,
+    ) -> Vec<u8> {
+        let (admin_pda, _) = find_admin_profile_pda_for(&self.program_id, &authority, admin_index);
+
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: accounts::AdminWithdrawAll {
+                authority,
+                admin_profile: admin_pda,
+                destination,
+            }
+            .to_account_metas(None),
+            data: instruction::AdminWithdrawAll {}.data(),
+        };
+
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     /// Prepares an `admin_close_profile` transaction.
@@ -273,12 +1055,12 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
     /// # Arguments
     ///
     /// * `authority` - The public key of the admin's wallet.
-    pub fn prepare_admin_close_profile(&self, authority: Pubkey) -> Vec<u8> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_solana_program::ID);
+    /// * `admin_index` - The index of the authority's `AdminProfile` to act on.
+    pub fn prepare_admin_close_profile(&self, authority: Pubkey, admin_index: u16) -> Vec<u8> {
+        let (admin_pda, _) = find_admin_profile_pda_for(&self.program_id, &authority, admin_index);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::AdminCloseProfile {
                 authority,
                 admin_profile: admin_pda,
@@ -287,7 +1069,7 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             data: instruction::AdminCloseProfile {}.data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     /// Prepares an `admin_dispatch_command` transaction.
@@ -295,21 +1077,30 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
     /// # Arguments
     ///
     /// * `authority` - The public key of the admin's wallet.
+    /// * `admin_index` - The index of the authority's `AdminProfile` to act on.
     /// * `target_user_profile_pda` - The PDA of the target `UserProfile`.
     /// * `command_id` - A `u64` identifier for the command.
     /// * `payload` - An opaque byte array for application-specific data.
+    /// * `mode` - Whether the admin expects the user to respond.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConnectorError::InvalidPayload`] if the builder's payload validator rejects
+    /// `payload`.
     pub fn prepare_admin_dispatch_command(
         &self,
         authority: Pubkey,
+        admin_index: u16,
         target_user_profile_pda: Pubkey,
         command_id: u64,
         payload: Vec<u8>,
-    ) -> Vec<u8> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_solana_program::ID);
+        mode: CommandMode,
+    ) -> Result<Vec<u8>, ConnectorError> {
+        self.validate_payload(&payload)?;
+        let (admin_pda, _) = find_admin_profile_pda_for(&self.program_id, &authority, admin_index);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::AdminDispatchCommand {
                 admin_authority: authority,
                 admin_profile: admin_pda,
@@ -319,11 +1110,195 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             data: instruction::AdminDispatchCommand {
                 command_id,
                 payload,
+                mode,
             }
             .data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        Ok(self.create_message_with_instructions(&authority, vec![ix]))
+    }
+
+    /// Prepares the transactions that dispatch the same command to many users.
+    ///
+    /// One `admin_dispatch_command` instruction is built per target and the instructions
+    /// are packed into as few transactions as possible. A transaction is closed once it
+    /// would exceed `options.max_accounts_per_tx` accounts or the maximum transaction size.
+    /// With `options.pack_by_compute` set, it is also closed once the estimated compute
+    /// units of its instructions (see [`estimate_admin_dispatch_compute_units`]) would
+    /// exceed the budget, and it starts with a matching `set_compute_unit_limit`. This
+    /// keeps large payloads from producing transactions that run out of compute.
+    ///
+    /// The commands are sent as [`CommandMode::OneWay`] notifications.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The public key of the admin's wallet.
+    /// * `admin_index` - The index of the authority's `AdminProfile` to act on.
+    /// * `target_user_profile_pdas` - The PDAs of the target `UserProfile`s.
+    /// * `command_id` - A `u64` identifier for the command.
+    /// * `payload` - An opaque byte array for application-specific data.
+    /// * `options` - How to pack the instructions into transactions.
+    ///
+    /// # Returns
+    ///
+    /// One serialized, unsigned message per transaction, in the order of the targets.
+    pub fn prepare_admin_broadcast(
+        &self,
+        authority: Pubkey,
+        admin_index: u16,
+        target_user_profile_pdas: &[Pubkey],
+        command_id: u64,
+        payload: Vec<u8>,
+        options: BroadcastOptions,
+    ) -> Vec<Vec<u8>> {
+        let payer = self.payer(authority);
+        let batches = self.pack_broadcast(
+            authority,
+            admin_index,
+            target_user_profile_pdas,
+            command_id,
+            payload,
+            |batch, units| Self::broadcast_batch_fits(&payer, batch, units, &options),
+        );
+
+        batches
+            .iter()
+            .map(|(batch, units)| Self::broadcast_message(&payer, batch, *units, &options))
+            .map(|msg| bincode::serde::encode_to_vec(&msg, bincode::config::standard()).unwrap())
+            .collect()
+    }
+
+    /// Prepares the transactions that notify every linked user that the admin's service is
+    /// shutting down.
+    ///
+    /// This is a [`prepare_admin_broadcast`](Self::prepare_admin_broadcast) of
+    /// [`ADMIN_SHUTDOWN_COMMAND_ID`], so the users' clients see an `AdminCommandDispatched`
+    /// with that id before the admin closes its profile. A PDA listed more than once is
+    /// notified only once, at its first position.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The public key of the admin's wallet.
+    /// * `admin_index` - The index of the authority's `AdminProfile` to act on.
+    /// * `user_profile_pdas` - The PDAs of the linked `UserProfile`s.
+    /// * `payload` - Application-specific details, e.g. the closing date.
+    /// * `options` - How to pack the instructions into transactions.
+    ///
+    /// # Returns
+    ///
+    /// One serialized, unsigned message per transaction, in the order of the users. The
+    /// messages are independent and can be submitted in any order.
+    pub fn prepare_admin_shutdown(
+        &self,
+        authority: Pubkey,
+        admin_index: u16,
+        user_profile_pdas: &[Pubkey],
+        payload: Vec<u8>,
+        options: BroadcastOptions,
+    ) -> Vec<Vec<u8>> {
+        let mut seen = HashSet::new();
+        let targets: Vec<Pubkey> = user_profile_pdas
+            .iter()
+            .copied()
+            .filter(|pda| seen.insert(*pda))
+            .collect();
+        self.prepare_admin_broadcast(
+            authority,
+            admin_index,
+            &targets,
+            ADMIN_SHUTDOWN_COMMAND_ID,
+            payload,
+            options,
+        )
+    }
+
+    /// Prepares the v0 transactions that dispatch the same command to many users, loading
+    /// the accounts found in `lookup_table` through it.
+    ///
+    /// This packs the instructions like [`prepare_admin_broadcast`](Self::prepare_admin_broadcast).
+    /// Every target PDA held by the table takes one byte in the message instead of 32, so a
+    /// table holding the targets fits many more dispatches into each transaction. Accounts
+    /// loaded from the table still count against `options.max_accounts_per_tx`. The table is
+    /// built with [`prepare_create_lookup_table`](Self::prepare_create_lookup_table) and
+    /// [`prepare_extend_lookup_table`](Self::prepare_extend_lookup_table).
+    ///
+    /// # Returns
+    ///
+    /// One serialized, unsigned [`VersionedMessage`] per transaction, in the order of the
+    /// targets. The blockhash of a v0 message is not at [`blockhash_placeholder_offset`], so
+    /// clients decode the message to set it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare_admin_broadcast_with_lookup_table(
+        &self,
+        authority: Pubkey,
+        admin_index: u16,
+        target_user_profile_pdas: &[Pubkey],
+        command_id: u64,
+        payload: Vec<u8>,
+        options: BroadcastOptions,
+        lookup_table: &AddressLookupTableAccount,
+    ) -> Vec<Vec<u8>> {
+        let payer = self.payer(authority);
+        let batches = self.pack_broadcast(
+            authority,
+            admin_index,
+            target_user_profile_pdas,
+            command_id,
+            payload,
+            |batch, units| {
+                Self::broadcast_batch_fits_v0(&payer, batch, units, &options, lookup_table)
+            },
+        );
+
+        batches
+            .iter()
+            .map(|(batch, units)| {
+                Self::broadcast_message_v0(&payer, batch, *units, &options, lookup_table)
+                    .expect("a single dispatch always compiles")
+            })
+            .map(|msg| {
+                let msg = VersionedMessage::V0(msg);
+                bincode::serde::encode_to_vec(&msg, bincode::config::standard()).unwrap()
+            })
+            .collect()
+    }
+
+    /// Prepares a transaction that creates an address lookup table owned by `authority`,
+    /// e.g. to hold the user PDAs of a [broadcast](Self::prepare_admin_broadcast_with_lookup_table).
+    ///
+    /// The table address is derived from `authority` and `recent_slot`, which must be a
+    /// slot the cluster still keeps in its `SlotHashes`, such as the latest finalized slot.
+    /// The fee payer also pays the rent of the table.
+    ///
+    /// # Returns
+    ///
+    /// The serialized, unsigned message and the address of the new table.
+    pub fn prepare_create_lookup_table(
+        &self,
+        authority: Pubkey,
+        recent_slot: u64,
+    ) -> (Vec<u8>, Pubkey) {
+        let (ix, table) = create_lookup_table(authority, self.payer(authority), recent_slot);
+        (
+            self.create_message_with_instructions(&authority, vec![ix]),
+            table,
+        )
+    }
+
+    /// Prepares a transaction that appends `new_addresses` to the lookup table `table` of
+    /// `authority`. The fee payer pays the additional rent.
+    ///
+    /// A table holds up to 256 addresses. Since the addresses are part of the transaction,
+    /// about 20 of them fit into one extension, so longer lists are split over several.
+    /// Addresses appended to a table can be used from the next slot on.
+    pub fn prepare_extend_lookup_table(
+        &self,
+        authority: Pubkey,
+        table: Pubkey,
+        new_addresses: Vec<Pubkey>,
+    ) -> Vec<u8> {
+        let ix = extend_lookup_table(table, authority, Some(self.payer(authority)), new_addresses);
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     // --- User Transaction Preparations ---
@@ -341,13 +1316,11 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
         target_admin_pda: Pubkey,
         communication_pubkey: Pubkey,
     ) -> Vec<u8> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), target_admin_pda.as_ref()],
-            &w3b2_solana_program::ID,
-        );
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &target_admin_pda);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::UserCreateProfile {
                 authority,
                 admin_profile: target_admin_pda,
@@ -362,7 +1335,7 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             .data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     /// Prepares a `user_update_comm_key` transaction.
@@ -378,13 +1351,11 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
         admin_profile_pda: Pubkey,
         new_key: Pubkey,
     ) -> Vec<u8> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), admin_profile_pda.as_ref()],
-            &w3b2_solana_program::ID,
-        );
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &admin_profile_pda);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::UserUpdateCommKey {
                 authority,
                 user_profile: user_pda,
@@ -394,7 +1365,116 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             data: instruction::UserUpdateCommKey { new_key }.data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        self.create_message_with_instructions(&authority, vec![ix])
+    }
+
+    /// Prepares a `user_set_low_balance_threshold` transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The user's wallet `Pubkey`.
+    /// * `admin_profile_pda` - The `Pubkey` of the `AdminProfile` PDA this user profile is linked to.
+    /// * `threshold` - The deposit balance in lamports below which paid commands emit a
+    ///   `UserLowBalance` event. `0` disables the notification.
+    pub fn prepare_user_set_low_balance_threshold(
+        &self,
+        authority: Pubkey,
+        admin_profile_pda: Pubkey,
+        threshold: u64,
+    ) -> Vec<u8> {
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &admin_profile_pda);
+
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: accounts::UserSetLowBalanceThreshold {
+                authority,
+                user_profile: user_pda,
+                admin_profile: admin_profile_pda,
+            }
+            .to_account_metas(None),
+            data: instruction::UserSetLowBalanceThreshold { threshold }.data(),
+        };
+
+        self.create_message_with_instructions(&authority, vec![ix])
+    }
+
+    /// Prepares a `user_set_spending_cap` transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The user's wallet `Pubkey`.
+    /// * `admin_profile_pda` - The `Pubkey` of the `AdminProfile` PDA this user profile is linked to.
+    /// * `max_price_per_command` - The highest oracle price in lamports the user accepts for a
+    ///   single `user_dispatch_command`. `0` removes the cap.
+    pub fn prepare_user_set_spending_cap(
+        &self,
+        authority: Pubkey,
+        admin_profile_pda: Pubkey,
+        max_price_per_command: u64,
+    ) -> Vec<u8> {
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &admin_profile_pda);
+
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: accounts::UserSetSpendingCap {
+                authority,
+                user_profile: user_pda,
+                admin_profile: admin_profile_pda,
+            }
+            .to_account_metas(None),
+            data: instruction::UserSetSpendingCap {
+                max_price_per_command,
+            }
+            .data(),
+        };
+
+        self.create_message_with_instructions(&authority, vec![ix])
+    }
+
+    /// Prepares a `user_grant_allowance` transaction, which lets the service charge `commands`
+    /// with `prepare_dispatch_with_allowance` until `total_cap` lamports were charged or
+    /// `expires_at` is reached. It replaces any allowance granted before.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The user's wallet `Pubkey`.
+    /// * `admin_profile_pda` - The `Pubkey` of the `AdminProfile` PDA this user profile is linked to.
+    /// * `commands` - The commands the service may charge, at most `MAX_ALLOWANCE_COMMANDS`.
+    /// * `total_cap` - The total in lamports the service may charge.
+    /// * `expires_at` - The Unix timestamp from which the allowance can no longer be used.
+    pub fn prepare_user_grant_allowance(
+        &self,
+        authority: Pubkey,
+        admin_profile_pda: Pubkey,
+        commands: Vec<u16>,
+        total_cap: u64,
+        expires_at: i64,
+    ) -> Vec<u8> {
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &admin_profile_pda);
+        let (allowance_pda, _) = find_allowance_pda_for(&self.program_id, &user_pda);
+
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: accounts::UserGrantAllowance {
+                authority,
+                admin_profile: admin_profile_pda,
+                user_profile: user_pda,
+                allowance: allowance_pda,
+                system_program: solana_sdk::system_program::id(),
+            }
+            .to_account_metas(None),
+            data: instruction::UserGrantAllowance {
+                commands,
+                total_cap,
+                expires_at,
+            }
+            .data(),
+        };
+
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     /// Prepares a `user_deposit` transaction.
@@ -410,13 +1490,11 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
         admin_profile_pda: Pubkey,
         amount: u64,
     ) -> Vec<u8> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), admin_profile_pda.as_ref()],
-            &w3b2_solana_program::ID,
-        );
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &admin_profile_pda);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::UserDeposit {
                 authority,
                 user_profile: user_pda,
@@ -427,7 +1505,7 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             data: instruction::UserDeposit { amount }.data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     /// Prepares a `user_withdraw` transaction.
@@ -445,13 +1523,11 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
         amount: u64,
         destination: Pubkey,
     ) -> Vec<u8> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), admin_profile_pda.as_ref()],
-            &w3b2_solana_program::ID,
-        );
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &admin_profile_pda);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::UserWithdraw {
                 authority,
                 user_profile: user_pda,
@@ -462,7 +1538,7 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             data: instruction::UserWithdraw { amount }.data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     /// Prepares a `user_close_profile` transaction.
@@ -476,13 +1552,11 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
         authority: Pubkey,
         admin_profile_pda: Pubkey,
     ) -> Vec<u8> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), admin_profile_pda.as_ref()],
-            &w3b2_solana_program::ID,
-        );
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &admin_profile_pda);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::UserCloseProfile {
                 authority,
                 user_profile: user_pda,
@@ -492,7 +1566,86 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             data: instruction::UserCloseProfile {}.data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        self.create_message_with_instructions(&authority, vec![ix])
+    }
+
+    // --- Shared Wallet Transaction Preparations ---
+
+    /// Prepares a `user_create_wallet` transaction, which creates the user's shared
+    /// `UserWallet`. Commands paid with
+    /// [`prepare_user_dispatch_command_from_wallet`](Self::prepare_user_dispatch_command_from_wallet)
+    /// draw from it instead of the user's profile for the service.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The user's wallet `Pubkey`, which pays for the new account.
+    pub fn prepare_user_create_wallet(&self, authority: Pubkey) -> Vec<u8> {
+        let (wallet_pda, _) = find_user_wallet_pda_for(&self.program_id, &authority);
+
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: accounts::UserCreateWallet {
+                authority,
+                wallet: wallet_pda,
+                system_program: solana_sdk::system_program::id(),
+            }
+            .to_account_metas(None),
+            data: instruction::UserCreateWallet {}.data(),
+        };
+
+        self.create_message_with_instructions(&authority, vec![ix])
+    }
+
+    /// Prepares a `wallet_deposit` transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The user's wallet `Pubkey`.
+    /// * `amount` - The amount of lamports to deposit into the shared wallet.
+    pub fn prepare_wallet_deposit(&self, authority: Pubkey, amount: u64) -> Vec<u8> {
+        let (wallet_pda, _) = find_user_wallet_pda_for(&self.program_id, &authority);
+
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: accounts::WalletDeposit {
+                authority,
+                wallet: wallet_pda,
+                system_program: solana_sdk::system_program::id(),
+            }
+            .to_account_metas(None),
+            data: instruction::WalletDeposit { amount }.data(),
+        };
+
+        self.create_message_with_instructions(&authority, vec![ix])
+    }
+
+    /// Prepares a `wallet_withdraw` transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The user's wallet `Pubkey`.
+    /// * `amount` - The amount of lamports to withdraw from the shared wallet.
+    /// * `destination` - The `Pubkey` of the wallet to receive the funds.
+    pub fn prepare_wallet_withdraw(
+        &self,
+        authority: Pubkey,
+        amount: u64,
+        destination: Pubkey,
+    ) -> Vec<u8> {
+        let (wallet_pda, _) = find_user_wallet_pda_for(&self.program_id, &authority);
+
+        let ix = Instruction {
+            program_id: self.program_id,
+            accounts: accounts::WalletWithdraw {
+                authority,
+                wallet: wallet_pda,
+                destination,
+            }
+            .to_account_metas(None),
+            data: instruction::WalletWithdraw { amount }.data(),
+        };
+
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     // --- Operational Transaction Preparations ---
@@ -508,35 +1661,116 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
     /// * `authority` - The user's wallet `Pubkey` that will sign the transaction.
     /// * `target_admin_pda` - The `Pubkey` of the target `AdminProfile` PDA.
     /// * `args` - A [`UserDispatchCommandArgs`] struct containing all oracle-signed parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConnectorError::OracleKeyOffCurve`] if `args.oracle_pubkey` is not a valid
+    /// Ed25519 public key, which the signature verification would reject on-chain,
+    /// [`ConnectorError::PriceSignedForAnotherUser`] if `args.user_profile_pda` is not the
+    /// `UserProfile` PDA of `authority` and `target_admin_pda`, and
+    /// [`ConnectorError::InvalidPayload`] if the builder's payload validator rejects
+    /// `args.payload`.
     pub fn prepare_user_dispatch_command(
         &self,
         authority: Pubkey,
         target_admin_pda: Pubkey,
         args: UserDispatchCommandArgs,
-    ) -> Vec<u8> {
-        // 1. Reconstruct the message that the oracle signed.
-        let message = [
-            args.command_id.to_le_bytes().as_ref(),
-            args.price.to_le_bytes().as_ref(),
-            args.timestamp.to_le_bytes().as_ref(),
-        ]
-        .concat();
-
-        // 2. Create the Ed25519 signature verification instruction.
-        let ed25519_ix = new_ed25519_instruction_with_signature(
-            &message,
-            &args.oracle_signature,
-            &args.oracle_pubkey.to_bytes(),
-        );
+    ) -> Result<Vec<u8>, ConnectorError> {
+        self.prepare_user_dispatch_command_with_compute_budget(
+            authority,
+            target_admin_pda,
+            args,
+            ComputeBudget::default(),
+        )
+    }
 
-        // 3. Create the main `user_dispatch_command` instruction.
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), target_admin_pda.as_ref()],
-            &w3b2_solana_program::ID,
-        );
+    /// Prepares a `user_dispatch_command` transaction that starts with the compute budget
+    /// instructions of `budget`, followed by the instructions of
+    /// [`prepare_user_dispatch_command`](Self::prepare_user_dispatch_command).
+    ///
+    /// The program finds the oracle's `Ed25519` verification anywhere in the transaction,
+    /// so the prepended instructions do not affect it.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`prepare_user_dispatch_command`](Self::prepare_user_dispatch_command).
+    pub fn prepare_user_dispatch_command_with_compute_budget(
+        &self,
+        authority: Pubkey,
+        target_admin_pda: Pubkey,
+        args: UserDispatchCommandArgs,
+        budget: ComputeBudget,
+    ) -> Result<Vec<u8>, ConnectorError> {
+        let mut instructions = budget.instructions();
+        instructions.extend(self.user_dispatch_instructions(authority, target_admin_pda, args)?);
+        Ok(self.create_message_with_instructions(&authority, instructions))
+    }
 
+    /// Prepares a single transaction that dispatches a command with
+    /// [`prepare_user_dispatch_command`](Self::prepare_user_dispatch_command) and then
+    /// records a `log_action` for the same user and admin profiles, saving a second
+    /// transaction for flows that log every dispatch.
+    ///
+    /// The instructions are ordered as the `Ed25519` verification, the
+    /// `user_dispatch_command` and the `log_action`. Both events are emitted by the same
+    /// transaction, or neither if it fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The user's wallet `Pubkey` that will sign the transaction.
+    /// * `target_admin_pda` - The `Pubkey` of the target `AdminProfile` PDA.
+    /// * `args` - A [`UserDispatchCommandArgs`] struct containing all oracle-signed parameters.
+    /// * `session_id`, `action_code`, `category`, `metadata` - The `log_action` record, as
+    ///   for [`prepare_log_action`](Self::prepare_log_action).
+    ///
+    /// # Errors
+    ///
+    /// The same as [`prepare_user_dispatch_command`](Self::prepare_user_dispatch_command).
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare_user_dispatch_and_log(
+        &self,
+        authority: Pubkey,
+        target_admin_pda: Pubkey,
+        args: UserDispatchCommandArgs,
+        session_id: u64,
+        action_code: u16,
+        category: u8,
+        metadata: Option<Vec<u8>>,
+    ) -> Result<Vec<u8>, ConnectorError> {
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &target_admin_pda);
+        let mut instructions =
+            self.user_dispatch_instructions(authority, target_admin_pda, args)?;
+        instructions.push(log_action_instruction(
+            self.program_id,
+            authority,
+            user_pda,
+            target_admin_pda,
+            session_id,
+            action_code,
+            category,
+            metadata,
+        ));
+        Ok(self.create_message_with_instructions(&authority, instructions))
+    }
+
+    /// Builds the `Ed25519` verification and the `user_dispatch_command` instruction, in
+    /// that order.
+    fn user_dispatch_instructions(
+        &self,
+        authority: Pubkey,
+        target_admin_pda: Pubkey,
+        args: UserDispatchCommandArgs,
+    ) -> Result<Vec<Instruction>, ConnectorError> {
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &target_admin_pda);
+
+        // 1. Create the Ed25519 verification of the oracle-signed price.
+        let ed25519_ix = self.price_verification_instruction(&args, user_pda)?;
+
+        // 2. Create the main `user_dispatch_command` instruction.
         let dispatch_ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::UserDispatchCommand {
                 authority,
                 user_profile: user_pda,
@@ -549,15 +1783,141 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
                 price: args.price,
                 timestamp: args.timestamp,
                 payload: args.payload,
+                mode: args.mode,
             }
             .data(),
         };
 
-        // 4. Create a transaction containing both instructions in the correct order.
-        TransactionBuilder::<C>::create_message_with_instructions(
-            &authority,
-            vec![ed25519_ix, dispatch_ix],
-        )
+        Ok(vec![ed25519_ix, dispatch_ix])
+    }
+
+    /// Prepares a `dispatch_with_allowance` transaction, in which the service charges a
+    /// command against the allowance the user granted with `user_grant_allowance`.
+    ///
+    /// Like [`prepare_user_dispatch_command`](Self::prepare_user_dispatch_command), the
+    /// transaction verifies the oracle-signed price, but it is signed and paid for by
+    /// `charger` instead of the user.
+    ///
+    /// # Arguments
+    ///
+    /// * `charger` - The admin's `authority` or `oracle_authority`, which signs the transaction.
+    /// * `user_authority` - The wallet `Pubkey` of the user who granted the allowance.
+    /// * `admin_profile_pda` - The `Pubkey` of the `AdminProfile` PDA of the service.
+    /// * `args` - A [`UserDispatchCommandArgs`] struct containing all oracle-signed parameters.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`prepare_user_dispatch_command`](Self::prepare_user_dispatch_command).
+    pub fn prepare_dispatch_with_allowance(
+        &self,
+        charger: Pubkey,
+        user_authority: Pubkey,
+        admin_profile_pda: Pubkey,
+        args: UserDispatchCommandArgs,
+    ) -> Result<Vec<u8>, ConnectorError> {
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &user_authority, &admin_profile_pda);
+        let ed25519_ix = self.price_verification_instruction(&args, user_pda)?;
+
+        let (allowance_pda, _) = find_allowance_pda_for(&self.program_id, &user_pda);
+        let dispatch_ix = Instruction {
+            program_id: self.program_id,
+            accounts: accounts::DispatchWithAllowance {
+                charger,
+                user_profile: user_pda,
+                admin_profile: admin_profile_pda,
+                allowance: allowance_pda,
+                instructions: sysvar::instructions::id(),
+            }
+            .to_account_metas(None),
+            data: instruction::DispatchWithAllowance {
+                command_id: args.command_id,
+                price: args.price,
+                timestamp: args.timestamp,
+                payload: args.payload,
+                mode: args.mode,
+            }
+            .data(),
+        };
+
+        Ok(self.create_message_with_instructions(&charger, vec![ed25519_ix, dispatch_ix]))
+    }
+
+    /// Prepares a `user_dispatch_command_from_wallet` transaction, which dispatches a command
+    /// like [`prepare_user_dispatch_command`](Self::prepare_user_dispatch_command) but pays
+    /// for it from the user's shared `UserWallet`.
+    ///
+    /// The user still needs a `UserProfile` for `target_admin_pda`: its ban status and
+    /// spending cap apply, but its deposit is left untouched.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`prepare_user_dispatch_command`](Self::prepare_user_dispatch_command).
+    pub fn prepare_user_dispatch_command_from_wallet(
+        &self,
+        authority: Pubkey,
+        target_admin_pda: Pubkey,
+        args: UserDispatchCommandArgs,
+    ) -> Result<Vec<u8>, ConnectorError> {
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &target_admin_pda);
+        let ed25519_ix = self.price_verification_instruction(&args, user_pda)?;
+
+        let (wallet_pda, _) = find_user_wallet_pda_for(&self.program_id, &authority);
+        let dispatch_ix = Instruction {
+            program_id: self.program_id,
+            accounts: accounts::UserDispatchCommandFromWallet {
+                authority,
+                user_profile: user_pda,
+                admin_profile: target_admin_pda,
+                wallet: wallet_pda,
+                instructions: sysvar::instructions::id(),
+            }
+            .to_account_metas(None),
+            data: instruction::UserDispatchCommandFromWallet {
+                command_id: args.command_id,
+                price: args.price,
+                timestamp: args.timestamp,
+                payload: args.payload,
+                mode: args.mode,
+            }
+            .data(),
+        };
+
+        Ok(self.create_message_with_instructions(&authority, vec![ed25519_ix, dispatch_ix]))
+    }
+
+    /// Builds the `Ed25519` instruction verifying the oracle's signature over the price
+    /// message of `args`, after checking the oracle key, the payload and that the price was
+    /// signed for `user_pda`, the profile dispatching the command.
+    fn price_verification_instruction(
+        &self,
+        args: &UserDispatchCommandArgs,
+        user_pda: Pubkey,
+    ) -> Result<Instruction, ConnectorError> {
+        if !args.oracle_pubkey.is_on_curve() {
+            return Err(ConnectorError::OracleKeyOffCurve(args.oracle_pubkey));
+        }
+        if args.user_profile_pda != user_pda {
+            return Err(ConnectorError::PriceSignedForAnotherUser {
+                signed_for: args.user_profile_pda,
+                dispatched_for: user_pda,
+            });
+        }
+        self.validate_payload(&args.payload)?;
+
+        // Reconstruct the message that the oracle signed.
+        let message = build_price_message(
+            args.command_id,
+            args.price,
+            args.timestamp,
+            args.user_profile_pda,
+        );
+        Ok(new_ed25519_instruction_with_signature(
+            &message,
+            &args.oracle_signature,
+            &args.oracle_pubkey.to_bytes(),
+        ))
     }
 
     /// Prepares a `user_request_unban` transaction.
@@ -571,13 +1931,11 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
         authority: Pubkey,
         admin_profile_pda: Pubkey,
     ) -> Vec<u8> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), admin_profile_pda.as_ref()],
-            &w3b2_solana_program::ID,
-        );
+        let (user_pda, _) =
+            find_user_profile_pda_for(&self.program_id, &authority, &admin_profile_pda);
 
         let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
+            program_id: self.program_id,
             accounts: accounts::UserRequestUnban {
                 authority,
                 user_profile: user_pda,
@@ -587,7 +1945,7 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
             data: instruction::UserRequestUnban {}.data(),
         };
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+        self.create_message_with_instructions(&authority, vec![ix])
     }
 
     /// Prepares a `log_action` transaction.
@@ -602,7 +1960,10 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
     /// * `user_profile_pda` - The `Pubkey` of the `UserProfile` PDA.
     /// * `admin_profile_pda` - The `Pubkey` of the `AdminProfile` PDA.
     /// * `session_id` - A `u64` identifier to correlate actions.
-    /// * `action_code` - A `u16` code for the specific action.
+    /// * `action_code` - A `u16` code for the specific action, interpreted within `category`.
+    /// * `category` - The namespace of `action_code`, see `w3b2_solana_program::action_categories`.
+    /// * `metadata` - An optional detail blob of at most `MAX_METADATA_SIZE` (128) bytes.
+    #[allow(clippy::too_many_arguments)]
     pub fn prepare_log_action(
         &self,
         authority: Pubkey,
@@ -610,22 +1971,98 @@ impl<C: AsyncRpcClient + ?Sized> TransactionBuilder<C> {
         admin_profile_pda: Pubkey,
         session_id: u64,
         action_code: u16,
+        category: u8,
+        metadata: Option<Vec<u8>>,
     ) -> Vec<u8> {
-        let ix = Instruction {
-            program_id: w3b2_solana_program::ID,
-            accounts: accounts::LogAction {
+        let ix = log_action_instruction(
+            self.program_id,
+            authority,
+            user_profile_pda,
+            admin_profile_pda,
+            session_id,
+            action_code,
+            category,
+            metadata,
+        );
+
+        self.create_message_with_instructions(&authority, vec![ix])
+    }
+
+    /// Prepares the `log_action` transactions that attach `payload` to a session in chunks,
+    /// for payloads larger than the `metadata` of a single record. See [`crate::chunking`]
+    /// for the chunk format.
+    ///
+    /// Consecutive chunks are packed into as few messages as the maximum transaction size
+    /// allows. The messages may be submitted in any order;
+    /// [`reassemble_payload`](crate::chunking::reassemble_payload) restores the payload from
+    /// the resulting `OffChainActionLogged` events.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConnectorError::PayloadTooLarge`] if the payload exceeds
+    /// [`MAX_CHUNKED_PAYLOAD_LEN`](crate::chunking::MAX_CHUNKED_PAYLOAD_LEN).
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare_log_action_chunked(
+        &self,
+        authority: Pubkey,
+        user_profile_pda: Pubkey,
+        admin_profile_pda: Pubkey,
+        session_id: u64,
+        action_code: u16,
+        category: u8,
+        payload: &[u8],
+    ) -> Result<Vec<Vec<u8>>, ConnectorError> {
+        let payer = self.payer(authority);
+        let mut messages = Vec::new();
+        let mut batch: Vec<Instruction> = Vec::new();
+        for metadata in chunk_payload(payload)? {
+            batch.push(log_action_instruction(
+                self.program_id,
                 authority,
-                user_profile: user_profile_pda,
-                admin_profile: admin_profile_pda,
-            }
-            .to_account_metas(None),
-            data: instruction::LogAction {
+                user_profile_pda,
+                admin_profile_pda,
                 session_id,
                 action_code,
+                category,
+                Some(metadata),
+            ));
+            if batch.len() > 1 && !fits_in_transaction(&Message::new(&batch, Some(&payer))) {
+                let ix = batch.pop().expect("batch is not empty");
+                messages.push(self.create_message_with_instructions(&authority, batch));
+                batch = vec![ix];
             }
-            .data(),
-        };
+        }
+        messages.push(self.create_message_with_instructions(&authority, batch));
+        Ok(messages)
+    }
+}
 
-        TransactionBuilder::<C>::create_message_with_instructions(&authority, vec![ix])
+/// Builds a `log_action` instruction.
+#[allow(clippy::too_many_arguments)]
+fn log_action_instruction(
+    program_id: Pubkey,
+    authority: Pubkey,
+    user_profile_pda: Pubkey,
+    admin_profile_pda: Pubkey,
+    session_id: u64,
+    action_code: u16,
+    category: u8,
+    metadata: Option<Vec<u8>>,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: accounts::LogAction {
+            authority,
+            user_profile: user_profile_pda,
+            admin_profile: admin_profile_pda,
+        }
+        .to_account_metas(None),
+        data: instruction::LogAction {
+            session_id,
+            action_code,
+            category,
+            metadata,
+        }
+        .data(),
     }
 }
@@ -2,6 +2,7 @@
 use serde::{Deserialize, Serialize};
 
 use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::pubkey::Pubkey;
 
 /// The top-level configuration for the `w3b2-solana-connector` library.
 ///
@@ -29,6 +30,64 @@ pub struct Solana {
     pub ws_url: String,
     #[cfg_attr(feature = "serde", serde(with = "serde_commitment"))]
     pub commitment: CommitmentLevel,
+    /// The id of the deployed W3B2 program. Defaults to the id the connector is built
+    /// against; set it to follow another deployment, such as a fork or a localnet redeploy.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "default_program_id", with = "serde_pubkey")
+    )]
+    pub program_id: Pubkey,
+    /// The source of real-time events used by the live worker.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub live_source: LiveSource,
+    /// The endpoint of a Yellowstone (Geyser) gRPC server. Required when
+    /// `live_source` is `geyser`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub geyser_url: Option<String>,
+    /// An optional access token sent as the `x-token` header to the Geyser endpoint.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub geyser_x_token: Option<String>,
+    /// The maximum number of signatures requested per `getSignaturesForAddress` call
+    /// (the RPC node caps this at 1000).
+    #[cfg_attr(feature = "serde", serde(default = "default_signatures_per_batch"))]
+    pub signatures_per_batch: usize,
+    /// The maximum number of `getTransaction` requests kept in flight while catching up.
+    #[cfg_attr(feature = "serde", serde(default = "default_tx_fetch_concurrency"))]
+    pub tx_fetch_concurrency: usize,
+    /// With `live_source = "auto"`, the number of consecutive failed or dropped WebSocket
+    /// subscriptions after which the live worker falls back to polling.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "default_ws_failures_before_fallback")
+    )]
+    pub ws_failures_before_fallback: u32,
+    /// With `live_source = "auto"`, the interval in seconds at which the program's
+    /// signatures are polled while the WebSocket is unavailable.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "default_fallback_poll_interval_secs")
+    )]
+    pub fallback_poll_interval_secs: u64,
+    /// With `live_source = "auto"`, the interval in seconds at which the live worker tries
+    /// to switch from polling back to the WebSocket.
+    #[cfg_attr(feature = "serde", serde(default = "default_ws_upgrade_interval_secs"))]
+    pub ws_upgrade_interval_secs: u64,
+}
+
+/// Selects where the live worker receives real-time transaction updates from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum LiveSource {
+    /// The standard Solana WebSocket `logsSubscribe` stream at `ws_url`.
+    #[default]
+    Websocket,
+    /// A Yellowstone gRPC transaction stream at `geyser_url`.
+    /// Requires the connector to be built with the `geyser` feature.
+    Geyser,
+    /// The WebSocket stream at `ws_url`, downgraded to polling the program's signatures
+    /// while the WebSocket keeps failing, and upgraded back once it is reachable again.
+    Auto,
 }
 
 /// Defines behavior for the event synchronization workers (`LiveWorker` and `CatchupWorker`).
@@ -38,10 +97,35 @@ pub struct Solana {
 pub struct Synchronizer {
     /// The maximum number of slots to look back during catch-up. `None` means no limit.
     pub max_catchup_depth: Option<u64>,
+    /// The maximum number of a PDA's most recent transactions replayed to a new listener.
+    /// `None` replays the whole history.
+    pub catchup_max_transactions: Option<usize>,
     /// The interval in seconds at which the `CatchupWorker` polls for historical transactions.
     pub poll_interval_secs: u64,
-    /// The maximum number of signatures to fetch in a single RPC call during catch-up.
-    pub max_signature_fetch: usize,
+    /// Enables reorg detection: delivered, not yet finalized transactions are re-checked on
+    /// every poll and a `Retracted` event is emitted for any that a fork dropped. Costs one
+    /// extra RPC call per poll; unnecessary with `finalized` commitment.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub detect_reorgs: bool,
+    /// Emits a `DispatchRejected` event for every `user_dispatch_command` that failed the
+    /// oracle checks, so that admins can alert on a misconfigured oracle. Costs one extra
+    /// RPC call per rejected command seen by the WebSocket live source.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub report_dispatch_rejections: bool,
+    /// The number of slots the synchronizer may fall behind the cluster before it is
+    /// considered lagging. `None` disables the stall alert; the lag is still tracked.
+    #[cfg_attr(feature = "serde", serde(default = "default_max_lag_slots"))]
+    pub max_lag_slots: Option<u64>,
+    /// How long, in seconds, the lag must stay above `max_lag_slots` before the
+    /// synchronizer is reported as stalled.
+    #[cfg_attr(feature = "serde", serde(default = "default_lag_alert_after_secs"))]
+    pub lag_alert_after_secs: u64,
+    /// How long, in seconds, the synchronizer may go without completing a catch-up pass
+    /// before the watchdog reports it as unhealthy. This catches workers that are wedged,
+    /// e.g. on an RPC call that never returns, rather than merely lagging. `0` disables the
+    /// watchdog.
+    #[cfg_attr(feature = "serde", serde(default = "default_watchdog_timeout_secs"))]
+    pub watchdog_timeout_secs: u64,
 }
 
 /// Defines capacities for various MPSC channels within the connector.
@@ -54,7 +138,21 @@ pub struct ChannelConfig {
     /// The buffer capacity for the command channel to the Dispatcher.
     pub dispatcher_command_buffer: usize,
     /// The default buffer capacity for individual listener channels (e.g., UserListener).
+    /// What happens once a listener falls this many live events behind depends on its
+    /// [`OverflowPolicy`](crate::listener::OverflowPolicy).
     pub listener_event_buffer: usize,
+    /// The buffer capacity of each shared fan-out of
+    /// [`broadcast_subscribe`](crate::workers::EventManagerHandle::broadcast_subscribe).
+    /// A receiver that falls this many events behind lags, and recovers the events it
+    /// missed through a catch-up of its PDA.
+    #[cfg_attr(feature = "serde", serde(default = "default_broadcast_capacity"))]
+    pub broadcast_capacity: usize,
+    /// The maximum number of listeners the dispatcher serves at once, across all PDAs.
+    /// Registering another one fails with
+    /// [`ConnectorError::TooManyListeners`](crate::error::ConnectorError::TooManyListeners)
+    /// until one of them is dropped. Unbounded if `None`, the default.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_listeners: Option<usize>,
 }
 
 
@@ -64,16 +162,70 @@ impl Default for Solana {
             rpc_url: "http://127.0.0.1:8899".to_string(),
             ws_url: "ws://127.0.0.1:8900".to_string(),
             commitment: CommitmentLevel::Confirmed,
+            program_id: default_program_id(),
+            live_source: LiveSource::default(),
+            geyser_url: None,
+            geyser_x_token: None,
+            signatures_per_batch: default_signatures_per_batch(),
+            tx_fetch_concurrency: default_tx_fetch_concurrency(),
+            ws_failures_before_fallback: default_ws_failures_before_fallback(),
+            fallback_poll_interval_secs: default_fallback_poll_interval_secs(),
+            ws_upgrade_interval_secs: default_ws_upgrade_interval_secs(),
         }
     }
 }
 
+fn default_program_id() -> Pubkey {
+    w3b2_solana_program::ID
+}
+
+fn default_signatures_per_batch() -> usize {
+    1000
+}
+
+fn default_tx_fetch_concurrency() -> usize {
+    8
+}
+
+fn default_ws_failures_before_fallback() -> u32 {
+    3
+}
+
+fn default_fallback_poll_interval_secs() -> u64 {
+    2
+}
+
+fn default_ws_upgrade_interval_secs() -> u64 {
+    60
+}
+
+fn default_broadcast_capacity() -> usize {
+    256
+}
+
+fn default_max_lag_slots() -> Option<u64> {
+    Some(150)
+}
+
+fn default_lag_alert_after_secs() -> u64 {
+    60
+}
+
+fn default_watchdog_timeout_secs() -> u64 {
+    120
+}
+
 impl Default for Synchronizer {
     fn default() -> Self {
         Self {
             max_catchup_depth: None,
+            catchup_max_transactions: None,
             poll_interval_secs: 3,
-            max_signature_fetch: 1000,
+            detect_reorgs: false,
+            report_dispatch_rejections: false,
+            max_lag_slots: default_max_lag_slots(),
+            lag_alert_after_secs: default_lag_alert_after_secs(),
+            watchdog_timeout_secs: default_watchdog_timeout_secs(),
         }
     }
 }
@@ -84,6 +236,8 @@ impl Default for ChannelConfig {
             dispatcher_event_buffer: 256,
             dispatcher_command_buffer: 128,
             listener_event_buffer: 128,
+            broadcast_capacity: default_broadcast_capacity(),
+            max_listeners: None,
         }
     }
 }
@@ -120,3 +274,26 @@ mod serde_commitment {
         Ok(level)
     }
 }
+
+#[cfg(feature = "serde")]
+mod serde_pubkey {
+
+    use super::*;
+    use serde::{de::Error, Deserializer, Serializer};
+    use std::str::FromStr;
+
+    pub fn serialize<S>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&pubkey.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Pubkey, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        Pubkey::from_str(&s).map_err(|e| D::Error::custom(format!("Invalid pubkey '{s}': {e}")))
+    }
+}
@@ -5,19 +5,37 @@
 //! ## Purpose
 //! It subscribes to the single, unified event stream produced by the `Synchronizer` and
 //! forwards each event only to the specific listeners that have registered an interest
-//! in one of the public keys involved in that event.
+//! in one of the public keys involved in that event. Any number of listeners may register
+//! for the same PDA; each of them receives its own copy of every matching event.
 //!
 //! This architecture prevents each `UserListener` or `AdminListener` from having to
 //! process and filter the entire "firehose" of on-chain events, significantly
 //! improving efficiency.
+//!
+//! The number of listeners served at once can be bounded with `channels.max_listeners`,
+//! so that a client leaking listeners fails to register more of them instead of growing
+//! the dispatcher's memory without end.
 use crate::{
     config::ConnectorConfig,
-    events::{BridgeEvent, EventSource},
+    error::ConnectorError,
+    events::{BridgeEvent, EventCategory},
+    listener::{CatchupProgress, HistoryOptions, OverflowPolicy},
+    storage::Storage,
 };
-use futures::future;
 use solana_sdk::pubkey::Pubkey;
-use std::{collections::HashMap, sync::Arc};
-use tokio::sync::mpsc;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::{
+    broadcast,
+    mpsc::{self, error::TrySendError},
+    watch,
+};
+use w3b2_solana_program::protocols::CommandMode;
 
 /// A background worker that routes events from a single source to multiple listeners.
 ///
@@ -25,8 +43,16 @@ use tokio::sync::mpsc;
 /// `Synchronizer`'s broadcast channel to the appropriate `mpsc` channels based on
 /// the public keys associated with each event.
 pub struct Dispatcher {
-    listeners: HashMap<Pubkey, ListenerChannels>,
+    /// The live channels of the registered listeners, by PDA and listener id.
+    listeners: HashMap<Pubkey, HashMap<u64, LiveListener>>,
+    catchup_tx: mpsc::Sender<CatchupRequest>,
+    /// Records the PDAs with a history listener, so they can be replayed after a restart.
+    storage: Arc<dyn Storage>,
+    /// Counts the listeners in `listeners` against `channels.max_listeners`.
+    slots: ListenerSlots,
     command_rx: mpsc::Receiver<DispatcherCommand>,
+    /// The ordered queue of `Register` and `Unregister` commands.
+    control_rx: mpsc::UnboundedReceiver<DispatcherCommand>,
     event_tx: mpsc::Sender<BridgeEvent>,
     event_rx: mpsc::Receiver<BridgeEvent>,
 }
@@ -35,20 +61,202 @@ pub struct Dispatcher {
 #[derive(Debug)]
 pub enum DispatcherCommand {
     Register(Pubkey, ListenerChannels),
-    Unregister(Pubkey),
+    /// Removes the listener with the given id from a PDA.
+    Unregister(Pubkey, u64),
+    /// Hands a catch-up of a PDA over to the `CatchupWorker` without registering a listener.
+    Catchup(CatchupRequest),
+    /// Answers whether at least one listener is registered for a PDA.
+    #[cfg(feature = "test-util")]
+    HasListener(Pubkey, tokio::sync::oneshot::Sender<bool>),
     Dispatch(BridgeEvent),
     Shutdown,
 }
 
 #[derive(Debug, Clone)]
 pub struct ListenerChannels {
-    pub live: mpsc::Sender<BridgeEvent>,
-    pub catchup: mpsc::Sender<BridgeEvent>,
+    /// Identifies the listener among all listeners of the same PDA.
+    pub id: u64,
+    pub live: LiveChannels,
+    /// `None` for live-only listeners, whose history is never replayed.
+    pub catchup: Option<mpsc::Sender<BridgeEvent>>,
+    /// How the history is replayed to `catchup`.
+    pub history: HistoryOptions,
+    /// Where the `CatchupWorker` reports how far the replay of the history has come.
+    pub progress: watch::Sender<CatchupProgress>,
+    /// What happens to a live event while `live` is full.
+    pub overflow: OverflowPolicy,
+    /// Counts the live events dropped under `overflow`.
+    pub dropped: Arc<AtomicU64>,
+}
+
+/// Where the dispatcher delivers the live events of a listener.
+#[derive(Debug, Clone)]
+pub enum LiveChannels {
+    /// Every live event goes to a single channel.
+    Single(mpsc::Sender<BridgeEvent>),
+    /// Every live event goes to a single ring buffer of `capacity` events, which overwrites
+    /// its oldest event when full. Used by [`OverflowPolicy::DropOldest`] listeners.
+    Ring {
+        tx: broadcast::Sender<BridgeEvent>,
+        capacity: usize,
+    },
+    /// Every live event goes to the channel of its [`EventCategory`]. Events of a category
+    /// without a channel are not delivered.
+    ByCategory(HashMap<EventCategory, mpsc::Sender<BridgeEvent>>),
+}
+
+impl LiveChannels {
+    /// Attempts to deliver `event` without waiting for room in the channel.
+    ///
+    /// A categorized listener is only reported as closed once all of its channels are: a
+    /// consumer that dropped the stream of one category still receives the others. A ring
+    /// buffer is never full, since it overwrites its oldest event instead. The undelivered
+    /// event is dropped from the error.
+    fn try_send(&self, event: BridgeEvent) -> Result<(), TrySendError<()>> {
+        let result = match self {
+            Self::Single(tx) => tx.try_send(event),
+            Self::Ring { tx, .. } => {
+                return tx
+                    .send(event)
+                    .map(|_| ())
+                    .map_err(|_| TrySendError::Closed(()));
+            }
+            Self::ByCategory(channels) => {
+                let Some(tx) = channels.get(&event.data.kind().category()) else {
+                    return Ok(());
+                };
+                match tx.try_send(event) {
+                    Err(TrySendError::Closed(_)) if channels.values().any(|tx| !tx.is_closed()) => {
+                        Ok(())
+                    }
+                    result => result,
+                }
+            }
+        };
+        result.map_err(|e| match e {
+            TrySendError::Full(_) => TrySendError::Full(()),
+            TrySendError::Closed(_) => TrySendError::Closed(()),
+        })
+    }
+
+    /// Delivers `event`, waiting for room in the channel. Fails once the listener is gone.
+    async fn send(&self, event: BridgeEvent) -> Result<(), ()> {
+        match self {
+            Self::Single(tx) => tx.send(event).await.map_err(|_| ()),
+            Self::Ring { .. } => self.try_send(event).map_err(|_| ()),
+            Self::ByCategory(channels) => {
+                let Some(tx) = channels.get(&event.data.kind().category()) else {
+                    return Ok(());
+                };
+                match tx.send(event).await {
+                    Err(_) if channels.values().any(|tx| !tx.is_closed()) => Ok(()),
+                    result => result.map_err(|_| ()),
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if delivering another event would overwrite the oldest buffered one.
+    fn ring_is_full(&self) -> bool {
+        matches!(self, Self::Ring { tx, capacity } if tx.len() >= *capacity)
+    }
+}
+
+/// The live side of a registered listener.
+#[derive(Debug)]
+struct LiveListener {
+    channels: LiveChannels,
+    overflow: OverflowPolicy,
+    dropped: Arc<AtomicU64>,
+}
+
+impl LiveListener {
+    /// Delivers `event` according to the listener's [`OverflowPolicy`]. Returns `false` once
+    /// the listener has disconnected.
+    async fn deliver(&self, id: u64, pda: &Pubkey, event: BridgeEvent) -> bool {
+        if self.channels.ring_is_full() {
+            self.record_drop(id, pda, "its oldest event");
+        }
+        match self.channels.try_send(event.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(())) if self.overflow == OverflowPolicy::Block => {
+                tracing::warn!(
+                    "Listener {} for PDA {} is not keeping up; waiting for room in its channel.",
+                    id,
+                    pda
+                );
+                self.channels.send(event).await.is_ok()
+            }
+            Err(TrySendError::Full(())) => {
+                self.record_drop(id, pda, "an event");
+                true
+            }
+            Err(TrySendError::Closed(())) => false,
+        }
+    }
+
+    fn record_drop(&self, id: u64, pda: &Pubkey, what: &str) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        tracing::warn!(
+            "Listener {} for PDA {} is not keeping up; dropped {} for it.",
+            id,
+            pda,
+            what
+        );
+    }
+}
+
+/// A request for the `CatchupWorker` to replay the history of a single PDA.
+///
+/// The worker sends the historical events for `pda` to `sender`, in the order and up to
+/// the limit given by `history`, followed by a [`CaughtUp`](crate::events::CaughtUp)
+/// marker, and then drops the sender. Its position in the history is published through
+/// `progress` along the way.
+#[derive(Debug)]
+pub struct CatchupRequest {
+    pub pda: Pubkey,
+    pub sender: mpsc::Sender<BridgeEvent>,
+    pub history: HistoryOptions,
+    pub progress: watch::Sender<CatchupProgress>,
 }
 
 #[derive(Clone, Debug)]
 pub struct DispatcherHandle {
     pub command_tx: mpsc::Sender<DispatcherCommand>,
+    /// Carries the `Register` and `Unregister` commands of listeners in the order they were
+    /// issued, so that an `Unregister` never overtakes the `Register` of its listener.
+    control_tx: mpsc::UnboundedSender<DispatcherCommand>,
+    slots: ListenerSlots,
+}
+
+/// Counts the registered listeners against `channels.max_listeners`.
+///
+/// A slot is taken by the [`DispatcherHandle`] before the `Register` command is sent, so
+/// that the caller learns about the limit right away, and given back by the `Dispatcher`
+/// once it removes the listener.
+#[derive(Clone, Debug)]
+struct ListenerSlots {
+    active: Arc<AtomicUsize>,
+    max: Option<usize>,
+}
+
+impl ListenerSlots {
+    fn acquire(&self) -> Result<(), ConnectorError> {
+        let Some(max) = self.max else {
+            self.active.fetch_add(1, Ordering::AcqRel);
+            return Ok(());
+        };
+        self.active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| {
+                (active < max).then_some(active + 1)
+            })
+            .map(|_| ())
+            .map_err(|_| ConnectorError::TooManyListeners(max))
+    }
+
+    fn release(&self) {
+        self.active.fetch_sub(1, Ordering::AcqRel);
+    }
 }
 
 /// A collection of arguments required for the `prepare_user_dispatch_command` method.
@@ -62,15 +270,54 @@ pub struct UserDispatchCommandArgs {
     pub price: u64,
     /// The Unix timestamp from the oracle's signature, used to prevent replay attacks.
     pub timestamp: i64,
+    /// The `UserProfile` PDA the oracle signed the price for. The price can only pay for a
+    /// command of this user.
+    pub user_profile_pda: Pubkey,
     /// An opaque byte array for application-specific data.
     pub payload: Vec<u8>,
     /// The public key of the oracle that signed the message.
     pub oracle_pubkey: Pubkey,
     /// The 64-byte Ed25519 signature from the oracle.
     pub oracle_signature: [u8; 64],
+    /// Whether the user expects the service to respond. Not covered by the oracle's signature.
+    pub mode: CommandMode,
 }
 
 impl DispatcherHandle {
+    /// Queues the registration command of a listener for the dispatcher.
+    ///
+    /// Fails with [`ConnectorError::TooManyListeners`] if the dispatcher already serves
+    /// `channels.max_listeners` listeners, in which case nothing is registered.
+    pub(crate) fn register(
+        &self,
+        pda: Pubkey,
+        channels: ListenerChannels,
+    ) -> Result<(), ConnectorError> {
+        if let Err(e) = self.slots.acquire() {
+            tracing::warn!("Rejected listener {} for PDA {}: {}", channels.id, pda, e);
+            return Err(e);
+        }
+        if self
+            .control_tx
+            .send(DispatcherCommand::Register(pda, channels))
+            .is_err()
+        {
+            // Nobody will ever remove the listener, so its slot is given back right away.
+            tracing::warn!(
+                "Failed to register listener for PDA {}: dispatcher is down",
+                pda
+            );
+            self.slots.release();
+        }
+        Ok(())
+    }
+
+    /// Queues the unregistration command of a listener for the dispatcher, behind the
+    /// listener's own `Register`.
+    pub(crate) fn unregister(&self, pda: Pubkey, id: u64) {
+        let _ = self.control_tx.send(DispatcherCommand::Unregister(pda, id));
+    }
+
     pub async fn dispatch(&self, event: BridgeEvent) {
         if self
             .command_tx
@@ -82,6 +329,55 @@ impl DispatcherHandle {
         }
     }
 
+    /// Requests a catch-up of `pda` according to `history`, without registering a listener.
+    ///
+    /// Returns the receiving end of the replayed events, which ends with a
+    /// [`CaughtUp`](crate::events::CaughtUp) marker unless the history could not be fetched
+    /// in full. The receiver closes right away if the dispatcher is down.
+    pub(crate) async fn request_catchup(
+        &self,
+        pda: Pubkey,
+        history: HistoryOptions,
+        capacity: usize,
+    ) -> mpsc::Receiver<BridgeEvent> {
+        let (sender, events) = mpsc::channel(capacity.max(1));
+        let (progress, _) = watch::channel(CatchupProgress::default());
+        let request = CatchupRequest {
+            pda,
+            sender,
+            history,
+            progress,
+        };
+        if self
+            .command_tx
+            .send(DispatcherCommand::Catchup(request))
+            .await
+            .is_err()
+        {
+            tracing::warn!(
+                "Failed to request catch-up for PDA {}: dispatcher may be down",
+                pda
+            );
+        }
+        events
+    }
+
+    /// Returns `true` once at least one listener is registered for `pda`, and `false` if
+    /// none is or the dispatcher is down.
+    #[cfg(feature = "test-util")]
+    pub(crate) async fn has_listener(&self, pda: Pubkey) -> bool {
+        let (reply, answer) = tokio::sync::oneshot::channel();
+        if self
+            .command_tx
+            .send(DispatcherCommand::HasListener(pda, reply))
+            .await
+            .is_err()
+        {
+            return false;
+        }
+        answer.await.unwrap_or(false)
+    }
+
     pub async fn stop(&self) {
         if self
             .command_tx
@@ -96,19 +392,38 @@ impl DispatcherHandle {
 
 impl Dispatcher {
     /// Creates a new `Dispatcher`.
+    ///
+    /// Catch-up channels of newly registered listeners are handed over to the
+    /// `CatchupWorker` through `catchup_tx`. The PDAs of those listeners are recorded in
+    /// `storage` while they have at least one listener.
     pub fn new(
         config: Arc<ConnectorConfig>,
         command_tx: mpsc::Sender<DispatcherCommand>,
         command_rx: mpsc::Receiver<DispatcherCommand>,
+        catchup_tx: mpsc::Sender<CatchupRequest>,
+        storage: Arc<dyn Storage>,
     ) -> (Self, DispatcherHandle) {
         let (event_tx, event_rx) = mpsc::channel(config.channels.dispatcher_event_buffer);
+        let (control_tx, control_rx) = mpsc::unbounded_channel();
+        let slots = ListenerSlots {
+            active: Arc::default(),
+            max: config.channels.max_listeners,
+        };
         let dispatcher = Self {
             listeners: HashMap::new(),
+            catchup_tx,
+            storage,
+            slots: slots.clone(),
             command_rx,
+            control_rx,
             event_tx,
             event_rx,
         };
-        let handle = DispatcherHandle { command_tx };
+        let handle = DispatcherHandle {
+            command_tx,
+            control_tx,
+            slots,
+        };
         (dispatcher, handle)
     }
 
@@ -123,6 +438,9 @@ impl Dispatcher {
                         break;
                     }
                 },
+                Some(command) = self.control_rx.recv() => {
+                    self.handle_command(command).await;
+                },
                 else => {
                     tracing::info!("All channels closed. Dispatcher shutting down.");
                     break;
@@ -133,32 +451,45 @@ impl Dispatcher {
     }
 
     /// Handles an incoming event by dispatching it to all relevant listeners.
+    ///
+    /// Every event coming from the `Synchronizer` is newer than the history replayed to a
+    /// listener on registration, so it is delivered to the listener's live channel,
+    /// regardless of which worker observed it.
+    ///
+    /// Every listener registered for one of the event's PDAs receives a copy. What happens
+    /// when a listener's channel is full depends on its [`OverflowPolicy`]: an event is
+    /// dropped, or the delivery to all listeners waits until the listener makes room.
     async fn handle_event(&mut self, event: BridgeEvent) {
-        let pdas = extract_pdas_from_event(&event.data);
-        let sends = pdas
-            .iter()
-            .filter_map(|pda| self.listeners.get(pda).map(|channels| (pda, channels)))
-            .map(|(pda, channels)| {
-                let target_tx = match event.source {
-                    EventSource::Live => &channels.live,
-                    EventSource::Catchup => &channels.catchup,
-                };
-                let event_clone = event.clone();
-                async move {
-                    if target_tx.send(event_clone).await.is_err() {
-                        tracing::warn!(
-                            "Listener for PDA {} disconnected. It will be removed.",
-                            pda
-                        );
-                        return Some(*pda);
-                    }
-                    None
+        for pda in extract_pdas_from_event(&event.data) {
+            let Some(listeners) = self.listeners.get_mut(&pda) else {
+                continue;
+            };
+            let mut disconnected = Vec::new();
+            for (id, listener) in listeners.iter() {
+                if !listener.deliver(*id, &pda, event.clone()).await {
+                    disconnected.push(*id);
                 }
-            });
+            }
+            for id in disconnected {
+                tracing::warn!(
+                    "Listener {} for PDA {} disconnected. It will be removed.",
+                    id,
+                    pda
+                );
+                listeners.remove(&id);
+                self.slots.release();
+            }
+            if listeners.is_empty() {
+                self.listeners.remove(&pda);
+                self.forget_pda(&pda).await;
+            }
+        }
+    }
 
-        let results = future::join_all(sends).await;
-        for pda_to_remove in results.into_iter().flatten() {
-            self.listeners.remove(&pda_to_remove);
+    /// Removes `pda` from the active PDAs once its last listener is gone.
+    async fn forget_pda(&self, pda: &Pubkey) {
+        if let Err(e) = self.storage.remove_active_pda(pda).await {
+            tracing::warn!("Failed to remove active PDA {}: {}", pda, e);
         }
     }
 
@@ -166,12 +497,52 @@ impl Dispatcher {
     async fn handle_command(&mut self, command: DispatcherCommand) -> bool {
         match command {
             DispatcherCommand::Register(pda, channels) => {
-                tracing::info!("Registering new listener for PDA {}", pda);
-                self.listeners.insert(pda, channels);
+                tracing::info!("Registering listener {} for PDA {}", channels.id, pda);
+                self.listeners.entry(pda).or_default().insert(
+                    channels.id,
+                    LiveListener {
+                        channels: channels.live,
+                        overflow: channels.overflow,
+                        dropped: channels.dropped,
+                    },
+                );
+                let Some(sender) = channels.catchup else {
+                    return false;
+                };
+                if let Err(e) = self.storage.add_active_pda(&pda).await {
+                    tracing::warn!("Failed to record PDA {} as active: {}", pda, e);
+                }
+                let request = CatchupRequest {
+                    pda,
+                    sender,
+                    history: channels.history,
+                    progress: channels.progress,
+                };
+                if self.catchup_tx.send(request).await.is_err() {
+                    tracing::warn!("Failed to request catch-up for PDA {}: worker is down", pda);
+                }
             }
-            DispatcherCommand::Unregister(pda) => {
-                tracing::info!("Unregistering listener for PDA {}", pda);
-                self.listeners.remove(&pda);
+            DispatcherCommand::Unregister(pda, id) => {
+                tracing::info!("Unregistering listener {} for PDA {}", id, pda);
+                if let Some(listeners) = self.listeners.get_mut(&pda) {
+                    if listeners.remove(&id).is_some() {
+                        self.slots.release();
+                    }
+                    if listeners.is_empty() {
+                        self.listeners.remove(&pda);
+                        self.forget_pda(&pda).await;
+                    }
+                }
+            }
+            DispatcherCommand::Catchup(request) => {
+                let pda = request.pda;
+                if self.catchup_tx.send(request).await.is_err() {
+                    tracing::warn!("Failed to request catch-up for PDA {}: worker is down", pda);
+                }
+            }
+            #[cfg(feature = "test-util")]
+            DispatcherCommand::HasListener(pda, reply) => {
+                let _ = reply.send(self.listeners.contains_key(&pda));
             }
             DispatcherCommand::Dispatch(event) => {
                 if self.event_tx.send(event).await.is_err() {
@@ -190,7 +561,7 @@ impl Dispatcher {
 
 /// A helper function that inspects a `BridgeEvent` and returns a `Vec<Pubkey>`
 /// of all relevant PDAs.
-fn extract_pdas_from_event(event_data: &crate::events::BridgeEventData) -> Vec<Pubkey> {
+pub(crate) fn extract_pdas_from_event(event_data: &crate::events::BridgeEventData) -> Vec<Pubkey> {
     match event_data {
         // Admin-only events
         crate::events::BridgeEventData::AdminProfileRegistered(e) => vec![e.admin_pda],
@@ -202,6 +573,10 @@ fn extract_pdas_from_event(event_data: &crate::events::BridgeEventData) -> Vec<P
         crate::events::BridgeEventData::UserCommKeyUpdated(e) => vec![e.user_profile_pda],
         crate::events::BridgeEventData::UserFundsDeposited(e) => vec![e.user_profile_pda],
         crate::events::BridgeEventData::UserFundsWithdrawn(e) => vec![e.user_profile_pda],
+        crate::events::BridgeEventData::UserLowBalanceThresholdUpdated(e) => {
+            vec![e.user_profile_pda]
+        }
+        crate::events::BridgeEventData::UserSpendingCapUpdated(e) => vec![e.user_profile_pda],
 
         // Events relevant to both User and Admin
         crate::events::BridgeEventData::UserProfileCreated(e) => {
@@ -221,12 +596,33 @@ fn extract_pdas_from_event(event_data: &crate::events::BridgeEventData) -> Vec<P
         crate::events::BridgeEventData::UserBanned(e) => {
             vec![e.user_profile_pda, e.admin_pda]
         }
+        crate::events::BridgeEventData::AllowanceGranted(e) => {
+            vec![e.user_profile_pda, e.admin_pda]
+        }
+        crate::events::BridgeEventData::AllowanceConsumed(e) => {
+            vec![e.user_profile_pda, e.admin_pda]
+        }
         crate::events::BridgeEventData::UserUnbanned(e) => {
             vec![e.user_profile_pda, e.admin_pda]
         }
         crate::events::BridgeEventData::UserUnbanRequested(e) => {
             vec![e.user_profile_pda, e.admin_pda]
         }
+        crate::events::BridgeEventData::UserLowBalance(e) => {
+            vec![e.user_profile_pda, e.admin_pda]
+        }
+
+        // Shared wallet events
+        crate::events::BridgeEventData::WalletCreated(e) => vec![e.wallet_pda],
+        crate::events::BridgeEventData::WalletFundsDeposited(e) => vec![e.wallet_pda],
+        crate::events::BridgeEventData::WalletFundsWithdrawn(e) => vec![e.wallet_pda],
+        crate::events::BridgeEventData::WalletCharged(e) => {
+            vec![e.wallet_pda, e.user_profile_pda, e.admin_pda]
+        }
+
+        crate::events::BridgeEventData::CaughtUp(e) => vec![e.pda],
+        crate::events::BridgeEventData::Retracted(e) => e.pdas.clone(),
+        crate::events::BridgeEventData::DispatchRejected(e) => vec![e.user_pda, e.admin_pda],
         crate::events::BridgeEventData::Unknown => vec![],
     }
 }
@@ -0,0 +1,96 @@
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+use w3b2_solana_program::instructions::{
+    MAX_TIMESTAMP_VALIDITY_SECONDS, MIN_TIMESTAMP_VALIDITY_SECONDS,
+};
+
+/// The length of an Ed25519 signature, in bytes.
+pub const SIGNATURE_LENGTH: usize = 64;
+
+/// Errors raised by the connector when it is given inputs that can never produce a valid
+/// transaction. They are detected before anything is built or sent, instead of surfacing
+/// as an on-chain failure. [`TooManyListeners`](Self::TooManyListeners) is the exception:
+/// it reports that the `EventManager` refuses another listener.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectorError {
+    /// An oracle signature was not [`SIGNATURE_LENGTH`] bytes long. Holds the actual length.
+    InvalidSignatureLength(usize),
+    /// An oracle public key is not a point on the Ed25519 curve, so no signature can ever
+    /// verify against it. Such keys are usually PDAs or truncated keys.
+    OracleKeyOffCurve(Pubkey),
+    /// An oracle price was signed for another user's profile than the one dispatching the
+    /// command, so the program would not find its signature.
+    PriceSignedForAnotherUser {
+        signed_for: Pubkey,
+        dispatched_for: Pubkey,
+    },
+    /// A payload is too large to be split into `log_action` chunks. Holds its length.
+    PayloadTooLarge(usize),
+    /// The event at this position does not carry a valid payload chunk, or disagrees with
+    /// the others on the number of chunks.
+    MalformedChunk(usize),
+    /// The chunks of a payload were logged under different sessions.
+    ChunkSessionMismatch { expected: u64, found: u64 },
+    /// A payload chunk with this sequence number was given more than once.
+    DuplicateChunk(u16),
+    /// The payload chunk with this sequence number was not given.
+    MissingChunk(u16),
+    /// The payload validator of the `TransactionBuilder` rejected a dispatch payload. Holds
+    /// the validator's description of the problem.
+    InvalidPayload(String),
+    /// A `timestamp_validity_seconds` outside the range the program accepts. Holds the value.
+    InvalidTimestampValidity(i64),
+    /// The dispatcher already serves `channels.max_listeners` listeners. Holds the limit.
+    TooManyListeners(usize),
+}
+
+impl fmt::Display for ConnectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSignatureLength(len) => write!(
+                f,
+                "the oracle signature must be {SIGNATURE_LENGTH} bytes long, got {len}"
+            ),
+            Self::OracleKeyOffCurve(key) => write!(
+                f,
+                "the oracle public key {key} is not a valid Ed25519 point"
+            ),
+            Self::PriceSignedForAnotherUser {
+                signed_for,
+                dispatched_for,
+            } => write!(
+                f,
+                "the oracle price was signed for user profile {signed_for}, not for \
+                 {dispatched_for}"
+            ),
+            Self::PayloadTooLarge(len) => write!(
+                f,
+                "a payload of {len} bytes does not fit in {} chunks",
+                u16::MAX
+            ),
+            Self::MalformedChunk(position) => {
+                write!(f, "event {position} does not carry a valid payload chunk")
+            }
+            Self::ChunkSessionMismatch { expected, found } => write!(
+                f,
+                "payload chunks belong to different sessions: {expected} and {found}"
+            ),
+            Self::DuplicateChunk(sequence) => {
+                write!(f, "payload chunk {sequence} was given more than once")
+            }
+            Self::MissingChunk(sequence) => write!(f, "payload chunk {sequence} is missing"),
+            Self::InvalidPayload(reason) => write!(f, "the payload is invalid: {reason}"),
+            Self::InvalidTimestampValidity(validity) => write!(
+                f,
+                "the timestamp validity must be between {MIN_TIMESTAMP_VALIDITY_SECONDS} and \
+                 {MAX_TIMESTAMP_VALIDITY_SECONDS} seconds, got {validity}"
+            ),
+            Self::TooManyListeners(max) => write!(
+                f,
+                "the dispatcher already serves its maximum of {max} listeners"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConnectorError {}
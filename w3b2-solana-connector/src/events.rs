@@ -1,8 +1,25 @@
 use anchor_lang::AnchorDeserialize;
 use anchor_lang::Discriminator;
+use anchor_lang::Event;
 use anyhow::Result;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiLoadedAddresses};
+use std::fmt;
+use std::str::FromStr;
+use w3b2_solana_program::errors::BridgeError;
 use w3b2_solana_program::events as OnChainEvent;
+use w3b2_solana_program::instruction::{
+    DispatchWithAllowance, UserDispatchCommand, UserDispatchCommandFromWallet,
+};
+
+/// The version of the event schema decoded by this module.
+///
+/// Bumped whenever an on-chain event gains, loses or reorders a field, so that consumers
+/// can detect that they decode events with a different layout than the connector.
+pub const EVENT_SCHEMA_VERSION: u32 = 9;
 
 /// Indicates the origin of a `BridgeEvent`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,6 +35,11 @@ pub enum EventSource {
 #[derive(Debug, Clone)]
 pub struct BridgeEvent {
     pub source: EventSource,
+    /// The signature of the transaction that emitted the event.
+    /// `None` for connector-generated markers such as [`CaughtUp`].
+    pub signature: Option<String>,
+    /// The slot of the transaction that emitted the event (or the slot a marker refers to).
+    pub slot: u64,
     pub data: BridgeEventData,
 }
 #[derive(Debug, Clone)]
@@ -38,124 +60,683 @@ pub enum BridgeEventData {
     UserBanned(OnChainEvent::UserBanned),
     UserUnbanned(OnChainEvent::UserUnbanned),
     UserUnbanRequested(OnChainEvent::UserUnbanRequested),
+    UserLowBalanceThresholdUpdated(OnChainEvent::UserLowBalanceThresholdUpdated),
+    UserLowBalance(OnChainEvent::UserLowBalance),
+    UserSpendingCapUpdated(OnChainEvent::UserSpendingCapUpdated),
+    AllowanceGranted(OnChainEvent::AllowanceGranted),
+    AllowanceConsumed(OnChainEvent::AllowanceConsumed),
+    WalletCreated(OnChainEvent::WalletCreated),
+    WalletFundsDeposited(OnChainEvent::WalletFundsDeposited),
+    WalletFundsWithdrawn(OnChainEvent::WalletFundsWithdrawn),
+    WalletCharged(OnChainEvent::WalletCharged),
+    /// A connector-generated marker, not an on-chain event. See [`CaughtUp`].
+    CaughtUp(CaughtUp),
+    /// A connector-generated notice that previously delivered events were rolled back.
+    /// See [`Retracted`].
+    Retracted(Retracted),
+    /// A connector-generated notice that a paid user command failed the oracle
+    /// checks. See [`DispatchRejected`].
+    DispatchRejected(DispatchRejected),
+    /// A `Program data:` log of the program that could not be decoded, because its
+    /// discriminator is not recognized by this version of the connector or because the log
+    /// is malformed, e.g. truncated by the RPC node. It concerns no PDA, so the dispatcher
+    /// delivers it to no listener. [`try_parse_log`] reports such logs as errors instead.
     Unknown,
 }
 
-pub fn try_parse_log(log: &str) -> Result<BridgeEvent> {
-    if let Some(data_str) = log.strip_prefix("Program data: ") {
-        if let Ok(bytes) = BASE64.decode(data_str.trim()) {
-            let data = &bytes;
-
-            fn try_match<E, F>(data: &[u8], map: F) -> Option<BridgeEventData>
-            where
-                E: AnchorDeserialize + Discriminator,
-                F: FnOnce(E) -> BridgeEventData,
-            {
-                let disc = E::DISCRIMINATOR;
-                if data.starts_with(disc) {
-                    if let Ok(e) = E::try_from_slice(&data[disc.len()..]) {
-                        return Some(map(e));
-                    }
-                }
-                None
+impl BridgeEventData {
+    /// Returns the kind of this event.
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::AdminProfileRegistered(_) => EventKind::AdminProfileRegistered,
+            Self::AdminConfigUpdated(_) => EventKind::AdminConfigUpdated,
+            Self::AdminFundsWithdrawn(_) => EventKind::AdminFundsWithdrawn,
+            Self::AdminProfileClosed(_) => EventKind::AdminProfileClosed,
+            Self::AdminCommandDispatched(_) => EventKind::AdminCommandDispatched,
+            Self::UserProfileCreated(_) => EventKind::UserProfileCreated,
+            Self::UserCommKeyUpdated(_) => EventKind::UserCommKeyUpdated,
+            Self::UserFundsDeposited(_) => EventKind::UserFundsDeposited,
+            Self::UserFundsWithdrawn(_) => EventKind::UserFundsWithdrawn,
+            Self::UserProfileClosed(_) => EventKind::UserProfileClosed,
+            Self::UserCommandDispatched(_) => EventKind::UserCommandDispatched,
+            Self::OffChainActionLogged(_) => EventKind::OffChainActionLogged,
+            Self::AdminUnbanFeeUpdated(_) => EventKind::AdminUnbanFeeUpdated,
+            Self::UserBanned(_) => EventKind::UserBanned,
+            Self::UserUnbanned(_) => EventKind::UserUnbanned,
+            Self::UserUnbanRequested(_) => EventKind::UserUnbanRequested,
+            Self::UserLowBalanceThresholdUpdated(_) => EventKind::UserLowBalanceThresholdUpdated,
+            Self::UserLowBalance(_) => EventKind::UserLowBalance,
+            Self::UserSpendingCapUpdated(_) => EventKind::UserSpendingCapUpdated,
+            Self::AllowanceGranted(_) => EventKind::AllowanceGranted,
+            Self::AllowanceConsumed(_) => EventKind::AllowanceConsumed,
+            Self::WalletCreated(_) => EventKind::WalletCreated,
+            Self::WalletFundsDeposited(_) => EventKind::WalletFundsDeposited,
+            Self::WalletFundsWithdrawn(_) => EventKind::WalletFundsWithdrawn,
+            Self::WalletCharged(_) => EventKind::WalletCharged,
+            Self::CaughtUp(_) => EventKind::CaughtUp,
+            Self::Retracted(_) => EventKind::Retracted,
+            Self::DispatchRejected(_) => EventKind::DispatchRejected,
+            Self::Unknown => EventKind::Unknown,
+        }
+    }
+
+    /// Encodes an on-chain event exactly as the program wrote it to its `Program data:` log.
+    /// Returns `None` for connector-generated markers and [`BridgeEventData::Unknown`].
+    pub fn to_program_data(&self) -> Option<Vec<u8>> {
+        let data = match self {
+            Self::AdminProfileRegistered(e) => e.data(),
+            Self::AdminConfigUpdated(e) => e.data(),
+            Self::AdminFundsWithdrawn(e) => e.data(),
+            Self::AdminProfileClosed(e) => e.data(),
+            Self::AdminCommandDispatched(e) => e.data(),
+            Self::UserProfileCreated(e) => e.data(),
+            Self::UserCommKeyUpdated(e) => e.data(),
+            Self::UserFundsDeposited(e) => e.data(),
+            Self::UserFundsWithdrawn(e) => e.data(),
+            Self::UserProfileClosed(e) => e.data(),
+            Self::UserCommandDispatched(e) => e.data(),
+            Self::OffChainActionLogged(e) => e.data(),
+            Self::AdminUnbanFeeUpdated(e) => e.data(),
+            Self::UserBanned(e) => e.data(),
+            Self::UserUnbanned(e) => e.data(),
+            Self::UserUnbanRequested(e) => e.data(),
+            Self::UserLowBalanceThresholdUpdated(e) => e.data(),
+            Self::UserLowBalance(e) => e.data(),
+            Self::UserSpendingCapUpdated(e) => e.data(),
+            Self::AllowanceGranted(e) => e.data(),
+            Self::AllowanceConsumed(e) => e.data(),
+            Self::WalletCreated(e) => e.data(),
+            Self::WalletFundsDeposited(e) => e.data(),
+            Self::WalletFundsWithdrawn(e) => e.data(),
+            Self::WalletCharged(e) => e.data(),
+            Self::CaughtUp(_) | Self::Retracted(_) | Self::DispatchRejected(_) | Self::Unknown => {
+                return None
+            }
+        };
+        Some(data)
+    }
+}
+
+/// The kind of a [`BridgeEventData`], without its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EventKind {
+    AdminProfileRegistered,
+    AdminConfigUpdated,
+    AdminFundsWithdrawn,
+    AdminProfileClosed,
+    AdminCommandDispatched,
+    UserProfileCreated,
+    UserCommKeyUpdated,
+    UserFundsDeposited,
+    UserFundsWithdrawn,
+    UserProfileClosed,
+    UserCommandDispatched,
+    OffChainActionLogged,
+    AdminUnbanFeeUpdated,
+    UserBanned,
+    UserUnbanned,
+    UserUnbanRequested,
+    UserLowBalanceThresholdUpdated,
+    UserLowBalance,
+    UserSpendingCapUpdated,
+    AllowanceGranted,
+    AllowanceConsumed,
+    WalletCreated,
+    WalletFundsDeposited,
+    WalletFundsWithdrawn,
+    WalletCharged,
+    CaughtUp,
+    Retracted,
+    DispatchRejected,
+    Unknown,
+}
+
+impl EventKind {
+    /// Every event kind, in declaration order.
+    pub const ALL: [EventKind; 29] = [
+        Self::AdminProfileRegistered,
+        Self::AdminConfigUpdated,
+        Self::AdminFundsWithdrawn,
+        Self::AdminProfileClosed,
+        Self::AdminCommandDispatched,
+        Self::UserProfileCreated,
+        Self::UserCommKeyUpdated,
+        Self::UserFundsDeposited,
+        Self::UserFundsWithdrawn,
+        Self::UserProfileClosed,
+        Self::UserCommandDispatched,
+        Self::OffChainActionLogged,
+        Self::AdminUnbanFeeUpdated,
+        Self::UserBanned,
+        Self::UserUnbanned,
+        Self::UserUnbanRequested,
+        Self::UserLowBalanceThresholdUpdated,
+        Self::UserLowBalance,
+        Self::UserSpendingCapUpdated,
+        Self::AllowanceGranted,
+        Self::AllowanceConsumed,
+        Self::WalletCreated,
+        Self::WalletFundsDeposited,
+        Self::WalletFundsWithdrawn,
+        Self::WalletCharged,
+        Self::CaughtUp,
+        Self::Retracted,
+        Self::DispatchRejected,
+        Self::Unknown,
+    ];
+
+    /// Returns the name of the event kind, which is also the name of its variant.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AdminProfileRegistered => "AdminProfileRegistered",
+            Self::AdminConfigUpdated => "AdminConfigUpdated",
+            Self::AdminFundsWithdrawn => "AdminFundsWithdrawn",
+            Self::AdminProfileClosed => "AdminProfileClosed",
+            Self::AdminCommandDispatched => "AdminCommandDispatched",
+            Self::UserProfileCreated => "UserProfileCreated",
+            Self::UserCommKeyUpdated => "UserCommKeyUpdated",
+            Self::UserFundsDeposited => "UserFundsDeposited",
+            Self::UserFundsWithdrawn => "UserFundsWithdrawn",
+            Self::UserProfileClosed => "UserProfileClosed",
+            Self::UserCommandDispatched => "UserCommandDispatched",
+            Self::OffChainActionLogged => "OffChainActionLogged",
+            Self::AdminUnbanFeeUpdated => "AdminUnbanFeeUpdated",
+            Self::UserBanned => "UserBanned",
+            Self::UserUnbanned => "UserUnbanned",
+            Self::UserUnbanRequested => "UserUnbanRequested",
+            Self::UserLowBalanceThresholdUpdated => "UserLowBalanceThresholdUpdated",
+            Self::UserLowBalance => "UserLowBalance",
+            Self::UserSpendingCapUpdated => "UserSpendingCapUpdated",
+            Self::AllowanceGranted => "AllowanceGranted",
+            Self::AllowanceConsumed => "AllowanceConsumed",
+            Self::WalletCreated => "WalletCreated",
+            Self::WalletFundsDeposited => "WalletFundsDeposited",
+            Self::WalletFundsWithdrawn => "WalletFundsWithdrawn",
+            Self::WalletCharged => "WalletCharged",
+            Self::CaughtUp => "CaughtUp",
+            Self::Retracted => "Retracted",
+            Self::DispatchRejected => "DispatchRejected",
+            Self::Unknown => "Unknown",
+        }
+    }
+
+    /// Returns the category the event kind belongs to.
+    pub fn category(&self) -> EventCategory {
+        match self {
+            Self::AdminFundsWithdrawn
+            | Self::UserFundsDeposited
+            | Self::UserFundsWithdrawn
+            | Self::UserCommandDispatched
+            | Self::UserLowBalance
+            | Self::AllowanceConsumed
+            | Self::WalletFundsDeposited
+            | Self::WalletFundsWithdrawn
+            | Self::WalletCharged
+            | Self::DispatchRejected => EventCategory::Financial,
+            Self::AdminProfileRegistered
+            | Self::AdminConfigUpdated
+            | Self::AdminProfileClosed
+            | Self::AdminUnbanFeeUpdated
+            | Self::UserProfileCreated
+            | Self::UserCommKeyUpdated
+            | Self::UserProfileClosed
+            | Self::UserBanned
+            | Self::UserUnbanned
+            | Self::UserUnbanRequested
+            | Self::UserLowBalanceThresholdUpdated
+            | Self::UserSpendingCapUpdated
+            | Self::AllowanceGranted
+            | Self::WalletCreated => EventCategory::Administrative,
+            Self::AdminCommandDispatched | Self::OffChainActionLogged => EventCategory::Messaging,
+            Self::CaughtUp | Self::Retracted | Self::Unknown => EventCategory::Connector,
+        }
+    }
+}
+
+/// A coarse grouping of [`EventKind`]s, for consumers that handle each group separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EventCategory {
+    /// Events that move or account for lamports: deposits, withdrawals, paid user commands
+    /// and balance warnings.
+    Financial,
+    /// Changes to profiles and their configuration, including bans and unbans.
+    Administrative,
+    /// Messages that move no funds: admin commands and logged off-chain actions.
+    Messaging,
+    /// Markers generated by the connector itself, such as `CaughtUp` and `Retracted`.
+    Connector,
+}
+
+impl EventCategory {
+    /// Every event category, in declaration order.
+    pub const ALL: [EventCategory; 4] = [
+        Self::Financial,
+        Self::Administrative,
+        Self::Messaging,
+        Self::Connector,
+    ];
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown event kind: {s}"))
+    }
+}
+
+/// Signals that a transaction whose events were already delivered has been dropped by a fork.
+///
+/// Emitted only when `synchronizer.detect_reorgs` is enabled. Every event previously
+/// delivered with the same `signature` must be treated as if it never happened.
+/// Applications using `finalized` commitment never observe rolled-back transactions
+/// and can leave reorg detection disabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Retracted {
+    /// The signature of the dropped transaction.
+    pub signature: String,
+    /// The PDAs the retracted events were delivered for.
+    pub pdas: Vec<Pubkey>,
+}
+
+/// Reports a `user_dispatch_command` or `dispatch_with_allowance` that failed because its
+/// oracle price signature was rejected.
+///
+/// A failed transaction cannot emit events, so this notice is built by the connector from
+/// the error and the accounts of the failed transaction. It is emitted only when
+/// `synchronizer.report_dispatch_rejections` is enabled, by the catch-up poll and the
+/// WebSocket live source (the Geyser source does not receive failed transactions), and is
+/// never part of a replayed PDA history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchRejected {
+    /// The signature of the failed transaction.
+    pub signature: String,
+    /// Why the oracle signature was rejected.
+    pub reason: DispatchRejectionReason,
+    /// The `UserProfile` PDA of the command.
+    pub user_pda: Pubkey,
+    /// The `AdminProfile` PDA of the command.
+    pub admin_pda: Pubkey,
+}
+
+/// The oracle check a rejected user command failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchRejectionReason {
+    /// The price was signed by a key other than the admin's `oracle_authority`.
+    InvalidOracleSigner,
+    /// The signed message does not match the command, or the signature is malformed.
+    SignatureVerificationFailed,
+    /// The signed timestamp is outside the admin's validity window.
+    TimestampTooOld,
+    /// The signed timestamp is too far ahead of the cluster clock.
+    TimestampInFuture,
+}
+
+impl DispatchRejectionReason {
+    /// Returns the reason matching a custom program error code, if it is an oracle check.
+    pub fn from_error_code(code: u32) -> Option<Self> {
+        [
+            Self::InvalidOracleSigner,
+            Self::SignatureVerificationFailed,
+            Self::TimestampTooOld,
+            Self::TimestampInFuture,
+        ]
+        .into_iter()
+        .find(|reason| u32::from(reason.error()) == code)
+    }
+
+    /// Returns the reason a transaction failed with, if it failed an oracle check.
+    pub fn from_transaction_error(err: &TransactionError) -> Option<Self> {
+        match err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+                Self::from_error_code(*code)
             }
+            _ => None,
+        }
+    }
 
-            let event_data = try_match::<OnChainEvent::AdminProfileRegistered, _>(
-                data,
-                BridgeEventData::AdminProfileRegistered,
-            )
-            .or_else(|| {
-                try_match::<OnChainEvent::AdminConfigUpdated, _>(
-                    data,
-                    BridgeEventData::AdminConfigUpdated,
-                )
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::AdminFundsWithdrawn, _>(
-                    data,
-                    BridgeEventData::AdminFundsWithdrawn,
-                )
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::AdminProfileClosed, _>(
-                    data,
-                    BridgeEventData::AdminProfileClosed,
-                )
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::AdminCommandDispatched, _>(
-                    data,
-                    BridgeEventData::AdminCommandDispatched,
-                )
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::UserProfileCreated, _>(
-                    data,
-                    BridgeEventData::UserProfileCreated,
-                )
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::UserCommKeyUpdated, _>(
-                    data,
-                    BridgeEventData::UserCommKeyUpdated,
-                )
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::UserFundsDeposited, _>(
-                    data,
-                    BridgeEventData::UserFundsDeposited,
-                )
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::UserFundsWithdrawn, _>(
-                    data,
-                    BridgeEventData::UserFundsWithdrawn,
-                )
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::UserProfileClosed, _>(
-                    data,
-                    BridgeEventData::UserProfileClosed,
-                )
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::UserCommandDispatched, _>(
-                    data,
-                    BridgeEventData::UserCommandDispatched,
-                )
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::OffChainActionLogged, _>(
-                    data,
-                    BridgeEventData::OffChainActionLogged,
-                )
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::AdminUnbanFeeUpdated, _>(
-                    data,
-                    BridgeEventData::AdminUnbanFeeUpdated,
-                )
-            })
-            .or_else(|| try_match::<OnChainEvent::UserBanned, _>(data, BridgeEventData::UserBanned))
-            .or_else(|| {
-                try_match::<OnChainEvent::UserUnbanned, _>(data, BridgeEventData::UserUnbanned)
-            })
-            .or_else(|| {
-                try_match::<OnChainEvent::UserUnbanRequested, _>(
-                    data,
-                    BridgeEventData::UserUnbanRequested,
-                )
-            })
-            .unwrap_or(BridgeEventData::Unknown);
-
-            if !matches!(event_data, BridgeEventData::Unknown) {
-                return Ok(BridgeEvent {
-                    source: EventSource::Catchup,
-                    data: event_data,
-                });
+    /// Returns the name of the reason, which is also the name of the `BridgeError`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidOracleSigner => "InvalidOracleSigner",
+            Self::SignatureVerificationFailed => "SignatureVerificationFailed",
+            Self::TimestampTooOld => "TimestampTooOld",
+            Self::TimestampInFuture => "TimestampInFuture",
+        }
+    }
+
+    fn error(self) -> BridgeError {
+        match self {
+            Self::InvalidOracleSigner => BridgeError::InvalidOracleSigner,
+            Self::SignatureVerificationFailed => BridgeError::SignatureVerificationFailed,
+            Self::TimestampTooOld => BridgeError::TimestampTooOld,
+            Self::TimestampInFuture => BridgeError::TimestampInFuture,
+        }
+    }
+}
+
+impl fmt::Display for DispatchRejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Builds the [`DispatchRejected`] notice of a failed transaction, if it failed because a
+/// `user_dispatch_command`, `user_dispatch_command_from_wallet` or `dispatch_with_allowance`
+/// did not pass the oracle checks.
+///
+/// The transaction must have been fetched with a binary encoding, so that its message can
+/// be decoded to find the profiles of the failing instruction.
+pub fn parse_dispatch_rejection(
+    signature: &str,
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Option<DispatchRejected> {
+    parse_dispatch_rejection_for(&w3b2_solana_program::ID, signature, tx)
+}
+
+/// Like [`parse_dispatch_rejection`], for the deployment of the program at `program_id`.
+pub fn parse_dispatch_rejection_for(
+    program_id: &Pubkey,
+    signature: &str,
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Option<DispatchRejected> {
+    let meta = tx.transaction.meta.as_ref()?;
+    let err = meta.err.as_ref()?;
+    let reason = DispatchRejectionReason::from_transaction_error(err)?;
+    let TransactionError::InstructionError(index, _) = err else {
+        return None;
+    };
+
+    let transaction = tx.transaction.transaction.decode()?;
+    let message = &transaction.message;
+    // Address lookup tables append their writable, then readonly, accounts to the keys.
+    let loaded: Option<&UiLoadedAddresses> = meta.loaded_addresses.as_ref().into();
+    let mut keys = message.static_account_keys().to_vec();
+    if let Some(loaded) = loaded {
+        for address in loaded.writable.iter().chain(&loaded.readonly) {
+            keys.push(address.parse().ok()?);
+        }
+    }
+
+    let ix = message.instructions().get(usize::from(*index))?;
+    let is_dispatch = keys.get(usize::from(ix.program_id_index)) == Some(program_id)
+        && (ix.data.starts_with(UserDispatchCommand::DISCRIMINATOR)
+            || ix
+                .data
+                .starts_with(UserDispatchCommandFromWallet::DISCRIMINATOR)
+            || ix.data.starts_with(DispatchWithAllowance::DISCRIMINATOR));
+    if !is_dispatch {
+        return None;
+    }
+    // All three instructions take the signer, `user_profile` and `admin_profile` first.
+    let account = |position: usize| {
+        let key_index = *ix.accounts.get(position)?;
+        keys.get(usize::from(key_index)).copied()
+    };
+    Some(DispatchRejected {
+        signature: signature.to_string(),
+        reason,
+        user_pda: account(1)?,
+        admin_pda: account(2)?,
+    })
+}
+
+/// Marks the end of the historical (catch-up) stream for a single PDA.
+///
+/// The `CatchupWorker` delivers this exactly once per listener, after every historical
+/// event for the PDA has been sent to its catch-up channel. The channel is closed right
+/// after the marker, so from this point on all events arrive through the live stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaughtUp {
+    /// The PDA whose history has been fully delivered.
+    pub pda: Pubkey,
+    /// The slot up to which the history was fetched.
+    pub slot: u64,
+}
+
+/// Parses every W3B2 event from the complete log output of a transaction.
+///
+/// The runtime brackets each program invocation with `Program <id> invoke [depth]` and
+/// `Program <id> success` (or `Program <id> failed: ...`) lines, so the logs form a call
+/// stack. A `Program data:` line belongs to the program on top of that stack. Tracking the
+/// stack lets events emitted by the W3B2 program while it is invoked through CPI (at any
+/// depth) be picked up, while `Program data:` lines written by other programs in the same
+/// transaction are ignored even if they happen to decode as W3B2 events.
+///
+/// A line of the W3B2 program that cannot be decoded becomes a [`BridgeEventData::Unknown`]
+/// event, see [`parse_log`].
+pub fn parse_program_logs<S: AsRef<str>>(logs: &[S]) -> Vec<BridgeEvent> {
+    parse_program_logs_for(&w3b2_solana_program::ID, logs)
+}
+
+/// Like [`parse_program_logs`], for the deployment of the program at `program_id`.
+pub fn parse_program_logs_for<S: AsRef<str>>(program_id: &Pubkey, logs: &[S]) -> Vec<BridgeEvent> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        let log = log.as_ref();
+        if log.starts_with("Program data: ") {
+            if stack.last() == Some(&program_id.as_str()) {
+                events.push(parse_log(log));
             }
+            continue;
+        }
+
+        let Some((id, rest)) = log.strip_prefix("Program ").and_then(|l| l.split_once(' ')) else {
+            continue;
+        };
+        if rest.starts_with("invoke [") {
+            stack.push(id);
+        } else if rest == "success" || rest.starts_with("failed") {
+            stack.pop();
         }
     }
 
-    Err(anyhow::anyhow!("Log is not a valid program event"))
+    events
+}
+
+/// Parses every W3B2 event from the logs of a fetched transaction, stamping each with the
+/// transaction's signature and slot and the given `source`.
+///
+/// A failed transaction has no events: anything it logged before failing was rolled back.
+pub fn parse_transaction_events(
+    signature: &str,
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+    source: EventSource,
+) -> Vec<BridgeEvent> {
+    parse_transaction_events_for(&w3b2_solana_program::ID, signature, tx, source)
+}
+
+/// Like [`parse_transaction_events`], for the deployment of the program at `program_id`.
+pub fn parse_transaction_events_for(
+    program_id: &Pubkey,
+    signature: &str,
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+    source: EventSource,
+) -> Vec<BridgeEvent> {
+    let logs: Option<&Vec<String>> = tx
+        .transaction
+        .meta
+        .as_ref()
+        .filter(|meta| meta.err.is_none())
+        .and_then(|meta| meta.log_messages.as_ref().into());
+    let Some(logs) = logs else {
+        return Vec::new();
+    };
+    parse_program_logs_for(program_id, logs)
+        .into_iter()
+        .map(|mut event| {
+            event.source = source;
+            event.signature = Some(signature.to_string());
+            event.slot = tx.slot;
+            event
+        })
+        .collect()
+}
+
+/// Parses a single `Program data:` log line into a [`BridgeEvent`].
+///
+/// Every event emitted by `w3b2-solana-program` has a corresponding [`BridgeEventData`]
+/// variant. Lines that are not program data, or whose discriminator does not belong to any
+/// known event, are rejected with an error. A known discriminator followed by a body that
+/// fails to deserialize is also an error, so a layout mismatch between the program and the
+/// connector is reported instead of being silently dropped.
+pub fn try_parse_log(log: &str) -> Result<BridgeEvent> {
+    let data_str = log
+        .strip_prefix("Program data: ")
+        .ok_or_else(|| anyhow::anyhow!("Log is not a valid program event"))?;
+    let data = BASE64
+        .decode(data_str.trim())
+        .map_err(|_| anyhow::anyhow!("Log is not a valid program event"))?;
+    try_parse_program_data(&data)
+}
+
+/// Parses a single `Program data:` log line like [`try_parse_log`], but never fails.
+///
+/// A line that cannot be decoded, such as truncated base64, data shorter than a
+/// discriminator or an event followed by trailing bytes, yields a
+/// [`BridgeEventData::Unknown`] event instead of being mis-parsed or silently dropped.
+pub fn parse_log(log: &str) -> BridgeEvent {
+    try_parse_log(log).unwrap_or_else(|e| {
+        tracing::debug!("Failed to decode a program data log ({}): {}", e, log);
+        BridgeEvent {
+            source: EventSource::Catchup,
+            signature: None,
+            slot: 0,
+            data: BridgeEventData::Unknown,
+        }
+    })
+}
+
+/// Decodes the payload of a `Program data:` line (the discriminator followed by the
+/// Borsh-encoded event) into a [`BridgeEvent`]. This is the inverse of
+/// [`BridgeEventData::to_program_data`].
+pub fn try_parse_program_data(data: &[u8]) -> Result<BridgeEvent> {
+    /// Decodes `data` as `E` if it starts with `E`'s discriminator.
+    fn try_match<E, F>(data: &[u8], map: F) -> Option<Result<BridgeEventData>>
+    where
+        E: AnchorDeserialize + Discriminator,
+        F: FnOnce(E) -> BridgeEventData,
+    {
+        let disc = E::DISCRIMINATOR;
+        let body = data.strip_prefix(disc)?;
+        Some(
+            E::try_from_slice(body).map(map).map_err(|e| {
+                anyhow::anyhow!("Malformed {} event: {}", std::any::type_name::<E>(), e)
+            }),
+        )
+    }
+
+    let event_data = try_match::<OnChainEvent::AdminProfileRegistered, _>(
+        data,
+        BridgeEventData::AdminProfileRegistered,
+    )
+    .or_else(|| {
+        try_match::<OnChainEvent::AdminConfigUpdated, _>(data, BridgeEventData::AdminConfigUpdated)
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::AdminFundsWithdrawn, _>(
+            data,
+            BridgeEventData::AdminFundsWithdrawn,
+        )
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::AdminProfileClosed, _>(data, BridgeEventData::AdminProfileClosed)
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::AdminCommandDispatched, _>(
+            data,
+            BridgeEventData::AdminCommandDispatched,
+        )
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::UserProfileCreated, _>(data, BridgeEventData::UserProfileCreated)
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::UserCommKeyUpdated, _>(data, BridgeEventData::UserCommKeyUpdated)
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::UserFundsDeposited, _>(data, BridgeEventData::UserFundsDeposited)
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::UserFundsWithdrawn, _>(data, BridgeEventData::UserFundsWithdrawn)
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::UserProfileClosed, _>(data, BridgeEventData::UserProfileClosed)
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::UserCommandDispatched, _>(
+            data,
+            BridgeEventData::UserCommandDispatched,
+        )
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::OffChainActionLogged, _>(
+            data,
+            BridgeEventData::OffChainActionLogged,
+        )
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::AdminUnbanFeeUpdated, _>(
+            data,
+            BridgeEventData::AdminUnbanFeeUpdated,
+        )
+    })
+    .or_else(|| try_match::<OnChainEvent::UserBanned, _>(data, BridgeEventData::UserBanned))
+    .or_else(|| try_match::<OnChainEvent::UserUnbanned, _>(data, BridgeEventData::UserUnbanned))
+    .or_else(|| {
+        try_match::<OnChainEvent::UserUnbanRequested, _>(data, BridgeEventData::UserUnbanRequested)
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::UserLowBalanceThresholdUpdated, _>(
+            data,
+            BridgeEventData::UserLowBalanceThresholdUpdated,
+        )
+    })
+    .or_else(|| try_match::<OnChainEvent::UserLowBalance, _>(data, BridgeEventData::UserLowBalance))
+    .or_else(|| {
+        try_match::<OnChainEvent::UserSpendingCapUpdated, _>(
+            data,
+            BridgeEventData::UserSpendingCapUpdated,
+        )
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::AllowanceGranted, _>(data, BridgeEventData::AllowanceGranted)
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::AllowanceConsumed, _>(data, BridgeEventData::AllowanceConsumed)
+    })
+    .or_else(|| try_match::<OnChainEvent::WalletCreated, _>(data, BridgeEventData::WalletCreated))
+    .or_else(|| {
+        try_match::<OnChainEvent::WalletFundsDeposited, _>(
+            data,
+            BridgeEventData::WalletFundsDeposited,
+        )
+    })
+    .or_else(|| {
+        try_match::<OnChainEvent::WalletFundsWithdrawn, _>(
+            data,
+            BridgeEventData::WalletFundsWithdrawn,
+        )
+    })
+    .or_else(|| try_match::<OnChainEvent::WalletCharged, _>(data, BridgeEventData::WalletCharged))
+    .ok_or_else(|| anyhow::anyhow!("Log carries an unknown event discriminator"))??;
+
+    Ok(BridgeEvent {
+        source: EventSource::Catchup,
+        signature: None,
+        slot: 0,
+        data: event_data,
+    })
 }
@@ -0,0 +1,89 @@
+//! # Library-Level Fan-Out
+//!
+//! Shares a single live-only upstream listener per PDA between any number of in-process
+//! consumers. The upstream's events are forwarded into a `broadcast` channel, so a slow
+//! consumer never holds back the others: it lags and recovers the events it missed through
+//! a catch-up instead, see [`BroadcastReceiver`](crate::listener::BroadcastReceiver).
+//!
+//! The upstream listener of a PDA is torn down as soon as its last receiver is dropped, and a
+//! new one is started by the next subscription.
+
+use crate::{
+    dispatcher::DispatcherHandle, error::ConnectorError, events::BridgeEvent,
+    listener::EventListener,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::broadcast;
+
+/// The `broadcast` senders of the PDAs with a running upstream listener.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FanOuts {
+    senders: Arc<Mutex<HashMap<Pubkey, broadcast::Sender<BridgeEvent>>>>,
+}
+
+impl FanOuts {
+    /// Returns a receiver for the live events of `pda`, starting its upstream listener if
+    /// no other receiver is using one. Each fan-out buffers up to `capacity` events for its
+    /// slowest receiver. Fails if the upstream listener cannot be registered.
+    pub(crate) fn subscribe(
+        &self,
+        pda: Pubkey,
+        dispatcher: &DispatcherHandle,
+        capacity: usize,
+    ) -> Result<broadcast::Receiver<BridgeEvent>, ConnectorError> {
+        let mut senders = self.senders.lock().unwrap();
+        if let Some(tx) = senders.get(&pda) {
+            return Ok(tx.subscribe());
+        }
+        let capacity = capacity.max(1);
+        let (tx, rx) = broadcast::channel(capacity);
+        let listener = EventListener::new_live_only(pda, dispatcher.clone(), capacity)?;
+        tokio::spawn(self.clone().forward(pda, listener, tx.clone()));
+        senders.insert(pda, tx);
+        tracing::debug!("Started the shared upstream listener for PDA {}", pda);
+        Ok(rx)
+    }
+
+    /// Forwards the live events of `listener` to the receivers of `pda` until the last of
+    /// them is gone or the `EventManager` shuts down.
+    async fn forward(
+        self,
+        pda: Pubkey,
+        mut listener: EventListener,
+        tx: broadcast::Sender<BridgeEvent>,
+    ) {
+        loop {
+            tokio::select! {
+                event = listener.next_live_event() => match event {
+                    // A failed send means no receiver is left, which `closed()` handles.
+                    Some(event) => {
+                        let _ = tx.send(event);
+                    }
+                    None => break,
+                },
+                _ = tx.closed() => {
+                    // A receiver may have subscribed since the last one dropped, so check
+                    // again under the lock before giving up the upstream listener.
+                    let mut senders = self.senders.lock().unwrap();
+                    if tx.receiver_count() == 0 {
+                        senders.remove(&pda);
+                        tracing::debug!("Stopped the shared upstream listener for PDA {}", pda);
+                        return;
+                    }
+                }
+            }
+        }
+        tracing::info!("Shared upstream listener for PDA {} has ended.", pda);
+        let mut senders = self.senders.lock().unwrap();
+        if senders
+            .get(&pda)
+            .is_some_and(|current| current.same_channel(&tx))
+        {
+            senders.remove(&pda);
+        }
+    }
+}
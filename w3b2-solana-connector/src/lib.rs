@@ -12,18 +12,52 @@
 //! *   [`listener`]: High-level event listeners (`UserListener`, `AdminListener`) that
 //!     subscribe to a specific on-chain PDA and provide separate streams for historical
 //!     (`catchup`) and real-time (`live`) events.
+/// Splitting payloads larger than a `log_action` record across several records.
+pub mod chunking;
 pub mod client;
 /// Defines configuration structures for the connector.
 pub mod config;
 /// The internal event routing worker (`Dispatcher`).
 mod dispatcher;
+/// The error type for invalid inputs detected by the connector.
+pub mod error;
+
+/// Sharing one upstream listener per PDA between several in-process consumers.
+mod fanout;
 
 /// Logic for parsing on-chain events from transaction logs.
 pub mod events;
 /// High-level, PDA-based event listeners (`UserListener`, `AdminListener`) with
 /// separate streams for historical and real-time events.
 pub mod listener;
+/// Building and signing the price messages that authorize paid user commands.
+pub mod oracle;
+/// Derivation of `AdminProfile` and `UserProfile` PDA addresses.
+pub mod pda;
+/// Folds that rebuild profile views from their event history.
+pub mod replay;
+/// Counting the RPC calls of the workers and the transaction builder.
+#[cfg(feature = "metrics")]
+pub mod rpc_stats;
+/// Decoding the results of simulated, unsigned transactions.
+pub mod simulation;
 /// A trait and default implementation for persistent synchronization state.
 pub mod storage;
 /// The background workers responsible for blockchain synchronization.
 pub mod workers;
+
+/// The id of the on-chain program this connector is built against.
+pub use w3b2_solana_program::ID as PROGRAM_ID;
+
+/// The limits enforced by the on-chain program, for validating input before it is sent.
+pub use w3b2_solana_program::instructions::{
+    MAX_ALLOWANCE_COMMANDS, MAX_METADATA_SIZE, MAX_PAYLOAD_SIZE, MAX_TIMESTAMP_AGE_SECONDS,
+    MAX_TIMESTAMP_FUTURE_SKEW_SECONDS, MAX_TIMESTAMP_VALIDITY_SECONDS,
+    MIN_TIMESTAMP_VALIDITY_SECONDS,
+};
+
+/// The custom errors of the on-chain program. [`ALL_ERRORS`] lists them in declaration order.
+pub use w3b2_solana_program::errors::{BridgeError, ALL_ERRORS};
+
+/// The version of this crate.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -9,14 +9,125 @@
 //!
 //! 1.  **Catch-up Stream**: Delivers all historical events for the PDA, from the beginning of
 //!     its history up to the point where the listener was created. This ensures that the
-//!     application has a complete and consistent view of the PDA's state.
+//!     application has a complete and consistent view of the PDA's state. The stream ends
+//!     with a single [`CaughtUp`](crate::events::CaughtUp) marker, after which it is closed.
 //!
 //! 2.  **Live Stream**: Delivers all new events that occur in real-time while the listener is active.
+//!
+//! While the catch-up stream is being replayed, [`EventListener::catchup_progress`] reports how
+//! far through the PDA's history it has come.
+//!
+//! A listener created with [`EventListener::new_with_history`] can instead receive its
+//! history newest-first, or only the newest part of it; see [`HistoryOptions`].
+//!
+//! Consumers that only need new events can create a live-only listener with
+//! [`EventListener::new_live_only`]. Its history is never replayed, so it costs no RPC calls,
+//! and its catch-up stream is closed from the start.
+//!
+//! [`EventListener::wait_for`] waits, up to a timeout, for the first live event that
+//! matches a predicate, e.g. the event of a command that was just dispatched.
+//!
+//! When a listener falls behind and its live channel fills up, its [`OverflowPolicy`]
+//! decides whether new events wait for room, or an event is dropped; see
+//! [`EventListener::dropped_events`].
+//!
+//! Consumers that handle each [`EventCategory`] separately can use a [`CategorizedListener`]
+//! instead, which receives the live events of every category on a stream of its own.
+//!
+//! Several consumers of the same PDA can share a single listener through a
+//! [`BroadcastReceiver`] each, which recovers the events it missed by falling behind
+//! through a catch-up of the PDA.
+//!
+//! Creating a listener fails with [`ConnectorError::TooManyListeners`] while the dispatcher
+//! already serves `channels.max_listeners` of them.
 
-use crate::dispatcher::{DispatcherCommand, DispatcherHandle, ListenerChannels};
+use crate::dispatcher::{DispatcherHandle, ListenerChannels, LiveChannels};
+use crate::error::ConnectorError;
 pub use crate::events::BridgeEvent;
+use crate::events::{BridgeEventData, EventCategory};
 use solana_sdk::pubkey::Pubkey;
-use tokio::sync::mpsc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc, watch,
+};
+use tokio_stream::wrappers::ReceiverStream;
+
+/// The id handed to the next listener, distinguishing listeners of the same PDA.
+static NEXT_LISTENER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// How far the replay of a listener's catch-up stream has come, in slots.
+///
+/// All fields are `0` until the `CatchupWorker` has started on the listener's PDA.
+/// `from_slot` is set once the oldest slot of the history is known, and `current_slot`
+/// then advances towards `target_slot`, the latest slot when the replay started. Once the
+/// history has been delivered, `current_slot` equals `target_slot`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CatchupProgress {
+    /// The slot of the oldest event being replayed.
+    pub from_slot: u64,
+    /// The slot of the last transaction whose events were delivered.
+    pub current_slot: u64,
+    /// The slot the replay catches up to.
+    pub target_slot: u64,
+}
+
+impl CatchupProgress {
+    /// Returns `true` once the whole history has been delivered.
+    pub fn is_complete(&self) -> bool {
+        self.target_slot > 0 && self.current_slot >= self.target_slot
+    }
+
+    /// Returns the share of the slot range already replayed, from `0.0` to `100.0`.
+    pub fn percent(&self) -> f64 {
+        if self.is_complete() {
+            return 100.0;
+        }
+        let total = self.target_slot.saturating_sub(self.from_slot);
+        if total == 0 {
+            return 0.0;
+        }
+        self.current_slot.saturating_sub(self.from_slot) as f64 * 100.0 / total as f64
+    }
+}
+
+/// Controls how the catch-up stream of a listener replays the PDA's history.
+///
+/// The default replays the whole history oldest-first, like [`EventListener::new`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryOptions {
+    /// Delivers the events newest-first instead of oldest-first. The stream still ends
+    /// with the [`CaughtUp`](crate::events::CaughtUp) marker.
+    pub newest_first: bool,
+    /// Only replays the events of the newest `limit` transactions. The configured
+    /// `catchup_max_transactions` still applies if it is smaller.
+    pub limit: Option<usize>,
+    /// Only replays the events of transactions after this slot, such as the last slot a
+    /// reconnecting consumer has already processed. Older transactions are not fetched.
+    pub since_slot: Option<u64>,
+}
+
+/// What the `Dispatcher` does with a live event for a listener whose live channel is full.
+///
+/// Every dropped event is counted in [`EventListener::dropped_events`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Discards the oldest buffered event to make room, so the listener always receives the
+    /// most recent events. Suits consumers that prefer freshness, such as analytics.
+    DropOldest,
+    /// Discards the new event and keeps the buffered ones.
+    DropNewest,
+    /// Waits until the listener has made room, logging a warning. No event is lost, but the
+    /// delivery to every other listener stalls meanwhile, so the listener must keep reading.
+    /// Suits consumers that must see every event, such as billing.
+    #[default]
+    Block,
+}
 
 /// A type alias for an [`EventListener`] configured to listen to a `UserProfile` PDA.
 pub type UserListener = EventListener;
@@ -29,6 +140,9 @@ pub type AdminListener = EventListener;
 /// channels to receive `BridgeEvent`s: one for historical "catch-up" events and one for
 /// "live" events from the WebSocket stream.
 ///
+/// Any number of listeners may watch the same PDA, and each receives every event. What
+/// happens while a listener's live channel is full depends on its [`OverflowPolicy`].
+///
 /// # Resource Management
 ///
 /// The `EventListener` automatically handles unsubscription from the `Dispatcher`
@@ -36,54 +150,136 @@ pub type AdminListener = EventListener;
 #[derive(Debug)]
 pub struct EventListener {
     /// A channel receiver for live events pushed from the WebSocket stream.
-    live_rx: mpsc::Receiver<BridgeEvent>,
+    live_rx: LiveReceiver,
+    /// The number of live events dropped under the listener's [`OverflowPolicy`].
+    dropped: Arc<AtomicU64>,
     /// A channel receiver for historical events queried by the `CatchupWorker`.
     catchup_rx: mpsc::Receiver<BridgeEvent>,
-    /// Contains the PDA and dispatcher handle needed for unsubscribing on `Drop`.
+    /// The progress of the catch-up replay, as reported by the `CatchupWorker`.
+    progress_rx: watch::Receiver<CatchupProgress>,
+    /// Contains the PDA, listener id and dispatcher handle needed for unsubscribing on `Drop`.
     /// This is an `Option` to allow for a clean "take" pattern, preventing double-unsubscription.
-    unsubscribe_info: Option<(Pubkey, DispatcherHandle)>,
+    unsubscribe_info: Option<(Pubkey, u64, DispatcherHandle)>,
 }
 
 impl EventListener {
     /// Creates a new `EventListener` and registers it with the `Dispatcher`.
     ///
-    /// This function spawns a Tokio task to send a `Register` command to the central
-    /// `Dispatcher`, which will then begin routing events for the specified PDA to
-    /// the channels provided by this listener.
+    /// This function queues a `Register` command for the central `Dispatcher`, which will
+    /// then begin routing events for the specified PDA to the channels provided by this
+    /// listener. The `Unregister` sent when the listener is dropped is queued behind it.
     ///
     /// # Arguments
     ///
     /// * `pda_to_listen_on` - The `Pubkey` of the `AdminProfile` or `UserProfile` PDA to monitor.
     /// * `dispatcher` - A handle to the central `Dispatcher` that manages all event subscriptions.
     /// * `channel_capacity` - The buffer capacity of the MPSC channels for live and catch-up events.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ConnectorError::TooManyListeners`] if the dispatcher already serves
+    /// `channels.max_listeners` listeners.
     pub fn new(
         pda_to_listen_on: Pubkey,
         dispatcher: DispatcherHandle,
         channel_capacity: usize,
-    ) -> Self {
-        let (live_tx, live_rx) = mpsc::channel(channel_capacity);
+    ) -> Result<Self, ConnectorError> {
+        Self::register(
+            pda_to_listen_on,
+            dispatcher,
+            channel_capacity,
+            Some(HistoryOptions::default()),
+            OverflowPolicy::default(),
+        )
+    }
+
+    /// Creates a new `EventListener` whose history is replayed according to `history`.
+    ///
+    /// While a newest-first history is replayed, [`catchup_progress`](Self::catchup_progress)
+    /// only reports the target slot; it completes once the replay has finished.
+    pub fn new_with_history(
+        pda_to_listen_on: Pubkey,
+        dispatcher: DispatcherHandle,
+        channel_capacity: usize,
+        history: HistoryOptions,
+    ) -> Result<Self, ConnectorError> {
+        Self::register(
+            pda_to_listen_on,
+            dispatcher,
+            channel_capacity,
+            Some(history),
+            OverflowPolicy::default(),
+        )
+    }
+
+    /// Creates a new `EventListener` that only receives live events.
+    ///
+    /// Unlike [`new`](Self::new), no catch-up is requested for the PDA, so the
+    /// `CatchupWorker` never fetches its history. [`next_catchup_event`](Self::next_catchup_event)
+    /// returns `None` right away and [`catchup_progress`](Self::catchup_progress) stays at its
+    /// default.
+    pub fn new_live_only(
+        pda_to_listen_on: Pubkey,
+        dispatcher: DispatcherHandle,
+        channel_capacity: usize,
+    ) -> Result<Self, ConnectorError> {
+        Self::register(
+            pda_to_listen_on,
+            dispatcher,
+            channel_capacity,
+            None,
+            OverflowPolicy::default(),
+        )
+    }
+
+    /// Registers a listener whose history is replayed according to `history`, or not at all
+    /// if it is `None`, and whose live channel overflows according to `overflow`.
+    pub(crate) fn register(
+        pda_to_listen_on: Pubkey,
+        dispatcher: DispatcherHandle,
+        channel_capacity: usize,
+        history: Option<HistoryOptions>,
+        overflow: OverflowPolicy,
+    ) -> Result<Self, ConnectorError> {
+        let (live, live_rx) = match overflow {
+            OverflowPolicy::DropOldest => {
+                let (tx, rx) = broadcast::channel(channel_capacity);
+                // The ring buffer's capacity is rounded up to a power of two.
+                let capacity = channel_capacity.next_power_of_two();
+                (LiveChannels::Ring { tx, capacity }, LiveReceiver::Ring(rx))
+            }
+            OverflowPolicy::DropNewest | OverflowPolicy::Block => {
+                let (tx, rx) = mpsc::channel(channel_capacity);
+                (LiveChannels::Single(tx), LiveReceiver::Queue(rx))
+            }
+        };
+        let dropped = Arc::new(AtomicU64::new(0));
         let (catchup_tx, catchup_rx) = mpsc::channel(channel_capacity);
+        // Dropping the sender of a live-only listener closes its catch-up stream.
+        let catchup_tx = history.is_some().then_some(catchup_tx);
+        let (progress_tx, progress_rx) = watch::channel(CatchupProgress::default());
+        let id = NEXT_LISTENER_ID.fetch_add(1, Ordering::Relaxed);
 
-        // Asynchronously send the registration command to the dispatcher
-        let dispatcher_clone = dispatcher.clone();
-        tokio::spawn(async move {
-            let _ = dispatcher_clone
-                .command_tx
-                .send(DispatcherCommand::Register(
-                    pda_to_listen_on,
-                    ListenerChannels {
-                        live: live_tx,
-                        catchup: catchup_tx,
-                    },
-                ))
-                .await;
-        });
+        dispatcher.register(
+            pda_to_listen_on,
+            ListenerChannels {
+                id,
+                live,
+                catchup: catchup_tx,
+                history: history.unwrap_or_default(),
+                progress: progress_tx,
+                overflow,
+                dropped: dropped.clone(),
+            },
+        )?;
 
-        Self {
+        Ok(Self {
             live_rx,
+            dropped,
             catchup_rx,
-            unsubscribe_info: Some((pda_to_listen_on, dispatcher)),
-        }
+            progress_rx,
+            unsubscribe_info: Some((pda_to_listen_on, id, dispatcher)),
+        })
     }
 
     /// Receives the next live event from the WebSocket stream.
@@ -91,16 +287,63 @@ impl EventListener {
     /// Returns `None` if the channel is closed, which typically happens when the
     /// `EventManager` is shut down.
     pub async fn next_live_event(&mut self) -> Option<BridgeEvent> {
-        self.live_rx.recv().await
+        match &mut self.live_rx {
+            LiveReceiver::Queue(rx) => rx.recv().await,
+            LiveReceiver::Ring(rx) => loop {
+                match rx.recv().await {
+                    Ok(event) => return Some(event),
+                    // The dispatcher has already counted the overwritten events.
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            },
+        }
+    }
+
+    /// Consumes live events until one matches `pred`, and returns it.
+    ///
+    /// Suits request/response flows that dispatch a command and then wait for its event.
+    /// The live events that do not match are discarded. Returns `None` if no event matched
+    /// within `timeout`, or if the live channel closed first.
+    pub async fn wait_for<F>(&mut self, pred: F, timeout: Duration) -> Option<BridgeEvent>
+    where
+        F: Fn(&BridgeEvent) -> bool,
+    {
+        let wait = async {
+            while let Some(event) = self.next_live_event().await {
+                if pred(&event) {
+                    return Some(event);
+                }
+            }
+            None
+        };
+        tokio::time::timeout(timeout, wait).await.ok().flatten()
+    }
+
+    /// Returns the number of live events this listener has missed because its channel was
+    /// full. Always `0` under [`OverflowPolicy::Block`].
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
     }
 
     /// Receives the next historical event from the catch-up worker.
     ///
-    /// Returns `None` once all historical events have been delivered.
+    /// The last event of a complete history is a
+    /// [`BridgeEventData::CaughtUp`](crate::events::BridgeEventData::CaughtUp) marker.
+    /// Returns `None` once all historical events have been delivered. If the channel
+    /// closes without the marker, the history could not be fetched in full.
     pub async fn next_catchup_event(&mut self) -> Option<BridgeEvent> {
         self.catchup_rx.recv().await
     }
 
+    /// Returns how far the replay of the catch-up stream has come.
+    ///
+    /// The progress tracks what the `CatchupWorker` has sent, which may be ahead of what
+    /// has been received through [`next_catchup_event`](Self::next_catchup_event).
+    pub fn catchup_progress(&self) -> CatchupProgress {
+        *self.progress_rx.borrow()
+    }
+
     /// Manually unsubscribes the listener from the event dispatcher.
     ///
     /// This method consumes the listener, preventing further use. After this is called, the
@@ -108,32 +351,310 @@ impl EventListener {
     /// not attempt to unsubscribe a second time. This is useful for cases where explicit
     /// cleanup is preferred over relying on the `Drop` trait.
     pub async fn unsubscribe(mut self) {
-        if let Some((pda, dispatcher)) = self.unsubscribe_info.take() {
+        if let Some((pda, id, dispatcher)) = self.unsubscribe_info.take() {
             tracing::debug!("Manual unsubscribe for EventListener on PDA {}", pda);
-            let _ = dispatcher
-                .command_tx
-                .send(DispatcherCommand::Unregister(pda))
-                .await;
+            dispatcher.unregister(pda, id);
         }
     }
 }
 
+/// The receiving end of a listener's live channel.
+#[derive(Debug)]
+enum LiveReceiver {
+    Queue(mpsc::Receiver<BridgeEvent>),
+    /// The ring buffer of an [`OverflowPolicy::DropOldest`] listener.
+    Ring(broadcast::Receiver<BridgeEvent>),
+}
+
 impl Drop for EventListener {
     /// Automatically unsubscribes the listener from the `Dispatcher` when it goes out of scope.
     fn drop(&mut self) {
         // Only perform automatic unsubscription if it hasn't been done manually via `unsubscribe()`.
-        if let Some((pda, dispatcher)) = self.unsubscribe_info.take() {
+        if let Some((pda, id, dispatcher)) = self.unsubscribe_info.take() {
             tracing::debug!(
                 "Automatic unsubscribe (on drop) for EventListener on PDA {}",
                 pda
             );
-            // Spawn a new task to send the unregister command without blocking the current thread.
-            tokio::spawn(async move {
-                let _ = dispatcher
-                    .command_tx
-                    .send(DispatcherCommand::Unregister(pda))
-                    .await;
-            });
+            dispatcher.unregister(pda, id);
+        }
+    }
+}
+
+/// A live-only listener that receives the events of every [`EventCategory`] on a channel of
+/// its own.
+///
+/// The `Dispatcher` sorts the events into the channels, so a burst of events of one category
+/// never fills the buffer of another. Events keep their order within a category, but not
+/// across categories. Dropping the stream of one category stops its delivery without
+/// affecting the others. All streams end once the listener is dropped or unsubscribed, so
+/// it must be kept alive while they are consumed.
+///
+/// A full channel misses new events, as under [`OverflowPolicy::DropNewest`], since the
+/// channels of streams that were never taken are never drained.
+#[derive(Debug)]
+pub struct CategorizedListener {
+    /// The receivers of the categories whose stream has not been taken yet.
+    receivers: HashMap<EventCategory, mpsc::Receiver<BridgeEvent>>,
+    /// The PDA, listener id and dispatcher handle needed for unsubscribing on `Drop`.
+    unsubscribe_info: Option<(Pubkey, u64, DispatcherHandle)>,
+}
+
+impl CategorizedListener {
+    /// Creates a new `CategorizedListener` and registers it with the `Dispatcher`.
+    ///
+    /// Each category gets a channel of `channel_capacity` events. No history is replayed
+    /// for the PDA. Fails like [`EventListener::new`].
+    pub fn new(
+        pda_to_listen_on: Pubkey,
+        dispatcher: DispatcherHandle,
+        channel_capacity: usize,
+    ) -> Result<Self, ConnectorError> {
+        let mut senders = HashMap::new();
+        let mut receivers = HashMap::new();
+        for category in EventCategory::ALL {
+            let (tx, rx) = mpsc::channel(channel_capacity);
+            senders.insert(category, tx);
+            receivers.insert(category, rx);
+        }
+        let id = NEXT_LISTENER_ID.fetch_add(1, Ordering::Relaxed);
+        // Nothing is replayed, so nobody reports progress.
+        let (progress_tx, _) = watch::channel(CatchupProgress::default());
+
+        dispatcher.register(
+            pda_to_listen_on,
+            ListenerChannels {
+                id,
+                live: LiveChannels::ByCategory(senders),
+                catchup: None,
+                history: HistoryOptions::default(),
+                progress: progress_tx,
+                overflow: OverflowPolicy::DropNewest,
+                dropped: Arc::default(),
+            },
+        )?;
+
+        Ok(Self {
+            receivers,
+            unsubscribe_info: Some((pda_to_listen_on, id, dispatcher)),
+        })
+    }
+
+    /// Takes the stream of live events of `category`.
+    ///
+    /// Each stream can be taken once. Later calls for the same category return a stream
+    /// that ends right away.
+    pub fn events(&mut self, category: EventCategory) -> ReceiverStream<BridgeEvent> {
+        let rx = self
+            .receivers
+            .remove(&category)
+            .unwrap_or_else(|| mpsc::channel(1).1);
+        ReceiverStream::new(rx)
+    }
+
+    /// Takes the stream of [`EventCategory::Financial`] events: deposits, withdrawals and
+    /// paid user commands.
+    pub fn financial_events(&mut self) -> ReceiverStream<BridgeEvent> {
+        self.events(EventCategory::Financial)
+    }
+
+    /// Takes the stream of [`EventCategory::Administrative`] events: profile and
+    /// configuration changes, bans and unbans.
+    pub fn admin_events(&mut self) -> ReceiverStream<BridgeEvent> {
+        self.events(EventCategory::Administrative)
+    }
+
+    /// Takes the stream of [`EventCategory::Messaging`] events.
+    pub fn messaging_events(&mut self) -> ReceiverStream<BridgeEvent> {
+        self.events(EventCategory::Messaging)
+    }
+
+    /// Takes the stream of [`EventCategory::Connector`] events.
+    pub fn connector_events(&mut self) -> ReceiverStream<BridgeEvent> {
+        self.events(EventCategory::Connector)
+    }
+
+    /// Manually unsubscribes the listener from the event dispatcher, ending all of its
+    /// streams. See [`EventListener::unsubscribe`].
+    pub async fn unsubscribe(mut self) {
+        if let Some((pda, id, dispatcher)) = self.unsubscribe_info.take() {
+            tracing::debug!("Manual unsubscribe for CategorizedListener on PDA {}", pda);
+            dispatcher.unregister(pda, id);
+        }
+    }
+}
+
+impl Drop for CategorizedListener {
+    /// Automatically unsubscribes the listener from the `Dispatcher` when it goes out of scope.
+    fn drop(&mut self) {
+        if let Some((pda, id, dispatcher)) = self.unsubscribe_info.take() {
+            tracing::debug!(
+                "Automatic unsubscribe (on drop) for CategorizedListener on PDA {}",
+                pda
+            );
+            dispatcher.unregister(pda, id);
+        }
+    }
+}
+
+/// A receiver of
+/// [`EventManagerHandle::broadcast_subscribe`](crate::workers::EventManagerHandle::broadcast_subscribe),
+/// which shares a single upstream listener with every other receiver of the same PDA.
+///
+/// A receiver that falls more than `channels.broadcast_capacity` events behind the others
+/// lags. Instead of skipping the events it missed, [`recv`](Self::recv) logs how many were
+/// dropped and recovers them through a catch-up of the PDA from the slot of the last event
+/// it returned, then continues with the live events. The recovered events carry
+/// [`EventSource::Catchup`](crate::events::EventSource::Catchup), and none of them is
+/// returned a second time when it also arrives live.
+#[derive(Debug)]
+pub struct BroadcastReceiver {
+    pda: Pubkey,
+    rx: broadcast::Receiver<BridgeEvent>,
+    dispatcher: DispatcherHandle,
+    /// The capacity of the catch-up channel of a recovery.
+    catchup_capacity: usize,
+    /// The recovered events that were not returned yet.
+    recovered: VecDeque<BridgeEvent>,
+    /// How many recovered events of each transaction may still arrive live, up to the
+    /// `recovered_until` slot.
+    duplicates: HashMap<String, usize>,
+    recovered_until: u64,
+    /// The slot of the last returned event, and how many events of each of its
+    /// transactions were returned.
+    last_slot: Option<u64>,
+    returned: HashMap<String, usize>,
+    lagged: u64,
+}
+
+impl BroadcastReceiver {
+    pub(crate) fn new(
+        pda: Pubkey,
+        rx: broadcast::Receiver<BridgeEvent>,
+        dispatcher: DispatcherHandle,
+        catchup_capacity: usize,
+    ) -> Self {
+        Self {
+            pda,
+            rx,
+            dispatcher,
+            catchup_capacity,
+            recovered: VecDeque::new(),
+            duplicates: HashMap::new(),
+            recovered_until: 0,
+            last_slot: None,
+            returned: HashMap::new(),
+            lagged: 0,
+        }
+    }
+
+    /// Receives the next event, recovering the events missed by lagging first.
+    ///
+    /// Returns `None` once the shared upstream listener has ended.
+    pub async fn recv(&mut self) -> Option<BridgeEvent> {
+        loop {
+            if let Some(event) = self.recovered.pop_front() {
+                self.record_returned(&event);
+                return Some(event);
+            }
+            match self.rx.recv().await {
+                Ok(event) => {
+                    if self.was_recovered(&event) {
+                        continue;
+                    }
+                    self.record_returned(&event);
+                    return Some(event);
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    self.lagged += skipped;
+                    self.recover(skipped).await;
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Returns the number of events this receiver has missed by lagging, whether or not
+    /// they were recovered.
+    pub fn lagged_events(&self) -> u64 {
+        self.lagged
+    }
+
+    /// Replays the history of the PDA from the slot of the last returned event into
+    /// `recovered`, leaving out the events that were already returned.
+    async fn recover(&mut self, skipped: u64) {
+        let Some(last_slot) = self.last_slot else {
+            tracing::warn!(
+                "Broadcast receiver for PDA {} lagged before its first event; skipped {} events.",
+                self.pda,
+                skipped
+            );
+            return;
+        };
+        tracing::warn!(
+            "Broadcast receiver for PDA {} is not keeping up; dropped {} events. Recovering them from slot {}.",
+            self.pda,
+            skipped,
+            last_slot
+        );
+        // Other events of the last returned slot may have been missed as well.
+        let history = HistoryOptions {
+            since_slot: Some(last_slot.saturating_sub(1)),
+            ..Default::default()
+        };
+        let mut events = self
+            .dispatcher
+            .request_catchup(self.pda, history, self.catchup_capacity)
+            .await;
+        let mut returned = self.returned.clone();
+        let mut complete = false;
+        while let Some(event) = events.recv().await {
+            if matches!(event.data, BridgeEventData::CaughtUp(_)) {
+                complete = true;
+                continue;
+            }
+            if let Some(signature) = &event.signature {
+                if let Some(count) = returned.get_mut(signature).filter(|count| **count > 0) {
+                    *count -= 1;
+                    continue;
+                }
+                *self.duplicates.entry(signature.clone()).or_default() += 1;
+            }
+            self.recovered_until = self.recovered_until.max(event.slot);
+            self.recovered.push_back(event);
+        }
+        if !complete {
+            tracing::warn!(
+                "Catch-up of PDA {} ended early; some of the dropped events were not recovered.",
+                self.pda
+            );
+        }
+    }
+
+    /// Returns `true` if a live `event` was already returned by a recovery.
+    fn was_recovered(&mut self, event: &BridgeEvent) -> bool {
+        if event.slot > self.recovered_until {
+            self.duplicates.clear();
+            return false;
+        }
+        let Some(count) = event
+            .signature
+            .as_ref()
+            .and_then(|signature| self.duplicates.get_mut(signature))
+            .filter(|count| **count > 0)
+        else {
+            return false;
+        };
+        *count -= 1;
+        true
+    }
+
+    fn record_returned(&mut self, event: &BridgeEvent) {
+        if self.last_slot != Some(event.slot) {
+            self.last_slot = Some(event.slot);
+            self.returned.clear();
+        }
+        if let Some(signature) = &event.signature {
+            *self.returned.entry(signature.clone()).or_default() += 1;
         }
     }
 }
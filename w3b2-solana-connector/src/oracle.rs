@@ -0,0 +1,52 @@
+//! Building and signing oracle price messages.
+//!
+//! A paid `user_dispatch_command` must be preceded by an `Ed25519` instruction proving that
+//! the admin's oracle signed the command's `command_id`, `price` and `timestamp` for the
+//! user's profile. These helpers produce exactly the message the program reconstructs
+//! on-chain, so oracle backends and tests do not have to reproduce its byte layout.
+//!
+//! The layout is version [`ORACLE_PRICE_MESSAGE_VERSION`] of the program's price message.
+//! The program accepts no other version, so these helpers only build the current one.
+
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use w3b2_solana_program::instructions::oracle_price_message;
+
+pub use w3b2_solana_program::instructions::ORACLE_PRICE_MESSAGE_VERSION;
+
+/// Builds the message an oracle signs to authorize a paid command of the user whose
+/// `UserProfile` PDA is `user_profile_pda`: `version (1 byte) | command_id (2 bytes) |
+/// price (8 bytes) | timestamp (8 bytes) | user_profile_pda (32 bytes)`, with integers in
+/// little-endian.
+pub fn build_price_message(
+    command_id: u16,
+    price: u64,
+    timestamp: i64,
+    user_profile_pda: Pubkey,
+) -> Vec<u8> {
+    oracle_price_message(command_id, price, timestamp, user_profile_pda)
+}
+
+/// Signs the price message of a command with the oracle's keypair, returning the 64-byte
+/// `Ed25519` signature expected in
+/// [`UserDispatchCommandArgs::oracle_signature`](crate::client::UserDispatchCommandArgs).
+///
+/// The signature is only valid for the user whose `UserProfile` PDA is `user_profile_pda`.
+pub fn sign_price_message(
+    oracle: &Keypair,
+    command_id: u16,
+    price: u64,
+    timestamp: i64,
+    user_profile_pda: Pubkey,
+) -> [u8; 64] {
+    oracle
+        .sign_message(&build_price_message(
+            command_id,
+            price,
+            timestamp,
+            user_profile_pda,
+        ))
+        .into()
+}
@@ -0,0 +1,90 @@
+//! Derivation of the program's PDA addresses.
+//!
+//! These helpers mirror the `seeds` constraints of `w3b2-solana-program` and are the single
+//! place where off-chain code should compute profile addresses.
+//!
+//! Every helper derives the address under the program id the connector is built against.
+//! Its `_for` variant takes the id of another deployment, such as the `program_id` of a
+//! [`Solana`](crate::config::Solana) config.
+//!
+//! The returned bump is the canonical one, the highest bump that yields an off-curve address,
+//! which is the bump Anchor's `bump` constraint accepts. Clients that already know it can
+//! rebuild the address with `Pubkey::create_program_address`, which hashes once instead of
+//! searching.
+
+use solana_sdk::pubkey::Pubkey;
+use w3b2_solana_program::state::admin_index_seed;
+
+/// The seed prefix of `AdminProfile` PDAs.
+pub const ADMIN_SEED: &[u8] = b"admin";
+/// The seed prefix of `UserProfile` PDAs.
+pub const USER_SEED: &[u8] = b"user";
+/// The seed prefix of `Allowance` PDAs.
+pub const ALLOWANCE_SEED: &[u8] = b"allowance";
+/// The seed prefix of `UserWallet` PDAs.
+pub const WALLET_SEED: &[u8] = b"wallet";
+
+/// Derives the `AdminProfile` PDA with the given `index` owned by `authority`, returning the
+/// address and its bump.
+///
+/// Seeds: `[b"admin", authority, admin_index_seed(index)]`. Index `0` resolves to the same
+/// address as the original `[b"admin", authority]` derivation.
+pub fn find_admin_profile_pda(authority: &Pubkey, index: u16) -> (Pubkey, u8) {
+    find_admin_profile_pda_for(&w3b2_solana_program::ID, authority, index)
+}
+
+/// Like [`find_admin_profile_pda`], for the deployment of the program at `program_id`.
+pub fn find_admin_profile_pda_for(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    index: u16,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ADMIN_SEED, authority.as_ref(), &admin_index_seed(index)],
+        program_id,
+    )
+}
+
+/// Derives the `UserProfile` PDA linking `authority` to `admin_profile_pda`, returning the
+/// address and its bump.
+///
+/// Seeds: `[b"user", authority, admin_profile_pda]`.
+pub fn find_user_profile_pda(authority: &Pubkey, admin_profile_pda: &Pubkey) -> (Pubkey, u8) {
+    find_user_profile_pda_for(&w3b2_solana_program::ID, authority, admin_profile_pda)
+}
+
+/// Like [`find_user_profile_pda`], for the deployment of the program at `program_id`.
+pub fn find_user_profile_pda_for(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    admin_profile_pda: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[USER_SEED, authority.as_ref(), admin_profile_pda.as_ref()],
+        program_id,
+    )
+}
+
+/// Derives the `Allowance` PDA of `user_profile_pda`, returning the address and its bump.
+///
+/// Seeds: `[b"allowance", user_profile_pda]`.
+pub fn find_allowance_pda(user_profile_pda: &Pubkey) -> (Pubkey, u8) {
+    find_allowance_pda_for(&w3b2_solana_program::ID, user_profile_pda)
+}
+
+/// Like [`find_allowance_pda`], for the deployment of the program at `program_id`.
+pub fn find_allowance_pda_for(program_id: &Pubkey, user_profile_pda: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ALLOWANCE_SEED, user_profile_pda.as_ref()], program_id)
+}
+
+/// Derives the shared `UserWallet` PDA of `authority`, returning the address and its bump.
+///
+/// Seeds: `[b"wallet", authority]`.
+pub fn find_user_wallet_pda(authority: &Pubkey) -> (Pubkey, u8) {
+    find_user_wallet_pda_for(&w3b2_solana_program::ID, authority)
+}
+
+/// Like [`find_user_wallet_pda`], for the deployment of the program at `program_id`.
+pub fn find_user_wallet_pda_for(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[WALLET_SEED, authority.as_ref()], program_id)
+}
@@ -0,0 +1,234 @@
+//! Deterministic folds that rebuild a profile's state from its event history.
+//!
+//! A service that stores the events of its listeners can rebuild a profile's balance and
+//! status without reading the account, e.g. to audit it or to recover a lost database. The
+//! folds mirror the program's state transitions field by field, so folding the complete
+//! history of a PDA reproduces the account as it is on chain.
+//!
+//! The input is the history of one PDA, oldest first, as its listener delivers it. A fold
+//! adopts the PDA of the first creation event it sees and ignores every event that concerns
+//! another profile, as well as connector markers such as `CaughtUp` and `Retracted`.
+
+use crate::events::{BridgeEvent, BridgeEventData};
+use solana_sdk::pubkey::Pubkey;
+
+/// The state of a `UserProfile` rebuilt from its events by [`fold_user_profile`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserProfileView {
+    /// The `UserProfile` PDA, taken from its `UserProfileCreated` event.
+    pub user_pda: Pubkey,
+    pub authority: Pubkey,
+    /// The `AdminProfile` PDA the profile was created for.
+    pub admin_pda: Pubkey,
+    pub communication_pubkey: Pubkey,
+    pub comm_key_version: u32,
+    pub deposit_balance: u64,
+    pub banned: bool,
+    pub unban_requested: bool,
+    pub ban_count: u32,
+    pub low_balance_threshold: u64,
+    pub low_balance_notified: bool,
+    pub max_price_per_command: u64,
+    /// Set by `UserProfileClosed`. A later `UserProfileCreated` starts a fresh view.
+    pub closed: bool,
+    /// The number of deposits into the profile.
+    pub deposits: u64,
+    /// The number of withdrawals from the profile.
+    pub withdrawals: u64,
+    /// The number of commands the user dispatched to the service, paid or not.
+    pub commands_dispatched: u64,
+    /// The number of commands the service dispatched to the user.
+    pub commands_received: u64,
+    /// The number of unban requests the user paid for.
+    pub unban_requests: u64,
+}
+
+/// The state of an `AdminProfile` rebuilt from its events by [`fold_admin_profile`].
+///
+/// `unban_fee_multiplier_bps` and `max_unban_fee_multiple` are not part of the view, because
+/// `AdminConfigUpdated` does not carry them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AdminProfileView {
+    /// The `AdminProfile` PDA, taken from its `AdminProfileRegistered` event.
+    pub admin_pda: Pubkey,
+    pub authority: Pubkey,
+    pub index: u16,
+    pub communication_pubkey: Pubkey,
+    pub comm_key_version: u32,
+    pub oracle_authority: Pubkey,
+    pub timestamp_validity_seconds: i64,
+    pub max_payload_size: u16,
+    pub require_distinct_oracle: bool,
+    pub unban_fee: u64,
+    pub balance: u64,
+    /// Set by `AdminProfileClosed`. A later `AdminProfileRegistered` starts a fresh view.
+    pub closed: bool,
+    /// The number of commands users dispatched to the service, paid or not.
+    pub commands_received: u64,
+    /// The number of commands the service dispatched to users.
+    pub commands_dispatched: u64,
+    /// The number of times the service banned a user.
+    pub bans: u64,
+    /// The number of times the service lifted a ban.
+    pub unbans: u64,
+    /// The number of withdrawals from the service's balance.
+    pub withdrawals: u64,
+}
+
+impl UserProfileView {
+    /// Whether a creation of `pda` (re)starts this view: always before the first creation,
+    /// and afterwards only for the same PDA, e.g. a profile recreated after it was closed.
+    fn adopts(&self, pda: Pubkey) -> bool {
+        self.user_pda == Pubkey::default() || self.user_pda == pda
+    }
+
+    /// Applies one event to the view. Events of other profiles are ignored.
+    pub fn apply(&mut self, event: &BridgeEvent) {
+        match &event.data {
+            BridgeEventData::UserProfileCreated(e) if self.adopts(e.user_pda) => {
+                *self = Self {
+                    user_pda: e.user_pda,
+                    authority: e.authority,
+                    admin_pda: e.target_admin_pda,
+                    communication_pubkey: e.communication_pubkey,
+                    ..Self::default()
+                };
+            }
+            BridgeEventData::UserCommKeyUpdated(e) if e.user_profile_pda == self.user_pda => {
+                self.communication_pubkey = e.new_comm_pubkey;
+                self.comm_key_version = e.comm_key_version;
+            }
+            BridgeEventData::UserFundsDeposited(e) if e.user_profile_pda == self.user_pda => {
+                self.deposit_balance = e.new_deposit_balance;
+                if self.deposit_balance >= self.low_balance_threshold {
+                    self.low_balance_notified = false;
+                }
+                self.deposits += 1;
+            }
+            BridgeEventData::UserFundsWithdrawn(e) if e.user_profile_pda == self.user_pda => {
+                self.deposit_balance = e.new_deposit_balance;
+                self.withdrawals += 1;
+            }
+            BridgeEventData::UserProfileClosed(e) if e.user_pda == self.user_pda => {
+                self.closed = true;
+            }
+            BridgeEventData::UserCommandDispatched(e) if e.sender_user_pda == self.user_pda => {
+                self.deposit_balance = self.deposit_balance.saturating_sub(e.price_paid);
+                self.commands_dispatched += 1;
+            }
+            BridgeEventData::AdminCommandDispatched(e) if e.target_user_pda == self.user_pda => {
+                self.commands_received += 1;
+            }
+            BridgeEventData::UserBanned(e) if e.user_profile_pda == self.user_pda => {
+                if !self.banned {
+                    self.ban_count = self.ban_count.saturating_add(1);
+                }
+                self.banned = true;
+            }
+            BridgeEventData::UserUnbanned(e) if e.user_profile_pda == self.user_pda => {
+                self.banned = false;
+                self.unban_requested = false;
+            }
+            BridgeEventData::UserUnbanRequested(e) if e.user_profile_pda == self.user_pda => {
+                self.deposit_balance = self.deposit_balance.saturating_sub(e.fee_paid);
+                self.unban_requested = true;
+                self.unban_requests += 1;
+            }
+            BridgeEventData::UserLowBalanceThresholdUpdated(e)
+                if e.user_profile_pda == self.user_pda =>
+            {
+                self.low_balance_threshold = e.new_threshold;
+                self.low_balance_notified = false;
+            }
+            BridgeEventData::UserLowBalance(e) if e.user_profile_pda == self.user_pda => {
+                self.low_balance_notified = true;
+            }
+            BridgeEventData::UserSpendingCapUpdated(e) if e.user_profile_pda == self.user_pda => {
+                self.max_price_per_command = e.new_max_price_per_command;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl AdminProfileView {
+    /// Whether a registration of `pda` (re)starts this view, as for user profiles.
+    fn adopts(&self, pda: Pubkey) -> bool {
+        self.admin_pda == Pubkey::default() || self.admin_pda == pda
+    }
+
+    /// Applies one event to the view. Events of other profiles are ignored.
+    pub fn apply(&mut self, event: &BridgeEvent) {
+        match &event.data {
+            BridgeEventData::AdminProfileRegistered(e) if self.adopts(e.admin_pda) => {
+                *self = Self {
+                    admin_pda: e.admin_pda,
+                    authority: e.authority,
+                    index: e.index,
+                    communication_pubkey: e.communication_pubkey,
+                    oracle_authority: e.oracle_authority,
+                    timestamp_validity_seconds: e.timestamp_validity_seconds,
+                    max_payload_size: e.max_payload_size,
+                    unban_fee: e.unban_fee,
+                    ..Self::default()
+                };
+            }
+            BridgeEventData::AdminConfigUpdated(e) if e.admin_pda == self.admin_pda => {
+                self.oracle_authority = e.new_oracle_authority;
+                self.timestamp_validity_seconds = e.new_timestamp_validity;
+                self.communication_pubkey = e.new_communication_pubkey;
+                self.comm_key_version = e.comm_key_version;
+                self.max_payload_size = e.new_max_payload_size;
+                self.require_distinct_oracle = e.require_distinct_oracle;
+            }
+            BridgeEventData::AdminUnbanFeeUpdated(e) if e.admin_pda == self.admin_pda => {
+                self.unban_fee = e.new_unban_fee;
+            }
+            BridgeEventData::AdminFundsWithdrawn(e) if e.admin_pda == self.admin_pda => {
+                self.balance = self.balance.saturating_sub(e.amount);
+                self.withdrawals += 1;
+            }
+            BridgeEventData::AdminProfileClosed(e) if e.admin_pda == self.admin_pda => {
+                self.balance = self.balance.saturating_sub(e.swept_balance);
+                self.closed = true;
+            }
+            BridgeEventData::AdminCommandDispatched(e) if e.sender_admin_pda == self.admin_pda => {
+                self.commands_dispatched += 1;
+            }
+            BridgeEventData::UserCommandDispatched(e) if e.target_admin_pda == self.admin_pda => {
+                self.balance = self.balance.saturating_add(e.price_paid);
+                self.commands_received += 1;
+            }
+            BridgeEventData::UserUnbanRequested(e) if e.admin_pda == self.admin_pda => {
+                self.balance = self.balance.saturating_add(e.fee_paid);
+            }
+            BridgeEventData::UserBanned(e) if e.admin_pda == self.admin_pda => {
+                self.bans += 1;
+            }
+            BridgeEventData::UserUnbanned(e) if e.admin_pda == self.admin_pda => {
+                self.unbans += 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Rebuilds a `UserProfile` from the history of its PDA, oldest event first.
+pub fn fold_user_profile(events: &[BridgeEvent]) -> UserProfileView {
+    events
+        .iter()
+        .fold(UserProfileView::default(), |mut view, event| {
+            view.apply(event);
+            view
+        })
+}
+
+/// Rebuilds an `AdminProfile` from the history of its PDA, oldest event first.
+pub fn fold_admin_profile(events: &[BridgeEvent]) -> AdminProfileView {
+    events
+        .iter()
+        .fold(AdminProfileView::default(), |mut view, event| {
+            view.apply(event);
+            view
+        })
+}
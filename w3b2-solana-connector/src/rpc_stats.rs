@@ -0,0 +1,261 @@
+//! # RPC Call Statistics
+//!
+//! Counts the RPC calls made by the connector and how many of them failed, per method, so
+//! that an operator can tell how much load the connector puts on its RPC provider and how
+//! reliable that provider is.
+//!
+//! The counters are kept by [`RpcStats`] and fed by [`CountingRpc`], a decorator that
+//! implements both [`SyncRpc`] and [`AsyncRpcClient`] for the client it wraps. The
+//! `EventManager` wraps the transport of its workers automatically; a `TransactionBuilder`
+//! is counted by building it over a `CountingRpc` that shares the same counters:
+//!
+//! ```ignore
+//! let counted = Arc::new(CountingRpc::new(rpc_client, handle.rpc_stats()));
+//! let builder = TransactionBuilder::new(counted);
+//! ```
+//!
+//! This module is only available with the `metrics` feature.
+
+use crate::client::AsyncRpcClient;
+use crate::workers::SyncRpc;
+use async_trait::async_trait;
+use solana_client::{
+    client_error::ClientError,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::RpcTransactionConfig,
+    rpc_response::{RpcConfirmedTransactionStatusWithSignature, RpcSimulateTransactionResult},
+};
+use solana_sdk::{
+    account::Account, hash::Hash, pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, TransactionStatus};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// The RPC methods counted by [`RpcStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RpcMethod {
+    GetSignaturesForAddress,
+    GetTransaction,
+    GetSlot,
+    GetSignatureStatuses,
+    GetLatestBlockhash,
+    SendAndConfirmTransaction,
+    SimulateTransaction,
+    IsBlockhashValid,
+    GetMultipleAccounts,
+}
+
+impl RpcMethod {
+    /// Every counted method, in declaration order.
+    pub const ALL: [RpcMethod; 9] = [
+        RpcMethod::GetSignaturesForAddress,
+        RpcMethod::GetTransaction,
+        RpcMethod::GetSlot,
+        RpcMethod::GetSignatureStatuses,
+        RpcMethod::GetLatestBlockhash,
+        RpcMethod::SendAndConfirmTransaction,
+        RpcMethod::SimulateTransaction,
+        RpcMethod::IsBlockhashValid,
+        RpcMethod::GetMultipleAccounts,
+    ];
+
+    /// The name of the JSON-RPC method.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RpcMethod::GetSignaturesForAddress => "getSignaturesForAddress",
+            RpcMethod::GetTransaction => "getTransaction",
+            RpcMethod::GetSlot => "getSlot",
+            RpcMethod::GetSignatureStatuses => "getSignatureStatuses",
+            RpcMethod::GetLatestBlockhash => "getLatestBlockhash",
+            RpcMethod::SendAndConfirmTransaction => "sendTransaction",
+            RpcMethod::SimulateTransaction => "simulateTransaction",
+            RpcMethod::IsBlockhashValid => "isBlockhashValid",
+            RpcMethod::GetMultipleAccounts => "getMultipleAccounts",
+        }
+    }
+}
+
+/// The number of calls made to one RPC method and how many of them failed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RpcCallStats {
+    pub calls: u64,
+    pub errors: u64,
+}
+
+impl RpcCallStats {
+    /// The share of the calls that failed, or `0.0` before the first call.
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.calls as f64
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counter {
+    calls: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// Shared, lock-free counters of the RPC calls made through a [`CountingRpc`].
+///
+/// Clones share the same counters.
+#[derive(Debug, Clone, Default)]
+pub struct RpcStats {
+    counters: Arc<[Counter; RpcMethod::ALL.len()]>,
+}
+
+impl RpcStats {
+    /// Creates counters that start at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a call to `method` that returned `result`.
+    pub fn record<T, E>(&self, method: RpcMethod, result: &Result<T, E>) {
+        let counter = &self.counters[method as usize];
+        counter.calls.fetch_add(1, Ordering::Relaxed);
+        if result.is_err() {
+            counter.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The calls made to `method` so far.
+    pub fn get(&self, method: RpcMethod) -> RpcCallStats {
+        let counter = &self.counters[method as usize];
+        RpcCallStats {
+            calls: counter.calls.load(Ordering::Relaxed),
+            errors: counter.errors.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The calls made so far, summed over every method.
+    pub fn total(&self) -> RpcCallStats {
+        RpcMethod::ALL.iter().map(|method| self.get(*method)).fold(
+            RpcCallStats::default(),
+            |total, stats| RpcCallStats {
+                calls: total.calls + stats.calls,
+                errors: total.errors + stats.errors,
+            },
+        )
+    }
+
+    /// The calls made so far to every method, in the order of [`RpcMethod::ALL`].
+    pub fn snapshot(&self) -> Vec<(RpcMethod, RpcCallStats)> {
+        RpcMethod::ALL
+            .iter()
+            .map(|method| (*method, self.get(*method)))
+            .collect()
+    }
+}
+
+/// An RPC client that counts every call it forwards to `inner` in its [`RpcStats`].
+pub struct CountingRpc<R: ?Sized> {
+    inner: Arc<R>,
+    stats: RpcStats,
+}
+
+impl<R: ?Sized> CountingRpc<R> {
+    /// Wraps `inner`, counting its calls in `stats`.
+    pub fn new(inner: Arc<R>, stats: RpcStats) -> Self {
+        Self { inner, stats }
+    }
+
+    /// The counters this client feeds.
+    pub fn stats(&self) -> &RpcStats {
+        &self.stats
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn record<T>(
+        &self,
+        method: RpcMethod,
+        result: Result<T, ClientError>,
+    ) -> Result<T, ClientError> {
+        self.stats.record(method, &result);
+        result
+    }
+}
+
+#[async_trait]
+impl<R: SyncRpc + ?Sized> SyncRpc for CountingRpc<R> {
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        config: GetConfirmedSignaturesForAddress2Config,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, ClientError> {
+        let result = SyncRpc::get_signatures_for_address(&*self.inner, address, config).await;
+        self.record(RpcMethod::GetSignaturesForAddress, result)
+    }
+
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, ClientError> {
+        let result = SyncRpc::get_transaction(&*self.inner, signature, config).await;
+        self.record(RpcMethod::GetTransaction, result)
+    }
+
+    async fn get_slot(&self) -> Result<u64, ClientError> {
+        let result = SyncRpc::get_slot(&*self.inner).await;
+        self.record(RpcMethod::GetSlot, result)
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>, ClientError> {
+        let result = SyncRpc::get_signature_statuses(&*self.inner, signatures).await;
+        self.record(RpcMethod::GetSignatureStatuses, result)
+    }
+}
+
+#[async_trait]
+impl<R: AsyncRpcClient + ?Sized> AsyncRpcClient for CountingRpc<R> {
+    async fn get_latest_blockhash(&self) -> Result<Hash, ClientError> {
+        let result = AsyncRpcClient::get_latest_blockhash(&*self.inner).await;
+        self.record(RpcMethod::GetLatestBlockhash, result)
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, ClientError> {
+        let result = AsyncRpcClient::send_and_confirm_transaction(&*self.inner, transaction).await;
+        self.record(RpcMethod::SendAndConfirmTransaction, result)
+    }
+
+    async fn simulate_unsigned_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<RpcSimulateTransactionResult, ClientError> {
+        let result = self.inner.simulate_unsigned_transaction(transaction).await;
+        self.record(RpcMethod::SimulateTransaction, result)
+    }
+
+    async fn is_blockhash_valid(&self, blockhash: &Hash) -> Result<bool, ClientError> {
+        let result = AsyncRpcClient::is_blockhash_valid(&*self.inner, blockhash).await;
+        self.record(RpcMethod::IsBlockhashValid, result)
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, ClientError> {
+        let result = AsyncRpcClient::get_multiple_accounts(&*self.inner, pubkeys).await;
+        self.record(RpcMethod::GetMultipleAccounts, result)
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>, ClientError> {
+        let result = AsyncRpcClient::get_signature_statuses(&*self.inner, signatures).await;
+        self.record(RpcMethod::GetSignatureStatuses, result)
+    }
+}
@@ -0,0 +1,122 @@
+//! # Transaction Simulation
+//!
+//! Decodes the result of simulating a prepared, unsigned message, so that a transaction
+//! that is bound to fail (e.g. because the user is banned or their deposit is too small)
+//! can be reported before the user is asked to sign it.
+//!
+//! Simulations are run by [`TransactionBuilder::simulate_message`](crate::client::TransactionBuilder::simulate_message).
+
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+use std::fmt;
+
+/// The outcome of simulating a prepared message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationReport {
+    /// Why the transaction would fail, or `None` if it would succeed.
+    pub error: Option<SimulationError>,
+    /// The log messages emitted during the simulation.
+    pub logs: Vec<String>,
+    /// The compute units consumed by the simulation, if reported by the node.
+    pub units_consumed: Option<u64>,
+}
+
+impl SimulationReport {
+    /// Builds a report from the raw result of a simulation, decoding its error.
+    pub fn new(
+        err: Option<TransactionError>,
+        logs: Vec<String>,
+        units_consumed: Option<u64>,
+    ) -> Self {
+        let error = err.map(|err| decode_simulation_error(err, &logs));
+        Self {
+            error,
+            logs,
+            units_consumed,
+        }
+    }
+
+    /// Returns `true` if the simulated transaction would succeed.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Why a simulated transaction would fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulationError {
+    /// An instruction failed with a custom program error, such as a `BridgeError` or an
+    /// Anchor constraint violation.
+    Program(DecodedProgramError),
+    /// The transaction failed for any other reason, e.g. an insufficient fee payer balance.
+    Transaction(TransactionError),
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Program(err) => err.fmt(f),
+            Self::Transaction(err) => err.fmt(f),
+        }
+    }
+}
+
+/// A custom program error, decoded from its error code and the program logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedProgramError {
+    /// The index of the failing instruction within the transaction.
+    pub instruction_index: u8,
+    /// The numeric error code, e.g. `6000` and above for a `BridgeError`.
+    pub code: u32,
+    /// The name of the error variant, e.g. `UserIsBanned`, if the program logged it.
+    pub name: Option<String>,
+    /// The human-readable error message, if the program logged it.
+    pub message: Option<String>,
+}
+
+impl fmt::Display for DecodedProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "instruction {} failed", self.instruction_index)?;
+        match (&self.name, &self.message) {
+            (Some(name), Some(message)) => write!(f, ": {name} ({}): {message}", self.code),
+            _ => write!(f, " with custom program error {}", self.code),
+        }
+    }
+}
+
+/// Decodes the error of a simulated transaction.
+///
+/// A custom program error is matched against the `AnchorError` line the program logs
+/// when an instruction fails, to recover the name and message of the error. Any other
+/// error is returned as is.
+pub fn decode_simulation_error(err: TransactionError, logs: &[String]) -> SimulationError {
+    let TransactionError::InstructionError(instruction_index, InstructionError::Custom(code)) = err
+    else {
+        return SimulationError::Transaction(err);
+    };
+    let (name, message) = logs
+        .iter()
+        .rev()
+        .find_map(|line| parse_anchor_error(line, code))
+        .unzip();
+    SimulationError::Program(DecodedProgramError {
+        instruction_index,
+        code,
+        name,
+        message,
+    })
+}
+
+/// Parses the name and message out of an Anchor error log line for `code`, such as
+/// `AnchorError occurred. Error Code: UserIsBanned. Error Number: 6010. Error Message: ....`
+fn parse_anchor_error(line: &str, code: u32) -> Option<(String, String)> {
+    let (_, rest) = line.split_once("Error Code: ")?;
+    let (name, rest) = rest.split_once(". Error Number: ")?;
+    let (number, message) = rest.split_once(". Error Message: ")?;
+    if number.parse::<u32>().ok()? != code {
+        return None;
+    }
+    // Anchor appends a period to the message.
+    let message = message.strip_suffix('.').unwrap_or(message);
+    Some((name.to_string(), message.to_string()))
+}
@@ -1,8 +1,45 @@
 use anyhow::Result;
 use async_trait::async_trait;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+
+use crate::events::EventKind;
+
+/// A single decoded event persisted by an event cache.
+///
+/// `data` is the raw `Program data:` payload (discriminator followed by the Borsh-encoded
+/// event), so it can be decoded again with [`crate::events::try_parse_program_data`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CachedEvent {
+    /// The slot of the transaction that emitted the event.
+    pub slot: u64,
+    /// The signature of the transaction that emitted the event.
+    pub signature: String,
+    /// The encoded event.
+    pub data: Vec<u8>,
+}
+
+/// The cached history of a single PDA.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CachedHistory {
+    /// The cached events, oldest first.
+    pub events: Vec<CachedEvent>,
+    /// The `(slot, signature)` of the newest transaction the history is complete up to.
+    pub cursor: Option<(u64, String)>,
+    /// Whether the oldest transactions were evicted to keep the history within a size
+    /// bound, so that `events` only hold its newest part.
+    pub truncated: bool,
+}
 
 /// A trait defining the required functionality for a persistent storage backend.
 /// This allows for different database implementations.
+///
+/// The event cache, event count and active PDA methods are optional. Their default
+/// implementations cache, count and record nothing, in which case every history replay is
+/// served from the RPC node and nothing is replayed ahead of time on startup.
 #[async_trait]
 pub trait Storage: Send + Sync {
     /// Retrieves the last synchronized slot number from the storage.
@@ -14,4 +51,67 @@ pub trait Storage: Send + Sync {
     /// Atomically sets the last synchronized slot and signature.
     /// This should be a transactional operation to ensure data consistency.
     async fn set_sync_state(&self, slot: u64, sig: &str) -> Result<()>;
+
+    /// Retrieves the cached event history of `pda`, or `None` if it is not cached.
+    async fn get_cached_events(&self, _pda: &Pubkey) -> Result<Option<CachedHistory>> {
+        Ok(None)
+    }
+
+    /// Appends `events` to the cached history of `pda` and moves its cursor from `since`
+    /// to `cursor`.
+    ///
+    /// The update must be ignored if the stored cursor no longer equals `since`, which
+    /// happens when concurrent replays of the same PDA race to cache the same range.
+    /// Implementations may bound the history of a PDA by evicting its oldest transactions,
+    /// but must then mark it as [`truncated`](CachedHistory::truncated), so that it is only
+    /// served to replays that do not reach past it.
+    async fn cache_events(
+        &self,
+        _pda: &Pubkey,
+        _since: Option<(u64, &str)>,
+        _events: &[CachedEvent],
+        _cursor: (u64, &str),
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Removes every cached event emitted by `signature` from the histories of `pdas`.
+    /// Called when the transaction is dropped by a fork.
+    async fn evict_cached_events(&self, _pdas: &[Pubkey], _signature: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Adds one to the count of each `(pda, kind)` pair, which may repeat.
+    ///
+    /// Called by the synchronizer with the events of every transaction it processes, right
+    /// before the sync cursor moves past that transaction. Events later retracted by a fork
+    /// stay counted.
+    async fn count_events(&self, _events: &[(Pubkey, EventKind)]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the number of events of each kind counted for `pda`. Kinds that never
+    /// occurred are omitted.
+    async fn get_event_counts(&self, _pda: &Pubkey) -> Result<HashMap<EventKind, u64>> {
+        Ok(HashMap::new())
+    }
+
+    /// Records `pda` as having at least one listener with a history replay. Recording a PDA
+    /// twice has no effect.
+    ///
+    /// The recorded PDAs are replayed when the connector starts again, so that their event
+    /// caches are warm before the listeners resubscribe after a crash.
+    async fn add_active_pda(&self, _pda: &Pubkey) -> Result<()> {
+        Ok(())
+    }
+
+    /// Forgets `pda` once its last listener is gone.
+    async fn remove_active_pda(&self, _pda: &Pubkey) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns every PDA recorded by [`Storage::add_active_pda`] and not removed since.
+    async fn list_active_pdas(&self) -> Result<Vec<Pubkey>> {
+        Ok(Vec::new())
+    }
 }
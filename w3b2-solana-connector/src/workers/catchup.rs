@@ -1,36 +1,91 @@
 use crate::{
-    events::{try_parse_log, EventSource},
-    workers::synchronizer::WorkerContext,
+    dispatcher::{extract_pdas_from_event, CatchupRequest},
+    events::{
+        parse_dispatch_rejection_for, parse_transaction_events_for, try_parse_program_data,
+        BridgeEvent, BridgeEventData, CaughtUp, EventSource,
+    },
+    listener::{CatchupProgress, HistoryOptions},
+    storage::{CachedEvent, CachedHistory},
+    workers::{rpc::SyncRpc, synchronizer::WorkerContext},
 };
 use anyhow::Result;
+use futures::{stream, Stream, StreamExt};
 use solana_client::{
     rpc_client::GetConfirmedSignaturesForAddress2Config,
     rpc_config::RpcTransactionConfig,
     rpc_response::RpcConfirmedTransactionStatusWithSignature,
 };
-use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
-use solana_transaction_status::{UiTransactionEncoding, EncodedConfirmedTransactionWithStatusMeta};
-use tokio::time::{sleep, Duration};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use tokio::{
+    sync::{mpsc, watch},
+    time::{interval_at, Duration, Instant, MissedTickBehavior},
+};
 
-pub struct CatchupWorker {
-    ctx: WorkerContext,
+/// Fetches transactions the `LiveWorker` may have missed and replays PDA history.
+///
+/// The worker has two duties:
+/// - It periodically polls the program's signatures starting from the last persisted
+///   cursor and dispatches any events it finds, filling gaps in the live stream. While the
+///   live worker has fallen back to polling, it also polls whenever the live worker asks.
+/// - For every newly registered listener, it replays the full history of that listener's
+///   PDA to its catch-up channel, followed by a [`CaughtUp`] marker.
+///
+/// On startup, it also replays the history of every PDA the storage still lists as active,
+/// i.e. that had a listener when the connector stopped, so that their event caches are
+/// brought up to date before the listeners resubscribe.
+pub struct CatchupWorker<R: SyncRpc + ?Sized> {
+    ctx: WorkerContext<R>,
     program_id: solana_sdk::pubkey::Pubkey,
+    requests: mpsc::Receiver<CatchupRequest>,
+    /// The PDAs to pre-warm, read by [`load_active_pdas`](Self::load_active_pdas).
+    active_pdas: Vec<Pubkey>,
 }
 
-impl CatchupWorker {
-    pub fn new(ctx: WorkerContext) -> Self {
-        Self { ctx, program_id: w3b2_solana_program::ID }
+impl<R: SyncRpc + ?Sized + 'static> CatchupWorker<R> {
+    pub fn new(ctx: WorkerContext<R>, requests: mpsc::Receiver<CatchupRequest>) -> Self {
+        let program_id = ctx.config.solana.program_id;
+        Self {
+            ctx,
+            program_id,
+            requests,
+            active_pdas: Vec::new(),
+        }
+    }
+
+    /// Reads the PDAs the storage lists as active, to be pre-warmed once the worker runs.
+    ///
+    /// Must be called before the `Dispatcher` runs, as it records the PDA of every listener
+    /// that registers from then on, and that listener's own replay already warms it.
+    pub async fn load_active_pdas(&mut self) {
+        match self.ctx.storage.list_active_pdas().await {
+            Ok(pdas) => self.active_pdas = pdas,
+            Err(e) => tracing::warn!("Failed to list active PDAs: {}", e),
+        }
     }
 
-    pub async fn run(self) -> Result<()> {
+    pub async fn run(mut self) -> Result<()> {
+        let period = Duration::from_secs(self.ctx.config.synchronizer.poll_interval_secs.max(1));
+        let mut ticker = interval_at(Instant::now() + period, period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        self.prewarm();
+
         loop {
             tokio::select! {
-                _ = sleep(Duration::from_secs(self.ctx.config.synchronizer.poll_interval_secs)) => {
+                _ = ticker.tick() => {
                     if let Err(e) = self.sync_transactions().await {
                         tracing::error!("Error during catch-up sync: {}", e);
                     }
                 },
-                _ = self.ctx.dispatcher.command_tx.closed() => {
+                _ = self.ctx.poll_requests.notified() => {
+                    if let Err(e) = self.sync_transactions().await {
+                        tracing::error!("Error during fallback poll: {}", e);
+                    }
+                },
+                Some(request) = self.requests.recv() => {
+                    tokio::spawn(PdaCatchup::new(self.ctx.clone(), request).run());
+                },
+                _ = self.ctx.shutdown_requested() => {
                     tracing::info!("CatchupWorker: shutdown signal received, exiting.");
                     return Ok(());
                 }
@@ -38,110 +93,576 @@ impl CatchupWorker {
         }
     }
 
+    /// Starts a replay for every active PDA read by `load_active_pdas`. The replayed events
+    /// are discarded; the replay only fills the PDA's event cache.
+    fn prewarm(&mut self) {
+        for pda in std::mem::take(&mut self.active_pdas) {
+            tracing::info!("Pre-warming the history of active PDA {}", pda);
+            let (sender, mut events) =
+                mpsc::channel(self.ctx.config.channels.listener_event_buffer);
+            tokio::spawn(async move { while events.recv().await.is_some() {} });
+            let request = CatchupRequest {
+                pda,
+                sender,
+                history: HistoryOptions::default(),
+                progress: watch::channel(CatchupProgress::default()).0,
+            };
+            tokio::spawn(PdaCatchup::new(self.ctx.clone(), request).run());
+        }
+    }
+
     async fn sync_transactions(&self) -> Result<()> {
+        // Everything up to this slot has been seen once the pass completes.
+        let current_slot = self.ctx.rpc_client.get_slot().await?;
         let signatures = self.fetch_new_signatures().await?;
         if !signatures.is_empty() {
             tracing::info!("Found {} new signatures to process.", signatures.len());
-            self.process_signatures(signatures).await?;
+            self.process_signatures(signatures, current_slot).await?;
+        }
+        if !self.ctx.is_shutting_down() {
+            self.ctx.lag.record_synced(current_slot);
         }
         Ok(())
     }
 
     async fn fetch_new_signatures(&self) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
-        let mut before: Option<Signature> = None;
         let last_known_sig = self.ctx.storage.get_last_sig().await?;
-        let mut signatures_to_process = Vec::new();
-
+        let last_known_slot = self.ctx.storage.get_last_slot().await?;
         tracing::debug!("Starting catch-up from last known signature: {:?}", last_known_sig);
+        let until = last_known_sig.as_deref().map(|sig| (sig, last_known_slot));
+        fetch_signatures(&self.ctx, &self.program_id, until, None).await
+    }
 
-        loop {
-            let page = self.fetch_signature_page(before).await?;
-            if page.is_empty() {
+    async fn process_signatures(
+        &self,
+        signatures: Vec<RpcConfirmedTransactionStatusWithSignature>,
+        current_slot: u64,
+    ) -> Result<()> {
+        let signatures = within_catchup_depth(&self.ctx, signatures, current_slot);
+
+        let mut transactions = fetch_transactions(&self.ctx, signatures);
+        while let Some((sig_info, tx)) = transactions.next().await {
+            if self.ctx.is_shutting_down() {
+                tracing::info!(
+                    "CatchupWorker: shutdown signal received, stopping the current pass."
+                );
                 break;
             }
-            before = page.last().and_then(|s| s.signature.parse().ok());
-
-            if let Some(ref known_sig) = last_known_sig {
-                if let Some(pos) = page.iter().position(|s| &s.signature == known_sig) {
-                    signatures_to_process.extend_from_slice(&page[..pos]);
-                    break;
-                }
+            if let Err(e) = self.process_one_transaction(&sig_info, tx).await {
+                tracing::error!(signature = %sig_info.signature, "Failed to process transaction: {}", e);
             }
-            signatures_to_process.extend(page);
         }
 
-        signatures_to_process.reverse();
-        Ok(signatures_to_process)
+        Ok(())
     }
 
-    async fn fetch_signature_page(&self, before: Option<Signature>) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
-        let config = GetConfirmedSignaturesForAddress2Config {
-            before,
-            until: None,
-            limit: Some(self.ctx.config.synchronizer.max_signature_fetch),
-            commitment: Some(CommitmentConfig { commitment: self.ctx.config.solana.commitment }),
-        };
-        self.ctx.rpc_client.get_signatures_for_address_with_config(&self.program_id, config).await.map_err(Into::into)
+    async fn process_one_transaction(
+        &self,
+        sig_info: &RpcConfirmedTransactionStatusWithSignature,
+        tx: Result<EncodedConfirmedTransactionWithStatusMeta>,
+    ) -> Result<()> {
+        let tx = tx?;
+        let slot = tx.slot;
+        let rejection = self
+            .ctx
+            .config
+            .synchronizer
+            .report_dispatch_rejections
+            .then(|| {
+                rejection_event(
+                    &self.program_id,
+                    &sig_info.signature,
+                    &tx,
+                    EventSource::Catchup,
+                )
+            })
+            .flatten();
+        let mut events = parse_transaction_events_for(
+            &self.program_id,
+            &sig_info.signature,
+            &tx,
+            EventSource::Catchup,
+        );
+        events.extend(rejection);
+        self.ctx.count_events(&events).await;
+        for event in events {
+            self.ctx.dispatch(event).await;
+        }
+        self.ctx
+            .storage
+            .set_sync_state(slot, &sig_info.signature)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Replays the complete history of a single PDA to one listener's catch-up channel.
+///
+/// If the storage keeps an event cache, the cached part of the history is served from it
+/// and only transactions newer than the cache cursor are fetched from the RPC node. The
+/// newly fetched events are then appended to the cache.
+struct PdaCatchup<R: SyncRpc + ?Sized> {
+    ctx: WorkerContext<R>,
+    request: CatchupRequest,
+}
+
+impl<R: SyncRpc + ?Sized> PdaCatchup<R> {
+    fn new(ctx: WorkerContext<R>, request: CatchupRequest) -> Self {
+        Self { ctx, request }
     }
 
-    async fn process_signatures(&self, signatures: Vec<RpcConfirmedTransactionStatusWithSignature>) -> Result<()> {
-        let current_slot = self.ctx.rpc_client.get_slot().await?;
-        let max_depth = self.ctx.config.synchronizer.max_catchup_depth;
+    /// Runs the replay. If it fails, the catch-up channel is closed without a
+    /// [`CaughtUp`] marker, so the listener can tell the history is incomplete.
+    async fn run(self) {
+        let pda = self.request.pda;
+        if let Err(e) = self.replay().await {
+            tracing::error!("Failed to replay history for PDA {}: {}", pda, e);
+        }
+    }
+
+    async fn replay(self) -> Result<()> {
+        let CatchupRequest {
+            pda,
+            sender,
+            history,
+            progress,
+        } = self.request;
+        let ctx = &self.ctx;
+        tracing::debug!("Replaying history for PDA {}", pda);
+
+        let current_slot = ctx.rpc_client.get_slot().await?;
+        progress.send_modify(|p| p.target_slot = current_slot);
+        let min_slot = ctx
+            .config
+            .synchronizer
+            .max_catchup_depth
+            .map_or(0, |depth| current_slot.saturating_sub(depth));
+        let mut replay = Replay {
+            ctx,
+            pda: &pda,
+            sender: &sender,
+            progress: &progress,
+            history,
+            caught_up_slot: current_slot,
+            to_cache: Vec::new(),
+        };
 
-        for sig_info in signatures {
-            if !self.is_within_catchup_depth(&sig_info, current_slot, max_depth) {
-                continue;
+        // Serve whatever the storage has cached first, then only fetch the newer part of
+        // the history from the RPC node.
+        let cached = match ctx.storage.get_cached_events(&pda).await {
+            Ok(cached) => cached.unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("Failed to read cached events for PDA {}: {}", pda, e);
+                CachedHistory::default()
             }
-            if let Err(e) = self.process_one_transaction(&sig_info).await {
-                tracing::error!(signature = %sig_info.signature, "Failed to process transaction: {}", e);
+        };
+        let max_transactions = match (
+            ctx.config.synchronizer.catchup_max_transactions,
+            history.limit,
+        ) {
+            (Some(max), Some(limit)) => Some(max.min(limit)),
+            (max, limit) => max.or(limit),
+        };
+        // A truncated cache only holds the newest part of the history, so a replay that
+        // reaches past it is served from the RPC node instead.
+        let since = history.since_slot;
+        let cached = if cached.truncated
+            && !truncated_cache_covers(&cached, max_transactions, since, min_slot)
+        {
+            CachedHistory::default()
+        } else {
+            cached
+        };
+        let until = cached
+            .cursor
+            .as_ref()
+            .map(|(slot, sig)| (sig.as_str(), *slot));
+        let signatures = fetch_signatures(ctx, &pda, until, max_transactions).await?;
+        // If the newer part alone fills the window, the cache is not extended either: the
+        // events between its cursor and the window were never fetched.
+        let truncated = max_transactions.is_some_and(|max| signatures.len() >= max);
+        let signatures = within_catchup_depth(ctx, signatures, current_slot);
+        let cursor = signatures.last().map(|s| (s.slot, s.signature.clone()));
+        // A resumed replay skips what the listener already has. The skipped transactions
+        // are not fetched, so the cache cannot be extended past them either.
+        let fetched = signatures.len();
+        let signatures: Vec<_> = signatures
+            .into_iter()
+            .filter(|s| since.is_none_or(|since| s.slot > since))
+            .collect();
+        let skipped = signatures.len() < fetched;
+
+        let cached_events = newest_cached_transactions(
+            &cached.events,
+            max_transactions.map(|max| max.saturating_sub(signatures.len())),
+        );
+        let cached_events: Vec<_> = cached_events
+            .iter()
+            .filter(|entry| entry.slot >= min_slot && since.is_none_or(|since| entry.slot > since))
+            .collect();
+
+        // A newest-first replay starts with the fetched part of the history, which is newer
+        // than the cache, and reports no progress until it is done.
+        let delivered = if history.newest_first {
+            replay
+                .fetched(signatures.into_iter().rev().collect())
+                .await?
+                && replay.cached(cached_events.into_iter().rev()).await?
+        } else {
+            let from_slot = cached_events
+                .first()
+                .map(|entry| entry.slot)
+                .or(signatures.first().map(|s| s.slot));
+            if let Some(from_slot) = from_slot {
+                start_progress(&progress, from_slot);
+            }
+            replay.cached(cached_events.into_iter()).await? && replay.fetched(signatures).await?
+        };
+        if !delivered {
+            tracing::debug!("Listener for PDA {} dropped during catch-up.", pda);
+            return Ok(());
+        }
+        let Replay {
+            caught_up_slot,
+            mut to_cache,
+            ..
+        } = replay;
+
+        if let Some((slot, signature)) = cursor.filter(|_| !truncated && !skipped) {
+            if history.newest_first {
+                to_cache.reverse();
+            }
+            let result = ctx
+                .storage
+                .cache_events(
+                    &pda,
+                    until.map(|(sig, slot)| (slot, sig)),
+                    &to_cache,
+                    (slot, &signature),
+                )
+                .await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to cache events for PDA {}: {}", pda, e);
             }
         }
 
+        let marker = BridgeEvent {
+            source: EventSource::Catchup,
+            signature: None,
+            slot: caught_up_slot,
+            data: BridgeEventData::CaughtUp(CaughtUp {
+                pda,
+                slot: caught_up_slot,
+            }),
+        };
+        let _ = sender.send(marker).await;
+        progress.send_modify(|p| {
+            if p.from_slot == 0 {
+                p.from_slot = caught_up_slot;
+            }
+            p.current_slot = caught_up_slot;
+            p.target_slot = caught_up_slot;
+        });
+        tracing::info!("PDA {} caught up at slot {}", pda, caught_up_slot);
         Ok(())
     }
+}
+
+/// The state of a single history replay, shared by its cached and fetched parts.
+struct Replay<'a, R: SyncRpc + ?Sized> {
+    ctx: &'a WorkerContext<R>,
+    pda: &'a Pubkey,
+    sender: &'a mpsc::Sender<BridgeEvent>,
+    progress: &'a watch::Sender<CatchupProgress>,
+    history: HistoryOptions,
+    caught_up_slot: u64,
+    /// The fetched events to append to the cache, in the order they were delivered.
+    to_cache: Vec<CachedEvent>,
+}
 
-    fn is_within_catchup_depth(&self, sig_info: &RpcConfirmedTransactionStatusWithSignature, current_slot: u64, max_depth: Option<u64>) -> bool {
-        if let Some(depth) = max_depth {
-            if sig_info.slot < current_slot.saturating_sub(depth) {
-                tracing::debug!("Skipping {} from slot {} due to max_catchup_depth", sig_info.signature, sig_info.slot);
-                return false;
+impl<R: SyncRpc + ?Sized> Replay<'_, R> {
+    /// Delivers cached events in the given order. Returns `false` if the listener is gone.
+    async fn cached<'e>(&mut self, entries: impl Iterator<Item = &'e CachedEvent>) -> Result<bool> {
+        for entry in entries {
+            let mut event = try_parse_program_data(&entry.data)?;
+            event.signature = Some(entry.signature.clone());
+            event.slot = entry.slot;
+            if !self.deliver(event).await {
+                return Ok(false);
             }
         }
-        true
+        Ok(true)
     }
 
-    async fn process_one_transaction(&self, sig_info: &RpcConfirmedTransactionStatusWithSignature) -> Result<()> {
-        let sig = sig_info.signature.parse::<Signature>()?;
-        if let Some(tx) = self.fetch_enriched_transaction(&sig).await? {
-            if let Some(logs) = tx.transaction.meta.and_then(|meta| meta.log_messages.into()) {
-                self.dispatch_events_from_logs(logs).await;
+    /// Fetches the given transactions and delivers the events that concern the PDA, in the
+    /// order of `signatures`. Returns `false` if the listener is gone.
+    async fn fetched(
+        &mut self,
+        signatures: Vec<RpcConfirmedTransactionStatusWithSignature>,
+    ) -> Result<bool> {
+        let ctx = self.ctx;
+        let mut transactions = fetch_transactions(ctx, signatures);
+        while let Some((sig_info, tx)) = transactions.next().await {
+            let tx = tx?;
+            let program_id = &ctx.config.solana.program_id;
+            let mut events: Vec<_> = parse_transaction_events_for(
+                program_id,
+                &sig_info.signature,
+                &tx,
+                EventSource::Catchup,
+            )
+            .into_iter()
+            .filter(|event| extract_pdas_from_event(&event.data).contains(self.pda))
+            .collect();
+            if self.history.newest_first {
+                events.reverse();
+            }
+            self.caught_up_slot = self.caught_up_slot.max(tx.slot);
+            for event in events {
+                if let Some(data) = event.data.to_program_data() {
+                    self.to_cache.push(CachedEvent {
+                        slot: event.slot,
+                        signature: sig_info.signature.clone(),
+                        data,
+                    });
+                }
+                if !self.deliver(event).await {
+                    return Ok(false);
+                }
+            }
+            if !self.history.newest_first {
+                advance_progress(self.progress, tx.slot);
             }
-            self.ctx.storage.set_sync_state(tx.slot, &sig_info.signature).await?;
         }
-        Ok(())
+        Ok(true)
+    }
+
+    async fn deliver(&mut self, event: BridgeEvent) -> bool {
+        let slot = event.slot;
+        self.caught_up_slot = self.caught_up_slot.max(slot);
+        self.ctx.track(&event);
+        if self.sender.send(event).await.is_err() {
+            return false;
+        }
+        if !self.history.newest_first {
+            advance_progress(self.progress, slot);
+        }
+        true
     }
+}
+
+/// Records the oldest slot of the history being replayed, where the replay starts.
+fn start_progress(progress: &watch::Sender<CatchupProgress>, from_slot: u64) {
+    progress.send_modify(|p| {
+        p.from_slot = from_slot;
+        p.current_slot = from_slot;
+    });
+}
+
+/// Moves the replay position forward to `slot`. The position never moves backwards.
+fn advance_progress(progress: &watch::Sender<CatchupProgress>, slot: u64) {
+    progress.send_if_modified(|p| {
+        let advanced = slot > p.current_slot;
+        p.current_slot = p.current_slot.max(slot);
+        advanced
+    });
+}
+
+/// Pages backwards through the signatures of `address` until the `until` cursor (exclusive),
+/// the beginning of its history, or `max` signatures are reached. Returns the signatures
+/// oldest-first.
+///
+/// The cursor is a `(signature, slot)` pair. Paging also stops at the first signature older
+/// than the cursor's slot, so a cursor whose transaction was dropped by a fork does not
+/// cause the whole history to be fetched again.
+async fn fetch_signatures<R: SyncRpc + ?Sized>(
+    ctx: &WorkerContext<R>,
+    address: &Pubkey,
+    until: Option<(&str, u64)>,
+    max: Option<usize>,
+) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+    let mut before: Option<Signature> = None;
+    let mut signatures = Vec::new();
 
-    async fn fetch_enriched_transaction(&self, sig: &Signature) -> Result<Option<EncodedConfirmedTransactionWithStatusMeta>> {
-        let tx_config = RpcTransactionConfig {
-            encoding: Some(UiTransactionEncoding::Base64),
-            commitment: Some(CommitmentConfig { commitment: self.ctx.config.solana.commitment }),
-            max_supported_transaction_version: Some(0),
+    loop {
+        let remaining = max.map_or(usize::MAX, |max| max.saturating_sub(signatures.len()));
+        if remaining == 0 {
+            break;
+        }
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before,
+            until: None,
+            limit: Some(ctx.config.solana.signatures_per_batch.min(remaining)),
+            commitment: Some(CommitmentConfig {
+                commitment: ctx.config.solana.commitment,
+            }),
         };
-        match self.ctx.rpc_client.get_transaction_with_config(sig, tx_config).await {
-            Ok(tx) => Ok(Some(tx)),
-            Err(e) => {
-                tracing::error!("Failed to get transaction {}: {}", sig, e);
-                Ok(None)
+        let page = ctx
+            .rpc_client
+            .get_signatures_for_address(address, config)
+            .await?;
+        if page.is_empty() {
+            break;
+        }
+        before = page.last().and_then(|s| s.signature.parse().ok());
+
+        if let Some((known_sig, known_slot)) = until {
+            if let Some(pos) = page
+                .iter()
+                .position(|s| s.signature == known_sig || s.slot < known_slot)
+            {
+                signatures.extend_from_slice(&page[..pos]);
+                break;
             }
         }
+        signatures.extend(page);
     }
 
-    async fn dispatch_events_from_logs(&self, logs: Vec<String>) {
-        for log in logs {
-            if let Ok(mut event) = try_parse_log(&log) {
-                event.source = EventSource::Catchup;
-                self.ctx.dispatcher.dispatch(event).await;
+    signatures.truncate(max.unwrap_or(usize::MAX));
+    signatures.reverse();
+    Ok(signatures)
+}
+
+/// Returns the events of the newest `max_transactions` transactions in a cached history,
+/// or all of them if `max_transactions` is `None`.
+fn newest_cached_transactions(
+    events: &[CachedEvent],
+    max_transactions: Option<usize>,
+) -> &[CachedEvent] {
+    let Some(max_transactions) = max_transactions else {
+        return events;
+    };
+    // The events of one transaction are stored next to each other.
+    let mut transactions = 0;
+    let mut start = events.len();
+    for (i, event) in events.iter().enumerate().rev() {
+        if events
+            .get(i + 1)
+            .is_none_or(|next| next.signature != event.signature)
+        {
+            if transactions == max_transactions {
+                break;
             }
+            transactions += 1;
         }
+        start = i;
     }
+    &events[start..]
+}
+
+/// Whether a truncated cached history still holds every transaction a replay needs: the
+/// newest `max_transactions`, or all of those after `since_slot` or from `min_slot` on.
+fn truncated_cache_covers(
+    cached: &CachedHistory,
+    max_transactions: Option<usize>,
+    since_slot: Option<u64>,
+    min_slot: u64,
+) -> bool {
+    // The evicted transactions are not newer than the oldest one that was kept.
+    let Some(oldest_slot) = cached
+        .events
+        .first()
+        .map(|event| event.slot)
+        .or(cached.cursor.as_ref().map(|(slot, _)| *slot))
+    else {
+        return false;
+    };
+    // The events of one transaction are stored next to each other.
+    let transactions = cached
+        .events
+        .windows(2)
+        .filter(|pair| pair[0].signature != pair[1].signature)
+        .count()
+        + usize::from(!cached.events.is_empty());
+    max_transactions.is_some_and(|max| max <= transactions)
+        || since_slot.is_some_and(|since| since >= oldest_slot)
+        || min_slot > oldest_slot
+}
+
+/// Drops signatures older than `max_catchup_depth` slots behind `current_slot`.
+fn within_catchup_depth<R: SyncRpc + ?Sized>(
+    ctx: &WorkerContext<R>,
+    signatures: Vec<RpcConfirmedTransactionStatusWithSignature>,
+    current_slot: u64,
+) -> Vec<RpcConfirmedTransactionStatusWithSignature> {
+    let Some(depth) = ctx.config.synchronizer.max_catchup_depth else {
+        return signatures;
+    };
+    signatures
+        .into_iter()
+        .filter(|sig_info| {
+            let keep = sig_info.slot >= current_slot.saturating_sub(depth);
+            if !keep {
+                tracing::debug!(
+                    "Skipping {} from slot {} due to max_catchup_depth",
+                    sig_info.signature,
+                    sig_info.slot
+                );
+            }
+            keep
+        })
+        .collect()
+}
+
+/// Fetches the given transactions with up to `tx_fetch_concurrency` requests in flight.
+///
+/// `buffered` yields the results in the original (oldest-first) order, so events are
+/// dispatched and the cursor advances in order even though fetches complete out of order.
+fn fetch_transactions<'a, R: SyncRpc + ?Sized>(
+    ctx: &'a WorkerContext<R>,
+    signatures: Vec<RpcConfirmedTransactionStatusWithSignature>,
+) -> impl Stream<
+    Item = (
+        RpcConfirmedTransactionStatusWithSignature,
+        Result<EncodedConfirmedTransactionWithStatusMeta>,
+    ),
+> + 'a {
+    let concurrency = ctx.config.solana.tx_fetch_concurrency.max(1);
+    stream::iter(signatures)
+        .map(move |sig_info| async move {
+            let tx = fetch_transaction(ctx, &sig_info.signature).await;
+            (sig_info, tx)
+        })
+        .buffered(concurrency)
+}
+
+/// Fetches a transaction with its status meta at the configured commitment.
+pub(super) async fn fetch_transaction<R: SyncRpc + ?Sized>(
+    ctx: &WorkerContext<R>,
+    signature: &str,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+    let sig = signature.parse::<Signature>()?;
+    let tx_config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(CommitmentConfig {
+            commitment: ctx.config.solana.commitment,
+        }),
+        max_supported_transaction_version: Some(0),
+    };
+    ctx.rpc_client
+        .get_transaction(&sig, tx_config)
+        .await
+        .map_err(Into::into)
+}
+
+/// Builds the `DispatchRejected` event of a transaction that failed the oracle checks.
+pub(super) fn rejection_event(
+    program_id: &Pubkey,
+    signature: &str,
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+    source: EventSource,
+) -> Option<BridgeEvent> {
+    let rejection = parse_dispatch_rejection_for(program_id, signature, tx)?;
+    tracing::info!(
+        "User command {} for PDA {} was rejected: {}",
+        signature,
+        rejection.user_pda,
+        rejection.reason
+    );
+    Some(BridgeEvent {
+        source,
+        signature: Some(signature.to_string()),
+        slot: tx.slot,
+        data: BridgeEventData::DispatchRejected(rejection),
+    })
 }
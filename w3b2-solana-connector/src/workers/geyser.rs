@@ -0,0 +1,162 @@
+use crate::{
+    events::{parse_program_logs_for, BridgeEvent, EventSource},
+    workers::{rpc::SyncRpc, synchronizer::WorkerContext},
+};
+use anyhow::{anyhow, Result};
+use proto::{
+    geyser_client::GeyserClient, subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest,
+    SubscribeRequestFilterTransactions, SubscribeRequestPing, SubscribeUpdateTransaction,
+};
+use solana_sdk::{
+    commitment_config::CommitmentLevel as SolanaCommitmentLevel, signature::Signature,
+};
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{metadata::AsciiMetadataValue, transport::Endpoint, Request};
+
+/// Client bindings for the subset of the Yellowstone gRPC protocol used by this worker.
+#[allow(clippy::all)]
+mod proto {
+    tonic::include_proto!("geyser");
+}
+
+/// The name of the transaction filter registered with the Geyser endpoint.
+const FILTER_NAME: &str = "w3b2";
+
+/// A live worker that receives transaction updates from a Yellowstone gRPC (Geyser) stream.
+///
+/// This is an alternative to the WebSocket-based `LiveWorker` for operators with access to
+/// a Geyser endpoint. It subscribes to all successful, non-vote transactions that include
+/// the program id, parses their log messages into `BridgeEvent`s, and feeds the dispatcher
+/// in exactly the same way as the WebSocket worker.
+pub struct GeyserLiveWorker<R: SyncRpc + ?Sized> {
+    ctx: WorkerContext<R>,
+}
+
+impl<R: SyncRpc + ?Sized> GeyserLiveWorker<R> {
+    pub fn new(ctx: WorkerContext<R>) -> Self {
+        Self { ctx }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let solana = &self.ctx.config.solana;
+        let url = solana
+            .geyser_url
+            .clone()
+            .ok_or_else(|| anyhow!("`geyser-url` must be set when `live-source` is \"geyser\""))?;
+        let x_token = solana
+            .geyser_x_token
+            .as_deref()
+            .map(AsciiMetadataValue::try_from)
+            .transpose()?;
+
+        let channel = Endpoint::from_shared(url)?.connect().await?;
+        // `tonic::Status` is the error type mandated by the interceptor signature.
+        #[allow(clippy::result_large_err)]
+        let mut client = GeyserClient::with_interceptor(channel, move |mut req: Request<()>| {
+            if let Some(token) = x_token.clone() {
+                req.metadata_mut().insert("x-token", token);
+            }
+            Ok(req)
+        });
+
+        // The request stream is kept open for the lifetime of the subscription so that
+        // pings from the server can be answered (some load balancers require it).
+        let (request_tx, request_rx) = mpsc::channel(8);
+        request_tx.send(self.subscribe_request()).await?;
+        let mut stream = client
+            .subscribe(ReceiverStream::new(request_rx))
+            .await?
+            .into_inner();
+
+        tracing::info!("Geyser live worker connected, listening for transactions...");
+
+        loop {
+            tokio::select! {
+                msg = stream.message() => {
+                    let Some(update) = msg? else { break };
+                    match update.update_oneof {
+                        Some(UpdateOneof::Transaction(tx)) => {
+                            if let Err(e) = self.handle_transaction(tx).await {
+                                tracing::error!("Error handling Geyser transaction: {}", e);
+                            }
+                        }
+                        Some(UpdateOneof::Ping(_)) => {
+                            let pong = SubscribeRequest {
+                                ping: Some(SubscribeRequestPing { id: 1 }),
+                                ..Default::default()
+                            };
+                            let _ = request_tx.send(pong).await;
+                        }
+                        None => {}
+                    }
+                },
+                _ = self.ctx.shutdown_requested() => {
+                    tracing::info!("GeyserLiveWorker: shutdown signal received, exiting.");
+                    return Ok(());
+                },
+            }
+        }
+
+        tracing::warn!("Geyser stream closed by the server.");
+        Ok(())
+    }
+
+    /// Builds the subscription request filtered to the program id.
+    fn subscribe_request(&self) -> SubscribeRequest {
+        let filter = SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            failed: Some(false),
+            account_include: vec![self.ctx.config.solana.program_id.to_string()],
+            ..Default::default()
+        };
+        let commitment = match self.ctx.config.solana.commitment {
+            SolanaCommitmentLevel::Processed => CommitmentLevel::Processed,
+            SolanaCommitmentLevel::Confirmed => CommitmentLevel::Confirmed,
+            SolanaCommitmentLevel::Finalized => CommitmentLevel::Finalized,
+        };
+
+        SubscribeRequest {
+            transactions: HashMap::from([(FILTER_NAME.to_string(), filter)]),
+            commitment: Some(commitment as i32),
+            ping: None,
+        }
+    }
+
+    async fn handle_transaction(&self, update: SubscribeUpdateTransaction) -> Result<()> {
+        let slot = update.slot;
+        let Some(info) = update.transaction else {
+            return Ok(());
+        };
+
+        if slot <= self.ctx.storage.get_last_slot().await? {
+            return Ok(());
+        }
+
+        let signature = Signature::try_from(info.signature.as_slice())
+            .map_err(|_| anyhow!("Geyser update carried a malformed signature"))?
+            .to_string();
+        let logs = info.meta.map(|meta| meta.log_messages).unwrap_or_default();
+
+        let events_to_dispatch: Vec<BridgeEvent> =
+            parse_program_logs_for(&self.ctx.config.solana.program_id, &logs)
+                .into_iter()
+                .map(|mut event| {
+                    event.source = EventSource::Live;
+                    event.signature = Some(signature.clone());
+                    event.slot = slot;
+                    tracing::info!("[GEYSER] slot={} event={:?}", slot, &event);
+                    event
+                })
+                .collect();
+
+        self.ctx.count_events(&events_to_dispatch).await;
+        for event in events_to_dispatch {
+            self.ctx.dispatch(event).await;
+        }
+
+        self.ctx.storage.set_sync_state(slot, &signature).await?;
+        Ok(())
+    }
+}
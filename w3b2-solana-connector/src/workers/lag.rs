@@ -0,0 +1,163 @@
+use crate::workers::{rpc::SyncRpc, synchronizer::WorkerContext, watchdog::Health};
+use anyhow::Result;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tokio::{
+    sync::watch,
+    time::{interval_at, Duration, Instant, MissedTickBehavior},
+};
+
+/// A snapshot of how far the synchronizer is behind the cluster.
+///
+/// Published by the `LagMonitor` once per poll interval and read through
+/// [`EventManagerHandle::sync_status`](crate::workers::EventManagerHandle::sync_status).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncStatus {
+    /// The latest slot reported by the RPC node.
+    pub chain_slot: u64,
+    /// The newest slot up to which the synchronizer has processed every transaction.
+    pub processed_slot: u64,
+    /// `chain_slot - processed_slot`.
+    pub lag_slots: u64,
+    /// Whether the lag has exceeded `max_lag_slots` for at least `lag_alert_after_secs`.
+    pub stalled: bool,
+}
+
+/// Shares the synchronizer's progress between the workers, the `LagMonitor` and the
+/// `Watchdog`.
+pub(crate) struct LagTracker {
+    /// The chain slot observed at the start of the newest complete catch-up pass.
+    synced_slot: AtomicU64,
+    status: watch::Sender<SyncStatus>,
+    /// When the newest catch-up pass completed, or when the tracker was created.
+    pub(crate) progressed_at: Mutex<Instant>,
+    pub(crate) health: watch::Sender<Health>,
+}
+
+impl Default for LagTracker {
+    fn default() -> Self {
+        Self {
+            synced_slot: AtomicU64::new(0),
+            status: watch::channel(SyncStatus::default()).0,
+            progressed_at: Mutex::new(Instant::now()),
+            health: watch::channel(Health {
+                last_progress: SystemTime::now(),
+                healthy: true,
+            })
+            .0,
+        }
+    }
+}
+
+impl LagTracker {
+    /// Records that every transaction up to `slot` has been processed, even if none of
+    /// them belonged to the program. This is the heartbeat watched by the `Watchdog`.
+    pub fn record_synced(&self, slot: u64) {
+        self.synced_slot.fetch_max(slot, Ordering::SeqCst);
+        *self.progressed_at.lock().unwrap() = Instant::now();
+        let was_healthy = self.health.borrow().healthy;
+        self.health.send_replace(Health {
+            last_progress: SystemTime::now(),
+            healthy: true,
+        });
+        if !was_healthy {
+            tracing::info!(slot, "Synchronizer is making progress again.");
+        }
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<SyncStatus> {
+        self.status.subscribe()
+    }
+}
+
+/// Periodically compares the cluster's slot with the synchronizer's progress.
+///
+/// The progress is the newer of the persisted cursor and the last complete catch-up pass,
+/// so an idle program does not look like a stalled synchronizer. When the lag stays above
+/// `max_lag_slots` for `lag_alert_after_secs`, a warning is logged once and
+/// [`SyncStatus::stalled`] is raised until the lag drops back below the threshold.
+pub struct LagMonitor<R: SyncRpc + ?Sized> {
+    ctx: WorkerContext<R>,
+    /// When the lag first exceeded the threshold, if it still does.
+    lagging_since: Option<Instant>,
+}
+
+impl<R: SyncRpc + ?Sized> LagMonitor<R> {
+    pub fn new(ctx: WorkerContext<R>) -> Self {
+        Self {
+            ctx,
+            lagging_since: None,
+        }
+    }
+
+    pub fn sync_status(&self) -> watch::Receiver<SyncStatus> {
+        self.ctx.lag.subscribe()
+    }
+
+    pub async fn run(mut self) -> Result<()> {
+        let period = Duration::from_secs(self.ctx.config.synchronizer.poll_interval_secs.max(1));
+        let mut ticker = interval_at(Instant::now() + period, period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    if let Err(e) = self.check_lag().await {
+                        tracing::error!("Error during lag check: {}", e);
+                    }
+                },
+                _ = self.ctx.shutdown_requested() => {
+                    tracing::info!("LagMonitor: shutdown signal received, exiting.");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    async fn check_lag(&mut self) -> Result<()> {
+        let chain_slot = self.ctx.rpc_client.get_slot().await?;
+        let processed_slot = self
+            .ctx
+            .storage
+            .get_last_slot()
+            .await?
+            .max(self.ctx.lag.synced_slot.load(Ordering::SeqCst));
+        let lag_slots = chain_slot.saturating_sub(processed_slot);
+        tracing::debug!(chain_slot, processed_slot, lag_slots, "Synchronizer lag");
+
+        let synchronizer = &self.ctx.config.synchronizer;
+        let lagging = synchronizer
+            .max_lag_slots
+            .is_some_and(|max_lag_slots| lag_slots > max_lag_slots);
+        let was_stalled = self.ctx.lag.status.borrow().stalled;
+        let stalled = if lagging {
+            let since = *self.lagging_since.get_or_insert_with(Instant::now);
+            since.elapsed() >= Duration::from_secs(synchronizer.lag_alert_after_secs)
+        } else {
+            self.lagging_since = None;
+            false
+        };
+
+        if stalled && !was_stalled {
+            tracing::warn!(
+                chain_slot,
+                processed_slot,
+                lag_slots,
+                "Synchronizer is stalled: {} slots behind the cluster for over {}s",
+                lag_slots,
+                synchronizer.lag_alert_after_secs
+            );
+        } else if was_stalled && !stalled {
+            tracing::info!(lag_slots, "Synchronizer has caught up with the cluster.");
+        }
+
+        self.ctx.lag.status.send_replace(SyncStatus {
+            chain_slot,
+            processed_slot,
+            lag_slots,
+            stalled,
+        });
+        Ok(())
+    }
+}
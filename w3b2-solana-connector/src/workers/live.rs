@@ -1,6 +1,11 @@
 use crate::{
-    events::{try_parse_log, BridgeEvent, EventSource},
-    workers::synchronizer::WorkerContext,
+    config::LiveSource,
+    events::{parse_program_logs_for, BridgeEvent, DispatchRejectionReason, EventSource},
+    workers::{
+        catchup::{fetch_transaction, rejection_event},
+        rpc::SyncRpc,
+        synchronizer::WorkerContext,
+    },
 };
 use anyhow::Result;
 use solana_client::{
@@ -8,23 +13,150 @@ use solana_client::{
     rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
     rpc_response::{Response, RpcLogsResponse},
 };
-use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::{commitment_config::CommitmentConfig, transaction::TransactionError};
+use tokio::time::{interval, interval_at, sleep, timeout, Duration, Instant};
 use tokio_stream::StreamExt;
 
-pub struct LiveWorker {
-    ctx: WorkerContext,
+/// How long a WebSocket connection attempt may take before it counts as failed.
+const WS_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long `live_source = "auto"` waits before resubscribing after a failure.
+const WS_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Why a log subscription stopped.
+enum StreamEnd {
+    /// The connector is shutting down.
+    Shutdown,
+    /// The server closed the subscription.
+    Disconnected,
+}
+
+/// Receives real-time program logs over the WebSocket `logsSubscribe` stream.
+///
+/// With `live_source = "auto"`, the worker is a small state machine. It stays subscribed
+/// to the WebSocket, resubscribing whenever the subscription fails or drops. After
+/// `ws_failures_before_fallback` consecutive failures it falls back to polling: every
+/// `fallback_poll_interval_secs`, the `CatchupWorker` is asked to poll the program's
+/// signatures. Every `ws_upgrade_interval_secs`, the worker tries to reconnect, and goes
+/// back to the WebSocket once that succeeds.
+pub struct LiveWorker<R: SyncRpc + ?Sized> {
+    ctx: WorkerContext<R>,
 }
 
-impl LiveWorker {
-    pub fn new(ctx: WorkerContext) -> Self {
+impl<R: SyncRpc + ?Sized> LiveWorker<R> {
+    pub fn new(ctx: WorkerContext<R>) -> Self {
         Self { ctx }
     }
 
     pub async fn run(self) -> Result<()> {
+        if self.ctx.config.solana.live_source == LiveSource::Auto {
+            return self.run_auto().await;
+        }
         let client = PubsubClient::new(&self.ctx.config.solana.ws_url).await?;
+        self.stream_logs(&client).await?;
+        Ok(())
+    }
+
+    /// Runs the WebSocket subscription with the polling fallback of `live_source = "auto"`.
+    async fn run_auto(&self) -> Result<()> {
+        let max_failures = self.ctx.config.solana.ws_failures_before_fallback.max(1);
+        let mut failures = 0;
+        let mut reconnected = None;
+        loop {
+            let client = match reconnected.take() {
+                Some(client) => Ok(client),
+                None => self.connect().await,
+            };
+            let end = match client {
+                Ok(client) => self.stream_logs(&client).await,
+                Err(e) => Err(e),
+            };
+            match end {
+                Ok(StreamEnd::Shutdown) => return Ok(()),
+                Ok(StreamEnd::Disconnected) => {
+                    // The subscription worked until now, so this is the first failure.
+                    failures = 1;
+                    tracing::warn!("WebSocket subscription was closed by the server.");
+                }
+                Err(e) => {
+                    failures += 1;
+                    tracing::warn!(
+                        "WebSocket subscription failed ({}/{}): {}",
+                        failures,
+                        max_failures,
+                        e
+                    );
+                }
+            }
+
+            if failures >= max_failures {
+                match self.poll_until_reconnected().await {
+                    Some(client) => {
+                        reconnected = Some(client);
+                        failures = 0;
+                    }
+                    None => return Ok(()),
+                }
+            } else {
+                tokio::select! {
+                    _ = sleep(WS_RECONNECT_DELAY) => {},
+                    _ = self.ctx.shutdown_requested() => return Ok(()),
+                }
+            }
+        }
+    }
+
+    /// Connects to `ws_url`, giving up after [`WS_CONNECT_TIMEOUT`].
+    async fn connect(&self) -> Result<PubsubClient> {
+        let url = &self.ctx.config.solana.ws_url;
+        match timeout(WS_CONNECT_TIMEOUT, PubsubClient::new(url)).await {
+            Ok(client) => Ok(client?),
+            Err(_) => Err(anyhow::anyhow!("connecting to {} timed out", url)),
+        }
+    }
+
+    /// Asks the `CatchupWorker` for a poll every `fallback_poll_interval_secs` until the
+    /// WebSocket can be reached again. Returns `None` once the connector shuts down.
+    async fn poll_until_reconnected(&self) -> Option<PubsubClient> {
+        let solana = &self.ctx.config.solana;
+        let poll_period = Duration::from_secs(solana.fallback_poll_interval_secs.max(1));
+        let upgrade_period = Duration::from_secs(solana.ws_upgrade_interval_secs.max(1));
+        tracing::warn!(
+            "WebSocket at {} is unavailable, falling back to polling every {}s.",
+            solana.ws_url,
+            poll_period.as_secs()
+        );
+
+        let mut poll = interval(poll_period);
+        let mut upgrade = interval_at(Instant::now() + upgrade_period, upgrade_period);
+        loop {
+            tokio::select! {
+                _ = poll.tick() => self.ctx.poll_requests.notify_one(),
+                _ = upgrade.tick() => match self.connect().await {
+                    Ok(client) => {
+                        tracing::info!("WebSocket at {} is reachable again, leaving polling mode.", solana.ws_url);
+                        return Some(client);
+                    }
+                    Err(e) => tracing::debug!("WebSocket is still unavailable: {}", e),
+                },
+                _ = self.ctx.shutdown_requested() => {
+                    tracing::info!("LiveWorker: shutdown signal received, exiting.");
+                    return None;
+                },
+            }
+        }
+    }
+
+    /// Subscribes to the program's logs on `client` and handles them until the
+    /// subscription ends or the connector shuts down.
+    async fn stream_logs(&self, client: &PubsubClient) -> Result<StreamEnd> {
         let (mut stream, _) = client
             .logs_subscribe(
-                RpcTransactionLogsFilter::Mentions(vec![w3b2_solana_program::ID.to_string()]),
+                RpcTransactionLogsFilter::Mentions(vec![self
+                    .ctx
+                    .config
+                    .solana
+                    .program_id
+                    .to_string()]),
                 RpcTransactionLogsConfig {
                     commitment: Some(CommitmentConfig { commitment: self.ctx.config.solana.commitment }),
                 },
@@ -40,14 +172,13 @@ impl LiveWorker {
                         tracing::error!("Error handling log message: {}", e);
                     }
                 },
-                _ = self.ctx.dispatcher.command_tx.closed() => {
+                _ = self.ctx.shutdown_requested() => {
                     tracing::info!("LiveWorker: shutdown signal received, exiting.");
-                    return Ok(());
+                    return Ok(StreamEnd::Shutdown);
                 },
-                else => break,
+                else => return Ok(StreamEnd::Disconnected),
             }
         }
-        Ok(())
     }
 
     async fn handle_log_message(&self, msg: Response<RpcLogsResponse>) -> Result<()> {
@@ -58,22 +189,59 @@ impl LiveWorker {
             return Ok(());
         }
 
-        let events_to_dispatch: Vec<BridgeEvent> = value
-            .logs
-            .into_iter()
-            .filter_map(|log| try_parse_log(&log).ok())
-            .map(|mut event| {
-                event.source = EventSource::Live;
-                tracing::info!("[LIVE] slot={} event={:?}", slot, &event);
-                event
-            })
-            .collect();
+        // A failed transaction has no events: anything it logged was rolled back.
+        let logs: &[String] = if value.err.is_none() {
+            &value.logs
+        } else {
+            &[]
+        };
+        let mut events_to_dispatch: Vec<BridgeEvent> =
+            parse_program_logs_for(&self.ctx.config.solana.program_id, logs)
+                .into_iter()
+                .map(|mut event| {
+                    event.source = EventSource::Live;
+                    event.signature = Some(value.signature.clone());
+                    event.slot = slot;
+                    tracing::info!("[LIVE] slot={} event={:?}", slot, &event);
+                    event
+                })
+                .collect();
+        if let Some(err) = &value.err {
+            events_to_dispatch.extend(self.dispatch_rejection(&value.signature, err).await);
+        }
 
+        self.ctx.count_events(&events_to_dispatch).await;
         for event in events_to_dispatch {
-            self.ctx.dispatcher.dispatch(event).await;
+            self.ctx.dispatch(event).await;
         }
 
         self.ctx.storage.set_sync_state(slot, &value.signature).await?;
         Ok(())
     }
+
+    /// Fetches a transaction that failed an oracle check to build its `DispatchRejected`
+    /// event. The log notification carries the error, but not the accounts of the command.
+    async fn dispatch_rejection(
+        &self,
+        signature: &str,
+        err: &TransactionError,
+    ) -> Option<BridgeEvent> {
+        if !self.ctx.config.synchronizer.report_dispatch_rejections
+            || DispatchRejectionReason::from_transaction_error(err).is_none()
+        {
+            return None;
+        }
+        match fetch_transaction(&self.ctx, signature).await {
+            Ok(tx) => rejection_event(
+                &self.ctx.config.solana.program_id,
+                signature,
+                &tx,
+                EventSource::Live,
+            ),
+            Err(e) => {
+                tracing::warn!("Failed to fetch rejected transaction {}: {}", signature, e);
+                None
+            }
+        }
+    }
 }
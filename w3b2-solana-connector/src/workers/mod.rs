@@ -12,25 +12,76 @@
 //! - **Workers**:
 //!   - `Synchronizer`: Continuously fetches and stores transaction signatures for all PDAs.
 //!   - `LiveWorker`: Subscribes to a WebSocket stream for real-time transaction updates.
-//!   - `CatchupWorker`: Fetches historical transactions for newly registered listeners.
+//!   - `GeyserLiveWorker`: An alternative to `LiveWorker` that consumes a Yellowstone gRPC
+//!     transaction stream (enabled with the `geyser` feature and `live_source = "geyser"`).
+//!   - `CatchupWorker`: Fills gaps in the live stream and replays the history of each newly
+//!     registered listener's PDA, ending it with a [`CaughtUp`](crate::events::CaughtUp) marker.
+//!   - `LagMonitor`: Compares the cluster's slot with the synchronizer's progress every poll
+//!     interval, publishes a [`SyncStatus`] and warns when the synchronizer stalls.
+//!   - `Watchdog`: Reports the synchronizer as unhealthy in its [`Health`] when no catch-up
+//!     pass has completed for `synchronizer.watchdog_timeout_secs`, e.g. because a worker
+//!     is wedged on an RPC call.
+//!   - `ReorgWorker`: Optional (`synchronizer.detect_reorgs`). Re-checks delivered, not yet
+//!     finalized transactions and emits [`Retracted`](crate::events::Retracted) for dropped ones.
 //!   - `Dispatcher`: Routes events from the workers to the correct listeners.
 //!
+//! ## RPC Transport
+//!
+//! The workers reach the Solana cluster through the [`SyncRpc`] trait rather than a
+//! concrete client. It is implemented for the nonblocking `RpcClient`, which is the
+//! default, but any transport implementing the trait can be supplied to [`EventManager::new`].
+//!
+//! With the `metrics` feature, the transport is wrapped in a
+//! [`CountingRpc`](crate::rpc_stats::CountingRpc) and the calls of the workers can be read
+//! through [`EventManagerHandle::rpc_stats`].
+//!
+//! ## Testing
+//!
+//! With the `test-util` feature, [`EventManager::new_with_source`] creates an `EventManager`
+//! whose workers are replaced by a fixed script of events, so that code consuming listeners
+//! can be tested without a cluster or an RPC mock.
+//!
 
 mod catchup;
+#[cfg(feature = "geyser")]
+mod geyser;
+mod lag;
 mod live;
+mod reorg;
+mod rpc;
+#[cfg(feature = "test-util")]
+mod scripted;
 mod synchronizer;
+mod watchdog;
+
+pub use lag::SyncStatus;
+pub use rpc::SyncRpc;
+pub use watchdog::Health;
 
+#[cfg(feature = "metrics")]
+use crate::rpc_stats::{CountingRpc, RpcStats};
 use crate::{
     config::ConnectorConfig,
     dispatcher::{Dispatcher, DispatcherHandle},
-    listener::{AdminListener, UserListener},
+    error::ConnectorError,
+    fanout::FanOuts,
+    listener::{
+        AdminListener, BroadcastReceiver, CategorizedListener, EventListener, HistoryOptions,
+        OverflowPolicy, UserListener,
+    },
     storage::Storage,
     workers::synchronizer::Synchronizer,
 };
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
-use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{mpsc, watch};
+
+/// The transport the workers are run with: the supplied one, counted with `metrics`.
+#[cfg(feature = "metrics")]
+type WorkerRpc<R> = CountingRpc<R>;
+#[cfg(not(feature = "metrics"))]
+type WorkerRpc<R> = R;
 
 /// A clonable, thread-safe handle for interacting with the `EventManager`'s background services.
 ///
@@ -40,14 +91,88 @@ use tokio::sync::mpsc;
 pub struct EventManagerHandle {
     dispatcher: DispatcherHandle,
     config: Arc<ConnectorConfig>,
+    /// Raised by [`stop`](Self::stop) to ask the workers to finish their current work.
+    shutdown: Arc<watch::Sender<bool>>,
+    stopped: watch::Receiver<bool>,
+    sync_status: watch::Receiver<SyncStatus>,
+    health: watch::Receiver<Health>,
+    /// The overflow policy of the listeners created through this handle.
+    overflow: OverflowPolicy,
+    /// The shared upstream listeners of [`broadcast_subscribe`](Self::broadcast_subscribe).
+    fan_outs: FanOuts,
+    /// The counters of the workers' RPC calls.
+    #[cfg(feature = "metrics")]
+    rpc_stats: RpcStats,
 }
 
 impl EventManagerHandle {
-    /// Sends a shutdown signal to the `EventManager`'s background services.
+    /// Shuts down the `EventManager`'s background services and waits for them to exit.
     ///
-    /// This will cause the `Dispatcher` and `Synchronizer` to gracefully terminate their loops.
+    /// The shutdown is ordered: the workers of the `Synchronizer` are asked to stop taking
+    /// new work and finish the transaction they are processing, which persists its cursor.
+    /// Only once they have all exited is the `Dispatcher` stopped, so the events of that
+    /// transaction still reach the listeners. This resolves after both have left their
+    /// loops. If the `EventManager` was never run, this returns immediately.
     pub async fn stop(&self) {
-        self.dispatcher.stop().await;
+        self.shutdown.send_replace(true);
+        let mut stopped = self.stopped.clone();
+        // An error means the `EventManager` was dropped, so there is nothing left to wait for.
+        let _ = stopped.wait_for(|stopped| *stopped).await;
+    }
+
+    /// Returns a receiver for the synchronizer's lag, updated once per poll interval.
+    ///
+    /// [`SyncStatus::stalled`] is raised when the synchronizer has been more than
+    /// `synchronizer.max_lag_slots` behind the cluster for `synchronizer.lag_alert_after_secs`.
+    /// Until the first check completes, the status is all zeros.
+    pub fn sync_status(&self) -> watch::Receiver<SyncStatus> {
+        self.sync_status.clone()
+    }
+
+    /// Returns a receiver for the synchronizer's liveness, including when it last made
+    /// progress.
+    ///
+    /// [`Health::healthy`] is cleared by the watchdog once no catch-up pass has completed
+    /// for `synchronizer.watchdog_timeout_secs`, which usually means that a worker is stuck,
+    /// and raised again by the next completed pass.
+    pub fn health(&self) -> watch::Receiver<Health> {
+        self.health.clone()
+    }
+
+    /// Returns the counters of the RPC calls made by the workers, per method.
+    ///
+    /// The counters are shared and keep counting after this returns. Wrap the client of a
+    /// `TransactionBuilder` in a [`CountingRpc`] with these counters to count its calls too.
+    #[cfg(feature = "metrics")]
+    pub fn rpc_stats(&self) -> RpcStats {
+        self.rpc_stats.clone()
+    }
+
+    /// Returns a handle whose listeners apply `policy` when their live channel is full,
+    /// instead of the default [`OverflowPolicy::Block`].
+    ///
+    /// Listeners of different consumers can use different policies, e.g. `DropOldest` for
+    /// an analytics feed that prefers fresh events and `Block` for billing. Categorized
+    /// listeners always drop new events.
+    pub fn with_overflow_policy(&self, policy: OverflowPolicy) -> Self {
+        Self {
+            overflow: policy,
+            ..self.clone()
+        }
+    }
+
+    fn listener(
+        &self,
+        pda: Pubkey,
+        history: Option<HistoryOptions>,
+    ) -> Result<EventListener, ConnectorError> {
+        EventListener::register(
+            pda,
+            self.dispatcher.clone(),
+            self.config.channels.listener_event_buffer,
+            history,
+            self.overflow,
+        )
     }
 
     /// Creates and returns a contextual listener for a `UserProfile` PDA.
@@ -57,12 +182,34 @@ impl EventManagerHandle {
     /// # Arguments
     ///
     /// * `user_profile_pda` - The public key of the user's profile PDA to monitor.
-    pub fn listen_as_user(&self, user_profile_pda: Pubkey) -> UserListener {
-        UserListener::new(
-            user_profile_pda,
-            self.dispatcher.clone(),
-            self.config.channels.listener_event_buffer,
-        )
+    ///
+    /// # Errors
+    ///
+    /// Like every `listen_*` method, fails with [`ConnectorError::TooManyListeners`] while
+    /// the dispatcher already serves `channels.max_listeners` listeners.
+    pub fn listen_as_user(&self, user_profile_pda: Pubkey) -> Result<UserListener, ConnectorError> {
+        self.listener(user_profile_pda, Some(HistoryOptions::default()))
+    }
+
+    /// Creates a listener for a `UserProfile` PDA whose history is replayed according to
+    /// `history`. See [`HistoryOptions`].
+    pub fn listen_as_user_with_history(
+        &self,
+        user_profile_pda: Pubkey,
+        history: HistoryOptions,
+    ) -> Result<UserListener, ConnectorError> {
+        self.listener(user_profile_pda, Some(history))
+    }
+
+    /// Creates a listener for a `UserProfile` PDA that only receives live events.
+    ///
+    /// No history is replayed for it, so it never triggers catch-up work. See
+    /// [`EventListener::new_live_only`](crate::listener::EventListener::new_live_only).
+    pub fn listen_as_user_live_only(
+        &self,
+        user_profile_pda: Pubkey,
+    ) -> Result<UserListener, ConnectorError> {
+        self.listener(user_profile_pda, None)
     }
 
     /// Creates and returns a contextual listener for an `AdminProfile` PDA.
@@ -70,13 +217,67 @@ impl EventManagerHandle {
     /// # Arguments
     ///
     /// * `admin_profile_pda` - The public key of the admin's profile PDA to monitor.
-    pub fn listen_as_admin(&self, admin_profile_pda: Pubkey) -> AdminListener {
-        AdminListener::new(
-            admin_profile_pda,
+    pub fn listen_as_admin(
+        &self,
+        admin_profile_pda: Pubkey,
+    ) -> Result<AdminListener, ConnectorError> {
+        self.listener(admin_profile_pda, Some(HistoryOptions::default()))
+    }
+
+    /// Creates a listener for an `AdminProfile` PDA whose history is replayed according to
+    /// `history`. See [`HistoryOptions`].
+    pub fn listen_as_admin_with_history(
+        &self,
+        admin_profile_pda: Pubkey,
+        history: HistoryOptions,
+    ) -> Result<AdminListener, ConnectorError> {
+        self.listener(admin_profile_pda, Some(history))
+    }
+
+    /// Creates a listener for an `AdminProfile` PDA that only receives live events.
+    ///
+    /// No history is replayed for it, so it never triggers catch-up work. See
+    /// [`EventListener::new_live_only`](crate::listener::EventListener::new_live_only).
+    pub fn listen_as_admin_live_only(
+        &self,
+        admin_profile_pda: Pubkey,
+    ) -> Result<AdminListener, ConnectorError> {
+        self.listener(admin_profile_pda, None)
+    }
+
+    /// Creates a live-only listener for a `UserProfile` or `AdminProfile` PDA that receives
+    /// the events of each [`EventCategory`](crate::events::EventCategory) on a separate
+    /// stream. Every stream buffers up to `listener_event_buffer` events.
+    pub fn listen_by_category(&self, pda: Pubkey) -> Result<CategorizedListener, ConnectorError> {
+        CategorizedListener::new(
+            pda,
             self.dispatcher.clone(),
             self.config.channels.listener_event_buffer,
         )
     }
+
+    /// Returns a receiver for the live events of a `UserProfile` or `AdminProfile` PDA that
+    /// shares a single upstream listener with every other receiver of the same PDA.
+    ///
+    /// Suits several in-process consumers that each need every event of the same PDAs,
+    /// without multiplying the work of the dispatcher. No history is replayed. Each
+    /// receiver buffers up to `broadcast_capacity` events; one that falls further behind
+    /// lags instead of holding back the others, and recovers the events it missed through
+    /// a catch-up of the PDA, see [`BroadcastReceiver`]. The upstream listener is torn down
+    /// as soon as the last receiver is dropped. Only starting the upstream listener can fail.
+    pub fn broadcast_subscribe(&self, pda: Pubkey) -> Result<BroadcastReceiver, ConnectorError> {
+        let rx = self.fan_outs.subscribe(
+            pda,
+            &self.dispatcher,
+            self.config.channels.broadcast_capacity,
+        )?;
+        Ok(BroadcastReceiver::new(
+            pda,
+            rx,
+            self.dispatcher.clone(),
+            self.config.channels.listener_event_buffer,
+        ))
+    }
 }
 
 /// The main background service manager for the connector.
@@ -84,12 +285,103 @@ impl EventManagerHandle {
 /// This struct orchestrates the `Synchronizer` and `Dispatcher` workers. It is created once,
 /// its [`run()`] method is spawned as a background task, and it is then consumed, leaving
 /// the [`EventManagerHandle`] as the only way to interact with the running services.
-pub struct EventManager {
-    synchronizer: Synchronizer,
+pub struct EventManager<R: SyncRpc + ?Sized = RpcClient> {
+    source: Source<R>,
     dispatcher: Dispatcher,
+    /// Stops the dispatcher once the source has exited.
+    control: DispatcherHandle,
+    shutdown: Arc<watch::Sender<bool>>,
+    stopped_tx: watch::Sender<bool>,
+}
+
+/// How long the source may take to finish its current work after a shutdown was requested,
+/// before the dispatcher is stopped regardless. A worker can only overrun it while the
+/// dispatcher is blocked on a listener that stopped reading.
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Resolves once `shutdown` is raised. Never resolves if its sender is gone.
+pub(crate) async fn shutdown_signalled(mut shutdown: watch::Receiver<bool>) {
+    if shutdown.wait_for(|requested| *requested).await.is_err() {
+        std::future::pending::<()>().await;
+    }
 }
 
+#[cfg(feature = "test-util")]
 impl EventManager {
+    /// Creates an `EventManager` that dispatches `scripted_events` instead of running the
+    /// synchronizer, for testing code that consumes listeners without any RPC.
+    ///
+    /// The events are dispatched in order, each once a listener is registered for the PDA
+    /// it is paired with, and reach every listener of the PDAs they involve, as they would
+    /// from the cluster. Catch-up streams replay an empty history, ending with a
+    /// [`CaughtUp`](crate::events::CaughtUp) marker at slot `0`. The returned handle
+    /// behaves as usual; its [`SyncStatus`] reads as zero and its [`Health`] as healthy.
+    /// The default configuration and an in-memory storage are used.
+    pub fn new_with_source(
+        scripted_events: Vec<(Pubkey, crate::events::BridgeEvent)>,
+    ) -> (Self, EventManagerHandle) {
+        let config = Arc::new(ConnectorConfig::default());
+        let shutdown = Arc::new(watch::channel(false).0);
+        let (dispatcher_cmd_tx, dispatcher_cmd_rx) =
+            mpsc::channel(config.channels.dispatcher_command_buffer);
+        let (catchup_tx, catchup_rx) = mpsc::channel(config.channels.dispatcher_command_buffer);
+        let (dispatcher, dispatcher_handle) = Dispatcher::new(
+            config.clone(),
+            dispatcher_cmd_tx,
+            dispatcher_cmd_rx,
+            catchup_tx,
+            Arc::new(scripted::ScriptedStorage),
+        );
+
+        let script = scripted::ScriptedSource::new(
+            scripted_events,
+            dispatcher_handle.clone(),
+            catchup_rx,
+            shutdown.subscribe(),
+        );
+        let sync_status = script.sync_status();
+        let health = script.health();
+        Self::assemble(
+            config,
+            Source::Scripted(script),
+            shutdown,
+            dispatcher,
+            dispatcher_handle,
+            sync_status,
+            health,
+            #[cfg(feature = "metrics")]
+            RpcStats::new(),
+        )
+    }
+}
+
+/// Where the events dispatched by an [`EventManager`] come from.
+#[allow(clippy::large_enum_variant)]
+enum Source<R: SyncRpc + ?Sized> {
+    Synchronizer(Synchronizer<WorkerRpc<R>>),
+    #[cfg(feature = "test-util")]
+    Scripted(scripted::ScriptedSource),
+}
+
+impl<R: SyncRpc + ?Sized + 'static> Source<R> {
+    async fn load_active_pdas(&mut self) {
+        match self {
+            Self::Synchronizer(synchronizer) => synchronizer.load_active_pdas().await,
+            #[cfg(feature = "test-util")]
+            Self::Scripted(_) => {}
+        }
+    }
+
+    async fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::Synchronizer(synchronizer) => synchronizer.run().await,
+            #[cfg(feature = "test-util")]
+            Self::Scripted(script) => script.run().await,
+        }
+    }
+}
+
+impl<R: SyncRpc + ?Sized + 'static> EventManager<R> {
     /// Creates a new `EventManager` and its associated [`EventManagerHandle`].
     ///
     /// This method sets up the necessary communication channels between the internal workers
@@ -99,7 +391,7 @@ impl EventManager {
     /// # Arguments
     ///
     /// * `config` - The shared connector configuration.
-    /// * `rpc_client` - A shared Solana RPC client.
+    /// * `rpc_client` - A shared RPC transport implementing [`SyncRpc`], typically an `RpcClient`.
     /// * `storage` - A shared, thread-safe storage backend for persisting sync state.
     ///
     /// # Returns
@@ -107,30 +399,84 @@ impl EventManager {
     /// A tuple containing the `EventManager` runner and its public `EventManagerHandle`.
     pub fn new(
         config: Arc<ConnectorConfig>,
-        rpc_client: Arc<RpcClient>,
+        rpc_client: Arc<R>,
         storage: Arc<dyn Storage>,
     ) -> (Self, EventManagerHandle) {
+        let shutdown = Arc::new(watch::channel(false).0);
         let (dispatcher_cmd_tx, dispatcher_cmd_rx) =
             mpsc::channel(config.channels.dispatcher_command_buffer);
 
-        let (dispatcher, dispatcher_handle) =
-            Dispatcher::new(config.clone(), dispatcher_cmd_tx, dispatcher_cmd_rx);
+        let (catchup_tx, catchup_rx) = mpsc::channel(config.channels.dispatcher_command_buffer);
+
+        let (dispatcher, dispatcher_handle) = Dispatcher::new(
+            config.clone(),
+            dispatcher_cmd_tx,
+            dispatcher_cmd_rx,
+            catchup_tx,
+            storage.clone(),
+        );
+
+        #[cfg(feature = "metrics")]
+        let rpc_stats = RpcStats::new();
+        #[cfg(feature = "metrics")]
+        let rpc_client = Arc::new(CountingRpc::new(rpc_client, rpc_stats.clone()));
 
         let synchronizer = Synchronizer::new(
             config.clone(),
             rpc_client,
             storage,
             dispatcher_handle.clone(),
+            catchup_rx,
+            shutdown.subscribe(),
         );
 
+        let sync_status = synchronizer.sync_status();
+        let health = synchronizer.health();
+        Self::assemble(
+            config,
+            Source::Synchronizer(synchronizer),
+            shutdown,
+            dispatcher,
+            dispatcher_handle,
+            sync_status,
+            health,
+            #[cfg(feature = "metrics")]
+            rpc_stats,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn assemble(
+        config: Arc<ConnectorConfig>,
+        source: Source<R>,
+        shutdown: Arc<watch::Sender<bool>>,
+        dispatcher: Dispatcher,
+        dispatcher_handle: DispatcherHandle,
+        sync_status: watch::Receiver<SyncStatus>,
+        health: watch::Receiver<Health>,
+        #[cfg(feature = "metrics")] rpc_stats: RpcStats,
+    ) -> (Self, EventManagerHandle) {
+        let (stopped_tx, stopped_rx) = watch::channel(false);
+
         let runner = Self {
-            synchronizer,
+            source,
             dispatcher,
+            control: dispatcher_handle.clone(),
+            shutdown: shutdown.clone(),
+            stopped_tx,
         };
 
         let handle = EventManagerHandle {
             dispatcher: dispatcher_handle,
             config,
+            shutdown,
+            stopped: stopped_rx,
+            sync_status,
+            health,
+            overflow: OverflowPolicy::default(),
+            fan_outs: FanOuts::default(),
+            #[cfg(feature = "metrics")]
+            rpc_stats,
         };
 
         (runner, handle)
@@ -141,18 +487,67 @@ impl EventManager {
     /// This method consumes the `EventManager` and should be spawned as a single, long-running
     /// background task. It will run until a shutdown is initiated via [`EventManagerHandle::stop()`]
     /// or a critical error occurs in one of the workers.
+    ///
+    /// Once a shutdown is requested, the `Synchronizer` finishes its current work and exits
+    /// first, so its last cursor write lands while the `Dispatcher` still delivers its events.
+    /// The `Dispatcher` is stopped after that, or after `SHUTDOWN_GRACE_PERIOD` if a worker
+    /// is stuck; a `Synchronizer` that is still running one grace period later is aborted.
+    /// If the `Dispatcher` exits on its own, the workers see its closed command
+    /// channel and exit as well.
     pub async fn run(self) {
         tracing::info!("Connector is running all background services.");
 
+        // Read before the dispatcher records the PDAs of new listeners, whose own replays
+        // would otherwise be repeated by the pre-warm.
+        let mut source = self.source;
+        source.load_active_pdas().await;
+        let control = self.control;
+        let synchronizer = async {
+            match source.run().await {
+                Err(e) => tracing::error!("Synchronizer exited with an error: {}", e),
+                Ok(()) => tracing::info!("Synchronizer has shut down."),
+            }
+        };
+        let dispatcher = async {
+            if let Err(e) = self.dispatcher.run().await {
+                tracing::error!("Dispatcher exited with an error: {}", e);
+            }
+            tracing::info!("Dispatcher has shut down.");
+        };
+        let grace_elapsed = async {
+            shutdown_signalled(self.shutdown.subscribe()).await;
+            tokio::time::sleep(SHUTDOWN_GRACE_PERIOD).await;
+        };
+        tokio::pin!(synchronizer, dispatcher);
+
         tokio::select! {
-            res = self.synchronizer.run() => {
-                if let Err(e) = res { tracing::error!("Synchronizer exited with an error: {}", e); }
-                else { tracing::info!("Synchronizer has shut down."); }
+            _ = &mut synchronizer => {
+                // Every worker has left its loop, so no cursor write is in flight anymore.
+                control.stop().await;
+                dispatcher.await;
+            },
+            _ = &mut dispatcher => {
+                // The dispatcher's command channel is now closed, which tells every worker to exit.
+                synchronizer.await;
             },
-            res = self.dispatcher.run() => {
-                if let Err(e) = res { tracing::error!("Dispatcher exited with an error: {}", e); }
-                tracing::info!("Dispatcher has shut down.");
+            _ = grace_elapsed => {
+                tracing::warn!(
+                    "Synchronizer did not shut down within {:?}; stopping the dispatcher.",
+                    SHUTDOWN_GRACE_PERIOD
+                );
+                control.stop().await;
+                dispatcher.await;
+                // A worker stuck in an RPC call may never see the closed channel, so the
+                // synchronizer gets one more grace period before it is dropped.
+                if tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, synchronizer).await.is_err() {
+                    tracing::warn!(
+                        "Synchronizer did not exit within {:?} after the dispatcher stopped; aborting it.",
+                        SHUTDOWN_GRACE_PERIOD
+                    );
+                }
             }
         }
+
+        let _ = self.stopped_tx.send(true);
     }
 }
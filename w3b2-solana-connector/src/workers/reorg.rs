@@ -0,0 +1,171 @@
+use crate::{
+    dispatcher::extract_pdas_from_event,
+    events::{BridgeEvent, BridgeEventData, EventSource, Retracted},
+    workers::{rpc::SyncRpc, synchronizer::WorkerContext},
+};
+use anyhow::Result;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::TransactionConfirmationStatus;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::time::{interval_at, Duration, Instant, MissedTickBehavior};
+
+/// The maximum number of signatures accepted by a single `getSignatureStatuses` call.
+const MAX_STATUSES_PER_CALL: usize = 256;
+
+/// The number of consecutive checks a signature must be missing before it is retracted.
+/// A single miss may just mean the RPC node has not caught up yet.
+const MISSES_BEFORE_RETRACT: u8 = 2;
+
+/// A delivered transaction that has not been finalized yet.
+struct PendingTx {
+    slot: u64,
+    pdas: Vec<Pubkey>,
+    misses: u8,
+}
+
+/// Remembers the transactions whose events have been dispatched until they are finalized.
+#[derive(Default)]
+pub(crate) struct ReorgTracker {
+    pending: Mutex<HashMap<String, PendingTx>>,
+}
+
+impl ReorgTracker {
+    /// Records that `event` has been delivered to the listeners of its PDAs.
+    pub fn track(&self, event: &BridgeEvent) {
+        let Some(signature) = &event.signature else {
+            return;
+        };
+        let mut pending = self.pending.lock().unwrap();
+        let entry = pending
+            .entry(signature.clone())
+            .or_insert_with(|| PendingTx {
+                slot: event.slot,
+                pdas: Vec::new(),
+                misses: 0,
+            });
+        for pda in extract_pdas_from_event(&event.data) {
+            if !entry.pdas.contains(&pda) {
+                entry.pdas.push(pda);
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Vec<String> {
+        self.pending.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+/// Periodically re-checks the tracked transactions and retracts the ones dropped by a fork.
+///
+/// A transaction stops being tracked once it is finalized. If the cluster no longer knows a
+/// tracked signature, a [`Retracted`] event is dispatched to every PDA that received events
+/// from it. If the signature reappears in a different slot, the transaction was re-included
+/// on the surviving fork and its events remain valid. Retracted events are also evicted
+/// from the storage's event cache.
+pub struct ReorgWorker<R: SyncRpc + ?Sized> {
+    ctx: WorkerContext<R>,
+    tracker: Arc<ReorgTracker>,
+}
+
+impl<R: SyncRpc + ?Sized> ReorgWorker<R> {
+    pub fn new(ctx: WorkerContext<R>, tracker: Arc<ReorgTracker>) -> Self {
+        Self { ctx, tracker }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let period = Duration::from_secs(self.ctx.config.synchronizer.poll_interval_secs.max(1));
+        let mut ticker = interval_at(Instant::now() + period, period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    if let Err(e) = self.check_pending().await {
+                        tracing::error!("Error during reorg check: {}", e);
+                    }
+                },
+                _ = self.ctx.shutdown_requested() => {
+                    tracing::info!("ReorgWorker: shutdown signal received, exiting.");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    async fn check_pending(&self) -> Result<()> {
+        let signatures = self.tracker.snapshot();
+        for chunk in signatures.chunks(MAX_STATUSES_PER_CALL) {
+            let parsed = chunk
+                .iter()
+                .map(|sig| sig.parse::<Signature>())
+                .collect::<Result<Vec<_>, _>>()?;
+            let statuses = self.ctx.rpc_client.get_signature_statuses(&parsed).await?;
+
+            let mut retracted = Vec::new();
+            {
+                let mut pending = self.tracker.pending.lock().unwrap();
+                for (signature, status) in chunk.iter().zip(statuses) {
+                    let Some(tx) = pending.get_mut(signature) else {
+                        continue;
+                    };
+                    match status {
+                        None => {
+                            tx.misses += 1;
+                            if tx.misses >= MISSES_BEFORE_RETRACT {
+                                let tx = pending.remove(signature).expect("entry exists");
+                                retracted.push((signature.clone(), tx));
+                            }
+                        }
+                        Some(status)
+                            if status.confirmation_status
+                                == Some(TransactionConfirmationStatus::Finalized) =>
+                        {
+                            pending.remove(signature);
+                        }
+                        Some(status) => {
+                            if status.slot != tx.slot {
+                                tracing::warn!(
+                                    "Transaction {} moved from slot {} to slot {}",
+                                    signature,
+                                    tx.slot,
+                                    status.slot
+                                );
+                                tx.slot = status.slot;
+                            }
+                            tx.misses = 0;
+                        }
+                    }
+                }
+            }
+
+            for (signature, tx) in retracted {
+                tracing::warn!(
+                    "Transaction {} was dropped by a fork, retracting its events.",
+                    signature
+                );
+                if let Err(e) = self
+                    .ctx
+                    .storage
+                    .evict_cached_events(&tx.pdas, &signature)
+                    .await
+                {
+                    tracing::warn!("Failed to evict cached events of {}: {}", signature, e);
+                }
+                let event = BridgeEvent {
+                    source: EventSource::Live,
+                    signature: Some(signature.clone()),
+                    slot: tx.slot,
+                    data: BridgeEventData::Retracted(Retracted {
+                        signature,
+                        pdas: tx.pdas,
+                    }),
+                };
+                self.ctx.dispatcher.dispatch(event).await;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use solana_client::{
+    client_error::ClientError, nonblocking::rpc_client::RpcClient,
+    rpc_client::GetConfirmedSignaturesForAddress2Config, rpc_config::RpcTransactionConfig,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, TransactionStatus};
+
+/// A trait abstracting over the RPC calls the synchronization workers depend on.
+///
+/// The workers only need a small, read-only slice of the Solana JSON-RPC API. Keeping
+/// that slice behind a trait allows the [`Synchronizer`](super::synchronizer::Synchronizer)
+/// to run against the live `RpcClient`, a custom transport (e.g. a load-balancing client),
+/// or an in-memory mock in tests.
+#[async_trait]
+pub trait SyncRpc: Send + Sync {
+    /// Fetches a page of confirmed transaction signatures involving `address`,
+    /// ordered from newest to oldest.
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        config: GetConfirmedSignaturesForAddress2Config,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, ClientError>;
+
+    /// Fetches a confirmed transaction together with its status metadata and logs.
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, ClientError>;
+
+    /// Fetches the slot that has reached the client's configured commitment level.
+    async fn get_slot(&self) -> Result<u64, ClientError>;
+
+    /// Fetches the statuses of up to 256 transactions, searching the full transaction
+    /// history. A `None` entry means the cluster does not know the signature.
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>, ClientError>;
+}
+
+#[async_trait]
+impl SyncRpc for RpcClient {
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        config: GetConfirmedSignaturesForAddress2Config,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, ClientError> {
+        self.get_signatures_for_address_with_config(address, config)
+            .await
+    }
+
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, ClientError> {
+        self.get_transaction_with_config(signature, config).await
+    }
+
+    async fn get_slot(&self) -> Result<u64, ClientError> {
+        self.get_slot().await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>, ClientError> {
+        self.get_signature_statuses_with_history(signatures)
+            .await
+            .map(|response| response.value)
+    }
+}
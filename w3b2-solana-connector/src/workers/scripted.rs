@@ -0,0 +1,128 @@
+use crate::{
+    dispatcher::{CatchupRequest, DispatcherHandle},
+    events::{BridgeEvent, BridgeEventData, CaughtUp, EventSource},
+    storage::Storage,
+    workers::{shutdown_signalled, Health, SyncStatus},
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    pin::pin,
+    time::{Duration, SystemTime},
+};
+use tokio::sync::{mpsc, watch};
+
+/// How often the source checks whether the next event has a listener to go to.
+const LISTENER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The storage of a scripted [`EventManager`](super::EventManager). The scripted source
+/// never synchronizes, so there is no cursor to keep and nothing else is stored.
+pub(crate) struct ScriptedStorage;
+
+#[async_trait]
+impl Storage for ScriptedStorage {
+    async fn get_last_slot(&self) -> Result<u64> {
+        Ok(0)
+    }
+
+    async fn get_last_sig(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    async fn set_sync_state(&self, _slot: u64, _sig: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Stands in for the `Synchronizer` of an [`EventManager`](super::EventManager) created with
+/// [`new_with_source`](super::EventManager::new_with_source).
+///
+/// Dispatches a fixed sequence of events without making any RPC call. Each event is held
+/// back until a listener is registered for its PDA, so that a test sees the same events in
+/// the same order on every run, however late it creates its listeners. Every catch-up
+/// request is answered with an empty history.
+pub(crate) struct ScriptedSource {
+    events: Vec<(Pubkey, BridgeEvent)>,
+    dispatcher: DispatcherHandle,
+    catchup_requests: mpsc::Receiver<CatchupRequest>,
+    shutdown: watch::Receiver<bool>,
+    sync_status: watch::Sender<SyncStatus>,
+    health: watch::Sender<Health>,
+}
+
+impl ScriptedSource {
+    pub(crate) fn new(
+        events: Vec<(Pubkey, BridgeEvent)>,
+        dispatcher: DispatcherHandle,
+        catchup_requests: mpsc::Receiver<CatchupRequest>,
+        shutdown: watch::Receiver<bool>,
+    ) -> Self {
+        let (sync_status, _) = watch::channel(SyncStatus::default());
+        let (health, _) = watch::channel(Health {
+            last_progress: SystemTime::now(),
+            healthy: true,
+        });
+        Self {
+            events,
+            dispatcher,
+            catchup_requests,
+            shutdown,
+            sync_status,
+            health,
+        }
+    }
+
+    /// Returns a receiver for a lag that always reads as zero.
+    pub(crate) fn sync_status(&self) -> watch::Receiver<SyncStatus> {
+        self.sync_status.subscribe()
+    }
+
+    /// Returns a receiver for a health that always reads as healthy.
+    pub(crate) fn health(&self) -> watch::Receiver<Health> {
+        self.health.subscribe()
+    }
+
+    /// Plays the script and answers catch-up requests until a shutdown is requested or the
+    /// `Dispatcher` shuts down.
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        tracing::info!("Playing {} scripted events.", self.events.len());
+        let dispatcher = self.dispatcher.clone();
+        let mut script = pin!(play(dispatcher.clone(), std::mem::take(&mut self.events)));
+        let mut played = false;
+        loop {
+            tokio::select! {
+                _ = &mut script, if !played => {
+                    tracing::info!("All scripted events have been dispatched.");
+                    played = true;
+                }
+                Some(request) = self.catchup_requests.recv() => {
+                    let marker = BridgeEvent {
+                        source: EventSource::Catchup,
+                        signature: None,
+                        slot: 0,
+                        data: BridgeEventData::CaughtUp(CaughtUp { pda: request.pda, slot: 0 }),
+                    };
+                    let _ = request.sender.send(marker).await;
+                }
+                _ = shutdown_signalled(self.shutdown.clone()) => break,
+                _ = dispatcher.command_tx.closed() => break,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Dispatches every event of `events` in order, each once a listener is registered for
+/// its PDA.
+async fn play(dispatcher: DispatcherHandle, events: Vec<(Pubkey, BridgeEvent)>) {
+    for (pda, event) in events {
+        while !dispatcher.has_listener(pda).await {
+            if dispatcher.command_tx.is_closed() {
+                return;
+            }
+            tokio::time::sleep(LISTENER_POLL_INTERVAL).await;
+        }
+        dispatcher.dispatch(event).await;
+    }
+}
@@ -1,33 +1,127 @@
+#[cfg(feature = "geyser")]
+use crate::workers::geyser::GeyserLiveWorker;
 use crate::{
-    config::ConnectorConfig,
-    dispatcher::DispatcherHandle,
+    config::{ConnectorConfig, LiveSource},
+    dispatcher::{extract_pdas_from_event, CatchupRequest, DispatcherHandle},
+    events::BridgeEvent,
     storage::Storage,
-    workers::{catchup::CatchupWorker, live::LiveWorker},
+    workers::{
+        catchup::CatchupWorker,
+        lag::{LagMonitor, LagTracker, SyncStatus},
+        live::LiveWorker,
+        reorg::{ReorgTracker, ReorgWorker},
+        rpc::SyncRpc,
+        shutdown_signalled,
+        watchdog::{Health, Watchdog},
+    },
 };
 use solana_client::nonblocking::rpc_client::RpcClient;
 use std::sync::Arc;
+use tokio::sync::{mpsc, watch, Notify};
 
 /// A shared context containing all dependencies required by the workers.
-#[derive(Clone)]
-pub(crate) struct WorkerContext {
+pub(crate) struct WorkerContext<R: SyncRpc + ?Sized = RpcClient> {
     pub config: Arc<ConnectorConfig>,
     pub storage: Arc<dyn Storage>,
-    pub rpc_client: Arc<RpcClient>,
+    pub rpc_client: Arc<R>,
     pub dispatcher: DispatcherHandle,
+    /// Present when `synchronizer.detect_reorgs` is enabled.
+    pub reorg_tracker: Option<Arc<ReorgTracker>>,
+    /// The synchronizer's progress, reported by the `LagMonitor`.
+    pub lag: Arc<LagTracker>,
+    /// Asks the `CatchupWorker` for an immediate poll, used by the live worker while it
+    /// has fallen back to polling.
+    pub poll_requests: Arc<Notify>,
+    /// Raised by `EventManagerHandle::stop` to ask the workers to finish their current work.
+    pub shutdown: watch::Receiver<bool>,
 }
 
-impl WorkerContext {
+// Implemented by hand so that cloning the context does not require `R: Clone`.
+impl<R: SyncRpc + ?Sized> Clone for WorkerContext<R> {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            storage: self.storage.clone(),
+            rpc_client: self.rpc_client.clone(),
+            dispatcher: self.dispatcher.clone(),
+            reorg_tracker: self.reorg_tracker.clone(),
+            lag: self.lag.clone(),
+            poll_requests: self.poll_requests.clone(),
+            shutdown: self.shutdown.clone(),
+        }
+    }
+}
+
+impl<R: SyncRpc + ?Sized> WorkerContext<R> {
     fn new(
         config: Arc<ConnectorConfig>,
-        rpc_client: Arc<RpcClient>,
+        rpc_client: Arc<R>,
         storage: Arc<dyn Storage>,
         dispatcher: DispatcherHandle,
+        shutdown: watch::Receiver<bool>,
     ) -> Self {
+        let reorg_tracker = config
+            .synchronizer
+            .detect_reorgs
+            .then(|| Arc::new(ReorgTracker::default()));
         Self {
             config,
             storage,
             rpc_client,
             dispatcher,
+            reorg_tracker,
+            lag: Arc::default(),
+            poll_requests: Arc::default(),
+            shutdown,
+        }
+    }
+
+    /// Resolves once a shutdown was requested or the dispatcher is gone, after which a
+    /// worker should finish its current work and exit.
+    pub async fn shutdown_requested(&self) {
+        tokio::select! {
+            _ = shutdown_signalled(self.shutdown.clone()) => {}
+            _ = self.dispatcher.command_tx.closed() => {}
+        }
+    }
+
+    /// Returns `true` once a shutdown was requested or the dispatcher is gone.
+    pub fn is_shutting_down(&self) -> bool {
+        *self.shutdown.borrow() || self.dispatcher.command_tx.is_closed()
+    }
+
+    /// Dispatches an event, recording it for reorg detection when enabled.
+    pub async fn dispatch(&self, event: BridgeEvent) {
+        self.track(&event);
+        self.dispatcher.dispatch(event).await;
+    }
+
+    /// Adds the events of a processed transaction to the per-PDA event counts of the
+    /// storage. A failure is logged rather than returned, so that counting never holds up
+    /// synchronization.
+    pub async fn count_events(&self, events: &[BridgeEvent]) {
+        let counted: Vec<_> = events
+            .iter()
+            .flat_map(|event| {
+                let kind = event.data.kind();
+                extract_pdas_from_event(&event.data)
+                    .into_iter()
+                    .map(move |pda| (pda, kind))
+            })
+            .collect();
+        if counted.is_empty() {
+            return;
+        }
+        if let Err(e) = self.storage.count_events(&counted).await {
+            tracing::warn!("Failed to update the event counts: {}", e);
+        }
+    }
+
+    /// Records an event delivered outside the dispatcher (e.g. a history replay) for
+    /// reorg detection when enabled.
+    pub fn track(&self, event: &BridgeEvent) {
+        if let Some(tracker) = &self.reorg_tracker {
+            tracker.track(event);
         }
     }
 }
@@ -39,44 +133,127 @@ impl WorkerContext {
 /// as a simple container and entry point for the dual-worker synchronization strategy,
 /// where the `LiveWorker` handles real-time events and the `CatchupWorker` fills
 /// in any historical gaps.
-pub struct Synchronizer {
-    catchup_worker: CatchupWorker,
-    live_worker: LiveWorker,
+///
+/// The Synchronizer is generic over the [`SyncRpc`] transport used by the workers,
+/// which defaults to the standard nonblocking `RpcClient`.
+pub struct Synchronizer<R: SyncRpc + ?Sized = RpcClient> {
+    catchup_worker: CatchupWorker<R>,
+    live_worker: Live<R>,
+    reorg_worker: Option<ReorgWorker<R>>,
+    lag_monitor: LagMonitor<R>,
+    watchdog: Watchdog<R>,
+}
+
+/// The real-time worker selected by `ConnectorConfig.solana.live_source`.
+enum Live<R: SyncRpc + ?Sized> {
+    WebSocket(LiveWorker<R>),
+    #[cfg(feature = "geyser")]
+    Geyser(GeyserLiveWorker<R>),
+    /// The configured source is not compiled into this build.
+    #[cfg(not(feature = "geyser"))]
+    Unsupported(LiveSource),
+}
+
+impl<R: SyncRpc + ?Sized> Live<R> {
+    fn new(context: WorkerContext<R>) -> Self {
+        match context.config.solana.live_source {
+            LiveSource::Websocket | LiveSource::Auto => Self::WebSocket(LiveWorker::new(context)),
+            #[cfg(feature = "geyser")]
+            LiveSource::Geyser => Self::Geyser(GeyserLiveWorker::new(context)),
+            #[cfg(not(feature = "geyser"))]
+            source => Self::Unsupported(source),
+        }
+    }
+
+    async fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::WebSocket(worker) => worker.run().await,
+            #[cfg(feature = "geyser")]
+            Self::Geyser(worker) => worker.run().await,
+            #[cfg(not(feature = "geyser"))]
+            Self::Unsupported(source) => Err(anyhow::anyhow!(
+                "live source {:?} requires the connector to be built with the `geyser` feature",
+                source
+            )),
+        }
+    }
 }
 
-impl Synchronizer {
+impl<R: SyncRpc + ?Sized + 'static> Synchronizer<R> {
     /// Creates a new `Synchronizer` instance.
     ///
     /// This constructor initializes the shared `WorkerContext` and uses it to create
-    /// instances of `CatchupWorker` and `LiveWorker`. The workers are prepared but
-    /// not started until the `run` method is called.
+    /// instances of `CatchupWorker` and the live worker selected by `live_source`
+    /// (`LiveWorker` for WebSocket, `GeyserLiveWorker` for Yellowstone gRPC). The workers
+    /// are prepared but not started until the `run` method is called.
     pub fn new(
         config: Arc<ConnectorConfig>,
-        rpc_client: Arc<RpcClient>,
+        rpc_client: Arc<R>,
         storage: Arc<dyn Storage>,
         dispatcher: DispatcherHandle,
+        catchup_requests: mpsc::Receiver<CatchupRequest>,
+        shutdown: watch::Receiver<bool>,
     ) -> Self {
-        let context = WorkerContext::new(config, rpc_client, storage, dispatcher);
-        let catchup_worker = CatchupWorker::new(context.clone());
-        let live_worker = LiveWorker::new(context);
+        let context = WorkerContext::new(config, rpc_client, storage, dispatcher, shutdown);
+        let catchup_worker = CatchupWorker::new(context.clone(), catchup_requests);
+        let reorg_worker = context
+            .reorg_tracker
+            .clone()
+            .map(|tracker| ReorgWorker::new(context.clone(), tracker));
+        let lag_monitor = LagMonitor::new(context.clone());
+        let watchdog = Watchdog::new(context.clone());
+        let live_worker = Live::new(context);
 
         Self {
             catchup_worker,
             live_worker,
+            reorg_worker,
+            lag_monitor,
+            watchdog,
         }
     }
 
-    /// Runs both the `CatchupWorker` and `LiveWorker` concurrently.
+    /// Returns a receiver for the lag snapshots published by the `LagMonitor`.
+    pub(crate) fn sync_status(&self) -> watch::Receiver<SyncStatus> {
+        self.lag_monitor.sync_status()
+    }
+
+    /// Returns a receiver for the health published by the `Watchdog`.
+    pub(crate) fn health(&self) -> watch::Receiver<Health> {
+        self.watchdog.health()
+    }
+
+    /// Reads the PDAs the `CatchupWorker` pre-warms on startup. See
+    /// [`CatchupWorker::load_active_pdas`].
+    pub async fn load_active_pdas(&mut self) {
+        self.catchup_worker.load_active_pdas().await;
+    }
+
+    /// Runs the `CatchupWorker`, the live worker, the `LagMonitor`, the `Watchdog` and, if
+    /// enabled, the `ReorgWorker` concurrently.
     ///
     /// This is the main execution method for the synchronization process. It uses
-    /// `tokio::try_join!` to spawn both workers. The `try_join!` macro ensures that
+    /// `tokio::try_join!` to spawn the workers. The `try_join!` macro ensures that
     /// if either worker returns an error, the other worker is immediately cancelled,
     /// and the error is propagated up. The method will run indefinitely until one
     /// of the workers fails or the parent task is cancelled.
     pub async fn run(self) -> anyhow::Result<()> {
         tracing::info!("Starting synchronizer workers...");
 
-        tokio::try_join!(self.catchup_worker.run(), self.live_worker.run())?;
+        let reorg = async {
+            match self.reorg_worker {
+                Some(worker) => worker.run().await,
+                None => Ok(()),
+            }
+        };
+
+        tokio::try_join!(
+            self.catchup_worker.run(),
+            self.live_worker.run(),
+            self.lag_monitor.run(),
+            self.watchdog.run(),
+            reorg
+        )?;
 
         Ok(())
     }
@@ -0,0 +1,80 @@
+use crate::workers::{rpc::SyncRpc, synchronizer::WorkerContext};
+use anyhow::Result;
+use std::time::SystemTime;
+use tokio::{
+    sync::watch,
+    time::{interval_at, Duration, Instant, MissedTickBehavior},
+};
+
+/// The synchronizer's liveness, as judged by the `Watchdog`.
+///
+/// Read through [`EventManagerHandle::health`](crate::workers::EventManagerHandle::health).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Health {
+    /// When the synchronizer last completed a catch-up pass, or when the `EventManager`
+    /// was created if it has not completed one yet.
+    pub last_progress: SystemTime,
+    /// Cleared once no pass has completed for `synchronizer.watchdog_timeout_secs`, and
+    /// raised again by the next one.
+    pub healthy: bool,
+}
+
+/// Reports the synchronizer as unhealthy when it stops making progress.
+///
+/// Every complete catch-up pass is a heartbeat, so the progress does not depend on the
+/// program having any traffic. Unlike the `LagMonitor`, the watchdog makes no RPC calls
+/// itself, so it keeps working when the workers are wedged on a call that never returns.
+pub struct Watchdog<R: SyncRpc + ?Sized> {
+    ctx: WorkerContext<R>,
+}
+
+impl<R: SyncRpc + ?Sized> Watchdog<R> {
+    pub fn new(ctx: WorkerContext<R>) -> Self {
+        Self { ctx }
+    }
+
+    pub fn health(&self) -> watch::Receiver<Health> {
+        self.ctx.lag.health.subscribe()
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let timeout_secs = self.ctx.config.synchronizer.watchdog_timeout_secs;
+        if timeout_secs == 0 {
+            return Ok(());
+        }
+        let timeout = Duration::from_secs(timeout_secs);
+        let period = Duration::from_secs(1);
+        let mut ticker = interval_at(Instant::now() + period, period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => self.check(timeout),
+                _ = self.ctx.shutdown_requested() => {
+                    tracing::info!("Watchdog: shutdown signal received, exiting.");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn check(&self, timeout: Duration) {
+        let idle = self.ctx.lag.progressed_at.lock().unwrap().elapsed();
+        if idle < timeout {
+            return;
+        }
+        // Only the transition to unhealthy is reported; the next heartbeat clears it.
+        let fired = self
+            .ctx
+            .lag
+            .health
+            .send_if_modified(|health| std::mem::replace(&mut health.healthy, false));
+        if fired {
+            tracing::error!(
+                idle_secs = idle.as_secs(),
+                "Synchronizer is unhealthy: no catch-up pass has completed for {}s",
+                idle.as_secs()
+            );
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use anchor_lang::AccountSerialize;
+use async_trait::async_trait;
+use solana_client::client_error::ClientError;
+use solana_sdk::{
+    account::Account, hash::Hash, pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use w3b2_solana_connector::client::{AsyncRpcClient, TransactionBuilder};
+use w3b2_solana_connector::pda::find_user_profile_pda;
+use w3b2_solana_program::state::UserProfile;
+
+/// An RPC client serving accounts from memory and recording the size of each batch.
+#[derive(Default)]
+struct AccountsRpc {
+    accounts: HashMap<Pubkey, Account>,
+    batches: Mutex<Vec<usize>>,
+}
+
+#[async_trait]
+impl AsyncRpcClient for AccountsRpc {
+    async fn get_latest_blockhash(&self) -> Result<Hash, ClientError> {
+        unimplemented!("account fetches do not need a blockhash")
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        _transaction: &Transaction,
+    ) -> Result<Signature, ClientError> {
+        unimplemented!("account fetches never submit transactions")
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, ClientError> {
+        assert!(pubkeys.len() <= 100, "the RPC node rejects larger batches");
+        self.batches.lock().unwrap().push(pubkeys.len());
+        Ok(pubkeys
+            .iter()
+            .map(|pubkey| self.accounts.get(pubkey).cloned())
+            .collect())
+    }
+}
+
+fn user_profile(authority: Pubkey, admin_pda: Pubkey, deposit_balance: u64) -> UserProfile {
+    UserProfile {
+        authority,
+        communication_pubkey: Pubkey::new_unique(),
+        admin_profile_on_creation: admin_pda,
+        deposit_balance,
+        banned: false,
+        unban_requested: false,
+        low_balance_threshold: 0,
+        low_balance_notified: false,
+        ban_count: 0,
+        max_price_per_command: 0,
+        comm_key_version: 0,
+    }
+}
+
+fn account(owner: Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_user_profiles_batches_and_keeps_missing_entries() {
+    // === 1. Arrange ===
+    let admin_pda = Pubkey::new_unique();
+    let pairs: Vec<(Pubkey, Pubkey)> = (0..150)
+        .map(|_| (Pubkey::new_unique(), admin_pda))
+        .collect();
+    let mut rpc = AccountsRpc::default();
+    // Every third user has a profile.
+    for (i, (authority, _)) in pairs.iter().enumerate().filter(|(i, _)| i % 3 == 0) {
+        let mut data = Vec::new();
+        user_profile(*authority, admin_pda, i as u64)
+            .try_serialize(&mut data)
+            .unwrap();
+        let (pda, _) = find_user_profile_pda(authority, &admin_pda);
+        rpc.accounts
+            .insert(pda, account(w3b2_solana_program::ID, data));
+    }
+    // Anyone can send lamports to the PDA of a missing profile.
+    let (funded_pda, _) = find_user_profile_pda(&pairs[1].0, &admin_pda);
+    rpc.accounts
+        .insert(funded_pda, account(Pubkey::default(), Vec::new()));
+    let rpc = Arc::new(rpc);
+
+    // === 2. Act ===
+    let profiles = TransactionBuilder::new(rpc.clone())
+        .fetch_user_profiles(&pairs)
+        .await
+        .unwrap();
+
+    // === 3. Assert ===
+    assert_eq!(*rpc.batches.lock().unwrap(), vec![100, 50]);
+    assert_eq!(profiles.len(), pairs.len());
+    for (i, (profile, (authority, _))) in profiles.iter().zip(&pairs).enumerate() {
+        if i % 3 == 0 {
+            let profile = profile.as_ref().expect("the profile exists");
+            assert_eq!(profile.authority, *authority);
+            assert_eq!(profile.deposit_balance, i as u64);
+        } else {
+            assert!(profile.is_none(), "profile {i} does not exist");
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_user_profiles_rejects_malformed_profiles() {
+    let pair = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (pda, _) = find_user_profile_pda(&pair.0, &pair.1);
+    let mut rpc = AccountsRpc::default();
+    rpc.accounts
+        .insert(pda, account(w3b2_solana_program::ID, vec![0; 16]));
+
+    let result = TransactionBuilder::new(Arc::new(rpc))
+        .fetch_user_profiles(&[pair])
+        .await;
+
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("not a UserProfile"));
+}
@@ -0,0 +1,92 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::Hash,
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::sync::Arc;
+use w3b2_solana_connector::client::{
+    blockhash_placeholder_offset, set_message_blockhash, BroadcastOptions, TransactionBuilder,
+    UserDispatchCommandArgs,
+};
+use w3b2_solana_connector::pda::find_user_profile_pda;
+use w3b2_solana_program::protocols::CommandMode;
+
+fn decode(message_bytes: &[u8]) -> Message {
+    bincode::serde::borrow_decode_from_slice(message_bytes, bincode::config::standard())
+        .unwrap()
+        .0
+}
+
+#[test]
+fn test_blockhash_placeholder_is_filled_in_place() {
+    // === 1. Arrange ===
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let authority = Pubkey::new_unique();
+    let admin_pda = Pubkey::new_unique();
+    let targets: Vec<Pubkey> = (0..20).map(|_| Pubkey::new_unique()).collect();
+    let mut messages = vec![
+        builder.prepare_admin_register_profile(authority, 0, Pubkey::new_unique()),
+        builder.prepare_user_deposit(authority, admin_pda, 1_000),
+        builder
+            .prepare_user_dispatch_command(
+                authority,
+                admin_pda,
+                UserDispatchCommandArgs {
+                    command_id: 1,
+                    price: 1_000,
+                    timestamp: 0,
+                    user_profile_pda: find_user_profile_pda(&authority, &admin_pda).0,
+                    payload: vec![1, 2, 3],
+                    oracle_pubkey: Keypair::new().pubkey(),
+                    oracle_signature: [0; 64],
+                    mode: CommandMode::OneWay,
+                },
+            )
+            .unwrap(),
+    ];
+    // Broadcast batches reference many more accounts than a single instruction.
+    messages.extend(builder.prepare_admin_broadcast(
+        authority,
+        0,
+        &targets,
+        1,
+        vec![],
+        BroadcastOptions::default(),
+    ));
+    let blockhash = Hash::new_unique();
+
+    for mut message_bytes in messages {
+        // === 2. Act ===
+        let offset = blockhash_placeholder_offset(&message_bytes).unwrap();
+        let placeholder = message_bytes[offset..offset + 32].to_vec();
+        assert!(set_message_blockhash(&mut message_bytes, &blockhash));
+
+        // === 3. Assert ===
+        assert_eq!(placeholder, [0; 32]);
+        let mut message = decode(&message_bytes);
+        assert_eq!(message.recent_blockhash, blockhash);
+        // Nothing but the blockhash changed.
+        message.recent_blockhash = Hash::default();
+        let mut original = message_bytes.clone();
+        original[offset..offset + 32].fill(0);
+        assert_eq!(
+            bincode::serde::encode_to_vec(&message, bincode::config::standard()).unwrap(),
+            original
+        );
+    }
+}
+
+#[test]
+fn test_blockhash_placeholder_offset_rejects_truncated_messages() {
+    assert_eq!(blockhash_placeholder_offset(&[]), None);
+    assert_eq!(blockhash_placeholder_offset(&[1, 0, 1]), None);
+    // A header and two account keys, but no blockhash.
+    let mut truncated = vec![1, 0, 1, 2];
+    truncated.extend([0; 64]);
+    assert_eq!(blockhash_placeholder_offset(&truncated), None);
+    truncated.extend([0; 32]);
+    assert_eq!(blockhash_placeholder_offset(&truncated), Some(68));
+}
@@ -0,0 +1,365 @@
+mod mock;
+
+use litesvm::LiteSVM;
+use mock::svm::PATH_SBF;
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_sdk::{
+    clock::Clock,
+    message::{AddressLookupTableAccount, Message, VersionedMessage},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use std::sync::Arc;
+use w3b2_solana_connector::client::{
+    estimate_admin_dispatch_compute_units, BroadcastOptions, TransactionBuilder,
+    ADMIN_SHUTDOWN_COMMAND_ID,
+};
+use w3b2_solana_connector::events::{parse_program_logs, BridgeEventData};
+use w3b2_solana_connector::pda::{find_admin_profile_pda, find_user_profile_pda};
+
+fn builder() -> TransactionBuilder<RpcClient> {
+    TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())))
+}
+
+fn decode(message_bytes: &[u8]) -> Message {
+    bincode::serde::borrow_decode_from_slice(message_bytes, bincode::config::standard())
+        .unwrap()
+        .0
+}
+
+/// Returns the number of `admin_dispatch_command` instructions in a message.
+fn dispatch_count(message: &Message) -> usize {
+    message
+        .instructions
+        .iter()
+        .filter(|ix| message.account_keys[ix.program_id_index as usize] == w3b2_solana_program::ID)
+        .count()
+}
+
+/// Returns the compute unit limit requested by a message, if any.
+fn requested_compute_units(message: &Message) -> Option<u32> {
+    let ix = message.instructions.first()?;
+    let limit = ComputeBudgetInstruction::set_compute_unit_limit(0);
+    (message.account_keys[ix.program_id_index as usize] == limit.program_id)
+        .then(|| u32::from_le_bytes(ix.data[1..5].try_into().unwrap()))
+}
+
+/// The size of a message once signed by its single fee payer.
+fn signed_size(message: &Message) -> usize {
+    1 + 64 + message.serialize().len()
+}
+
+#[test]
+fn test_broadcast_packs_by_transaction_size() {
+    // === 1. Arrange ===
+    let admin = Pubkey::new_unique();
+    let targets: Vec<Pubkey> = (0..100).map(|_| Pubkey::new_unique()).collect();
+
+    // === 2. Act ===
+    let messages = builder().prepare_admin_broadcast(
+        admin,
+        0,
+        &targets,
+        1,
+        vec![0; 16],
+        BroadcastOptions::default(),
+    );
+
+    // === 3. Assert ===
+    let messages: Vec<Message> = messages.iter().map(|m| decode(m)).collect();
+    assert!(messages.len() > 1);
+    assert_eq!(
+        messages.iter().map(dispatch_count).sum::<usize>(),
+        targets.len()
+    );
+    assert!(messages.iter().all(|m| signed_size(m) <= 1232));
+    assert!(messages
+        .iter()
+        .all(|m| requested_compute_units(m).is_none()));
+    // The targets keep their order across the transactions.
+    let packed: Vec<Pubkey> = messages
+        .iter()
+        .flat_map(|m| {
+            m.instructions
+                .iter()
+                .map(|ix| m.account_keys[ix.accounts[2] as usize])
+        })
+        .collect();
+    assert_eq!(packed, targets);
+}
+
+#[test]
+fn test_broadcast_respects_account_limit() {
+    let targets: Vec<Pubkey> = (0..10).map(|_| Pubkey::new_unique()).collect();
+    let options = BroadcastOptions {
+        max_accounts_per_tx: 6,
+        ..Default::default()
+    };
+
+    let messages =
+        builder().prepare_admin_broadcast(Pubkey::new_unique(), 0, &targets, 1, vec![], options);
+
+    // The authority, the admin PDA and the program leave room for three users per transaction.
+    let counts: Vec<usize> = messages
+        .iter()
+        .map(|m| dispatch_count(&decode(m)))
+        .collect();
+    assert_eq!(counts, vec![3, 3, 3, 1]);
+}
+
+#[test]
+fn test_broadcast_packs_by_compute_budget() {
+    // === 1. Arrange ===
+    let targets: Vec<Pubkey> = (0..8).map(|_| Pubkey::new_unique()).collect();
+    let payload = vec![0; 100];
+    let units_per_ix = estimate_admin_dispatch_compute_units(payload.len());
+    let options = BroadcastOptions {
+        pack_by_compute: Some(3 * units_per_ix + 150),
+        ..Default::default()
+    };
+
+    // === 2. Act ===
+    let messages =
+        builder().prepare_admin_broadcast(Pubkey::new_unique(), 0, &targets, 1, payload, options);
+
+    // === 3. Assert ===
+    let messages: Vec<Message> = messages.iter().map(|m| decode(m)).collect();
+    let counts: Vec<usize> = messages.iter().map(dispatch_count).collect();
+    assert_eq!(counts, vec![3, 3, 2]);
+    let limits: Vec<Option<u32>> = messages.iter().map(requested_compute_units).collect();
+    assert_eq!(
+        limits,
+        vec![
+            Some(3 * units_per_ix + 150),
+            Some(3 * units_per_ix + 150),
+            Some(2 * units_per_ix + 150)
+        ]
+    );
+}
+
+#[test]
+fn test_lookup_table_packs_more_dispatches_per_transaction() {
+    // === 1. Arrange ===
+    // The lookup table program is built into LiteSVM, so no program binary is needed.
+    let mut svm = LiteSVM::new();
+    let admin = Keypair::new();
+    svm.airdrop(&admin.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    let targets: Vec<Pubkey> = (0..60).map(|_| Pubkey::new_unique()).collect();
+    let builder = builder();
+
+    let send = |svm: &mut LiteSVM, message_bytes: Vec<u8>| {
+        let mut message = decode(&message_bytes);
+        message.recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new(&[&admin], message, svm.latest_blockhash());
+        svm.send_transaction(tx).unwrap();
+    };
+
+    // === 2. Act ===
+    let slot = svm.get_sysvar::<Clock>().slot;
+    let (create, table) = builder.prepare_create_lookup_table(admin.pubkey(), slot);
+    send(&mut svm, create);
+    for chunk in targets.chunks(20) {
+        let extend = builder.prepare_extend_lookup_table(admin.pubkey(), table, chunk.to_vec());
+        send(&mut svm, extend);
+    }
+    let account = svm.get_account(&table).unwrap();
+    let lookup_table = AddressLookupTableAccount {
+        key: table,
+        addresses: AddressLookupTable::deserialize(&account.data)
+            .unwrap()
+            .addresses
+            .to_vec(),
+    };
+
+    let legacy = builder.prepare_admin_broadcast(
+        admin.pubkey(),
+        0,
+        &targets,
+        1,
+        vec![0; 16],
+        BroadcastOptions::default(),
+    );
+    let versioned = builder.prepare_admin_broadcast_with_lookup_table(
+        admin.pubkey(),
+        0,
+        &targets,
+        1,
+        vec![0; 16],
+        BroadcastOptions::default(),
+        &lookup_table,
+    );
+
+    // === 3. Assert ===
+    assert_eq!(lookup_table.addresses, targets);
+    let versioned: Vec<VersionedMessage> = versioned
+        .iter()
+        .map(|m| {
+            bincode::serde::borrow_decode_from_slice(m, bincode::config::standard())
+                .unwrap()
+                .0
+        })
+        .collect();
+    let legacy_max = legacy
+        .iter()
+        .map(|m| dispatch_count(&decode(m)))
+        .max()
+        .unwrap();
+    let VersionedMessage::V0(first) = &versioned[0] else {
+        panic!("expected a v0 message");
+    };
+    let v0_dispatches = |m: &VersionedMessage| {
+        m.instructions()
+            .iter()
+            .filter(|ix| {
+                m.static_account_keys()[ix.program_id_index as usize] == w3b2_solana_program::ID
+            })
+            .count()
+    };
+    // A legacy transaction cannot hold as many dispatches as the first v0 one.
+    assert!(v0_dispatches(&versioned[0]) > legacy_max);
+    assert!(versioned.len() < legacy.len());
+    assert_eq!(
+        versioned.iter().map(v0_dispatches).sum::<usize>(),
+        targets.len()
+    );
+    assert_eq!(first.address_table_lookups[0].account_key, table);
+    assert!(versioned
+        .iter()
+        .all(|m| 1 + 64 + m.serialize().len() <= 1232));
+}
+
+#[test]
+#[ignore = "Requires a compiled BPF program"]
+fn test_compute_estimate_covers_actual_consumption() {
+    // === 1. Arrange ===
+    let mut svm = LiteSVM::new();
+    svm.add_program_from_file(w3b2_solana_program::ID, PATH_SBF)
+        .unwrap();
+    svm.set_sysvar(&Clock::default());
+
+    let builder = builder();
+    let admin = Keypair::new();
+    let user = Keypair::new();
+    svm.airdrop(&admin.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    let (admin_pda, _) = Pubkey::find_program_address(
+        &[b"admin", admin.pubkey().as_ref()],
+        &w3b2_solana_program::ID,
+    );
+    let (user_pda, _) = Pubkey::find_program_address(
+        &[b"user", user.pubkey().as_ref(), admin_pda.as_ref()],
+        &w3b2_solana_program::ID,
+    );
+    let setup = [
+        (
+            builder.prepare_admin_register_profile(admin.pubkey(), 0, Pubkey::new_unique()),
+            &admin,
+        ),
+        (
+            builder.prepare_user_create_profile(user.pubkey(), admin_pda, Pubkey::new_unique()),
+            &user,
+        ),
+    ];
+    for (message_bytes, signer) in setup {
+        let mut message = decode(&message_bytes);
+        message.recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new(&[signer], message, svm.latest_blockhash());
+        svm.send_transaction(tx).unwrap();
+    }
+
+    // === 2. Act & 3. Assert ===
+    for payload_len in [0, 128, 512, 900] {
+        let messages = builder.prepare_admin_broadcast(
+            admin.pubkey(),
+            0,
+            &[user_pda],
+            1,
+            vec![7; payload_len],
+            BroadcastOptions {
+                pack_by_compute: Some(1_400_000),
+                ..Default::default()
+            },
+        );
+        let mut message = decode(&messages[0]);
+        message.recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new(&[&admin], message, svm.latest_blockhash());
+        let consumed = svm.send_transaction(tx).unwrap().compute_units_consumed;
+        svm.expire_blockhash();
+
+        let estimate = u64::from(estimate_admin_dispatch_compute_units(payload_len)) + 150;
+        assert!(
+            consumed <= estimate,
+            "a {payload_len} byte payload consumed {consumed} CU, above the estimate of {estimate}"
+        );
+        println!("✅ {payload_len} byte payload: consumed {consumed} CU, estimated {estimate} CU.");
+    }
+}
+
+#[test]
+#[ignore = "Requires a compiled BPF program"]
+fn test_shutdown_notifies_every_linked_user_once() {
+    // === 1. Arrange ===
+    let mut svm = LiteSVM::new();
+    svm.add_program_from_file(w3b2_solana_program::ID, PATH_SBF)
+        .unwrap();
+    svm.set_sysvar(&Clock::default());
+
+    let builder = builder();
+    let admin = Keypair::new();
+    svm.airdrop(&admin.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    let (admin_pda, _) = find_admin_profile_pda(&admin.pubkey(), 0);
+    let send = |svm: &mut LiteSVM, message_bytes: Vec<u8>, signer: &Keypair| {
+        let mut message = decode(&message_bytes);
+        message.recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new(&[signer], message, svm.latest_blockhash());
+        let logs = svm.send_transaction(tx).unwrap().logs;
+        svm.expire_blockhash();
+        parse_program_logs(&logs)
+    };
+    send(
+        &mut svm,
+        builder.prepare_admin_register_profile(admin.pubkey(), 0, Pubkey::new_unique()),
+        &admin,
+    );
+    let mut user_pdas = Vec::new();
+    // Enough users to need several transactions.
+    for _ in 0..30 {
+        let user = Keypair::new();
+        svm.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+        let create =
+            builder.prepare_user_create_profile(user.pubkey(), admin_pda, Pubkey::new_unique());
+        send(&mut svm, create, &user);
+        user_pdas.push(find_user_profile_pda(&user.pubkey(), &admin_pda).0);
+    }
+    // A PDA listed twice is still notified once.
+    let mut targets = user_pdas.clone();
+    targets.push(user_pdas[0]);
+
+    // === 2. Act ===
+    let messages = builder.prepare_admin_shutdown(
+        admin.pubkey(),
+        0,
+        &targets,
+        b"closing".to_vec(),
+        BroadcastOptions::default(),
+    );
+    let notified: Vec<Pubkey> = messages
+        .into_iter()
+        .flat_map(|message| send(&mut svm, message, &admin))
+        .filter_map(|event| match event.data {
+            BridgeEventData::AdminCommandDispatched(e) => {
+                assert_eq!(e.command_id, ADMIN_SHUTDOWN_COMMAND_ID);
+                assert_eq!(e.payload, b"closing");
+                Some(e.target_user_pda)
+            }
+            _ => None,
+        })
+        .collect();
+
+    // === 3. Assert ===
+    assert_eq!(notified, user_pdas);
+}
@@ -0,0 +1,126 @@
+use anchor_lang::AnchorDeserialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{message::Message, pubkey::Pubkey};
+use std::sync::Arc;
+use w3b2_solana_connector::{
+    chunking::{chunk_payload, reassemble_payload, CHUNK_DATA_LEN, MAX_CHUNKED_PAYLOAD_LEN},
+    client::TransactionBuilder,
+    error::ConnectorError,
+};
+use w3b2_solana_program::{
+    events::OffChainActionLogged, instruction::LogAction, instructions::MAX_METADATA_SIZE,
+};
+
+/// Builds the event the program would emit for a `log_action` carrying `metadata`.
+fn logged(session_id: u64, metadata: Vec<u8>) -> OffChainActionLogged {
+    OffChainActionLogged {
+        actor: Pubkey::default(),
+        user_profile_pda: Pubkey::default(),
+        admin_profile_pda: Pubkey::default(),
+        session_id,
+        category: 0,
+        action_code: 0,
+        metadata,
+        ts: 0,
+    }
+}
+
+#[test]
+fn test_chunked_payload_round_trip() {
+    // === 1. Arrange ===
+    let payload: Vec<u8> = (0..4096u32).map(|i| (i * 7) as u8).collect();
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let authority = Pubkey::new_unique();
+
+    // === 2. Act ===
+    let messages = builder
+        .prepare_log_action_chunked(
+            authority,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            42,
+            1,
+            0,
+            &payload,
+        )
+        .unwrap();
+    // Recover the events from the instructions, as the program would emit them.
+    let mut events = Vec::new();
+    for message_bytes in &messages {
+        let message: Message =
+            bincode::serde::borrow_decode_from_slice(message_bytes, bincode::config::standard())
+                .unwrap()
+                .0;
+        assert!(1 + 64 + message.serialize().len() <= 1232);
+        for ix in &message.instructions {
+            let args = LogAction::deserialize(&mut &ix.data[8..]).unwrap();
+            let metadata = args.metadata.unwrap();
+            assert!(metadata.len() <= MAX_METADATA_SIZE);
+            events.push(logged(args.session_id, metadata));
+        }
+    }
+
+    // === 3. Assert ===
+    assert_eq!(events.len(), payload.len().div_ceil(CHUNK_DATA_LEN));
+    assert!(
+        messages.len() < events.len(),
+        "chunks should share transactions"
+    );
+    assert_eq!(reassemble_payload(&events).unwrap(), payload);
+    // Transactions can land out of order.
+    events.reverse();
+    assert_eq!(reassemble_payload(&events).unwrap(), payload);
+}
+
+#[test]
+fn test_reassembly_rejects_incomplete_or_inconsistent_chunks() {
+    let payload = vec![1u8; CHUNK_DATA_LEN * 3];
+    let events: Vec<_> = chunk_payload(&payload)
+        .unwrap()
+        .into_iter()
+        .map(|metadata| logged(7, metadata))
+        .collect();
+
+    assert_eq!(
+        reassemble_payload(&[]),
+        Err(ConnectorError::MissingChunk(0))
+    );
+    assert_eq!(
+        reassemble_payload(&[events[0].clone(), events[2].clone()]),
+        Err(ConnectorError::MissingChunk(1))
+    );
+    assert_eq!(
+        reassemble_payload(&[events[0].clone(), events[1].clone(), events[1].clone()]),
+        Err(ConnectorError::DuplicateChunk(1))
+    );
+    assert_eq!(
+        reassemble_payload(&[events[0].clone(), logged(8, events[1].metadata.clone())]),
+        Err(ConnectorError::ChunkSessionMismatch {
+            expected: 7,
+            found: 8
+        })
+    );
+    assert_eq!(
+        reassemble_payload(&[events[0].clone(), logged(7, b"plain metadata".to_vec())]),
+        Err(ConnectorError::MalformedChunk(1))
+    );
+    // A chunk of a differently sized payload.
+    let other = chunk_payload(&[0; 10]).unwrap().remove(0);
+    assert_eq!(
+        reassemble_payload(&[events[0].clone(), logged(7, other)]),
+        Err(ConnectorError::MalformedChunk(1))
+    );
+}
+
+#[test]
+fn test_chunk_payload_limits() {
+    assert_eq!(
+        reassemble_payload(&[logged(0, chunk_payload(&[]).unwrap().remove(0))]),
+        Ok(vec![])
+    );
+    assert_eq!(
+        chunk_payload(&vec![0; MAX_CHUNKED_PAYLOAD_LEN + 1]),
+        Err(ConnectorError::PayloadTooLarge(MAX_CHUNKED_PAYLOAD_LEN + 1))
+    );
+}
@@ -0,0 +1,974 @@
+mod mock;
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose, Engine as _};
+use litesvm::LiteSVM;
+use mock::{
+    dispatch_transaction, event_log, invocation_logs,
+    svm::{execute, PATH_SBF},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    clock::Clock,
+    instruction::InstructionError,
+    message::Message,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::{collections::BTreeSet, sync::Arc};
+use w3b2_solana_connector::{
+    client::{TransactionBuilder, UserDispatchCommandArgs},
+    events::{
+        parse_dispatch_rejection, parse_log, parse_program_logs, parse_transaction_events,
+        try_parse_log, try_parse_program_data, BridgeEventData, DispatchRejectionReason,
+        EventSource,
+    },
+    oracle::sign_price_message,
+    pda::find_user_profile_pda,
+};
+use w3b2_solana_program::errors::BridgeError;
+use w3b2_solana_program::events::*;
+use w3b2_solana_program::instruction;
+use w3b2_solana_program::protocols::CommandMode;
+
+/// Every event the program emits, by the name of its `BridgeEventData` variant.
+const PROGRAM_EVENTS: [&str; 25] = [
+    "AdminProfileRegistered",
+    "AdminConfigUpdated",
+    "AdminFundsWithdrawn",
+    "AdminProfileClosed",
+    "AdminCommandDispatched",
+    "UserProfileCreated",
+    "UserCommKeyUpdated",
+    "UserFundsDeposited",
+    "UserFundsWithdrawn",
+    "UserProfileClosed",
+    "UserCommandDispatched",
+    "OffChainActionLogged",
+    "AdminUnbanFeeUpdated",
+    "UserBanned",
+    "UserUnbanned",
+    "UserUnbanRequested",
+    "UserLowBalanceThresholdUpdated",
+    "UserLowBalance",
+    "UserSpendingCapUpdated",
+    "AllowanceGranted",
+    "AllowanceConsumed",
+    "WalletCreated",
+    "WalletFundsDeposited",
+    "WalletFundsWithdrawn",
+    "WalletCharged",
+];
+
+/// Returns the name of the variant, e.g. `"UserBanned"` for `BridgeEventData::UserBanned(..)`.
+fn variant_name(data: &BridgeEventData) -> String {
+    let debug = format!("{:?}", data);
+    debug.split(['(', ' ']).next().unwrap().to_string()
+}
+
+fn parse(log: &str) -> BridgeEventData {
+    try_parse_log(log).expect("log should parse").data
+}
+
+#[test]
+fn test_parser_decodes_every_program_event() {
+    // === 1. Arrange ===
+    let key = Pubkey::new_unique();
+    let logs = [
+        event_log(&AdminProfileRegistered {
+            authority: key,
+            admin_pda: key,
+            communication_pubkey: key,
+            index: 1,
+            oracle_authority: key,
+            timestamp_validity_seconds: 60,
+            max_payload_size: 1000,
+            unban_fee: 0,
+            unban_fee_multiplier_bps: 0,
+            max_unban_fee_multiple: 0,
+            ts: 1,
+        }),
+        event_log(&AdminConfigUpdated {
+            authority: key,
+            admin_pda: key,
+            new_oracle_authority: key,
+            new_timestamp_validity: 60,
+            new_communication_pubkey: key,
+            comm_key_version: 2,
+            new_max_payload_size: 512,
+            require_distinct_oracle: true,
+            ts: 2,
+        }),
+        event_log(&AdminFundsWithdrawn {
+            authority: key,
+            admin_pda: key,
+            amount: 3,
+            destination: key,
+            ts: 3,
+        }),
+        event_log(&AdminProfileClosed {
+            authority: key,
+            admin_pda: key,
+            swept_balance: 4,
+            ts: 4,
+        }),
+        event_log(&AdminCommandDispatched {
+            sender: key,
+            sender_admin_pda: key,
+            target_user_pda: key,
+            command_id: 5,
+            payload: vec![5; 5],
+            mode: CommandMode::RequestResponse,
+            ts: 5,
+        }),
+        event_log(&UserProfileCreated {
+            authority: key,
+            user_pda: key,
+            target_admin_pda: key,
+            communication_pubkey: key,
+            ts: 6,
+        }),
+        event_log(&UserCommKeyUpdated {
+            authority: key,
+            user_profile_pda: key,
+            new_comm_pubkey: key,
+            comm_key_version: 7,
+            ts: 7,
+        }),
+        event_log(&UserFundsDeposited {
+            authority: key,
+            user_profile_pda: key,
+            amount: 8,
+            new_deposit_balance: 8,
+            source: key,
+            ts: 8,
+        }),
+        event_log(&UserFundsWithdrawn {
+            authority: key,
+            user_profile_pda: key,
+            amount: 9,
+            destination: key,
+            new_deposit_balance: 0,
+            ts: 9,
+        }),
+        event_log(&UserProfileClosed {
+            authority: key,
+            user_pda: key,
+            admin_pda: key,
+            was_banned: false,
+            ts: 10,
+        }),
+        event_log(&UserCommandDispatched {
+            sender: key,
+            sender_user_pda: key,
+            target_admin_pda: key,
+            command_id: 11,
+            price_paid: 11,
+            oracle_authority: key,
+            payload: vec![11],
+            mode: CommandMode::OneWay,
+            ts: 11,
+        }),
+        event_log(&OffChainActionLogged {
+            actor: key,
+            user_profile_pda: key,
+            admin_profile_pda: key,
+            session_id: 12,
+            category: 12,
+            action_code: 12,
+            metadata: vec![12],
+            ts: 12,
+        }),
+        event_log(&AdminUnbanFeeUpdated {
+            authority: key,
+            admin_pda: key,
+            new_unban_fee: 13,
+            ts: 13,
+        }),
+        event_log(&UserBanned {
+            admin_authority: key,
+            admin_pda: key,
+            user_profile_pda: key,
+            ts: 14,
+        }),
+        event_log(&UserUnbanned {
+            admin_authority: key,
+            admin_pda: key,
+            user_profile_pda: key,
+            ts: 15,
+        }),
+        event_log(&UserUnbanRequested {
+            user_authority: key,
+            user_profile_pda: key,
+            admin_pda: key,
+            fee_paid: 16,
+            ts: 16,
+        }),
+        event_log(&UserLowBalanceThresholdUpdated {
+            authority: key,
+            user_profile_pda: key,
+            new_threshold: 17,
+            ts: 17,
+        }),
+        event_log(&UserLowBalance {
+            authority: key,
+            user_profile_pda: key,
+            admin_pda: key,
+            deposit_balance: 18,
+            threshold: 18,
+            ts: 18,
+        }),
+        event_log(&UserSpendingCapUpdated {
+            authority: key,
+            user_profile_pda: key,
+            new_max_price_per_command: 19,
+            ts: 19,
+        }),
+        event_log(&AllowanceGranted {
+            authority: key,
+            user_profile_pda: key,
+            admin_pda: key,
+            commands: vec![1, 2],
+            total_cap: 20,
+            expires_at: 20,
+            ts: 20,
+        }),
+        event_log(&AllowanceConsumed {
+            user_profile_pda: key,
+            admin_pda: key,
+            charger: key,
+            command_id: 1,
+            amount: 21,
+            remaining_cap: 21,
+            ts: 21,
+        }),
+        event_log(&WalletCreated {
+            authority: key,
+            wallet_pda: key,
+            ts: 22,
+        }),
+        event_log(&WalletFundsDeposited {
+            authority: key,
+            wallet_pda: key,
+            amount: 23,
+            new_deposit_balance: 23,
+            ts: 23,
+        }),
+        event_log(&WalletFundsWithdrawn {
+            authority: key,
+            wallet_pda: key,
+            amount: 24,
+            destination: key,
+            new_deposit_balance: 24,
+            ts: 24,
+        }),
+        event_log(&WalletCharged {
+            wallet_pda: key,
+            user_profile_pda: key,
+            admin_pda: key,
+            amount: 25,
+            new_deposit_balance: 25,
+            ts: 25,
+        }),
+    ];
+
+    // === 2. Act ===
+    let parsed: Vec<String> = logs.iter().map(|log| variant_name(&parse(log))).collect();
+
+    // === 3. Assert ===
+    assert_eq!(parsed, PROGRAM_EVENTS);
+}
+
+#[test]
+fn test_parser_rejects_unknown_and_malformed_events() {
+    // === 1. Arrange ===
+    let unknown = format!(
+        "Program data: {}",
+        general_purpose::STANDARD.encode([0xAB; 40])
+    );
+    let truncated = format!(
+        "Program data: {}",
+        general_purpose::STANDARD.encode([UserBanned::DISCRIMINATOR, &[1, 2, 3][..]].concat())
+    );
+
+    // === 2. Act & 3. Assert ===
+    assert!(try_parse_log("Program log: Instruction: UserDeposit").is_err());
+    assert!(try_parse_log("Program data: not-base64!").is_err());
+    let unknown_err = try_parse_log(&unknown).unwrap_err().to_string();
+    assert!(
+        unknown_err.contains("unknown event discriminator"),
+        "{unknown_err}"
+    );
+    let malformed_err = try_parse_log(&truncated).unwrap_err().to_string();
+    assert!(malformed_err.contains("Malformed"), "{malformed_err}");
+    assert!(malformed_err.contains("UserBanned"), "{malformed_err}");
+}
+
+#[test]
+fn test_undecodable_program_logs_become_unknown_events() {
+    // === 1. Arrange ===
+    let log = event_log(&UserBanned {
+        admin_authority: Pubkey::new_unique(),
+        admin_pda: Pubkey::new_unique(),
+        user_profile_pda: Pubkey::new_unique(),
+        ts: 0,
+    });
+    let encoded = log.strip_prefix("Program data: ").unwrap();
+    let data = general_purpose::STANDARD.decode(encoded).unwrap();
+
+    let mut malformed = vec!["Program data: not-base64!".to_string()];
+    // Every truncation of the base64 text, as cut off by an RPC node.
+    malformed.extend((0..encoded.len()).map(|len| format!("Program data: {}", &encoded[..len])));
+    // Every truncation of the data, including data shorter than the discriminator.
+    malformed.extend((0..data.len()).map(|len| {
+        format!(
+            "Program data: {}",
+            general_purpose::STANDARD.encode(&data[..len])
+        )
+    }));
+    // A complete event followed by trailing garbage.
+    malformed.push(format!(
+        "Program data: {}",
+        general_purpose::STANDARD.encode([&data[..], &[0xFF; 5]].concat())
+    ));
+    malformed.push(format!("{log} garbage"));
+
+    // === 2. Act ===
+    let events = parse_program_logs(&invocation_logs(
+        &w3b2_solana_program::ID,
+        1,
+        malformed.clone(),
+    ));
+
+    // === 3. Assert ===
+    assert_eq!(events.len(), malformed.len());
+    for (event, log) in events.iter().zip(&malformed) {
+        assert!(
+            matches!(event.data, BridgeEventData::Unknown),
+            "{log}: {:?}",
+            event.data
+        );
+    }
+    assert!(matches!(
+        parse_log(&log).data,
+        BridgeEventData::UserBanned(_)
+    ));
+}
+
+#[test]
+fn test_parser_never_panics_or_misparses_random_data() {
+    let mut rng = StdRng::seed_from_u64(414);
+    let discriminators = [
+        UserBanned::DISCRIMINATOR,
+        UserFundsDeposited::DISCRIMINATOR,
+        AdminCommandDispatched::DISCRIMINATOR,
+        OffChainActionLogged::DISCRIMINATOR,
+        WalletCharged::DISCRIMINATOR,
+    ];
+
+    for _ in 0..10_000 {
+        let mut data = vec![0u8; rng.gen_range(0..256)];
+        rng.fill(&mut data[..]);
+        // Half of the inputs carry a real discriminator, to reach the event decoders.
+        if rng.gen_bool(0.5) {
+            let discriminator = discriminators[rng.gen_range(0..discriminators.len())];
+            data.splice(0..0, discriminator.iter().copied());
+        }
+
+        // Whatever decodes must be exactly what the program would have written.
+        if let Ok(event) = try_parse_program_data(&data) {
+            assert_eq!(event.data.to_program_data(), Some(data.clone()));
+        }
+        let encoded = general_purpose::STANDARD.encode(&data);
+        let cut = rng.gen_range(0..=encoded.len());
+        parse_log(&format!("Program data: {}", &encoded[..cut]));
+        parse_log(&format!("Program data: {}", String::from_utf8_lossy(&data)));
+    }
+}
+
+#[test]
+fn test_events_emitted_through_cpi_are_parsed() {
+    // === 1. Arrange ===
+    // A caller program invokes W3B2 twice via CPI: once directly and once through an
+    // intermediate program. The caller also logs data that decodes as a W3B2 event,
+    // which must not be attributed to W3B2.
+    let caller = Pubkey::new_unique();
+    let router = Pubkey::new_unique();
+    let w3b2 = w3b2_solana_program::ID;
+    let user_pda = Pubkey::new_unique();
+    let deposit = |amount| {
+        event_log(&UserFundsDeposited {
+            authority: Pubkey::new_unique(),
+            user_profile_pda: user_pda,
+            amount,
+            new_deposit_balance: amount,
+            source: Pubkey::new_unique(),
+            ts: 0,
+        })
+    };
+
+    let nested_w3b2 = invocation_logs(
+        &w3b2,
+        3,
+        vec![
+            "Program log: Instruction: UserDeposit".to_string(),
+            deposit(2),
+        ],
+    );
+    let mut router_logs = vec!["Program log: routing".to_string()];
+    router_logs.extend(nested_w3b2);
+    router_logs.push(format!(
+        "Program {} consumed 9000 of 180000 compute units",
+        w3b2
+    ));
+
+    let mut caller_logs = vec!["Program log: Instruction: Compose".to_string()];
+    caller_logs.extend(invocation_logs(
+        &w3b2,
+        2,
+        vec![
+            "Program log: Instruction: UserDeposit".to_string(),
+            deposit(1),
+        ],
+    ));
+    caller_logs.push(deposit(999));
+    caller_logs.extend(invocation_logs(&router, 2, router_logs));
+    caller_logs.push(format!("Program return: {} AQ==", caller));
+
+    let mut logs = vec![
+        "Program ComputeBudget111111111111111111111111111111 invoke [1]".to_string(),
+        "Program ComputeBudget111111111111111111111111111111 success".to_string(),
+    ];
+    logs.extend(invocation_logs(&caller, 1, caller_logs));
+    logs.extend(invocation_logs(&w3b2, 1, vec![deposit(3)]));
+
+    // === 2. Act ===
+    let events = parse_program_logs(&logs);
+
+    // === 3. Assert ===
+    let amounts: Vec<u64> = events
+        .iter()
+        .map(|e| match &e.data {
+            BridgeEventData::UserFundsDeposited(e) => e.amount,
+            other => panic!("unexpected event: {:?}", other),
+        })
+        .collect();
+    assert_eq!(amounts, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_failed_invocation_pops_the_program_stack() {
+    // === 1. Arrange ===
+    let other = Pubkey::new_unique();
+    let user_pda = Pubkey::new_unique();
+    let log = event_log(&UserBanned {
+        admin_authority: Pubkey::new_unique(),
+        admin_pda: Pubkey::new_unique(),
+        user_profile_pda: user_pda,
+        ts: 0,
+    });
+    let mut inner = vec![format!("Program {} invoke [2]", w3b2_solana_program::ID)];
+    inner.push(format!(
+        "Program {} failed: custom program error: 0x1770",
+        w3b2_solana_program::ID
+    ));
+    inner.push(log.clone());
+    let logs = invocation_logs(&other, 1, inner);
+
+    // === 2. Act ===
+    let events = parse_program_logs(&logs);
+
+    // === 3. Assert ===
+    assert!(
+        events.is_empty(),
+        "data logged by the caller after a failed CPI is not W3B2's"
+    );
+    assert_eq!(
+        parse_program_logs(&invocation_logs(&w3b2_solana_program::ID, 1, vec![log])).len(),
+        1
+    );
+}
+
+/// Signs and executes a message prepared by the `TransactionBuilder`, returning the
+/// parsed events from the transaction logs.
+/// Builds a fetched transaction, as returned by `getTransaction`, that failed with `err`.
+fn failed_transaction(
+    transaction: &Transaction,
+    err: TransactionError,
+) -> EncodedConfirmedTransactionWithStatusMeta {
+    fetched_transaction(transaction, Some(err), &[])
+}
+
+/// Builds a fetched transaction, as returned by `getTransaction`, with the given outcome
+/// and log messages.
+fn fetched_transaction(
+    transaction: &Transaction,
+    err: Option<TransactionError>,
+    logs: &[String],
+) -> EncodedConfirmedTransactionWithStatusMeta {
+    let encoded = bincode::serde::encode_to_vec(transaction, bincode::config::legacy()).unwrap();
+    let status = match &err {
+        Some(err) => serde_json::json!({ "Err": err }),
+        None => serde_json::json!({ "Ok": null }),
+    };
+    serde_json::from_value(serde_json::json!({
+        "slot": 7,
+        "transaction": [general_purpose::STANDARD.encode(encoded), "base64"],
+        "meta": {
+            "err": err,
+            "status": status,
+            "fee": 5000,
+            "preBalances": [],
+            "postBalances": [],
+            "logMessages": logs,
+        },
+        "blockTime": null,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_transaction_events_are_parsed_from_fetched_logs() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let funder = Pubkey::new_unique();
+    let logs = invocation_logs(
+        &w3b2_solana_program::ID,
+        1,
+        vec![
+            "Program log: Instruction: UserDeposit".to_string(),
+            event_log(&UserFundsDeposited {
+                authority: Pubkey::new_unique(),
+                user_profile_pda: user_pda,
+                amount: 500,
+                new_deposit_balance: 1_500,
+                source: funder,
+                ts: 0,
+            }),
+        ],
+    );
+    let transaction = dispatch_transaction(Pubkey::new_unique(), Pubkey::new_unique());
+
+    // === 2. Act ===
+    let events = parse_transaction_events(
+        "sig",
+        &fetched_transaction(&transaction, None, &logs),
+        EventSource::Live,
+    );
+    let failed = parse_transaction_events(
+        "sig",
+        &fetched_transaction(&transaction, Some(TransactionError::AccountInUse), &logs),
+        EventSource::Live,
+    );
+
+    // === 3. Assert ===
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].signature.as_deref(), Some("sig"));
+    assert_eq!(events[0].slot, 7);
+    assert_eq!(events[0].source, EventSource::Live);
+    let BridgeEventData::UserFundsDeposited(deposit) = &events[0].data else {
+        panic!("unexpected event {:?}", events[0].data);
+    };
+    assert_eq!(deposit.user_profile_pda, user_pda);
+    assert_eq!(deposit.amount, 500);
+    assert_eq!(deposit.source, funder);
+    // The logs of a failed transaction were rolled back.
+    assert!(failed.is_empty());
+}
+
+#[test]
+fn test_dispatch_rejection_is_parsed_from_failed_transaction() {
+    // === 1. Arrange ===
+    let authority = Pubkey::new_unique();
+    let admin_pda = Pubkey::new_unique();
+    let transaction = dispatch_transaction(authority, admin_pda);
+    let custom = |index, err: BridgeError| {
+        TransactionError::InstructionError(index, InstructionError::Custom(err.into()))
+    };
+
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let oracle = Keypair::new();
+    let message_bytes = builder
+        .prepare_dispatch_with_allowance(
+            oracle.pubkey(),
+            authority,
+            admin_pda,
+            UserDispatchCommandArgs {
+                command_id: 1,
+                price: 1_000,
+                timestamp: 0,
+                user_profile_pda: find_user_profile_pda(&authority, &admin_pda).0,
+                payload: vec![],
+                oracle_pubkey: oracle.pubkey(),
+                oracle_signature: [0; 64],
+                mode: CommandMode::OneWay,
+            },
+        )
+        .unwrap();
+    let message: Message =
+        bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())
+            .unwrap()
+            .0;
+    let allowance_transaction = Transaction::new_unsigned(message);
+
+    // === 2. Act ===
+    let rejected = parse_dispatch_rejection(
+        "sig",
+        &failed_transaction(&transaction, custom(1, BridgeError::TimestampTooOld)),
+    );
+    let rejected_allowance = parse_dispatch_rejection(
+        "sig",
+        &failed_transaction(
+            &allowance_transaction,
+            custom(1, BridgeError::InvalidOracleSigner),
+        ),
+    );
+    let banned = parse_dispatch_rejection(
+        "sig",
+        &failed_transaction(&transaction, custom(1, BridgeError::UserIsBanned)),
+    );
+    // The same code raised by the signature verification instruction is not ours.
+    let foreign = parse_dispatch_rejection(
+        "sig",
+        &failed_transaction(&transaction, custom(0, BridgeError::TimestampTooOld)),
+    );
+
+    // === 3. Assert ===
+    let rejected = rejected.expect("the oracle check failed");
+    assert_eq!(rejected.signature, "sig");
+    assert_eq!(rejected.reason, DispatchRejectionReason::TimestampTooOld);
+    assert_eq!(
+        rejected.user_pda,
+        find_user_profile_pda(&authority, &admin_pda).0
+    );
+    assert_eq!(rejected.admin_pda, admin_pda);
+    let rejected_allowance = rejected_allowance.expect("the oracle check failed");
+    assert_eq!(
+        rejected_allowance.reason,
+        DispatchRejectionReason::InvalidOracleSigner
+    );
+    assert_eq!(rejected_allowance.user_pda, rejected.user_pda);
+    assert_eq!(rejected_allowance.admin_pda, admin_pda);
+    assert!(banned.is_none());
+    assert!(foreign.is_none());
+}
+
+/// Decodes the arguments of the last instruction of a prepared message.
+fn last_instruction_args<T: AnchorDeserialize>(message_bytes: &[u8]) -> T {
+    let message: Message =
+        bincode::serde::borrow_decode_from_slice(message_bytes, bincode::config::standard())
+            .unwrap()
+            .0;
+    let data = &message.instructions.last().unwrap().data;
+    T::try_from_slice(&data[8..]).unwrap()
+}
+
+#[test]
+fn test_command_mode_round_trips_into_the_event() {
+    // === 1. Arrange ===
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let admin = Keypair::new();
+    let oracle = Keypair::new();
+    let user_pda = Pubkey::new_unique();
+    let admin_pda = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let (user_profile_pda, _) = find_user_profile_pda(&user, &admin_pda);
+
+    // === 2. Act ===
+    let admin_args: instruction::AdminDispatchCommand = last_instruction_args(
+        &builder
+            .prepare_admin_dispatch_command(
+                admin.pubkey(),
+                0,
+                user_pda,
+                9,
+                vec![1],
+                CommandMode::RequestResponse,
+            )
+            .unwrap(),
+    );
+    let user_args: instruction::UserDispatchCommand = last_instruction_args(
+        &builder
+            .prepare_user_dispatch_command(
+                user,
+                admin_pda,
+                UserDispatchCommandArgs {
+                    command_id: 1,
+                    price: 0,
+                    timestamp: 0,
+                    user_profile_pda,
+                    payload: vec![2],
+                    oracle_pubkey: oracle.pubkey(),
+                    oracle_signature: sign_price_message(&oracle, 1, 0, 0, user_profile_pda),
+                    mode: CommandMode::RequestResponse,
+                },
+            )
+            .unwrap(),
+    );
+    // The program copies the mode from the instruction into the event unchanged.
+    let admin_event = parse(&event_log(&AdminCommandDispatched {
+        sender: admin.pubkey(),
+        sender_admin_pda: admin_pda,
+        target_user_pda: user_pda,
+        command_id: admin_args.command_id,
+        payload: admin_args.payload,
+        mode: admin_args.mode,
+        ts: 0,
+    }));
+    let user_event = parse(&event_log(&UserCommandDispatched {
+        sender: Pubkey::new_unique(),
+        sender_user_pda: user_pda,
+        target_admin_pda: admin_pda,
+        command_id: user_args.command_id,
+        price_paid: user_args.price,
+        oracle_authority: Pubkey::new_unique(),
+        payload: user_args.payload,
+        mode: user_args.mode,
+        ts: 0,
+    }));
+
+    // === 3. Assert ===
+    assert_eq!(CommandMode::default(), CommandMode::OneWay);
+    let BridgeEventData::AdminCommandDispatched(admin_event) = admin_event else {
+        panic!("unexpected event {admin_event:?}");
+    };
+    let BridgeEventData::UserCommandDispatched(user_event) = user_event else {
+        panic!("unexpected event {user_event:?}");
+    };
+    assert_eq!(admin_event.mode, CommandMode::RequestResponse);
+    assert_eq!(user_event.mode, CommandMode::RequestResponse);
+}
+
+#[test]
+#[ignore = "Requires a compiled BPF program"]
+fn test_every_emitted_event_is_parsed() {
+    // === 1. Arrange ===
+    let mut svm = LiteSVM::new();
+    svm.add_program_from_file(w3b2_solana_program::ID, PATH_SBF)
+        .unwrap();
+    svm.set_sysvar(&Clock::default());
+
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let admin = Keypair::new();
+    let user = Keypair::new();
+    svm.airdrop(&admin.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    let (admin_pda, _) = Pubkey::find_program_address(
+        &[b"admin", admin.pubkey().as_ref()],
+        &w3b2_solana_program::ID,
+    );
+    let (user_pda, _) = Pubkey::find_program_address(
+        &[b"user", user.pubkey().as_ref(), admin_pda.as_ref()],
+        &w3b2_solana_program::ID,
+    );
+
+    let unban_fee = 50_000;
+    let price = 100_000u64;
+    let command_id = 7u16;
+    let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+    let oracle_signature = sign_price_message(&admin, command_id, price, timestamp, user_pda);
+
+    // === 2. Act ===
+    // Walk the full profile lifecycle, so that every instruction emits its event(s).
+    let steps: Vec<(Vec<u8>, &Keypair)> = vec![
+        (
+            builder.prepare_admin_register_profile(admin.pubkey(), 0, Pubkey::new_unique()),
+            &admin,
+        ),
+        (
+            builder
+                .prepare_admin_set_config(
+                    admin.pubkey(),
+                    0,
+                    None,
+                    None,
+                    None,
+                    Some(unban_fee),
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap(),
+            &admin,
+        ),
+        (
+            builder.prepare_user_create_profile(user.pubkey(), admin_pda, Pubkey::new_unique()),
+            &user,
+        ),
+        (
+            builder.prepare_user_update_comm_key(user.pubkey(), admin_pda, Pubkey::new_unique()),
+            &user,
+        ),
+        (
+            builder.prepare_user_deposit(user.pubkey(), admin_pda, 1_000_000),
+            &user,
+        ),
+        (
+            builder.prepare_user_set_low_balance_threshold(user.pubkey(), admin_pda, 1_000_000),
+            &user,
+        ),
+        (
+            builder.prepare_user_set_spending_cap(user.pubkey(), admin_pda, price),
+            &user,
+        ),
+        (
+            builder
+                .prepare_user_dispatch_command(
+                    user.pubkey(),
+                    admin_pda,
+                    UserDispatchCommandArgs {
+                        command_id,
+                        price,
+                        timestamp,
+                        user_profile_pda: user_pda,
+                        payload: vec![1, 2, 3],
+                        oracle_pubkey: admin.pubkey(),
+                        oracle_signature,
+                        mode: CommandMode::RequestResponse,
+                    },
+                )
+                .unwrap(),
+            &user,
+        ),
+        (
+            builder.prepare_user_grant_allowance(
+                user.pubkey(),
+                admin_pda,
+                vec![command_id],
+                price,
+                timestamp + 3600,
+            ),
+            &user,
+        ),
+        (
+            builder
+                .prepare_dispatch_with_allowance(
+                    admin.pubkey(),
+                    user.pubkey(),
+                    admin_pda,
+                    UserDispatchCommandArgs {
+                        command_id,
+                        price,
+                        timestamp,
+                        user_profile_pda: user_pda,
+                        payload: vec![4],
+                        oracle_pubkey: admin.pubkey(),
+                        oracle_signature,
+                        mode: CommandMode::OneWay,
+                    },
+                )
+                .unwrap(),
+            &admin,
+        ),
+        (
+            builder
+                .prepare_admin_dispatch_command(
+                    admin.pubkey(),
+                    0,
+                    user_pda,
+                    9,
+                    vec![4, 5],
+                    CommandMode::OneWay,
+                )
+                .unwrap(),
+            &admin,
+        ),
+        (builder.prepare_user_create_wallet(user.pubkey()), &user),
+        (
+            builder.prepare_wallet_deposit(user.pubkey(), 1_000_000),
+            &user,
+        ),
+        (
+            builder
+                .prepare_user_dispatch_command_from_wallet(
+                    user.pubkey(),
+                    admin_pda,
+                    UserDispatchCommandArgs {
+                        command_id,
+                        price,
+                        timestamp,
+                        user_profile_pda: user_pda,
+                        payload: vec![6],
+                        oracle_pubkey: admin.pubkey(),
+                        oracle_signature,
+                        mode: CommandMode::OneWay,
+                    },
+                )
+                .unwrap(),
+            &user,
+        ),
+        (
+            builder.prepare_wallet_withdraw(user.pubkey(), 10_000, user.pubkey()),
+            &user,
+        ),
+        (
+            builder.prepare_log_action(
+                user.pubkey(),
+                user_pda,
+                admin_pda,
+                1,
+                200,
+                1,
+                Some(vec![7]),
+            ),
+            &user,
+        ),
+        (
+            builder.prepare_admin_ban_user(admin.pubkey(), 0, user_pda),
+            &admin,
+        ),
+        (
+            builder.prepare_user_request_unban(user.pubkey(), admin_pda),
+            &user,
+        ),
+        (
+            builder.prepare_admin_unban_user(admin.pubkey(), 0, user_pda),
+            &admin,
+        ),
+        (
+            builder.prepare_user_withdraw(user.pubkey(), admin_pda, 10_000, user.pubkey()),
+            &user,
+        ),
+        (
+            builder.prepare_admin_withdraw(admin.pubkey(), 0, price, admin.pubkey()),
+            &admin,
+        ),
+        (
+            builder.prepare_user_close_profile(user.pubkey(), admin_pda),
+            &user,
+        ),
+        (
+            builder.prepare_admin_close_profile(admin.pubkey(), 0),
+            &admin,
+        ),
+    ];
+    let mut emitted = Vec::new();
+    for (message_bytes, signer) in steps {
+        let result = execute(&mut svm, message_bytes, signer).expect("transaction should succeed");
+        emitted.extend(parse_program_logs(&result.logs).into_iter().map(|e| e.data));
+    }
+
+    // === 3. Assert ===
+    assert!(emitted
+        .iter()
+        .all(|e| !matches!(e, BridgeEventData::Unknown)));
+    assert!(emitted.iter().any(|e| matches!(
+        e,
+        BridgeEventData::UserCommandDispatched(e) if e.mode == CommandMode::RequestResponse
+    )));
+    assert!(emitted.iter().any(|e| matches!(
+        e,
+        BridgeEventData::AdminCommandDispatched(e) if e.mode == CommandMode::OneWay
+    )));
+    let seen: BTreeSet<String> = emitted.iter().map(variant_name).collect();
+    let expected: BTreeSet<String> = PROGRAM_EVENTS.iter().map(|s| s.to_string()).collect();
+    assert_eq!(seen, expected);
+}
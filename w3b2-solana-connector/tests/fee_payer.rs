@@ -0,0 +1,98 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::Hash,
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    signer::SignerError,
+    transaction::Transaction,
+};
+use std::sync::Arc;
+use w3b2_solana_connector::client::{
+    blockhash_placeholder_offset, set_message_blockhash, BroadcastOptions, TransactionBuilder,
+};
+
+fn builder() -> TransactionBuilder<RpcClient> {
+    TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())))
+}
+
+fn decode(message_bytes: &[u8]) -> Message {
+    bincode::serde::borrow_decode_from_slice(message_bytes, bincode::config::standard())
+        .unwrap()
+        .0
+}
+
+#[test]
+fn test_external_fee_payer_requires_both_signatures() {
+    // === 1. Arrange ===
+    let relayer = Keypair::new();
+    let user = Keypair::new();
+    let builder = builder().with_fee_payer(relayer.pubkey());
+    let blockhash = Hash::new_unique();
+
+    // === 2. Act ===
+    let mut message_bytes =
+        builder.prepare_user_deposit(user.pubkey(), Pubkey::new_unique(), 1_000);
+    assert!(set_message_blockhash(&mut message_bytes, &blockhash));
+    let message = decode(&message_bytes);
+
+    // === 3. Assert ===
+    assert_eq!(message.header.num_required_signatures, 2);
+    assert_eq!(message.account_keys[0], relayer.pubkey());
+    assert_eq!(message.account_keys[1], user.pubkey());
+    assert_eq!(message.recent_blockhash, blockhash);
+
+    // Neither signer can complete the transaction alone.
+    for signer in [&relayer, &user] {
+        let mut tx = Transaction::new_unsigned(message.clone());
+        assert_eq!(
+            tx.try_sign(&[signer], blockhash),
+            Err(SignerError::NotEnoughSigners)
+        );
+    }
+    let mut tx = Transaction::new_unsigned(message);
+    tx.partial_sign(&[&user], blockhash);
+    assert!(!tx.is_signed());
+    tx.partial_sign(&[&relayer], blockhash);
+    assert!(tx.is_signed());
+    assert!(tx.verify().is_ok());
+}
+
+#[test]
+fn test_fee_payer_defaults_to_the_authority() {
+    let authority = Pubkey::new_unique();
+
+    let message = decode(&builder().prepare_user_deposit(authority, Pubkey::new_unique(), 1_000));
+
+    assert_eq!(message.header.num_required_signatures, 1);
+    assert_eq!(message.account_keys[0], authority);
+}
+
+#[test]
+fn test_broadcast_with_external_fee_payer_accounts_for_the_extra_signature() {
+    // === 1. Arrange ===
+    let relayer = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let targets: Vec<Pubkey> = (0..100).map(|_| Pubkey::new_unique()).collect();
+
+    // === 2. Act ===
+    let messages = builder().with_fee_payer(relayer).prepare_admin_broadcast(
+        authority,
+        0,
+        &targets,
+        1,
+        vec![0; 16],
+        BroadcastOptions::default(),
+    );
+
+    // === 3. Assert ===
+    for message_bytes in &messages {
+        let message = decode(message_bytes);
+        assert_eq!(message.header.num_required_signatures, 2);
+        assert_eq!(message.account_keys[..2], [relayer, authority]);
+        assert!(1 + 2 * 64 + message.serialize().len() <= 1232);
+        // The placeholder skips the additional account key.
+        let offset = blockhash_placeholder_offset(message_bytes).unwrap();
+        assert_eq!(offset, 4 + message.account_keys.len() * 32);
+    }
+}
@@ -0,0 +1,467 @@
+#![allow(dead_code)]
+
+//! In-memory doubles for the connector's external dependencies.
+//!
+//! These helpers allow the `EventManager` and its workers to be exercised end-to-end
+//! without a validator: [`MockSyncRpc`] serves canned signatures and transactions,
+//! [`MemoryStorage`] keeps the sync cursor and event cache in memory, and
+//! [`spawn_ws_stub`] provides a minimal WebSocket endpoint so the `LiveWorker` can
+//! subscribe successfully.
+
+use anchor_lang::Event;
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use futures::{SinkExt, StreamExt};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::RpcTransactionConfig,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_sdk::{
+    commitment_config::CommitmentLevel,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, TransactionConfirmationStatus, TransactionStatus,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+use w3b2_solana_connector::{
+    client::{TransactionBuilder, UserDispatchCommandArgs},
+    config::{ChannelConfig, ConnectorConfig, Solana, Synchronizer},
+    events::EventKind,
+    pda::find_user_profile_pda,
+    storage::{CachedEvent, CachedHistory, Storage},
+    workers::SyncRpc,
+};
+use w3b2_solana_program::protocols::CommandMode;
+
+pub mod svm;
+
+/// A transaction known to the [`MockSyncRpc`].
+struct MockTransaction {
+    signature: String,
+    slot: u64,
+    accounts: Vec<Pubkey>,
+    logs: Vec<String>,
+    /// The base64-encoded transaction, empty unless a test needs its instructions.
+    transaction: String,
+    err: Option<TransactionError>,
+}
+
+/// An in-memory implementation of [`SyncRpc`] backed by a list of canned transactions.
+#[derive(Default)]
+pub struct MockSyncRpc {
+    /// Transactions in the order they were "confirmed" (oldest first).
+    transactions: Mutex<Vec<MockTransaction>>,
+    slot: AtomicU64,
+    signature_requests: AtomicU64,
+    transaction_requests: AtomicU64,
+    /// The address of every `get_signatures_for_address` call, in order.
+    requested_addresses: Mutex<Vec<Pubkey>>,
+    /// When set, `get_signatures_for_address` fails, stalling the catch-up worker.
+    fail_signature_requests: AtomicBool,
+    /// When set, `get_slot` does not return until reset, wedging every caller.
+    hang_slot_requests: AtomicBool,
+}
+
+impl MockSyncRpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a confirmed transaction touching `accounts` that produced `logs`.
+    ///
+    /// The program id is always treated as one of the transaction's accounts, and `logs`
+    /// are wrapped in a top-level invocation of the program, as the runtime would log them.
+    pub fn push_transaction(
+        &self,
+        signature: &str,
+        slot: u64,
+        accounts: &[Pubkey],
+        logs: Vec<String>,
+    ) {
+        let mut accounts = accounts.to_vec();
+        accounts.push(w3b2_solana_program::ID);
+        let logs = invocation_logs(&w3b2_solana_program::ID, 1, logs);
+        self.slot.fetch_max(slot, Ordering::SeqCst);
+        self.transactions.lock().unwrap().push(MockTransaction {
+            signature: signature.to_string(),
+            slot,
+            accounts,
+            logs,
+            transaction: String::new(),
+            err: None,
+        });
+    }
+
+    /// Records a transaction that failed with `err` after logging `logs`. The transaction
+    /// itself is kept, so that the failing instruction can be inspected.
+    pub fn push_failed_transaction(
+        &self,
+        signature: &str,
+        slot: u64,
+        transaction: &Transaction,
+        err: TransactionError,
+        logs: Vec<String>,
+    ) {
+        let program_id = w3b2_solana_program::ID;
+        let mut logs = [vec![format!("Program {program_id} invoke [1]")], logs].concat();
+        logs.push(format!("Program {program_id} failed: {err}"));
+        let encoded =
+            bincode::serde::encode_to_vec(transaction, bincode::config::legacy()).unwrap();
+        self.slot.fetch_max(slot, Ordering::SeqCst);
+        self.transactions.lock().unwrap().push(MockTransaction {
+            signature: signature.to_string(),
+            slot,
+            accounts: transaction.message.account_keys.clone(),
+            logs,
+            transaction: general_purpose::STANDARD.encode(encoded),
+            err: Some(err),
+        });
+    }
+
+    /// Forgets a transaction, as if the fork it was included in had been abandoned.
+    pub fn drop_transaction(&self, signature: &str) {
+        self.transactions
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.signature != signature);
+    }
+
+    /// Advances the cluster's slot without confirming any transaction.
+    pub fn advance_slot(&self, slot: u64) {
+        self.slot.fetch_max(slot, Ordering::SeqCst);
+    }
+
+    /// Makes every `get_signatures_for_address` call fail until reset.
+    pub fn fail_signature_requests(&self, fail: bool) {
+        self.fail_signature_requests.store(fail, Ordering::SeqCst);
+    }
+
+    /// Makes every `get_slot` call hang until reset, as an RPC node that accepts requests
+    /// but never answers them would.
+    pub fn hang_slot_requests(&self, hang: bool) {
+        self.hang_slot_requests.store(hang, Ordering::SeqCst);
+    }
+
+    /// The number of `get_signatures_for_address` calls served so far.
+    pub fn signature_requests(&self) -> u64 {
+        self.signature_requests.load(Ordering::SeqCst)
+    }
+
+    /// The number of `get_signatures_for_address` calls served so far for `address`.
+    pub fn signature_requests_for(&self, address: &Pubkey) -> usize {
+        self.requested_addresses
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|requested| *requested == address)
+            .count()
+    }
+
+    /// The number of `get_transaction` calls served so far.
+    pub fn transaction_requests(&self) -> u64 {
+        self.transaction_requests.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl SyncRpc for MockSyncRpc {
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        config: GetConfirmedSignaturesForAddress2Config,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, ClientError> {
+        if self.fail_signature_requests.load(Ordering::SeqCst) {
+            return Err(
+                ClientErrorKind::Custom("signature requests are failing".to_string()).into(),
+            );
+        }
+        self.signature_requests.fetch_add(1, Ordering::SeqCst);
+        self.requested_addresses.lock().unwrap().push(*address);
+        let transactions = self.transactions.lock().unwrap();
+        let before = config.before.map(|sig| sig.to_string());
+
+        Ok(transactions
+            .iter()
+            .rev()
+            .filter(|tx| tx.accounts.contains(address))
+            .skip_while(|tx| before.as_ref().is_some_and(|b| *b != tx.signature))
+            .skip(usize::from(before.is_some()))
+            .take(config.limit.unwrap_or(1000))
+            .map(|tx| RpcConfirmedTransactionStatusWithSignature {
+                signature: tx.signature.clone(),
+                slot: tx.slot,
+                err: None,
+                memo: None,
+                block_time: None,
+                confirmation_status: None,
+            })
+            .collect())
+    }
+
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+        _config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, ClientError> {
+        self.transaction_requests.fetch_add(1, Ordering::SeqCst);
+        let response = {
+            let transactions = self.transactions.lock().unwrap();
+            let tx = transactions
+                .iter()
+                .find(|tx| tx.signature == signature.to_string())
+                .expect("transaction should be known to the mock");
+
+            serde_json::json!({
+                "slot": tx.slot,
+                "transaction": [tx.transaction, "base64"],
+                "meta": {
+                    "err": tx.err,
+                    "status": { "Ok": null },
+                    "fee": 5000,
+                    "preBalances": [],
+                    "postBalances": [],
+                    "logMessages": tx.logs,
+                },
+                "blockTime": null,
+            })
+        };
+
+        // Vary the latency per transaction so concurrent fetches complete out of order.
+        let jitter = signature.as_ref()[0] % 5;
+        tokio::time::sleep(Duration::from_millis(jitter as u64)).await;
+
+        Ok(serde_json::from_value(response).expect("mock transaction should deserialize"))
+    }
+
+    async fn get_slot(&self) -> Result<u64, ClientError> {
+        while self.hang_slot_requests.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        Ok(self.slot.load(Ordering::SeqCst))
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>, ClientError> {
+        let transactions = self.transactions.lock().unwrap();
+        Ok(signatures
+            .iter()
+            .map(|sig| {
+                transactions
+                    .iter()
+                    .find(|tx| tx.signature == sig.to_string())
+                    .map(|tx| TransactionStatus {
+                        slot: tx.slot,
+                        confirmations: Some(1),
+                        status: Ok(()),
+                        err: None,
+                        confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+                    })
+            })
+            .collect())
+    }
+}
+
+/// A [`Storage`] implementation that keeps the sync cursor and the event cache in memory.
+#[derive(Default)]
+pub struct MemoryStorage {
+    state: Mutex<(u64, Option<String>)>,
+    event_cache: Mutex<HashMap<Pubkey, CachedHistory>>,
+    event_counts: Mutex<HashMap<Pubkey, HashMap<EventKind, u64>>>,
+    active_pdas: Mutex<HashSet<Pubkey>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn get_last_slot(&self) -> anyhow::Result<u64> {
+        Ok(self.state.lock().unwrap().0)
+    }
+
+    async fn get_last_sig(&self) -> anyhow::Result<Option<String>> {
+        Ok(self.state.lock().unwrap().1.clone())
+    }
+
+    async fn set_sync_state(&self, slot: u64, sig: &str) -> anyhow::Result<()> {
+        *self.state.lock().unwrap() = (slot, Some(sig.to_string()));
+        Ok(())
+    }
+
+    async fn get_cached_events(&self, pda: &Pubkey) -> anyhow::Result<Option<CachedHistory>> {
+        Ok(self.event_cache.lock().unwrap().get(pda).cloned())
+    }
+
+    async fn cache_events(
+        &self,
+        pda: &Pubkey,
+        since: Option<(u64, &str)>,
+        events: &[CachedEvent],
+        cursor: (u64, &str),
+    ) -> anyhow::Result<()> {
+        let mut cache = self.event_cache.lock().unwrap();
+        let history = cache.entry(*pda).or_default();
+        if history
+            .cursor
+            .as_ref()
+            .map(|(slot, sig)| (*slot, sig.as_str()))
+            != since
+        {
+            return Ok(());
+        }
+        history.events.extend_from_slice(events);
+        history.cursor = Some((cursor.0, cursor.1.to_string()));
+        Ok(())
+    }
+
+    async fn count_events(&self, events: &[(Pubkey, EventKind)]) -> anyhow::Result<()> {
+        let mut counts = self.event_counts.lock().unwrap();
+        for (pda, kind) in events {
+            *counts.entry(*pda).or_default().entry(*kind).or_default() += 1;
+        }
+        Ok(())
+    }
+
+    async fn get_event_counts(&self, pda: &Pubkey) -> anyhow::Result<HashMap<EventKind, u64>> {
+        Ok(self
+            .event_counts
+            .lock()
+            .unwrap()
+            .get(pda)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn add_active_pda(&self, pda: &Pubkey) -> anyhow::Result<()> {
+        self.active_pdas.lock().unwrap().insert(*pda);
+        Ok(())
+    }
+
+    async fn remove_active_pda(&self, pda: &Pubkey) -> anyhow::Result<()> {
+        self.active_pdas.lock().unwrap().remove(pda);
+        Ok(())
+    }
+
+    async fn list_active_pdas(&self) -> anyhow::Result<Vec<Pubkey>> {
+        Ok(self.active_pdas.lock().unwrap().iter().copied().collect())
+    }
+}
+
+/// Starts a WebSocket server that acknowledges every subscription request and then
+/// stays silent. Returns the `ws://` URL to connect to.
+pub async fn spawn_ws_stub() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                    return;
+                };
+                let mut next_subscription = 0u64;
+                while let Some(Ok(msg)) = ws.next().await {
+                    let Message::Text(text) = msg else { continue };
+                    let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                    let reply = serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "result": next_subscription,
+                        "id": request["id"],
+                    });
+                    next_subscription += 1;
+                    if ws.send(Message::Text(reply.to_string())).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+
+    format!("ws://{}", addr)
+}
+
+/// Builds a connector configuration suitable for tests, pointing at `ws_url`.
+pub fn test_config(ws_url: String) -> ConnectorConfig {
+    ConnectorConfig {
+        solana: Solana {
+            rpc_url: "http://127.0.0.1:0".to_string(),
+            ws_url,
+            commitment: CommitmentLevel::Confirmed,
+            ..Default::default()
+        },
+        synchronizer: Synchronizer {
+            poll_interval_secs: 1,
+            ..Default::default()
+        },
+        channels: ChannelConfig::default(),
+    }
+}
+
+/// Renders an Anchor event as the `Program data:` log line the program would emit.
+pub fn event_log<E: Event>(event: &E) -> String {
+    format!(
+        "Program data: {}",
+        general_purpose::STANDARD.encode(event.data())
+    )
+}
+
+/// Wraps `logs` in the `invoke`/`success` lines the runtime emits around a program invocation.
+pub fn invocation_logs(program_id: &Pubkey, depth: u8, logs: Vec<String>) -> Vec<String> {
+    let mut wrapped = vec![format!("Program {} invoke [{}]", program_id, depth)];
+    wrapped.extend(logs);
+    wrapped.push(format!("Program {} success", program_id));
+    wrapped
+}
+
+/// Returns a fresh, unique transaction signature.
+pub fn random_signature() -> String {
+    Signature::new_unique().to_string()
+}
+
+/// Builds an unsigned `user_dispatch_command` transaction of `authority` for `admin_pda`.
+/// Its first instruction verifies the oracle signature, the second one dispatches.
+pub fn dispatch_transaction(authority: Pubkey, admin_pda: Pubkey) -> Transaction {
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let (user_pda, _) = find_user_profile_pda(&authority, &admin_pda);
+    let message_bytes = builder
+        .prepare_user_dispatch_command(
+            authority,
+            admin_pda,
+            UserDispatchCommandArgs {
+                command_id: 1,
+                price: 1_000,
+                timestamp: 0,
+                user_profile_pda: user_pda,
+                payload: vec![],
+                oracle_pubkey: Keypair::new().pubkey(),
+                oracle_signature: [0; 64],
+                mode: CommandMode::OneWay,
+            },
+        )
+        .unwrap();
+    let (message, _) =
+        bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())
+            .unwrap();
+    Transaction::new_unsigned(message)
+}
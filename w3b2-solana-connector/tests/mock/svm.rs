@@ -0,0 +1,29 @@
+#![allow(dead_code)]
+
+//! Helpers for running the compiled program in LiteSVM.
+//!
+//! Kept apart from the RPC doubles so that other crates' tests can include this file on its
+//! own, without the connector's test dependencies.
+
+use litesvm::{types::TransactionResult, LiteSVM};
+use solana_sdk::{message::Message, signature::Keypair, transaction::Transaction};
+
+/// A constant path to the compiled on-chain program binary (`.so` file).
+pub const PATH_SBF: &str = "../target/deploy/w3b2_solana_program.so";
+
+/// Signs and executes a message prepared by the `TransactionBuilder`, then expires the
+/// blockhash so that an identical follow-up message is not rejected as a duplicate.
+#[allow(clippy::result_large_err)]
+pub fn execute(svm: &mut LiteSVM, message_bytes: Vec<u8>, signer: &Keypair) -> TransactionResult {
+    let mut message: Message =
+        bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())
+            .unwrap()
+            .0;
+    message.recent_blockhash = svm.latest_blockhash();
+    let mut tx = Transaction::new_unsigned(message);
+    tx.sign(&[signer], svm.latest_blockhash());
+
+    let result = svm.send_transaction(tx);
+    svm.expire_blockhash();
+    result
+}
@@ -0,0 +1,331 @@
+mod mock;
+
+use litesvm::LiteSVM;
+use mock::svm::{execute, PATH_SBF};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    clock::Clock,
+    ed25519_program,
+    message::Message,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+use std::sync::Arc;
+use w3b2_solana_connector::{
+    client::{
+        oracle_signature_from_slice, ComputeBudget, PreparedMessage, TransactionBuilder,
+        UserDispatchCommandArgs,
+    },
+    error::ConnectorError,
+    oracle::{build_price_message, sign_price_message, ORACLE_PRICE_MESSAGE_VERSION},
+    pda::{find_admin_profile_pda, find_user_profile_pda},
+};
+use w3b2_solana_program::protocols::CommandMode;
+use w3b2_solana_program::state::UserProfile;
+
+#[test]
+fn test_price_message_layout() {
+    let user_pda = Pubkey::new_unique();
+    let message = build_price_message(0x0102, 0x0304, -1, user_pda);
+
+    assert_eq!(message.len(), 1 + 2 + 8 + 8 + 32);
+    assert_eq!(message[0], ORACLE_PRICE_MESSAGE_VERSION);
+    assert_eq!(&message[1..3], &0x0102u16.to_le_bytes());
+    assert_eq!(&message[3..11], &0x0304u64.to_le_bytes());
+    assert_eq!(&message[11..19], &(-1i64).to_le_bytes());
+    assert_eq!(&message[19..], user_pda.as_ref());
+}
+
+#[test]
+fn test_signed_price_message_verifies_against_the_oracle_key() {
+    let oracle = Keypair::new();
+    let user_pda = Pubkey::new_unique();
+    let verifies = |signature: &Signature, message: Vec<u8>| {
+        signature.verify(oracle.pubkey().as_ref(), &message)
+    };
+
+    let signature = Signature::from(sign_price_message(&oracle, 7, 1_000, 42, user_pda));
+
+    assert!(verifies(
+        &signature,
+        build_price_message(7, 1_000, 42, user_pda)
+    ));
+    assert!(!verifies(
+        &signature,
+        build_price_message(7, 1_001, 42, user_pda)
+    ));
+    assert!(!verifies(
+        &signature,
+        build_price_message(7, 1_000, 42, Pubkey::new_unique())
+    ));
+}
+
+#[test]
+fn test_oracle_signature_length_is_validated() {
+    assert_eq!(oracle_signature_from_slice(&[7; 64]), Ok([7; 64]));
+    assert_eq!(
+        oracle_signature_from_slice(&[7; 63]),
+        Err(ConnectorError::InvalidSignatureLength(63))
+    );
+    assert_eq!(
+        oracle_signature_from_slice(&[]),
+        Err(ConnectorError::InvalidSignatureLength(0))
+    );
+}
+
+#[test]
+fn test_dispatch_with_an_off_curve_oracle_key_is_rejected() {
+    // === 1. Arrange ===
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let user = Pubkey::new_unique();
+    let (admin_pda, _) = find_admin_profile_pda(&Pubkey::new_unique(), 0);
+    let (user_pda, _) = find_user_profile_pda(&user, &admin_pda);
+    // A PDA is off the curve by construction, so it can never have signed anything.
+    let args = |oracle_pubkey| UserDispatchCommandArgs {
+        command_id: 1,
+        price: 1_000,
+        timestamp: 0,
+        user_profile_pda: user_pda,
+        payload: vec![],
+        oracle_pubkey,
+        oracle_signature: [0; 64],
+        mode: CommandMode::OneWay,
+    };
+
+    // === 2. Act ===
+    let off_curve = builder.prepare_user_dispatch_command(user, admin_pda, args(admin_pda));
+    let on_curve =
+        builder.prepare_user_dispatch_command(user, admin_pda, args(Keypair::new().pubkey()));
+
+    // === 3. Assert ===
+    let err = off_curve.unwrap_err();
+    assert_eq!(err, ConnectorError::OracleKeyOffCurve(admin_pda));
+    assert!(err.to_string().contains("not a valid Ed25519 point"));
+    assert!(on_curve.is_ok());
+}
+
+#[test]
+fn test_dispatch_with_a_price_signed_for_another_user_is_rejected() {
+    // === 1. Arrange ===
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let oracle = Keypair::new();
+    let (admin_pda, _) = find_admin_profile_pda(&Pubkey::new_unique(), 0);
+    let (user, other_user) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (user_pda, _) = find_user_profile_pda(&user, &admin_pda);
+    let (other_user_pda, _) = find_user_profile_pda(&other_user, &admin_pda);
+    // The oracle signs the price for `user` only.
+    let args = || UserDispatchCommandArgs {
+        command_id: 1,
+        price: 1_000,
+        timestamp: 0,
+        user_profile_pda: user_pda,
+        payload: vec![],
+        oracle_pubkey: oracle.pubkey(),
+        oracle_signature: sign_price_message(&oracle, 1, 1_000, 0, user_pda),
+        mode: CommandMode::OneWay,
+    };
+
+    // === 2. Act ===
+    let replayed = builder.prepare_user_dispatch_command(other_user, admin_pda, args());
+    let replayed_from_wallet =
+        builder.prepare_user_dispatch_command_from_wallet(other_user, admin_pda, args());
+    let intended = builder.prepare_user_dispatch_command(user, admin_pda, args());
+
+    // === 3. Assert ===
+    let expected = ConnectorError::PriceSignedForAnotherUser {
+        signed_for: user_pda,
+        dispatched_for: other_user_pda,
+    };
+    assert_eq!(replayed.unwrap_err(), expected);
+    assert_eq!(replayed_from_wallet.unwrap_err(), expected);
+    assert!(intended.is_ok());
+}
+
+#[test]
+fn test_dispatch_with_compute_budget_keeps_the_oracle_verification() {
+    // === 1. Arrange ===
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let oracle = Keypair::new();
+    let (authority, admin_pda) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (user_pda, _) = find_user_profile_pda(&authority, &admin_pda);
+    let args = || UserDispatchCommandArgs {
+        command_id: 1,
+        price: 1_000,
+        timestamp: 0,
+        user_profile_pda: user_pda,
+        payload: vec![],
+        oracle_pubkey: oracle.pubkey(),
+        oracle_signature: sign_price_message(&oracle, 1, 1_000, 0, user_pda),
+        mode: CommandMode::OneWay,
+    };
+    let program_ids = |message_bytes: Vec<u8>| {
+        let message: Message =
+            bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())
+                .unwrap()
+                .0;
+        message
+            .instructions
+            .iter()
+            .map(|ix| *ix.program_id(&message.account_keys))
+            .collect::<Vec<_>>()
+    };
+
+    // === 2. Act ===
+    let plain = builder
+        .prepare_user_dispatch_command(authority, admin_pda, args())
+        .unwrap();
+    let budgeted = builder
+        .prepare_user_dispatch_command_with_compute_budget(
+            authority,
+            admin_pda,
+            args(),
+            ComputeBudget {
+                unit_limit: Some(300_000),
+                unit_price: Some(10),
+            },
+        )
+        .unwrap();
+
+    // === 3. Assert ===
+    let dispatch = [ed25519_program::ID, w3b2_solana_program::ID];
+    assert_eq!(program_ids(plain), dispatch);
+    assert_eq!(
+        program_ids(budgeted),
+        [
+            solana_compute_budget_interface::ID,
+            solana_compute_budget_interface::ID,
+            dispatch[0],
+            dispatch[1]
+        ]
+    );
+}
+
+#[test]
+fn test_prepared_dispatch_decodes_into_its_instructions() {
+    // === 1. Arrange ===
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let oracle = Keypair::new();
+    let (authority, admin_pda) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (user_pda, _) = find_user_profile_pda(&authority, &admin_pda);
+    let args = UserDispatchCommandArgs {
+        command_id: 1,
+        price: 1_000,
+        timestamp: 0,
+        user_profile_pda: user_pda,
+        payload: vec![7; 4],
+        oracle_pubkey: oracle.pubkey(),
+        oracle_signature: sign_price_message(&oracle, 1, 1_000, 0, user_pda),
+        mode: CommandMode::OneWay,
+    };
+
+    // === 2. Act ===
+    let bytes = builder
+        .prepare_user_dispatch_command(authority, admin_pda, args)
+        .unwrap();
+    let prepared = PreparedMessage::decode(bytes.clone()).unwrap();
+
+    // === 3. Assert ===
+    assert_eq!(prepared.bytes, bytes);
+    assert_eq!(
+        prepared.program_ids(),
+        [ed25519_program::ID, w3b2_solana_program::ID]
+    );
+    let message = &prepared.message;
+    assert_eq!(message.account_keys[0], authority);
+    let dispatch = &message.instructions[1];
+    let accounts: Vec<Pubkey> = dispatch
+        .accounts
+        .iter()
+        .map(|&index| message.account_keys[index as usize])
+        .collect();
+    assert_eq!(
+        accounts,
+        [
+            authority,
+            user_pda,
+            admin_pda,
+            solana_sdk::sysvar::instructions::ID
+        ]
+    );
+    // The dispatch data ends with the payload and the mode.
+    assert!(dispatch
+        .data
+        .ends_with(&[4, 0, 0, 0, 7, 7, 7, 7, CommandMode::OneWay as u8]));
+    assert!(PreparedMessage::decode(vec![1, 2, 3]).is_err());
+}
+
+#[test]
+#[ignore = "Requires a compiled BPF program"]
+fn test_signed_price_message_is_accepted_on_chain() {
+    // === 1. Arrange ===
+    let mut svm = LiteSVM::new();
+    svm.add_program_from_file(w3b2_solana_program::ID, PATH_SBF)
+        .unwrap();
+    svm.set_sysvar(&Clock::default());
+
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let admin = Keypair::new();
+    let user = Keypair::new();
+    svm.airdrop(&admin.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    let (admin_pda, _) = find_admin_profile_pda(&admin.pubkey(), 0);
+    let (user_pda, _) = find_user_profile_pda(&user.pubkey(), &admin_pda);
+
+    let deposit = LAMPORTS_PER_SOL;
+    let setup = [
+        (
+            builder.prepare_admin_register_profile(admin.pubkey(), 0, Pubkey::new_unique()),
+            &admin,
+        ),
+        (
+            builder.prepare_user_create_profile(user.pubkey(), admin_pda, Pubkey::new_unique()),
+            &user,
+        ),
+        (
+            builder.prepare_user_deposit(user.pubkey(), admin_pda, deposit),
+            &user,
+        ),
+    ];
+    for (message_bytes, signer) in setup {
+        execute(&mut svm, message_bytes, signer).expect("transaction should succeed");
+    }
+
+    // === 2. Act ===
+    // The admin is its own oracle by default.
+    let (command_id, price) = (3u16, 250_000u64);
+    let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+    let dispatch = builder
+        .prepare_user_dispatch_command(
+            user.pubkey(),
+            admin_pda,
+            UserDispatchCommandArgs {
+                command_id,
+                price,
+                timestamp,
+                user_profile_pda: user_pda,
+                payload: vec![],
+                oracle_pubkey: admin.pubkey(),
+                oracle_signature: sign_price_message(
+                    &admin, command_id, price, timestamp, user_pda,
+                ),
+                mode: CommandMode::OneWay,
+            },
+        )
+        .unwrap();
+    execute(&mut svm, dispatch, &user).expect("transaction should succeed");
+
+    // === 3. Assert ===
+    let account = svm.get_account(&user_pda).unwrap();
+    let user_profile = <UserProfile as anchor_lang::AccountDeserialize>::try_deserialize(
+        &mut account.data.as_slice(),
+    )
+    .unwrap();
+    assert_eq!(user_profile.deposit_balance, deposit - price);
+    println!("✅ The program accepted the oracle signature and charged {price} lamports.");
+}
@@ -0,0 +1,102 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::sync::Arc;
+use w3b2_solana_connector::{
+    client::{TransactionBuilder, UserDispatchCommandArgs},
+    error::ConnectorError,
+    oracle::sign_price_message,
+    pda::find_user_profile_pda,
+};
+use w3b2_solana_program::protocols::CommandMode;
+
+fn builder() -> TransactionBuilder<RpcClient> {
+    TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())))
+}
+
+/// Builds the oracle-signed arguments of a command of `authority` for `admin_pda`.
+fn user_args(authority: Pubkey, admin_pda: Pubkey, payload: Vec<u8>) -> UserDispatchCommandArgs {
+    let oracle = Keypair::new();
+    let (user_pda, _) = find_user_profile_pda(&authority, &admin_pda);
+    UserDispatchCommandArgs {
+        command_id: 1,
+        price: 1_000,
+        timestamp: 0,
+        user_profile_pda: user_pda,
+        payload,
+        oracle_pubkey: oracle.pubkey(),
+        oracle_signature: sign_price_message(&oracle, 1, 1_000, 0, user_pda),
+        mode: CommandMode::OneWay,
+    }
+}
+
+#[test]
+fn test_payload_validator_rejects_payloads_before_building() {
+    // === 1. Arrange ===
+    let builder = builder().with_payload_validator(Arc::new(|payload: &[u8]| {
+        if payload.is_empty() {
+            Err("the payload must not be empty".to_string())
+        } else {
+            Ok(())
+        }
+    }));
+    let admin_dispatch = |payload| {
+        builder.prepare_admin_dispatch_command(
+            Pubkey::new_unique(),
+            0,
+            Pubkey::new_unique(),
+            1,
+            payload,
+            CommandMode::OneWay,
+        )
+    };
+    let (authority, admin_pda) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let user_dispatch = |payload| {
+        builder.prepare_user_dispatch_command(
+            authority,
+            admin_pda,
+            user_args(authority, admin_pda, payload),
+        )
+    };
+
+    // === 2. Act ===
+    let empty_admin = admin_dispatch(vec![]);
+    let empty_user = user_dispatch(vec![]);
+    let valid_admin = admin_dispatch(vec![1]);
+    let valid_user = user_dispatch(vec![1]);
+
+    // === 3. Assert ===
+    let expected = ConnectorError::InvalidPayload("the payload must not be empty".to_string());
+    assert_eq!(empty_admin.unwrap_err(), expected);
+    assert_eq!(empty_user.unwrap_err(), expected);
+    assert!(expected
+        .to_string()
+        .contains("the payload must not be empty"));
+    assert!(valid_admin.is_ok());
+    assert!(valid_user.is_ok());
+}
+
+#[test]
+fn test_payloads_are_not_validated_by_default() {
+    let builder = builder();
+
+    let admin = builder.prepare_admin_dispatch_command(
+        Pubkey::new_unique(),
+        0,
+        Pubkey::new_unique(),
+        1,
+        vec![],
+        CommandMode::OneWay,
+    );
+    let (authority, admin_pda) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let user = builder.prepare_user_dispatch_command(
+        authority,
+        admin_pda,
+        user_args(authority, admin_pda, vec![]),
+    );
+
+    assert!(admin.is_ok());
+    assert!(user.is_ok());
+}
@@ -0,0 +1,83 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use w3b2_solana_connector::client::{PreparedMessage, TransactionBuilder};
+use w3b2_solana_connector::pda::{
+    find_admin_profile_pda, find_admin_profile_pda_for, find_user_profile_pda,
+    find_user_profile_pda_for, ADMIN_SEED, USER_SEED,
+};
+use w3b2_solana_program::state::admin_index_seed;
+
+/// Asserts that `bump` is the canonical bump of `seeds`: it rebuilds `address`, and no higher
+/// bump yields a valid PDA. This is the bump Anchor's `bump` constraint accepts.
+fn assert_canonical_bump(seeds: &[&[u8]], address: Pubkey, bump: u8) {
+    let with_bump = |bump: u8| {
+        let bump_seed = [bump];
+        let mut seeds = seeds.to_vec();
+        seeds.push(&bump_seed);
+        Pubkey::create_program_address(&seeds, &w3b2_solana_program::ID)
+    };
+
+    assert_eq!(with_bump(bump).unwrap(), address);
+    for higher in (bump..u8::MAX).map(|bump| bump + 1) {
+        assert!(with_bump(higher).is_err(), "bump {higher} is also valid");
+    }
+}
+
+#[test]
+fn test_admin_profile_pda_returns_the_canonical_bump() {
+    let authority = Pubkey::new_unique();
+
+    for index in [0, 1, 7] {
+        let (pda, bump) = find_admin_profile_pda(&authority, index);
+        let index_seed = admin_index_seed(index);
+
+        assert_canonical_bump(&[ADMIN_SEED, authority.as_ref(), &index_seed], pda, bump);
+    }
+}
+
+#[test]
+fn test_user_profile_pda_returns_the_canonical_bump() {
+    let authority = Pubkey::new_unique();
+    let (admin_pda, _) = find_admin_profile_pda(&Pubkey::new_unique(), 0);
+
+    let (pda, bump) = find_user_profile_pda(&authority, &admin_pda);
+
+    assert_canonical_bump(
+        &[USER_SEED, authority.as_ref(), admin_pda.as_ref()],
+        pda,
+        bump,
+    );
+}
+
+#[test]
+fn test_builder_addresses_an_overridden_program_id() {
+    // === 1. Arrange ===
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let rpc_client = Arc::new(RpcClient::new("http://127.0.0.1:0".to_string()));
+    let builder = TransactionBuilder::new(rpc_client).with_program_id(program_id);
+    let (admin_pda, _) = find_admin_profile_pda_for(&program_id, &authority, 0);
+    let (user_pda, _) = find_user_profile_pda_for(&program_id, &authority, &admin_pda);
+
+    // === 2. Act ===
+    let register = PreparedMessage::decode(builder.prepare_admin_register_profile(
+        authority,
+        0,
+        Pubkey::new_unique(),
+    ))
+    .unwrap();
+    let deposit =
+        PreparedMessage::decode(builder.prepare_user_deposit(authority, admin_pda, 1_000)).unwrap();
+
+    // === 3. Assert ===
+    assert_ne!(admin_pda, find_admin_profile_pda(&authority, 0).0);
+    assert_eq!(register.program_ids(), vec![program_id]);
+    assert!(register.message.account_keys.contains(&admin_pda));
+    assert_eq!(deposit.program_ids(), vec![program_id]);
+    assert!(deposit.message.account_keys.contains(&user_pda));
+    assert!(!deposit
+        .message
+        .account_keys
+        .contains(&find_user_profile_pda(&authority, &admin_pda).0));
+}
@@ -1,19 +1,27 @@
 use anchor_lang::AccountDeserialize;
 use async_trait::async_trait;
+use solana_banks_interface::TransactionConfirmationStatus as BanksConfirmationStatus;
 use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_program_test::*;
 use solana_sdk::message::Message;
 use solana_sdk::transport::TransportError;
+use solana_sdk::{ed25519_program, sysvar};
 use solana_sdk::{
     hash::Hash,
     native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::{Keypair, Signature, Signer},
     transaction::Transaction,
 };
 use solana_system_interface::instruction as system_instruction;
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
 use std::{env, sync::Arc};
 use w3b2_solana_connector::client::{AsyncRpcClient, TransactionBuilder, UserDispatchCommandArgs};
+use w3b2_solana_connector::events::{parse_program_logs, BridgeEventData};
+use w3b2_solana_connector::oracle::sign_price_message;
+use w3b2_solana_connector::pda::find_user_profile_pda;
+use w3b2_solana_program::protocols::CommandMode;
 use w3b2_solana_program::state::AdminProfile;
 
 // A mock RPC client that wraps BanksClient for testing purposes.
@@ -32,9 +40,42 @@ impl AsyncRpcClient for MockRpcClient {
     async fn send_and_confirm_transaction(
         &self,
         _transaction: &Transaction,
-    ) -> Result<solana_sdk::signature::Signature, ClientError> {
+    ) -> Result<Signature, ClientError> {
         unimplemented!("This should not be called in the new test flow")
     }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>, ClientError> {
+        let statuses = self
+            .0
+            .get_transaction_statuses(signatures.to_vec())
+            .await
+            .map_err(|e| ClientError::from(TransportError::from(e)))?;
+        Ok(statuses
+            .into_iter()
+            .map(|status| {
+                status.map(|status| TransactionStatus {
+                    slot: status.slot,
+                    confirmations: status.confirmations,
+                    status: status.err.clone().map_or(Ok(()), Err),
+                    err: status.err,
+                    confirmation_status: status.confirmation_status.map(|level| match level {
+                        BanksConfirmationStatus::Processed => {
+                            TransactionConfirmationStatus::Processed
+                        }
+                        BanksConfirmationStatus::Confirmed => {
+                            TransactionConfirmationStatus::Confirmed
+                        }
+                        BanksConfirmationStatus::Finalized => {
+                            TransactionConfirmationStatus::Finalized
+                        }
+                    }),
+                })
+            })
+            .collect())
+    }
 }
 
 /// Sets up the `solana-program-test` environment and starts a test validator.
@@ -85,8 +126,11 @@ async fn setup_admin_profile(
         &w3b2_solana_program::ID,
     );
 
-    let message_bytes = transaction_builder
-        .prepare_admin_register_profile(admin_authority.pubkey(), admin_comm_key.pubkey());
+    let message_bytes = transaction_builder.prepare_admin_register_profile(
+        admin_authority.pubkey(),
+        0,
+        admin_comm_key.pubkey(),
+    );
 
     let mut admin_message: Message = bincode::serde::borrow_decode_from_slice(
         message_bytes.as_slice(),
@@ -190,7 +234,8 @@ async fn test_admin_close_profile() -> anyhow::Result<()> {
         .get_balance(admin_authority.pubkey())
         .await?;
 
-    let message_bytes = transaction_builder.prepare_admin_close_profile(admin_authority.pubkey());
+    let message_bytes =
+        transaction_builder.prepare_admin_close_profile(admin_authority.pubkey(), 0);
 
     let mut close_message: Message = bincode::serde::borrow_decode_from_slice(
         message_bytes.as_slice(),
@@ -234,11 +279,16 @@ async fn test_admin_set_config() -> anyhow::Result<()> {
 
     let message_bytes = transaction_builder.prepare_admin_set_config(
         admin_authority.pubkey(),
+        0,
         Some(new_oracle.pubkey()),
         Some(new_validity),
         Some(new_comm_key.pubkey()),
         Some(100), // New unban fee
-    );
+        None,
+        None,
+        None,
+        None,
+    )?;
 
     let mut set_config_message: Message =
         bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())?.0;
@@ -277,10 +327,12 @@ async fn test_admin_dispatch_command() -> anyhow::Result<()> {
 
     let message_bytes = transaction_builder.prepare_admin_dispatch_command(
         admin_authority.pubkey(),
+        0,
         user_pda,
         command_id,
         payload.clone(),
-    );
+        CommandMode::OneWay,
+    )?;
     let mut dispatch_message: Message =
         bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())?.0;
     dispatch_message.recent_blockhash = context.last_blockhash;
@@ -301,8 +353,6 @@ async fn test_admin_dispatch_command() -> anyhow::Result<()> {
     Ok(())
 }
 
-use std::convert::TryInto;
-
 #[tokio::test]
 #[ignore = "Requires a compiled BPF program"]
 async fn test_full_payment_cycle_and_withdraw() -> anyhow::Result<()> {
@@ -331,14 +381,14 @@ async fn test_full_payment_cycle_and_withdraw() -> anyhow::Result<()> {
     let command_price = 100_000u64;
     let timestamp = chrono::Utc::now().timestamp();
 
-    // The oracle (the admin in this case) signs the price data
-    let message = [
-        command_id.to_le_bytes().as_ref(),
-        command_price.to_le_bytes().as_ref(),
-        timestamp.to_le_bytes().as_ref(),
-    ]
-    .concat();
-    let signature = admin_authority.sign_message(&message);
+    // The oracle (the admin in this case) signs the price data for the user's profile
+    let signature = sign_price_message(
+        &admin_authority,
+        command_id,
+        command_price,
+        timestamp,
+        user_pda,
+    );
 
     let message_bytes = transaction_builder.prepare_user_dispatch_command(
         user_authority.pubkey(),
@@ -347,11 +397,13 @@ async fn test_full_payment_cycle_and_withdraw() -> anyhow::Result<()> {
             command_id,
             price: command_price,
             timestamp,
+            user_profile_pda: user_pda,
             payload: vec![1, 2, 3], // Dummy payload
             oracle_pubkey: admin_authority.pubkey(),
-            oracle_signature: signature.as_ref().try_into().unwrap(),
+            oracle_signature: signature,
+            mode: CommandMode::OneWay,
         },
-    );
+    )?;
     let mut dispatch_message: Message =
         bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())?.0;
     dispatch_message.recent_blockhash = context.last_blockhash;
@@ -386,6 +438,7 @@ async fn test_full_payment_cycle_and_withdraw() -> anyhow::Result<()> {
 
     let message_bytes = transaction_builder.prepare_admin_withdraw(
         admin_authority.pubkey(),
+        0,
         command_price,
         admin_authority.pubkey(), // Destination is the admin's own wallet
     );
@@ -638,6 +691,8 @@ async fn test_log_action_by_user() -> anyhow::Result<()> {
         admin_pda,
         session_id,
         action_code,
+        w3b2_solana_program::action_categories::HTTP,
+        None,
     );
     let mut log_message: Message =
         bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())?.0;
@@ -658,6 +713,138 @@ async fn test_log_action_by_user() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Builds the oracle-signed arguments of a paid command of `user_pda`, signed by `oracle`.
+fn paid_command_args(
+    oracle: &Keypair,
+    command_id: u16,
+    price: u64,
+    user_pda: Pubkey,
+) -> UserDispatchCommandArgs {
+    let timestamp = chrono::Utc::now().timestamp();
+    UserDispatchCommandArgs {
+        command_id,
+        price,
+        timestamp,
+        user_profile_pda: user_pda,
+        payload: vec![1, 2, 3],
+        oracle_pubkey: oracle.pubkey(),
+        oracle_signature: sign_price_message(oracle, command_id, price, timestamp, user_pda),
+        mode: CommandMode::OneWay,
+    }
+}
+
+#[test]
+fn test_dispatch_and_log_orders_its_instructions() {
+    // === 1. Arrange ===
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let user = Pubkey::new_unique();
+    let admin_pda = Pubkey::new_unique();
+    let (user_pda, _) = find_user_profile_pda(&user, &admin_pda);
+
+    // === 2. Act ===
+    let message_bytes = builder
+        .prepare_user_dispatch_and_log(
+            user,
+            admin_pda,
+            paid_command_args(&Keypair::new(), 7, 1_000, user_pda),
+            99,
+            200,
+            w3b2_solana_program::action_categories::HTTP,
+            None,
+        )
+        .unwrap();
+    let message: Message =
+        bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())
+            .unwrap()
+            .0;
+
+    // === 3. Assert ===
+    let programs: Vec<Pubkey> = message
+        .instructions
+        .iter()
+        .map(|ix| *ix.program_id(&message.account_keys))
+        .collect();
+    assert_eq!(
+        programs,
+        vec![
+            ed25519_program::id(),
+            w3b2_solana_program::ID,
+            w3b2_solana_program::ID
+        ]
+    );
+    let accounts = |index: usize| -> Vec<Pubkey> {
+        message.instructions[index]
+            .accounts
+            .iter()
+            .map(|&i| message.account_keys[i as usize])
+            .collect()
+    };
+    assert!(accounts(1).contains(&sysvar::instructions::id()));
+    assert_eq!(&accounts(1)[..3], &[user, user_pda, admin_pda]);
+    assert_eq!(accounts(2), vec![user, user_pda, admin_pda]);
+}
+
+#[tokio::test]
+#[ignore = "Requires a compiled BPF program"]
+async fn test_dispatch_and_log_emits_both_events() -> anyhow::Result<()> {
+    // === 1. Arrange ===
+    let mut context = setup_test_environment().await;
+    let (transaction_builder, (admin_authority, admin_pda), (user_authority, user_pda)) =
+        setup_user_profile(&mut context).await?;
+    let message_bytes =
+        transaction_builder.prepare_user_deposit(user_authority.pubkey(), admin_pda, 200_000);
+    let mut deposit_message: Message =
+        bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())?.0;
+    deposit_message.recent_blockhash = context.last_blockhash;
+    let mut deposit_tx = Transaction::new_unsigned(deposit_message);
+    deposit_tx.sign(&[&user_authority], context.last_blockhash);
+    context.banks_client.process_transaction(deposit_tx).await?;
+    context.last_blockhash = context.banks_client.get_latest_blockhash().await?;
+
+    // === 2. Act ===
+    let message_bytes = transaction_builder.prepare_user_dispatch_and_log(
+        user_authority.pubkey(),
+        admin_pda,
+        paid_command_args(&admin_authority, 42, 100_000, user_pda),
+        12345,
+        200,
+        w3b2_solana_program::action_categories::HTTP,
+        None,
+    )?;
+    let mut message: Message =
+        bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())?.0;
+    message.recent_blockhash = context.last_blockhash;
+    let mut tx = Transaction::new_unsigned(message);
+    tx.sign(&[&user_authority], context.last_blockhash);
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await?;
+
+    // === 3. Assert ===
+    result.result?;
+    let logs = result.metadata.expect("transaction metadata").log_messages;
+    let events: Vec<BridgeEventData> = parse_program_logs(&logs)
+        .into_iter()
+        .map(|e| e.data)
+        .collect();
+    let dispatched = events.iter().position(|e| {
+        matches!(e, BridgeEventData::UserCommandDispatched(e)
+            if e.sender_user_pda == user_pda && e.command_id == 42)
+    });
+    let logged = events.iter().position(|e| {
+        matches!(e, BridgeEventData::OffChainActionLogged(e)
+            if e.user_profile_pda == user_pda && e.session_id == 12345)
+    });
+    assert!(
+        dispatched.expect("the command should be dispatched")
+            < logged.expect("the action should be logged")
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 #[ignore = "Requires a compiled BPF program"]
 async fn test_full_ban_unban_cycle() -> anyhow::Result<()> {
@@ -670,11 +857,16 @@ async fn test_full_ban_unban_cycle() -> anyhow::Result<()> {
 
     let message_bytes = transaction_builder.prepare_admin_set_config(
         admin_authority.pubkey(),
+        0,
         None,
         None,
         None,
         Some(unban_fee),
-    );
+        None,
+        None,
+        None,
+        None,
+    )?;
     let mut set_config_message: Message =
         bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())?.0;
     set_config_message.recent_blockhash = context.last_blockhash;
@@ -688,7 +880,7 @@ async fn test_full_ban_unban_cycle() -> anyhow::Result<()> {
 
     // === 2. Act: Admin bans the user ===
     let message_bytes =
-        transaction_builder.prepare_admin_ban_user(admin_authority.pubkey(), user_pda);
+        transaction_builder.prepare_admin_ban_user(admin_authority.pubkey(), 0, user_pda);
     let mut ban_message: Message =
         bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())?.0;
     ban_message.recent_blockhash = context.last_blockhash;
@@ -744,7 +936,7 @@ async fn test_full_ban_unban_cycle() -> anyhow::Result<()> {
 
     // === 6. Act: Admin unbans the user ===
     let message_bytes =
-        transaction_builder.prepare_admin_unban_user(admin_authority.pubkey(), user_pda);
+        transaction_builder.prepare_admin_unban_user(admin_authority.pubkey(), 0, user_pda);
     let mut unban_message: Message =
         bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())?.0;
     unban_message.recent_blockhash = context.last_blockhash;
@@ -765,3 +957,42 @@ async fn test_full_ban_unban_cycle() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_signature_statuses_through_banks_client() -> anyhow::Result<()> {
+    // === 1. Arrange: a bank without the program is enough for a plain transfer ===
+    let mut context = ProgramTest::default().start_with_context().await;
+    let rpc_client = MockRpcClient(context.banks_client.clone());
+    let recipient = create_funded_keypair(&mut context).await?;
+    let transfer_tx = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &recipient.pubkey(),
+            &Pubkey::new_unique(),
+            LAMPORTS_PER_SOL / 2,
+        )],
+        Some(&recipient.pubkey()),
+        &[&recipient],
+        context.last_blockhash,
+    );
+    let signature = transfer_tx.signatures[0];
+    context
+        .banks_client
+        .process_transaction(transfer_tx)
+        .await?;
+
+    // === 2. Act ===
+    let unknown = Signature::new_unique();
+    let statuses = rpc_client
+        .get_signature_statuses(&[signature, unknown])
+        .await?;
+
+    // === 3. Assert ===
+    assert_eq!(statuses.len(), 2);
+    let status = statuses[0].as_ref().expect("the transfer was processed");
+    assert!(status.err.is_none());
+    assert!(status.status.is_ok());
+    assert!(status.confirmation_status.is_some());
+    assert!(statuses[1].is_none());
+
+    Ok(())
+}
@@ -0,0 +1,284 @@
+mod mock;
+
+use anchor_lang::AccountDeserialize;
+use litesvm::LiteSVM;
+use mock::svm::{execute, PATH_SBF};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    clock::Clock,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::sync::Arc;
+use w3b2_solana_connector::{
+    client::{TransactionBuilder, UserDispatchCommandArgs},
+    events::{parse_program_logs, BridgeEvent, BridgeEventData, CaughtUp, EventSource},
+    oracle::sign_price_message,
+    pda::{find_admin_profile_pda, find_user_profile_pda},
+    replay::{fold_admin_profile, fold_user_profile},
+};
+use w3b2_solana_program::events::*;
+use w3b2_solana_program::protocols::CommandMode;
+use w3b2_solana_program::state::{AdminProfile, UserProfile};
+
+fn event(data: BridgeEventData) -> BridgeEvent {
+    BridgeEvent {
+        source: EventSource::Catchup,
+        signature: None,
+        slot: 0,
+        data,
+    }
+}
+
+#[test]
+fn test_fold_follows_the_profile_lifecycle() {
+    // === 1. Arrange ===
+    let user = Pubkey::new_unique();
+    let user_pda = Pubkey::new_unique();
+    let admin_pda = Pubkey::new_unique();
+    let other_pda = Pubkey::new_unique();
+    let banned = |user_profile_pda| {
+        event(BridgeEventData::UserBanned(UserBanned {
+            admin_authority: Pubkey::new_unique(),
+            admin_pda,
+            user_profile_pda,
+            ts: 0,
+        }))
+    };
+    let dispatched = |sender_user_pda, price_paid| {
+        event(BridgeEventData::UserCommandDispatched(
+            UserCommandDispatched {
+                sender: user,
+                sender_user_pda,
+                target_admin_pda: admin_pda,
+                command_id: 1,
+                price_paid,
+                oracle_authority: admin_pda,
+                payload: vec![],
+                mode: CommandMode::OneWay,
+                ts: 0,
+            },
+        ))
+    };
+    let history = vec![
+        // Events before the profile exists are ignored.
+        dispatched(user_pda, 1),
+        event(BridgeEventData::UserProfileCreated(UserProfileCreated {
+            authority: user,
+            user_pda,
+            target_admin_pda: admin_pda,
+            communication_pubkey: user,
+            ts: 0,
+        })),
+        event(BridgeEventData::UserFundsDeposited(UserFundsDeposited {
+            authority: user,
+            user_profile_pda: user_pda,
+            amount: 1_000,
+            new_deposit_balance: 1_000,
+            source: user,
+            ts: 0,
+        })),
+        dispatched(user_pda, 300),
+        dispatched(other_pda, 500),
+        banned(user_pda),
+        banned(user_pda),
+        banned(other_pda),
+        event(BridgeEventData::UserUnbanRequested(UserUnbanRequested {
+            user_authority: user,
+            user_profile_pda: user_pda,
+            admin_pda,
+            fee_paid: 200,
+            ts: 0,
+        })),
+        event(BridgeEventData::CaughtUp(CaughtUp {
+            pda: user_pda,
+            slot: 0,
+        })),
+    ];
+
+    // === 2. Act ===
+    let view = fold_user_profile(&history);
+
+    // === 3. Assert ===
+    assert_eq!(view.user_pda, user_pda);
+    assert_eq!(view.admin_pda, admin_pda);
+    assert_eq!(view.deposit_balance, 500);
+    assert!(view.banned);
+    assert!(view.unban_requested);
+    assert_eq!(view.ban_count, 1, "a repeated ban does not count twice");
+    assert_eq!(view.commands_dispatched, 1);
+    assert_eq!(view.unban_requests, 1);
+    assert!(!view.closed);
+}
+
+fn try_execute(svm: &mut LiteSVM, message_bytes: Vec<u8>, signer: &Keypair) -> Vec<BridgeEvent> {
+    // A rejected operation emits nothing and leaves both accounts unchanged.
+    execute(svm, message_bytes, signer)
+        .map(|meta| parse_program_logs(&meta.logs))
+        .unwrap_or_default()
+}
+
+#[test]
+#[ignore = "Requires a compiled BPF program"]
+fn test_fold_matches_the_accounts_after_random_operations() {
+    for seed in 0..8u64 {
+        // === 1. Arrange ===
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut svm = LiteSVM::new();
+        svm.add_program_from_file(w3b2_solana_program::ID, PATH_SBF)
+            .unwrap();
+        svm.set_sysvar(&Clock::default());
+
+        let builder =
+            TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+        let admin = Keypair::new();
+        let user = Keypair::new();
+        svm.airdrop(&admin.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+        let (admin_pda, _) = find_admin_profile_pda(&admin.pubkey(), 0);
+        let (user_pda, _) = find_user_profile_pda(&user.pubkey(), &admin_pda);
+        let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+
+        let mut history = Vec::new();
+        let setup: Vec<(Vec<u8>, &Keypair)> = vec![
+            (
+                builder.prepare_admin_register_profile(admin.pubkey(), 0, Pubkey::new_unique()),
+                &admin,
+            ),
+            (
+                builder
+                    .prepare_admin_set_config(
+                        admin.pubkey(),
+                        0,
+                        None,
+                        None,
+                        None,
+                        Some(20_000),
+                        None,
+                        None,
+                        None,
+                        None,
+                    )
+                    .unwrap(),
+                &admin,
+            ),
+            (
+                builder.prepare_user_create_profile(user.pubkey(), admin_pda, Pubkey::new_unique()),
+                &user,
+            ),
+        ];
+        for (message_bytes, signer) in setup {
+            history.extend(try_execute(&mut svm, message_bytes, signer));
+        }
+
+        // === 2. Act ===
+        // Random operations, some of which the program rejects (e.g. an unban of a user
+        // who is not banned, or a withdrawal above the balance).
+        for _ in 0..40 {
+            let amount = rng.gen_range(0..200_000u64);
+            let (message_bytes, signer) = match rng.gen_range(0..9) {
+                0 => (
+                    builder.prepare_user_deposit(user.pubkey(), admin_pda, amount),
+                    &user,
+                ),
+                1 => (
+                    builder.prepare_user_withdraw(user.pubkey(), admin_pda, amount, user.pubkey()),
+                    &user,
+                ),
+                2 | 3 => {
+                    let command_id = rng.gen_range(1..10);
+                    let args = UserDispatchCommandArgs {
+                        command_id,
+                        price: amount,
+                        timestamp,
+                        user_profile_pda: user_pda,
+                        payload: vec![],
+                        oracle_pubkey: admin.pubkey(),
+                        oracle_signature: sign_price_message(
+                            &admin, command_id, amount, timestamp, user_pda,
+                        ),
+                        mode: CommandMode::OneWay,
+                    };
+                    (
+                        builder
+                            .prepare_user_dispatch_command(user.pubkey(), admin_pda, args)
+                            .unwrap(),
+                        &user,
+                    )
+                }
+                4 => (
+                    builder.prepare_admin_ban_user(admin.pubkey(), 0, user_pda),
+                    &admin,
+                ),
+                5 => (
+                    builder.prepare_user_request_unban(user.pubkey(), admin_pda),
+                    &user,
+                ),
+                6 => (
+                    builder.prepare_admin_unban_user(admin.pubkey(), 0, user_pda),
+                    &admin,
+                ),
+                7 => (
+                    builder.prepare_user_set_low_balance_threshold(
+                        user.pubkey(),
+                        admin_pda,
+                        amount,
+                    ),
+                    &user,
+                ),
+                _ => (
+                    builder.prepare_admin_withdraw(admin.pubkey(), 0, amount / 4, admin.pubkey()),
+                    &admin,
+                ),
+            };
+            history.extend(try_execute(&mut svm, message_bytes, signer));
+        }
+        let user_view = fold_user_profile(&history);
+        let admin_view = fold_admin_profile(&history);
+
+        // === 3. Assert ===
+        let account = svm.get_account(&user_pda).unwrap();
+        let user_profile = UserProfile::try_deserialize(&mut account.data.as_slice()).unwrap();
+        let account = svm.get_account(&admin_pda).unwrap();
+        let admin_profile = AdminProfile::try_deserialize(&mut account.data.as_slice()).unwrap();
+
+        assert_eq!(
+            user_view.deposit_balance, user_profile.deposit_balance,
+            "seed {seed}"
+        );
+        assert_eq!(user_view.banned, user_profile.banned, "seed {seed}");
+        assert_eq!(
+            user_view.unban_requested, user_profile.unban_requested,
+            "seed {seed}"
+        );
+        assert_eq!(user_view.ban_count, user_profile.ban_count, "seed {seed}");
+        assert_eq!(
+            user_view.low_balance_threshold, user_profile.low_balance_threshold,
+            "seed {seed}"
+        );
+        assert_eq!(
+            user_view.low_balance_notified, user_profile.low_balance_notified,
+            "seed {seed}"
+        );
+        assert_eq!(
+            user_view.communication_pubkey, user_profile.communication_pubkey,
+            "seed {seed}"
+        );
+        assert_eq!(admin_view.balance, admin_profile.balance, "seed {seed}");
+        assert_eq!(admin_view.unban_fee, admin_profile.unban_fee, "seed {seed}");
+        assert_eq!(
+            admin_view.oracle_authority, admin_profile.oracle_authority,
+            "seed {seed}"
+        );
+        assert_eq!(
+            admin_view.comm_key_version, admin_profile.comm_key_version,
+            "seed {seed}"
+        );
+        assert_eq!(
+            admin_view.commands_received, user_view.commands_dispatched,
+            "seed {seed}"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "test-util")]
+
+use solana_sdk::pubkey::Pubkey;
+use std::time::Duration;
+use w3b2_solana_connector::{
+    events::{BridgeEvent, BridgeEventData, EventSource},
+    listener::EventListener,
+    workers::EventManager,
+};
+use w3b2_solana_program::events::{UserBanned, UserUnbanned};
+
+/// A consumer under test: follows the ban status of a user through their listener and
+/// records every change it observes.
+async fn track_ban_status(listener: &mut EventListener, changes: usize) -> Vec<bool> {
+    let mut statuses = Vec::new();
+    while statuses.len() < changes {
+        let Some(event) = listener.next_live_event().await else {
+            break;
+        };
+        match event.data {
+            BridgeEventData::UserBanned(_) => statuses.push(true),
+            BridgeEventData::UserUnbanned(_) => statuses.push(false),
+            _ => {}
+        }
+    }
+    statuses
+}
+
+fn scripted(slot: u64, data: BridgeEventData) -> BridgeEvent {
+    BridgeEvent {
+        source: EventSource::Live,
+        signature: Some(format!("scripted-{slot}")),
+        slot,
+        data,
+    }
+}
+
+#[tokio::test]
+async fn test_scripted_source_drives_a_consumer_through_ban_and_unban() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let admin_pda = Pubkey::new_unique();
+    let admin_authority = Pubkey::new_unique();
+    let script = vec![
+        (
+            user_pda,
+            scripted(
+                10,
+                BridgeEventData::UserBanned(UserBanned {
+                    admin_authority,
+                    admin_pda,
+                    user_profile_pda: user_pda,
+                    ts: 0,
+                }),
+            ),
+        ),
+        (
+            user_pda,
+            scripted(
+                11,
+                BridgeEventData::UserUnbanned(UserUnbanned {
+                    admin_authority,
+                    admin_pda,
+                    user_profile_pda: user_pda,
+                    ts: 1,
+                }),
+            ),
+        ),
+    ];
+    let (event_manager, handle) = EventManager::new_with_source(script);
+    tokio::spawn(event_manager.run());
+
+    // === 2. Act ===
+    // The listener is created after the manager started; no scripted event is lost.
+    let mut listener = handle.listen_as_user(user_pda).unwrap();
+    let caught_up = tokio::time::timeout(Duration::from_secs(5), listener.next_catchup_event())
+        .await
+        .expect("the empty history should be replayed");
+    let statuses = tokio::time::timeout(Duration::from_secs(5), track_ban_status(&mut listener, 2))
+        .await
+        .expect("the scripted events should arrive");
+
+    // === 3. Assert ===
+    assert!(matches!(
+        caught_up.map(|event| event.data),
+        Some(BridgeEventData::CaughtUp(_))
+    ));
+    assert_eq!(statuses, vec![true, false]);
+
+    drop(listener);
+    tokio::time::timeout(Duration::from_secs(5), handle.stop())
+        .await
+        .expect("the scripted manager should shut down");
+}
@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use solana_client::client_error::ClientError;
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_sdk::{
+    hash::Hash, instruction::InstructionError, message::Message, pubkey::Pubkey,
+    signature::Signature, transaction::Transaction, transaction::TransactionError,
+};
+use std::sync::{Arc, Mutex};
+use w3b2_solana_connector::client::{AsyncRpcClient, TransactionBuilder};
+use w3b2_solana_connector::simulation::{
+    decode_simulation_error, DecodedProgramError, SimulationError,
+};
+use w3b2_solana_program::errors::BridgeError;
+
+/// An RPC client whose simulations fail with a fixed error and logs.
+struct SimulatingRpc {
+    err: Option<TransactionError>,
+    logs: Vec<String>,
+    simulated: Mutex<Vec<Transaction>>,
+}
+
+#[async_trait]
+impl AsyncRpcClient for SimulatingRpc {
+    async fn get_latest_blockhash(&self) -> Result<Hash, ClientError> {
+        unimplemented!("simulations use the latest blockhash of the node")
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        _transaction: &Transaction,
+    ) -> Result<Signature, ClientError> {
+        unimplemented!("simulations never submit transactions")
+    }
+
+    async fn simulate_unsigned_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<RpcSimulateTransactionResult, ClientError> {
+        self.simulated.lock().unwrap().push(transaction.clone());
+        Ok(RpcSimulateTransactionResult {
+            err: self.err.clone(),
+            logs: Some(self.logs.clone()),
+            accounts: None,
+            units_consumed: Some(4_321),
+            loaded_accounts_data_size: None,
+            return_data: None,
+            inner_instructions: None,
+            replacement_blockhash: None,
+        })
+    }
+}
+
+fn custom_error(instruction_index: u8, code: u32) -> TransactionError {
+    TransactionError::InstructionError(instruction_index, InstructionError::Custom(code))
+}
+
+#[tokio::test]
+async fn test_simulate_message_decodes_bridge_error() {
+    // === 1. Arrange ===
+    let code = u32::from(BridgeError::UserIsBanned);
+    let logs = vec![
+        format!("Program {} invoke [1]", w3b2_solana_program::ID),
+        "Program log: Instruction: UserDeposit".to_string(),
+        format!(
+            "Program log: AnchorError occurred. Error Code: UserIsBanned. Error Number: {code}. \
+             Error Message: User Is Banned: This action cannot be performed while the user is banned.."
+        ),
+        format!(
+            "Program {} failed: custom program error: {code:#x}",
+            w3b2_solana_program::ID
+        ),
+    ];
+    let rpc = Arc::new(SimulatingRpc {
+        err: Some(custom_error(0, code)),
+        logs: logs.clone(),
+        simulated: Mutex::new(Vec::new()),
+    });
+    let builder = TransactionBuilder::new(rpc.clone());
+    let message_bytes =
+        builder.prepare_user_deposit(Pubkey::new_unique(), Pubkey::new_unique(), 1_000);
+    let message: Message =
+        bincode::serde::borrow_decode_from_slice(&message_bytes, bincode::config::standard())
+            .unwrap()
+            .0;
+
+    // === 2. Act ===
+    let report = builder.simulate_message(message.clone()).await.unwrap();
+
+    // === 3. Assert ===
+    assert!(!report.is_success());
+    assert_eq!(
+        report.error,
+        Some(SimulationError::Program(DecodedProgramError {
+            instruction_index: 0,
+            code,
+            name: Some("UserIsBanned".to_string()),
+            message: Some(
+                "User Is Banned: This action cannot be performed while the user is banned."
+                    .to_string()
+            ),
+        }))
+    );
+    assert_eq!(report.logs, logs);
+    assert_eq!(report.units_consumed, Some(4_321));
+    // The message is simulated as is, without signatures.
+    let simulated = rpc.simulated.lock().unwrap();
+    assert_eq!(simulated.len(), 1);
+    assert_eq!(simulated[0].message, message);
+    assert!(simulated[0]
+        .signatures
+        .iter()
+        .all(|signature| *signature == Signature::default()));
+}
+
+#[test]
+fn test_decode_simulation_error_handles_constraint_and_unlogged_errors() {
+    // An Anchor account constraint, logged with the offending account.
+    let logs = vec![
+        "Program log: AnchorError caused by account: user_profile. Error Code: \
+         AccountNotInitialized. Error Number: 3012. Error Message: The program expected this \
+         account to be already initialized."
+            .to_string(),
+    ];
+    let SimulationError::Program(err) = decode_simulation_error(custom_error(1, 3012), &logs)
+    else {
+        panic!("expected a program error");
+    };
+    assert_eq!(err.instruction_index, 1);
+    assert_eq!(err.name.as_deref(), Some("AccountNotInitialized"));
+    assert_eq!(
+        err.message.as_deref(),
+        Some("The program expected this account to be already initialized")
+    );
+
+    // A code without a matching log line keeps only the number.
+    let SimulationError::Program(err) = decode_simulation_error(custom_error(0, 6001), &logs)
+    else {
+        panic!("expected a program error");
+    };
+    assert_eq!((err.code, err.name, err.message), (6001, None, None));
+
+    // Errors that are not custom program errors are passed through.
+    assert_eq!(
+        decode_simulation_error(TransactionError::InsufficientFundsForFee, &logs),
+        SimulationError::Transaction(TransactionError::InsufficientFundsForFee)
+    );
+}
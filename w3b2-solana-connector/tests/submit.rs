@@ -0,0 +1,246 @@
+use async_trait::async_trait;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_rpc_client_api::request::{RpcError, RpcResponseErrorData};
+use solana_rpc_client_api::response::RpcSimulateTransactionResult;
+use solana_sdk::{
+    hash::Hash,
+    instruction::InstructionError,
+    message::Message,
+    signature::{Keypair, Signature, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use w3b2_solana_connector::client::{
+    is_transient_submit_error, AsyncRpcClient, SubmitRetryPolicy, TransactionBuilder,
+};
+
+/// An RPC client that answers submissions from a queue of scripted results.
+struct FlakyRpc {
+    results: Mutex<VecDeque<Result<Signature, ClientError>>>,
+    submissions: Mutex<u32>,
+    blockhash_valid: bool,
+}
+
+impl FlakyRpc {
+    fn new(results: Vec<Result<Signature, ClientError>>) -> Arc<Self> {
+        Arc::new(Self {
+            results: Mutex::new(results.into()),
+            submissions: Mutex::new(0),
+            blockhash_valid: true,
+        })
+    }
+
+    fn submissions(&self) -> u32 {
+        *self.submissions.lock().unwrap()
+    }
+}
+
+#[async_trait]
+impl AsyncRpcClient for FlakyRpc {
+    async fn get_latest_blockhash(&self) -> Result<Hash, ClientError> {
+        Ok(Hash::new_unique())
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        _transaction: &Transaction,
+    ) -> Result<Signature, ClientError> {
+        *self.submissions.lock().unwrap() += 1;
+        self.results
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("no more scripted results")
+    }
+
+    async fn is_blockhash_valid(&self, _blockhash: &Hash) -> Result<bool, ClientError> {
+        Ok(self.blockhash_valid)
+    }
+}
+
+fn io_error() -> ClientError {
+    io::Error::new(io::ErrorKind::ConnectionReset, "connection reset").into()
+}
+
+/// An error as reported by a failed preflight simulation.
+fn preflight_error(err: TransactionError) -> ClientError {
+    ClientErrorKind::RpcError(RpcError::RpcResponseError {
+        code: -32002,
+        message: "Transaction simulation failed".to_string(),
+        data: RpcResponseErrorData::SendTransactionPreflightFailure(RpcSimulateTransactionResult {
+            err: Some(err),
+            logs: None,
+            accounts: None,
+            units_consumed: None,
+            loaded_accounts_data_size: None,
+            return_data: None,
+            inner_instructions: None,
+            replacement_blockhash: None,
+        }),
+    })
+    .into()
+}
+
+fn signed_transaction() -> Transaction {
+    let payer = Keypair::new();
+    let message = Message::new(&[], Some(&payer.pubkey()));
+    Transaction::new(&[&payer], message, Hash::new_unique())
+}
+
+fn policy(max_retries: u32) -> SubmitRetryPolicy {
+    SubmitRetryPolicy {
+        max_retries,
+        base_delay: Duration::from_millis(1),
+    }
+}
+
+#[tokio::test]
+async fn test_submit_retries_transient_failures_until_success() {
+    // === 1. Arrange ===
+    let signature = Signature::new_unique();
+    let rpc = FlakyRpc::new(vec![
+        Err(io_error()),
+        Err(preflight_error(TransactionError::BlockhashNotFound)),
+        Ok(signature),
+    ]);
+    let builder = TransactionBuilder::new(rpc.clone());
+
+    // === 2. Act ===
+    let result = builder
+        .submit_transaction_with_retry(&signed_transaction(), &policy(3))
+        .await;
+
+    // === 3. Assert ===
+    assert_eq!(result.unwrap(), signature);
+    assert_eq!(rpc.submissions(), 3);
+}
+
+#[tokio::test]
+async fn test_submit_gives_up_after_max_retries() {
+    let rpc = FlakyRpc::new(vec![
+        Err(io_error()),
+        Err(io_error()),
+        Ok(Signature::default()),
+    ]);
+    let builder = TransactionBuilder::new(rpc.clone());
+
+    let err = builder
+        .submit_transaction_with_retry(&signed_transaction(), &policy(1))
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.attempts, 2);
+    assert!(matches!(err.source.kind(), ClientErrorKind::Io(_)));
+    assert_eq!(rpc.submissions(), 2);
+}
+
+#[tokio::test]
+async fn test_submit_does_not_retry_program_errors() {
+    // === 1. Arrange ===
+    let program_error = TransactionError::InstructionError(0, InstructionError::Custom(6000));
+    let rpc = FlakyRpc::new(vec![
+        Err(preflight_error(program_error.clone())),
+        Err(preflight_error(TransactionError::AlreadyProcessed)),
+    ]);
+    let builder = TransactionBuilder::new(rpc.clone());
+
+    // === 2. Act ===
+    let first = builder
+        .submit_transaction_with_retry(&signed_transaction(), &policy(5))
+        .await
+        .unwrap_err();
+    let second = builder
+        .submit_transaction_with_retry(&signed_transaction(), &policy(5))
+        .await
+        .unwrap_err();
+
+    // === 3. Assert ===
+    assert_eq!(first.attempts, 1);
+    assert_eq!(first.source.get_transaction_error(), Some(program_error));
+    assert_eq!(second.attempts, 1);
+    assert_eq!(rpc.submissions(), 2);
+}
+
+#[tokio::test]
+async fn test_submit_stops_once_the_blockhash_expired() {
+    let rpc = Arc::new(FlakyRpc {
+        results: Mutex::new(vec![Err(preflight_error(TransactionError::BlockhashNotFound))].into()),
+        submissions: Mutex::new(0),
+        blockhash_valid: false,
+    });
+    let builder = TransactionBuilder::new(rpc.clone());
+
+    let err = builder
+        .submit_transaction_with_retry(&signed_transaction(), &policy(5))
+        .await
+        .unwrap_err();
+
+    // The transaction has to be re-signed with a new blockhash, so resending it is pointless.
+    assert_eq!(err.attempts, 1);
+    assert_eq!(
+        err.source.get_transaction_error(),
+        Some(TransactionError::BlockhashNotFound)
+    );
+    assert!(err.blockhash_expired);
+    assert_eq!(rpc.submissions(), 1);
+}
+
+#[tokio::test]
+async fn test_submit_reports_expiry_of_unconfirmed_transactions() {
+    // === 1. Arrange ===
+    // `RpcClient` gives up confirming a transaction once its blockhash is too old.
+    let unconfirmed = || -> ClientError {
+        ClientErrorKind::RpcError(RpcError::ForUser(
+            "unable to confirm transaction".to_string(),
+        ))
+        .into()
+    };
+    let expired = Arc::new(FlakyRpc {
+        results: Mutex::new(vec![Err(unconfirmed())].into()),
+        submissions: Mutex::new(0),
+        blockhash_valid: false,
+    });
+    let lagging = FlakyRpc::new(vec![Err(preflight_error(
+        TransactionError::BlockhashNotFound,
+    ))]);
+
+    // === 2. Act ===
+    let expired_err = TransactionBuilder::new(expired)
+        .submit_transaction_with_retry(&signed_transaction(), &policy(0))
+        .await
+        .unwrap_err();
+    let lagging_err = TransactionBuilder::new(lagging)
+        .submit_transaction_with_retry(&signed_transaction(), &policy(0))
+        .await
+        .unwrap_err();
+
+    // === 3. Assert ===
+    assert!(expired_err.blockhash_expired);
+    assert!(expired_err.to_string().contains("blockhash"));
+    // A node that does not know a still valid blockhash is lagging behind.
+    assert!(!lagging_err.blockhash_expired);
+}
+
+#[test]
+fn test_transient_error_classification() {
+    let node_unhealthy: ClientError = ClientErrorKind::RpcError(RpcError::RpcResponseError {
+        code: -32005,
+        message: "Node is unhealthy".to_string(),
+        data: RpcResponseErrorData::Empty,
+    })
+    .into();
+    assert!(is_transient_submit_error(&io_error()));
+    assert!(is_transient_submit_error(&node_unhealthy));
+    assert!(!is_transient_submit_error(&preflight_error(
+        TransactionError::AlreadyProcessed
+    )));
+    assert!(!is_transient_submit_error(&preflight_error(
+        TransactionError::InsufficientFundsForFee
+    )));
+    assert!(!is_transient_submit_error(
+        &ClientErrorKind::Custom("bad request".to_string()).into()
+    ));
+}
@@ -0,0 +1,1665 @@
+mod mock;
+
+use mock::*;
+use solana_sdk::{instruction::InstructionError, pubkey::Pubkey, transaction::TransactionError};
+use std::{sync::Arc, time::Duration};
+use tokio_stream::StreamExt;
+use w3b2_solana_connector::config::{ConnectorConfig, LiveSource};
+use w3b2_solana_connector::{
+    error::ConnectorError,
+    events::{
+        BridgeEvent, BridgeEventData, CaughtUp, DispatchRejectionReason, EventCategory, EventKind,
+        EventSource,
+    },
+    listener::{EventListener, HistoryOptions, OverflowPolicy},
+    pda::find_user_profile_pda,
+    storage::Storage,
+    workers::EventManager,
+};
+use w3b2_solana_program::errors::BridgeError;
+use w3b2_solana_program::events::{UserBanned, UserCommandDispatched, UserFundsDeposited};
+use w3b2_solana_program::protocols::CommandMode;
+
+#[tokio::test]
+async fn test_catchup_worker_uses_pluggable_rpc() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let signature = random_signature();
+    let rpc = Arc::new(MockSyncRpc::new());
+    rpc.push_transaction(
+        &signature,
+        42,
+        &[user_pda],
+        vec![event_log(&UserFundsDeposited {
+            authority: Pubkey::new_unique(),
+            user_profile_pda: user_pda,
+            amount: 1_000,
+            new_deposit_balance: 1_000,
+            source: Pubkey::new_unique(),
+            ts: 0,
+        })],
+    );
+    let storage = Arc::new(MemoryStorage::new());
+    let config = Arc::new(test_config(spawn_ws_stub().await));
+
+    let (event_manager, handle) = EventManager::new(config, rpc.clone(), storage.clone());
+    tokio::spawn(event_manager.run());
+
+    // === 2. Act ===
+    // The program-wide poll picks the transaction up and delivers it on the live channel.
+    let mut listener = handle.listen_as_user(user_pda).unwrap();
+    let event = tokio::time::timeout(Duration::from_secs(5), listener.next_live_event())
+        .await
+        .expect("polled event should arrive")
+        .expect("listener channel should be open");
+
+    // === 3. Assert ===
+    assert_eq!(event.source, EventSource::Catchup);
+    match event.data {
+        BridgeEventData::UserFundsDeposited(e) => assert_eq!(e.amount, 1_000),
+        other => panic!("unexpected event: {:?}", other),
+    }
+    assert!(rpc.signature_requests() > 0);
+    wait_for_cursor(storage.as_ref(), &signature).await;
+    assert_eq!(storage.get_last_slot().await.unwrap(), 42);
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_every_listener_of_a_pda_receives_live_events() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    let config = Arc::new(test_config(spawn_ws_stub().await));
+    let (event_manager, handle) =
+        EventManager::new(config, rpc.clone(), Arc::new(MemoryStorage::new()));
+    tokio::spawn(event_manager.run());
+
+    let mut first = handle.listen_as_user(user_pda).unwrap();
+    let mut second = handle.listen_as_user(user_pda).unwrap();
+    // The history is replayed only after registration, so both listeners are now routed.
+    drain_catchup(&mut first).await;
+    drain_catchup(&mut second).await;
+
+    // === 2. Act ===
+    rpc.push_transaction(
+        &random_signature(),
+        30,
+        &[user_pda],
+        vec![deposit_log(user_pda, 7)],
+    );
+    let mut received = Vec::new();
+    for listener in [&mut first, &mut second] {
+        let event = tokio::time::timeout(Duration::from_secs(5), listener.next_live_event())
+            .await
+            .expect("polled event should arrive at every listener")
+            .expect("listener channel should be open");
+        received.push(event.data);
+    }
+    // Unsubscribing one listener leaves the other one registered.
+    first.unsubscribe().await;
+    rpc.push_transaction(
+        &random_signature(),
+        31,
+        &[user_pda],
+        vec![deposit_log(user_pda, 8)],
+    );
+    let after_unsubscribe = tokio::time::timeout(Duration::from_secs(5), second.next_live_event())
+        .await
+        .expect("polled event should arrive at the remaining listener")
+        .expect("listener channel should be open");
+
+    // === 3. Assert ===
+    for data in received {
+        assert!(matches!(data, BridgeEventData::UserFundsDeposited(e) if e.amount == 7));
+    }
+    assert!(
+        matches!(after_unsubscribe.data, BridgeEventData::UserFundsDeposited(e) if e.amount == 8)
+    );
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_listener_registrations_are_bounded_by_max_listeners() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let mut config = test_config(spawn_ws_stub().await);
+    config.channels.max_listeners = Some(2);
+    let (event_manager, handle) = EventManager::new(
+        Arc::new(config),
+        Arc::new(MockSyncRpc::new()),
+        Arc::new(MemoryStorage::new()),
+    );
+    tokio::spawn(event_manager.run());
+
+    let first = handle.listen_as_user_live_only(user_pda).unwrap();
+    let _second = handle
+        .listen_as_admin_live_only(Pubkey::new_unique())
+        .unwrap();
+
+    // === 2. Act ===
+    let over_limit = handle.listen_as_user(user_pda);
+    let over_limit_categorized = handle.listen_by_category(user_pda);
+    first.unsubscribe().await;
+    // The slot is given back once the dispatcher has removed the listener.
+    let after_release = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Ok(listener) = handle.listen_as_user(user_pda) {
+                return listener;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+
+    // === 3. Assert ===
+    assert_eq!(over_limit.err(), Some(ConnectorError::TooManyListeners(2)));
+    assert_eq!(
+        over_limit_categorized.err(),
+        Some(ConnectorError::TooManyListeners(2))
+    );
+    assert!(
+        after_release.is_ok(),
+        "the released slot should be reusable"
+    );
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_listener_dropped_right_after_registering_frees_its_slot() {
+    // === 1. Arrange ===
+    let mut config = test_config(spawn_ws_stub().await);
+    config.channels.max_listeners = Some(1);
+    let (event_manager, handle) = EventManager::new(
+        Arc::new(config),
+        Arc::new(MockSyncRpc::new()),
+        Arc::new(MemoryStorage::new()),
+    );
+    tokio::spawn(event_manager.run());
+
+    // === 2. Act ===
+    // Each listener is dropped before the dispatcher has seen its registration. No event
+    // ever arrives for these PDAs, so only the `Unregister` can give the slot back.
+    for _ in 0..50 {
+        let listener = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(listener) = handle.listen_as_user(Pubkey::new_unique()) {
+                    return listener;
+                }
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("the slot of the previous listener should be given back");
+        drop(listener);
+    }
+    let last = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Ok(listener) = handle.listen_as_user_live_only(Pubkey::new_unique()) {
+                return listener;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+
+    // === 3. Assert ===
+    assert!(
+        last.is_ok(),
+        "no slot should stay taken by a dropped listener"
+    );
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_wait_for_returns_the_matching_dispatch() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let admin_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    let config = Arc::new(test_config(spawn_ws_stub().await));
+    let (event_manager, handle) =
+        EventManager::new(config, rpc.clone(), Arc::new(MemoryStorage::new()));
+    tokio::spawn(event_manager.run());
+
+    let mut listener = handle.listen_as_user(user_pda).unwrap();
+    drain_catchup(&mut listener).await;
+    let dispatch_log = |command_id| {
+        event_log(&UserCommandDispatched {
+            sender: Pubkey::new_unique(),
+            sender_user_pda: user_pda,
+            target_admin_pda: admin_pda,
+            command_id,
+            price_paid: 0,
+            oracle_authority: Pubkey::new_unique(),
+            payload: vec![],
+            mode: CommandMode::RequestResponse,
+            ts: 0,
+        })
+    };
+    let is_command = |command_id| {
+        move |event: &BridgeEvent| {
+            matches!(
+                &event.data,
+                BridgeEventData::UserCommandDispatched(e) if e.command_id == command_id
+            )
+        }
+    };
+
+    // === 2. Act ===
+    rpc.push_transaction(
+        &random_signature(),
+        30,
+        &[user_pda],
+        vec![deposit_log(user_pda, 1)],
+    );
+    rpc.push_transaction(&random_signature(), 31, &[user_pda], vec![dispatch_log(6)]);
+    rpc.push_transaction(&random_signature(), 32, &[user_pda], vec![dispatch_log(7)]);
+    let matched = listener
+        .wait_for(is_command(7), Duration::from_secs(5))
+        .await;
+    let missing = listener
+        .wait_for(is_command(8), Duration::from_millis(300))
+        .await;
+
+    // === 3. Assert ===
+    let matched = matched.expect("the dispatch of command 7 should arrive");
+    assert_eq!(matched.slot, 32);
+    assert!(missing.is_none(), "no dispatch of command 8 was sent");
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_broadcast_subscribers_share_one_upstream_listener() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    let config = Arc::new(test_config(spawn_ws_stub().await));
+    let (event_manager, handle) =
+        EventManager::new(config, rpc.clone(), Arc::new(MemoryStorage::new()));
+    tokio::spawn(event_manager.run());
+
+    let mut receivers: Vec<_> = (0..3)
+        .map(|_| handle.broadcast_subscribe(user_pda).unwrap())
+        .collect();
+    // Gives the shared upstream listener time to register with the dispatcher.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // === 2. Act ===
+    rpc.push_transaction(
+        &random_signature(),
+        30,
+        &[user_pda],
+        vec![deposit_log(user_pda, 7)],
+    );
+    rpc.push_transaction(
+        &random_signature(),
+        31,
+        &[user_pda],
+        vec![deposit_log(user_pda, 8)],
+    );
+    let mut received = Vec::new();
+    for rx in &mut receivers {
+        let mut amounts = Vec::new();
+        for _ in 0..2 {
+            let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .expect("polled event should arrive at every receiver")
+                .expect("broadcast channel should be open");
+            match event.data {
+                BridgeEventData::UserFundsDeposited(e) => amounts.push(e.amount),
+                other => panic!("unexpected event: {:?}", other),
+            }
+        }
+        received.push(amounts);
+    }
+
+    // === 3. Assert ===
+    assert_eq!(received, vec![vec![7, 8]; 3]);
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_last_broadcast_receiver_dropped_frees_the_upstream_listener() {
+    // === 1. Arrange ===
+    let mut config = test_config(spawn_ws_stub().await);
+    config.channels.max_listeners = Some(1);
+    let (event_manager, handle) = EventManager::new(
+        Arc::new(config),
+        Arc::new(MockSyncRpc::new()),
+        Arc::new(MemoryStorage::new()),
+    );
+    tokio::spawn(event_manager.run());
+    let rx = handle.broadcast_subscribe(Pubkey::new_unique()).unwrap();
+
+    // === 2. Act ===
+    // No event ever arrives for the PDA, so only the dropped receiver can stop the upstream.
+    drop(rx);
+    let after_release = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Ok(listener) = handle.listen_as_user_live_only(Pubkey::new_unique()) {
+                return listener;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+
+    // === 3. Assert ===
+    assert!(
+        after_release.is_ok(),
+        "the upstream listener's slot should be given back"
+    );
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_lagging_broadcast_receiver_recovers_missed_events_via_catchup() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    let storage = Arc::new(MemoryStorage::new());
+    let mut config = test_config(spawn_ws_stub().await);
+    config.channels.broadcast_capacity = 2;
+    let (event_manager, handle) = EventManager::new(Arc::new(config), rpc.clone(), storage.clone());
+    tokio::spawn(event_manager.run());
+
+    let mut rx = handle.broadcast_subscribe(user_pda).unwrap();
+    // Gives the shared upstream listener time to register with the dispatcher.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    rpc.push_transaction(
+        &random_signature(),
+        10,
+        &[user_pda],
+        vec![deposit_log(user_pda, 0)],
+    );
+    let first = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("polled event should arrive")
+        .expect("broadcast channel should be open");
+
+    // === 2. Act ===
+    // Five more deposits overflow the two-event buffer while the receiver is not reading.
+    let mut last_signature = String::new();
+    for i in 1..=5 {
+        last_signature = random_signature();
+        rpc.push_transaction(
+            &last_signature,
+            10 + i,
+            &[user_pda],
+            vec![deposit_log(user_pda, i)],
+        );
+    }
+    wait_for_cursor(storage.as_ref(), &last_signature).await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let mut events = vec![first];
+    while let Ok(Some(event)) = tokio::time::timeout(Duration::from_millis(500), rx.recv()).await {
+        events.push(event);
+    }
+
+    // === 3. Assert ===
+    let amounts: Vec<u64> = events
+        .iter()
+        .map(|event| match &event.data {
+            BridgeEventData::UserFundsDeposited(e) => e.amount,
+            other => panic!("unexpected event: {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        amounts,
+        vec![0, 1, 2, 3, 4, 5],
+        "every event is delivered exactly once"
+    );
+    assert_eq!(rx.lagged_events(), 3);
+
+    handle.stop().await;
+}
+
+/// Pushes five deposits to a listener with a two-event buffer that only starts reading once
+/// all of them were dispatched. Returns the amounts it received and its dropped-event count.
+async fn deliver_to_slow_consumer(policy: OverflowPolicy) -> (Vec<u64>, u64) {
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    let storage = Arc::new(MemoryStorage::new());
+    let mut config = test_config(spawn_ws_stub().await);
+    config.channels.listener_event_buffer = 2;
+    let (event_manager, handle) = EventManager::new(Arc::new(config), rpc.clone(), storage.clone());
+    tokio::spawn(event_manager.run());
+
+    let mut listener = handle
+        .with_overflow_policy(policy)
+        .listen_as_user(user_pda)
+        .unwrap();
+    drain_catchup(&mut listener).await;
+    let mut last_signature = String::new();
+    for i in 0..5 {
+        last_signature = random_signature();
+        rpc.push_transaction(
+            &last_signature,
+            10 + i,
+            &[user_pda],
+            vec![deposit_log(user_pda, i)],
+        );
+    }
+    wait_for_cursor(storage.as_ref(), &last_signature).await;
+    if policy != OverflowPolicy::Block {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while listener.dropped_events() < 3 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the overflowing events should be dropped");
+    }
+
+    let mut amounts = Vec::new();
+    while let Ok(Some(event)) =
+        tokio::time::timeout(Duration::from_millis(500), listener.next_live_event()).await
+    {
+        if let BridgeEventData::UserFundsDeposited(e) = event.data {
+            amounts.push(e.amount);
+        }
+    }
+    let dropped = listener.dropped_events();
+    drop(listener);
+    handle.stop().await;
+    (amounts, dropped)
+}
+
+#[tokio::test]
+async fn test_block_policy_delivers_every_event_to_a_slow_consumer() {
+    let (amounts, dropped) = deliver_to_slow_consumer(OverflowPolicy::Block).await;
+
+    assert_eq!(amounts, vec![0, 1, 2, 3, 4]);
+    assert_eq!(dropped, 0);
+}
+
+#[tokio::test]
+async fn test_drop_newest_policy_keeps_the_buffered_events() {
+    let (amounts, dropped) = deliver_to_slow_consumer(OverflowPolicy::DropNewest).await;
+
+    assert_eq!(amounts, vec![0, 1]);
+    assert_eq!(dropped, 3);
+}
+
+#[tokio::test]
+async fn test_drop_oldest_policy_keeps_the_freshest_events() {
+    let (amounts, dropped) = deliver_to_slow_consumer(OverflowPolicy::DropOldest).await;
+
+    assert_eq!(amounts, vec![3, 4]);
+    assert_eq!(dropped, 3);
+}
+
+#[tokio::test]
+async fn test_auto_live_source_falls_back_to_polling() {
+    // === 1. Arrange ===
+    // Nothing listens on the port of a dropped listener, so every subscription fails.
+    let closed_port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    let mut config = test_config(format!("ws://127.0.0.1:{closed_port}"));
+    config.solana.live_source = LiveSource::Auto;
+    config.solana.ws_failures_before_fallback = 2;
+    config.solana.fallback_poll_interval_secs = 1;
+    config.solana.ws_upgrade_interval_secs = 3600;
+    // The regular catch-up poll never runs during the test, so only the fallback can deliver.
+    config.synchronizer.poll_interval_secs = 3600;
+    let (event_manager, handle) = EventManager::new(
+        Arc::new(config),
+        rpc.clone(),
+        Arc::new(MemoryStorage::new()),
+    );
+    let manager = tokio::spawn(event_manager.run());
+
+    let mut listener = handle.listen_as_user(user_pda).unwrap();
+    drain_catchup(&mut listener).await;
+
+    // === 2. Act ===
+    rpc.push_transaction(
+        &random_signature(),
+        10,
+        &[user_pda],
+        vec![deposit_log(user_pda, 7)],
+    );
+    let event = tokio::time::timeout(Duration::from_secs(10), listener.next_live_event())
+        .await
+        .expect("polling should take over from the failed WebSocket")
+        .unwrap();
+
+    // === 3. Assert ===
+    assert!(matches!(event.data, BridgeEventData::UserFundsDeposited(e) if e.amount == 7));
+    // The failed WebSocket does not take the synchronizer down.
+    assert!(!manager.is_finished());
+    drop(listener);
+    handle.stop().await;
+}
+
+/// Waits until the storage cursor points at `signature`.
+async fn wait_for_cursor(storage: &dyn Storage, signature: &str) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while storage.get_last_sig().await.unwrap().as_deref() != Some(signature) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("cursor should be persisted");
+}
+
+/// Drains a listener's catch-up channel until it is closed.
+async fn drain_catchup(listener: &mut EventListener) -> Vec<BridgeEventData> {
+    let mut events = Vec::new();
+    loop {
+        match tokio::time::timeout(Duration::from_secs(5), listener.next_catchup_event()).await {
+            Ok(Some(event)) => events.push(event.data),
+            Ok(None) => return events,
+            Err(_) => panic!("catch-up channel should close after the history is delivered"),
+        }
+    }
+}
+
+/// Builds the log line of a `UserFundsDeposited` event for `user_pda`.
+fn deposit_log(user_pda: Pubkey, amount: u64) -> String {
+    event_log(&UserFundsDeposited {
+        authority: Pubkey::new_unique(),
+        user_profile_pda: user_pda,
+        amount,
+        new_deposit_balance: amount,
+        source: Pubkey::new_unique(),
+        ts: 0,
+    })
+}
+
+#[tokio::test]
+async fn test_catchup_preserves_order_with_concurrent_fetches() {
+    // === 1. Arrange ===
+    const TX_COUNT: u64 = 200;
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    for i in 0..TX_COUNT {
+        rpc.push_transaction(
+            &random_signature(),
+            i + 1,
+            &[user_pda],
+            vec![deposit_log(user_pda, i)],
+        );
+    }
+
+    let mut config: ConnectorConfig = test_config(spawn_ws_stub().await);
+    config.solana.signatures_per_batch = 25;
+    config.solana.tx_fetch_concurrency = 8;
+
+    let (event_manager, handle) = EventManager::new(
+        Arc::new(config),
+        rpc.clone(),
+        Arc::new(MemoryStorage::new()),
+    );
+    tokio::spawn(event_manager.run());
+
+    // === 2. Act ===
+    let mut listener = handle.listen_as_user(user_pda).unwrap();
+    let mut amounts = Vec::new();
+    while amounts.len() < TX_COUNT as usize {
+        let event = tokio::time::timeout(Duration::from_secs(10), listener.next_catchup_event())
+            .await
+            .expect("all catch-up events should arrive")
+            .expect("listener channel should be open");
+        if let BridgeEventData::UserFundsDeposited(e) = event.data {
+            amounts.push(e.amount);
+        }
+    }
+
+    // === 3. Assert ===
+    assert_eq!(amounts, (0..TX_COUNT).collect::<Vec<_>>());
+    // 200 signatures in pages of 25, plus the final empty page.
+    assert!(rpc.signature_requests() >= TX_COUNT / 25);
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_caught_up_marker_arrives_exactly_once_per_subscription() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let quiet_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    for i in 0..3 {
+        rpc.push_transaction(
+            &random_signature(),
+            10 + i,
+            &[user_pda],
+            vec![deposit_log(user_pda, i)],
+        );
+    }
+    let other_pda = Pubkey::new_unique();
+    rpc.push_transaction(
+        &random_signature(),
+        20,
+        &[other_pda],
+        vec![deposit_log(other_pda, 99)],
+    );
+
+    let config = Arc::new(test_config(spawn_ws_stub().await));
+    let (event_manager, handle) =
+        EventManager::new(config, rpc.clone(), Arc::new(MemoryStorage::new()));
+    tokio::spawn(event_manager.run());
+
+    // === 2. Act ===
+    let mut first = handle.listen_as_user(user_pda).unwrap();
+    let mut second = handle.listen_as_user(user_pda).unwrap();
+    let mut quiet = handle.listen_as_admin(quiet_pda).unwrap();
+    let first_events = drain_catchup(&mut first).await;
+    let second_events = drain_catchup(&mut second).await;
+    let quiet_events = drain_catchup(&mut quiet).await;
+
+    // === 3. Assert ===
+    for events in [&first_events, &second_events] {
+        let markers: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                BridgeEventData::CaughtUp(marker) => Some(*marker),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            markers,
+            vec![CaughtUp {
+                pda: user_pda,
+                slot: 20
+            }]
+        );
+        assert!(matches!(events.last(), Some(BridgeEventData::CaughtUp(_))));
+        assert_eq!(
+            events.len(),
+            4,
+            "3 historical deposits followed by the marker"
+        );
+    }
+    assert_eq!(quiet_events.len(), 1);
+    assert!(
+        matches!(quiet_events[0], BridgeEventData::CaughtUp(CaughtUp { pda, .. }) if pda == quiet_pda)
+    );
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_dropped_transaction_is_retracted() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let signature = random_signature();
+    let rpc = Arc::new(MockSyncRpc::new());
+    rpc.push_transaction(
+        &signature,
+        42,
+        &[user_pda],
+        vec![deposit_log(user_pda, 500)],
+    );
+
+    let mut config = test_config(spawn_ws_stub().await);
+    config.synchronizer.detect_reorgs = true;
+    let (event_manager, handle) = EventManager::new(
+        Arc::new(config),
+        rpc.clone(),
+        Arc::new(MemoryStorage::new()),
+    );
+    tokio::spawn(event_manager.run());
+
+    let mut listener = handle.listen_as_user(user_pda).unwrap();
+    let delivered = tokio::time::timeout(Duration::from_secs(5), listener.next_live_event())
+        .await
+        .expect("polled event should arrive")
+        .expect("listener channel should be open");
+    assert_eq!(delivered.signature.as_deref(), Some(signature.as_str()));
+    assert_eq!(delivered.slot, 42);
+
+    // === 2. Act ===
+    rpc.drop_transaction(&signature);
+    let retraction = tokio::time::timeout(Duration::from_secs(10), listener.next_live_event())
+        .await
+        .expect("retraction should arrive")
+        .expect("listener channel should be open");
+
+    // === 3. Assert ===
+    match retraction.data {
+        BridgeEventData::Retracted(e) => {
+            assert_eq!(e.signature, signature);
+            assert_eq!(e.pdas, vec![user_pda]);
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_stop_waits_for_workers_and_persists_cursor() {
+    // === 1. Arrange ===
+    const TX_COUNT: u64 = 500;
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    let mut signatures = Vec::new();
+    for i in 0..TX_COUNT {
+        let signature = random_signature();
+        rpc.push_transaction(
+            &signature,
+            i + 1,
+            &[user_pda],
+            vec![deposit_log(user_pda, i)],
+        );
+        signatures.push(signature);
+    }
+
+    let mut config: ConnectorConfig = test_config(spawn_ws_stub().await);
+    config.solana.tx_fetch_concurrency = 1;
+    let storage = Arc::new(MemoryStorage::new());
+    let (event_manager, handle) = EventManager::new(Arc::new(config), rpc.clone(), storage.clone());
+    let runner = tokio::spawn(event_manager.run());
+
+    // Wait until the program-wide catch-up pass is underway.
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while storage.get_last_sig().await.unwrap().is_none() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("catch-up should start");
+
+    // === 2. Act ===
+    tokio::time::timeout(Duration::from_secs(5), handle.stop())
+        .await
+        .expect("stop should resolve once the workers have exited");
+
+    // === 3. Assert ===
+    assert!(
+        runner.is_finished(),
+        "the EventManager should have exited when stop() returns"
+    );
+    let slot = storage.get_last_slot().await.unwrap();
+    let sig = storage
+        .get_last_sig()
+        .await
+        .unwrap()
+        .expect("cursor should be persisted");
+    assert!(slot < TX_COUNT, "stop should interrupt the catch-up pass");
+    assert_eq!(
+        sig,
+        signatures[slot as usize - 1],
+        "cursor slot and signature should match"
+    );
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(
+        storage.get_last_slot().await.unwrap(),
+        slot,
+        "no writes after stop"
+    );
+
+    // Stopping again is a no-op.
+    handle.stop().await;
+}
+
+/// Delays the cursor write of one transaction, announcing when it has started.
+struct SlowCursorStorage {
+    inner: MemoryStorage,
+    slow_signature: String,
+    write_started: tokio::sync::Notify,
+}
+
+#[async_trait::async_trait]
+impl Storage for SlowCursorStorage {
+    async fn get_last_slot(&self) -> anyhow::Result<u64> {
+        self.inner.get_last_slot().await
+    }
+
+    async fn get_last_sig(&self) -> anyhow::Result<Option<String>> {
+        self.inner.get_last_sig().await
+    }
+
+    async fn set_sync_state(&self, slot: u64, sig: &str) -> anyhow::Result<()> {
+        if sig == self.slow_signature {
+            self.write_started.notify_one();
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        self.inner.set_sync_state(slot, sig).await
+    }
+}
+
+#[tokio::test]
+async fn test_stop_during_a_cursor_write_keeps_the_cursor_durable() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let signature = random_signature();
+    let rpc = Arc::new(MockSyncRpc::new());
+    rpc.push_transaction(&signature, 10, &[user_pda], vec![deposit_log(user_pda, 7)]);
+    let storage = Arc::new(SlowCursorStorage {
+        inner: MemoryStorage::new(),
+        slow_signature: signature.clone(),
+        write_started: tokio::sync::Notify::new(),
+    });
+    let config = Arc::new(test_config(spawn_ws_stub().await));
+    let (event_manager, handle) = EventManager::new(config, rpc.clone(), storage.clone());
+    let runner = tokio::spawn(event_manager.run());
+
+    // Wait until the catch-up pass is persisting the cursor for the transaction.
+    tokio::time::timeout(Duration::from_secs(5), storage.write_started.notified())
+        .await
+        .expect("the cursor write should start");
+
+    // === 2. Act ===
+    tokio::time::timeout(Duration::from_secs(5), handle.stop())
+        .await
+        .expect("stop should resolve once the write has landed");
+
+    // === 3. Assert ===
+    assert!(
+        runner.is_finished(),
+        "the EventManager should have exited when stop() returns"
+    );
+    assert_eq!(
+        storage.get_last_sig().await.unwrap().as_deref(),
+        Some(signature.as_str())
+    );
+    assert_eq!(storage.get_last_slot().await.unwrap(), 10);
+}
+
+#[tokio::test]
+async fn test_history_is_served_from_the_event_cache() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    for i in 0..3 {
+        rpc.push_transaction(
+            &random_signature(),
+            10 + i,
+            &[user_pda],
+            vec![deposit_log(user_pda, i)],
+        );
+    }
+    let storage = Arc::new(MemoryStorage::new());
+    let mut config = test_config(spawn_ws_stub().await);
+    // Keep the program-wide poll out of the way so only replays fetch transactions.
+    config.synchronizer.poll_interval_secs = 3600;
+
+    let (event_manager, handle) = EventManager::new(Arc::new(config), rpc.clone(), storage.clone());
+    tokio::spawn(event_manager.run());
+
+    // === 2. Act ===
+    let first = drain_catchup(&mut handle.listen_as_user(user_pda).unwrap()).await;
+    let fetched_by_first = rpc.transaction_requests();
+    rpc.push_transaction(
+        &random_signature(),
+        20,
+        &[user_pda],
+        vec![deposit_log(user_pda, 3)],
+    );
+    let second = drain_catchup(&mut handle.listen_as_user(user_pda).unwrap()).await;
+
+    // === 3. Assert ===
+    let amounts = |events: &[BridgeEventData]| -> Vec<u64> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                BridgeEventData::UserFundsDeposited(e) => Some(e.amount),
+                _ => None,
+            })
+            .collect()
+    };
+    assert_eq!(amounts(&first), vec![0, 1, 2]);
+    assert_eq!(amounts(&second), vec![0, 1, 2, 3]);
+    assert_eq!(fetched_by_first, 3);
+    assert_eq!(
+        rpc.transaction_requests(),
+        4,
+        "only the new transaction is fetched again"
+    );
+    let cached = storage.get_cached_events(&user_pda).await.unwrap().unwrap();
+    assert_eq!(cached.events.len(), 4);
+    assert_eq!(cached.cursor.map(|(slot, _)| slot), Some(20));
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_active_pdas_are_prewarmed_after_a_restart() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let old = [random_signature(), random_signature()];
+    let rpc = Arc::new(MockSyncRpc::new());
+    for (i, sig) in old.iter().enumerate() {
+        rpc.push_transaction(
+            sig,
+            10 + i as u64,
+            &[user_pda],
+            vec![deposit_log(user_pda, i as u64)],
+        );
+    }
+    let storage = Arc::new(MemoryStorage::new());
+    let mut config = test_config(spawn_ws_stub().await);
+    // Keep the program-wide poll out of the way so only replays fetch transactions.
+    config.synchronizer.poll_interval_secs = 3600;
+    let config = Arc::new(config);
+
+    let (event_manager, handle) = EventManager::new(config.clone(), rpc, storage.clone());
+    tokio::spawn(event_manager.run());
+    let mut listener = handle.listen_as_user(user_pda).unwrap();
+    let _live_only = handle
+        .listen_as_user_live_only(Pubkey::new_unique())
+        .unwrap();
+    drain_catchup(&mut listener).await;
+    // The connector goes down while the listeners are still subscribed. Only the PDA with a
+    // history replay is recorded.
+    handle.stop().await;
+    assert_eq!(storage.list_active_pdas().await.unwrap(), vec![user_pda]);
+    drop(listener);
+
+    // === 2. Act ===
+    let restarted_rpc = Arc::new(MockSyncRpc::new());
+    for (i, sig) in old.iter().enumerate() {
+        restarted_rpc.push_transaction(
+            sig,
+            10 + i as u64,
+            &[user_pda],
+            vec![deposit_log(user_pda, i as u64)],
+        );
+    }
+    restarted_rpc.push_transaction(
+        &random_signature(),
+        20,
+        &[user_pda],
+        vec![deposit_log(user_pda, 2)],
+    );
+    let (event_manager, handle) = EventManager::new(config, restarted_rpc.clone(), storage.clone());
+    tokio::spawn(event_manager.run());
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let cached = storage.get_cached_events(&user_pda).await.unwrap().unwrap();
+            if cached.cursor.is_some_and(|(slot, _)| slot == 20) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the active PDA should be replayed without a new subscription");
+
+    // === 3. Assert ===
+    assert!(restarted_rpc.signature_requests_for(&user_pda) > 0);
+    assert_eq!(
+        restarted_rpc.transaction_requests(),
+        1,
+        "only the new transaction is fetched"
+    );
+
+    // A resubscribing listener is served from the warmed cache.
+    let mut listener = handle.listen_as_user(user_pda).unwrap();
+    assert_eq!(drain_catchup(&mut listener).await.len(), 4);
+    assert_eq!(restarted_rpc.transaction_requests(), 1);
+
+    // The PDA is forgotten once its last listener is gone.
+    drop(listener);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !storage.list_active_pdas().await.unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the PDA should be removed from the active PDAs");
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_catchup_progress_advances_monotonically() {
+    // === 1. Arrange ===
+    const TX_COUNT: u64 = 20;
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    for i in 0..TX_COUNT {
+        rpc.push_transaction(
+            &random_signature(),
+            100 + i,
+            &[user_pda],
+            vec![deposit_log(user_pda, i)],
+        );
+    }
+    let mut config = test_config(spawn_ws_stub().await);
+    config.synchronizer.poll_interval_secs = 3600;
+    // A single-slot buffer keeps the replay in step with the listener.
+    config.channels.listener_event_buffer = 1;
+
+    let (event_manager, handle) = EventManager::new(
+        Arc::new(config),
+        rpc.clone(),
+        Arc::new(MemoryStorage::new()),
+    );
+    tokio::spawn(event_manager.run());
+
+    // === 2. Act ===
+    let mut listener = handle.listen_as_user(user_pda).unwrap();
+    let mut samples = vec![listener.catchup_progress()];
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), listener.next_catchup_event())
+            .await
+            .expect("catch-up events should arrive");
+        samples.push(listener.catchup_progress());
+        if event.is_none() {
+            break;
+        }
+    }
+
+    // === 3. Assert ===
+    assert!(samples
+        .windows(2)
+        .all(|pair| pair[0].current_slot <= pair[1].current_slot));
+    assert!(
+        samples
+            .iter()
+            .any(|p| p.current_slot > 100 && !p.is_complete()),
+        "progress is reported mid-replay"
+    );
+    let last = *samples.last().unwrap();
+    assert_eq!(last.from_slot, 100);
+    assert_eq!(last.current_slot, 100 + TX_COUNT - 1);
+    assert_eq!(last.target_slot, 100 + TX_COUNT - 1);
+    assert!(last.is_complete());
+    assert_eq!(last.percent(), 100.0);
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_catchup_max_transactions_limits_the_replay() {
+    // === 1. Arrange ===
+    const TX_COUNT: u64 = 50;
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    for i in 0..TX_COUNT {
+        rpc.push_transaction(
+            &random_signature(),
+            10 + i,
+            &[user_pda],
+            vec![deposit_log(user_pda, i)],
+        );
+    }
+    let storage = Arc::new(MemoryStorage::new());
+    let mut config = test_config(spawn_ws_stub().await);
+    config.synchronizer.poll_interval_secs = 3600;
+    config.synchronizer.catchup_max_transactions = Some(5);
+
+    let (event_manager, handle) = EventManager::new(Arc::new(config), rpc.clone(), storage.clone());
+    tokio::spawn(event_manager.run());
+
+    // === 2. Act ===
+    let events = drain_catchup(&mut handle.listen_as_user(user_pda).unwrap()).await;
+
+    // === 3. Assert ===
+    let amounts: Vec<u64> = events
+        .iter()
+        .filter_map(|e| match e {
+            BridgeEventData::UserFundsDeposited(e) => Some(e.amount),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(amounts, (TX_COUNT - 5..TX_COUNT).collect::<Vec<_>>());
+    assert!(matches!(
+        events.last(),
+        Some(BridgeEventData::CaughtUp(CaughtUp { slot, .. })) if *slot == 10 + TX_COUNT - 1
+    ));
+    assert_eq!(rpc.transaction_requests(), 5);
+    // The window does not reach back to the start of the history, so it is not cached.
+    assert!(storage
+        .get_cached_events(&user_pda)
+        .await
+        .unwrap()
+        .is_none());
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_history_options_control_order_and_count() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    for i in 0..6 {
+        rpc.push_transaction(
+            &random_signature(),
+            10 + i,
+            &[user_pda],
+            vec![deposit_log(user_pda, i)],
+        );
+    }
+    let storage = Arc::new(MemoryStorage::new());
+    let mut config = test_config(spawn_ws_stub().await);
+    config.synchronizer.poll_interval_secs = 3600;
+
+    let (event_manager, handle) = EventManager::new(Arc::new(config), rpc.clone(), storage.clone());
+    tokio::spawn(event_manager.run());
+    let history = |newest_first, limit| HistoryOptions {
+        newest_first,
+        limit,
+        ..Default::default()
+    };
+
+    // === 2. Act ===
+    let oldest_first = drain_catchup(
+        &mut handle
+            .listen_as_user_with_history(user_pda, history(false, Some(3)))
+            .unwrap(),
+    )
+    .await;
+    let newest_first = drain_catchup(
+        &mut handle
+            .listen_as_user_with_history(user_pda, history(true, Some(3)))
+            .unwrap(),
+    )
+    .await;
+    // Fill the cache, then replay a history that is partly cached and partly fetched.
+    drain_catchup(&mut handle.listen_as_user(user_pda).unwrap()).await;
+    rpc.push_transaction(
+        &random_signature(),
+        20,
+        &[user_pda],
+        vec![deposit_log(user_pda, 6)],
+    );
+    let mut listener = handle
+        .listen_as_user_with_history(user_pda, history(true, None))
+        .unwrap();
+    let full_newest_first = drain_catchup(&mut listener).await;
+
+    // === 3. Assert ===
+    let amounts = |events: &[BridgeEventData]| -> Vec<u64> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                BridgeEventData::UserFundsDeposited(e) => Some(e.amount),
+                _ => None,
+            })
+            .collect()
+    };
+    assert_eq!(amounts(&oldest_first), vec![3, 4, 5]);
+    assert_eq!(amounts(&newest_first), vec![5, 4, 3]);
+    assert_eq!(amounts(&full_newest_first), vec![6, 5, 4, 3, 2, 1, 0]);
+    for events in [&oldest_first, &newest_first, &full_newest_first] {
+        assert!(matches!(events.last(), Some(BridgeEventData::CaughtUp(_))));
+    }
+    assert!(listener.catchup_progress().is_complete());
+    let cached = storage.get_cached_events(&user_pda).await.unwrap().unwrap();
+    assert_eq!(
+        cached.events.len(),
+        7,
+        "the newest-first replay extends the cache in order"
+    );
+    assert_eq!(cached.cursor.map(|(slot, _)| slot), Some(20));
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_history_since_slot_skips_processed_transactions() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    for i in 0..6 {
+        rpc.push_transaction(
+            &random_signature(),
+            10 + i,
+            &[user_pda],
+            vec![deposit_log(user_pda, i)],
+        );
+    }
+    let storage = Arc::new(MemoryStorage::new());
+    let mut config = test_config(spawn_ws_stub().await);
+    config.synchronizer.poll_interval_secs = 3600;
+
+    let (event_manager, handle) = EventManager::new(Arc::new(config), rpc.clone(), storage.clone());
+    tokio::spawn(event_manager.run());
+
+    // === 2. Act ===
+    let history = HistoryOptions {
+        since_slot: Some(12),
+        ..Default::default()
+    };
+    let events = drain_catchup(
+        &mut handle
+            .listen_as_user_with_history(user_pda, history)
+            .unwrap(),
+    )
+    .await;
+
+    // === 3. Assert ===
+    let amounts: Vec<u64> = events
+        .iter()
+        .filter_map(|e| match e {
+            BridgeEventData::UserFundsDeposited(e) => Some(e.amount),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(amounts, vec![3, 4, 5]);
+    assert!(matches!(
+        events.last(),
+        Some(BridgeEventData::CaughtUp(CaughtUp { slot, .. })) if *slot == 15
+    ));
+    assert_eq!(
+        rpc.transaction_requests(),
+        3,
+        "transactions up to since_slot are not fetched"
+    );
+    // The replay does not reach back to the start of the history, so it is not cached.
+    assert!(storage
+        .get_cached_events(&user_pda)
+        .await
+        .unwrap()
+        .is_none());
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_processed_events_are_counted_per_pda() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let admin_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    rpc.push_transaction(
+        &random_signature(),
+        1,
+        &[user_pda],
+        vec![deposit_log(user_pda, 1)],
+    );
+    // One transaction emitting two events.
+    rpc.push_transaction(
+        &random_signature(),
+        2,
+        &[user_pda],
+        vec![deposit_log(user_pda, 2), deposit_log(user_pda, 3)],
+    );
+    let last_signature = random_signature();
+    rpc.push_transaction(
+        &last_signature,
+        3,
+        &[user_pda, admin_pda],
+        vec![event_log(&UserBanned {
+            admin_authority: Pubkey::new_unique(),
+            admin_pda,
+            user_profile_pda: user_pda,
+            ts: 0,
+        })],
+    );
+    let storage = Arc::new(MemoryStorage::new());
+    let config = Arc::new(test_config(spawn_ws_stub().await));
+    let (event_manager, handle) = EventManager::new(config, rpc, storage.clone());
+
+    // === 2. Act ===
+    tokio::spawn(event_manager.run());
+    wait_for_cursor(storage.as_ref(), &last_signature).await;
+
+    // === 3. Assert ===
+    let user_counts = storage.get_event_counts(&user_pda).await.unwrap();
+    assert_eq!(user_counts.len(), 2);
+    assert_eq!(user_counts[&EventKind::UserFundsDeposited], 3);
+    assert_eq!(user_counts[&EventKind::UserBanned], 1);
+    let admin_counts = storage.get_event_counts(&admin_pda).await.unwrap();
+    assert_eq!(
+        admin_counts.into_iter().collect::<Vec<_>>(),
+        vec![(EventKind::UserBanned, 1)]
+    );
+    assert!(storage
+        .get_event_counts(&Pubkey::new_unique())
+        .await
+        .unwrap()
+        .is_empty());
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_live_only_listener_never_triggers_catchup() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    let storage = Arc::new(MemoryStorage::new());
+    let config = Arc::new(test_config(spawn_ws_stub().await));
+    let (event_manager, handle) = EventManager::new(config, rpc.clone(), storage);
+    tokio::spawn(event_manager.run());
+
+    // === 2. Act ===
+    let mut listener = handle.listen_as_user_live_only(user_pda).unwrap();
+    rpc.push_transaction(
+        &random_signature(),
+        1,
+        &[user_pda],
+        vec![deposit_log(user_pda, 7)],
+    );
+    let event = tokio::time::timeout(Duration::from_secs(5), listener.next_live_event())
+        .await
+        .expect("polled event should arrive")
+        .expect("listener channel should be open");
+
+    // === 3. Assert ===
+    match event.data {
+        BridgeEventData::UserFundsDeposited(e) => assert_eq!(e.amount, 7),
+        other => panic!("unexpected event: {:?}", other),
+    }
+    assert!(listener.next_catchup_event().await.is_none());
+    assert_eq!(listener.catchup_progress(), Default::default());
+    assert_eq!(rpc.signature_requests_for(&user_pda), 0);
+
+    // A regular listener of the same PDA does replay its history.
+    let mut regular = handle.listen_as_user(user_pda).unwrap();
+    assert!(matches!(
+        drain_catchup(&mut regular).await.last(),
+        Some(BridgeEventData::CaughtUp(_))
+    ));
+    assert!(rpc.signature_requests_for(&user_pda) > 0);
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_rejected_dispatch_is_reported_instead_of_its_events() {
+    // === 1. Arrange ===
+    let authority = Pubkey::new_unique();
+    let admin_pda = Pubkey::new_unique();
+    let (user_pda, _) = find_user_profile_pda(&authority, &admin_pda);
+    let transaction = dispatch_transaction(authority, admin_pda);
+    let code = u32::from(BridgeError::InvalidOracleSigner);
+    let rejected_signature = random_signature();
+    let rpc = Arc::new(MockSyncRpc::new());
+    // An event logged before the failure was rolled back with the rest of the transaction.
+    rpc.push_failed_transaction(
+        &rejected_signature,
+        1,
+        &transaction,
+        TransactionError::InstructionError(1, InstructionError::Custom(code)),
+        vec![deposit_log(user_pda, 1)],
+    );
+    let last_signature = random_signature();
+    rpc.push_transaction(
+        &last_signature,
+        2,
+        &[user_pda],
+        vec![deposit_log(user_pda, 2)],
+    );
+    let storage = Arc::new(MemoryStorage::new());
+    let mut config = test_config(spawn_ws_stub().await);
+    config.synchronizer.report_dispatch_rejections = true;
+    let (event_manager, handle) = EventManager::new(Arc::new(config), rpc, storage.clone());
+    tokio::spawn(event_manager.run());
+
+    // === 2. Act ===
+    let mut listener = handle.listen_as_admin_live_only(admin_pda).unwrap();
+    let event = tokio::time::timeout(Duration::from_secs(5), listener.next_live_event())
+        .await
+        .expect("the rejection should be reported")
+        .expect("listener channel should be open");
+    wait_for_cursor(storage.as_ref(), &last_signature).await;
+
+    // === 3. Assert ===
+    assert_eq!(
+        event.signature.as_deref(),
+        Some(rejected_signature.as_str())
+    );
+    match event.data {
+        BridgeEventData::DispatchRejected(e) => {
+            assert_eq!(e.reason, DispatchRejectionReason::InvalidOracleSigner);
+            assert_eq!(e.user_pda, user_pda);
+            assert_eq!(e.admin_pda, admin_pda);
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+    let user_counts = storage.get_event_counts(&user_pda).await.unwrap();
+    assert_eq!(user_counts[&EventKind::UserFundsDeposited], 1);
+    assert_eq!(user_counts[&EventKind::DispatchRejected], 1);
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_categorized_listener_sorts_events_by_category() {
+    // === 1. Arrange ===
+    let admin_pda = Pubkey::new_unique();
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    let config = Arc::new(test_config(spawn_ws_stub().await));
+    let (event_manager, handle) =
+        EventManager::new(config, rpc.clone(), Arc::new(MemoryStorage::new()));
+    tokio::spawn(event_manager.run());
+
+    let mut listener = handle.listen_by_category(user_pda).unwrap();
+    let mut financial = listener.financial_events();
+    let mut admin = listener.admin_events();
+    // A consumer that does not care about a category may drop its stream.
+    drop(listener.messaging_events());
+
+    // === 2. Act ===
+    rpc.push_transaction(
+        &random_signature(),
+        1,
+        &[user_pda],
+        vec![event_log(&UserBanned {
+            admin_authority: Pubkey::new_unique(),
+            admin_pda,
+            user_profile_pda: user_pda,
+            ts: 0,
+        })],
+    );
+    rpc.push_transaction(
+        &random_signature(),
+        2,
+        &[user_pda],
+        vec![deposit_log(user_pda, 7)],
+    );
+    let financial_event = tokio::time::timeout(Duration::from_secs(5), financial.next())
+        .await
+        .expect("the deposit should arrive")
+        .expect("the financial stream should be open");
+    let admin_event = tokio::time::timeout(Duration::from_secs(5), admin.next())
+        .await
+        .expect("the ban should arrive")
+        .expect("the admin stream should be open");
+
+    // === 3. Assert ===
+    assert!(
+        matches!(financial_event.data, BridgeEventData::UserFundsDeposited(e) if e.amount == 7)
+    );
+    assert!(
+        matches!(admin_event.data, BridgeEventData::UserBanned(e) if e.user_profile_pda == user_pda)
+    );
+    assert_eq!(
+        EventKind::UserFundsDeposited.category(),
+        EventCategory::Financial
+    );
+    assert_eq!(
+        EventKind::UserBanned.category(),
+        EventCategory::Administrative
+    );
+    // Each stream only carries its own category.
+    assert!(
+        tokio::time::timeout(Duration::from_millis(200), financial.next())
+            .await
+            .is_err()
+    );
+    // A stream can only be taken once.
+    assert!(listener.financial_events().next().await.is_none());
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_stalled_synchronizer_raises_the_lag_alert() {
+    // === 1. Arrange ===
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    rpc.push_transaction(
+        &random_signature(),
+        10,
+        &[user_pda],
+        vec![deposit_log(user_pda, 1)],
+    );
+
+    let mut config = test_config(spawn_ws_stub().await);
+    config.synchronizer.max_lag_slots = Some(100);
+    config.synchronizer.lag_alert_after_secs = 1;
+    let storage = Arc::new(MemoryStorage::new());
+    let (event_manager, handle) = EventManager::new(Arc::new(config), rpc.clone(), storage.clone());
+    tokio::spawn(event_manager.run());
+    let mut status = handle.sync_status();
+
+    let synced = tokio::time::timeout(
+        Duration::from_secs(5),
+        status.wait_for(|s| s.processed_slot == 10),
+    )
+    .await
+    .expect("the lag should be reported")
+    .unwrap()
+    .to_owned();
+    assert_eq!(synced.lag_slots, 0);
+    assert!(!synced.stalled);
+
+    // === 2. Act ===
+    // The cluster moves on while the synchronizer cannot fetch new signatures.
+    rpc.fail_signature_requests(true);
+    rpc.advance_slot(1_000);
+    let stalled = tokio::time::timeout(Duration::from_secs(10), status.wait_for(|s| s.stalled))
+        .await
+        .expect("the stall alert should fire")
+        .unwrap()
+        .to_owned();
+
+    // === 3. Assert ===
+    assert_eq!(stalled.chain_slot, 1_000);
+    assert_eq!(stalled.processed_slot, 10);
+    assert_eq!(stalled.lag_slots, 990);
+
+    // The alert clears once the synchronizer catches up again.
+    rpc.fail_signature_requests(false);
+    let recovered = tokio::time::timeout(Duration::from_secs(10), status.wait_for(|s| !s.stalled))
+        .await
+        .expect("the alert should clear")
+        .unwrap()
+        .to_owned();
+    assert_eq!(recovered.lag_slots, 0);
+
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_watchdog_reports_a_wedged_synchronizer() {
+    // === 1. Arrange ===
+    let rpc = Arc::new(MockSyncRpc::new());
+    let mut config = test_config(spawn_ws_stub().await);
+    config.synchronizer.watchdog_timeout_secs = 2;
+    let storage = Arc::new(MemoryStorage::new());
+    let (event_manager, handle) = EventManager::new(Arc::new(config), rpc.clone(), storage);
+    tokio::spawn(event_manager.run());
+    let mut health = handle.health();
+    let started = health.borrow().last_progress;
+
+    // Every catch-up pass counts as progress, even without any transactions.
+    let progressed = tokio::time::timeout(
+        Duration::from_secs(5),
+        health.wait_for(|h| h.last_progress > started),
+    )
+    .await
+    .expect("a catch-up pass should complete")
+    .unwrap()
+    .to_owned();
+    assert!(progressed.healthy);
+
+    // === 2. Act ===
+    // The RPC node stops answering, so the catch-up worker hangs without returning an error.
+    rpc.hang_slot_requests(true);
+    let wedged = tokio::time::timeout(Duration::from_secs(10), health.wait_for(|h| !h.healthy))
+        .await
+        .expect("the watchdog should fire")
+        .unwrap()
+        .to_owned();
+
+    // === 3. Assert ===
+    assert!(wedged.last_progress >= progressed.last_progress);
+
+    // The synchronizer is healthy again once it completes another pass.
+    rpc.hang_slot_requests(false);
+    let recovered = tokio::time::timeout(Duration::from_secs(10), health.wait_for(|h| h.healthy))
+        .await
+        .expect("the synchronizer should recover")
+        .unwrap()
+        .to_owned();
+    assert!(recovered.last_progress > wedged.last_progress);
+
+    handle.stop().await;
+}
+
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn test_rpc_stats_count_the_calls_of_a_catchup_run() {
+    use w3b2_solana_connector::rpc_stats::RpcMethod;
+
+    // === 1. Arrange ===
+    const TX_COUNT: u64 = 5;
+    let user_pda = Pubkey::new_unique();
+    let rpc = Arc::new(MockSyncRpc::new());
+    for i in 0..TX_COUNT {
+        rpc.push_transaction(
+            &random_signature(),
+            100 + i,
+            &[user_pda],
+            vec![deposit_log(user_pda, i)],
+        );
+    }
+    let mut config = test_config(spawn_ws_stub().await);
+    config.synchronizer.poll_interval_secs = 3600;
+
+    let (event_manager, handle) = EventManager::new(
+        Arc::new(config),
+        rpc.clone(),
+        Arc::new(MemoryStorage::new()),
+    );
+    tokio::spawn(event_manager.run());
+    let stats = handle.rpc_stats();
+
+    // === 2. Act ===
+    let mut listener = handle.listen_as_user(user_pda).unwrap();
+    while tokio::time::timeout(Duration::from_secs(5), listener.next_catchup_event())
+        .await
+        .expect("catch-up events should arrive")
+        .is_some()
+    {}
+    rpc.fail_signature_requests(true);
+    handle.listen_as_user(Pubkey::new_unique()).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while stats.get(RpcMethod::GetSignaturesForAddress).errors == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the failed signature request should be counted");
+
+    // === 3. Assert ===
+    let signatures = stats.get(RpcMethod::GetSignaturesForAddress);
+    assert_eq!(
+        signatures.calls - signatures.errors,
+        rpc.signature_requests()
+    );
+    let transactions = stats.get(RpcMethod::GetTransaction);
+    assert_eq!(transactions.calls, rpc.transaction_requests());
+    assert!(transactions.calls >= TX_COUNT);
+    assert_eq!(transactions.errors, 0);
+    assert!(stats.get(RpcMethod::GetSlot).calls > 0);
+    assert!(stats.total().error_rate() > 0.0);
+
+    handle.stop().await;
+}
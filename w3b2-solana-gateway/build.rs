@@ -1,5 +1,12 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tonic_build::configure().build_server(true).compile(
+    let mut builder = tonic_build::configure().build_server(true);
+    if std::env::var_os("CARGO_FEATURE_HTTP").is_some() {
+        // The HTTP bridge reads and writes the generated messages as JSON.
+        builder = builder
+            .type_attribute(".w3b2", "#[derive(serde::Serialize, serde::Deserialize)]")
+            .message_attribute(".w3b2", "#[serde(default)]");
+    }
+    builder.compile(
         &["../proto/types.proto", "../proto/gateway.proto"], // The file to compile
         &["../proto"],                                       // The directory to search in
     )?;
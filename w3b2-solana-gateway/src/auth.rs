@@ -0,0 +1,170 @@
+//! # Client Authentication
+//!
+//! Resolves the [`Principal`] behind every request before it reaches the rate limiter and
+//! the RPC handlers.
+//!
+//! - **[`Authenticator`]**: Maps the request metadata to a `Principal`, or rejects the
+//!   request. Operators embedding the gateway can supply their own implementation, e.g. to
+//!   validate JWTs, through [`crate::grpc::build_server_with_authenticator`].
+//! - **[`StaticApiKeyAuthenticator`]**: The default, driven by `[gateway.auth]`. It checks
+//!   the `x-api-key` metadata entry against a fixed list of keys.
+//! - **[`AuthService`]**: Wraps the gRPC service, runs the authenticator and stores the
+//!   resolved `Principal` in the request extensions, where [`crate::rate_limit::client_key`]
+//!   picks it up. Handlers run inside a tracing span carrying the principal.
+
+use crate::config::AuthConfig;
+use crate::rate_limit::API_KEY_HEADER;
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::Arc;
+use tonic::body::BoxBody;
+use tonic::codegen::{http, BoxFuture, Context, Poll, Service};
+use tonic::metadata::MetadataMap;
+use tonic::server::NamedService;
+use tonic::Status;
+use tracing::Instrument;
+
+/// The identity a request was authenticated as.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Principal {
+    /// A client that presented no credentials. It is identified by its IP address.
+    Anonymous,
+    /// An authenticated client, such as the owner of an API key or the subject of a token.
+    Named(String),
+}
+
+impl Principal {
+    /// Returns the principal's name, or `None` for [`Principal::Anonymous`].
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Anonymous => None,
+            Self::Named(name) => Some(name),
+        }
+    }
+}
+
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Anonymous => f.write_str("anonymous"),
+            Self::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Resolves the [`Principal`] of a request from its metadata.
+#[async_trait]
+pub trait Authenticator: Send + Sync + 'static {
+    /// Returns the principal the request was sent by, or the `Status` to reject it with,
+    /// typically `Status::unauthenticated`.
+    async fn authenticate(&self, metadata: &MetadataMap) -> Result<Principal, Status>;
+}
+
+/// Authenticates clients by the `x-api-key` metadata entry.
+///
+/// With no keys configured, every client is accepted: clients that send an `x-api-key` are
+/// named after it, and all others are anonymous. Otherwise, only the configured keys are
+/// accepted, and requests without a key are rejected.
+pub struct StaticApiKeyAuthenticator {
+    api_keys: HashSet<String>,
+}
+
+impl StaticApiKeyAuthenticator {
+    /// Creates an authenticator that accepts the given keys.
+    pub fn new(api_keys: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            api_keys: api_keys.into_iter().collect(),
+        }
+    }
+
+    /// Creates an authenticator from the gateway's auth settings.
+    pub fn from_config(config: &AuthConfig) -> Self {
+        Self::new(config.api_keys.iter().cloned())
+    }
+}
+
+#[async_trait]
+impl Authenticator for StaticApiKeyAuthenticator {
+    async fn authenticate(&self, metadata: &MetadataMap) -> Result<Principal, Status> {
+        let api_key = metadata
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok());
+        if self.api_keys.is_empty() {
+            let name = api_key.map(str::to_string);
+            return Ok(name.map_or(Principal::Anonymous, Principal::Named));
+        }
+        match api_key {
+            Some(key) if self.api_keys.contains(key) => Ok(Principal::Named(key.to_string())),
+            Some(_) => Err(Status::unauthenticated("Invalid API key")),
+            None => Err(Status::unauthenticated(format!(
+                "Missing `{API_KEY_HEADER}` metadata entry"
+            ))),
+        }
+    }
+}
+
+/// Authenticates every request to the wrapped gRPC service with an [`Authenticator`].
+#[derive(Clone)]
+pub struct AuthService<S> {
+    inner: S,
+    authenticator: Arc<dyn Authenticator>,
+}
+
+impl<S> AuthService<S> {
+    pub fn new(inner: S, authenticator: Arc<dyn Authenticator>) -> Self {
+        Self {
+            inner,
+            authenticator,
+        }
+    }
+}
+
+impl<S, B> Service<http::Request<B>> for AuthService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>, Error = Infallible>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<B>) -> Self::Future {
+        // The ready service is the one that must handle the request; leave the clone behind.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let authenticator = self.authenticator.clone();
+
+        Box::pin(async move {
+            let metadata = MetadataMap::from_headers(request.headers().clone());
+            let principal = match authenticator.authenticate(&metadata).await {
+                Ok(principal) => principal,
+                Err(status) => {
+                    tracing::warn!(
+                        "Rejected unauthenticated request to {}: {}",
+                        request.uri().path(),
+                        status.message()
+                    );
+                    return Ok(status.to_http());
+                }
+            };
+            let span =
+                tracing::info_span!("rpc", principal = %principal, path = request.uri().path());
+            request.extensions_mut().insert(principal);
+            inner.call(request).instrument(span).await
+        })
+    }
+}
+
+impl<S: NamedService> NamedService for AuthService<S> {
+    const NAME: &'static str = S::NAME;
+}
@@ -1,6 +1,10 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tonic::codec::CompressionEncoding;
 use w3b2_solana_logger::logging::LogConfig;
+use w3b2_solana_connector::client::SubmitRetryPolicy;
 use w3b2_solana_connector::config::ConnectorConfig;
 
 /// The top-level configuration for the W3B2 Gateway application.
@@ -19,18 +23,151 @@ pub struct GatewayConfig {
 pub struct GatewaySpecificConfig {
     pub db_path: String,
     #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
     pub grpc: GrpcConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
+    pub submit: SubmitConfig,
+    /// Further program deployments served next to the one in `[connector]`, keyed by realm
+    /// name. Clients select a realm with the `x-realm` metadata entry.
+    #[serde(default)]
+    pub realms: HashMap<String, RealmConfig>,
     /// Logging configuration.
     #[serde(default)]
     pub log: LogConfig,
 }
 
+/// Settings for the gateway's `sled` database.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct StorageConfig {
+    /// Persist the decoded events of every replayed PDA, so history requests after a restart
+    /// are served from the database instead of re-fetching transactions from the RPC node.
+    pub cache_events: bool,
+    /// The maximum number of events cached per PDA. Longer histories keep only their newest
+    /// events, which serve replays limited to them; other replays use the RPC node.
+    pub max_cached_events_per_pda: usize,
+}
+
+/// Settings of the default [`StaticApiKeyAuthenticator`](crate::auth::StaticApiKeyAuthenticator).
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct AuthConfig {
+    /// The API keys clients may send in the `x-api-key` metadata entry. If empty, any key
+    /// is accepted as the client's name, and clients without one are served anonymously.
+    pub api_keys: Vec<String>,
+    /// The principals allowed to call the administrative RPCs, such as `UnsubscribeAll`.
+    /// If empty, the administrative RPCs are disabled.
+    pub admin_principals: Vec<String>,
+}
+
+/// Per-client limits that protect the gateway from misbehaving clients.
+///
+/// Clients are identified by the name of their authenticated principal, which is their
+/// `x-api-key` metadata entry by default, or by their IP address if they are anonymous.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct RateLimitConfig {
+    /// The sustained number of requests per second allowed per client. `0` disables the limit.
+    pub requests_per_second: u32,
+    /// The number of requests a client may send in a burst above the sustained rate.
+    pub burst: u32,
+    /// The maximum number of live and history event streams a client may have open at once.
+    pub max_streams_per_client: usize,
+}
+
+/// Gateway-wide resource limits.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct LimitsConfig {
+    /// The maximum number of live subscriptions across all clients.
+    pub max_subscriptions: usize,
+}
+
+/// How `SubmitTransaction` retries transient RPC failures, such as a dropped connection
+/// or an unhealthy node. Program errors are never retried.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct SubmitConfig {
+    /// How many times a failed submission is retried. `0` disables retries.
+    pub max_retries: u32,
+    /// The delay before the first retry, in milliseconds. It doubles with every retry.
+    pub base_delay_ms: u64,
+}
+
+/// A program deployment, such as devnet next to mainnet, served by the same gateway.
+///
+/// Every realm has its own RPC client, `EventManager`, database and program id, set in
+/// its `connector.solana`. Realms share all other gateway settings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RealmConfig {
+    /// Path to the realm's `sled` database. PDAs are derived identically on every cluster,
+    /// so it must differ from `gateway.db-path` and from the paths of the other realms.
+    pub db_path: String,
+    /// The connector settings of the realm, in the same format as `[connector]`.
+    #[serde(default)]
+    pub connector: ConnectorConfig,
+}
+
 /// gRPC server connection settings.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct GrpcConfig {
     pub host: String,
     pub port: u16,
+    /// Send a heartbeat item on live streams that have been idle for this many seconds,
+    /// so that proxies and load balancers do not close them. `0` disables heartbeats.
+    #[serde(default = "default_heartbeat_interval_secs")]
+    pub heartbeat_interval_secs: u64,
+    /// Also accept `grpc-web` requests over HTTP/1.1, so that browsers can call the gateway
+    /// without a translating proxy such as Envoy.
+    #[serde(default)]
+    pub enable_web: bool,
+    /// The origins allowed to call the gateway through `grpc-web`. Empty allows any origin.
+    #[serde(default)]
+    pub cors_allowed_origins: Vec<String>,
+    /// The compression algorithms the gateway accepts on requests and uses for responses,
+    /// in order of preference. A response is only compressed if the client advertises
+    /// support for one of them in `grpc-accept-encoding`. Empty disables compression.
+    #[serde(default)]
+    pub compression: Vec<GrpcCompression>,
+}
+
+/// Settings of the HTTP/JSON bridge, which requires the `http` feature.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct HttpConfig {
+    /// Serve the unary RPCs as JSON endpoints and the event streams as Server-Sent Events,
+    /// next to the gRPC server.
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+}
+
+/// A compression algorithm for gRPC messages.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GrpcCompression {
+    Gzip,
+    Zstd,
+}
+
+impl From<GrpcCompression> for CompressionEncoding {
+    fn from(compression: GrpcCompression) -> Self {
+        match compression {
+            GrpcCompression::Gzip => CompressionEncoding::Gzip,
+            GrpcCompression::Zstd => CompressionEncoding::Zstd,
+        }
+    }
 }
 
 
@@ -38,21 +175,103 @@ impl Default for GatewaySpecificConfig {
     fn default() -> Self {
         Self {
             db_path: "./w3b2_gateway.db".to_string(),
+            storage: StorageConfig::default(),
             grpc: GrpcConfig::default(),
+            http: HttpConfig::default(),
+            auth: AuthConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            limits: LimitsConfig::default(),
+            submit: SubmitConfig::default(),
+            realms: HashMap::new(),
             log: LogConfig::default(),
         }
     }
 }
 
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            cache_events: false,
+            max_cached_events_per_pda: 10_000,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 20,
+            burst: 40,
+            max_streams_per_client: 16,
+        }
+    }
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_subscriptions: 10_000,
+        }
+    }
+}
+
+impl SubmitConfig {
+    /// Returns the retry policy passed to the connector.
+    pub fn retry_policy(&self) -> SubmitRetryPolicy {
+        SubmitRetryPolicy {
+            max_retries: self.max_retries,
+            base_delay: Duration::from_millis(self.base_delay_ms),
+        }
+    }
+}
+
+impl Default for SubmitConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay_ms: 500,
+        }
+    }
+}
+
 impl Default for GrpcConfig {
     fn default() -> Self {
         Self {
             host: "127.0.0.1".to_string(),
             port: 50051,
+            heartbeat_interval_secs: default_heartbeat_interval_secs(),
+            enable_web: false,
+            cors_allowed_origins: Vec::new(),
+            compression: Vec::new(),
         }
     }
 }
 
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+        }
+    }
+}
+
+fn default_heartbeat_interval_secs() -> u64 {
+    30
+}
+
+impl GatewayConfig {
+    /// Returns the configuration `realm` is served with: this one, with the realm's
+    /// connector settings and database.
+    pub fn for_realm(&self, realm: &RealmConfig) -> GatewayConfig {
+        let mut config = self.clone();
+        config.connector = realm.connector.clone();
+        config.gateway.db_path = realm.db_path.clone();
+        config.gateway.realms.clear();
+        config
+    }
+}
 
 /// Loads the gateway configuration from a specified TOML file.
 ///
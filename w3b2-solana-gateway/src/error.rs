@@ -1,7 +1,17 @@
 use solana_client::client_error::ClientError;
 use solana_sdk::pubkey::ParsePubkeyError;
 use thiserror::Error;
+use tonic::metadata::MetadataValue;
 use tonic::Status;
+use w3b2_solana_connector::client::{is_transient_submit_error, SubmitError};
+use w3b2_solana_connector::error::ConnectorError;
+
+/// The metadata key carrying how many times a failed transaction was submitted.
+pub const SUBMIT_ATTEMPTS_METADATA_KEY: &str = "x-submit-attempts";
+/// The metadata key carrying a machine-readable reason for a failed request.
+pub const ERROR_REASON_METADATA_KEY: &str = "x-error-reason";
+/// The error reason of a transaction whose blockhash expired before it landed.
+pub const BLOCKHASH_EXPIRED_REASON: &str = "BLOCKHASH_EXPIRED";
 
 /// Defines the primary error types for the gRPC gateway.
 #[derive(Error, Debug)]
@@ -9,6 +19,9 @@ pub enum GatewayError {
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
 
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
     #[error("Internal connector error: {0}")]
     Connector(#[from] Box<ClientError>),
 
@@ -17,6 +30,21 @@ pub enum GatewayError {
 
     #[error("Deserialization failed: {0}")]
     Deserialization(#[from] bincode::error::DecodeError),
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] anyhow::Error),
+
+    #[error("{0}")]
+    Submit(Box<SubmitError>),
+
+    #[error("The transaction blockhash expired after {attempts} attempt(s)")]
+    BlockhashExpired { attempts: u32 },
+
+    #[error("The deadline of the request elapsed")]
+    DeadlineExceeded,
+
+    #[error("Resource exhausted: {0}")]
+    ResourceExhausted(String),
 }
 
 /// Allows automatic conversion from our custom `GatewayError` into a `tonic::Status`.
@@ -25,6 +53,7 @@ impl From<GatewayError> for Status {
     fn from(err: GatewayError) -> Self {
         match err {
             GatewayError::InvalidArgument(reason) => Status::invalid_argument(reason),
+            GatewayError::PermissionDenied(reason) => Status::permission_denied(reason),
             GatewayError::Connector(e) => Status::internal(format!("Blockchain client error: {e}")),
             GatewayError::Serialization(e) => {
                 Status::internal(format!("Data serialization error: {e}"))
@@ -32,6 +61,49 @@ impl From<GatewayError> for Status {
             GatewayError::Deserialization(e) => {
                 Status::invalid_argument(format!("Invalid data format for deserialization: {e}"))
             }
+            GatewayError::Storage(e) => Status::internal(format!("Storage error: {e}")),
+            GatewayError::Submit(e) => {
+                let message = format!("Blockchain client error: {e}");
+                // Retries were exhausted on a transient failure, so the client may try again.
+                let mut status = if is_transient_submit_error(&e.source) {
+                    Status::unavailable(message)
+                } else {
+                    Status::internal(message)
+                };
+                status.metadata_mut().insert(
+                    SUBMIT_ATTEMPTS_METADATA_KEY,
+                    MetadataValue::from(e.attempts),
+                );
+                status
+            }
+            GatewayError::BlockhashExpired { attempts } => {
+                let mut status = Status::failed_precondition(
+                    "The blockhash of the transaction has expired: sign it again with a fresh \
+                     blockhash from GetLatestBlockhash",
+                );
+                let metadata = status.metadata_mut();
+                metadata.insert(
+                    ERROR_REASON_METADATA_KEY,
+                    MetadataValue::from_static(BLOCKHASH_EXPIRED_REASON),
+                );
+                metadata.insert(SUBMIT_ATTEMPTS_METADATA_KEY, MetadataValue::from(attempts));
+                status
+            }
+            GatewayError::DeadlineExceeded => Status::deadline_exceeded(err.to_string()),
+            GatewayError::ResourceExhausted(reason) => Status::resource_exhausted(reason),
+        }
+    }
+}
+
+/// Surfaces an expired blockhash separately, since the client only needs to sign again.
+impl From<SubmitError> for GatewayError {
+    fn from(err: SubmitError) -> Self {
+        if err.blockhash_expired {
+            GatewayError::BlockhashExpired {
+                attempts: err.attempts,
+            }
+        } else {
+            GatewayError::Submit(Box::new(err))
         }
     }
 }
@@ -42,3 +114,14 @@ impl From<ParsePubkeyError> for GatewayError {
         GatewayError::InvalidArgument(format!("Invalid public key format: {err}"))
     }
 }
+
+/// Every `ConnectorError` but the listener limit reports a malformed input, so it is the
+/// client's to fix. The listener limit clears once other streams end.
+impl From<ConnectorError> for GatewayError {
+    fn from(err: ConnectorError) -> Self {
+        match err {
+            ConnectorError::TooManyListeners(_) => GatewayError::ResourceExhausted(err.to_string()),
+            _ => GatewayError::InvalidArgument(err.to_string()),
+        }
+    }
+}
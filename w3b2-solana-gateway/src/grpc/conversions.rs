@@ -1,5 +1,136 @@
 use crate::grpc::proto::w3b2::protocol::gateway;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::time::{SystemTime, UNIX_EPOCH};
+use w3b2_solana_connector::client::{CommandMode, UserProfile};
 use w3b2_solana_connector::events as ConnectorEvents;
+use w3b2_solana_connector::simulation::{SimulationError, SimulationReport};
+
+/// Builds the keepalive item sent on idle live streams.
+pub(crate) fn heartbeat_item() -> gateway::EventStreamItem {
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64);
+    gateway::EventStreamItem {
+        source: gateway::EventSource::Live as i32,
+        event: Some(gateway::BridgeEvent {
+            event: Some(gateway::bridge_event::Event::Heartbeat(
+                gateway::Heartbeat { ts },
+            )),
+        }),
+        signature: String::new(),
+        slot: 0,
+    }
+}
+
+/// Builds the acknowledgement sent on a resumed live stream once the events after
+/// `since_slot`, up to `slot`, have been replayed.
+pub(crate) fn resumed_item(pda: &Pubkey, since_slot: u64, slot: u64) -> gateway::EventStreamItem {
+    gateway::EventStreamItem {
+        source: gateway::EventSource::Catchup as i32,
+        event: Some(gateway::BridgeEvent {
+            event: Some(gateway::bridge_event::Event::Resumed(gateway::Resumed {
+                pda: pda.to_string(),
+                since_slot,
+                slot,
+            })),
+        }),
+        signature: String::new(),
+        slot,
+    }
+}
+
+/// Builds the `GetTransactionLogs` response of a fetched transaction.
+pub(crate) fn transaction_logs_response(
+    program_id: &Pubkey,
+    signature: &str,
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+) -> gateway::TransactionLogsResponse {
+    let source = ConnectorEvents::EventSource::Catchup;
+    let mut events =
+        ConnectorEvents::parse_transaction_events_for(program_id, signature, tx, source);
+    if let Some(rejection) =
+        ConnectorEvents::parse_dispatch_rejection_for(program_id, signature, tx)
+    {
+        events.push(ConnectorEvents::BridgeEvent {
+            source,
+            signature: Some(signature.to_string()),
+            slot: tx.slot,
+            data: ConnectorEvents::BridgeEventData::DispatchRejected(rejection),
+        });
+    }
+    let meta = tx.transaction.meta.as_ref();
+    let logs: Option<&Vec<String>> = meta.and_then(|meta| meta.log_messages.as_ref().into());
+    gateway::TransactionLogsResponse {
+        slot: tx.slot,
+        logs: logs.cloned().unwrap_or_default(),
+        events: events.into_iter().map(Into::into).collect(),
+        error: meta
+            .and_then(|meta| meta.err.as_ref())
+            .map(ToString::to_string),
+    }
+}
+
+impl From<SimulationReport> for gateway::SimulationResponse {
+    fn from(report: SimulationReport) -> Self {
+        let program_error = match &report.error {
+            Some(SimulationError::Program(err)) => Some(gateway::ProgramError {
+                instruction_index: err.instruction_index.into(),
+                code: err.code,
+                name: err.name.clone().unwrap_or_default(),
+                message: err.message.clone().unwrap_or_default(),
+            }),
+            _ => None,
+        };
+        gateway::SimulationResponse {
+            success: report.is_success(),
+            program_error,
+            error: report
+                .error
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            logs: report.logs,
+            units_consumed: report.units_consumed.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<UserProfile> for gateway::UserProfile {
+    fn from(profile: UserProfile) -> Self {
+        gateway::UserProfile {
+            authority: profile.authority.to_string(),
+            communication_pubkey: profile.communication_pubkey.to_string(),
+            admin_profile_pda: profile.admin_profile_on_creation.to_string(),
+            deposit_balance: profile.deposit_balance,
+            banned: profile.banned,
+            unban_requested: profile.unban_requested,
+            low_balance_threshold: profile.low_balance_threshold,
+            low_balance_notified: profile.low_balance_notified,
+            ban_count: profile.ban_count,
+            max_price_per_command: profile.max_price_per_command,
+            comm_key_version: profile.comm_key_version,
+        }
+    }
+}
+
+impl From<CommandMode> for gateway::CommandMode {
+    fn from(mode: CommandMode) -> Self {
+        match mode {
+            CommandMode::OneWay => gateway::CommandMode::OneWay,
+            CommandMode::RequestResponse => gateway::CommandMode::RequestResponse,
+        }
+    }
+}
+
+impl From<gateway::CommandMode> for CommandMode {
+    fn from(mode: gateway::CommandMode) -> Self {
+        match mode {
+            gateway::CommandMode::OneWay => CommandMode::OneWay,
+            gateway::CommandMode::RequestResponse => CommandMode::RequestResponse,
+        }
+    }
+}
 
 impl From<ConnectorEvents::EventSource> for gateway::EventSource {
     fn from(source: ConnectorEvents::EventSource) -> Self {
@@ -13,6 +144,8 @@ impl From<ConnectorEvents::EventSource> for gateway::EventSource {
 impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
     fn from(event: ConnectorEvents::BridgeEvent) -> Self {
         let source = gateway::EventSource::from(event.source);
+        let signature = event.signature.unwrap_or_default();
+        let slot = event.slot;
         let event_data = event.data;
 
         let bridge_event_oneof = match event_data {
@@ -23,6 +156,13 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
                         authority: e.authority.to_string(),
                         communication_pubkey: e.communication_pubkey.to_string(),
                         ts: e.ts,
+                        index: e.index.into(),
+                        oracle_authority: e.oracle_authority.to_string(),
+                        timestamp_validity_seconds: e.timestamp_validity_seconds,
+                        max_payload_size: e.max_payload_size.into(),
+                        unban_fee: e.unban_fee,
+                        unban_fee_multiplier_bps: e.unban_fee_multiplier_bps.into(),
+                        max_unban_fee_multiple: e.max_unban_fee_multiple.into(),
                     },
                 ))
             }
@@ -33,6 +173,9 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
                     new_oracle_authority: e.new_oracle_authority.to_string(),
                     new_timestamp_validity: e.new_timestamp_validity,
                     new_communication_pubkey: e.new_communication_pubkey.to_string(),
+                    new_max_payload_size: e.new_max_payload_size.into(),
+                    comm_key_version: e.comm_key_version,
+                    require_distinct_oracle: e.require_distinct_oracle,
                     ts: e.ts,
                 }),
             ),
@@ -50,6 +193,7 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
                     authority: e.authority.to_string(),
                     admin_pda: e.admin_pda.to_string(),
                     ts: e.ts,
+                    swept_balance: e.swept_balance,
                 }),
             ),
             ConnectorEvents::BridgeEventData::AdminCommandDispatched(e) => {
@@ -61,6 +205,8 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
                         command_id: e.command_id as u32,
                         payload: e.payload,
                         ts: e.ts,
+                        mode: gateway::CommandMode::from(e.mode) as i32,
+                        oracle_authority: e.oracle_authority.to_string(),
                     },
                 ))
             }
@@ -78,6 +224,7 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
                     authority: e.authority.to_string(),
                     user_profile_pda: e.user_profile_pda.to_string(),
                     new_comm_pubkey: e.new_comm_pubkey.to_string(),
+                    comm_key_version: e.comm_key_version,
                     ts: e.ts,
                 }),
             ),
@@ -87,6 +234,7 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
                     user_profile_pda: e.user_profile_pda.to_string(),
                     amount: e.amount,
                     new_deposit_balance: e.new_deposit_balance,
+                    source: e.source.to_string(),
                     ts: e.ts,
                 }),
             ),
@@ -106,6 +254,7 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
                     user_pda: e.user_pda.to_string(),
                     admin_pda: e.admin_pda.to_string(),
                     ts: e.ts,
+                    was_banned: e.was_banned,
                 }),
             ),
             ConnectorEvents::BridgeEventData::UserCommandDispatched(e) => {
@@ -118,6 +267,7 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
                         price_paid: e.price_paid,
                         payload: e.payload,
                         ts: e.ts,
+                        mode: gateway::CommandMode::from(e.mode) as i32,
                     },
                 ))
             }
@@ -127,7 +277,9 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
                     user_profile_pda: e.user_profile_pda.to_string(),
                     admin_profile_pda: e.admin_profile_pda.to_string(),
                     session_id: e.session_id,
+                    category: e.category.into(),
                     action_code: e.action_code as u32,
+                    metadata: e.metadata,
                     ts: e.ts,
                 }),
             ),
@@ -164,6 +316,114 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
                     ts: e.ts,
                 }),
             ),
+            ConnectorEvents::BridgeEventData::UserLowBalanceThresholdUpdated(e) => Some(
+                gateway::bridge_event::Event::UserLowBalanceThresholdUpdated(
+                    gateway::UserLowBalanceThresholdUpdated {
+                        authority: e.authority.to_string(),
+                        user_profile_pda: e.user_profile_pda.to_string(),
+                        new_threshold: e.new_threshold,
+                        ts: e.ts,
+                    },
+                ),
+            ),
+            ConnectorEvents::BridgeEventData::UserLowBalance(e) => Some(
+                gateway::bridge_event::Event::UserLowBalance(gateway::UserLowBalance {
+                    authority: e.authority.to_string(),
+                    user_profile_pda: e.user_profile_pda.to_string(),
+                    admin_pda: e.admin_pda.to_string(),
+                    deposit_balance: e.deposit_balance,
+                    threshold: e.threshold,
+                    ts: e.ts,
+                }),
+            ),
+            ConnectorEvents::BridgeEventData::UserSpendingCapUpdated(e) => {
+                Some(gateway::bridge_event::Event::UserSpendingCapUpdated(
+                    gateway::UserSpendingCapUpdated {
+                        authority: e.authority.to_string(),
+                        user_profile_pda: e.user_profile_pda.to_string(),
+                        new_max_price_per_command: e.new_max_price_per_command,
+                        ts: e.ts,
+                    },
+                ))
+            }
+            ConnectorEvents::BridgeEventData::AllowanceGranted(e) => Some(
+                gateway::bridge_event::Event::AllowanceGranted(gateway::AllowanceGranted {
+                    authority: e.authority.to_string(),
+                    user_profile_pda: e.user_profile_pda.to_string(),
+                    admin_pda: e.admin_pda.to_string(),
+                    commands: e.commands.iter().map(|&id| id as u32).collect(),
+                    total_cap: e.total_cap,
+                    expires_at: e.expires_at,
+                    ts: e.ts,
+                }),
+            ),
+            ConnectorEvents::BridgeEventData::AllowanceConsumed(e) => Some(
+                gateway::bridge_event::Event::AllowanceConsumed(gateway::AllowanceConsumed {
+                    user_profile_pda: e.user_profile_pda.to_string(),
+                    admin_pda: e.admin_pda.to_string(),
+                    charger: e.charger.to_string(),
+                    command_id: e.command_id as u32,
+                    amount: e.amount,
+                    remaining_cap: e.remaining_cap,
+                    ts: e.ts,
+                }),
+            ),
+            ConnectorEvents::BridgeEventData::WalletCreated(e) => Some(
+                gateway::bridge_event::Event::WalletCreated(gateway::WalletCreated {
+                    authority: e.authority.to_string(),
+                    wallet_pda: e.wallet_pda.to_string(),
+                    ts: e.ts,
+                }),
+            ),
+            ConnectorEvents::BridgeEventData::WalletFundsDeposited(e) => Some(
+                gateway::bridge_event::Event::WalletFundsDeposited(gateway::WalletFundsDeposited {
+                    authority: e.authority.to_string(),
+                    wallet_pda: e.wallet_pda.to_string(),
+                    amount: e.amount,
+                    new_deposit_balance: e.new_deposit_balance,
+                    ts: e.ts,
+                }),
+            ),
+            ConnectorEvents::BridgeEventData::WalletFundsWithdrawn(e) => Some(
+                gateway::bridge_event::Event::WalletFundsWithdrawn(gateway::WalletFundsWithdrawn {
+                    authority: e.authority.to_string(),
+                    wallet_pda: e.wallet_pda.to_string(),
+                    amount: e.amount,
+                    destination: e.destination.to_string(),
+                    new_deposit_balance: e.new_deposit_balance,
+                    ts: e.ts,
+                }),
+            ),
+            ConnectorEvents::BridgeEventData::WalletCharged(e) => Some(
+                gateway::bridge_event::Event::WalletCharged(gateway::WalletCharged {
+                    wallet_pda: e.wallet_pda.to_string(),
+                    user_profile_pda: e.user_profile_pda.to_string(),
+                    admin_pda: e.admin_pda.to_string(),
+                    amount: e.amount,
+                    new_deposit_balance: e.new_deposit_balance,
+                    ts: e.ts,
+                }),
+            ),
+            ConnectorEvents::BridgeEventData::CaughtUp(e) => {
+                Some(gateway::bridge_event::Event::CaughtUp(gateway::CaughtUp {
+                    pda: e.pda.to_string(),
+                    slot: e.slot,
+                }))
+            }
+            ConnectorEvents::BridgeEventData::Retracted(e) => Some(
+                gateway::bridge_event::Event::Retracted(gateway::Retracted {
+                    signature: e.signature,
+                    pdas: e.pdas.iter().map(ToString::to_string).collect(),
+                }),
+            ),
+            ConnectorEvents::BridgeEventData::DispatchRejected(e) => Some(
+                gateway::bridge_event::Event::DispatchRejected(gateway::DispatchRejected {
+                    signature: e.signature,
+                    reason: e.reason.to_string(),
+                    user_pda: e.user_pda.to_string(),
+                    admin_pda: e.admin_pda.to_string(),
+                }),
+            ),
             ConnectorEvents::BridgeEventData::Unknown => None,
         };
 
@@ -174,6 +434,8 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::EventStreamItem {
         Self {
             source: source as i32,
             event: Some(bridge_event),
+            signature,
+            slot,
         }
     }
 }
@@ -9,7 +9,13 @@
 //!   It holds the application's shared state, [`AppState`].
 //!
 //! - **[`AppState`]**: A container for shared, thread-safe components needed by the gRPC
-//!   service methods, primarily a handle to the `EventManager`.
+//!   service methods, primarily a handle to the `EventManager`. A gateway configured with
+//!   `gateway.realms` holds one `AppState` per realm, selected by the `x-realm` metadata entry.
+//!
+//! Every RPC is authenticated by the [`Authenticator`] in [`crate::auth`], then passes through
+//! the per-client rate limiter in [`crate::rate_limit`], and event streams additionally count
+//! against a per-client concurrent stream limit. Handlers that wait on the RPC node give up
+//! with `DEADLINE_EXCEEDED` once the deadline the client set on its call elapses.
 //!
 //! - **[`start`]**: The main entry point for initializing and running the gateway. It sets up
 //!   the database, spawns the `EventManager` for background event processing, and starts
@@ -34,37 +40,77 @@
 
 mod conversions;
 
-use anyhow::Result;
-use dashmap::DashMap;
+use anyhow::{Context, Result};
+use base64::{prelude::BASE64_STANDARD, Engine as _};
+use futures::future::BoxFuture;
+use futures::{stream, Stream, StreamExt};
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, message::Message, pubkey::Pubkey, signature::Signature,
+    transaction::Transaction,
+};
+use solana_transaction_status::UiTransactionEncoding;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::net::SocketAddr;
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::{mpsc, watch};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio::time::{interval_at, Duration, Instant, MissedTickBehavior};
 use tokio_stream::wrappers::ReceiverStream;
-use tonic::{transport::Server, Request, Response, Status};
-use w3b2_solana_connector::listener::EventListener;
+use tonic::codegen::http::{header, HeaderName, HeaderValue, Method};
+use tonic::metadata::MetadataMap;
+use tonic::service::interceptor::InterceptedService;
+use tonic::{transport::Server, Extensions, Request, Response, Status};
+use tonic_web::GrpcWebLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use w3b2_solana_connector::events::{BridgeEventData, EVENT_SCHEMA_VERSION};
+use w3b2_solana_connector::listener::{BridgeEvent, EventListener, HistoryOptions};
 use w3b2_solana_connector::workers::{EventManager, EventManagerHandle};
 
-use w3b2_solana_connector::client::{TransactionBuilder, UserDispatchCommandArgs};
+use w3b2_solana_connector::client::{
+    blockhash_placeholder_offset, oracle_signature_from_slice, CommandMode, TransactionBuilder,
+    UserDispatchCommandArgs,
+};
+use w3b2_solana_connector::pda::{find_admin_profile_pda_for, find_user_profile_pda_for};
+use w3b2_solana_connector::storage::Storage;
+use w3b2_solana_connector::{
+    ALL_ERRORS, MAX_ALLOWANCE_COMMANDS, MAX_METADATA_SIZE, MAX_PAYLOAD_SIZE,
+    MAX_TIMESTAMP_AGE_SECONDS, MAX_TIMESTAMP_FUTURE_SKEW_SECONDS, MAX_TIMESTAMP_VALIDITY_SECONDS,
+    MIN_TIMESTAMP_VALIDITY_SECONDS,
+};
 
 use crate::grpc::proto::w3b2::protocol::gateway::bridge_gateway_service_server::{
     BridgeGatewayService, BridgeGatewayServiceServer,
 };
 use crate::{
+    auth::{AuthService, Authenticator, Principal, StaticApiKeyAuthenticator},
     config::GatewayConfig,
     error::GatewayError,
     grpc::proto::w3b2::protocol::gateway::{
-        self, BlockhashResponse, EventStreamItem, ListenRequest, PrepareAdminBanUserRequest,
-        PrepareAdminCloseProfileRequest, PrepareAdminDispatchCommandRequest,
-        PrepareAdminRegisterProfileRequest, PrepareAdminSetConfigRequest,
-        PrepareAdminUnbanUserRequest, PrepareAdminWithdrawRequest, PrepareLogActionRequest,
-        PrepareUserCloseProfileRequest, PrepareUserCreateProfileRequest, PrepareUserDepositRequest,
-        PrepareUserDispatchCommandRequest, PrepareUserRequestUnbanRequest,
-        PrepareUserUpdateCommKeyRequest, PrepareUserWithdrawRequest, SubmitTransactionRequest,
-        TransactionResponse, UnsignedTransactionResponse, UnsubscribeRequest,
+        self, BlockhashResponse, DeriveAdminPdaRequest, DeriveUserPdaRequest, DerivedPdaResponse,
+        EventCountsResponse, EventStreamItem, GatewayInfoResponse, GetEventCountsRequest,
+        GetTransactionLogsRequest, GetUserProfilesBatchRequest, ListPendingUnbanRequestsRequest,
+        ListSubscriptionsResponse, ListenRequest, PendingUnbanRequest,
+        PendingUnbanRequestsResponse, PrepareAdminBanUserRequest, PrepareAdminCloseProfileRequest,
+        PrepareAdminDispatchCommandRequest, PrepareAdminRegisterProfileRequest,
+        PrepareAdminSetConfigRequest, PrepareAdminUnbanUserRequest, PrepareAdminWithdrawAllRequest,
+        PrepareAdminWithdrawRequest, PrepareBatchItem, PrepareBatchRequest, PrepareBatchResponse,
+        PrepareLogActionRequest, PrepareUserCloseProfileRequest, PrepareUserCreateProfileRequest,
+        PrepareUserDepositRequest, PrepareUserDispatchCommandRequest,
+        PrepareUserRequestUnbanRequest, PrepareUserSetLowBalanceThresholdRequest,
+        PrepareUserSetSpendingCapRequest, PrepareUserUpdateCommKeyRequest,
+        PrepareUserWithdrawRequest, ProgramErrorInfo, ProtocolLimitsResponse,
+        SimulatePreparedTransactionRequest, SimulationResponse, SubmitTransactionRequest,
+        SubscriptionInfo, SyncStatusResponse, TailRequest, TransactionLogsResponse,
+        TransactionResponse, UnsignedTransactionResponse, UnsubscribeAllResponse,
+        UnsubscribeRequest, UserProfileEntry, UserProfilesBatchResponse,
     },
+    payload_limits::PayloadLimits,
+    rate_limit::{self, client_key, RateLimiter, StreamLimiter, StreamPermit},
     storage::SledStorage,
+    subscriptions::SubscriptionRegistry,
 };
 
 /// Generated protobuf code.
@@ -78,6 +124,28 @@ pub mod proto {
     }
 }
 
+/// The metadata key selecting the realm a request is served by. Requests without it are
+/// served by the default realm, configured in `[connector]`.
+pub const REALM_METADATA_KEY: &str = "x-realm";
+
+/// The metadata key selecting how the `prepare_*` RPCs return the prepared message:
+/// `bincode` (the default) as raw bytes in `unsigned_tx_message`, or `base64` as a string in
+/// `unsigned_tx_message_base64` that `@solana/web3.js` can decode with `Message.from`.
+pub const MESSAGE_ENCODING_METADATA_KEY: &str = "x-message-encoding";
+
+/// The metadata key carrying the time a client allows for its call, as set by gRPC clients
+/// with a deadline.
+const GRPC_TIMEOUT_METADATA_KEY: &str = "grpc-timeout";
+
+/// The maximum number of historical events a `Tail` request may ask for.
+const MAX_TAIL_LOOKBACK: u32 = 1000;
+
+/// The maximum number of profiles a single `GetUserProfilesBatch` request may ask for.
+const MAX_USER_PROFILES_PER_BATCH: usize = 1000;
+
+/// The maximum number of steps a single `PrepareBatch` request may ask for.
+const MAX_PREPARE_BATCH_STEPS: usize = 32;
+
 /// A container for the application's shared, thread-safe state.
 ///
 /// An `Arc` of this struct is cloned for each gRPC service instance,
@@ -90,9 +158,42 @@ pub struct AppState {
     pub event_manager: EventManagerHandle,
     /// The gateway's configuration.
     pub config: Arc<GatewayConfig>,
-    /// A map storing `watch` channel senders to signal termination for active event subscriptions.
-    /// The key is the subscribed PDA's `Pubkey`.
-    pub active_subscriptions: Arc<DashMap<Pubkey, watch::Sender<()>>>,
+    /// The registry of active live subscriptions. It shares one upstream listener per PDA
+    /// between all clients watching it and enforces the global subscription limit.
+    pub subscriptions: Arc<SubscriptionRegistry>,
+    /// Tracks the number of open event streams per client.
+    pub stream_limiter: Arc<StreamLimiter>,
+    /// The storage shared with the `EventManager`, read directly for event counts.
+    pub storage: Arc<SledStorage>,
+    /// The cached payload limits of the admins, checked before a dispatch is prepared.
+    pub payload_limits: Arc<PayloadLimits>,
+    /// The states of the additional realms in `gateway.realms`, by name. Each has its own
+    /// RPC client, `EventManager`, subscriptions and storage, and no realms of its own.
+    pub realms: Arc<HashMap<String, AppState>>,
+}
+
+impl AppState {
+    /// Returns the state of the realm `request` is addressed to, following its
+    /// [`REALM_METADATA_KEY`] entry.
+    pub fn realm<T>(&self, request: &Request<T>) -> Result<&AppState, GatewayError> {
+        let Some(realm) = request.metadata().get(REALM_METADATA_KEY) else {
+            return Ok(self);
+        };
+        let realm = realm.to_str().unwrap_or_default();
+        self.realms
+            .get(realm)
+            .ok_or_else(|| GatewayError::InvalidArgument(format!("Unknown realm '{realm}'")))
+    }
+
+    /// The id of the program deployment this state's connector is configured for.
+    pub fn program_id(&self) -> Pubkey {
+        self.config.connector.solana.program_id
+    }
+
+    /// Creates a `TransactionBuilder` over this state's RPC client, addressed to its program id.
+    pub fn transaction_builder(&self) -> TransactionBuilder<RpcClient> {
+        TransactionBuilder::new(self.rpc_client.clone()).with_program_id(self.program_id())
+    }
 }
 
 /// The gRPC server implementation for the `BridgeGatewayService`.
@@ -106,50 +207,264 @@ impl GatewayServer {
     pub fn new(state: AppState) -> Self {
         Self { state }
     }
+
+    /// Prepares one step of a `PrepareBatch` request through the matching `Prepare*` RPC,
+    /// called with the `metadata` of the batch request.
+    async fn prepare_batch_item(
+        &self,
+        metadata: MetadataMap,
+        item: PrepareBatchItem,
+    ) -> Result<UnsignedTransactionResponse, Status> {
+        use gateway::prepare_batch_item::Request as Step;
+
+        let Some(step) = item.request else {
+            return Err(Status::invalid_argument("The step carries no request"));
+        };
+        macro_rules! prepare {
+            ($($step:ident => $rpc:ident),* $(,)?) => {
+                match step {
+                    $(Step::$step(req) => {
+                        self.$rpc(Request::from_parts(metadata, Extensions::new(), req)).await
+                    })*
+                }
+            };
+        }
+        let response = prepare! {
+            AdminRegisterProfile => prepare_admin_register_profile,
+            AdminSetConfig => prepare_admin_set_config,
+            AdminWithdraw => prepare_admin_withdraw,
+            AdminWithdrawAll => prepare_admin_withdraw_all,
+            AdminCloseProfile => prepare_admin_close_profile,
+            AdminDispatchCommand => prepare_admin_dispatch_command,
+            AdminBanUser => prepare_admin_ban_user,
+            AdminUnbanUser => prepare_admin_unban_user,
+            UserCreateProfile => prepare_user_create_profile,
+            UserUpdateCommKey => prepare_user_update_comm_key,
+            UserSetLowBalanceThreshold => prepare_user_set_low_balance_threshold,
+            UserSetSpendingCap => prepare_user_set_spending_cap,
+            UserDeposit => prepare_user_deposit,
+            UserWithdraw => prepare_user_withdraw,
+            UserCloseProfile => prepare_user_close_profile,
+            UserDispatchCommand => prepare_user_dispatch_command,
+            UserRequestUnban => prepare_user_request_unban,
+            LogAction => prepare_log_action,
+        }?;
+        Ok(response.into_inner())
+    }
 }
 
 /// The main entry point to initialize and start the gRPC server and all background services.
-pub async fn start(config: &GatewayConfig) -> Result<EventManagerHandle> {
-    // --- 1. Initialize dependencies ---
-    let db = sled::open(&config.gateway.db_path)?;
-    let storage = Arc::new(SledStorage::new(db));
+///
+/// Returns the handles of the `EventManager`s of the default realm and of every configured
+/// realm, so that the caller can stop them.
+pub async fn start(config: &GatewayConfig) -> Result<Vec<EventManagerHandle>> {
+    let authenticator = Arc::new(StaticApiKeyAuthenticator::from_config(&config.gateway.auth));
+    start_with_authenticator(config, authenticator).await
+}
+
+/// Like [`start`], but authenticates clients with `authenticator` instead of the
+/// `[gateway.auth]` API keys.
+pub async fn start_with_authenticator(
+    config: &GatewayConfig,
+    authenticator: Arc<dyn Authenticator>,
+) -> Result<Vec<EventManagerHandle>> {
     let addr = format!("{}:{}", config.gateway.grpc.host, config.gateway.grpc.port).parse()?;
+    #[cfg(not(feature = "http"))]
+    if config.gateway.http.enabled {
+        anyhow::bail!(
+            "`gateway.http.enabled` requires the gateway to be built with the `http` feature"
+        );
+    }
+    // Stream limits are per client, whichever realms its streams are opened in.
+    let stream_limiter = Arc::new(StreamLimiter::new(&config.gateway.rate_limit));
+
+    // --- 1. Start the EventManager of every realm ---
+    let mut handles = Vec::new();
+    let mut realms = HashMap::new();
+    for (name, realm) in &config.gateway.realms {
+        let (state, handle) = start_realm(config.for_realm(realm), stream_limiter.clone())
+            .with_context(|| format!("Failed to start realm '{name}'"))?;
+        tracing::info!(
+            "Serving realm '{}' from {}",
+            name,
+            sanitize_rpc_url(&realm.connector.solana.rpc_url)
+        );
+        realms.insert(name.clone(), state);
+        handles.push(handle);
+    }
+    let (mut app_state, handle) = start_realm(config.clone(), stream_limiter)?;
+    app_state.realms = Arc::new(realms);
+    handles.push(handle);
+
+    #[cfg(feature = "http")]
+    if config.gateway.http.enabled {
+        let http_addr: SocketAddr =
+            format!("{}:{}", config.gateway.http.host, config.gateway.http.port).parse()?;
+        let http_server = crate::http::build_server_with_authenticator(
+            app_state.clone(),
+            http_addr,
+            authenticator.clone(),
+        )?;
+        tracing::info!("HTTP/JSON bridge listening on {}", http_addr);
+        tokio::spawn(async move {
+            if let Err(e) = http_server.await {
+                tracing::error!("HTTP server failed: {}", e);
+            }
+        });
+    }
+
+    let grpc_server = build_server_with_authenticator(app_state, addr, authenticator)?;
+
+    tracing::info!(
+        "Non-Custodial gRPC Gateway with Event Streaming listening on {}",
+        addr
+    );
+
+    tokio::spawn(async move {
+        if let Err(e) = grpc_server.await {
+            tracing::error!("gRPC server failed: {}", e);
+        }
+    });
+
+    // Return the handles so the caller can gracefully shut down the event managers.
+    Ok(handles)
+}
+
+/// Opens the database of a single realm and spawns its `EventManager`.
+fn start_realm(
+    config: GatewayConfig,
+    stream_limiter: Arc<StreamLimiter>,
+) -> Result<(AppState, EventManagerHandle)> {
+    let db = sled::open(&config.gateway.db_path)?;
+    let mut storage = SledStorage::new(db);
+    if config.gateway.storage.cache_events {
+        storage = storage.with_event_cache(config.gateway.storage.max_cached_events_per_pda)?;
+    }
+    let storage = Arc::new(storage);
     let rpc_client = Arc::new(RpcClient::new(config.connector.solana.rpc_url.clone()));
 
-    // --- 2. Create and spawn the EventManager service ---
     let (event_manager_runner, event_manager_handle) = EventManager::new(
         Arc::new(config.connector.clone()),
         rpc_client.clone(),
-        storage,
+        storage.clone(),
     );
     tokio::spawn(event_manager_runner.run());
 
-    // --- 3. Set up the gRPC server state ---
-    let handle_for_server = event_manager_handle.clone();
-    let app_state = AppState {
+    let state = AppState {
         rpc_client,
-        event_manager: handle_for_server,
-        config: Arc::new(config.clone()),
-        active_subscriptions: Arc::new(DashMap::new()),
+        event_manager: event_manager_handle.clone(),
+        subscriptions: Arc::new(SubscriptionRegistry::new(
+            config.gateway.limits.max_subscriptions,
+            config.connector.channels.listener_event_buffer,
+        )),
+        stream_limiter,
+        storage,
+        payload_limits: Arc::new(PayloadLimits::new()),
+        realms: Arc::default(),
+        config: Arc::new(config),
     };
+    Ok((state, event_manager_handle))
+}
 
-    let gateway_server = GatewayServer::new(app_state);
-    let grpc_server =
-        Server::builder().add_service(BridgeGatewayServiceServer::new(gateway_server));
+/// Builds the gRPC server for `state`, returning the future that serves it on `addr`.
+///
+/// If `grpc.enable_web` is set, the server also accepts HTTP/1.1 and translates `grpc-web`
+/// requests, answering CORS preflights for the origins in `grpc.cors_allowed_origins`.
+/// Responses are compressed with the first of `grpc.compression` the client accepts.
+pub fn build_server(
+    state: AppState,
+    addr: SocketAddr,
+) -> Result<BoxFuture<'static, Result<(), tonic::transport::Error>>> {
+    let authenticator = Arc::new(StaticApiKeyAuthenticator::from_config(
+        &state.config.gateway.auth,
+    ));
+    build_server_with_authenticator(state, addr, authenticator)
+}
 
-    tracing::info!(
-        "Non-Custodial gRPC Gateway with Event Streaming listening on {}",
-        addr
+/// Like [`build_server`], but authenticates clients with `authenticator` instead of the
+/// `[gateway.auth]` API keys. Rate limits apply per resolved principal.
+pub fn build_server_with_authenticator(
+    state: AppState,
+    addr: SocketAddr,
+    authenticator: Arc<dyn Authenticator>,
+) -> Result<BoxFuture<'static, Result<(), tonic::transport::Error>>> {
+    let grpc_config = state.config.gateway.grpc.clone();
+    let rate_limiter = Arc::new(RateLimiter::new(&state.config.gateway.rate_limit));
+    let mut gateway = BridgeGatewayServiceServer::new(GatewayServer::new(state));
+    for &compression in &grpc_config.compression {
+        gateway = gateway
+            .accept_compressed(compression.into())
+            .send_compressed(compression.into());
+    }
+    let service = AuthService::new(
+        InterceptedService::new(gateway, rate_limit::interceptor(rate_limiter)),
+        authenticator,
     );
 
-    tokio::spawn(async move {
-        if let Err(e) = grpc_server.serve(addr).await {
-            tracing::error!("gRPC server failed: {}", e);
-        }
-    });
+    if !grpc_config.enable_web {
+        return Ok(Box::pin(Server::builder().add_service(service).serve(addr)));
+    }
+    let cors = cors_layer(&grpc_config.cors_allowed_origins)?;
+    tracing::info!("gRPC-web is enabled for browser clients.");
+    Ok(Box::pin(
+        Server::builder()
+            .accept_http1(true)
+            .layer(cors)
+            .layer(GrpcWebLayer::new())
+            .add_service(service)
+            .serve(addr),
+    ))
+}
+
+/// Builds the CORS policy for `grpc-web` clients. An empty `allowed_origins` allows any
+/// origin.
+fn cors_layer(allowed_origins: &[String]) -> Result<CorsLayer> {
+    let allow_origin = if allowed_origins.is_empty() {
+        AllowOrigin::any()
+    } else {
+        let origins = allowed_origins
+            .iter()
+            .map(|origin| {
+                HeaderValue::from_str(origin)
+                    .with_context(|| format!("Invalid CORS origin '{origin}'"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        AllowOrigin::list(origins)
+    };
 
-    // Return the handle so the caller can gracefully shut down the event manager.
-    Ok(event_manager_handle)
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::POST])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            HeaderName::from_static("x-grpc-web"),
+            HeaderName::from_static("x-user-agent"),
+            HeaderName::from_static("grpc-timeout"),
+            HeaderName::from_static("x-api-key"),
+            HeaderName::from_static(REALM_METADATA_KEY),
+            HeaderName::from_static(MESSAGE_ENCODING_METADATA_KEY),
+        ])
+        .expose_headers([
+            HeaderName::from_static("grpc-status"),
+            HeaderName::from_static("grpc-message"),
+            HeaderName::from_static("grpc-status-details-bin"),
+        ])
+        .max_age(Duration::from_secs(24 * 60 * 60)))
+}
+
+/// Reduces an RPC URL to its scheme, host and port, dropping credentials, the path and the
+/// query string, which commonly carry provider API keys.
+pub fn sanitize_rpc_url(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    if scheme.is_empty() {
+        host.to_string()
+    } else {
+        format!("{scheme}://{host}")
+    }
 }
 
 /// A helper function to parse a string into a `Pubkey`, returning a `GatewayError` on failure.
@@ -157,70 +472,523 @@ fn parse_pubkey(s: &str) -> Result<Pubkey, GatewayError> {
     Pubkey::from_str(s).map_err(GatewayError::from)
 }
 
+/// Fails with `PermissionDenied` unless `request` was authenticated as one of the
+/// `admin_principals`.
+fn require_admin<T>(request: &Request<T>, admin_principals: &[String]) -> Result<(), GatewayError> {
+    let principal = request.extensions().get::<Principal>();
+    match principal.and_then(Principal::name) {
+        Some(name) if admin_principals.iter().any(|admin| admin == name) => Ok(()),
+        _ => Err(GatewayError::PermissionDenied(format!(
+            "{} is not an admin principal",
+            principal.unwrap_or(&Principal::Anonymous)
+        ))),
+    }
+}
+
+/// Creates the `TransactionBuilder` of a `Prepare*` request, with its optional fee payer.
+fn transaction_builder(
+    state: &AppState,
+    fee_payer: Option<&str>,
+) -> Result<TransactionBuilder<RpcClient>, GatewayError> {
+    let builder = state.transaction_builder();
+    Ok(match fee_payer {
+        Some(fee_payer) => builder.with_fee_payer(parse_pubkey(fee_payer)?),
+        None => builder,
+    })
+}
+
+/// How the `prepare_*` RPCs return the prepared message, see
+/// [`MESSAGE_ENCODING_METADATA_KEY`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum MessageEncoding {
+    #[default]
+    Bincode,
+    Base64,
+}
+
+/// Returns the message encoding `request` asks for in its [`MESSAGE_ENCODING_METADATA_KEY`]
+/// entry.
+fn message_encoding<T>(request: &Request<T>) -> Result<MessageEncoding, GatewayError> {
+    let Some(encoding) = request.metadata().get(MESSAGE_ENCODING_METADATA_KEY) else {
+        return Ok(MessageEncoding::default());
+    };
+    match encoding.to_str().unwrap_or_default() {
+        "bincode" => Ok(MessageEncoding::Bincode),
+        "base64" => Ok(MessageEncoding::Base64),
+        other => Err(GatewayError::InvalidArgument(format!(
+            "Unknown message encoding '{other}', expected 'bincode' or 'base64'"
+        ))),
+    }
+}
+
+/// Wraps a message prepared by the `TransactionBuilder` into a response, along with the
+/// offset of its zeroed blockhash placeholder.
+///
+/// The message is serialized in the Solana wire format either way, so the base64 string
+/// decodes to the same bytes that `bincode` returns.
+fn unsigned_transaction(
+    unsigned_tx_message: Vec<u8>,
+    encoding: MessageEncoding,
+) -> UnsignedTransactionResponse {
+    let offset = blockhash_placeholder_offset(&unsigned_tx_message)
+        .expect("the TransactionBuilder always serializes a complete message");
+    match encoding {
+        MessageEncoding::Bincode => UnsignedTransactionResponse {
+            unsigned_tx_message,
+            blockhash_placeholder_offset: offset as u32,
+            ..Default::default()
+        },
+        MessageEncoding::Base64 => UnsignedTransactionResponse {
+            unsigned_tx_message_base64: BASE64_STANDARD.encode(unsigned_tx_message),
+            blockhash_placeholder_offset: offset as u32,
+            ..Default::default()
+        },
+    }
+}
+
+/// Returns when the deadline the client set on `request` elapses, if it set one.
+///
+/// The `grpc-timeout` entry is at most eight digits followed by a unit: `H`, `M`, `S`, `m`
+/// (milliseconds), `u` (microseconds) or `n` (nanoseconds). A malformed entry is ignored.
+pub fn request_deadline<T>(request: &Request<T>) -> Option<Instant> {
+    let timeout = request
+        .metadata()
+        .get(GRPC_TIMEOUT_METADATA_KEY)?
+        .to_str()
+        .ok()?;
+    let (value, unit) = timeout.split_at(timeout.len().checked_sub(1)?);
+    if value.is_empty() || value.len() > 8 || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let value: u64 = value.parse().ok()?;
+    let timeout = match unit {
+        "H" => Duration::from_secs(value * 60 * 60),
+        "M" => Duration::from_secs(value * 60),
+        "S" => Duration::from_secs(value),
+        "m" => Duration::from_millis(value),
+        "u" => Duration::from_micros(value),
+        "n" => Duration::from_nanos(value),
+        _ => return None,
+    };
+    Some(Instant::now() + timeout)
+}
+
+/// Runs a handler's RPC-bound `work` until `deadline`, so that a slow RPC node does not hold
+/// the handler's resources after the client has given up.
+async fn within_deadline<T>(
+    deadline: Option<Instant>,
+    work: impl Future<Output = Result<T, GatewayError>>,
+) -> Result<T, GatewayError> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, work)
+            .await
+            .map_err(|_| GatewayError::DeadlineExceeded)?,
+        None => work.await,
+    }
+}
+
+/// A helper function to narrow a protobuf `uint32` admin profile index to the program's `u16`.
+fn parse_admin_index(index: u32) -> Result<u16, GatewayError> {
+    u16::try_from(index).map_err(|_| {
+        GatewayError::InvalidArgument(format!("admin index {index} does not fit in a u16"))
+    })
+}
+
+/// A helper function to narrow an optional protobuf `uint32` field to a `u16`.
+fn parse_optional_u16(field: &str, value: Option<u32>) -> Result<Option<u16>, GatewayError> {
+    value
+        .map(|value| {
+            u16::try_from(value).map_err(|_| {
+                GatewayError::InvalidArgument(format!("{field} {value} does not fit in a u16"))
+            })
+        })
+        .transpose()
+}
+
+/// A helper function to convert a protobuf `CommandMode` into the program's.
+fn parse_command_mode(mode: i32) -> Result<CommandMode, GatewayError> {
+    gateway::CommandMode::try_from(mode)
+        .map(CommandMode::from)
+        .map_err(|_| GatewayError::InvalidArgument(format!("unknown command mode {mode}")))
+}
+
+/// A helper function to read the history options of a `ListenRequest`.
+fn history_options(req: &ListenRequest) -> Result<HistoryOptions, GatewayError> {
+    if req.limit == Some(0) {
+        return Err(GatewayError::InvalidArgument(
+            "limit must be greater than 0".to_string(),
+        ));
+    }
+    Ok(HistoryOptions {
+        newest_first: req.reverse,
+        limit: req.limit.map(|limit| limit as usize),
+        since_slot: req.since_slot,
+    })
+}
+
+/// Folds the event history of an admin profile into its queue of pending unban requests:
+/// the users that are banned and have requested an unban, oldest request first.
+///
+/// A user leaves the queue once they are unbanned or their profile is closed. Since only
+/// banned users can request an unban, a request also marks its user as banned, so the queue
+/// stays correct for a history that was truncated after the ban.
+pub fn pending_unban_requests<'a>(
+    events: impl IntoIterator<Item = &'a BridgeEventData>,
+) -> Vec<PendingUnbanRequest> {
+    #[derive(Default)]
+    struct UserState {
+        banned: bool,
+        request: Option<(usize, PendingUnbanRequest)>,
+    }
+
+    let mut users: HashMap<Pubkey, UserState> = HashMap::new();
+    for (seq, event) in events.into_iter().enumerate() {
+        match event {
+            BridgeEventData::UserBanned(e) => {
+                users.entry(e.user_profile_pda).or_default().banned = true;
+            }
+            BridgeEventData::UserUnbanRequested(e) => {
+                let user = users.entry(e.user_profile_pda).or_default();
+                user.banned = true;
+                user.request = Some((
+                    seq,
+                    PendingUnbanRequest {
+                        user_profile_pda: e.user_profile_pda.to_string(),
+                        user_authority: e.user_authority.to_string(),
+                        fee_paid: e.fee_paid,
+                        requested_at: e.ts,
+                    },
+                ));
+            }
+            BridgeEventData::UserUnbanned(e) => {
+                users.remove(&e.user_profile_pda);
+            }
+            BridgeEventData::UserProfileClosed(e) => {
+                users.remove(&e.user_pda);
+            }
+            _ => {}
+        }
+    }
+
+    let mut pending: Vec<_> = users
+        .into_values()
+        .filter(|user| user.banned)
+        .filter_map(|user| user.request)
+        .collect();
+    pending.sort_by_key(|(seq, _)| *seq);
+    pending.into_iter().map(|(_, request)| request).collect()
+}
+
+/// Turns a listener into a stream of its live events.
+fn live_events(listener: EventListener) -> impl Stream<Item = BridgeEvent> + Send + Unpin {
+    Box::pin(stream::unfold(listener, |mut listener| async move {
+        listener
+            .next_live_event()
+            .await
+            .map(|event| (event, listener))
+    }))
+}
+
+/// Turns a listener into a stream of its catch-up events.
+fn catchup_events(listener: EventListener) -> impl Stream<Item = BridgeEvent> + Send + Unpin {
+    Box::pin(stream::unfold(listener, |mut listener| async move {
+        listener
+            .next_catchup_event()
+            .await
+            .map(|event| (event, listener))
+    }))
+}
+
 /// A helper to handle the logic for streaming **live** events.
 ///
-/// This function registers a persistent listener and spawns a background task that
-/// forwards live events to the gRPC stream. It manages the subscription lifecycle,
-/// cleaning up when the client disconnects or unsubscribes.
-async fn handle_live_stream(
+/// This function joins the shared upstream of `pda`, opening it with `upstream` if no other
+/// client is watching the PDA, or fails with the error of `upstream`. It then spawns a
+/// background task that forwards live events to the gRPC stream. It manages the
+/// subscription lifecycle, cleaning up when the client disconnects or unsubscribes. The
+/// stream's `permit` is released when the task ends, or taken over by the client's next
+/// stream as soon as this one's client side is closed.
+///
+/// While no event has been forwarded for `heartbeat-interval-secs`, a heartbeat item is sent
+/// instead so that proxies between the gateway and the client keep the stream open.
+pub async fn handle_live_stream<S>(
     state: &AppState,
     pda: Pubkey,
-    mut listener: EventListener,
-) -> Result<Response<ReceiverStream<Result<EventStreamItem, Status>>>, Status> {
+    client: String,
+    upstream: impl FnOnce() -> Result<S, Status>,
+    permit: StreamPermit,
+) -> Result<Response<ReceiverStream<Result<EventStreamItem, Status>>>, Status>
+where
+    S: Stream<Item = BridgeEvent> + Send + Unpin + 'static,
+{
+    handle_tail_stream(state, pda, client, upstream, stream::empty(), 0, permit).await
+}
+
+/// A helper to handle the logic for `Tail` streams: a live stream that starts with the
+/// `last_n` most recent events of `history`, which is drained first.
+///
+/// The live upstream is joined before `history` is read, so no event falls in between.
+/// Live events of transactions already sent as part of the lookback are skipped.
+pub async fn handle_tail_stream<S, H>(
+    state: &AppState,
+    pda: Pubkey,
+    client: String,
+    upstream: impl FnOnce() -> Result<S, Status>,
+    history: H,
+    last_n: usize,
+    permit: StreamPermit,
+) -> Result<Response<ReceiverStream<Result<EventStreamItem, Status>>>, Status>
+where
+    S: Stream<Item = BridgeEvent> + Send + Unpin + 'static,
+    H: Stream<Item = BridgeEvent> + Send + Unpin + 'static,
+{
+    spawn_live_stream(
+        state,
+        pda,
+        client,
+        upstream,
+        history,
+        Lookback::Last(last_n),
+        permit,
+    )
+}
+
+/// A helper to handle the logic for live streams resumed with `since_slot`: every event of
+/// `history` after `since_slot` is sent first, followed by a `Resumed` acknowledgement.
+///
+/// `history` is expected to be a catch-up stream that ends with its `CaughtUp` marker; if
+/// it ends without one, the history is incomplete and the stream fails with `UNAVAILABLE`
+/// instead of going live with a gap. As with `Tail`, the live upstream is joined before
+/// `history` is read, and live events of replayed transactions, or of transactions up to
+/// `since_slot`, are skipped.
+pub async fn handle_resumed_stream<S, H>(
+    state: &AppState,
+    pda: Pubkey,
+    client: String,
+    upstream: impl FnOnce() -> Result<S, Status>,
+    history: H,
+    since_slot: u64,
+    permit: StreamPermit,
+) -> Result<Response<ReceiverStream<Result<EventStreamItem, Status>>>, Status>
+where
+    S: Stream<Item = BridgeEvent> + Send + Unpin + 'static,
+    H: Stream<Item = BridgeEvent> + Send + Unpin + 'static,
+{
+    spawn_live_stream(
+        state,
+        pda,
+        client,
+        upstream,
+        history,
+        Lookback::Since(since_slot),
+        permit,
+    )
+}
+
+/// The part of a PDA's history a live stream starts with.
+#[derive(Debug, Clone, Copy)]
+enum Lookback {
+    /// The `n` most recent events.
+    Last(usize),
+    /// Every event after the slot, acknowledged with a `Resumed` marker.
+    Since(u64),
+}
+
+/// Joins the shared upstream of `pda` and spawns the task forwarding the `lookback` from
+/// `history`, then the live events, to the returned gRPC stream.
+fn spawn_live_stream<S, H>(
+    state: &AppState,
+    pda: Pubkey,
+    client: String,
+    upstream: impl FnOnce() -> Result<S, Status>,
+    history: H,
+    lookback: Lookback,
+    permit: StreamPermit,
+) -> Result<Response<ReceiverStream<Result<EventStreamItem, Status>>>, Status>
+where
+    S: Stream<Item = BridgeEvent> + Send + Unpin + 'static,
+    H: Stream<Item = BridgeEvent> + Send + Unpin + 'static,
+{
     let (tx, rx) = mpsc::channel(state.config.connector.channels.listener_event_buffer);
+    permit.bind(&tx);
 
-    // Create a watch channel to signal termination for this specific stream.
-    let (stop_tx, mut stop_rx) = watch::channel(());
-    if state.active_subscriptions.insert(pda, stop_tx).is_some() {
-        return Err(Status::already_exists(format!(
-            "A listener for PDA {pda} is already active"
-        )));
-    }
+    // Reserve a slot in the registry. It is released when `subscription` is dropped at the
+    // end of the task, however the stream ends.
+    let mut subscription = state.subscriptions.subscribe(pda, client, upstream)?;
+
+    let heartbeat_secs = state.config.gateway.grpc.heartbeat_interval_secs;
+    let heartbeat_period = Duration::from_secs(heartbeat_secs.max(1));
+    let mut heartbeat = interval_at(Instant::now() + heartbeat_period, heartbeat_period);
+    heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
-    let active_subscriptions_clone = state.active_subscriptions.clone();
+    let max_catchup_depth = state.config.connector.synchronizer.max_catchup_depth;
 
     tokio::spawn(async move {
+        let _permit = permit;
+        let (events, resumed) = match lookback {
+            Lookback::Last(last_n) => (recent_events(history, last_n).await, None),
+            Lookback::Since(since_slot) => match events_since(history, since_slot).await {
+                Some((events, slot)) => {
+                    let since_slot = effective_resume_slot(since_slot, slot, max_catchup_depth);
+                    (
+                        events,
+                        Some(conversions::resumed_item(&pda, since_slot, slot)),
+                    )
+                }
+                None => {
+                    tracing::warn!(
+                        "History of PDA {} after slot {} is incomplete.",
+                        pda,
+                        since_slot
+                    );
+                    let _ = tx
+                        .send(Err(Status::unavailable(format!(
+                            "The events after slot {since_slot} could not be replayed"
+                        ))))
+                        .await;
+                    return;
+                }
+            },
+        };
+        // Transactions the client already has are not sent again by the live stream.
+        let skip_until = match lookback {
+            Lookback::Since(since_slot) => Some(since_slot),
+            Lookback::Last(_) => None,
+        };
+        let sent: HashSet<String> = events.iter().filter_map(|e| e.signature.clone()).collect();
+        for item in events
+            .into_iter()
+            .map(gateway::EventStreamItem::from)
+            .chain(resumed)
+        {
+            if tx.send(Ok(item)).await.is_err() {
+                tracing::warn!("Client for PDA {} disconnected during lookback.", pda);
+                return;
+            }
+        }
+
         // Listen for live events and the stop signal.
         loop {
             tokio::select! {
-                _ = stop_rx.changed() => {
-                    tracing::info!("Unsubscribe signal received for PDA {}. Closing stream.", pda);
-                    break;
-                }
-                Some(event) = listener.next_live_event() => {
-                    if tx.send(Ok(gateway::EventStreamItem::from(event))).await.is_err() {
+                _ = heartbeat.tick(), if heartbeat_secs > 0 => {
+                    if tx.send(Ok(conversions::heartbeat_item())).await.is_err() {
                         tracing::warn!("Client for PDA {} disconnected during live stream.", pda);
                         break;
                     }
                 }
-                else => {
-                    tracing::info!("Event manager shut down for PDA {}. Closing stream.", pda);
+                _ = tx.closed() => {
+                    tracing::info!("Client for PDA {} disconnected from live stream.", pda);
                     break;
                 }
+                event = subscription.recv() => match event {
+                    None => {
+                        tracing::info!("Unsubscribe signal received for PDA {}. Closing stream.", pda);
+                        break;
+                    }
+                    Some(Ok(event)) if event.signature.as_ref().is_some_and(|sig| sent.contains(sig)) => {}
+                    Some(Ok(event)) if skip_until.is_some_and(|slot| event.slot <= slot) => {}
+                    Some(Ok(event)) => {
+                        if tx.send(Ok(gateway::EventStreamItem::from(event))).await.is_err() {
+                            tracing::warn!("Client for PDA {} disconnected during live stream.", pda);
+                            break;
+                        }
+                        heartbeat.reset();
+                    }
+                    Some(Err(RecvError::Lagged(missed))) => {
+                        tracing::warn!("Client for PDA {} lagged behind by {} events. Closing stream.", pda, missed);
+                        let _ = tx
+                            .send(Err(Status::data_loss(format!(
+                                "The stream fell behind and missed {missed} events"
+                            ))))
+                            .await;
+                        break;
+                    }
+                    Some(Err(RecvError::Closed)) => {
+                        tracing::info!("Event manager shut down for PDA {}. Closing stream.", pda);
+                        break;
+                    }
+                }
             }
         }
 
-        active_subscriptions_clone.remove(&pda);
+        drop(subscription);
         tracing::info!("Live event stream for PDA {} has ended.", pda);
     });
 
     Ok(Response::new(ReceiverStream::new(rx)))
 }
 
+/// Drains `history` and returns its last `last_n` events, oldest first, without the
+/// `CaughtUp` marker.
+async fn recent_events(
+    mut history: impl Stream<Item = BridgeEvent> + Unpin,
+    last_n: usize,
+) -> Vec<BridgeEvent> {
+    if last_n == 0 {
+        return Vec::new();
+    }
+    let mut events = VecDeque::with_capacity(last_n);
+    while let Some(event) = history.next().await {
+        if matches!(event.data, BridgeEventData::CaughtUp(_)) {
+            continue;
+        }
+        if events.len() == last_n {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+    events.into()
+}
+
+/// Drains `history` and returns its events after `since_slot`, oldest first, along with the
+/// slot of its `CaughtUp` marker. Returns `None` if `history` ends without the marker.
+async fn events_since(
+    mut history: impl Stream<Item = BridgeEvent> + Unpin,
+    since_slot: u64,
+) -> Option<(Vec<BridgeEvent>, u64)> {
+    let mut events = Vec::new();
+    while let Some(event) = history.next().await {
+        if let BridgeEventData::CaughtUp(caught_up) = &event.data {
+            return Some((events, caught_up.slot));
+        }
+        if event.slot > since_slot {
+            events.push(event);
+        }
+    }
+    None
+}
+
+/// The slot after which a replay from `since_slot` that caught up at `caught_up_slot` has
+/// delivered every event: `max_catchup_depth` keeps the replay from reaching further back.
+fn effective_resume_slot(
+    since_slot: u64,
+    caught_up_slot: u64,
+    max_catchup_depth: Option<u64>,
+) -> u64 {
+    // The replay keeps the transactions from `caught_up_slot - depth` on.
+    match max_catchup_depth.map(|depth| caught_up_slot.saturating_sub(depth)) {
+        Some(oldest) if oldest > since_slot.saturating_add(1) => oldest - 1,
+        _ => since_slot,
+    }
+}
+
 /// A helper to handle the logic for streaming **historical** events.
 ///
 /// This function creates a temporary listener, drains all events from its
 /// catch-up channel, and sends them to the client. The stream closes automatically
-/// once all historical events have been sent.
+/// once all historical events have been sent, which also releases its `permit`.
 async fn handle_history_stream(
     state: &AppState,
     pda: Pubkey,
     mut listener: EventListener,
+    permit: StreamPermit,
 ) -> Result<Response<ReceiverStream<Result<EventStreamItem, Status>>>, Status> {
     let (tx, rx) = mpsc::channel(state.config.connector.channels.listener_event_buffer);
+    permit.bind(&tx);
 
     tokio::spawn(async move {
-        // Drain all catchup events and send them to the client.
+        let _permit = permit;
+        // Drain all catchup events and send them to the client. The last item of a
+        // complete history is the `CaughtUp` marker, after which the channel closes.
         while let Some(event) = listener.next_catchup_event().await {
             if tx
                 .send(Ok(gateway::EventStreamItem::from(event)))
@@ -228,7 +996,7 @@ async fn handle_history_stream(
                 .is_err()
             {
                 tracing::warn!("Client for PDA {} disconnected during history stream.", pda);
-                // No need to remove from active_subscriptions as this is a temporary listener.
+                // History streams are not registered in `subscriptions`, so there is nothing to release.
                 break;
             }
         }
@@ -247,18 +1015,48 @@ impl BridgeGatewayService for GatewayServer {
     /// Subscribes to a stream of **live** events for a specific UserProfile PDA.
     ///
     /// This stream remains open indefinitely, pushing events as they are confirmed on-chain.
-    /// It does NOT include historical events. For history, use `get_user_event_history`.
+    /// It does NOT include historical events, except for those after `since_slot` when a
+    /// client resumes. For history, use `get_user_event_history`.
     async fn stream_user_live_events(
         &self,
         request: Request<ListenRequest>,
     ) -> Result<Response<Self::StreamUserLiveEventsStream>, Status> {
+        let client = client_key(&request);
+        let state = self.state.realm(&request).map_err(Status::from)?;
+        let permit = state.stream_limiter.acquire(client.clone())?;
         let req = request.into_inner();
         tracing::info!("Received StreamUserLiveEvents request for PDA: {}", req.pda);
 
         let pda = parse_pubkey(&req.pda).map_err(Status::from)?;
 
-        let listener = self.state.event_manager.listen_as_user(pda);
-        handle_live_stream(&self.state, pda, listener).await
+        let event_manager = state.event_manager.clone();
+        let upstream = move || {
+            event_manager
+                .listen_as_user_live_only(pda)
+                .map(live_events)
+                .map_err(|e| Status::from(GatewayError::from(e)))
+        };
+        let Some(since_slot) = req.since_slot else {
+            return handle_live_stream(state, pda, client, upstream, permit).await;
+        };
+        let history = HistoryOptions {
+            since_slot: Some(since_slot),
+            ..Default::default()
+        };
+        let listener = state
+            .event_manager
+            .listen_as_user_with_history(pda, history)
+            .map_err(GatewayError::from)?;
+        handle_resumed_stream(
+            state,
+            pda,
+            client,
+            upstream,
+            catchup_events(listener),
+            since_slot,
+            permit,
+        )
+        .await
     }
 
     type StreamAdminLiveEventsStream = ReceiverStream<Result<EventStreamItem, Status>>;
@@ -266,11 +1064,15 @@ impl BridgeGatewayService for GatewayServer {
     /// Subscribes to a stream of **live** events for a specific AdminProfile PDA.
     ///
     /// This stream remains open indefinitely, pushing events as they are confirmed on-chain.
-    /// It does NOT include historical events. For history, use `get_admin_event_history`.
+    /// It does NOT include historical events, except for those after `since_slot` when a
+    /// client resumes. For history, use `get_admin_event_history`.
     async fn stream_admin_live_events(
         &self,
         request: Request<ListenRequest>,
     ) -> Result<Response<Self::StreamAdminLiveEventsStream>, Status> {
+        let client = client_key(&request);
+        let state = self.state.realm(&request).map_err(Status::from)?;
+        let permit = state.stream_limiter.acquire(client.clone())?;
         let req = request.into_inner();
         tracing::info!(
             "Received StreamAdminLiveEvents request for PDA: {}",
@@ -279,13 +1081,40 @@ impl BridgeGatewayService for GatewayServer {
 
         let pda = parse_pubkey(&req.pda).map_err(Status::from)?;
 
-        let listener = self.state.event_manager.listen_as_admin(pda);
-        handle_live_stream(&self.state, pda, listener).await
+        let event_manager = state.event_manager.clone();
+        let upstream = move || {
+            event_manager
+                .listen_as_admin_live_only(pda)
+                .map(live_events)
+                .map_err(|e| Status::from(GatewayError::from(e)))
+        };
+        let Some(since_slot) = req.since_slot else {
+            return handle_live_stream(state, pda, client, upstream, permit).await;
+        };
+        let history = HistoryOptions {
+            since_slot: Some(since_slot),
+            ..Default::default()
+        };
+        let listener = state
+            .event_manager
+            .listen_as_admin_with_history(pda, history)
+            .map_err(GatewayError::from)?;
+        handle_resumed_stream(
+            state,
+            pda,
+            client,
+            upstream,
+            catchup_events(listener),
+            since_slot,
+            permit,
+        )
+        .await
     }
 
     type GetUserEventHistoryStream = ReceiverStream<Result<EventStreamItem, Status>>;
 
-    /// Fetches all historical events for a specific UserProfile PDA.
+    /// Fetches the historical events for a specific UserProfile PDA, oldest-first unless
+    /// `reverse` is set, and limited to the newest `limit` transactions if given.
     ///
     /// This is a "one-shot" stream that closes automatically after the last historical
     /// event has been delivered.
@@ -293,16 +1122,23 @@ impl BridgeGatewayService for GatewayServer {
         &self,
         request: Request<ListenRequest>,
     ) -> Result<Response<Self::GetUserEventHistoryStream>, Status> {
+        let state = self.state.realm(&request).map_err(Status::from)?;
+        let permit = state.stream_limiter.acquire(client_key(&request))?;
         let req = request.into_inner();
         tracing::info!("Received GetUserEventHistory request for PDA: {}", req.pda);
         let pda = parse_pubkey(&req.pda).map_err(Status::from)?;
-        let listener = self.state.event_manager.listen_as_user(pda);
-        handle_history_stream(&self.state, pda, listener).await
+        let history = history_options(&req).map_err(Status::from)?;
+        let listener = state
+            .event_manager
+            .listen_as_user_with_history(pda, history)
+            .map_err(GatewayError::from)?;
+        handle_history_stream(state, pda, listener, permit).await
     }
 
     type GetAdminEventHistoryStream = ReceiverStream<Result<EventStreamItem, Status>>;
 
-    /// Fetches all historical events for a specific AdminProfile PDA.
+    /// Fetches the historical events for a specific AdminProfile PDA, oldest-first unless
+    /// `reverse` is set, and limited to the newest `limit` transactions if given.
     ///
     /// This is a "one-shot" stream that closes automatically after the last historical
     /// event has been delivered.
@@ -310,11 +1146,76 @@ impl BridgeGatewayService for GatewayServer {
         &self,
         request: Request<ListenRequest>,
     ) -> Result<Response<Self::GetAdminEventHistoryStream>, Status> {
+        let state = self.state.realm(&request).map_err(Status::from)?;
+        let permit = state.stream_limiter.acquire(client_key(&request))?;
         let req = request.into_inner();
         tracing::info!("Received GetAdminEventHistory request for PDA: {}", req.pda);
         let pda = parse_pubkey(&req.pda).map_err(Status::from)?;
-        let listener = self.state.event_manager.listen_as_admin(pda);
-        handle_history_stream(&self.state, pda, listener).await
+        let history = history_options(&req).map_err(Status::from)?;
+        let listener = state
+            .event_manager
+            .listen_as_admin_with_history(pda, history)
+            .map_err(GatewayError::from)?;
+        handle_history_stream(state, pda, listener, permit).await
+    }
+
+    type TailStream = ReceiverStream<Result<EventStreamItem, Status>>;
+
+    /// Subscribes to the **live** events of a PDA, preceded by its `last_n` most recent
+    /// historical events.
+    ///
+    /// The lookback is replayed like a history stream, so it is served from the event cache
+    /// when `cache-events` is enabled. With `last_n = 0`, no history is fetched at all.
+    async fn tail(
+        &self,
+        request: Request<TailRequest>,
+    ) -> Result<Response<Self::TailStream>, Status> {
+        let client = client_key(&request);
+        let state = self.state.realm(&request).map_err(Status::from)?;
+        let req = request.into_inner();
+        tracing::info!(
+            "Received Tail request for PDA: {} (last_n = {})",
+            req.pda,
+            req.last_n
+        );
+
+        let pda = parse_pubkey(&req.pda).map_err(Status::from)?;
+        if req.last_n > MAX_TAIL_LOOKBACK {
+            return Err(Status::invalid_argument(format!(
+                "last_n {} exceeds the maximum of {MAX_TAIL_LOOKBACK}",
+                req.last_n
+            )));
+        }
+        let permit = state.stream_limiter.acquire(client.clone())?;
+
+        let event_manager = state.event_manager.clone();
+        let upstream = move || {
+            event_manager
+                .listen_as_user_live_only(pda)
+                .map(live_events)
+                .map_err(|e| Status::from(GatewayError::from(e)))
+        };
+        // The catch-up listener is only created if the lookback is wanted.
+        let history = match req.last_n {
+            0 => None,
+            _ => Some(catchup_events(
+                state
+                    .event_manager
+                    .listen_as_user(pda)
+                    .map_err(GatewayError::from)?,
+            )),
+        };
+        let history = stream::iter(history).flatten();
+        handle_tail_stream(
+            state,
+            pda,
+            client,
+            upstream,
+            history,
+            req.last_n as usize,
+            permit,
+        )
+        .await
     }
 
     /// Manually closes an active **live** event stream subscription.
@@ -325,13 +1226,14 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<UnsubscribeRequest>,
     ) -> Result<Response<()>, Status> {
         let result: Result<Response<()>, GatewayError> = (async {
+            let client = client_key(&request);
+            let state = self.state.realm(&request)?;
             let req = request.into_inner();
             let pda_to_stop = parse_pubkey(&req.pda)?;
             tracing::info!("Received Unsubscribe request for PDA: {}", pda_to_stop);
 
-            // Find the subscription and send a stop signal by dropping the sender.
-            if let Some((_, stop_tx)) = self.state.active_subscriptions.remove(&pda_to_stop) {
-                let _ = stop_tx.send(());
+            // Find the subscription and send it a stop signal.
+            if state.subscriptions.unsubscribe(&pda_to_stop, &client) {
                 tracing::info!("Successfully signaled termination for PDA: {}", pda_to_stop);
             } else {
                 tracing::warn!(
@@ -347,6 +1249,57 @@ impl BridgeGatewayService for GatewayServer {
         result.map_err(Status::from)
     }
 
+    /// Lists the PDAs with live streams in the requested realm.
+    async fn list_subscriptions(
+        &self,
+        request: Request<()>,
+    ) -> Result<Response<ListSubscriptionsResponse>, Status> {
+        let result: Result<Response<ListSubscriptionsResponse>, GatewayError> = (async {
+            require_admin(&request, &self.state.config.gateway.auth.admin_principals)?;
+            let state = self.state.realm(&request)?;
+
+            let subscriptions = state
+                .subscriptions
+                .list()
+                .into_iter()
+                .map(|subscription| SubscriptionInfo {
+                    pda: subscription.pda.to_string(),
+                    client_streams: subscription.client_streams as u32,
+                    age_seconds: subscription.age.as_secs(),
+                })
+                .collect();
+
+            Ok(Response::new(ListSubscriptionsResponse { subscriptions }))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    /// Stops the live streams of all clients in the requested realm.
+    async fn unsubscribe_all(
+        &self,
+        request: Request<()>,
+    ) -> Result<Response<UnsubscribeAllResponse>, Status> {
+        let result: Result<Response<UnsubscribeAllResponse>, GatewayError> = (async {
+            require_admin(&request, &self.state.config.gateway.auth.admin_principals)?;
+            let state = self.state.realm(&request)?;
+
+            let stopped_streams = state.subscriptions.unsubscribe_all();
+            tracing::info!(
+                "Signaled termination of all {} live streams",
+                stopped_streams
+            );
+
+            Ok(Response::new(UnsubscribeAllResponse {
+                stopped_streams: stopped_streams as u32,
+            }))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
     // --- Transaction Preparation ---
 
     /// Prepares an unsigned `user_request_unban` transaction.
@@ -360,18 +1313,21 @@ impl BridgeGatewayService for GatewayServer {
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
             let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
             let unsigned_tx_message =
                 builder.prepare_user_request_unban(authority, admin_profile_pda);
 
             tracing::debug!("Prepared user_request_unban tx for authority {}", authority);
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
@@ -389,19 +1345,23 @@ impl BridgeGatewayService for GatewayServer {
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            let admin_index = parse_admin_index(req.admin_index)?;
             let target_user_profile_pda = parse_pubkey(&req.target_user_profile_pda)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
             let unsigned_tx_message =
-                builder.prepare_admin_ban_user(authority, target_user_profile_pda);
+                builder.prepare_admin_ban_user(authority, admin_index, target_user_profile_pda);
 
             tracing::debug!("Prepared admin_ban_user tx for authority {}", authority);
 
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
@@ -419,19 +1379,23 @@ impl BridgeGatewayService for GatewayServer {
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            let admin_index = parse_admin_index(req.admin_index)?;
             let target_user_profile_pda = parse_pubkey(&req.target_user_profile_pda)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
             let unsigned_tx_message =
-                builder.prepare_admin_unban_user(authority, target_user_profile_pda);
+                builder.prepare_admin_unban_user(authority, admin_index, target_user_profile_pda);
 
             tracing::debug!("Prepared admin_unban_user tx for authority {}", authority);
 
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
@@ -449,22 +1413,29 @@ impl BridgeGatewayService for GatewayServer {
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            let admin_index = parse_admin_index(req.admin_index)?;
             let communication_pubkey = parse_pubkey(&req.communication_pubkey)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
-            let unsigned_tx_message =
-                builder.prepare_admin_register_profile(authority, communication_pubkey);
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
+            let unsigned_tx_message = builder.prepare_admin_register_profile(
+                authority,
+                admin_index,
+                communication_pubkey,
+            );
 
             tracing::debug!(
                 "Prepared admin_register_profile tx for authority {}",
                 authority
             );
 
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
@@ -482,8 +1453,11 @@ impl BridgeGatewayService for GatewayServer {
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            let admin_index = parse_admin_index(req.admin_index)?;
             let new_oracle_authority = req
                 .new_oracle_authority
                 .map(|s| parse_pubkey(&s))
@@ -492,20 +1466,34 @@ impl BridgeGatewayService for GatewayServer {
                 .new_communication_pubkey
                 .map(|s| parse_pubkey(&s))
                 .transpose()?;
-
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let new_max_payload_size =
+                parse_optional_u16("new_max_payload_size", req.new_max_payload_size)?;
+            let new_unban_fee_multiplier_bps = parse_optional_u16(
+                "new_unban_fee_multiplier_bps",
+                req.new_unban_fee_multiplier_bps,
+            )?;
+            let new_max_unban_fee_multiple =
+                parse_optional_u16("new_max_unban_fee_multiple", req.new_max_unban_fee_multiple)?;
+
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
             let unsigned_tx_message = builder.prepare_admin_set_config(
                 authority,
+                admin_index,
                 new_oracle_authority,
                 req.new_timestamp_validity,
                 new_communication_pubkey,
                 req.new_unban_fee,
-            );
+                new_max_payload_size,
+                new_unban_fee_multiplier_bps,
+                new_max_unban_fee_multiple,
+                req.new_require_distinct_oracle,
+            )?;
             tracing::debug!("Prepared admin_set_config tx for authority {}", authority);
 
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
@@ -523,92 +1511,152 @@ impl BridgeGatewayService for GatewayServer {
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            let admin_index = parse_admin_index(req.admin_index)?;
             let destination = parse_pubkey(&req.destination)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
             let unsigned_tx_message =
-                builder.prepare_admin_withdraw(authority, req.amount, destination);
+                builder.prepare_admin_withdraw(authority, admin_index, req.amount, destination);
 
             tracing::debug!("Prepared admin_withdraw tx for authority {}", authority);
 
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
         result.map_err(Status::from)
     }
 
-    /// Prepares an unsigned `admin_close_profile` transaction.
-    async fn prepare_admin_close_profile(
+    /// Prepares an unsigned `admin_withdraw_all` transaction.
+    async fn prepare_admin_withdraw_all(
         &self,
-        request: Request<PrepareAdminCloseProfileRequest>,
+        request: Request<PrepareAdminWithdrawAllRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
             tracing::info!(
-                "Received PrepareAdminCloseProfile request: {:?}",
+                "Received PrepareAdminWithdrawAll request: {:?}",
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            let admin_index = parse_admin_index(req.admin_index)?;
+            let destination = parse_pubkey(&req.destination)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
-            let unsigned_tx_message = builder.prepare_admin_close_profile(authority);
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
+            let unsigned_tx_message =
+                builder.prepare_admin_withdraw_all(authority, admin_index, destination);
 
-            tracing::debug!(
-                "Prepared admin_close_profile tx for authority {}",
-                authority
-            );
+            tracing::debug!("Prepared admin_withdraw_all tx for authority {}", authority);
 
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
         result.map_err(Status::from)
     }
 
-    /// Prepares an unsigned `admin_dispatch_command` transaction.
-    async fn prepare_admin_dispatch_command(
+    /// Prepares an unsigned `admin_close_profile` transaction.
+    async fn prepare_admin_close_profile(
         &self,
-        request: Request<PrepareAdminDispatchCommandRequest>,
+        request: Request<PrepareAdminCloseProfileRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
             tracing::info!(
-                "Received PrepareAdminDispatchCommand request: {:?}",
+                "Received PrepareAdminCloseProfile request: {:?}",
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
-            let target_user_profile_pda = parse_pubkey(&req.target_user_profile_pda)?;
+            let admin_index = parse_admin_index(req.admin_index)?;
+
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
+            let unsigned_tx_message = builder.prepare_admin_close_profile(authority, admin_index);
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
-            let unsigned_tx_message = builder.prepare_admin_dispatch_command(
-                authority,
-                target_user_profile_pda,
-                req.command_id,
-                req.payload,
-            );
             tracing::debug!(
-                "Prepared admin_dispatch_command tx for authority {}",
+                "Prepared admin_close_profile tx for authority {}",
                 authority
             );
 
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
         result.map_err(Status::from)
     }
 
+    /// Prepares an unsigned `admin_dispatch_command` transaction.
+    async fn prepare_admin_dispatch_command(
+        &self,
+        request: Request<PrepareAdminDispatchCommandRequest>,
+    ) -> Result<Response<UnsignedTransactionResponse>, Status> {
+        let deadline = request_deadline(&request);
+        let result: Result<Response<UnsignedTransactionResponse>, GatewayError> =
+            within_deadline(deadline, async {
+                tracing::info!(
+                    "Received PrepareAdminDispatchCommand request: {:?}",
+                    request.get_ref()
+                );
+
+                let state = self.state.realm(&request)?;
+                let encoding = message_encoding(&request)?;
+                let req = request.into_inner();
+                let authority = parse_pubkey(&req.authority_pubkey)?;
+                let admin_index = parse_admin_index(req.admin_index)?;
+                let target_user_profile_pda = parse_pubkey(&req.target_user_profile_pda)?;
+                let (admin_profile_pda, _) =
+                    find_admin_profile_pda_for(&state.program_id(), &authority, admin_index);
+                state
+                    .payload_limits
+                    .check(
+                        &state.transaction_builder(),
+                        &admin_profile_pda,
+                        req.payload.len(),
+                    )
+                    .await?;
+
+                let builder = transaction_builder(state, req.fee_payer.as_deref())?;
+                let unsigned_tx_message = builder.prepare_admin_dispatch_command(
+                    authority,
+                    admin_index,
+                    target_user_profile_pda,
+                    req.command_id,
+                    req.payload,
+                    parse_command_mode(req.mode)?,
+                )?;
+                tracing::debug!(
+                    "Prepared admin_dispatch_command tx for authority {}",
+                    authority
+                );
+
+                Ok(Response::new(unsigned_transaction(
+                    unsigned_tx_message,
+                    encoding,
+                )))
+            })
+            .await;
+
+        result.map_err(Status::from)
+    }
+
     /// Prepares an unsigned `user_create_profile` transaction.
     async fn prepare_user_create_profile(
         &self,
@@ -620,12 +1668,14 @@ impl BridgeGatewayService for GatewayServer {
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
             let target_admin_pda = parse_pubkey(&req.target_admin_pda)?;
             let communication_pubkey = parse_pubkey(&req.communication_pubkey)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
             let unsigned_tx_message = builder.prepare_user_create_profile(
                 authority,
                 target_admin_pda,
@@ -636,9 +1686,10 @@ impl BridgeGatewayService for GatewayServer {
                 "Prepared user_create_profile tx for authority {}",
                 authority
             );
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
@@ -656,12 +1707,14 @@ impl BridgeGatewayService for GatewayServer {
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
             let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
             let new_key = parse_pubkey(&req.new_key)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
             let unsigned_tx_message =
                 builder.prepare_user_update_comm_key(authority, admin_profile_pda, new_key);
 
@@ -669,9 +1722,86 @@ impl BridgeGatewayService for GatewayServer {
                 "Prepared user_update_comm_key tx for authority {}",
                 authority
             );
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    /// Prepares an unsigned `user_set_low_balance_threshold` transaction.
+    async fn prepare_user_set_low_balance_threshold(
+        &self,
+        request: Request<PrepareUserSetLowBalanceThresholdRequest>,
+    ) -> Result<Response<UnsignedTransactionResponse>, Status> {
+        let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
+            tracing::info!(
+                "Received PrepareUserSetLowBalanceThreshold request: {:?}",
+                request.get_ref()
+            );
+
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
+            let req = request.into_inner();
+            let authority = parse_pubkey(&req.authority_pubkey)?;
+            let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
+
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
+            let unsigned_tx_message = builder.prepare_user_set_low_balance_threshold(
+                authority,
+                admin_profile_pda,
+                req.threshold,
+            );
+
+            tracing::debug!(
+                "Prepared user_set_low_balance_threshold tx for authority {}",
+                authority
+            );
+            Ok(Response::new(unsigned_transaction(
+                unsigned_tx_message,
+                encoding,
+            )))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    /// Prepares an unsigned `user_set_spending_cap` transaction.
+    async fn prepare_user_set_spending_cap(
+        &self,
+        request: Request<PrepareUserSetSpendingCapRequest>,
+    ) -> Result<Response<UnsignedTransactionResponse>, Status> {
+        let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
+            tracing::info!(
+                "Received PrepareUserSetSpendingCap request: {:?}",
+                request.get_ref()
+            );
+
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
+            let req = request.into_inner();
+            let authority = parse_pubkey(&req.authority_pubkey)?;
+            let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
+
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
+            let unsigned_tx_message = builder.prepare_user_set_spending_cap(
+                authority,
+                admin_profile_pda,
+                req.max_price_per_command,
+            );
+
+            tracing::debug!(
+                "Prepared user_set_spending_cap tx for authority {}",
+                authority
+            );
+            Ok(Response::new(unsigned_transaction(
+                unsigned_tx_message,
+                encoding,
+            )))
         })
         .await;
 
@@ -689,18 +1819,21 @@ impl BridgeGatewayService for GatewayServer {
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
             let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
             let unsigned_tx_message =
                 builder.prepare_user_deposit(authority, admin_profile_pda, req.amount);
 
             tracing::debug!("Prepared user_deposit tx for authority {}", authority);
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
@@ -718,12 +1851,14 @@ impl BridgeGatewayService for GatewayServer {
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
             let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
             let destination = parse_pubkey(&req.destination)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
             let unsigned_tx_message = builder.prepare_user_withdraw(
                 authority,
                 admin_profile_pda,
@@ -732,9 +1867,10 @@ impl BridgeGatewayService for GatewayServer {
             );
 
             tracing::debug!("Prepared user_withdraw tx for authority {}", authority);
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
@@ -752,18 +1888,21 @@ impl BridgeGatewayService for GatewayServer {
                 request.get_ref()
             );
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
             let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
             let unsigned_tx_message =
                 builder.prepare_user_close_profile(authority, admin_profile_pda);
 
             tracing::debug!("Prepared user_close_profile tx for authority {}", authority);
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
@@ -775,44 +1914,59 @@ impl BridgeGatewayService for GatewayServer {
         &self,
         request: Request<PrepareUserDispatchCommandRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
-        let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareUserDispatchCommand request: {:?}",
-                request.get_ref()
-            );
-
-            let req = request.into_inner();
-            let authority = parse_pubkey(&req.authority_pubkey)?;
-            let target_admin_pda = parse_pubkey(&req.target_admin_pda)?;
-            let oracle_pubkey = parse_pubkey(&req.oracle_pubkey)?;
-
-            // Convert the signature from Vec<u8> to [u8; 64]
-            let oracle_signature: [u8; 64] = req.oracle_signature.try_into().map_err(|_| {
-                GatewayError::InvalidArgument("Oracle signature must be 64 bytes".to_string())
-            })?;
+        let deadline = request_deadline(&request);
+        let result: Result<Response<UnsignedTransactionResponse>, GatewayError> =
+            within_deadline(deadline, async {
+                tracing::info!(
+                    "Received PrepareUserDispatchCommand request: {:?}",
+                    request.get_ref()
+                );
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
-            let unsigned_tx_message = builder.prepare_user_dispatch_command(
-                authority,
-                target_admin_pda,
-                UserDispatchCommandArgs {
-                    command_id: req.command_id as u16,
-                    price: req.price,
-                    timestamp: req.timestamp,
-                    payload: req.payload,
-                    oracle_pubkey,
-                    oracle_signature,
-                },
-            );
-            tracing::debug!(
-                "Prepared user_dispatch_command tx for authority {}",
-                authority
-            );
-            Ok(Response::new(UnsignedTransactionResponse {
-                unsigned_tx_message,
-            }))
-        })
-        .await;
+                let state = self.state.realm(&request)?;
+                let encoding = message_encoding(&request)?;
+                let req = request.into_inner();
+                let authority = parse_pubkey(&req.authority_pubkey)?;
+                let target_admin_pda = parse_pubkey(&req.target_admin_pda)?;
+                let oracle_pubkey = parse_pubkey(&req.oracle_pubkey)?;
+
+                let oracle_signature = oracle_signature_from_slice(&req.oracle_signature)?;
+                state
+                    .payload_limits
+                    .check(
+                        &state.transaction_builder(),
+                        &target_admin_pda,
+                        req.payload.len(),
+                    )
+                    .await?;
+
+                // The oracle signs the price for the profile of this authority and admin.
+                let (user_profile_pda, _) =
+                    find_user_profile_pda_for(&state.program_id(), &authority, &target_admin_pda);
+                let builder = transaction_builder(state, req.fee_payer.as_deref())?;
+                let unsigned_tx_message = builder.prepare_user_dispatch_command(
+                    authority,
+                    target_admin_pda,
+                    UserDispatchCommandArgs {
+                        command_id: req.command_id as u16,
+                        price: req.price,
+                        timestamp: req.timestamp,
+                        user_profile_pda,
+                        payload: req.payload,
+                        oracle_pubkey,
+                        oracle_signature,
+                        mode: parse_command_mode(req.mode)?,
+                    },
+                )?;
+                tracing::debug!(
+                    "Prepared user_dispatch_command tx for authority {}",
+                    authority
+                );
+                Ok(Response::new(unsigned_transaction(
+                    unsigned_tx_message,
+                    encoding,
+                )))
+            })
+            .await;
 
         result.map_err(Status::from)
     }
@@ -825,65 +1979,190 @@ impl BridgeGatewayService for GatewayServer {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
             tracing::info!("Received PrepareLogAction request: {:?}", request.get_ref());
 
+            let state = self.state.realm(&request)?;
+            let encoding = message_encoding(&request)?;
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
             let user_profile_pda = parse_pubkey(&req.user_profile_pda)?;
             let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
+            let category = u8::try_from(req.category).map_err(|_| {
+                GatewayError::InvalidArgument(format!(
+                    "category {} does not fit in a u8",
+                    req.category
+                ))
+            })?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = transaction_builder(state, req.fee_payer.as_deref())?;
             let unsigned_tx_message = builder.prepare_log_action(
                 authority,
                 user_profile_pda,
                 admin_profile_pda,
                 req.session_id,
                 req.action_code as u16,
+                category,
+                req.metadata,
             );
             tracing::debug!("Prepared log_action tx for authority {}", authority);
-            Ok(Response::new(UnsignedTransactionResponse {
+            Ok(Response::new(unsigned_transaction(
                 unsigned_tx_message,
-            }))
+                encoding,
+            )))
         })
         .await;
 
         result.map_err(Status::from)
     }
 
+    /// Prepares the unsigned transactions of several `Prepare*` requests, in order.
+    ///
+    /// Every step is prepared by the matching `Prepare*` RPC with the metadata of the batch,
+    /// so it is served by the same realm and encoding. Nothing is read from the chain to
+    /// build a step, so a step may use the PDAs created by an earlier one, such as the
+    /// `AdminProfile` registered in the same batch. The first failing step fails the batch,
+    /// with its index in the message.
+    async fn prepare_batch(
+        &self,
+        request: Request<PrepareBatchRequest>,
+    ) -> Result<Response<PrepareBatchResponse>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        tracing::info!(
+            "Received PrepareBatch request with {} steps",
+            req.requests.len()
+        );
+        if req.requests.is_empty() || req.requests.len() > MAX_PREPARE_BATCH_STEPS {
+            return Err(Status::invalid_argument(format!(
+                "A batch must have between 1 and {MAX_PREPARE_BATCH_STEPS} steps, got {}",
+                req.requests.len()
+            )));
+        }
+
+        let mut messages = Vec::with_capacity(req.requests.len());
+        for (index, item) in req.requests.into_iter().enumerate() {
+            let message = self
+                .prepare_batch_item(metadata.clone(), item)
+                .await
+                .map_err(|status| {
+                    Status::new(status.code(), format!("Step {index}: {}", status.message()))
+                })?;
+            messages.push(message);
+        }
+        Ok(Response::new(PrepareBatchResponse { messages }))
+    }
+
     /// Submits a signed transaction to the network.
     async fn submit_transaction(
         &self,
         request: Request<SubmitTransactionRequest>,
     ) -> Result<Response<TransactionResponse>, Status> {
-        let result: Result<Response<TransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received SubmitTransaction request with {} bytes",
-                request.get_ref().signed_tx.len()
-            );
+        let deadline = request_deadline(&request);
+        let result: Result<Response<TransactionResponse>, GatewayError> =
+            within_deadline(deadline, async {
+                tracing::info!(
+                    "Received SubmitTransaction request with {} bytes",
+                    request.get_ref().signed_tx.len()
+                );
 
-            let req = request.into_inner();
-            let tx_bytes = req.signed_tx;
+                let state = self.state.realm(&request)?;
+                let req = request.into_inner();
+                let tx_bytes = req.signed_tx;
+
+                let (transaction, _len): (Transaction, usize) =
+                    bincode::serde::borrow_decode_from_slice(
+                        tx_bytes.as_slice(),
+                        bincode::config::standard(),
+                    )
+                    .map_err(GatewayError::from)?;
+
+                tracing::debug!("Deserialized transaction: {:?}", transaction);
+
+                let builder = state.transaction_builder();
+                let policy = state.config.gateway.submit.retry_policy();
+                let signature = builder
+                    .submit_transaction_with_retry(&transaction, &policy)
+                    .await
+                    .map_err(GatewayError::from)?;
+                tracing::info!("Submitted transaction, signature: {}", signature);
+
+                Ok(Response::new(TransactionResponse {
+                    signature: signature.to_string(),
+                }))
+            })
+            .await;
+
+        result.map_err(Status::from)
+    }
 
-            let (transaction, _len): (Transaction, usize) =
-                bincode::serde::borrow_decode_from_slice(
-                    tx_bytes.as_slice(),
+    /// Simulates a prepared, unsigned transaction and decodes why it would fail.
+    async fn simulate_prepared_transaction(
+        &self,
+        request: Request<SimulatePreparedTransactionRequest>,
+    ) -> Result<Response<SimulationResponse>, Status> {
+        let deadline = request_deadline(&request);
+        let result: Result<Response<SimulationResponse>, GatewayError> =
+            within_deadline(deadline, async {
+                let state = self.state.realm(&request)?;
+                let req = request.into_inner();
+                tracing::info!(
+                    "Received SimulatePreparedTransaction request with {} bytes",
+                    req.unsigned_tx_message.len()
+                );
+
+                let (message, _len): (Message, usize) = bincode::serde::borrow_decode_from_slice(
+                    req.unsigned_tx_message.as_slice(),
                     bincode::config::standard(),
                 )
                 .map_err(GatewayError::from)?;
 
-            tracing::debug!("Deserialized transaction: {:?}", transaction);
-
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
-            let signature = builder
-                .submit_transaction(&transaction)
-                .await
-                .map_err(|e| GatewayError::Connector(Box::new(e)))?;
-            tracing::info!("Submitted transaction, signature: {}", signature);
+                let builder = state.transaction_builder();
+                let report = builder
+                    .simulate_message(message)
+                    .await
+                    .map_err(|e| GatewayError::Connector(Box::new(e)))?;
+                if let Some(err) = &report.error {
+                    tracing::info!("Simulated transaction would fail: {}", err);
+                }
 
-            Ok(Response::new(TransactionResponse {
-                signature: signature.to_string(),
-            }))
-        })
-        .await;
+                Ok(Response::new(report.into()))
+            })
+            .await;
+        result.map_err(Status::from)
+    }
 
+    /// Fetches the logs of a confirmed transaction and decodes its W3B2 events.
+    async fn get_transaction_logs(
+        &self,
+        request: Request<GetTransactionLogsRequest>,
+    ) -> Result<Response<TransactionLogsResponse>, Status> {
+        let deadline = request_deadline(&request);
+        let result: Result<Response<TransactionLogsResponse>, GatewayError> =
+            within_deadline(deadline, async {
+                let state = self.state.realm(&request)?;
+                let req = request.into_inner();
+                tracing::info!("Received GetTransactionLogs request for {}", req.signature);
+                let signature = Signature::from_str(&req.signature).map_err(|e| {
+                    GatewayError::InvalidArgument(format!("Invalid transaction signature: {e}"))
+                })?;
+
+                let config = RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(CommitmentConfig {
+                        commitment: state.config.connector.solana.commitment,
+                    }),
+                    max_supported_transaction_version: Some(0),
+                };
+                let tx = state
+                    .rpc_client
+                    .get_transaction_with_config(&signature, config)
+                    .await
+                    .map_err(|e| GatewayError::Connector(Box::new(e)))?;
+
+                Ok(Response::new(conversions::transaction_logs_response(
+                    &state.program_id(),
+                    &req.signature,
+                    &tx,
+                )))
+            })
+            .await;
         result.map_err(Status::from)
     }
 
@@ -892,23 +2171,269 @@ impl BridgeGatewayService for GatewayServer {
     /// Fetches the latest blockhash from the Solana network.
     async fn get_latest_blockhash(
         &self,
-        _request: Request<()>,
+        request: Request<()>,
     ) -> Result<Response<BlockhashResponse>, Status> {
-        let result: Result<Response<BlockhashResponse>, GatewayError> = (async {
-            let blockhash = self
-                .state
-                .rpc_client
-                .get_latest_blockhash()
-                .await
-                .map_err(|e| GatewayError::Connector(Box::new(e)))?;
+        let deadline = request_deadline(&request);
+        let result: Result<Response<BlockhashResponse>, GatewayError> =
+            within_deadline(deadline, async {
+                let rpc_client = &self.state.realm(&request)?.rpc_client;
+                let (blockhash, last_valid_block_height) = rpc_client
+                    .get_latest_blockhash_with_commitment(rpc_client.commitment())
+                    .await
+                    .map_err(|e| GatewayError::Connector(Box::new(e)))?;
+                let block_height = rpc_client
+                    .get_block_height()
+                    .await
+                    .map_err(|e| GatewayError::Connector(Box::new(e)))?;
+
+                tracing::info!("Received GetLatestBlockhash request (hash={})", blockhash);
+
+                Ok(Response::new(BlockhashResponse {
+                    blockhash: blockhash.to_bytes().to_vec(),
+                    last_valid_block_height,
+                    block_height,
+                }))
+            })
+            .await;
+        result.map_err(Status::from)
+    }
+
+    /// Returns the program id, cluster and versions this gateway is bound to.
+    async fn get_gateway_info(
+        &self,
+        request: Request<()>,
+    ) -> Result<Response<GatewayInfoResponse>, Status> {
+        tracing::debug!("Received GetGatewayInfo request");
+        let state = self.state.realm(&request).map_err(Status::from)?;
+        Ok(Response::new(GatewayInfoResponse {
+            program_id: state.program_id().to_string(),
+            rpc_cluster: sanitize_rpc_url(&state.config.connector.solana.rpc_url),
+            gateway_version: env!("CARGO_PKG_VERSION").to_string(),
+            connector_version: w3b2_solana_connector::VERSION.to_string(),
+            event_schema_version: EVENT_SCHEMA_VERSION,
+        }))
+    }
+
+    /// Reports the program's limits and error table, read from the program crate.
+    async fn get_protocol_limits(
+        &self,
+        request: Request<()>,
+    ) -> Result<Response<ProtocolLimitsResponse>, Status> {
+        tracing::debug!("Received GetProtocolLimits request");
+        self.state.realm(&request).map_err(Status::from)?;
+        let errors = ALL_ERRORS
+            .iter()
+            .map(|error| {
+                let info = ProgramErrorInfo {
+                    name: error.name(),
+                    message: error.to_string(),
+                };
+                (u32::from(*error), info)
+            })
+            .collect();
+        Ok(Response::new(ProtocolLimitsResponse {
+            max_payload_size: MAX_PAYLOAD_SIZE as u32,
+            max_metadata_size: MAX_METADATA_SIZE as u32,
+            max_timestamp_age_seconds: MAX_TIMESTAMP_AGE_SECONDS,
+            max_allowance_commands: MAX_ALLOWANCE_COMMANDS as u32,
+            errors,
+            min_timestamp_validity_seconds: MIN_TIMESTAMP_VALIDITY_SECONDS,
+            max_timestamp_validity_seconds: MAX_TIMESTAMP_VALIDITY_SECONDS,
+            max_timestamp_future_skew_seconds: MAX_TIMESTAMP_FUTURE_SKEW_SECONDS,
+        }))
+    }
+
+    /// Reports the lag of the realm's synchronizer.
+    async fn get_sync_status(
+        &self,
+        request: Request<()>,
+    ) -> Result<Response<SyncStatusResponse>, Status> {
+        tracing::debug!("Received GetSyncStatus request");
+        let state = self.state.realm(&request).map_err(Status::from)?;
+        let status = *state.event_manager.sync_status().borrow();
+        let health = *state.event_manager.health().borrow();
+        let last_progress_ts = health
+            .last_progress
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs() as i64);
+        Ok(Response::new(SyncStatusResponse {
+            chain_slot: status.chain_slot,
+            processed_slot: status.processed_slot,
+            lag_slots: status.lag_slots,
+            stalled: status.stalled,
+            last_progress_ts,
+            healthy: health.healthy,
+        }))
+    }
+
+    /// Derives the `AdminProfile` PDA of an admin wallet.
+    async fn derive_admin_pda(
+        &self,
+        request: Request<DeriveAdminPdaRequest>,
+    ) -> Result<Response<DerivedPdaResponse>, Status> {
+        let result: Result<Response<DerivedPdaResponse>, GatewayError> = (async {
+            let state = self.state.realm(&request)?;
+            let req = request.into_inner();
+            let authority = parse_pubkey(&req.authority_pubkey)?;
 
-            tracing::info!("Received GetLatestBlockhash request (hash={})", blockhash);
+            let index = parse_admin_index(req.index)?;
 
-            Ok(Response::new(BlockhashResponse {
-                blockhash: blockhash.to_bytes().to_vec(),
+            let (pda, bump) = find_admin_profile_pda_for(&state.program_id(), &authority, index);
+            tracing::debug!("Derived admin PDA {} for authority {}", pda, authority);
+            Ok(Response::new(DerivedPdaResponse {
+                pda: pda.to_string(),
+                bump: bump.into(),
             }))
         })
         .await;
         result.map_err(Status::from)
     }
+
+    /// Derives the `UserProfile` PDA of a user wallet for a given admin profile.
+    async fn derive_user_pda(
+        &self,
+        request: Request<DeriveUserPdaRequest>,
+    ) -> Result<Response<DerivedPdaResponse>, Status> {
+        let result: Result<Response<DerivedPdaResponse>, GatewayError> = (async {
+            let state = self.state.realm(&request)?;
+            let req = request.into_inner();
+            let authority = parse_pubkey(&req.authority_pubkey)?;
+            let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
+
+            let (pda, bump) =
+                find_user_profile_pda_for(&state.program_id(), &authority, &admin_profile_pda);
+            tracing::debug!("Derived user PDA {} for authority {}", pda, authority);
+            Ok(Response::new(DerivedPdaResponse {
+                pda: pda.to_string(),
+                bump: bump.into(),
+            }))
+        })
+        .await;
+        result.map_err(Status::from)
+    }
+
+    /// Returns the per-type event counts of a PDA from the gateway's storage.
+    async fn get_event_counts(
+        &self,
+        request: Request<GetEventCountsRequest>,
+    ) -> Result<Response<EventCountsResponse>, Status> {
+        let result: Result<Response<EventCountsResponse>, GatewayError> = (async {
+            let state = self.state.realm(&request)?;
+            let pda = parse_pubkey(&request.into_inner().pda)?;
+            tracing::debug!("Received GetEventCounts request for PDA {}", pda);
+
+            let counts = state.storage.get_event_counts(&pda).await?;
+            Ok(Response::new(EventCountsResponse {
+                counts: counts
+                    .into_iter()
+                    .map(|(kind, count)| (kind.to_string(), count))
+                    .collect(),
+            }))
+        })
+        .await;
+        result.map_err(Status::from)
+    }
+
+    /// Replays the history of an admin profile and folds it into its pending unban requests.
+    ///
+    /// The replay is served from the event cache when `cache-events` is enabled, and counts
+    /// against the client's stream limit while it runs, or until the client's deadline.
+    async fn list_pending_unban_requests(
+        &self,
+        request: Request<ListPendingUnbanRequestsRequest>,
+    ) -> Result<Response<PendingUnbanRequestsResponse>, Status> {
+        let state = self.state.realm(&request).map_err(Status::from)?;
+        let _permit = state.stream_limiter.acquire(client_key(&request))?;
+        let deadline = request_deadline(&request);
+        let admin_pda = parse_pubkey(&request.into_inner().admin_pda).map_err(Status::from)?;
+        tracing::debug!(
+            "Received ListPendingUnbanRequests request for admin PDA {}",
+            admin_pda
+        );
+
+        let mut listener = state
+            .event_manager
+            .listen_as_admin(admin_pda)
+            .map_err(GatewayError::from)?;
+        let mut history = Vec::new();
+        let replay = async {
+            while let Some(event) = listener.next_catchup_event().await {
+                if matches!(event.data, BridgeEventData::CaughtUp(_)) {
+                    return Ok(true);
+                }
+                history.push(event.data);
+            }
+            Ok(false)
+        };
+        let caught_up = within_deadline(deadline, replay)
+            .await
+            .map_err(Status::from)?;
+        if !caught_up {
+            return Err(Status::unavailable(format!(
+                "The event history of {admin_pda} could not be replayed"
+            )));
+        }
+
+        Ok(Response::new(PendingUnbanRequestsResponse {
+            requests: pending_unban_requests(&history),
+        }))
+    }
+
+    /// Fetches many `UserProfile` accounts with batched `getMultipleAccounts` requests.
+    async fn get_user_profiles_batch(
+        &self,
+        request: Request<GetUserProfilesBatchRequest>,
+    ) -> Result<Response<UserProfilesBatchResponse>, Status> {
+        let deadline = request_deadline(&request);
+        let result: Result<Response<UserProfilesBatchResponse>, GatewayError> =
+            within_deadline(deadline, async {
+                let state = self.state.realm(&request)?;
+                let users = request.into_inner().users;
+                if users.len() > MAX_USER_PROFILES_PER_BATCH {
+                    return Err(GatewayError::InvalidArgument(format!(
+                    "Too many user profiles requested: {} (at most {MAX_USER_PROFILES_PER_BATCH})",
+                    users.len()
+                )));
+                }
+                let pairs = users
+                    .iter()
+                    .map(|user| {
+                        Ok((
+                            parse_pubkey(&user.authority_pubkey)?,
+                            parse_pubkey(&user.admin_profile_pda)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, GatewayError>>()?;
+                tracing::debug!(
+                    "Received GetUserProfilesBatch request for {} users",
+                    pairs.len()
+                );
+
+                let builder = state.transaction_builder();
+                let profiles = builder
+                    .fetch_user_profiles(&pairs)
+                    .await
+                    .map_err(|e| GatewayError::Connector(Box::new(e)))?;
+                Ok(Response::new(UserProfilesBatchResponse {
+                    profiles: pairs
+                        .iter()
+                        .zip(profiles)
+                        .map(
+                            |((authority, admin_profile_pda), profile)| UserProfileEntry {
+                                pda: find_user_profile_pda_for(
+                                    &builder.program_id(),
+                                    authority,
+                                    admin_profile_pda,
+                                )
+                                .0
+                                .to_string(),
+                                profile: profile.map(Into::into),
+                            },
+                        )
+                        .collect(),
+                }))
+            })
+            .await;
+        result.map_err(Status::from)
+    }
 }
@@ -0,0 +1,341 @@
+//! # HTTP/JSON Bridge
+//!
+//! Serves the gateway's RPCs to clients that cannot speak gRPC, such as `curl`, plain
+//! `fetch` or serverless functions, on the separate port configured in `[gateway.http]`.
+//! This module is only available with the `http` feature.
+//!
+//! - **Unary RPCs** are served as `POST /v1/<rpc>`, named after the RPC in snake_case, e.g.
+//!   `POST /v1/prepare_user_deposit`. The request and response bodies are the JSON form of
+//!   the Protobuf messages: fields keep their snake_case names, `bytes` are arrays of
+//!   numbers and enums are numbers. RPCs taking `google.protobuf.Empty` ignore the body.
+//! - **Event streams** are served as `GET /v1/<rpc>?pda=...` Server-Sent Events, with the
+//!   fields of the request in the query string. Every `EventStreamItem` is sent as one
+//!   `message` event carrying its JSON, and a failure as a final `error` event.
+//!
+//! Requests are handled by the same [`GatewayServer`] as gRPC calls. The request headers
+//! take the place of the metadata (`x-api-key`, `x-realm`, `x-message-encoding`,
+//! `grpc-timeout`), and requests are authenticated and rate limited like gRPC calls, with
+//! buckets of their own. A failed call is answered with the HTTP status of its gRPC code
+//! (see [`http_status`]), the body `{"code": 3, "message": "..."}`, and the metadata of the
+//! `Status`, such as `x-error-reason`, as response headers.
+
+use crate::auth::{Authenticator, StaticApiKeyAuthenticator};
+use crate::grpc::proto::w3b2::protocol::gateway::{
+    bridge_gateway_service_server::BridgeGatewayService, DeriveAdminPdaRequest,
+    DeriveUserPdaRequest, EventStreamItem, GetEventCountsRequest, GetTransactionLogsRequest,
+    GetUserProfilesBatchRequest, ListPendingUnbanRequestsRequest, ListenRequest,
+    PrepareAdminBanUserRequest, PrepareAdminCloseProfileRequest,
+    PrepareAdminDispatchCommandRequest, PrepareAdminRegisterProfileRequest,
+    PrepareAdminSetConfigRequest, PrepareAdminUnbanUserRequest, PrepareAdminWithdrawAllRequest,
+    PrepareAdminWithdrawRequest, PrepareBatchRequest, PrepareLogActionRequest,
+    PrepareUserCloseProfileRequest, PrepareUserCreateProfileRequest, PrepareUserDepositRequest,
+    PrepareUserDispatchCommandRequest, PrepareUserRequestUnbanRequest,
+    PrepareUserSetLowBalanceThresholdRequest, PrepareUserSetSpendingCapRequest,
+    PrepareUserUpdateCommKeyRequest, PrepareUserWithdrawRequest,
+    SimulatePreparedTransactionRequest, SubmitTransactionRequest, TailRequest, UnsubscribeRequest,
+};
+use crate::grpc::{AppState, GatewayServer};
+use crate::rate_limit::{client_key, RateLimiter};
+use anyhow::Result;
+use axum::extract::{ConnectInfo, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::future::BoxFuture;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tonic::metadata::MetadataMap;
+use tonic::transport::server::TcpConnectInfo;
+use tonic::{Code, Extensions, Request, Status};
+use tracing::Instrument;
+
+/// Routes `POST /v1/<rpc>` to the unary RPC `<rpc>`, reading its request from the JSON body.
+macro_rules! unary_routes {
+    ($router:expr, $($rpc:ident($request:ty)),* $(,)?) => {
+        $router$(.route(
+            concat!("/v1/", stringify!($rpc)),
+            post(
+                |State(bridge): State<Bridge>,
+                 ConnectInfo(peer): ConnectInfo<SocketAddr>,
+                 headers: HeaderMap,
+                 Json(message): Json<$request>| async move {
+                    let path = concat!("/v1/", stringify!($rpc));
+                    unary(&bridge, peer, headers, path, message, |server, request| async move {
+                        server.$rpc(request).await
+                    })
+                    .await
+                },
+            ),
+        ))*
+    };
+}
+
+/// Routes `POST /v1/<rpc>` to the unary RPC `<rpc>` taking `google.protobuf.Empty`.
+macro_rules! empty_routes {
+    ($router:expr, $($rpc:ident),* $(,)?) => {
+        $router$(.route(
+            concat!("/v1/", stringify!($rpc)),
+            post(
+                |State(bridge): State<Bridge>,
+                 ConnectInfo(peer): ConnectInfo<SocketAddr>,
+                 headers: HeaderMap| async move {
+                    let path = concat!("/v1/", stringify!($rpc));
+                    unary(&bridge, peer, headers, path, (), |server, request| async move {
+                        server.$rpc(request).await
+                    })
+                    .await
+                },
+            ),
+        ))*
+    };
+}
+
+/// Routes `GET /v1/<rpc>` to the streaming RPC `<rpc>`, reading its request from the query
+/// string and answering with Server-Sent Events.
+macro_rules! stream_routes {
+    ($router:expr, $($rpc:ident($request:ty)),* $(,)?) => {
+        $router$(.route(
+            concat!("/v1/", stringify!($rpc)),
+            get(
+                |State(bridge): State<Bridge>,
+                 ConnectInfo(peer): ConnectInfo<SocketAddr>,
+                 headers: HeaderMap,
+                 Query(message): Query<$request>| async move {
+                    let path = concat!("/v1/", stringify!($rpc));
+                    server_sent_events(&bridge, peer, headers, path, message, |server, request| async move {
+                        server.$rpc(request).await
+                    })
+                    .await
+                },
+            ),
+        ))*
+    };
+}
+
+/// The state shared by the routes of the bridge.
+#[derive(Clone)]
+struct Bridge {
+    server: Arc<GatewayServer>,
+    authenticator: Arc<dyn Authenticator>,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl Bridge {
+    /// Turns an HTTP request into the `Request` a gRPC handler expects, authenticating and
+    /// rate limiting it on the way. Returns it with the tracing span to handle it in.
+    #[allow(clippy::result_large_err)]
+    async fn request<T>(
+        &self,
+        peer: SocketAddr,
+        headers: HeaderMap,
+        path: &str,
+        message: T,
+    ) -> Result<(Request<T>, tracing::Span), Status> {
+        let metadata = MetadataMap::from_headers(headers);
+        let principal = self
+            .authenticator
+            .authenticate(&metadata)
+            .await
+            .inspect_err(|status| {
+                tracing::warn!(
+                    "Rejected unauthenticated request to {}: {}",
+                    path,
+                    status.message()
+                );
+            })?;
+        let span = tracing::info_span!("rpc", principal = %principal, path);
+
+        let mut extensions = Extensions::new();
+        extensions.insert(principal);
+        extensions.insert(TcpConnectInfo {
+            local_addr: None,
+            remote_addr: Some(peer),
+        });
+        let request = Request::from_parts(metadata, extensions, message);
+        self.rate_limiter.check(&client_key(&request))?;
+        Ok((request, span))
+    }
+}
+
+/// Builds the HTTP/JSON bridge for `state`, returning the future that serves it on `addr`.
+pub fn build_server(state: AppState, addr: SocketAddr) -> Result<BoxFuture<'static, Result<()>>> {
+    let authenticator = Arc::new(StaticApiKeyAuthenticator::from_config(
+        &state.config.gateway.auth,
+    ));
+    build_server_with_authenticator(state, addr, authenticator)
+}
+
+/// Like [`build_server`], but authenticates clients with `authenticator` instead of the
+/// `[gateway.auth]` API keys.
+pub fn build_server_with_authenticator(
+    state: AppState,
+    addr: SocketAddr,
+    authenticator: Arc<dyn Authenticator>,
+) -> Result<BoxFuture<'static, Result<()>>> {
+    let bridge = Bridge {
+        rate_limiter: Arc::new(RateLimiter::new(&state.config.gateway.rate_limit)),
+        server: Arc::new(GatewayServer::new(state)),
+        authenticator,
+    };
+    let server = axum::Server::try_bind(&addr)?
+        .serve(router(bridge).into_make_service_with_connect_info::<SocketAddr>());
+    Ok(Box::pin(async move { Ok(server.await?) }))
+}
+
+fn router(bridge: Bridge) -> Router {
+    let router = Router::new();
+    let router = unary_routes!(
+        router,
+        unsubscribe(UnsubscribeRequest),
+        prepare_admin_register_profile(PrepareAdminRegisterProfileRequest),
+        prepare_admin_set_config(PrepareAdminSetConfigRequest),
+        prepare_admin_withdraw(PrepareAdminWithdrawRequest),
+        prepare_admin_withdraw_all(PrepareAdminWithdrawAllRequest),
+        prepare_admin_close_profile(PrepareAdminCloseProfileRequest),
+        prepare_admin_dispatch_command(PrepareAdminDispatchCommandRequest),
+        prepare_admin_ban_user(PrepareAdminBanUserRequest),
+        prepare_admin_unban_user(PrepareAdminUnbanUserRequest),
+        prepare_user_create_profile(PrepareUserCreateProfileRequest),
+        prepare_user_update_comm_key(PrepareUserUpdateCommKeyRequest),
+        prepare_user_set_low_balance_threshold(PrepareUserSetLowBalanceThresholdRequest),
+        prepare_user_set_spending_cap(PrepareUserSetSpendingCapRequest),
+        prepare_user_deposit(PrepareUserDepositRequest),
+        prepare_user_withdraw(PrepareUserWithdrawRequest),
+        prepare_user_close_profile(PrepareUserCloseProfileRequest),
+        prepare_user_dispatch_command(PrepareUserDispatchCommandRequest),
+        prepare_user_request_unban(PrepareUserRequestUnbanRequest),
+        prepare_log_action(PrepareLogActionRequest),
+        prepare_batch(PrepareBatchRequest),
+        submit_transaction(SubmitTransactionRequest),
+        simulate_prepared_transaction(SimulatePreparedTransactionRequest),
+        get_transaction_logs(GetTransactionLogsRequest),
+        derive_admin_pda(DeriveAdminPdaRequest),
+        derive_user_pda(DeriveUserPdaRequest),
+        get_event_counts(GetEventCountsRequest),
+        get_user_profiles_batch(GetUserProfilesBatchRequest),
+        list_pending_unban_requests(ListPendingUnbanRequestsRequest),
+    );
+    let router = empty_routes!(
+        router,
+        list_subscriptions,
+        unsubscribe_all,
+        get_latest_blockhash,
+        get_gateway_info,
+        get_protocol_limits,
+        get_sync_status,
+    );
+    let router = stream_routes!(
+        router,
+        stream_user_live_events(ListenRequest),
+        stream_admin_live_events(ListenRequest),
+        get_user_event_history(ListenRequest),
+        get_admin_event_history(ListenRequest),
+        tail(TailRequest),
+    );
+    router.with_state(bridge)
+}
+
+/// Serves a unary RPC, answering with the JSON of its response.
+async fn unary<T, R, F, Fut>(
+    bridge: &Bridge,
+    peer: SocketAddr,
+    headers: HeaderMap,
+    path: &str,
+    message: T,
+    rpc: F,
+) -> Response
+where
+    F: FnOnce(Arc<GatewayServer>, Request<T>) -> Fut,
+    Fut: Future<Output = Result<tonic::Response<R>, Status>>,
+    R: Serialize,
+{
+    let (request, span) = match bridge.request(peer, headers, path, message).await {
+        Ok(request) => request,
+        Err(status) => return status_response(status),
+    };
+    match rpc(bridge.server.clone(), request).instrument(span).await {
+        Ok(response) => Json(response.into_inner()).into_response(),
+        Err(status) => status_response(status),
+    }
+}
+
+/// Serves a streaming RPC, answering with one Server-Sent Event per item.
+async fn server_sent_events<T, S, F, Fut>(
+    bridge: &Bridge,
+    peer: SocketAddr,
+    headers: HeaderMap,
+    path: &str,
+    message: T,
+    rpc: F,
+) -> Response
+where
+    F: FnOnce(Arc<GatewayServer>, Request<T>) -> Fut,
+    Fut: Future<Output = Result<tonic::Response<S>, Status>>,
+    S: Stream<Item = Result<EventStreamItem, Status>> + Send + 'static,
+{
+    let (request, span) = match bridge.request(peer, headers, path, message).await {
+        Ok(request) => request,
+        Err(status) => return status_response(status),
+    };
+    let items = match rpc(bridge.server.clone(), request).instrument(span).await {
+        Ok(response) => response.into_inner(),
+        Err(status) => return status_response(status),
+    };
+    let events = items.map(|item| {
+        let event = item.and_then(|item| {
+            Event::default()
+                .json_data(item)
+                .map_err(|e| Status::internal(format!("Failed to encode event: {e}")))
+        });
+        Ok::<_, Infallible>(event.unwrap_or_else(|status| {
+            Event::default()
+                .event("error")
+                .data(error_body(&status).to_string())
+        }))
+    });
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Returns the HTTP status code a failed call with `code` is answered with, following the
+/// mapping of the Google API HTTP transcoding.
+pub fn http_status(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+            StatusCode::BAD_REQUEST
+        }
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Cancelled => StatusCode::from_u16(499).expect("499 is a valid status code"),
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn error_body(status: &Status) -> serde_json::Value {
+    serde_json::json!({
+        "code": status.code() as i32,
+        "message": status.message(),
+    })
+}
+
+fn status_response(status: Status) -> Response {
+    let mut response = (http_status(status.code()), Json(error_body(&status))).into_response();
+    response
+        .headers_mut()
+        .extend(status.metadata().clone().into_headers());
+    response
+}
@@ -16,15 +16,27 @@
 //!
 //! - `grpc`: Contains the gRPC server implementation, including the `ListenAsUser`
 //!   and `ListenAsAdmin` streaming RPCs.
+//! - `http`: (with the `http` feature) Serves the RPCs as JSON endpoints and Server-Sent
+//!   Events for clients without gRPC support.
+//! - `auth`: Pluggable authentication of the clients behind each request.
+//! - `payload_limits`: Checks dispatch payloads against the admins' payload limits.
+//! - `rate_limit`: Per-client request and stream limits.
 //! - `storage`: Manages the persistent storage layer used for event catch-up.
+//! - `subscriptions`: Tracks active live subscriptions and the global subscription limit.
 //! - `config`: Handles application configuration.
 //! - `cli`: Defines the command-line interface for running the gateway.
 
+pub mod auth;
 pub mod cli;
 pub mod config;
 pub mod error;
 pub mod grpc;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod payload_limits;
+pub mod rate_limit;
 pub mod storage;
+pub mod subscriptions;
 
 use anyhow::Result;
 use clap::Parser;
@@ -61,12 +73,13 @@ fn load_config_from_cli(run_cmd: cli::RunCmd) -> Result<GatewayConfig> {
 
 /// Starts the gRPC server and handles graceful shutdown.
 async fn run_server(config: GatewayConfig) -> Result<()> {
-    let event_manager_handle = grpc::start(&config).await?;
+    let event_manager_handles = grpc::start(&config).await?;
 
     match signal::ctrl_c().await {
         Ok(()) => {
             tracing::info!("Received Ctrl+C, initiating graceful shutdown...");
-            event_manager_handle.stop().await;
+            // Resolves only once all connector workers have exited and the sync cursors are persisted.
+            futures::future::join_all(event_manager_handles.iter().map(|h| h.stop())).await;
             tracing::info!("Shutdown complete.");
         }
         Err(err) => {
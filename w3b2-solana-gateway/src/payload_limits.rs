@@ -0,0 +1,92 @@
+//! # Payload Limits
+//!
+//! Checks the payload of a `prepare_*_dispatch_command` request against the limit the
+//! on-chain program will enforce, so that an oversized payload is rejected before the
+//! client signs a transaction that can only fail.
+//!
+//! Every admin may lower the global `MAX_PAYLOAD_SIZE` through its `max_payload_size`.
+//! The limits of the admins are fetched from their `AdminProfile` accounts and cached for
+//! [`ADMIN_LIMIT_TTL`], so a burst of dispatches costs a single RPC call. If the profile
+//! cannot be fetched, only the global limit is checked.
+
+use crate::error::GatewayError;
+use dashmap::DashMap;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::time::{Duration, Instant};
+use w3b2_solana_connector::client::TransactionBuilder;
+use w3b2_solana_connector::MAX_PAYLOAD_SIZE;
+
+/// How long the fetched payload limit of an admin is reused.
+pub const ADMIN_LIMIT_TTL: Duration = Duration::from_secs(60);
+
+/// The cached payload limits of the admins, by `AdminProfile` PDA.
+#[derive(Default)]
+pub struct PayloadLimits {
+    limits: DashMap<Pubkey, (usize, Instant)>,
+}
+
+impl PayloadLimits {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fails with `InvalidArgument` if `payload_len` exceeds the payload limit of
+    /// `admin_profile_pda`, or the global `MAX_PAYLOAD_SIZE` if that is smaller.
+    ///
+    /// The profile is fetched through `builder`, so it is only trusted if it is owned by the
+    /// program the builder is addressed to.
+    pub async fn check(
+        &self,
+        builder: &TransactionBuilder<RpcClient>,
+        admin_profile_pda: &Pubkey,
+        payload_len: usize,
+    ) -> Result<(), GatewayError> {
+        check_payload_len(payload_len, MAX_PAYLOAD_SIZE)?;
+        let limit = self.admin_limit(builder, admin_profile_pda).await;
+        check_payload_len(payload_len, limit)
+    }
+
+    /// Returns the payload limit of `admin_profile_pda`, from the cache while it is fresh.
+    async fn admin_limit(
+        &self,
+        builder: &TransactionBuilder<RpcClient>,
+        admin_profile_pda: &Pubkey,
+    ) -> usize {
+        if let Some(entry) = self.limits.get(admin_profile_pda) {
+            let (limit, fetched) = *entry;
+            if fetched.elapsed() < ADMIN_LIMIT_TTL {
+                return limit;
+            }
+        }
+        match builder.fetch_admin_profile(admin_profile_pda).await {
+            Ok(Some(profile)) => {
+                let limit = (profile.max_payload_size as usize).min(MAX_PAYLOAD_SIZE);
+                self.limits
+                    .insert(*admin_profile_pda, (limit, Instant::now()));
+                limit
+            }
+            // The transaction fails for other reasons, so there is nothing to cache.
+            Ok(None) => MAX_PAYLOAD_SIZE,
+            Err(e) => {
+                tracing::debug!(
+                    "Failed to fetch the payload limit of admin {}, checking the global limit only: {}",
+                    admin_profile_pda,
+                    e
+                );
+                MAX_PAYLOAD_SIZE
+            }
+        }
+    }
+}
+
+/// Fails with `InvalidArgument` if `payload_len` exceeds `limit`.
+fn check_payload_len(payload_len: usize, limit: usize) -> Result<(), GatewayError> {
+    if payload_len > limit {
+        return Err(GatewayError::InvalidArgument(format!(
+            "The payload is {payload_len} bytes, but at most {limit} bytes are allowed"
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,224 @@
+//! # Per-Client Rate Limiting
+//!
+//! Protects the gateway from clients that flood it with requests or hold open an
+//! excessive number of event streams.
+//!
+//! - **[`RateLimiter`]**: A token bucket per client, checked for every RPC by the tonic
+//!   interceptor returned from [`interceptor`].
+//! - **[`StreamLimiter`]**: Caps the number of event streams a single client may have open
+//!   at once. Each open stream holds a [`StreamPermit`], which releases its slot on drop.
+//!   A stream whose client has already gone away does not count against the limit, so a
+//!   client can reconnect immediately after its connection drops.
+//!
+//! Clients are identified by [`client_key`]: the name of the [`Principal`] resolved by the
+//! gateway's [`Authenticator`](crate::auth::Authenticator), or the IP address of the peer
+//! for anonymous clients.
+
+use crate::auth::Principal;
+use crate::config::RateLimitConfig;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tonic::{Request, Status};
+
+/// The metadata key clients can use to identify themselves independently of their address.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// The number of tracked clients above which idle token buckets are pruned.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Buckets that have not been touched for this long are considered idle.
+const IDLE_BUCKET_TTL: Duration = Duration::from_secs(60);
+
+/// Returns the key used to identify the client that sent `request`.
+pub fn client_key<T>(request: &Request<T>) -> String {
+    let name = match request.extensions().get::<Principal>() {
+        Some(principal) => principal.name().map(str::to_string),
+        // The request has not passed through the authentication layer, e.g. a handler
+        // called directly. Fall back to the unverified API key.
+        None => request
+            .metadata()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    };
+    if let Some(name) = name {
+        return format!("key:{name}");
+    }
+    match request.remote_addr() {
+        Some(addr) => format!("peer:{}", addr.ip()),
+        None => "unknown".to_string(),
+    }
+}
+
+/// Builds a tonic interceptor that rejects requests from clients over their rate limit.
+pub fn interceptor(
+    limiter: Arc<RateLimiter>,
+) -> impl FnMut(Request<()>) -> Result<Request<()>, Status> + Clone {
+    #[allow(clippy::result_large_err)]
+    move |request: Request<()>| {
+        limiter.check(&client_key(&request))?;
+        Ok(request)
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// A token-bucket rate limiter keyed by client.
+///
+/// Every client starts with `burst` tokens and regains `requests_per_second` tokens per
+/// second, up to `burst`. Each request consumes one token. A `requests_per_second` of `0`
+/// disables the limit.
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    buckets: DashMap<String, TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Creates a new `RateLimiter` from the gateway's rate limit settings.
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            requests_per_second: f64::from(config.requests_per_second),
+            burst: f64::from(config.burst.max(1)),
+            buckets: DashMap::new(),
+        }
+    }
+
+    /// Consumes a token for `client`, or returns `Status::resource_exhausted` if it has none left.
+    #[allow(clippy::result_large_err)]
+    pub fn check(&self, client: &str) -> Result<(), Status> {
+        if self.requests_per_second <= 0.0 {
+            return Ok(());
+        }
+        if self.buckets.len() > PRUNE_THRESHOLD {
+            self.buckets
+                .retain(|_, bucket| bucket.last_refill.elapsed() < IDLE_BUCKET_TTL);
+        }
+
+        let now = Instant::now();
+        let mut bucket = self
+            .buckets
+            .entry(client.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: self.burst,
+                last_refill: now,
+            });
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.requests_per_second).min(self.burst);
+        bucket.last_refill = now;
+
+        if bucket.tokens < 1.0 {
+            tracing::warn!("Rate limit exceeded for client {}", client);
+            return Err(Status::resource_exhausted("Rate limit exceeded"));
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+/// Reports whether the client of an open stream has gone away.
+type ClosedProbe = Box<dyn Fn() -> bool + Send + Sync>;
+
+/// Limits the number of concurrently open event streams per client.
+pub struct StreamLimiter {
+    max_streams_per_client: usize,
+    /// The open streams of each client, by permit id, with the probe of the stream's
+    /// channel once the permit has been bound to one.
+    open_streams: DashMap<String, HashMap<u64, Option<ClosedProbe>>>,
+    next_id: AtomicU64,
+}
+
+impl StreamLimiter {
+    /// Creates a new `StreamLimiter` from the gateway's rate limit settings.
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            max_streams_per_client: config.max_streams_per_client,
+            open_streams: DashMap::new(),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Reserves a stream slot for `client`, or returns `Status::resource_exhausted` if the
+    /// client already has `max_streams_per_client` streams open.
+    ///
+    /// When the client is at its limit, streams whose client side has already been closed
+    /// are evicted first. Their tasks may not have noticed the disconnect yet, but they no
+    /// longer deliver anything, so a reconnecting client takes over their slots.
+    #[allow(clippy::result_large_err)]
+    pub fn acquire(self: &Arc<Self>, client: String) -> Result<StreamPermit, Status> {
+        let mut open = self.open_streams.entry(client.clone()).or_default();
+        if open.len() >= self.max_streams_per_client {
+            let before = open.len();
+            open.retain(|_, probe| !probe.as_ref().is_some_and(|closed| closed()));
+            if open.len() < before {
+                tracing::debug!(
+                    "Evicted {} stale streams of client {}",
+                    before - open.len(),
+                    client
+                );
+            }
+        }
+        if open.len() >= self.max_streams_per_client {
+            tracing::warn!("Stream limit exceeded for client {}", client);
+            return Err(Status::resource_exhausted(format!(
+                "Too many concurrent streams (limit {})",
+                self.max_streams_per_client
+            )));
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        open.insert(id, None);
+        drop(open);
+
+        Ok(StreamPermit {
+            limiter: self.clone(),
+            client,
+            id,
+        })
+    }
+
+    /// Returns the number of streams `client` currently has open.
+    pub fn open_streams(&self, client: &str) -> usize {
+        self.open_streams.get(client).map_or(0, |open| open.len())
+    }
+
+    fn release(&self, client: &str, id: u64) {
+        self.open_streams.remove_if_mut(client, |_, open| {
+            open.remove(&id);
+            open.is_empty()
+        });
+    }
+}
+
+/// A slot in a [`StreamLimiter`], held for as long as the stream is open.
+pub struct StreamPermit {
+    limiter: Arc<StreamLimiter>,
+    client: String,
+    id: u64,
+}
+
+impl StreamPermit {
+    /// Ties the slot to the channel feeding the client's stream. Once the client side of
+    /// `tx` is closed, the slot can be taken over by a new stream of the same client even
+    /// if this permit has not been dropped yet.
+    pub fn bind<T: Send + 'static>(&self, tx: &mpsc::Sender<T>) {
+        let tx = tx.clone();
+        if let Some(mut open) = self.limiter.open_streams.get_mut(&self.client) {
+            if let Some(probe) = open.get_mut(&self.id) {
+                *probe = Some(Box::new(move || tx.is_closed()));
+            }
+        }
+    }
+}
+
+impl Drop for StreamPermit {
+    fn drop(&mut self) {
+        self.limiter.release(&self.client, self.id);
+    }
+}
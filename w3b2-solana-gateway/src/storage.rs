@@ -2,17 +2,134 @@
 /// defined in the `w3b2-solana-connector` library.
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use sled::{transaction::TransactionalTree, Db};
+use sled::{
+    transaction::{ConflictableTransactionError, TransactionalTree},
+    Db, Transactional, Tree,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
 
-use w3b2_solana_connector::storage::Storage;
+use w3b2_solana_connector::events::EventKind;
+use w3b2_solana_connector::storage::{CachedEvent, CachedHistory, Storage};
 
 /// A `sled`-backed implementation of the `Storage` trait.
 ///
 /// It uses a single `sled` database to transactionally store the `last_slot`
-/// and `last_sig` processed by the synchronizer.
+/// and `last_sig` processed by the synchronizer, the per-PDA event counts, the active PDAs,
+/// and optionally an event cache.
 #[derive(Clone)]
 pub struct SledStorage {
     db: Db,
+    event_cache: Option<EventCache>,
+}
+
+/// The persisted event histories of replayed PDAs.
+///
+/// Events are keyed by `pda ++ slot ++ id` (big-endian), so a prefix scan over a PDA yields
+/// its history in order. The `id` is a database-wide counter that keeps events from the same
+/// slot in the order they were cached. The cursor tree maps each cached PDA to a
+/// [`CacheCursor`].
+#[derive(Clone)]
+struct EventCache {
+    events: Tree,
+    cursors: Tree,
+    max_events_per_pda: usize,
+}
+
+/// The newest transaction a cached history is complete up to, the number of events in it,
+/// and whether its oldest transactions were evicted.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CacheCursor {
+    slot: u64,
+    signature: String,
+    len: u64,
+    truncated: bool,
+}
+
+fn encode<T: serde::Serialize>(value: &T) -> Result<Vec<u8>> {
+    Ok(bincode::serde::encode_to_vec(
+        value,
+        bincode::config::standard(),
+    )?)
+}
+
+fn decode<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    Ok(bincode::serde::decode_from_slice(bytes, bincode::config::standard())?.0)
+}
+
+fn event_key(pda: &Pubkey, slot: u64, id: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(48);
+    key.extend_from_slice(pda.as_ref());
+    key.extend_from_slice(&slot.to_be_bytes());
+    key.extend_from_slice(&id.to_be_bytes());
+    key
+}
+
+/// The name of the tree holding the event counts. Counts are keyed by `pda ++ kind name`
+/// and stored as big-endian `u64`s, so a prefix scan over a PDA yields all of its counts.
+const EVENT_COUNTS_TREE: &str = "event_counts";
+
+fn event_count_key(pda: &Pubkey, kind: EventKind) -> Vec<u8> {
+    [pda.as_ref(), kind.as_str().as_bytes()].concat()
+}
+
+/// The name of the tree holding the active PDAs, keyed by their bytes with empty values.
+const ACTIVE_PDAS_TREE: &str = "active_pdas";
+
+impl EventCache {
+    fn cursor(&self, pda: &Pubkey) -> Result<Option<CacheCursor>> {
+        self.cursors.get(pda)?.map(|v| decode(&v)).transpose()
+    }
+
+    /// Evicts the oldest transactions of `pda` until its history fits in
+    /// `max_events_per_pda`, and marks it as truncated. The events of a transaction are
+    /// evicted together, so the history never starts in the middle of one.
+    fn evict_oldest(&self, pda: &Pubkey) -> Result<()> {
+        let Some(cursor) = self.cursor(pda)? else {
+            return Ok(());
+        };
+        let mut excess = cursor.len.saturating_sub(self.max_events_per_pda as u64);
+        if excess == 0 {
+            return Ok(());
+        }
+        let mut keys = Vec::new();
+        let mut last_signature = None;
+        for entry in self.events.scan_prefix(pda) {
+            let (key, value) = entry?;
+            let signature = decode::<CachedEvent>(&value)?.signature;
+            if excess == 0 && last_signature.as_ref() != Some(&signature) {
+                break;
+            }
+            keys.push(key);
+            excess = excess.saturating_sub(1);
+            last_signature = Some(signature);
+        }
+
+        (&self.events, &self.cursors)
+            .transaction(|(events_tx, cursors_tx)| {
+                // A concurrent update of the history is left alone; it evicts on its own.
+                let stored = cursors_tx
+                    .get(pda)?
+                    .and_then(|v| decode::<CacheCursor>(&v).ok());
+                if stored.is_none_or(|c| c.signature != cursor.signature || c.len != cursor.len) {
+                    return Ok(());
+                }
+                for key in &keys {
+                    events_tx.remove(key.clone())?;
+                }
+                let new_cursor = encode(&CacheCursor {
+                    slot: cursor.slot,
+                    signature: cursor.signature.clone(),
+                    len: cursor.len - keys.len() as u64,
+                    truncated: true,
+                })
+                .map_err(|_| ConflictableTransactionError::Abort(()))?;
+                cursors_tx.insert(pda.as_ref(), new_cursor)?;
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })
+            .map_err(|e| anyhow!("Sled transaction for the event cache failed: {e:?}"))?;
+        Ok(())
+    }
 }
 
 impl SledStorage {
@@ -22,7 +139,20 @@ impl SledStorage {
     ///
     /// * `db` - A `sled::Db` instance. This can be shared with `SledKeystore`.
     pub fn new(db: Db) -> Self {
-        Self { db }
+        Self {
+            db,
+            event_cache: None,
+        }
+    }
+
+    /// Enables the event cache, keeping up to `max_events_per_pda` events per PDA.
+    pub fn with_event_cache(mut self, max_events_per_pda: usize) -> Result<Self> {
+        self.event_cache = Some(EventCache {
+            events: self.db.open_tree("event_cache::events")?,
+            cursors: self.db.open_tree("event_cache::cursors")?,
+            max_events_per_pda,
+        });
+        Ok(self)
     }
 }
 
@@ -53,16 +183,173 @@ impl Storage for SledStorage {
     /// Atomically sets the last synchronized slot and signature using a `sled` transaction.
     /// This ensures that the sync state is always consistent.
     async fn set_sync_state(&self, slot: u64, sig: &str) -> Result<()> {
-        self.db.transaction(
-            |tx: &TransactionalTree| -> Result<(), sled::transaction::ConflictableTransactionError<()>> {
-                tx.insert("sync::last_slot", slot.to_string().as_bytes())?;
-                tx.insert("sync::last_sig", sig.as_bytes())?;
-                Ok(())
-            },
-        ).map_err(|e| anyhow!("Sled transaction for sync state failed: {e:?}"))?;
+        self.db
+            .transaction(
+                |tx: &TransactionalTree| -> Result<(), ConflictableTransactionError<()>> {
+                    tx.insert("sync::last_slot", slot.to_string().as_bytes())?;
+                    tx.insert("sync::last_sig", sig.as_bytes())?;
+                    Ok(())
+                },
+            )
+            .map_err(|e| anyhow!("Sled transaction for sync state failed: {e:?}"))?;
 
         self.db.flush_async().await?;
 
         Ok(())
     }
+
+    /// Reads the cached history of `pda` with a prefix scan over the events tree.
+    async fn get_cached_events(&self, pda: &Pubkey) -> Result<Option<CachedHistory>> {
+        let Some(cache) = &self.event_cache else {
+            return Ok(None);
+        };
+        let Some(cursor) = cache.cursor(pda)? else {
+            return Ok(None);
+        };
+        let events = cache
+            .events
+            .scan_prefix(pda)
+            .values()
+            .map(|v| decode::<CachedEvent>(&v?))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(CachedHistory {
+            events,
+            cursor: Some((cursor.slot, cursor.signature)),
+            truncated: cursor.truncated,
+        }))
+    }
+
+    /// Appends `events` and moves the cursor in a single transaction, provided the stored
+    /// cursor still equals `since`. If the history then exceeds `max_events_per_pda`, its
+    /// oldest transactions are evicted.
+    async fn cache_events(
+        &self,
+        pda: &Pubkey,
+        since: Option<(u64, &str)>,
+        events: &[CachedEvent],
+        cursor: (u64, &str),
+    ) -> Result<()> {
+        let Some(cache) = &self.event_cache else {
+            return Ok(());
+        };
+        let (slot, signature) = cursor;
+        let values = events
+            .iter()
+            .map(|event| Ok((event.slot, encode(event)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        (&cache.events, &cache.cursors)
+            .transaction(|(events_tx, cursors_tx)| {
+                let stored = cursors_tx
+                    .get(pda)?
+                    .and_then(|v| decode::<CacheCursor>(&v).ok());
+                let stored_since = stored.as_ref().map(|c| (c.slot, c.signature.as_str()));
+                if stored_since != since {
+                    return Ok(());
+                }
+
+                let len = stored.as_ref().map_or(0, |c| c.len) + values.len() as u64;
+                let new_cursor = encode(&CacheCursor {
+                    slot,
+                    signature: signature.to_string(),
+                    len,
+                    truncated: stored.is_some_and(|c| c.truncated),
+                })
+                .map_err(|_| ConflictableTransactionError::Abort(()))?;
+                for (slot, value) in &values {
+                    let id = events_tx.generate_id()?;
+                    events_tx.insert(event_key(pda, *slot, id), value.as_slice())?;
+                }
+                cursors_tx.insert(pda.as_ref(), new_cursor)?;
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })
+            .map_err(|e| anyhow!("Sled transaction for the event cache failed: {e:?}"))?;
+        cache.evict_oldest(pda)?;
+
+        self.db.flush_async().await?;
+        Ok(())
+    }
+
+    /// Removes the events of `signature` from each PDA's history and updates its length.
+    async fn evict_cached_events(&self, pdas: &[Pubkey], signature: &str) -> Result<()> {
+        let Some(cache) = &self.event_cache else {
+            return Ok(());
+        };
+        for pda in pdas {
+            let Some(mut cursor) = cache.cursor(pda)? else {
+                continue;
+            };
+            for entry in cache.events.scan_prefix(pda) {
+                let (key, value) = entry?;
+                if decode::<CachedEvent>(&value)?.signature == signature {
+                    cache.events.remove(key)?;
+                    cursor.len = cursor.len.saturating_sub(1);
+                }
+            }
+            cache.cursors.insert(pda.as_ref(), encode(&cursor)?)?;
+        }
+        self.db.flush_async().await?;
+        Ok(())
+    }
+
+    /// Increments the counts of all `events` in a single transaction.
+    async fn count_events(&self, events: &[(Pubkey, EventKind)]) -> Result<()> {
+        let counts = self.db.open_tree(EVENT_COUNTS_TREE)?;
+        let keys: Vec<Vec<u8>> = events
+            .iter()
+            .map(|(pda, kind)| event_count_key(pda, *kind))
+            .collect();
+        counts
+            .transaction(|tx| {
+                for key in &keys {
+                    let count = tx
+                        .get(key)?
+                        .and_then(|v| <[u8; 8]>::try_from(v.as_ref()).ok())
+                        .map_or(0, u64::from_be_bytes);
+                    tx.insert(key.as_slice(), &(count + 1).to_be_bytes())?;
+                }
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })
+            .map_err(|e| anyhow!("Sled transaction for the event counts failed: {e:?}"))?;
+        // The sync cursor is written and flushed right after, which persists the counts too.
+        Ok(())
+    }
+
+    /// Reads the counts of `pda` with a prefix scan over the counts tree.
+    async fn get_event_counts(&self, pda: &Pubkey) -> Result<HashMap<EventKind, u64>> {
+        let counts = self.db.open_tree(EVENT_COUNTS_TREE)?;
+        counts
+            .scan_prefix(pda)
+            .map(|entry| {
+                let (key, value) = entry?;
+                let kind = std::str::from_utf8(&key[32..])?.parse::<EventKind>()?;
+                let count = <[u8; 8]>::try_from(value.as_ref())
+                    .map_err(|_| anyhow!("Malformed event count for {kind}"))?;
+                Ok((kind, u64::from_be_bytes(count)))
+            })
+            .collect()
+    }
+
+    async fn add_active_pda(&self, pda: &Pubkey) -> Result<()> {
+        self.db
+            .open_tree(ACTIVE_PDAS_TREE)?
+            .insert(pda.as_ref(), &[])?;
+        self.db.flush_async().await?;
+        Ok(())
+    }
+
+    async fn remove_active_pda(&self, pda: &Pubkey) -> Result<()> {
+        self.db.open_tree(ACTIVE_PDAS_TREE)?.remove(pda.as_ref())?;
+        self.db.flush_async().await?;
+        Ok(())
+    }
+
+    async fn list_active_pdas(&self) -> Result<Vec<Pubkey>> {
+        self.db
+            .open_tree(ACTIVE_PDAS_TREE)?
+            .iter()
+            .keys()
+            .map(|key| Ok(Pubkey::try_from(key?.as_ref())?))
+            .collect()
+    }
 }
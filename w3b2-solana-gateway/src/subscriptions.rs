@@ -0,0 +1,342 @@
+//! # Live Subscription Registry
+//!
+//! Tracks the gateway's active live event subscriptions and enforces the global
+//! `max_subscriptions` limit.
+//!
+//! Any number of clients may watch the same PDA. The first subscription to a PDA starts a
+//! single upstream listener whose events are fanned out to every client stream through a
+//! `broadcast` channel. The fan-out is reference-counted: when the last client stream for
+//! the PDA ends, the upstream listener is torn down.
+//!
+//! A successful [`SubscriptionRegistry::subscribe`] returns a [`Subscription`] guard that
+//! owns one slot of the limit and one reference to the fan-out. Dropping the guard releases
+//! both, so the bookkeeping stays accurate no matter how the stream ends: client disconnect,
+//! `unsubscribe`, event manager shutdown or a panic in the forwarding task.
+
+use dashmap::{mapref::entry::Entry, DashMap};
+use futures::{Stream, StreamExt};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
+use tokio::task::AbortHandle;
+use tonic::Status;
+use w3b2_solana_connector::events::BridgeEvent;
+
+/// The shared upstream of all client streams watching one PDA.
+struct FanOut {
+    /// Distinguishes this fan-out from a later one for the same PDA.
+    id: u64,
+    events: broadcast::Sender<BridgeEvent>,
+    /// The client streams using this fan-out, by subscription id, with the key of the
+    /// client that opened them and their stop signal.
+    clients: HashMap<u64, (String, watch::Sender<()>)>,
+    /// The task forwarding events from the upstream listener into `events`.
+    upstream: AbortHandle,
+    /// When the upstream listener was started.
+    started: Instant,
+}
+
+/// A PDA with an active upstream listener, as reported by [`SubscriptionRegistry::list`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveSubscription {
+    pub pda: Pubkey,
+    /// The number of client streams sharing the PDA's upstream listener.
+    pub client_streams: usize,
+    /// How long the upstream listener has been running.
+    pub age: Duration,
+}
+
+/// The registry of active live subscriptions.
+pub struct SubscriptionRegistry {
+    max_subscriptions: usize,
+    /// The capacity of each fan-out's `broadcast` channel.
+    buffer: usize,
+    fan_outs: DashMap<Pubkey, FanOut>,
+    /// The number of client streams holding a slot. This is tracked separately from
+    /// `fan_outs` so that the limit can be checked without locking every shard of the map.
+    count: AtomicUsize,
+    next_id: AtomicU64,
+}
+
+impl SubscriptionRegistry {
+    /// Creates an empty registry that allows at most `max_subscriptions` live client streams.
+    /// Each fan-out buffers up to `buffer` events for its slowest client.
+    pub fn new(max_subscriptions: usize, buffer: usize) -> Self {
+        Self {
+            max_subscriptions,
+            buffer: buffer.max(1),
+            fan_outs: DashMap::new(),
+            count: AtomicUsize::new(0),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Registers a live client stream for `pda` on behalf of `client`.
+    ///
+    /// If no other client is watching `pda`, `upstream` is called to open the upstream
+    /// event stream, which is then shared with every later subscriber until the last one
+    /// leaves. A fan-out whose upstream task has died is replaced rather than joined. Fails
+    /// with `Status::resource_exhausted` if the registry is full, or with the error of
+    /// `upstream` if the upstream cannot be opened.
+    #[allow(clippy::result_large_err)]
+    pub fn subscribe<F, S>(
+        self: &Arc<Self>,
+        pda: Pubkey,
+        client: String,
+        upstream: F,
+    ) -> Result<Subscription, Status>
+    where
+        F: FnOnce() -> Result<S, Status>,
+        S: Stream<Item = BridgeEvent> + Send + Unpin + 'static,
+    {
+        let reserved = self
+            .count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count < self.max_subscriptions).then_some(count + 1)
+            });
+        if reserved.is_err() {
+            tracing::warn!(
+                active_subscriptions = self.len(),
+                max_subscriptions = self.max_subscriptions,
+                "Rejected live subscription for PDA {}: subscription limit reached",
+                pda
+            );
+            return Err(Status::resource_exhausted(format!(
+                "The gateway has reached its limit of {} live subscriptions",
+                self.max_subscriptions
+            )));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (stop_tx, stop_rx) = watch::channel(());
+        let (fan_out_id, events) = match self.fan_outs.entry(pda) {
+            // The upstream task can only be gone without removing its fan-out if it
+            // panicked. Joining it would leave the client waiting for events forever.
+            Entry::Occupied(mut entry) if entry.get().upstream.is_finished() => {
+                tracing::warn!("Replacing the stale upstream listener for PDA {}", pda);
+                let upstream = self.open_upstream(pda, upstream)?;
+                let (fan_out, events) = self.start_fan_out(pda, id, client, stop_tx, upstream);
+                // Dropping the stale fan-out closes the channel of its remaining clients.
+                entry.insert(fan_out);
+                (id, events)
+            }
+            Entry::Occupied(mut entry) => {
+                let fan_out = entry.get_mut();
+                fan_out.clients.insert(id, (client, stop_tx));
+                tracing::debug!(
+                    "Sharing the upstream listener for PDA {} with {} client streams",
+                    pda,
+                    fan_out.clients.len()
+                );
+                (fan_out.id, fan_out.events.subscribe())
+            }
+            Entry::Vacant(entry) => {
+                let upstream = self.open_upstream(pda, upstream)?;
+                let (fan_out, events) = self.start_fan_out(pda, id, client, stop_tx, upstream);
+                entry.insert(fan_out);
+                tracing::debug!("Started the upstream listener for PDA {}", pda);
+                (id, events)
+            }
+        };
+        self.report();
+
+        Ok(Subscription {
+            registry: self.clone(),
+            pda,
+            id,
+            fan_out_id,
+            events,
+            stop_rx,
+        })
+    }
+
+    /// Opens the upstream of `pda`. If that fails, the slot reserved for the client stream
+    /// is given back.
+    #[allow(clippy::result_large_err)]
+    fn open_upstream<F, S>(&self, pda: Pubkey, upstream: F) -> Result<S, Status>
+    where
+        F: FnOnce() -> Result<S, Status>,
+    {
+        upstream().inspect_err(|e| {
+            tracing::warn!(
+                "Failed to open the upstream listener for PDA {}: {}",
+                pda,
+                e
+            );
+            self.count.fetch_sub(1, Ordering::AcqRel);
+        })
+    }
+
+    /// Spawns the task forwarding `upstream` into a new fan-out whose only client is the
+    /// stream with the given id.
+    fn start_fan_out<S>(
+        self: &Arc<Self>,
+        pda: Pubkey,
+        id: u64,
+        client: String,
+        stop_tx: watch::Sender<()>,
+        upstream: S,
+    ) -> (FanOut, broadcast::Receiver<BridgeEvent>)
+    where
+        S: Stream<Item = BridgeEvent> + Send + Unpin + 'static,
+    {
+        let (events_tx, events) = broadcast::channel(self.buffer);
+        let upstream = tokio::spawn(self.clone().forward(pda, id, upstream, events_tx.clone()));
+        let fan_out = FanOut {
+            id,
+            events: events_tx,
+            clients: HashMap::from([(id, (client, stop_tx))]),
+            upstream: upstream.abort_handle(),
+            started: Instant::now(),
+        };
+        (fan_out, events)
+    }
+
+    /// Forwards upstream events of `pda` to the fan-out with the given id. If the upstream
+    /// ends, the fan-out is removed so that its client streams see the channel close.
+    async fn forward<S>(
+        self: Arc<Self>,
+        pda: Pubkey,
+        fan_out_id: u64,
+        mut upstream: S,
+        events: broadcast::Sender<BridgeEvent>,
+    ) where
+        S: Stream<Item = BridgeEvent> + Unpin,
+    {
+        while let Some(event) = upstream.next().await {
+            // An error only means that no client stream is receiving at the moment.
+            let _ = events.send(event);
+        }
+        tracing::info!("Upstream listener for PDA {} has ended.", pda);
+        self.fan_outs
+            .remove_if(&pda, |_, fan_out| fan_out.id == fan_out_id);
+    }
+
+    /// Signals every live stream that `client` has open for `pda` to stop. Streams of other
+    /// clients watching the same PDA are not affected. Returns `false` if there were none.
+    ///
+    /// The slots themselves are freed once the streams' [`Subscription`] guards are dropped.
+    pub fn unsubscribe(&self, pda: &Pubkey, client: &str) -> bool {
+        let Some(mut fan_out) = self.fan_outs.get_mut(pda) else {
+            return false;
+        };
+        let mut stopped = false;
+        fan_out.clients.retain(|_, (owner, stop_tx)| {
+            if owner != client {
+                return true;
+            }
+            let _ = stop_tx.send(());
+            stopped = true;
+            false
+        });
+        stopped
+    }
+
+    /// Signals every live stream of every client to stop and returns how many were
+    /// signaled. The upstream listeners are torn down once the streams have ended.
+    pub fn unsubscribe_all(&self) -> usize {
+        let mut stopped = 0;
+        for mut fan_out in self.fan_outs.iter_mut() {
+            for (_, (_, stop_tx)) in fan_out.clients.drain() {
+                let _ = stop_tx.send(());
+                stopped += 1;
+            }
+        }
+        stopped
+    }
+
+    /// Returns the PDAs that currently have client streams, with the number of streams and
+    /// the age of their upstream listener.
+    pub fn list(&self) -> Vec<ActiveSubscription> {
+        self.fan_outs
+            .iter()
+            .filter(|fan_out| !fan_out.clients.is_empty())
+            .map(|fan_out| ActiveSubscription {
+                pda: *fan_out.key(),
+                client_streams: fan_out.clients.len(),
+                age: fan_out.started.elapsed(),
+            })
+            .collect()
+    }
+
+    /// Returns the number of live client streams currently holding a slot.
+    pub fn len(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if no live client streams are active.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of PDAs with an active upstream listener.
+    pub fn upstream_count(&self) -> usize {
+        self.fan_outs.len()
+    }
+
+    /// Returns the maximum number of live client streams.
+    pub fn max_subscriptions(&self) -> usize {
+        self.max_subscriptions
+    }
+
+    /// Emits the current subscription count as structured log fields.
+    fn report(&self) {
+        tracing::info!(
+            active_subscriptions = self.len(),
+            max_subscriptions = self.max_subscriptions,
+            "Live subscription count changed"
+        );
+    }
+
+    /// Removes a client stream from its fan-out and tears the fan-out down if it was the
+    /// last one.
+    fn release(&self, pda: &Pubkey, fan_out_id: u64, id: u64) {
+        let removed = self.fan_outs.remove_if_mut(pda, |_, fan_out| {
+            if fan_out.id != fan_out_id {
+                return false;
+            }
+            fan_out.clients.remove(&id);
+            fan_out.clients.is_empty()
+        });
+        if let Some((_, fan_out)) = removed {
+            fan_out.upstream.abort();
+            tracing::debug!("Stopped the upstream listener for PDA {}", pda);
+        }
+        self.count.fetch_sub(1, Ordering::AcqRel);
+        self.report();
+    }
+}
+
+/// One client stream's slot in the [`SubscriptionRegistry`], held for as long as the
+/// stream is open.
+pub struct Subscription {
+    registry: Arc<SubscriptionRegistry>,
+    pda: Pubkey,
+    id: u64,
+    fan_out_id: u64,
+    events: broadcast::Receiver<BridgeEvent>,
+    stop_rx: watch::Receiver<()>,
+}
+
+impl Subscription {
+    /// Receives the next event of the shared upstream, or `None` once the stream is stopped
+    /// through [`SubscriptionRegistry::unsubscribe`].
+    ///
+    /// Returns `RecvError::Lagged` if this stream fell more than the fan-out's buffer behind
+    /// the upstream and missed events, and `RecvError::Closed` once the upstream has ended.
+    pub async fn recv(&mut self) -> Option<Result<BridgeEvent, broadcast::error::RecvError>> {
+        tokio::select! {
+            Ok(()) = self.stop_rx.changed() => None,
+            event = self.events.recv() => Some(event),
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.registry.release(&self.pda, self.fan_out_id, self.id);
+    }
+}
@@ -12,8 +12,8 @@ use tokio::time::timeout;
 use tonic::Request;
 use w3b2_solana_gateway::grpc::proto::w3b2::protocol::gateway::{
     bridge_event::Event, bridge_gateway_service_client::BridgeGatewayServiceClient,
-    EventStreamItem, ListenRequest, PrepareAdminRegisterProfileRequest,
-    PrepareUserCreateProfileRequest, SubmitTransactionRequest,
+    DeriveAdminPdaRequest, DeriveUserPdaRequest, EventStreamItem, ListenRequest,
+    PrepareAdminRegisterProfileRequest, PrepareUserCreateProfileRequest, SubmitTransactionRequest,
 };
 
 /// Constructs the gateway URL from environment variables, with fallbacks for Docker.
@@ -128,6 +128,8 @@ impl TestHarness {
         let prepare_req = Request::new(PrepareAdminRegisterProfileRequest {
             authority_pubkey: authority.pubkey().to_string(),
             communication_pubkey: Keypair::new().pubkey().to_string(),
+            admin_index: 0,
+            fee_payer: None,
         });
         let response = self
             .grpc_client
@@ -153,6 +155,7 @@ impl TestHarness {
             authority_pubkey: authority.pubkey().to_string(),
             target_admin_pda: admin_pda.to_string(),
             communication_pubkey: Keypair::new().pubkey().to_string(),
+            fee_payer: None,
         });
         let response = self
             .grpc_client
@@ -179,6 +182,7 @@ async fn listen_for_admin_event(
 ) -> Option<Event> {
     let request = Request::new(ListenRequest {
         pda: pda.to_string(),
+        ..Default::default()
     });
     let mut stream = client
         .stream_admin_live_events(request)
@@ -201,6 +205,7 @@ async fn listen_for_user_event(
 ) -> Option<Event> {
     let request = Request::new(ListenRequest {
         pda: pda.to_string(),
+        ..Default::default()
     });
     let mut stream = client
         .stream_user_live_events(request)
@@ -318,3 +323,81 @@ async fn test_create_user_profile() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+#[ignore = "run via docker with the required program id"]
+async fn test_derive_pdas() -> anyhow::Result<()> {
+    // === 1. Arrange ===
+    let mut harness = TestHarness::new().await;
+    let admin_authority = Keypair::new().pubkey();
+    let user_authority = Keypair::new().pubkey();
+    let (expected_admin_pda, expected_admin_bump) =
+        Pubkey::find_program_address(&[b"admin", admin_authority.as_ref()], &harness.program_id);
+    let (expected_second_admin_pda, _) = Pubkey::find_program_address(
+        &[b"admin", admin_authority.as_ref(), &1u16.to_le_bytes()],
+        &harness.program_id,
+    );
+    let (expected_user_pda, expected_user_bump) = Pubkey::find_program_address(
+        &[
+            b"user",
+            user_authority.as_ref(),
+            expected_admin_pda.as_ref(),
+        ],
+        &harness.program_id,
+    );
+
+    // === 2. Act ===
+    let admin = harness
+        .grpc_client
+        .derive_admin_pda(Request::new(DeriveAdminPdaRequest {
+            authority_pubkey: admin_authority.to_string(),
+            index: 0,
+        }))
+        .await?
+        .into_inner();
+    let second_admin = harness
+        .grpc_client
+        .derive_admin_pda(Request::new(DeriveAdminPdaRequest {
+            authority_pubkey: admin_authority.to_string(),
+            index: 1,
+        }))
+        .await?
+        .into_inner();
+    let user = harness
+        .grpc_client
+        .derive_user_pda(Request::new(DeriveUserPdaRequest {
+            authority_pubkey: user_authority.to_string(),
+            admin_profile_pda: admin.pda.clone(),
+        }))
+        .await?
+        .into_inner();
+    let invalid = harness
+        .grpc_client
+        .derive_admin_pda(Request::new(DeriveAdminPdaRequest {
+            authority_pubkey: "not-a-pubkey".to_string(),
+            index: 0,
+        }))
+        .await;
+    let invalid_index = harness
+        .grpc_client
+        .derive_admin_pda(Request::new(DeriveAdminPdaRequest {
+            authority_pubkey: admin_authority.to_string(),
+            index: u32::from(u16::MAX) + 1,
+        }))
+        .await;
+
+    // === 3. Assert ===
+    assert_eq!(admin.pda, expected_admin_pda.to_string());
+    assert_eq!(admin.bump, u32::from(expected_admin_bump));
+    assert_eq!(second_admin.pda, expected_second_admin_pda.to_string());
+    assert_eq!(user.pda, expected_user_pda.to_string());
+    assert_eq!(user.bump, u32::from(expected_user_bump));
+    assert_eq!(invalid.unwrap_err().code(), tonic::Code::InvalidArgument);
+    assert_eq!(
+        invalid_index.unwrap_err().code(),
+        tonic::Code::InvalidArgument
+    );
+    println!("✅ Derived PDAs match the program seeds.");
+
+    Ok(())
+}
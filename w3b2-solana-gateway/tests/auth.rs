@@ -0,0 +1,120 @@
+mod common;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tonic::metadata::MetadataMap;
+use tonic::transport::Channel;
+use tonic::{Code, Request, Status};
+use w3b2_solana_gateway::auth::{Authenticator, Principal, StaticApiKeyAuthenticator};
+use w3b2_solana_gateway::config::GatewayConfig;
+use w3b2_solana_gateway::grpc::build_server_with_authenticator;
+use w3b2_solana_gateway::grpc::proto::w3b2::protocol::gateway::bridge_gateway_service_client::BridgeGatewayServiceClient;
+
+/// Accepts `authorization: Bearer <name>` and names the principal after the token.
+struct BearerAuthenticator;
+
+#[tonic::async_trait]
+impl Authenticator for BearerAuthenticator {
+    async fn authenticate(&self, metadata: &MetadataMap) -> Result<Principal, Status> {
+        metadata
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| Principal::Named(token.to_string()))
+            .ok_or_else(|| Status::unauthenticated("Missing bearer token"))
+    }
+}
+
+/// Starts a gateway that authenticates with `BearerAuthenticator` and allows each client
+/// a single request.
+async fn start_gateway() -> BridgeGatewayServiceClient<Channel> {
+    let mut config = GatewayConfig::default();
+    config.gateway.rate_limit.requests_per_second = 1;
+    config.gateway.rate_limit.burst = 1;
+    let addr: SocketAddr = format!("127.0.0.1:{}", portpicker::pick_unused_port().unwrap())
+        .parse()
+        .unwrap();
+
+    let server = build_server_with_authenticator(
+        common::app_state(config),
+        addr,
+        Arc::new(BearerAuthenticator),
+    )
+    .unwrap();
+    tokio::spawn(server);
+
+    for _ in 0..50 {
+        if TcpStream::connect(addr).await.is_ok() {
+            return BridgeGatewayServiceClient::connect(format!("http://{addr}"))
+                .await
+                .unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("gateway did not start listening on {addr}");
+}
+
+fn request_as(token: &str) -> Request<()> {
+    let mut request = Request::new(());
+    request
+        .metadata_mut()
+        .insert("authorization", format!("Bearer {token}").parse().unwrap());
+    request
+}
+
+#[tokio::test]
+async fn test_custom_authenticator_gates_requests_and_names_the_rate_limited_client() {
+    // === 1. Arrange ===
+    let mut client = start_gateway().await;
+
+    // === 2. Act ===
+    let anonymous = client.get_gateway_info(Request::new(())).await;
+    let alice = client.get_gateway_info(request_as("alice")).await;
+    let alice_again = client.get_gateway_info(request_as("alice")).await;
+    let bob = client.get_gateway_info(request_as("bob")).await;
+
+    // === 3. Assert ===
+    assert_eq!(anonymous.unwrap_err().code(), Code::Unauthenticated);
+    assert!(alice.is_ok());
+    // All requests come from the same address, but are limited per principal.
+    assert_eq!(alice_again.unwrap_err().code(), Code::ResourceExhausted);
+    assert!(bob.is_ok());
+}
+
+#[tokio::test]
+async fn test_static_api_key_authenticator() {
+    let metadata = |api_key: Option<&str>| {
+        let mut metadata = MetadataMap::new();
+        if let Some(api_key) = api_key {
+            metadata.insert("x-api-key", api_key.parse().unwrap());
+        }
+        metadata
+    };
+
+    // Without configured keys, any key names the client and none is required.
+    let open = StaticApiKeyAuthenticator::new([]);
+    assert_eq!(
+        open.authenticate(&metadata(Some("alice"))).await.unwrap(),
+        Principal::Named("alice".to_string())
+    );
+    assert_eq!(
+        open.authenticate(&metadata(None)).await.unwrap(),
+        Principal::Anonymous
+    );
+
+    // Otherwise, only the configured keys are accepted.
+    let closed = StaticApiKeyAuthenticator::new(["secret".to_string()]);
+    assert_eq!(
+        closed
+            .authenticate(&metadata(Some("secret")))
+            .await
+            .unwrap(),
+        Principal::Named("secret".to_string())
+    );
+    for api_key in [Some("guess"), None] {
+        let rejected = closed.authenticate(&metadata(api_key)).await.unwrap_err();
+        assert_eq!(rejected.code(), Code::Unauthenticated);
+    }
+}
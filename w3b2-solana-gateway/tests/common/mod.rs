@@ -0,0 +1,35 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use std::sync::Arc;
+use w3b2_solana_connector::workers::EventManager;
+use w3b2_solana_gateway::config::GatewayConfig;
+use w3b2_solana_gateway::grpc::AppState;
+use w3b2_solana_gateway::payload_limits::PayloadLimits;
+use w3b2_solana_gateway::rate_limit::StreamLimiter;
+use w3b2_solana_gateway::storage::SledStorage;
+use w3b2_solana_gateway::subscriptions::SubscriptionRegistry;
+
+/// Builds an `AppState` backed by a temporary database whose event manager is never run,
+/// for exercising handlers without a validator.
+pub fn app_state(config: GatewayConfig) -> AppState {
+    let rpc_client = Arc::new(RpcClient::new(config.connector.solana.rpc_url.clone()));
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let storage = Arc::new(SledStorage::new(db));
+    let (_, event_manager) = EventManager::new(
+        Arc::new(config.connector.clone()),
+        rpc_client.clone(),
+        storage.clone(),
+    );
+    AppState {
+        rpc_client,
+        event_manager,
+        subscriptions: Arc::new(SubscriptionRegistry::new(
+            config.gateway.limits.max_subscriptions,
+            config.connector.channels.listener_event_buffer,
+        )),
+        stream_limiter: Arc::new(StreamLimiter::new(&config.gateway.rate_limit)),
+        storage,
+        payload_limits: Arc::new(PayloadLimits::new()),
+        realms: Arc::default(),
+        config: Arc::new(config),
+    }
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use solana_sdk::pubkey::Pubkey;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tonic::codec::CompressionEncoding;
+use tonic::transport::Channel;
+use w3b2_solana_gateway::config::{GatewayConfig, GrpcCompression};
+use w3b2_solana_gateway::grpc::build_server;
+use w3b2_solana_gateway::grpc::proto::w3b2::protocol::gateway::{
+    bridge_gateway_service_client::BridgeGatewayServiceClient, ListenRequest,
+};
+
+/// Starts a gateway server that compresses its responses with `compression`.
+async fn start_gateway(compression: Vec<GrpcCompression>) -> SocketAddr {
+    let mut config = GatewayConfig::default();
+    config.gateway.grpc.compression = compression;
+    let addr: SocketAddr = format!("127.0.0.1:{}", portpicker::pick_unused_port().unwrap())
+        .parse()
+        .unwrap();
+
+    let server = build_server(common::app_state(config), addr).unwrap();
+    tokio::spawn(server);
+
+    // Wait for the server to accept connections.
+    for _ in 0..50 {
+        if TcpStream::connect(addr).await.is_ok() {
+            return addr;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("gateway did not start listening on {addr}");
+}
+
+async fn client(addr: SocketAddr) -> BridgeGatewayServiceClient<Channel> {
+    BridgeGatewayServiceClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap()
+}
+
+/// Opens a history stream and returns the `grpc-encoding` of its response, if any.
+async fn history_encoding(client: &mut BridgeGatewayServiceClient<Channel>) -> Option<String> {
+    let response = client
+        .get_user_event_history(ListenRequest {
+            pda: Pubkey::new_unique().to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    response
+        .metadata()
+        .get("grpc-encoding")
+        .map(|encoding| encoding.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn test_history_streams_are_compressed_for_clients_that_accept_it() {
+    // === 1. Arrange ===
+    let addr = start_gateway(vec![GrpcCompression::Zstd, GrpcCompression::Gzip]).await;
+    let mut gzip_client = client(addr)
+        .await
+        .accept_compressed(CompressionEncoding::Gzip);
+    let mut plain_client = client(addr).await;
+
+    // === 2. Act ===
+    let gzip = history_encoding(&mut gzip_client).await;
+    let plain = history_encoding(&mut plain_client).await;
+
+    // === 3. Assert ===
+    assert_eq!(gzip.as_deref(), Some("gzip"));
+    // Clients that do not advertise compression still get plain responses.
+    assert_eq!(plain, None);
+}
+
+#[tokio::test]
+async fn test_compression_is_disabled_by_default() {
+    // === 1. Arrange ===
+    let addr = start_gateway(Vec::new()).await;
+    let mut client = client(addr)
+        .await
+        .accept_compressed(CompressionEncoding::Gzip);
+
+    // === 2. Act ===
+    let encoding = history_encoding(&mut client).await;
+
+    // === 3. Assert ===
+    assert_eq!(encoding, None);
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::time::Instant;
+use tonic::{Code, Request};
+use w3b2_solana_connector::workers::EventManager;
+use w3b2_solana_gateway::config::GatewayConfig;
+use w3b2_solana_gateway::grpc::proto::w3b2::protocol::gateway::bridge_gateway_service_server::BridgeGatewayService;
+use w3b2_solana_gateway::grpc::proto::w3b2::protocol::gateway::ListPendingUnbanRequestsRequest;
+use w3b2_solana_gateway::grpc::{request_deadline, GatewayServer};
+
+/// Starts an RPC node that accepts connections but never answers, and returns its address.
+async fn unresponsive_rpc() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            connections.push(socket);
+        }
+    });
+    addr.to_string()
+}
+
+fn with_timeout<T>(message: T, timeout: &str) -> Request<T> {
+    let mut request = Request::new(message);
+    request
+        .metadata_mut()
+        .insert("grpc-timeout", timeout.parse().unwrap());
+    request
+}
+
+#[test]
+fn test_request_deadline_parses_the_grpc_timeout() {
+    let now = Instant::now();
+    let deadline = |timeout| request_deadline(&with_timeout((), timeout));
+
+    let millis = deadline("250m").unwrap();
+    assert!(millis >= now + Duration::from_millis(250));
+    assert!(millis < now + Duration::from_secs(1));
+    assert!(deadline("2H").unwrap() >= now + Duration::from_secs(2 * 60 * 60));
+    assert!(request_deadline(&Request::new(())).is_none());
+    for malformed in ["", "m", "10", "10x", "-1S", "123456789S"] {
+        assert!(deadline(malformed).is_none(), "{malformed:?}");
+    }
+}
+
+#[tokio::test]
+async fn test_slow_rpc_calls_end_at_the_client_deadline() {
+    // === 1. Arrange ===
+    let mut config = GatewayConfig::default();
+    config.connector.solana.rpc_url = format!("http://{}", unresponsive_rpc().await);
+    let server = GatewayServer::new(common::app_state(config));
+    let started = Instant::now();
+
+    // === 2. Act ===
+    let status = tokio::time::timeout(
+        Duration::from_secs(10),
+        server.get_latest_blockhash(with_timeout((), "200m")),
+    )
+    .await
+    .expect("the handler should give up at the deadline")
+    .unwrap_err();
+
+    // === 3. Assert ===
+    assert_eq!(status.code(), Code::DeadlineExceeded);
+    assert!(started.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn test_history_replays_end_at_the_client_deadline_and_release_their_permit() {
+    // === 1. Arrange ===
+    // The synchronizer cannot reach the RPC node, so the replay never catches up.
+    let rpc = unresponsive_rpc().await;
+    let mut config = GatewayConfig::default();
+    config.connector.solana.rpc_url = format!("http://{rpc}");
+    config.connector.solana.ws_url = format!("ws://{rpc}");
+    config.gateway.rate_limit.max_streams_per_client = 1;
+    let mut state = common::app_state(config);
+    let (event_manager, handle) = EventManager::new(
+        Arc::new(state.config.connector.clone()),
+        state.rpc_client.clone(),
+        state.storage.clone(),
+    );
+    tokio::spawn(event_manager.run());
+    state.event_manager = handle;
+    let server = GatewayServer::new(state);
+    let replay = || {
+        let request = ListPendingUnbanRequestsRequest {
+            admin_pda: Pubkey::new_unique().to_string(),
+        };
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            server.list_pending_unban_requests(with_timeout(request, "100m")),
+        )
+    };
+
+    // === 2. Act ===
+    let first = replay()
+        .await
+        .expect("the handler should give up at the deadline");
+    let second = replay()
+        .await
+        .expect("the handler should give up at the deadline");
+
+    // === 3. Assert ===
+    // The second replay only gets a stream permit if the first one released its own.
+    assert_eq!(first.unwrap_err().code(), Code::DeadlineExceeded);
+    assert_eq!(second.unwrap_err().code(), Code::DeadlineExceeded);
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use w3b2_solana_gateway::config::GatewayConfig;
+use w3b2_solana_gateway::grpc::build_server;
+
+const GET_GATEWAY_INFO: &str = "/w3b2.protocol.gateway.BridgeGatewayService/GetGatewayInfo";
+
+/// Starts a gateway server with `grpc-web` enabled for `allowed_origins`.
+async fn start_web_gateway(allowed_origins: &[&str]) -> SocketAddr {
+    let mut config = GatewayConfig::default();
+    config.gateway.grpc.enable_web = true;
+    config.gateway.grpc.cors_allowed_origins =
+        allowed_origins.iter().map(ToString::to_string).collect();
+    let addr: SocketAddr = format!("127.0.0.1:{}", portpicker::pick_unused_port().unwrap())
+        .parse()
+        .unwrap();
+
+    let server = build_server(common::app_state(config), addr).unwrap();
+    tokio::spawn(server);
+
+    // Wait for the server to accept connections.
+    for _ in 0..50 {
+        if TcpStream::connect(addr).await.is_ok() {
+            return addr;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("gateway did not start listening on {addr}");
+}
+
+/// Sends a raw HTTP/1.1 request and returns the raw response.
+async fn http1_request(addr: SocketAddr, head: &str, body: &[u8]) -> Vec<u8> {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "{head}Host: {addr}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    stream.write_all(body).await.unwrap();
+
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("the gateway should answer")
+        .unwrap();
+    response
+}
+
+#[tokio::test]
+async fn test_grpc_web_call_over_http1() {
+    // === 1. Arrange ===
+    let addr = start_web_gateway(&[]).await;
+    // A single uncompressed, empty `google.protobuf.Empty` message.
+    let body = [0u8, 0, 0, 0, 0];
+
+    // === 2. Act ===
+    let response = http1_request(
+        addr,
+        &format!(
+            "POST {GET_GATEWAY_INFO} HTTP/1.1\r\n\
+             Content-Type: application/grpc-web+proto\r\n\
+             X-Grpc-Web: 1\r\n"
+        ),
+        &body,
+    )
+    .await;
+
+    // === 3. Assert ===
+    let response = String::from_utf8_lossy(&response).to_lowercase();
+    assert!(response.starts_with("http/1.1 200"), "{response}");
+    assert!(response.contains("content-type: application/grpc-web+proto"));
+    // grpc-web carries the trailers in the body.
+    assert!(response.contains("grpc-status:0"), "{response}");
+}
+
+#[tokio::test]
+async fn test_grpc_web_cors_preflight_honours_allowed_origins() {
+    // === 1. Arrange ===
+    let addr = start_web_gateway(&["https://dashboard.example"]).await;
+    let preflight = |origin: &str| {
+        format!(
+            "OPTIONS {GET_GATEWAY_INFO} HTTP/1.1\r\n\
+             Origin: {origin}\r\n\
+             Access-Control-Request-Method: POST\r\n\
+             Access-Control-Request-Headers: content-type,x-grpc-web,x-api-key\r\n"
+        )
+    };
+
+    // === 2. Act ===
+    let allowed = http1_request(addr, &preflight("https://dashboard.example"), &[]).await;
+    let rejected = http1_request(addr, &preflight("https://evil.example"), &[]).await;
+
+    // === 3. Assert ===
+    let allowed = String::from_utf8_lossy(&allowed).to_lowercase();
+    assert!(allowed.starts_with("http/1.1 200"), "{allowed}");
+    assert!(allowed.contains("access-control-allow-origin: https://dashboard.example"));
+    assert!(allowed.contains("x-api-key"));
+    let rejected = String::from_utf8_lossy(&rejected).to_lowercase();
+    assert!(
+        !rejected.contains("access-control-allow-origin"),
+        "{rejected}"
+    );
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "http")]
+
+mod common;
+
+use solana_sdk::pubkey::Pubkey;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use w3b2_solana_connector::pda::find_admin_profile_pda;
+use w3b2_solana_gateway::config::GatewayConfig;
+use w3b2_solana_gateway::http::build_server;
+
+/// Starts the HTTP/JSON bridge of a gateway configured with `config`.
+async fn start_http_gateway(config: GatewayConfig) -> SocketAddr {
+    let addr: SocketAddr = format!("127.0.0.1:{}", portpicker::pick_unused_port().unwrap())
+        .parse()
+        .unwrap();
+
+    let server = build_server(common::app_state(config), addr).unwrap();
+    tokio::spawn(server);
+
+    // Wait for the server to accept connections.
+    for _ in 0..50 {
+        if TcpStream::connect(addr).await.is_ok() {
+            return addr;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("gateway did not start listening on {addr}");
+}
+
+/// Sends an HTTP/1.1 request and returns the status code and the body of the response.
+async fn http_request(
+    addr: SocketAddr,
+    request_line: &str,
+    headers: &[&str],
+    body: &str,
+) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut request = format!("{request_line} HTTP/1.1\r\nHost: {addr}\r\n");
+    for header in headers {
+        request.push_str(&format!("{header}\r\n"));
+    }
+    request.push_str(&format!(
+        "Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    ));
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("the gateway should answer")
+        .unwrap();
+    let response = String::from_utf8(response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, body.to_string())
+}
+
+#[tokio::test]
+async fn test_unary_rpcs_are_served_as_json() {
+    // === 1. Arrange ===
+    let addr = start_http_gateway(GatewayConfig::default()).await;
+    let authority = Pubkey::new_unique();
+
+    // === 2. Act ===
+    let (info_status, info) = http_request(addr, "POST /v1/get_gateway_info", &[], "").await;
+    let (pda_status, pda) = http_request(
+        addr,
+        "POST /v1/derive_admin_pda",
+        &[],
+        &format!(r#"{{"authority_pubkey":"{authority}","index":1}}"#),
+    )
+    .await;
+
+    // === 3. Assert ===
+    assert_eq!(info_status, 200, "{info}");
+    let info: serde_json::Value = serde_json::from_str(&info).unwrap();
+    assert_eq!(
+        info["program_id"],
+        w3b2_solana_connector::PROGRAM_ID.to_string()
+    );
+    assert_eq!(info["gateway_version"], env!("CARGO_PKG_VERSION"));
+
+    assert_eq!(pda_status, 200, "{pda}");
+    let pda: serde_json::Value = serde_json::from_str(&pda).unwrap();
+    let (expected_pda, expected_bump) = find_admin_profile_pda(&authority, 1);
+    assert_eq!(pda["pda"], expected_pda.to_string());
+    assert_eq!(pda["bump"], expected_bump);
+}
+
+#[tokio::test]
+async fn test_failed_calls_map_to_http_status_codes() {
+    // === 1. Arrange ===
+    let mut config = GatewayConfig::default();
+    config.gateway.auth.api_keys = vec!["secret".to_string()];
+    let addr = start_http_gateway(config).await;
+
+    // === 2. Act ===
+    let (unauthenticated_status, unauthenticated) =
+        http_request(addr, "POST /v1/get_gateway_info", &[], "").await;
+    let (invalid_status, invalid) = http_request(
+        addr,
+        "POST /v1/derive_admin_pda",
+        &["x-api-key: secret"],
+        r#"{"authority_pubkey":"not-a-pubkey"}"#,
+    )
+    .await;
+    let (stream_status, stream) = http_request(
+        addr,
+        "GET /v1/stream_user_live_events?pda=not-a-pubkey",
+        &["x-api-key: secret"],
+        "",
+    )
+    .await;
+
+    // === 3. Assert ===
+    assert_eq!(unauthenticated_status, 401);
+    let unauthenticated: serde_json::Value = serde_json::from_str(&unauthenticated).unwrap();
+    assert_eq!(unauthenticated["code"], tonic::Code::Unauthenticated as i32);
+
+    assert_eq!(invalid_status, 400);
+    let invalid: serde_json::Value = serde_json::from_str(&invalid).unwrap();
+    assert_eq!(invalid["code"], tonic::Code::InvalidArgument as i32);
+    assert!(!invalid["message"].as_str().unwrap().is_empty());
+
+    // A stream that cannot be opened fails before any event is sent.
+    assert_eq!(stream_status, 400, "{stream}");
+}
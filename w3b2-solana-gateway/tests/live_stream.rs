@@ -0,0 +1,472 @@
+mod common;
+
+use solana_sdk::pubkey::Pubkey;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout};
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+use tonic::{Code, Request};
+use w3b2_solana_connector::events::{
+    BridgeEvent, BridgeEventData, CaughtUp, EventSource, Retracted,
+};
+use w3b2_solana_gateway::auth::Principal;
+use w3b2_solana_gateway::config::GatewayConfig;
+use w3b2_solana_gateway::grpc::proto::w3b2::protocol::gateway::bridge_gateway_service_server::BridgeGatewayService;
+use w3b2_solana_gateway::grpc::proto::w3b2::protocol::gateway::{
+    bridge_event::Event, EventSource as GatewayEventSource, EventStreamItem,
+};
+use w3b2_solana_gateway::grpc::{
+    handle_live_stream, handle_resumed_stream, handle_tail_stream, AppState, GatewayServer,
+};
+
+/// Builds an `AppState` whose live events are fed directly through the upstream passed
+/// to `handle_live_stream`.
+fn app_state(heartbeat_interval_secs: u64) -> AppState {
+    let mut config = GatewayConfig::default();
+    config.gateway.grpc.heartbeat_interval_secs = heartbeat_interval_secs;
+    common::app_state(config)
+}
+
+fn event(pda: Pubkey, slot: u64) -> BridgeEvent {
+    BridgeEvent {
+        source: EventSource::Live,
+        signature: None,
+        slot,
+        data: BridgeEventData::CaughtUp(CaughtUp { pda, slot }),
+    }
+}
+
+/// An event of the transaction `signature`. Its payload does not matter to the streams.
+fn signed_event(source: EventSource, signature: &str, slot: u64) -> BridgeEvent {
+    BridgeEvent {
+        source,
+        signature: Some(signature.to_string()),
+        slot,
+        data: BridgeEventData::Retracted(Retracted {
+            signature: signature.to_string(),
+            pdas: Vec::new(),
+        }),
+    }
+}
+
+fn is_heartbeat(item: &EventStreamItem) -> bool {
+    matches!(
+        item.event.as_ref().and_then(|event| event.event.as_ref()),
+        Some(Event::Heartbeat(_))
+    )
+}
+
+#[tokio::test]
+async fn test_heartbeats_are_sent_only_while_the_stream_is_idle() {
+    // === 1. Arrange ===
+    let state = app_state(1);
+    let pda = Pubkey::new_unique();
+    let (upstream_tx, upstream_rx) = mpsc::channel(16);
+    let permit = state.stream_limiter.acquire("alice".to_string()).unwrap();
+    let mut stream = handle_live_stream(
+        &state,
+        pda,
+        "alice".to_string(),
+        || Ok(ReceiverStream::new(upstream_rx)),
+        permit,
+    )
+    .await
+    .unwrap()
+    .into_inner();
+
+    // === 2. Act & Assert: silence produces a heartbeat ===
+    let item = timeout(Duration::from_secs(3), stream.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(is_heartbeat(&item));
+    assert!(item.signature.is_empty());
+
+    // === 3. Act & Assert: a steady flow of events suppresses heartbeats ===
+    let producer = tokio::spawn(async move {
+        for slot in 1..=10 {
+            upstream_tx.send(event(pda, slot)).await.unwrap();
+            sleep(Duration::from_millis(250)).await;
+        }
+        upstream_tx
+    });
+    for slot in 1..=10 {
+        let item = timeout(Duration::from_secs(3), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(
+            !is_heartbeat(&item),
+            "unexpected heartbeat while events were flowing"
+        );
+        assert_eq!(item.slot, slot);
+    }
+    let _upstream_tx = producer.await.unwrap();
+
+    // === 4. Act & Assert: heartbeats resume once the stream is idle again ===
+    let item = timeout(Duration::from_secs(3), stream.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(is_heartbeat(&item));
+    println!("✅ Heartbeats are sent only while the stream is idle.");
+}
+
+#[tokio::test]
+async fn test_zero_heartbeat_interval_disables_heartbeats() {
+    // === 1. Arrange ===
+    let state = app_state(0);
+    let (_upstream_tx, upstream_rx) = mpsc::channel::<BridgeEvent>(16);
+    let permit = state.stream_limiter.acquire("alice".to_string()).unwrap();
+    let mut stream = handle_live_stream(
+        &state,
+        Pubkey::new_unique(),
+        "alice".to_string(),
+        || Ok(ReceiverStream::new(upstream_rx)),
+        permit,
+    )
+    .await
+    .unwrap()
+    .into_inner();
+
+    // === 2. Act ===
+    let item = timeout(Duration::from_millis(1500), stream.next()).await;
+
+    // === 3. Assert ===
+    assert!(item.is_err(), "no item should be sent on an idle stream");
+}
+
+#[tokio::test]
+async fn test_tail_replays_the_last_events_before_going_live() {
+    // === 1. Arrange ===
+    let state = app_state(0);
+    let pda = Pubkey::new_unique();
+    let mut history: Vec<BridgeEvent> = (1..=5)
+        .map(|slot| signed_event(EventSource::Catchup, &format!("sig{slot}"), slot))
+        .collect();
+    history.push(event(pda, 5));
+    let (upstream_tx, upstream_rx) = mpsc::channel(16);
+    let permit = state.stream_limiter.acquire("alice".to_string()).unwrap();
+    let mut stream = handle_tail_stream(
+        &state,
+        pda,
+        "alice".to_string(),
+        || Ok(ReceiverStream::new(upstream_rx)),
+        tokio_stream::iter(history),
+        3,
+        permit,
+    )
+    .await
+    .unwrap()
+    .into_inner();
+
+    // === 2. Act ===
+    // The newest historical transaction is also seen live, followed by a new one.
+    upstream_tx
+        .send(signed_event(EventSource::Live, "sig5", 5))
+        .await
+        .unwrap();
+    upstream_tx
+        .send(signed_event(EventSource::Live, "sig6", 6))
+        .await
+        .unwrap();
+    let mut items = Vec::new();
+    for _ in 0..4 {
+        let item = timeout(Duration::from_secs(3), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        items.push(item);
+    }
+
+    // === 3. Assert ===
+    let received: Vec<(String, u64)> = items
+        .iter()
+        .map(|item| (item.signature.clone(), item.slot))
+        .collect();
+    assert_eq!(
+        received,
+        [("sig3", 3), ("sig4", 4), ("sig5", 5), ("sig6", 6)].map(|(s, slot)| (s.to_string(), slot))
+    );
+    let sources: Vec<i32> = items.iter().map(|item| item.source).collect();
+    assert_eq!(
+        sources,
+        [
+            GatewayEventSource::Catchup,
+            GatewayEventSource::Catchup,
+            GatewayEventSource::Catchup,
+            GatewayEventSource::Live
+        ]
+        .map(|source| source as i32)
+    );
+    assert!(timeout(Duration::from_millis(300), stream.next())
+        .await
+        .is_err());
+}
+
+/// Receives the next `n` items of `stream`.
+async fn next_items(
+    stream: &mut ReceiverStream<Result<EventStreamItem, tonic::Status>>,
+    n: usize,
+) -> Vec<EventStreamItem> {
+    let mut items = Vec::new();
+    for _ in 0..n {
+        let item = timeout(Duration::from_secs(3), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        items.push(item);
+    }
+    items
+}
+
+#[tokio::test]
+async fn test_resumed_stream_delivers_every_event_exactly_once() {
+    // === 1. Arrange ===
+    let state = app_state(0);
+    let pda = Pubkey::new_unique();
+    let (upstream_tx, upstream_rx) = mpsc::channel(16);
+    let permit = state.stream_limiter.acquire("alice".to_string()).unwrap();
+    let mut first = handle_live_stream(
+        &state,
+        pda,
+        "alice".to_string(),
+        || Ok(ReceiverStream::new(upstream_rx)),
+        permit,
+    )
+    .await
+    .unwrap()
+    .into_inner();
+    for slot in 1..=3 {
+        upstream_tx
+            .send(signed_event(EventSource::Live, &format!("sig{slot}"), slot))
+            .await
+            .unwrap();
+    }
+    let before_disconnect = next_items(&mut first, 3).await;
+    drop(first);
+    drop(upstream_tx);
+    // Wait for the first stream to release the shared upstream of the PDA.
+    while !state.subscriptions.list().is_empty() {
+        sleep(Duration::from_millis(10)).await;
+    }
+
+    // === 2. Act ===
+    // `sig4` lands while the client is away. It is in the history, and is still in flight
+    // on the live upstream when the client resumes from the last slot it processed.
+    let since_slot = before_disconnect.last().unwrap().slot;
+    let mut history: Vec<BridgeEvent> = (1..=4)
+        .map(|slot| signed_event(EventSource::Catchup, &format!("sig{slot}"), slot))
+        .collect();
+    history.push(event(pda, 4));
+    let (upstream_tx, upstream_rx) = mpsc::channel(16);
+    let permit = state.stream_limiter.acquire("alice".to_string()).unwrap();
+    let mut resumed = handle_resumed_stream(
+        &state,
+        pda,
+        "alice".to_string(),
+        || Ok(ReceiverStream::new(upstream_rx)),
+        tokio_stream::iter(history),
+        since_slot,
+        permit,
+    )
+    .await
+    .unwrap()
+    .into_inner();
+    for (signature, slot) in [("sig3", 3), ("sig4", 4), ("sig5", 5)] {
+        upstream_tx
+            .send(signed_event(EventSource::Live, signature, slot))
+            .await
+            .unwrap();
+    }
+    let after_reconnect = next_items(&mut resumed, 3).await;
+
+    // === 3. Assert ===
+    assert_eq!(since_slot, 3);
+    let Some(Event::Resumed(ack)) = after_reconnect[1]
+        .event
+        .as_ref()
+        .and_then(|event| event.event.as_ref())
+    else {
+        panic!(
+            "expected the resume acknowledgement, got {:?}",
+            after_reconnect[1]
+        );
+    };
+    assert_eq!(ack.pda, pda.to_string());
+    assert_eq!(ack.since_slot, 3);
+    assert_eq!(ack.slot, 4);
+
+    let signatures: Vec<&str> = before_disconnect
+        .iter()
+        .chain(&after_reconnect)
+        .filter(|item| !item.signature.is_empty())
+        .map(|item| item.signature.as_str())
+        .collect();
+    assert_eq!(signatures, ["sig1", "sig2", "sig3", "sig4", "sig5"]);
+    assert_eq!(
+        after_reconnect[0].source,
+        GatewayEventSource::Catchup as i32
+    );
+    assert_eq!(after_reconnect[2].source, GatewayEventSource::Live as i32);
+    assert!(timeout(Duration::from_millis(300), resumed.next())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_resumed_stream_fails_on_an_incomplete_history() {
+    // === 1. Arrange ===
+    let state = app_state(0);
+    let pda = Pubkey::new_unique();
+    let (_upstream_tx, upstream_rx) = mpsc::channel::<BridgeEvent>(16);
+    // The replay failed, so the history ends without its `CaughtUp` marker.
+    let history = vec![signed_event(EventSource::Catchup, "sig4", 4)];
+    let permit = state.stream_limiter.acquire("alice".to_string()).unwrap();
+
+    // === 2. Act ===
+    let mut stream = handle_resumed_stream(
+        &state,
+        pda,
+        "alice".to_string(),
+        || Ok(ReceiverStream::new(upstream_rx)),
+        tokio_stream::iter(history),
+        3,
+        permit,
+    )
+    .await
+    .unwrap()
+    .into_inner();
+    let item = timeout(Duration::from_secs(3), stream.next())
+        .await
+        .unwrap()
+        .unwrap();
+
+    // === 3. Assert ===
+    assert_eq!(item.unwrap_err().code(), Code::Unavailable);
+}
+
+#[tokio::test]
+async fn test_client_can_resubscribe_right_after_its_stream_drops() {
+    // === 1. Arrange ===
+    let mut config = GatewayConfig::default();
+    config.gateway.rate_limit.max_streams_per_client = 1;
+    let state = common::app_state(config);
+    let pda = Pubkey::new_unique();
+    let (_upstream_tx, upstream_rx) = mpsc::channel::<BridgeEvent>(16);
+    let permit = state.stream_limiter.acquire("alice".to_string()).unwrap();
+    let stream = handle_live_stream(
+        &state,
+        pda,
+        "alice".to_string(),
+        || Ok(ReceiverStream::new(upstream_rx)),
+        permit,
+    )
+    .await
+    .unwrap()
+    .into_inner();
+
+    // === 2. Act ===
+    // The connection drops. The stream's task has not run since, so it still holds the
+    // client's only slot when the client reconnects.
+    drop(stream);
+    let permit = state.stream_limiter.acquire("alice".to_string());
+
+    // === 3. Assert ===
+    let permit = permit.expect("the stale stream should give up its slot");
+    assert_eq!(state.stream_limiter.open_streams("alice"), 1);
+    // A stream that is still connected keeps its slot.
+    let (_upstream_tx, upstream_rx) = mpsc::channel::<BridgeEvent>(16);
+    let _stream = handle_live_stream(
+        &state,
+        pda,
+        "alice".to_string(),
+        || Ok(ReceiverStream::new(upstream_rx)),
+        permit,
+    )
+    .await
+    .unwrap();
+    assert!(state.stream_limiter.acquire("alice".to_string()).is_err());
+}
+
+#[tokio::test]
+async fn test_admin_lists_and_stops_all_live_streams() {
+    // === 1. Arrange ===
+    let mut config = GatewayConfig::default();
+    config.gateway.grpc.heartbeat_interval_secs = 0;
+    config.gateway.auth.admin_principals = vec!["ops".to_string()];
+    let state = common::app_state(config);
+    let shared_pda = Pubkey::new_unique();
+    let other_pda = Pubkey::new_unique();
+    let mut upstreams = Vec::new();
+    let mut streams = Vec::new();
+    for (client, pda) in [
+        ("alice", shared_pda),
+        ("bob", shared_pda),
+        ("carol", other_pda),
+    ] {
+        let (upstream_tx, upstream_rx) = mpsc::channel::<BridgeEvent>(16);
+        let permit = state.stream_limiter.acquire(client.to_string()).unwrap();
+        let stream = handle_live_stream(
+            &state,
+            pda,
+            client.to_string(),
+            || Ok(ReceiverStream::new(upstream_rx)),
+            permit,
+        )
+        .await
+        .unwrap()
+        .into_inner();
+        upstreams.push(upstream_tx);
+        streams.push(stream);
+    }
+    let server = GatewayServer::new(state.clone());
+    let request_as = |name: &str| {
+        let mut request = Request::new(());
+        request
+            .extensions_mut()
+            .insert(Principal::Named(name.to_string()));
+        request
+    };
+
+    // === 2. Act ===
+    let anonymous = server.list_subscriptions(Request::new(())).await;
+    let not_an_admin = server.unsubscribe_all(request_as("alice")).await;
+    let mut listed = server
+        .list_subscriptions(request_as("ops"))
+        .await
+        .unwrap()
+        .into_inner()
+        .subscriptions;
+    let stopped = server
+        .unsubscribe_all(request_as("ops"))
+        .await
+        .unwrap()
+        .into_inner();
+
+    // === 3. Assert ===
+    assert_eq!(anonymous.unwrap_err().code(), Code::PermissionDenied);
+    assert_eq!(not_an_admin.unwrap_err().code(), Code::PermissionDenied);
+    listed.sort_by_key(|subscription| subscription.client_streams);
+    let listed: Vec<(String, u32)> = listed
+        .into_iter()
+        .map(|subscription| (subscription.pda, subscription.client_streams))
+        .collect();
+    assert_eq!(
+        listed,
+        [(other_pda.to_string(), 1), (shared_pda.to_string(), 2)]
+    );
+    assert_eq!(stopped.stopped_streams, 3);
+    for mut stream in streams {
+        let item = timeout(Duration::from_secs(1), stream.next())
+            .await
+            .unwrap();
+        assert!(item.is_none(), "the stream should have been closed");
+    }
+    assert!(state.subscriptions.list().is_empty());
+}
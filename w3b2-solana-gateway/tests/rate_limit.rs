@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::{Code, Request};
+use w3b2_solana_gateway::config::RateLimitConfig;
+use w3b2_solana_gateway::rate_limit::{
+    client_key, interceptor, RateLimiter, StreamLimiter, API_KEY_HEADER,
+};
+
+fn rate_limit_config(
+    requests_per_second: u32,
+    burst: u32,
+    max_streams_per_client: usize,
+) -> RateLimitConfig {
+    RateLimitConfig {
+        requests_per_second,
+        burst,
+        max_streams_per_client,
+    }
+}
+
+fn request_with_api_key(api_key: &str) -> Request<()> {
+    let mut request = Request::new(());
+    request
+        .metadata_mut()
+        .insert(API_KEY_HEADER, api_key.parse().unwrap());
+    request
+}
+
+#[test]
+fn test_request_rate_limit_is_enforced_per_client() {
+    // === 1. Arrange ===
+    let limiter = Arc::new(RateLimiter::new(&rate_limit_config(1, 3, 1)));
+    let mut intercept = interceptor(limiter);
+
+    // === 2. Act ===
+    let within_burst = (0..3).all(|_| intercept(request_with_api_key("alice")).is_ok());
+    let over_limit = intercept(request_with_api_key("alice"));
+    let other_client = intercept(request_with_api_key("bob"));
+
+    // === 3. Assert ===
+    assert!(within_burst);
+    assert_eq!(over_limit.unwrap_err().code(), Code::ResourceExhausted);
+    assert!(other_client.is_ok());
+}
+
+#[test]
+fn test_request_rate_limit_refills_over_time() {
+    // === 1. Arrange ===
+    let limiter = RateLimiter::new(&rate_limit_config(100, 1, 1));
+    limiter.check("alice").unwrap();
+    assert_eq!(
+        limiter.check("alice").unwrap_err().code(),
+        Code::ResourceExhausted
+    );
+
+    // === 2. Act ===
+    std::thread::sleep(Duration::from_millis(20));
+
+    // === 3. Assert ===
+    assert!(limiter.check("alice").is_ok());
+}
+
+#[test]
+fn test_zero_requests_per_second_disables_the_limit() {
+    let limiter = RateLimiter::new(&rate_limit_config(0, 1, 1));
+    assert!((0..100).all(|_| limiter.check("alice").is_ok()));
+}
+
+#[test]
+fn test_stream_limit_is_enforced_and_released_on_drop() {
+    // === 1. Arrange ===
+    let limiter = Arc::new(StreamLimiter::new(&rate_limit_config(1, 1, 2)));
+    let client = client_key(&request_with_api_key("alice"));
+
+    // === 2. Act ===
+    let first = limiter.acquire(client.clone()).unwrap();
+    let _second = limiter.acquire(client.clone()).unwrap();
+    let over_limit = limiter.acquire(client.clone());
+    let other_client = limiter.acquire(client_key(&request_with_api_key("bob")));
+    drop(first);
+    let after_release = limiter.acquire(client.clone());
+
+    // === 3. Assert ===
+    assert_eq!(over_limit.err().unwrap().code(), Code::ResourceExhausted);
+    assert!(other_client.is_ok());
+    assert!(after_release.is_ok());
+    assert_eq!(limiter.open_streams(&client), 2);
+}
+
+#[test]
+fn test_partial_rate_limit_section_keeps_the_other_defaults() {
+    // === 1. Arrange ===
+    let section = config::File::from_str("requests-per-second = 5", config::FileFormat::Toml);
+
+    // === 2. Act ===
+    let parsed: RateLimitConfig = config::Config::builder()
+        .add_source(section)
+        .build()
+        .unwrap()
+        .try_deserialize()
+        .unwrap();
+
+    // === 3. Assert ===
+    let defaults = RateLimitConfig::default();
+    assert_eq!(parsed.requests_per_second, 5);
+    assert_eq!(parsed.burst, defaults.burst);
+    assert_eq!(
+        parsed.max_streams_per_client,
+        defaults.max_streams_per_client
+    );
+}
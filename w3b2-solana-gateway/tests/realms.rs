@@ -0,0 +1,78 @@
+mod common;
+
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tonic::{Code, Request};
+use w3b2_solana_connector::events::EventKind;
+use w3b2_solana_connector::storage::Storage;
+use w3b2_solana_gateway::config::{GatewayConfig, RealmConfig};
+use w3b2_solana_gateway::grpc::proto::w3b2::protocol::gateway::bridge_gateway_service_server::BridgeGatewayService;
+use w3b2_solana_gateway::grpc::proto::w3b2::protocol::gateway::GetEventCountsRequest;
+use w3b2_solana_gateway::grpc::{GatewayServer, REALM_METADATA_KEY};
+
+/// Adds the `x-realm` metadata entry to `request`.
+fn in_realm<T>(mut request: Request<T>, realm: &str) -> Request<T> {
+    request
+        .metadata_mut()
+        .insert(REALM_METADATA_KEY, realm.parse().unwrap());
+    request
+}
+
+#[tokio::test]
+async fn test_requests_are_routed_to_their_realm() {
+    // === 1. Arrange ===
+    let mut config = GatewayConfig::default();
+    config.connector.solana.rpc_url = "https://mainnet.example.com".to_string();
+    let mut devnet = RealmConfig {
+        db_path: "./w3b2_gateway_devnet.db".to_string(),
+        connector: Default::default(),
+    };
+    devnet.connector.solana.rpc_url = "https://devnet.example.com".to_string();
+    let devnet_state = common::app_state(config.for_realm(&devnet));
+
+    // The same PDA has events on devnet only.
+    let pda = Pubkey::new_unique();
+    devnet_state
+        .storage
+        .count_events(&[(pda, EventKind::UserFundsDeposited)])
+        .await
+        .unwrap();
+
+    let mut state = common::app_state(config);
+    state.realms = Arc::new(HashMap::from([("devnet".to_string(), devnet_state)]));
+    let server = GatewayServer::new(state);
+    let counts = |realm: Option<&str>| {
+        let request = Request::new(GetEventCountsRequest {
+            pda: pda.to_string(),
+        });
+        let request = match realm {
+            Some(realm) => in_realm(request, realm),
+            None => request,
+        };
+        server.get_event_counts(request)
+    };
+
+    // === 2. Act ===
+    let default_info = server.get_gateway_info(Request::new(())).await.unwrap();
+    let devnet_info = server
+        .get_gateway_info(in_realm(Request::new(()), "devnet"))
+        .await
+        .unwrap();
+    let default_counts = counts(None).await.unwrap().into_inner();
+    let devnet_counts = counts(Some("devnet")).await.unwrap().into_inner();
+    let unknown = counts(Some("testnet")).await;
+
+    // === 3. Assert ===
+    assert_eq!(
+        default_info.into_inner().rpc_cluster,
+        "https://mainnet.example.com"
+    );
+    assert_eq!(
+        devnet_info.into_inner().rpc_cluster,
+        "https://devnet.example.com"
+    );
+    assert!(default_counts.counts.is_empty());
+    assert_eq!(devnet_counts.counts.get("UserFundsDeposited"), Some(&1));
+    assert_eq!(unknown.unwrap_err().code(), Code::InvalidArgument);
+}
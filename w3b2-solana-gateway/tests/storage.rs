@@ -0,0 +1,188 @@
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use w3b2_solana_connector::events::EventKind;
+use w3b2_solana_connector::storage::{CachedEvent, Storage};
+use w3b2_solana_gateway::storage::SledStorage;
+
+fn temporary_storage(max_events_per_pda: usize) -> SledStorage {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    SledStorage::new(db)
+        .with_event_cache(max_events_per_pda)
+        .unwrap()
+}
+
+fn cached_event(slot: u64, signature: &str) -> CachedEvent {
+    CachedEvent {
+        slot,
+        signature: signature.to_string(),
+        data: vec![slot as u8; 16],
+    }
+}
+
+#[tokio::test]
+async fn test_event_cache_round_trip_and_eviction() {
+    // === 1. Arrange ===
+    let storage = temporary_storage(100);
+    let pda = Pubkey::new_unique();
+    let first = [
+        cached_event(5, "a"),
+        cached_event(5, "a"),
+        cached_event(7, "b"),
+    ];
+    let second = [cached_event(9, "c")];
+
+    // === 2. Act ===
+    storage
+        .cache_events(&pda, None, &first, (7, "b"))
+        .await
+        .unwrap();
+    // A concurrent replay that started from an empty cache must not append a duplicate range.
+    storage
+        .cache_events(&pda, None, &first, (7, "b"))
+        .await
+        .unwrap();
+    storage
+        .cache_events(&pda, Some((7, "b")), &second, (12, "d"))
+        .await
+        .unwrap();
+    let history = storage.get_cached_events(&pda).await.unwrap().unwrap();
+    storage.evict_cached_events(&[pda], "a").await.unwrap();
+    let evicted = storage.get_cached_events(&pda).await.unwrap().unwrap();
+
+    // === 3. Assert ===
+    assert_eq!(history.events, [&first[..], &second[..]].concat());
+    assert_eq!(history.cursor, Some((12, "d".to_string())));
+    assert_eq!(
+        evicted.events,
+        vec![cached_event(7, "b"), cached_event(9, "c")]
+    );
+    assert!(storage
+        .get_cached_events(&Pubkey::new_unique())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_event_cache_keeps_the_newest_events_over_the_limit() {
+    // === 1. Arrange ===
+    let storage = temporary_storage(3);
+    let pda = Pubkey::new_unique();
+
+    // === 2. Act ===
+    storage
+        .cache_events(&pda, None, &[cached_event(1, "a")], (1, "a"))
+        .await
+        .unwrap();
+    let within_limit = storage.get_cached_events(&pda).await.unwrap().unwrap();
+    // The two events of "b" are evicted together, so the history never starts inside it.
+    storage
+        .cache_events(
+            &pda,
+            Some((1, "a")),
+            &[
+                cached_event(2, "b"),
+                cached_event(2, "b"),
+                cached_event(3, "c"),
+            ],
+            (3, "c"),
+        )
+        .await
+        .unwrap();
+    storage
+        .cache_events(&pda, Some((3, "c")), &[cached_event(4, "d")], (4, "d"))
+        .await
+        .unwrap();
+    let over_limit = storage.get_cached_events(&pda).await.unwrap().unwrap();
+
+    // === 3. Assert ===
+    assert!(!within_limit.truncated);
+    assert_eq!(
+        over_limit.events,
+        vec![cached_event(3, "c"), cached_event(4, "d")]
+    );
+    assert_eq!(over_limit.cursor, Some((4, "d".to_string())));
+    assert!(over_limit.truncated);
+}
+
+#[tokio::test]
+async fn test_event_counts_survive_a_restart() {
+    // === 1. Arrange ===
+    let dir = tempfile::tempdir().unwrap();
+    // Without the background flusher, dropping the database releases its lock right away.
+    let open = || {
+        sled::Config::new()
+            .path(dir.path())
+            .flush_every_ms(None)
+            .open()
+            .unwrap()
+    };
+    let user_pda = Pubkey::new_unique();
+    let admin_pda = Pubkey::new_unique();
+
+    // === 2. Act ===
+    {
+        let storage = SledStorage::new(open());
+        storage
+            .count_events(&[
+                (user_pda, EventKind::UserFundsDeposited),
+                (user_pda, EventKind::UserFundsDeposited),
+                (user_pda, EventKind::UserBanned),
+                (admin_pda, EventKind::UserBanned),
+            ])
+            .await
+            .unwrap();
+        storage
+            .count_events(&[(user_pda, EventKind::UserFundsDeposited)])
+            .await
+            .unwrap();
+        storage.set_sync_state(1, "a").await.unwrap();
+    }
+    let reopened = SledStorage::new(open());
+
+    // === 3. Assert ===
+    assert_eq!(
+        reopened.get_event_counts(&user_pda).await.unwrap(),
+        HashMap::from([
+            (EventKind::UserFundsDeposited, 3),
+            (EventKind::UserBanned, 1)
+        ])
+    );
+    assert_eq!(
+        reopened.get_event_counts(&admin_pda).await.unwrap(),
+        HashMap::from([(EventKind::UserBanned, 1)])
+    );
+    assert!(reopened
+        .get_event_counts(&Pubkey::new_unique())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_active_pdas_survive_a_restart() {
+    // === 1. Arrange ===
+    let dir = tempfile::tempdir().unwrap();
+    let open = || {
+        sled::Config::new()
+            .path(dir.path())
+            .flush_every_ms(None)
+            .open()
+            .unwrap()
+    };
+    let kept = Pubkey::new_unique();
+    let removed = Pubkey::new_unique();
+
+    // === 2. Act ===
+    {
+        let storage = SledStorage::new(open());
+        storage.add_active_pda(&kept).await.unwrap();
+        storage.add_active_pda(&kept).await.unwrap();
+        storage.add_active_pda(&removed).await.unwrap();
+        storage.remove_active_pda(&removed).await.unwrap();
+    }
+    let reopened = SledStorage::new(open());
+
+    // === 3. Assert ===
+    assert_eq!(reopened.list_active_pdas().await.unwrap(), vec![kept]);
+}
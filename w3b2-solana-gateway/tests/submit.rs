@@ -0,0 +1,54 @@
+use solana_client::client_error::ClientError;
+use solana_sdk::transaction::TransactionError;
+use tonic::{Code, Status};
+use w3b2_solana_connector::client::SubmitError;
+use w3b2_solana_gateway::error::{
+    GatewayError, BLOCKHASH_EXPIRED_REASON, ERROR_REASON_METADATA_KEY, SUBMIT_ATTEMPTS_METADATA_KEY,
+};
+
+fn submit_error(source: ClientError, blockhash_expired: bool) -> SubmitError {
+    SubmitError {
+        attempts: 3,
+        source,
+        blockhash_expired,
+    }
+}
+
+fn metadata<'a>(status: &'a Status, key: &str) -> Option<&'a str> {
+    status
+        .metadata()
+        .get(key)
+        .map(|value| value.to_str().unwrap())
+}
+
+#[test]
+fn test_expired_blockhash_maps_to_failed_precondition() {
+    // === 1. Arrange ===
+    let err = submit_error(TransactionError::BlockhashNotFound.into(), true);
+
+    // === 2. Act ===
+    let status = Status::from(GatewayError::from(err));
+
+    // === 3. Assert ===
+    assert_eq!(status.code(), Code::FailedPrecondition);
+    assert_eq!(
+        metadata(&status, ERROR_REASON_METADATA_KEY),
+        Some(BLOCKHASH_EXPIRED_REASON)
+    );
+    assert_eq!(metadata(&status, SUBMIT_ATTEMPTS_METADATA_KEY), Some("3"));
+}
+
+#[test]
+fn test_other_submit_failures_keep_their_status() {
+    let lagging = submit_error(TransactionError::BlockhashNotFound.into(), false);
+    let rejected = submit_error(TransactionError::AlreadyProcessed.into(), false);
+
+    let lagging = Status::from(GatewayError::from(lagging));
+    let rejected = Status::from(GatewayError::from(rejected));
+
+    // A node that does not know a valid blockhash is lagging, so the client may retry.
+    assert_eq!(lagging.code(), Code::Unavailable);
+    assert_eq!(rejected.code(), Code::Internal);
+    assert_eq!(metadata(&rejected, ERROR_REASON_METADATA_KEY), None);
+    assert_eq!(metadata(&rejected, SUBMIT_ATTEMPTS_METADATA_KEY), Some("3"));
+}
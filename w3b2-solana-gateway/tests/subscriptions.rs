@@ -0,0 +1,231 @@
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::Code;
+use w3b2_solana_connector::events::{BridgeEvent, BridgeEventData, CaughtUp, EventSource};
+use w3b2_solana_gateway::config::LimitsConfig;
+use w3b2_solana_gateway::subscriptions::{Subscription, SubscriptionRegistry};
+
+fn event(pda: Pubkey, slot: u64) -> BridgeEvent {
+    BridgeEvent {
+        source: EventSource::Live,
+        signature: None,
+        slot,
+        data: BridgeEventData::CaughtUp(CaughtUp { pda, slot }),
+    }
+}
+
+/// Subscribes to `pda` with an upstream that is never fed.
+#[allow(clippy::result_large_err)]
+fn subscribe_idle(
+    registry: &Arc<SubscriptionRegistry>,
+    pda: Pubkey,
+    client: &str,
+) -> Result<Subscription, tonic::Status> {
+    registry.subscribe(pda, client.to_string(), || {
+        Ok(ReceiverStream::new(mpsc::channel::<BridgeEvent>(1).1))
+    })
+}
+
+async fn next_slot(subscription: &mut Subscription) -> u64 {
+    let event = timeout(Duration::from_secs(1), subscription.recv())
+        .await
+        .unwrap();
+    event.unwrap().unwrap().slot
+}
+
+#[tokio::test]
+async fn test_subscription_limit_is_enforced_and_released_on_drop() {
+    // === 1. Arrange ===
+    let registry = Arc::new(SubscriptionRegistry::new(2, 16));
+    let pda = Pubkey::new_unique();
+    let first = subscribe_idle(&registry, pda, "alice").unwrap();
+    let _second = subscribe_idle(&registry, pda, "bob").unwrap();
+
+    // === 2. Act ===
+    let over_limit = subscribe_idle(&registry, Pubkey::new_unique(), "carol");
+    drop(first);
+    let after_release = subscribe_idle(&registry, Pubkey::new_unique(), "carol");
+
+    // === 3. Assert ===
+    assert_eq!(over_limit.err().unwrap().code(), Code::ResourceExhausted);
+    assert!(after_release.is_ok());
+    assert_eq!(registry.len(), 2);
+}
+
+#[tokio::test]
+async fn test_failed_upstream_gives_back_its_slot() {
+    // === 1. Arrange ===
+    let registry = Arc::new(SubscriptionRegistry::new(1, 16));
+    let pda = Pubkey::new_unique();
+
+    // === 2. Act ===
+    let failed = registry.subscribe(pda, "alice".to_string(), || {
+        Err::<ReceiverStream<BridgeEvent>, _>(tonic::Status::resource_exhausted(
+            "the dispatcher already serves its maximum of 1 listeners",
+        ))
+    });
+    let retried = subscribe_idle(&registry, pda, "alice");
+
+    // === 3. Assert ===
+    assert_eq!(failed.err().unwrap().code(), Code::ResourceExhausted);
+    assert!(retried.is_ok());
+    assert_eq!(registry.upstream_count(), 1);
+}
+
+#[tokio::test]
+async fn test_clients_share_one_upstream_per_pda() {
+    // === 1. Arrange ===
+    let registry = Arc::new(SubscriptionRegistry::new(10, 16));
+    let pda = Pubkey::new_unique();
+    let (upstream_tx, upstream_rx) = mpsc::channel(16);
+    let mut upstream_rx = Some(upstream_rx);
+    let mut open_upstream = || {
+        Ok::<_, tonic::Status>(ReceiverStream::new(
+            upstream_rx.take().expect("upstream opened twice"),
+        ))
+    };
+
+    // === 2. Act ===
+    let mut first = registry
+        .subscribe(pda, "alice".to_string(), &mut open_upstream)
+        .unwrap();
+    let mut second = registry
+        .subscribe(pda, "bob".to_string(), &mut open_upstream)
+        .unwrap();
+    upstream_tx.send(event(pda, 7)).await.unwrap();
+
+    // === 3. Assert ===
+    assert_eq!(next_slot(&mut first).await, 7);
+    assert_eq!(next_slot(&mut second).await, 7);
+    assert_eq!(registry.len(), 2);
+    assert_eq!(registry.upstream_count(), 1);
+
+    // The upstream is torn down only when the last client leaves.
+    drop(first);
+    assert!(!upstream_tx.is_closed());
+    drop(second);
+    timeout(Duration::from_secs(1), upstream_tx.closed())
+        .await
+        .unwrap();
+    assert_eq!(registry.upstream_count(), 0);
+    assert!(registry.is_empty());
+}
+
+#[tokio::test]
+async fn test_unsubscribe_only_stops_the_callers_streams() {
+    // === 1. Arrange ===
+    let registry = Arc::new(SubscriptionRegistry::new(10, 16));
+    let pda = Pubkey::new_unique();
+    let (upstream_tx, upstream_rx) = mpsc::channel(16);
+    let mut alice = registry
+        .subscribe(pda, "alice".to_string(), || {
+            Ok(ReceiverStream::new(upstream_rx))
+        })
+        .unwrap();
+    let mut bob = subscribe_idle(&registry, pda, "bob").unwrap();
+
+    // === 2. Act ===
+    let stopped = registry.unsubscribe(&pda, "alice");
+    let alice_next = timeout(Duration::from_secs(1), alice.recv()).await.unwrap();
+    drop(alice);
+    upstream_tx.send(event(pda, 9)).await.unwrap();
+
+    // === 3. Assert ===
+    assert!(stopped);
+    assert!(alice_next.is_none());
+    assert_eq!(next_slot(&mut bob).await, 9);
+    assert!(!registry.unsubscribe(&pda, "alice"));
+    assert!(!registry.unsubscribe(&Pubkey::new_unique(), "bob"));
+    assert_eq!(registry.len(), 1);
+}
+
+#[tokio::test]
+async fn test_client_streams_close_when_the_upstream_ends() {
+    // === 1. Arrange ===
+    let registry = Arc::new(SubscriptionRegistry::new(10, 16));
+    let pda = Pubkey::new_unique();
+    let (upstream_tx, upstream_rx) = mpsc::channel(16);
+    let mut subscription = registry
+        .subscribe(pda, "alice".to_string(), || {
+            Ok(ReceiverStream::new(upstream_rx))
+        })
+        .unwrap();
+
+    // === 2. Act ===
+    drop(upstream_tx);
+    let next = timeout(Duration::from_secs(1), subscription.recv())
+        .await
+        .unwrap();
+
+    // === 3. Assert ===
+    assert!(matches!(
+        next,
+        Some(Err(tokio::sync::broadcast::error::RecvError::Closed))
+    ));
+    assert_eq!(registry.upstream_count(), 0);
+    drop(subscription);
+    assert!(registry.is_empty());
+}
+
+#[tokio::test]
+async fn test_resubscribe_replaces_a_crashed_upstream() {
+    // === 1. Arrange ===
+    let registry = Arc::new(SubscriptionRegistry::new(10, 16));
+    let pda = Pubkey::new_unique();
+    let mut crashed = registry
+        .subscribe(pda, "alice".to_string(), || {
+            Ok(futures::stream::poll_fn(|_| -> Poll<Option<BridgeEvent>> {
+                panic!("upstream listener crashed")
+            }))
+        })
+        .unwrap();
+    // Let the forwarding task run into the panic.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // === 2. Act ===
+    let (upstream_tx, upstream_rx) = mpsc::channel(16);
+    let mut fresh = registry
+        .subscribe(pda, "alice".to_string(), || {
+            Ok(ReceiverStream::new(upstream_rx))
+        })
+        .unwrap();
+    upstream_tx.send(event(pda, 3)).await.unwrap();
+
+    // === 3. Assert ===
+    assert_eq!(next_slot(&mut fresh).await, 3);
+    let next = timeout(Duration::from_secs(1), crashed.recv())
+        .await
+        .unwrap();
+    assert!(matches!(
+        next,
+        Some(Err(tokio::sync::broadcast::error::RecvError::Closed))
+    ));
+    assert_eq!(registry.upstream_count(), 1);
+    drop(crashed);
+    assert_eq!(registry.len(), 1);
+}
+
+#[test]
+fn test_empty_limits_section_keeps_the_defaults() {
+    // === 1. Arrange ===
+    let section = config::File::from_str("", config::FileFormat::Toml);
+
+    // === 2. Act ===
+    let parsed: LimitsConfig = config::Config::builder()
+        .add_source(section)
+        .build()
+        .unwrap()
+        .try_deserialize()
+        .unwrap();
+
+    // === 3. Assert ===
+    assert_eq!(
+        parsed.max_subscriptions,
+        LimitsConfig::default().max_subscriptions
+    );
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use solana_sdk::pubkey::Pubkey;
+use tonic::{Code, Request};
+use w3b2_solana_connector::events::BridgeEventData;
+use w3b2_solana_gateway::config::GatewayConfig;
+use w3b2_solana_gateway::grpc::proto::w3b2::protocol::gateway::bridge_gateway_service_server::BridgeGatewayService;
+use w3b2_solana_gateway::grpc::proto::w3b2::protocol::gateway::ListPendingUnbanRequestsRequest;
+use w3b2_solana_gateway::grpc::{pending_unban_requests, GatewayServer};
+use w3b2_solana_program::events::{
+    UserBanned, UserProfileClosed, UserUnbanRequested, UserUnbanned,
+};
+
+fn banned(admin_pda: Pubkey, user_pda: Pubkey) -> BridgeEventData {
+    BridgeEventData::UserBanned(UserBanned {
+        admin_authority: Pubkey::new_unique(),
+        admin_pda,
+        user_profile_pda: user_pda,
+        ts: 1,
+    })
+}
+
+fn unban_requested(admin_pda: Pubkey, user_pda: Pubkey, fee_paid: u64, ts: i64) -> BridgeEventData {
+    BridgeEventData::UserUnbanRequested(UserUnbanRequested {
+        user_authority: Pubkey::new_unique(),
+        user_profile_pda: user_pda,
+        admin_pda,
+        fee_paid,
+        ts,
+    })
+}
+
+fn unbanned(admin_pda: Pubkey, user_pda: Pubkey) -> BridgeEventData {
+    BridgeEventData::UserUnbanned(UserUnbanned {
+        admin_authority: Pubkey::new_unique(),
+        admin_pda,
+        user_profile_pda: user_pda,
+        ts: 3,
+    })
+}
+
+#[test]
+fn test_unbanning_a_user_empties_the_queue() {
+    let admin_pda = Pubkey::new_unique();
+    let user_pda = Pubkey::new_unique();
+    let history = [
+        banned(admin_pda, user_pda),
+        unban_requested(admin_pda, user_pda, 50_000, 2),
+        unbanned(admin_pda, user_pda),
+    ];
+
+    assert_eq!(pending_unban_requests(&history[..2]).len(), 1);
+    assert!(pending_unban_requests(&history).is_empty());
+}
+
+#[test]
+fn test_pending_unban_requests_are_listed_oldest_first() {
+    // === 1. Arrange ===
+    let admin_pda = Pubkey::new_unique();
+    let (first, second, closed, rebanned) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let history = vec![
+        banned(admin_pda, second),
+        banned(admin_pda, first),
+        banned(admin_pda, closed),
+        banned(admin_pda, rebanned),
+        unban_requested(admin_pda, first, 10, 20),
+        unban_requested(admin_pda, second, 30, 40),
+        unban_requested(admin_pda, closed, 50, 60),
+        BridgeEventData::UserProfileClosed(UserProfileClosed {
+            authority: Pubkey::new_unique(),
+            user_pda: closed,
+            admin_pda,
+            was_banned: true,
+            ts: 70,
+        }),
+        // A user banned again after being unbanned has not requested an unban yet.
+        unban_requested(admin_pda, rebanned, 80, 90),
+        unbanned(admin_pda, rebanned),
+        banned(admin_pda, rebanned),
+    ];
+
+    // === 2. Act ===
+    let pending = pending_unban_requests(&history);
+
+    // === 3. Assert ===
+    let users: Vec<_> = pending.iter().map(|r| r.user_profile_pda.clone()).collect();
+    assert_eq!(users, vec![first.to_string(), second.to_string()]);
+    assert_eq!((pending[0].fee_paid, pending[0].requested_at), (10, 20));
+    assert_eq!((pending[1].fee_paid, pending[1].requested_at), (30, 40));
+}
+
+#[tokio::test]
+async fn test_list_pending_unban_requests_rejects_an_invalid_admin_pda() {
+    let server = GatewayServer::new(common::app_state(GatewayConfig::default()));
+
+    let result = server
+        .list_pending_unban_requests(Request::new(ListPendingUnbanRequestsRequest {
+            admin_pda: "not-a-pda".to_string(),
+        }))
+        .await;
+
+    assert_eq!(result.unwrap_err().code(), Code::InvalidArgument);
+}
@@ -1,10 +1,14 @@
 use anyhow::Result;
 use serde::Deserialize;
-use std::{fs::File, str::FromStr};
+use std::{fs::File, path::Path, str::FromStr};
 use tracing::Level;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::{
     filter::LevelFilter,
-    fmt::{self, writer::MakeWriterExt},
+    fmt::{
+        self,
+        writer::{BoxMakeWriter, MakeWriterExt},
+    },
     prelude::*,
     Registry,
 };
@@ -25,12 +29,67 @@ pub enum LogOutput {
     File,
 }
 
+/// How often the log file is rolled over when `output` is `file`.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    /// Writes to `file_path` for the whole lifetime of the process.
+    #[default]
+    Never,
+    /// Starts a new file every hour, named `<file_path>.YYYY-MM-DD-HH`.
+    Hourly,
+    /// Starts a new file every day, named `<file_path>.YYYY-MM-DD`.
+    Daily,
+}
+
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct LogConfig {
     pub level: String,
     pub format: LogFormat,
     pub output: LogOutput,
     pub file_path: Option<String>,
+    #[serde(default)]
+    pub rotation: LogRotation,
+    /// The number of rotated log files to keep. When a new file is started, the oldest
+    /// ones beyond this limit are deleted. Unlimited if not set.
+    #[serde(default)]
+    pub max_files: Option<usize>,
+}
+
+/// Opens the writer for `output = "file"`: a plain file, or a rolling appender if
+/// rotation is enabled.
+fn file_writer(config: &LogConfig, file_path: &str) -> Result<BoxMakeWriter> {
+    let rotation = match config.rotation {
+        LogRotation::Never => {
+            if config.max_files.is_some() {
+                anyhow::bail!("'max_files' requires log 'rotation' to be 'hourly' or 'daily'");
+            }
+            return Ok(BoxMakeWriter::new(File::create(file_path)?));
+        }
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Daily => Rotation::DAILY,
+    };
+
+    let path = Path::new(file_path);
+    let prefix = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Log 'file_path' {file_path} has no file name"))?;
+    let directory = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    let mut builder = RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(prefix);
+    if let Some(max_files) = config.max_files {
+        if max_files == 0 {
+            anyhow::bail!("'max_files' must be at least 1");
+        }
+        builder = builder.max_log_files(max_files);
+    }
+    Ok(BoxMakeWriter::new(builder.build(directory)?))
 }
 
 pub fn init(config: &LogConfig) -> Result<()> {
@@ -44,8 +103,7 @@ pub fn init(config: &LogConfig) -> Result<()> {
                 .file_path
                 .as_deref()
                 .ok_or_else(|| anyhow::anyhow!("Log output is 'file' but 'file_path' is not specified"))?;
-            let log_file = File::create(file_path)?;
-            let file_writer = log_file.with_max_level(log_level);
+            let file_writer = file_writer(config, file_path)?.with_max_level(log_level);
 
             match config.format {
                 LogFormat::Json => {
@@ -0,0 +1,32 @@
+//! Generates the list of every `BridgeError` variant from `src/errors.rs`, so that
+//! off-chain clients can enumerate the program's errors without a hand-maintained copy.
+
+use std::{env, fs, path::Path};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=src/errors.rs");
+    let source = fs::read_to_string("src/errors.rs")?;
+    let body = source
+        .split_once("pub enum BridgeError {")
+        .and_then(|(_, rest)| rest.split_once("\n}"))
+        .ok_or("src/errors.rs does not declare `pub enum BridgeError`")?
+        .0;
+
+    // Every variant is a unit variant on a line of its own, after its doc comment and `#[msg]`.
+    let variants: Vec<&str> = body
+        .lines()
+        .filter_map(|line| line.trim().strip_suffix(','))
+        .filter(|name| name.chars().all(|c| c.is_ascii_alphanumeric()))
+        .filter(|name| name.starts_with(|c: char| c.is_ascii_uppercase()))
+        .collect();
+
+    let mut table = String::from("&[\n");
+    for variant in variants {
+        table.push_str(&format!("    BridgeError::{variant},\n"));
+    }
+    table.push(']');
+
+    let out = Path::new(&env::var("OUT_DIR")?).join("bridge_errors.rs");
+    fs::write(out, table)?;
+    Ok(())
+}
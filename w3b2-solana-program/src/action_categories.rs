@@ -0,0 +1,24 @@
+//! # Action Categories
+//!
+//! Shared namespaces for the `category` argument of `log_action`.
+//!
+//! The `action_code` of an `OffChainActionLogged` event only has a meaning within its
+//! category, so different subsystems can reuse the same codes (e.g. `404`) without their
+//! audit records becoming ambiguous. Values `0..=127` are reserved for the categories
+//! below; services are free to use `128..=255` for their own namespaces.
+
+/// Uncategorized actions. This is the category of records created before categories existed.
+pub const GENERAL: u8 = 0;
+/// HTTP requests served by the service. `action_code` is the HTTP status code.
+pub const HTTP: u8 = 1;
+/// Session lifecycle events, such as a handshake completing or a session expiring.
+pub const SESSION: u8 = 2;
+/// Billing events, such as an invoice being issued or a refund being granted.
+pub const BILLING: u8 = 3;
+/// Security events, such as a failed authentication or a revoked key.
+pub const SECURITY: u8 = 4;
+/// Storage operations, such as a file being uploaded or deleted.
+pub const STORAGE: u8 = 5;
+
+/// The first category value available for service-defined namespaces.
+pub const CUSTOM_START: u8 = 128;
@@ -59,4 +59,64 @@ pub enum BridgeError {
     /// Used when an admin attempts to ban their own profile.
     #[msg("Cannot Ban Self: An admin cannot ban their own profile.")]
     CannotBanSelf,
+
+    /// Used when an admin tries to set a `max_payload_size` above the global `MAX_PAYLOAD_SIZE`.
+    #[msg(
+        "Payload Limit Too Large: The payload limit cannot exceed the global maximum payload size."
+    )]
+    PayloadLimitTooLarge,
+
+    /// Used when the `metadata` passed to `log_action` exceeds `MAX_METADATA_SIZE`.
+    #[msg("Metadata Too Large: The action metadata exceeds the maximum allowed size.")]
+    MetadataTooLarge,
+
+    /// Used when the oracle-signed price of a command exceeds the user's `max_price_per_command`.
+    #[msg("Price Exceeds User Cap: The command price is above the user's spending cap.")]
+    PriceExceedsUserCap,
+
+    /// Used when more than one Ed25519 instruction in a transaction verifies the price message.
+    #[msg(
+        "Ambiguous Signature Verification: More than one instruction verifies the price message."
+    )]
+    AmbiguousSignatureVerification,
+
+    /// Used when `admin_set_config` would leave a profile that requires a distinct oracle
+    /// with an `oracle_authority` equal to its `authority`.
+    #[msg("Oracle Must Be Distinct: The oracle authority must differ from the admin authority.")]
+    OracleMustBeDistinct,
+
+    /// Used when a command drawn against an allowance costs more than its remaining cap.
+    #[msg("Allowance Exhausted: The command price exceeds what remains of the allowance.")]
+    AllowanceExhausted,
+
+    /// Used when an allowance is granted or drawn against at or after its `expires_at`.
+    #[msg("Allowance Expired: The allowance is no longer valid.")]
+    AllowanceExpired,
+
+    /// Used when a command drawn against an allowance is not one of the commands it covers.
+    #[msg("Command Not In Allowance: The allowance does not cover this command.")]
+    CommandNotInAllowance,
+
+    /// Used when an allowance lists more than `MAX_ALLOWANCE_COMMANDS` commands.
+    #[msg(
+        "Too Many Allowance Commands: The allowance lists more commands than the program allows."
+    )]
+    TooManyAllowanceCommands,
+
+    /// Used when an admin sets a `timestamp_validity_seconds` outside the allowed range.
+    #[msg("Invalid Timestamp Validity: The validity must be between 5 and 3600 seconds.")]
+    InvalidTimestampValidity,
+
+    /// Used when the timestamp in the signed message is further ahead of the cluster clock
+    /// than `MAX_TIMESTAMP_FUTURE_SKEW_SECONDS`.
+    #[msg("Timestamp In Future: The provided timestamp is too far ahead of the cluster clock.")]
+    TimestampInFuture,
+
+    /// Used when a command is dispatched to an `AdminProfile` that holds no `authority`.
+    #[msg("Admin Profile Invalid: The target admin profile is not initialized.")]
+    AdminProfileInvalid,
 }
+
+/// Every `BridgeError` in declaration order, so the error at index `i` has the code
+/// `ERROR_CODE_OFFSET + i`. Generated by `build.rs` from this file.
+pub const ALL_ERRORS: &[BridgeError] = include!(concat!(env!("OUT_DIR"), "/bridge_errors.rs"));
@@ -1,3 +1,4 @@
+use crate::protocols::CommandMode;
 use anchor_lang::prelude::*;
 
 // --- Admin Events ---
@@ -15,6 +16,22 @@ pub struct AdminProfileRegistered {
     /// The public key provided by the admin for secure off-chain communication,
     /// typically used for hybrid encryption.
     pub communication_pubkey: Pubkey,
+    /// The index of the profile among the authority's profiles.
+    pub index: u16,
+    /// The initial oracle authority, which is the admin's own key.
+    pub oracle_authority: Pubkey,
+    /// The initial validity period for oracle signatures in seconds.
+    pub timestamp_validity_seconds: i64,
+    /// The initial payload size limit in bytes for dispatch instructions.
+    pub max_payload_size: u16,
+    /// The initial base fee for an unban request, in lamports.
+    pub unban_fee: u64,
+    /// How much the unban fee initially grows with each earlier ban, in basis points of
+    /// `unban_fee`.
+    pub unban_fee_multiplier_bps: u16,
+    /// The initial upper bound of the escalated unban fee, as a multiple of `unban_fee`.
+    /// `0` means the fee is not capped.
+    pub max_unban_fee_multiple: u16,
     /// The Unix timestamp (in seconds) when the registration occurred.
     pub ts: i64,
 }
@@ -33,6 +50,14 @@ pub struct AdminConfigUpdated {
     pub new_timestamp_validity: i64,
     /// The new public key for off-chain communication.
     pub new_communication_pubkey: Pubkey,
+    /// The profile's `comm_key_version` after the update. It only grows when the update
+    /// replaced the communication key.
+    pub comm_key_version: u32,
+    /// The payload size limit in bytes for dispatch instructions after the update.
+    pub new_max_payload_size: u16,
+    /// Whether the profile requires an `oracle_authority` distinct from its `authority`
+    /// after the update.
+    pub require_distinct_oracle: bool,
     /// The Unix timestamp of the update.
     pub ts: i64,
 }
@@ -61,6 +86,9 @@ pub struct AdminProfileClosed {
     pub authority: Pubkey,
     /// The public key of the `AdminProfile` **PDA** that was closed.
     pub admin_pda: Pubkey,
+    /// The internal `balance` in lamports that was still in the profile and was returned
+    /// to the `authority` along with the rent.
+    pub swept_balance: u64,
     /// The Unix timestamp of the account closure.
     pub ts: i64,
 }
@@ -79,6 +107,8 @@ pub struct AdminCommandDispatched {
     pub command_id: u64,
     /// An opaque byte array containing application-specific data for the command.
     pub payload: Vec<u8>,
+    /// Whether the admin expects the user to respond to the command.
+    pub mode: CommandMode,
     /// The Unix timestamp when the command was dispatched.
     pub ts: i64,
 }
@@ -111,6 +141,9 @@ pub struct UserCommKeyUpdated {
     pub user_profile_pda: Pubkey,
     /// The new communication public key for the `UserProfile`.
     pub new_comm_pubkey: Pubkey,
+    /// The profile's `comm_key_version` after the update. A key cache should ignore an
+    /// update whose version is not above the one it already holds.
+    pub comm_key_version: u32,
     /// The Unix timestamp of the update.
     pub ts: i64,
 }
@@ -127,6 +160,9 @@ pub struct UserFundsDeposited {
     pub amount: u64, // This is correct, it's the amount for this specific deposit.
     /// The user's new total `deposit_balance` after this transaction.
     pub new_deposit_balance: u64,
+    /// The wallet the lamports were transferred from. This is the signing `authority`,
+    /// even when another wallet pays the transaction fees.
+    pub source: Pubkey,
     /// The Unix timestamp of the deposit.
     pub ts: i64,
 }
@@ -159,6 +195,8 @@ pub struct UserProfileClosed {
     pub user_pda: Pubkey,
     /// The public key of the `AdminProfile` **PDA** this profile was linked to.
     pub admin_pda: Pubkey,
+    /// Whether the user was banned when the profile was closed.
+    pub was_banned: bool,
     /// The Unix timestamp of the account closure.
     pub ts: i64,
 }
@@ -179,8 +217,12 @@ pub struct UserCommandDispatched {
     pub command_id: u16,
     /// The amount in lamports deducted from the user's deposit balance for this command (0 if free).
     pub price_paid: u64,
+    /// The oracle key of the target admin whose signature authorized the price.
+    pub oracle_authority: Pubkey,
     /// An opaque byte array containing application-specific data for the command.
     pub payload: Vec<u8>,
+    /// Whether the user expects the service to respond to the command.
+    pub mode: CommandMode,
     /// The Unix timestamp when the command was dispatched.
     pub ts: i64,
 }
@@ -197,8 +239,13 @@ pub struct OffChainActionLogged {
     pub admin_profile_pda: Pubkey,
     /// A `u64` identifier used to correlate multiple off-chain actions to a single on-chain session.
     pub session_id: u64,
-    /// A `u16` code representing the specific type of off-chain action taken (e.g., 200 for HTTP OK).
+    /// The namespace of `action_code`, e.g. [`crate::action_categories::HTTP`].
+    pub category: u8,
+    /// A `u16` code representing the specific type of off-chain action taken, interpreted
+    /// within its `category` (e.g., 200 for HTTP OK).
     pub action_code: u16,
+    /// An optional detail blob for the action (e.g. a resource id). Empty if none was given.
+    pub metadata: Vec<u8>,
     /// The Unix timestamp of the logged action.
     pub ts: i64,
 }
@@ -260,3 +307,159 @@ pub struct UserUnbanRequested {
     /// The Unix timestamp of the request.
     pub ts: i64,
 }
+
+/// Emitted when a user sets the deposit balance below which they want to be notified.
+#[event]
+#[derive(Debug, Clone)]
+pub struct UserLowBalanceThresholdUpdated {
+    /// The public key of the user's wallet (`authority`) who authorized this update.
+    pub authority: Pubkey,
+    /// The PDA of the user profile that was updated.
+    pub user_profile_pda: Pubkey,
+    /// The new threshold in lamports. `0` disables low-balance notifications.
+    pub new_threshold: u64,
+    /// The Unix timestamp of the update.
+    pub ts: i64,
+}
+
+/// Emitted when a user sets the highest price they accept for a single command.
+#[event]
+#[derive(Debug, Clone)]
+pub struct UserSpendingCapUpdated {
+    /// The public key of the user's wallet (`authority`) who authorized this update.
+    pub authority: Pubkey,
+    /// The PDA of the user profile that was updated.
+    pub user_profile_pda: Pubkey,
+    /// The new cap in lamports. `0` removes the cap.
+    pub new_max_price_per_command: u64,
+    /// The Unix timestamp of the update.
+    pub ts: i64,
+}
+
+/// Emitted when a paid command drops a user's `deposit_balance` below their
+/// `low_balance_threshold`. It is emitted only once per crossing, so services can use it to
+/// prompt the user to top up before a command fails for insufficient balance.
+#[event]
+#[derive(Debug, Clone)]
+pub struct UserLowBalance {
+    /// The public key of the user's wallet (`authority`).
+    pub authority: Pubkey,
+    /// The PDA of the user profile whose balance is low.
+    pub user_profile_pda: Pubkey,
+    /// The public key of the `AdminProfile` PDA the command was paid to.
+    pub admin_pda: Pubkey,
+    /// The user's `deposit_balance` after the payment.
+    pub deposit_balance: u64,
+    /// The user's configured `low_balance_threshold`.
+    pub threshold: u64,
+    /// The Unix timestamp of the command that crossed the threshold.
+    pub ts: i64,
+}
+
+/// Emitted when a user grants a service an allowance to charge commands on their behalf.
+#[event]
+#[derive(Debug, Clone)]
+pub struct AllowanceGranted {
+    /// The public key of the user's wallet (`authority`) who granted the allowance.
+    pub authority: Pubkey,
+    /// The PDA of the user profile the allowance draws from.
+    pub user_profile_pda: Pubkey,
+    /// The public key of the `AdminProfile` PDA allowed to charge the commands.
+    pub admin_pda: Pubkey,
+    /// The commands the allowance covers.
+    pub commands: Vec<u16>,
+    /// The total in lamports the service may charge against the allowance.
+    pub total_cap: u64,
+    /// The Unix timestamp from which the allowance can no longer be drawn against.
+    pub expires_at: i64,
+    /// The Unix timestamp of the grant.
+    pub ts: i64,
+}
+
+/// Emitted when a service charges a command against a user's allowance. It follows the
+/// `UserCommandDispatched` event of the same command.
+#[event]
+#[derive(Debug, Clone)]
+pub struct AllowanceConsumed {
+    /// The PDA of the user profile that paid for the command.
+    pub user_profile_pda: Pubkey,
+    /// The public key of the `AdminProfile` PDA the command was paid to.
+    pub admin_pda: Pubkey,
+    /// The service key (the admin's `authority` or `oracle_authority`) that charged it.
+    pub charger: Pubkey,
+    /// The command that was charged.
+    pub command_id: u16,
+    /// The price of the command in lamports.
+    pub amount: u64,
+    /// What remains of the allowance in lamports after the command.
+    pub remaining_cap: u64,
+    /// The Unix timestamp of the command.
+    pub ts: i64,
+}
+
+// --- Shared Wallet Events ---
+
+/// Emitted when a user creates their shared `UserWallet`.
+#[event]
+#[derive(Debug, Clone)]
+pub struct WalletCreated {
+    /// The public key of the user's wallet (`authority`) who owns the shared wallet.
+    pub authority: Pubkey,
+    /// The PDA of the new `UserWallet`.
+    pub wallet_pda: Pubkey,
+    /// The Unix timestamp of the creation.
+    pub ts: i64,
+}
+
+/// Emitted when a user deposits funds into their shared `UserWallet`.
+#[event]
+#[derive(Debug, Clone)]
+pub struct WalletFundsDeposited {
+    /// The public key of the user's wallet (`authority`) who made the deposit.
+    pub authority: Pubkey,
+    /// The PDA of the `UserWallet` that received the funds.
+    pub wallet_pda: Pubkey,
+    /// The amount of lamports deposited.
+    pub amount: u64,
+    /// The new `deposit_balance` of the wallet after the deposit.
+    pub new_deposit_balance: u64,
+    /// The Unix timestamp of the deposit.
+    pub ts: i64,
+}
+
+/// Emitted when a user withdraws funds from their shared `UserWallet`.
+#[event]
+#[derive(Debug, Clone)]
+pub struct WalletFundsWithdrawn {
+    /// The public key of the user's wallet (`authority`) who made the withdrawal.
+    pub authority: Pubkey,
+    /// The PDA of the `UserWallet` from which funds were withdrawn.
+    pub wallet_pda: Pubkey,
+    /// The amount of lamports withdrawn.
+    pub amount: u64,
+    /// The public key of the account that received the withdrawn funds.
+    pub destination: Pubkey,
+    /// The new `deposit_balance` of the wallet after the withdrawal.
+    pub new_deposit_balance: u64,
+    /// The Unix timestamp of the withdrawal.
+    pub ts: i64,
+}
+
+/// Emitted when a paid command is paid from a user's shared `UserWallet` instead of their
+/// `UserProfile`. It follows the `UserCommandDispatched` event of the same command.
+#[event]
+#[derive(Debug, Clone)]
+pub struct WalletCharged {
+    /// The PDA of the `UserWallet` that paid for the command.
+    pub wallet_pda: Pubkey,
+    /// The PDA of the user profile the command was dispatched through.
+    pub user_profile_pda: Pubkey,
+    /// The public key of the `AdminProfile` PDA the command was paid to.
+    pub admin_pda: Pubkey,
+    /// The price of the command in lamports.
+    pub amount: u64,
+    /// The new `deposit_balance` of the wallet after the payment.
+    pub new_deposit_balance: u64,
+    /// The Unix timestamp of the command.
+    pub ts: i64,
+}
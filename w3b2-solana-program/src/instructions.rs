@@ -21,10 +21,92 @@ use solana_program::{
 };
 use solana_sdk_ids::ed25519_program;
 
-/// The maximum size in bytes for the `payload` in dispatch instructions.
+/// The absolute maximum size in bytes for the `payload` in dispatch instructions.
+/// Each admin may configure a lower limit via `max_payload_size`.
 pub const MAX_PAYLOAD_SIZE: usize = 1000;
+/// The maximum size in bytes of the optional `metadata` attached to `log_action`.
+pub const MAX_METADATA_SIZE: usize = 128;
 /// The default maximum age of a signed timestamp in seconds before it is considered expired.
 pub const MAX_TIMESTAMP_AGE_SECONDS: i64 = 60;
+/// The shortest `timestamp_validity_seconds` an admin can configure. Shorter windows reject
+/// honest signatures that are merely delayed by clock skew or block times.
+pub const MIN_TIMESTAMP_VALIDITY_SECONDS: i64 = 5;
+/// The longest `timestamp_validity_seconds` an admin can configure. Longer windows leave
+/// signed prices open to replay for too long.
+pub const MAX_TIMESTAMP_VALIDITY_SECONDS: i64 = 3600;
+/// How far in the future a signed timestamp may lie, to tolerate an oracle whose clock runs
+/// ahead of the cluster's. A timestamp further ahead would extend the admin's validity window.
+pub const MAX_TIMESTAMP_FUTURE_SKEW_SECONDS: i64 = 15;
+const _: () = assert!(
+    MIN_TIMESTAMP_VALIDITY_SECONDS <= MAX_TIMESTAMP_AGE_SECONDS
+        && MAX_TIMESTAMP_AGE_SECONDS <= MAX_TIMESTAMP_VALIDITY_SECONDS
+);
+/// The maximum number of commands a single `Allowance` can cover.
+pub const MAX_ALLOWANCE_COMMANDS: usize = 16;
+
+/// The version of the oracle price message built by [`oracle_price_message`].
+///
+/// Version 1 was the unversioned `command_id | price | timestamp` layout, which did not
+/// bind a price to a user. It is no longer accepted.
+pub const ORACLE_PRICE_MESSAGE_VERSION: u8 = 2;
+
+/// Builds the message an oracle signs to authorize a paid `user_dispatch_command`.
+///
+/// The format is `version (1 byte) | command_id (2 bytes) | price (8 bytes) | timestamp
+/// (8 bytes) | user_profile (32 bytes)`, with integers in little-endian. `user_profile` is
+/// the PDA of the `UserProfile` the price is signed for, so that a price signed for one
+/// user cannot be submitted by another. Off-chain oracles should use
+/// `w3b2_solana_connector::oracle` rather than reproducing this layout.
+pub fn oracle_price_message(
+    command_id: u16,
+    price: u64,
+    timestamp: i64,
+    user_profile: Pubkey,
+) -> Vec<u8> {
+    [
+        [ORACLE_PRICE_MESSAGE_VERSION].as_ref(),
+        command_id.to_le_bytes().as_ref(),
+        price.to_le_bytes().as_ref(),
+        timestamp.to_le_bytes().as_ref(),
+        user_profile.as_ref(),
+    ]
+    .concat()
+}
+
+/// Checks a dispatch `payload` against the admin's configured limit, which itself is
+/// capped by the global `MAX_PAYLOAD_SIZE`.
+fn payload_within_limit(payload: &[u8], admin_profile: &AdminProfile) -> bool {
+    payload.len() <= (admin_profile.max_payload_size as usize).min(MAX_PAYLOAD_SIZE)
+}
+
+/// Checks an oracle-signed `price` against the user's `max_price_per_command` (`0` for no cap).
+fn price_within_user_cap(price: u64, user_profile: &UserProfile) -> bool {
+    user_profile.max_price_per_command == 0 || price <= user_profile.max_price_per_command
+}
+
+/// The denominator of `AdminProfile::unban_fee_multiplier_bps`.
+const BASIS_POINTS: u128 = 10_000;
+
+/// Computes the fee a user pays for `user_request_unban`.
+///
+/// Every ban before the current one adds `multiplier_bps` basis points of `unban_fee`, so a
+/// first offender pays `unban_fee` and the fee grows linearly for repeat offenders. The
+/// result is capped at `max_multiple` times `unban_fee` unless `max_multiple` is `0`, and
+/// saturates at `u64::MAX`.
+pub fn escalated_unban_fee(
+    unban_fee: u64,
+    ban_count: u32,
+    multiplier_bps: u16,
+    max_multiple: u16,
+) -> u64 {
+    let earlier_bans = ban_count.saturating_sub(1) as u128;
+    let mut fee =
+        unban_fee as u128 * (BASIS_POINTS + earlier_bans * multiplier_bps as u128) / BASIS_POINTS;
+    if max_multiple > 0 {
+        fee = fee.min(unban_fee as u128 * max_multiple as u128);
+    }
+    u64::try_from(fee).unwrap_or(u64::MAX)
+}
 
 // --- Admin Instructions ---
 
@@ -38,13 +120,17 @@ pub const MAX_TIMESTAMP_AGE_SECONDS: i64 = 60;
 ///
 /// * `ctx` - The context, containing the [`AdminRegisterProfile`] accounts.
 /// * `communication_pubkey` - The public key the admin will use for off-chain communication.
+/// * `index` - The profile index, which lets one wallet register several profiles.
+///   `None` is the same as `Some(0)`, the profile at the original address.
 ///
 /// # Events
 ///
-/// * [`AdminProfileRegistered`] - On successful creation of the profile.
+/// * [`AdminProfileRegistered`] - On successful creation of the profile, carrying its
+///   initial configuration so that indexers do not need to fetch the account.
 pub fn admin_register_profile(
     ctx: Context<AdminRegisterProfile>,
     communication_pubkey: Pubkey,
+    index: Option<u16>,
 ) -> Result<()> {
     let admin_profile = &mut ctx.accounts.admin_profile;
     admin_profile.authority = ctx.accounts.authority.key();
@@ -54,11 +140,24 @@ pub fn admin_register_profile(
     admin_profile.timestamp_validity_seconds = MAX_TIMESTAMP_AGE_SECONDS; // Set default value
     admin_profile.balance = 0;
     admin_profile.unban_fee = 0; // Default unban fee is 0
+    admin_profile.max_payload_size = MAX_PAYLOAD_SIZE as u16;
+    admin_profile.index = index.unwrap_or_default();
+    admin_profile.unban_fee_multiplier_bps = 0; // The unban fee is flat by default
+    admin_profile.max_unban_fee_multiple = 0;
+    admin_profile.comm_key_version = 0;
+    admin_profile.require_distinct_oracle = false;
 
     emit!(AdminProfileRegistered {
         authority: admin_profile.authority,
         admin_pda: admin_profile.key(),
         communication_pubkey: admin_profile.communication_pubkey,
+        index: admin_profile.index,
+        oracle_authority: admin_profile.oracle_authority,
+        timestamp_validity_seconds: admin_profile.timestamp_validity_seconds,
+        max_payload_size: admin_profile.max_payload_size,
+        unban_fee: admin_profile.unban_fee,
+        unban_fee_multiplier_bps: admin_profile.unban_fee_multiplier_bps,
+        max_unban_fee_multiple: admin_profile.max_unban_fee_multiple,
         ts: Clock::get()?.unix_timestamp,
     });
     Ok(())
@@ -66,7 +165,8 @@ pub fn admin_register_profile(
 
 /// Bans a user, preventing them from using the service.
 ///
-/// Sets the `banned` flag on the specified `UserProfile` to `true`.
+/// Sets the `banned` flag on the specified `UserProfile` to `true` and, unless the user was
+/// already banned, increments its `ban_count`.
 ///
 /// # Arguments
 ///
@@ -89,6 +189,9 @@ pub fn admin_ban_user(ctx: Context<AdminBanUser>) -> Result<()> {
         BridgeError::CannotBanSelf
     );
 
+    if !user_profile.banned {
+        user_profile.ban_count = user_profile.ban_count.saturating_add(1);
+    }
     user_profile.banned = true;
 
     emit!(UserBanned {
@@ -162,7 +265,9 @@ pub fn admin_unban_user(ctx: Context<AdminUnbanUser>) -> Result<()> {
 /// Sets the configuration for an `AdminProfile`.
 ///
 /// Allows the admin to update the `oracle_authority`, `timestamp_validity_seconds`,
-/// `communication_pubkey`, and `unban_fee`. Any field passed as `None` will be ignored.
+/// `communication_pubkey`, `unban_fee`, `max_payload_size`, `unban_fee_multiplier_bps`,
+/// `max_unban_fee_multiple` and `require_distinct_oracle`. Any field passed as `None` will
+/// be ignored.
 ///
 /// # Arguments
 ///
@@ -170,18 +275,40 @@ pub fn admin_unban_user(ctx: Context<AdminUnbanUser>) -> Result<()> {
 /// * `new_oracle_authority` - An optional new `Pubkey` for the oracle.
 /// * `new_timestamp_validity` - An optional new duration in seconds for signature validity.
 /// * `new_communication_pubkey` - An optional new `Pubkey` for off-chain communication.
+///   Setting it increments the profile's `comm_key_version`, even if the key is unchanged.
 /// * `new_unban_fee` - An optional new fee in lamports for unban requests.
+/// * `new_max_payload_size` - An optional new payload limit in bytes for dispatch instructions.
+/// * `new_unban_fee_multiplier_bps` - An optional new per-ban increase of the unban fee, in
+///   basis points of `unban_fee`.
+/// * `new_max_unban_fee_multiple` - An optional new cap of the escalated unban fee, as a
+///   multiple of `unban_fee` (`0` for no cap).
+/// * `new_require_distinct_oracle` - Optionally turns the requirement that the
+///   `oracle_authority` differs from the `authority` on or off.
+///
+/// # Errors
+///
+/// * `PayloadLimitTooLarge` - If `new_max_payload_size` exceeds `MAX_PAYLOAD_SIZE`.
+/// * `InvalidTimestampValidity` - If `new_timestamp_validity` is outside
+///   `MIN_TIMESTAMP_VALIDITY_SECONDS..=MAX_TIMESTAMP_VALIDITY_SECONDS`.
+/// * `OracleMustBeDistinct` - If the profile requires a distinct oracle after the update,
+///   but its `oracle_authority` equals its `authority`. The check applies to the resulting
+///   configuration, so the flag can be turned on together with a new oracle.
 ///
 /// # Events
 ///
 /// * [`AdminConfigUpdated`] - Always emitted on successful execution.
 /// * [`AdminUnbanFeeUpdated`] - Emitted only if the `unban_fee` was changed.
+#[allow(clippy::too_many_arguments)]
 pub fn admin_set_config(
     ctx: Context<AdminSetConfig>,
     new_oracle_authority: Option<Pubkey>,
     new_timestamp_validity: Option<i64>,
     new_communication_pubkey: Option<Pubkey>,
     new_unban_fee: Option<u64>,
+    new_max_payload_size: Option<u16>,
+    new_unban_fee_multiplier_bps: Option<u16>,
+    new_max_unban_fee_multiple: Option<u16>,
+    new_require_distinct_oracle: Option<bool>,
 ) -> Result<()> {
     let admin_profile = &mut ctx.accounts.admin_profile;
     let mut fee_updated = false;
@@ -190,15 +317,42 @@ pub fn admin_set_config(
         admin_profile.oracle_authority = new_oracle;
     }
     if let Some(new_validity) = new_timestamp_validity {
+        require!(
+            (MIN_TIMESTAMP_VALIDITY_SECONDS..=MAX_TIMESTAMP_VALIDITY_SECONDS)
+                .contains(&new_validity),
+            BridgeError::InvalidTimestampValidity
+        );
         admin_profile.timestamp_validity_seconds = new_validity;
     }
     if let Some(new_comm_key) = new_communication_pubkey {
         admin_profile.communication_pubkey = new_comm_key;
+        admin_profile.comm_key_version = admin_profile.comm_key_version.wrapping_add(1);
     }
     if let Some(new_fee) = new_unban_fee {
         admin_profile.unban_fee = new_fee;
         fee_updated = true;
     }
+    if let Some(new_limit) = new_max_payload_size {
+        require!(
+            new_limit as usize <= MAX_PAYLOAD_SIZE,
+            BridgeError::PayloadLimitTooLarge
+        );
+        admin_profile.max_payload_size = new_limit;
+    }
+    if let Some(new_multiplier) = new_unban_fee_multiplier_bps {
+        admin_profile.unban_fee_multiplier_bps = new_multiplier;
+    }
+    if let Some(new_max_multiple) = new_max_unban_fee_multiple {
+        admin_profile.max_unban_fee_multiple = new_max_multiple;
+    }
+    if let Some(require_distinct) = new_require_distinct_oracle {
+        admin_profile.require_distinct_oracle = require_distinct;
+    }
+    require!(
+        !admin_profile.require_distinct_oracle
+            || admin_profile.oracle_authority != admin_profile.authority,
+        BridgeError::OracleMustBeDistinct
+    );
 
     emit!(AdminConfigUpdated {
         authority: admin_profile.authority,
@@ -206,6 +360,9 @@ pub fn admin_set_config(
         new_oracle_authority: admin_profile.oracle_authority,
         new_timestamp_validity: admin_profile.timestamp_validity_seconds,
         new_communication_pubkey: admin_profile.communication_pubkey,
+        comm_key_version: admin_profile.comm_key_version,
+        new_max_payload_size: admin_profile.max_payload_size,
+        require_distinct_oracle: admin_profile.require_distinct_oracle,
         ts: Clock::get()?.unix_timestamp,
     });
 
@@ -221,10 +378,11 @@ pub fn admin_set_config(
     Ok(())
 }
 
-/// Closes an `AdminProfile` account and refunds its rent lamports to the owner.
+/// Closes an `AdminProfile` account and returns all of its lamports to the owner.
 ///
-/// **Note:** This instruction only returns the lamports required for rent. Any funds
-/// in the internal `balance` must be withdrawn via `admin_withdraw` *before* closing.
+/// Any funds left in the internal `balance` are swept to the `authority` together with the
+/// rent, so closing a profile never strands its earnings. Withdrawing them to another
+/// wallet first, via `admin_withdraw` or `admin_withdraw_all`, remains possible.
 ///
 /// # Arguments
 ///
@@ -232,11 +390,18 @@ pub fn admin_set_config(
 ///
 /// # Events
 ///
-/// * [`AdminProfileClosed`] - On successful closure.
+/// * [`AdminProfileClosed`] - On successful closure, with the swept `balance`.
 pub fn admin_close_profile(ctx: Context<AdminCloseProfile>) -> Result<()> {
+    // The `close` constraint moves every lamport of the PDA, the balance included, to the
+    // authority once the instruction returns. Zero the balance so the account agrees.
+    let admin_profile = &mut ctx.accounts.admin_profile;
+    let swept_balance = admin_profile.balance;
+    admin_profile.balance = 0;
+
     emit!(AdminProfileClosed {
         authority: ctx.accounts.authority.key(),
-        admin_pda: ctx.accounts.admin_profile.key(),
+        admin_pda: admin_profile.key(),
+        swept_balance,
         ts: Clock::get()?.unix_timestamp,
     });
     Ok(())
@@ -261,9 +426,47 @@ pub fn admin_close_profile(ctx: Context<AdminCloseProfile>) -> Result<()> {
 ///
 /// * [`AdminFundsWithdrawn`] - On successful withdrawal.
 pub fn admin_withdraw(ctx: Context<AdminWithdraw>, amount: u64) -> Result<()> {
-    let admin_profile = &mut ctx.accounts.admin_profile;
-    let destination = &ctx.accounts.destination;
+    withdraw_admin_balance(
+        &mut ctx.accounts.admin_profile,
+        &ctx.accounts.destination,
+        amount,
+    )
+}
+
+/// Withdraws the entire internal balance of an `AdminProfile`.
+///
+/// This is a convenience over [`admin_withdraw`] that spares the caller from reading the
+/// balance first, e.g. to move the earnings to another wallet before closing the profile
+/// with [`admin_close_profile`]. The PDA keeps its rent-exempt reserve and its `balance` is
+/// set to zero.
+///
+/// # Arguments
+///
+/// * `ctx` - The context, containing the [`AdminWithdrawAll`] accounts.
+///
+/// # Errors
+///
+/// * `RentExemptViolation` - If the PDA's lamport balance does not cover both its internal
+///   `balance` and the rent-exempt minimum.
+///
+/// # Events
+///
+/// * [`AdminFundsWithdrawn`] - On success, with the withdrawn `amount`.
+pub fn admin_withdraw_all(ctx: Context<AdminWithdrawAll>) -> Result<()> {
+    let amount = ctx.accounts.admin_profile.balance;
+    withdraw_admin_balance(
+        &mut ctx.accounts.admin_profile,
+        &ctx.accounts.destination,
+        amount,
+    )
+}
 
+/// Moves `amount` lamports of an admin's internal balance to `destination`.
+fn withdraw_admin_balance<'info>(
+    admin_profile: &mut Account<'info, AdminProfile>,
+    destination: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
     // Check if the internal balance is sufficient.
     require!(
         admin_profile.balance >= amount,
@@ -305,10 +508,11 @@ pub fn admin_withdraw(ctx: Context<AdminWithdraw>, amount: u64) -> Result<()> {
 /// * `ctx` - The context, containing the [`AdminDispatchCommand`] accounts.
 /// * `command_id` - A `u64` identifier for the admin's command.
 /// * `payload` - An opaque `Vec<u8>` for application-specific data.
+/// * `mode` - Whether the admin expects a response, recorded in the event.
 ///
 /// # Errors
 ///
-/// * `PayloadTooLarge` - If the `payload` exceeds `MAX_PAYLOAD_SIZE`.
+/// * `PayloadTooLarge` - If the `payload` exceeds the admin's `max_payload_size`.
 ///
 /// # Events
 ///
@@ -317,9 +521,10 @@ pub fn admin_dispatch_command(
     ctx: Context<AdminDispatchCommand>,
     command_id: u64,
     payload: Vec<u8>,
+    mode: CommandMode,
 ) -> Result<()> {
     require!(
-        payload.len() <= MAX_PAYLOAD_SIZE,
+        payload_within_limit(&payload, &ctx.accounts.admin_profile),
         BridgeError::PayloadTooLarge
     );
 
@@ -329,6 +534,7 @@ pub fn admin_dispatch_command(
         target_user_pda: ctx.accounts.user_profile.key(),
         command_id,
         payload,
+        mode,
         ts: Clock::get()?.unix_timestamp,
     });
 
@@ -363,6 +569,11 @@ pub fn user_create_profile(
     user_profile.admin_profile_on_creation = target_admin_pda;
     user_profile.banned = false;
     user_profile.unban_requested = false;
+    user_profile.low_balance_threshold = 0;
+    user_profile.low_balance_notified = false;
+    user_profile.ban_count = 0;
+    user_profile.max_price_per_command = 0;
+    user_profile.comm_key_version = 0;
 
     emit!(UserProfileCreated {
         authority: user_profile.authority,
@@ -374,7 +585,8 @@ pub fn user_create_profile(
     Ok(())
 }
 
-/// Updates the `communication_pubkey` for an existing `UserProfile`.
+/// Updates the `communication_pubkey` for an existing `UserProfile` and increments its
+/// `comm_key_version`.
 ///
 /// # Arguments
 ///
@@ -387,10 +599,12 @@ pub fn user_create_profile(
 pub fn user_update_comm_key(ctx: Context<UserUpdateCommKey>, new_key: Pubkey) -> Result<()> {
     let user_profile = &mut ctx.accounts.user_profile;
     user_profile.communication_pubkey = new_key;
+    user_profile.comm_key_version = user_profile.comm_key_version.wrapping_add(1);
     emit!(UserCommKeyUpdated {
         authority: ctx.accounts.authority.key(),
         user_profile_pda: user_profile.key(),
         new_comm_pubkey: new_key,
+        comm_key_version: user_profile.comm_key_version,
         ts: Clock::get()?.unix_timestamp,
     });
     Ok(())
@@ -402,6 +616,11 @@ pub fn user_update_comm_key(ctx: Context<UserUpdateCommKey>, new_key: Pubkey) ->
 /// held by the `user_profile` PDA (both for rent and from any remaining `deposit_balance`)
 /// are safely returned to the user's `authority` wallet.
 ///
+/// Closing is allowed while the user is banned. A ban restricts what the user can do with
+/// the admin's service, but the deposit belongs to the user and must never be held back
+/// by it. The ban state at closure is reported in the event so that the admin can tell a
+/// banned user leaving from an ordinary closure.
+///
 /// # Arguments
 ///
 /// * `ctx` - The context, containing the [`UserCloseProfile`] accounts.
@@ -414,6 +633,7 @@ pub fn user_close_profile(ctx: Context<UserCloseProfile>) -> Result<()> {
         authority: ctx.accounts.authority.key(),
         user_pda: ctx.accounts.user_profile.key(),
         admin_pda: ctx.accounts.admin_profile.key(),
+        was_banned: ctx.accounts.user_profile.banned,
         ts: Clock::get()?.unix_timestamp,
     });
     Ok(())
@@ -421,7 +641,8 @@ pub fn user_close_profile(ctx: Context<UserCloseProfile>) -> Result<()> {
 
 /// Allows a banned user to pay a fee to request an unban from the admin.
 ///
-/// If the `unban_fee` is greater than zero, this instruction transfers the fee amount
+/// The fee is the admin's `unban_fee`, escalated for users who were banned before (see
+/// [`escalated_unban_fee`]). If it is greater than zero, this instruction transfers the fee
 /// from the user's `deposit_balance` to the admin's `balance`. It then sets the
 /// `unban_requested` flag to `true`.
 ///
@@ -433,7 +654,7 @@ pub fn user_close_profile(ctx: Context<UserCloseProfile>) -> Result<()> {
 ///
 /// * `UserNotBanned` - If the user is not currently banned.
 /// * `UnbanAlreadyRequested` - If an unban has already been requested and is pending review.
-/// * `InsufficientDepositBalance` - If the user's balance is less than the `unban_fee`.
+/// * `InsufficientDepositBalance` - If the user's balance is less than the fee.
 /// * `RentExemptViolation` - If the fee transfer would leave the user's PDA below the rent-exempt minimum.
 ///
 /// # Events
@@ -442,7 +663,12 @@ pub fn user_close_profile(ctx: Context<UserCloseProfile>) -> Result<()> {
 pub fn user_request_unban(ctx: Context<UserRequestUnban>) -> Result<()> {
     let user_profile = &mut ctx.accounts.user_profile;
     let admin_profile = &mut ctx.accounts.admin_profile;
-    let fee = admin_profile.unban_fee;
+    let fee = escalated_unban_fee(
+        admin_profile.unban_fee,
+        user_profile.ban_count,
+        admin_profile.unban_fee_multiplier_bps,
+        admin_profile.max_unban_fee_multiple,
+    );
 
     // The user must be banned to request an unban.
     require!(user_profile.banned, BridgeError::UserNotBanned);
@@ -490,6 +716,119 @@ pub fn user_request_unban(ctx: Context<UserRequestUnban>) -> Result<()> {
     Ok(())
 }
 
+/// Sets the `low_balance_threshold` of a `UserProfile`.
+///
+/// Once set, a paid `user_dispatch_command` that leaves the user's `deposit_balance` below
+/// the threshold emits a [`UserLowBalance`] event. Changing the threshold re-arms the
+/// notification.
+///
+/// # Arguments
+///
+/// * `ctx` - The context, containing the [`UserSetLowBalanceThreshold`] accounts.
+/// * `threshold` - The threshold in lamports. `0` disables the notification.
+///
+/// # Events
+///
+/// * [`UserLowBalanceThresholdUpdated`] - On successful update.
+pub fn user_set_low_balance_threshold(
+    ctx: Context<UserSetLowBalanceThreshold>,
+    threshold: u64,
+) -> Result<()> {
+    let user_profile = &mut ctx.accounts.user_profile;
+    user_profile.low_balance_threshold = threshold;
+    user_profile.low_balance_notified = false;
+    emit!(UserLowBalanceThresholdUpdated {
+        authority: ctx.accounts.authority.key(),
+        user_profile_pda: user_profile.key(),
+        new_threshold: threshold,
+        ts: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Sets the `max_price_per_command` of a `UserProfile`.
+///
+/// The cap is enforced by `user_dispatch_command` against the oracle-signed price, so a
+/// compromised oracle cannot charge the user more than they agreed to in a single command.
+///
+/// # Arguments
+///
+/// * `ctx` - The context, containing the [`UserSetSpendingCap`] accounts.
+/// * `max_price_per_command` - The cap in lamports. `0` removes the cap.
+///
+/// # Events
+///
+/// * [`UserSpendingCapUpdated`] - On successful update.
+pub fn user_set_spending_cap(
+    ctx: Context<UserSetSpendingCap>,
+    max_price_per_command: u64,
+) -> Result<()> {
+    let user_profile = &mut ctx.accounts.user_profile;
+    user_profile.max_price_per_command = max_price_per_command;
+    emit!(UserSpendingCapUpdated {
+        authority: ctx.accounts.authority.key(),
+        user_profile_pda: user_profile.key(),
+        new_max_price_per_command: max_price_per_command,
+        ts: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Grants the user's service an [`Allowance`] to charge a set of commands without the user
+/// signing each one, replacing any allowance granted before.
+///
+/// The service draws against it with [`dispatch_with_allowance`] until `total_cap` lamports
+/// have been charged or `expires_at` is reached. Granting an allowance with a `total_cap`
+/// of `0` effectively revokes it.
+///
+/// # Arguments
+///
+/// * `ctx` - The context, containing the [`UserGrantAllowance`] accounts.
+/// * `commands` - The commands the service may charge, at most `MAX_ALLOWANCE_COMMANDS`.
+/// * `total_cap` - The total in lamports the service may charge.
+/// * `expires_at` - The Unix timestamp from which the allowance can no longer be used.
+///
+/// # Errors
+///
+/// * `UserIsBanned` - If the user's profile is marked as banned.
+/// * `TooManyAllowanceCommands` - If `commands` has more than `MAX_ALLOWANCE_COMMANDS` entries.
+/// * `AllowanceExpired` - If `expires_at` is not in the future.
+///
+/// # Events
+///
+/// * [`AllowanceGranted`] - On successful grant.
+pub fn user_grant_allowance(
+    ctx: Context<UserGrantAllowance>,
+    commands: Vec<u16>,
+    total_cap: u64,
+    expires_at: i64,
+) -> Result<()> {
+    require!(!ctx.accounts.user_profile.banned, BridgeError::UserIsBanned);
+    require!(
+        commands.len() <= MAX_ALLOWANCE_COMMANDS,
+        BridgeError::TooManyAllowanceCommands
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require!(expires_at > now, BridgeError::AllowanceExpired);
+
+    let allowance = &mut ctx.accounts.allowance;
+    allowance.user_profile = ctx.accounts.user_profile.key();
+    allowance.commands = commands.clone();
+    allowance.remaining_cap = total_cap;
+    allowance.expires_at = expires_at;
+
+    emit!(AllowanceGranted {
+        authority: ctx.accounts.authority.key(),
+        user_profile_pda: ctx.accounts.user_profile.key(),
+        admin_pda: ctx.accounts.admin_profile.key(),
+        commands,
+        total_cap,
+        expires_at,
+        ts: now,
+    });
+    Ok(())
+}
+
 /// Deposits lamports into a `UserProfile` PDA.
 ///
 /// This pre-funds a user's account to pay for future service calls to the linked admin.
@@ -523,12 +862,17 @@ pub fn user_deposit(ctx: Context<UserDeposit>, amount: u64) -> Result<()> {
 
     // Update the internal deposit balance state.
     user_profile.deposit_balance += amount;
+    // A balance back at the threshold re-arms the low-balance notification.
+    if user_profile.deposit_balance >= user_profile.low_balance_threshold {
+        user_profile.low_balance_notified = false;
+    }
 
     emit!(UserFundsDeposited {
         authority: user_profile.authority,
         user_profile_pda: user_profile.key(),
         amount,
         new_deposit_balance: user_profile.deposit_balance,
+        source: ctx.accounts.authority.key(),
         ts: Clock::get()?.unix_timestamp,
     });
     Ok(())
@@ -588,6 +932,117 @@ pub fn user_withdraw(ctx: Context<UserWithdraw>, amount: u64) -> Result<()> {
     Ok(())
 }
 
+// --- Shared Wallet Instructions ---
+
+/// Creates the user's shared [`UserWallet`], with an empty balance.
+///
+/// The wallet is opt-in: profiles keep their own deposits, and only commands dispatched
+/// with [`user_dispatch_command_from_wallet`] are paid from it.
+///
+/// # Arguments
+///
+/// * `ctx` - The context, containing the [`UserCreateWallet`] accounts.
+///
+/// # Events
+///
+/// * [`WalletCreated`] - On successful creation.
+pub fn user_create_wallet(ctx: Context<UserCreateWallet>) -> Result<()> {
+    let wallet = &mut ctx.accounts.wallet;
+    wallet.authority = ctx.accounts.authority.key();
+    wallet.deposit_balance = 0;
+
+    emit!(WalletCreated {
+        authority: wallet.authority,
+        wallet_pda: wallet.key(),
+        ts: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Deposits lamports into the user's shared [`UserWallet`].
+///
+/// # Arguments
+///
+/// * `ctx` - The context, containing the [`WalletDeposit`] accounts.
+/// * `amount` - The number of lamports to deposit.
+///
+/// # Events
+///
+/// * [`WalletFundsDeposited`] - On successful deposit.
+pub fn wallet_deposit(ctx: Context<WalletDeposit>, amount: u64) -> Result<()> {
+    let wallet = &mut ctx.accounts.wallet;
+
+    invoke(
+        &system_instruction::transfer(
+            &ctx.accounts.authority.key(),
+            &wallet.to_account_info().key(),
+            amount,
+        ),
+        &[
+            ctx.accounts.authority.to_account_info(),
+            wallet.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+    )?;
+
+    wallet.deposit_balance += amount;
+
+    emit!(WalletFundsDeposited {
+        authority: wallet.authority,
+        wallet_pda: wallet.key(),
+        amount,
+        new_deposit_balance: wallet.deposit_balance,
+        ts: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Withdraws unspent funds from the user's shared [`UserWallet`].
+///
+/// # Arguments
+///
+/// * `ctx` - The context, containing the [`WalletWithdraw`] accounts.
+/// * `amount` - The number of lamports to withdraw.
+///
+/// # Errors
+///
+/// * `InsufficientDepositBalance` - If the wallet's `deposit_balance` is less than `amount`.
+/// * `RentExemptViolation` - If the withdrawal would leave the PDA below the rent-exempt minimum.
+///
+/// # Events
+///
+/// * [`WalletFundsWithdrawn`] - On successful withdrawal.
+pub fn wallet_withdraw(ctx: Context<WalletWithdraw>, amount: u64) -> Result<()> {
+    let wallet = &mut ctx.accounts.wallet;
+    let destination = &ctx.accounts.destination;
+
+    require!(
+        wallet.deposit_balance >= amount,
+        BridgeError::InsufficientDepositBalance
+    );
+
+    let rent = Rent::get()?;
+    let rent_exempt_minimum = rent.minimum_balance(wallet.to_account_info().data_len());
+    require!(
+        wallet.to_account_info().lamports() - amount >= rent_exempt_minimum,
+        BridgeError::RentExemptViolation
+    );
+
+    **wallet.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **destination.to_account_info().try_borrow_mut_lamports()? += amount;
+    wallet.deposit_balance -= amount;
+
+    emit!(WalletFundsWithdrawn {
+        authority: wallet.authority,
+        wallet_pda: wallet.key(),
+        amount,
+        destination: destination.key(),
+        new_deposit_balance: wallet.deposit_balance,
+        ts: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
 // --- Operational Instructions ---
 
 /// Dispatches a command from a user to a service, potentially with payment.
@@ -598,9 +1053,10 @@ pub fn user_withdraw(ctx: Context<UserWithdraw>, amount: u64) -> Result<()> {
 ///
 /// # Pre-requisites
 ///
-/// This instruction **must** be preceded by an `ed25519_dalek` signature verification
-/// instruction in the same transaction. The program inspects this preceding instruction
-/// to authenticate the `price`, `command_id`, and `timestamp`.
+/// The transaction **must** contain exactly one Ed25519 signature verification
+/// instruction over the oracle price message of `command_id`, `price`, `timestamp` and the
+/// `user_profile` PDA. It may be placed anywhere in the transaction, e.g. after a compute
+/// budget instruction.
 ///
 /// # Arguments
 ///
@@ -609,75 +1065,227 @@ pub fn user_withdraw(ctx: Context<UserWithdraw>, amount: u64) -> Result<()> {
 /// * `price` - The price in lamports, as signed by the oracle.
 /// * `timestamp` - The Unix timestamp from the signed message, to prevent replay attacks.
 /// * `payload` - An opaque `Vec<u8>` for application-specific data.
+/// * `mode` - Whether the user expects a response, recorded in the event.
 ///
 /// # Errors
 ///
+/// * `AdminProfileInvalid` - If the `admin_profile` has no `authority` or does not live at
+///   the PDA derived from its `authority` and `index`, e.g. a stale link after a redeployment.
 /// * `UserIsBanned` - If the user's profile is marked as banned.
-/// * `PayloadTooLarge` - If the `payload` exceeds `MAX_PAYLOAD_SIZE`.
-/// * `InstructionMismatch` - If the preceding instruction is not a valid Ed25519 signature verification.
+/// * `PayloadTooLarge` - If the `payload` exceeds the admin's `max_payload_size`.
+/// * `InstructionMismatch` - If the transaction contains no Ed25519 signature verification.
+/// * `AmbiguousSignatureVerification` - If more than one verification covers the price message.
 /// * `InvalidOracleSigner` - If the signature was not from the admin's designated `oracle_authority`.
 /// * `TimestampTooOld` - If the signed timestamp has expired.
-/// * `SignatureVerificationFailed` - If the signed message content does not match the provided arguments.
+/// * `TimestampInFuture` - If the signed timestamp is more than
+///   `MAX_TIMESTAMP_FUTURE_SKEW_SECONDS` ahead of the cluster clock.
+/// * `SignatureVerificationFailed` - If no verification covers the message reconstructed
+///   from the provided arguments, including a price signed for another user's profile.
+/// * `PriceExceedsUserCap` - If the price is above the user's non-zero `max_price_per_command`.
 /// * `InsufficientDepositBalance` - If the user's balance is less than the `price`.
 /// * `RentExemptViolation` - If the payment would leave the user's PDA below the rent-exempt minimum.
 ///
 /// # Events
 ///
 /// * [`UserCommandDispatched`] - On successful dispatch and payment.
+/// * [`UserLowBalance`] - If the payment left the user's `deposit_balance` below their
+///   `low_balance_threshold` for the first time since it was last at or above it.
 pub fn user_dispatch_command(
     ctx: Context<UserDispatchCommand>,
     command_id: u16,
     price: u64,
     timestamp: i64,
     payload: Vec<u8>,
+    mode: CommandMode,
 ) -> Result<()> {
-    let user_profile = &mut ctx.accounts.user_profile;
+    let accounts = ctx.accounts;
+    settle_user_command(
+        accounts.authority.key(),
+        &mut accounts.user_profile,
+        &mut accounts.admin_profile,
+        None,
+        &accounts.instructions,
+        command_id,
+        price,
+        timestamp,
+        payload,
+        mode,
+    )
+}
 
-    require!(!user_profile.banned, BridgeError::UserIsBanned);
+/// Charges a user command against the user's [`Allowance`], without the user's signature.
+///
+/// The service (its `authority` or `oracle_authority`) submits the command on the user's
+/// behalf. Apart from the signer, the command is processed exactly like
+/// [`user_dispatch_command`]: the oracle must sign its price, and the price is paid from the
+/// user's `deposit_balance` within the user's spending cap. The allowance additionally
+/// bounds which commands can be charged, until when, and how much in total.
+///
+/// # Pre-requisites
+///
+/// The same Ed25519 signature verification as [`user_dispatch_command`].
+///
+/// # Arguments
+///
+/// * `ctx` - The context, containing the [`DispatchWithAllowance`] accounts.
+/// * `command_id`, `price`, `timestamp`, `payload`, `mode` - As in [`user_dispatch_command`].
+///
+/// # Errors
+///
+/// * `SignerUnauthorized` - If the signer is neither the admin's `authority` nor its `oracle_authority`.
+/// * `AllowanceExpired` - If the allowance expired.
+/// * `CommandNotInAllowance` - If the allowance does not cover `command_id`.
+/// * `AllowanceExhausted` - If nothing remains of the allowance or `price` exceeds what remains.
+/// * Any error of [`user_dispatch_command`].
+///
+/// # Events
+///
+/// * [`UserCommandDispatched`] and, if due, [`UserLowBalance`], as in [`user_dispatch_command`].
+/// * [`AllowanceConsumed`] - On successful dispatch and payment.
+pub fn dispatch_with_allowance(
+    ctx: Context<DispatchWithAllowance>,
+    command_id: u16,
+    price: u64,
+    timestamp: i64,
+    payload: Vec<u8>,
+    mode: CommandMode,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let allowance = &mut accounts.allowance;
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < allowance.expires_at, BridgeError::AllowanceExpired);
     require!(
-        payload.len() <= MAX_PAYLOAD_SIZE,
-        BridgeError::PayloadTooLarge
+        allowance.commands.contains(&command_id),
+        BridgeError::CommandNotInAllowance
+    );
+    require!(
+        allowance.remaining_cap > 0 && price <= allowance.remaining_cap,
+        BridgeError::AllowanceExhausted
     );
 
-    let user_profile = &mut ctx.accounts.user_profile;
-    let admin_profile = &mut ctx.accounts.admin_profile;
-    let ixs = &ctx.accounts.instructions;
+    settle_user_command(
+        accounts.user_profile.authority,
+        &mut accounts.user_profile,
+        &mut accounts.admin_profile,
+        None,
+        &accounts.instructions,
+        command_id,
+        price,
+        timestamp,
+        payload,
+        mode,
+    )?;
 
-    // --- Oracle Signature Verification ---
+    allowance.remaining_cap -= price;
+    emit!(AllowanceConsumed {
+        user_profile_pda: accounts.user_profile.key(),
+        admin_pda: accounts.admin_profile.key(),
+        charger: accounts.charger.key(),
+        command_id,
+        amount: price,
+        remaining_cap: allowance.remaining_cap,
+        ts: now,
+    });
+    Ok(())
+}
 
-    // The transaction must include an ed25519 signature verification instruction
-    // immediately before this one. We will inspect it to authenticate the price.
-    let current_ix_index = load_current_index_checked(&ixs.to_account_info())?;
+/// Dispatches a command from a user to a service, paying for it from the user's shared
+/// [`UserWallet`] instead of their `UserProfile`.
+///
+/// Apart from the source of the payment, the command is processed exactly like
+/// [`user_dispatch_command`]: the user's profile for the service must exist and not be
+/// banned, and its spending cap applies. This lets a user of several services fund one
+/// balance for all of them.
+///
+/// # Pre-requisites
+///
+/// The same Ed25519 signature verification as [`user_dispatch_command`].
+///
+/// # Arguments
+///
+/// * `ctx` - The context, containing the [`UserDispatchCommandFromWallet`] accounts.
+/// * `command_id`, `price`, `timestamp`, `payload`, `mode` - As in [`user_dispatch_command`].
+///
+/// # Errors
+///
+/// * Any error of [`user_dispatch_command`], with `InsufficientDepositBalance` and
+///   `RentExemptViolation` referring to the wallet.
+///
+/// # Events
+///
+/// * [`UserCommandDispatched`] - On successful dispatch and payment.
+/// * [`WalletCharged`] - If the command was not free.
+pub fn user_dispatch_command_from_wallet(
+    ctx: Context<UserDispatchCommandFromWallet>,
+    command_id: u16,
+    price: u64,
+    timestamp: i64,
+    payload: Vec<u8>,
+    mode: CommandMode,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    settle_user_command(
+        accounts.authority.key(),
+        &mut accounts.user_profile,
+        &mut accounts.admin_profile,
+        Some(&mut accounts.wallet),
+        &accounts.instructions,
+        command_id,
+        price,
+        timestamp,
+        payload,
+        mode,
+    )
+}
 
-    // The verification instruction must be the one immediately preceding this one.
-    require_gt!(current_ix_index, 0, BridgeError::InstructionMismatch);
-    let verify_ix_index = (current_ix_index - 1) as usize;
-    let verify_ix = load_instruction_at_checked(verify_ix_index, &ixs.to_account_info())?;
+/// Guards the dispatch paths against a stale link to an `AdminProfile`.
+///
+/// The account constraints already derive the profile's PDA from its own fields, so this
+/// only rejects a profile that holds no `authority`.
+fn require_valid_admin_profile(admin_profile: &Account<AdminProfile>) -> Result<()> {
+    require_keys_neq!(
+        admin_profile.authority,
+        Pubkey::default(),
+        BridgeError::AdminProfileInvalid
+    );
+    Ok(())
+}
 
-    // Check that it's an ed25519 program instruction.
-    require_keys_eq!(
-        verify_ix.program_id,
-        ed25519_program::ID,
-        BridgeError::InstructionMismatch
+/// Verifies and pays for a user command on behalf of `sender`, the user's wallet.
+///
+/// Shared by [`user_dispatch_command`], [`dispatch_with_allowance`] and
+/// [`user_dispatch_command_from_wallet`], which differ only in who authorizes the command
+/// and what pays for it. The price is paid from `wallet` if one is given, and from the
+/// `user_profile` otherwise.
+#[allow(clippy::too_many_arguments)]
+fn settle_user_command<'info>(
+    sender: Pubkey,
+    user_profile: &mut Account<'info, UserProfile>,
+    admin_profile: &mut Account<'info, AdminProfile>,
+    mut wallet: Option<&mut Account<'info, UserWallet>>,
+    ixs: &AccountInfo<'info>,
+    command_id: u16,
+    price: u64,
+    timestamp: i64,
+    payload: Vec<u8>,
+    mode: CommandMode,
+) -> Result<()> {
+    require_valid_admin_profile(admin_profile)?;
+    require!(!user_profile.banned, BridgeError::UserIsBanned);
+    require!(
+        payload_within_limit(&payload, admin_profile),
+        BridgeError::PayloadTooLarge
     );
 
-    // The signature is considered valid if the instruction did not fail.
-    // We now need to check *what* was signed.
-    let ix_data = &verify_ix.data;
+    // --- Oracle Signature Verification ---
 
-    // Extract pubkey, signature, and message from the instruction data.
-    // See https://docs.solana.com/developing/runtime-facilities/programs#ed25519-program
-    // let signer_pubkey_bytes = &ix_data[16..48];
-    // let message_data = &ix_data[48..];
-    let signer_pubkey_bytes = &ix_data[16..48];
-    let message_data = &ix_data[112..];
+    // The transaction must verify the oracle's signature over exactly this price message,
+    // which is bound to this user's profile.
+    let expected_message = oracle_price_message(command_id, price, timestamp, user_profile.key());
+    let signer_pubkey = find_price_signer(ixs, &expected_message)?;
 
     // Verify the signer is the admin's designated oracle.
-    let signer_pubkey = Pubkey::new_from_array(
-        signer_pubkey_bytes
-            .try_into()
-            .map_err(|_| ProgramError::InvalidInstructionData)?,
-    );
     require_keys_eq!(
         signer_pubkey,
         admin_profile.oracle_authority,
@@ -690,58 +1298,171 @@ pub fn user_dispatch_command(
         now.saturating_sub(timestamp) <= admin_profile.timestamp_validity_seconds,
         BridgeError::TimestampTooOld
     );
+    // A timestamp from the future would stay valid for longer than the admin allows.
+    require!(
+        timestamp.saturating_sub(now) <= MAX_TIMESTAMP_FUTURE_SKEW_SECONDS,
+        BridgeError::TimestampInFuture
+    );
 
-    // Reconstruct the signed message and verify it matches.
-    // The message format is: command_id (2 bytes) | price (8 bytes) | timestamp (8 bytes)
-    let expected_message = [
-        command_id.to_le_bytes().as_ref(),
-        price.to_le_bytes().as_ref(),
-        timestamp.to_le_bytes().as_ref(),
-    ]
-    .concat();
-
+    // The user's own cap applies even to a correctly signed price.
     require!(
-        message_data == expected_message,
-        BridgeError::SignatureVerificationFailed
+        price_within_user_cap(price, user_profile),
+        BridgeError::PriceExceedsUserCap
     );
 
     // --- Payment Processing ---
 
     // If the command is not free, process the payment.
     if price > 0 {
+        let (payer, deposit_balance) = match wallet.as_mut() {
+            Some(wallet) => (wallet.to_account_info(), &mut wallet.deposit_balance),
+            None => (
+                user_profile.to_account_info(),
+                &mut user_profile.deposit_balance,
+            ),
+        };
         require!(
-            user_profile.deposit_balance >= price,
+            *deposit_balance >= price,
             BridgeError::InsufficientDepositBalance
         );
 
         let rent = Rent::get()?;
-        let rent_exempt_minimum = rent.minimum_balance(user_profile.to_account_info().data_len());
+        let rent_exempt_minimum = rent.minimum_balance(payer.data_len());
         require!(
-            user_profile.to_account_info().lamports() - price >= rent_exempt_minimum,
+            payer.lamports() - price >= rent_exempt_minimum,
             BridgeError::RentExemptViolation
         );
 
-        // Transfer lamports from the user's PDA to the admin's PDA.
-        **user_profile.to_account_info().try_borrow_mut_lamports()? -= price;
+        // Transfer lamports from the paying PDA to the admin's PDA.
+        **payer.try_borrow_mut_lamports()? -= price;
         **admin_profile.to_account_info().try_borrow_mut_lamports()? += price;
 
-        // Update the internal balances of both profiles.
-        user_profile.deposit_balance -= price;
+        // Update the internal balances of the payer and the admin.
+        *deposit_balance -= price;
         admin_profile.balance += price;
     }
 
+    let ts = Clock::get()?.unix_timestamp;
     emit!(UserCommandDispatched {
-        sender: ctx.accounts.authority.key(),
+        sender,
         sender_user_pda: user_profile.key(),
         target_admin_pda: admin_profile.key(),
         command_id,
         price_paid: price,
+        oracle_authority: admin_profile.oracle_authority,
         payload,
-        ts: Clock::get()?.unix_timestamp,
+        mode,
+        ts,
     });
+
+    if let Some(wallet) = wallet {
+        if price > 0 {
+            emit!(WalletCharged {
+                wallet_pda: wallet.key(),
+                user_profile_pda: user_profile.key(),
+                admin_pda: admin_profile.key(),
+                amount: price,
+                new_deposit_balance: wallet.deposit_balance,
+                ts,
+            });
+        }
+        // The low-balance notification tracks the profile's own deposit.
+        return Ok(());
+    }
+
+    if price > 0
+        && user_profile.deposit_balance < user_profile.low_balance_threshold
+        && !user_profile.low_balance_notified
+    {
+        user_profile.low_balance_notified = true;
+        emit!(UserLowBalance {
+            authority: sender,
+            user_profile_pda: user_profile.key(),
+            admin_pda: admin_profile.key(),
+            deposit_balance: user_profile.deposit_balance,
+            threshold: user_profile.low_balance_threshold,
+            ts,
+        });
+    }
     Ok(())
 }
 
+/// Finds the Ed25519 signature verification of `expected_message` in the transaction and
+/// returns the public key it was verified against.
+///
+/// The verification may be placed anywhere in the transaction, so that compute budget or
+/// other instructions can be combined with the dispatch. The runtime rejects the whole
+/// transaction if a verification fails, so a present instruction proves its signature.
+/// To keep the signer unambiguous, exactly one verification must cover the message.
+fn find_price_signer(ixs: &AccountInfo, expected_message: &[u8]) -> Result<Pubkey> {
+    let current_index = load_current_index_checked(ixs)?;
+    // The instructions sysvar starts with the number of instructions (u16 LE).
+    let count = {
+        let data = ixs.try_borrow_data()?;
+        let count: [u8; 2] = data
+            .get(..2)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        u16::from_le_bytes(count)
+    };
+
+    let mut verifications = 0;
+    let mut signer = None;
+    for index in (0..count).filter(|index| *index != current_index) {
+        let ix = load_instruction_at_checked(index as usize, ixs)?;
+        if ix.program_id != ed25519_program::ID {
+            continue;
+        }
+        verifications += 1;
+        let Some((pubkey, message)) = parse_ed25519_instruction(&ix.data, index) else {
+            continue;
+        };
+        if message == expected_message {
+            require!(
+                signer.is_none(),
+                BridgeError::AmbiguousSignatureVerification
+            );
+            signer = Some(pubkey);
+        }
+    }
+
+    require_gt!(verifications, 0, BridgeError::InstructionMismatch);
+    signer.ok_or_else(|| error!(BridgeError::SignatureVerificationFailed))
+}
+
+/// Reads the public key and message of an Ed25519 program instruction at `index` that
+/// verifies a single signature over data stored in the instruction itself.
+///
+/// Returns `None` for any other layout, including offsets pointing into other
+/// instructions, since what was verified could then differ from what this one contains.
+/// See https://docs.solana.com/developing/runtime-facilities/programs#ed25519-program
+fn parse_ed25519_instruction(data: &[u8], index: u16) -> Option<(Pubkey, &[u8])> {
+    // A signature count and a padding byte, followed by 7 u16 offsets per signature.
+    const OFFSETS_START: usize = 2;
+    const OFFSETS_LEN: usize = 14;
+    if data.len() < OFFSETS_START + OFFSETS_LEN || data[0] != 1 {
+        return None;
+    }
+    let read_u16 = |field: usize| {
+        let at = OFFSETS_START + 2 * field;
+        u16::from_le_bytes([data[at], data[at + 1]])
+    };
+    let in_this_instruction = |ix_index: u16| ix_index == u16::MAX || ix_index == index;
+    let (pubkey_offset, pubkey_ix) = (read_u16(2) as usize, read_u16(3));
+    let (message_offset, message_len, message_ix) =
+        (read_u16(4) as usize, read_u16(5) as usize, read_u16(6));
+    if !in_this_instruction(pubkey_ix) || !in_this_instruction(message_ix) {
+        return None;
+    }
+
+    let pubkey: [u8; 32] = data
+        .get(pubkey_offset..pubkey_offset + 32)?
+        .try_into()
+        .ok()?;
+    let message = data.get(message_offset..message_offset + message_len)?;
+    Some((Pubkey::new_from_array(pubkey), message))
+}
+
 /// Logs a significant off-chain action to the blockchain.
 ///
 /// This creates an immutable, auditable record of events that happen outside the
@@ -753,11 +1474,29 @@ pub fn user_dispatch_command(
 /// * `ctx` - The context, containing the [`LogAction`] accounts.
 /// * `session_id` - A `u64` identifier to correlate this action with a session.
 /// * `action_code` - A `u16` code representing the specific off-chain action.
+/// * `category` - The namespace `action_code` belongs to. See [`crate::action_categories`].
+/// * `metadata` - An optional detail blob (e.g. a resource id), at most `MAX_METADATA_SIZE` bytes.
+///
+/// # Errors
+///
+/// * `MetadataTooLarge` - If `metadata` exceeds `MAX_METADATA_SIZE`.
 ///
 /// # Events
 ///
 /// * [`OffChainActionLogged`] - On successful logging.
-pub fn log_action(ctx: Context<LogAction>, session_id: u64, action_code: u16) -> Result<()> {
+pub fn log_action(
+    ctx: Context<LogAction>,
+    session_id: u64,
+    action_code: u16,
+    category: u8,
+    metadata: Option<Vec<u8>>,
+) -> Result<()> {
+    let metadata = metadata.unwrap_or_default();
+    require!(
+        metadata.len() <= MAX_METADATA_SIZE,
+        BridgeError::MetadataTooLarge
+    );
+
     let actor = ctx.accounts.authority.key();
 
     emit!(OffChainActionLogged {
@@ -765,7 +1504,9 @@ pub fn log_action(ctx: Context<LogAction>, session_id: u64, action_code: u16) ->
         user_profile_pda: ctx.accounts.user_profile.key(),
         admin_profile_pda: ctx.accounts.admin_profile.key(),
         session_id,
+        category,
         action_code,
+        metadata,
         ts: Clock::get()?.unix_timestamp,
     });
     Ok(())
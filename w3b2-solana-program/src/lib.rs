@@ -14,6 +14,8 @@
 //!     like the oracle key and serves as a treasury for collected fees.
 //!   - [`UserProfile`]: Represents an end-user's relationship with a specific service. It
 //!     holds the user's pre-paid deposit balance for that service.
+//!   - [`UserWallet`]: An opt-in deposit shared by all of a user's profiles, for users who
+//!     prefer to fund one balance for several services.
 //!
 //! - **Non-Custodial Payments:** Users deposit funds into their own `UserProfile` PDA, which
 //!   is controlled by the program. Payments for services are transferred from the user's
@@ -36,11 +38,13 @@
 //! - [`state`]: Defines the data structures for all on-chain accounts (PDAs).
 //! - [`events`]: Declares all on-chain events emitted by the program.
 //! - [`errors`]: Defines custom errors for clear and specific failure modes.
+//! - [`action_categories`]: Named `category` values for `log_action` shared with off-chain consumers.
 
 #![allow(deprecated)]
 #![allow(unexpected_cfgs)]
 #![allow(elided_lifetimes_in_paths)]
 
+pub mod action_categories;
 pub mod errors;
 pub mod events;
 pub mod instructions;
@@ -50,6 +54,7 @@ pub mod state;
 use anchor_lang::prelude::*;
 use errors::*;
 use events::*;
+use protocols::CommandMode;
 use state::*;
 
 declare_id!("HykRMCadVCe49q4GVrXKTwLG3fqCEgd5W5qQqN3AFAEY");
@@ -69,11 +74,12 @@ pub mod w3b2_solana_program {
     pub fn admin_register_profile(
         ctx: Context<AdminRegisterProfile>,
         communication_pubkey: Pubkey,
+        index: Option<u16>,
     ) -> Result<()> {
-        instructions::admin_register_profile(ctx, communication_pubkey)
+        instructions::admin_register_profile(ctx, communication_pubkey, index)
     }
 
-    /// Closes an `AdminProfile` account and refunds its rent lamports to the owner.
+    /// Closes an `AdminProfile` account and returns its rent and remaining balance to the owner.
     /// See [`instructions::admin_close_profile`] for details.
     pub fn admin_close_profile(ctx: Context<AdminCloseProfile>) -> Result<()> {
         instructions::admin_close_profile(ctx)
@@ -81,12 +87,17 @@ pub mod w3b2_solana_program {
 
     /// Sets or updates the configuration for an existing `AdminProfile`.
     /// See [`instructions::admin_set_config`] for details.
+    #[allow(clippy::too_many_arguments)]
     pub fn admin_set_config(
         ctx: Context<AdminSetConfig>,
         new_oracle_authority: Option<Pubkey>,
         new_timestamp_validity: Option<i64>,
         new_communication_pubkey: Option<Pubkey>,
         new_unban_fee: Option<u64>,
+        new_max_payload_size: Option<u16>,
+        new_unban_fee_multiplier_bps: Option<u16>,
+        new_max_unban_fee_multiple: Option<u16>,
+        new_require_distinct_oracle: Option<bool>,
     ) -> Result<()> {
         instructions::admin_set_config(
             ctx,
@@ -94,6 +105,10 @@ pub mod w3b2_solana_program {
             new_timestamp_validity,
             new_communication_pubkey,
             new_unban_fee,
+            new_max_payload_size,
+            new_unban_fee_multiplier_bps,
+            new_max_unban_fee_multiple,
+            new_require_distinct_oracle,
         )
     }
 
@@ -103,14 +118,21 @@ pub mod w3b2_solana_program {
         instructions::admin_withdraw(ctx, amount)
     }
 
+    /// Withdraws the entire internal balance of an `AdminProfile`.
+    /// See [`instructions::admin_withdraw_all`] for details.
+    pub fn admin_withdraw_all(ctx: Context<AdminWithdrawAll>) -> Result<()> {
+        instructions::admin_withdraw_all(ctx)
+    }
+
     /// Dispatches a non-financial command from an admin to a user.
     /// See [`instructions::admin_dispatch_command`] for details.
     pub fn admin_dispatch_command(
         ctx: Context<AdminDispatchCommand>,
         command_id: u64,
         payload: Vec<u8>,
+        mode: CommandMode,
     ) -> Result<()> {
-        instructions::admin_dispatch_command(ctx, command_id, payload)
+        instructions::admin_dispatch_command(ctx, command_id, payload, mode)
     }
 
     /// Bans a user, preventing them from interacting with the service.
@@ -143,6 +165,35 @@ pub mod w3b2_solana_program {
         instructions::user_update_comm_key(ctx, new_key)
     }
 
+    /// Sets the deposit balance below which paid commands emit a `UserLowBalance` event.
+    /// See [`instructions::user_set_low_balance_threshold`] for details.
+    pub fn user_set_low_balance_threshold(
+        ctx: Context<UserSetLowBalanceThreshold>,
+        threshold: u64,
+    ) -> Result<()> {
+        instructions::user_set_low_balance_threshold(ctx, threshold)
+    }
+
+    /// Caps the oracle price the user accepts for a single command.
+    /// See [`instructions::user_set_spending_cap`] for details.
+    pub fn user_set_spending_cap(
+        ctx: Context<UserSetSpendingCap>,
+        max_price_per_command: u64,
+    ) -> Result<()> {
+        instructions::user_set_spending_cap(ctx, max_price_per_command)
+    }
+
+    /// Grants the user's service a time-boxed allowance to charge a set of commands.
+    /// See [`instructions::user_grant_allowance`] for details.
+    pub fn user_grant_allowance(
+        ctx: Context<UserGrantAllowance>,
+        commands: Vec<u16>,
+        total_cap: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::user_grant_allowance(ctx, commands, total_cap, expires_at)
+    }
+
     /// Closes a `UserProfile` account and refunds all lamports to the user.
     /// See [`instructions::user_close_profile`] for details.
     pub fn user_close_profile(ctx: Context<UserCloseProfile>) -> Result<()> {
@@ -167,6 +218,26 @@ pub mod w3b2_solana_program {
         instructions::user_request_unban(ctx)
     }
 
+    // --- Shared Wallet Instructions ---
+
+    /// Creates the user's shared `UserWallet` PDA, which can pay for commands to any service.
+    /// See [`instructions::user_create_wallet`] for details.
+    pub fn user_create_wallet(ctx: Context<UserCreateWallet>) -> Result<()> {
+        instructions::user_create_wallet(ctx)
+    }
+
+    /// Deposits lamports into the user's shared `UserWallet`.
+    /// See [`instructions::wallet_deposit`] for details.
+    pub fn wallet_deposit(ctx: Context<WalletDeposit>, amount: u64) -> Result<()> {
+        instructions::wallet_deposit(ctx, amount)
+    }
+
+    /// Withdraws unspent funds from the user's shared `UserWallet`.
+    /// See [`instructions::wallet_withdraw`] for details.
+    pub fn wallet_withdraw(ctx: Context<WalletWithdraw>, amount: u64) -> Result<()> {
+        instructions::wallet_withdraw(ctx, amount)
+    }
+
     // --- Operational Instructions ---
 
     /// Dispatches a command from a user to a service, verifying a signed price from an oracle.
@@ -177,13 +248,48 @@ pub mod w3b2_solana_program {
         price: u64,
         timestamp: i64,
         payload: Vec<u8>,
+        mode: CommandMode,
     ) -> Result<()> {
-        instructions::user_dispatch_command(ctx, command_id, price, timestamp, payload)
+        instructions::user_dispatch_command(ctx, command_id, price, timestamp, payload, mode)
+    }
+
+    /// Dispatches a command like `user_dispatch_command`, paying from the user's shared `UserWallet`.
+    /// See [`instructions::user_dispatch_command_from_wallet`] for details.
+    pub fn user_dispatch_command_from_wallet(
+        ctx: Context<UserDispatchCommandFromWallet>,
+        command_id: u16,
+        price: u64,
+        timestamp: i64,
+        payload: Vec<u8>,
+        mode: CommandMode,
+    ) -> Result<()> {
+        instructions::user_dispatch_command_from_wallet(
+            ctx, command_id, price, timestamp, payload, mode,
+        )
+    }
+
+    /// Charges a user command against the user's allowance, signed by the service.
+    /// See [`instructions::dispatch_with_allowance`] for details.
+    pub fn dispatch_with_allowance(
+        ctx: Context<DispatchWithAllowance>,
+        command_id: u16,
+        price: u64,
+        timestamp: i64,
+        payload: Vec<u8>,
+        mode: CommandMode,
+    ) -> Result<()> {
+        instructions::dispatch_with_allowance(ctx, command_id, price, timestamp, payload, mode)
     }
 
     /// Logs a significant off-chain action to the blockchain for an audit trail.
     /// See [`instructions::log_action`] for details.
-    pub fn log_action(ctx: Context<LogAction>, session_id: u64, action_code: u16) -> Result<()> {
-        instructions::log_action(ctx, session_id, action_code)
+    pub fn log_action(
+        ctx: Context<LogAction>,
+        session_id: u64,
+        action_code: u16,
+        category: u8,
+        metadata: Option<Vec<u8>>,
+    ) -> Result<()> {
+        instructions::log_action(ctx, session_id, action_code, category, metadata)
     }
 }
@@ -16,7 +16,11 @@ use crate::instructions::MAX_PAYLOAD_SIZE;
 
 /// Defines the expected communication flow for an off-chain service after
 /// receiving a command via a `dispatch` instruction.
-#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// The mode is passed to `user_dispatch_command` and `admin_dispatch_command` and recorded
+/// in the events they emit, so off-chain routers can set up a reply channel when one is
+/// expected.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum CommandMode {
     /// The off-chain service is expected to process the command and subsequently
     /// initiate a new on-chain transaction (e.g., `admin_dispatch_command`) to
@@ -24,6 +28,7 @@ pub enum CommandMode {
     RequestResponse = 0,
     /// The on-chain command is the final step in the sequence. The off-chain service
     /// executes the requested action, but no on-chain response is expected.
+    #[default]
     OneWay = 1,
 }
 
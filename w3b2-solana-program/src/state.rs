@@ -13,6 +13,7 @@
 //!     are derived correctly.
 
 use crate::errors::BridgeError;
+use crate::instructions::MAX_ALLOWANCE_COMMANDS;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program;
 
@@ -23,10 +24,10 @@ use anchor_lang::solana_program;
 /// Represents the on-chain profile for a Service Provider (an "Admin").
 ///
 /// This PDA holds the service's configuration and serves as a treasury for collected fees.
-/// Its address is deterministically derived from the admin's wallet key, ensuring that
-/// each admin can only have one profile.
+/// Its address is deterministically derived from the admin's wallet key and a profile
+/// index, so a single wallet can operate several independent services.
 ///
-/// - **PDA Seeds:** `[b"admin", authority.key().as_ref()]`
+/// - **PDA Seeds:** `[b"admin", authority.key().as_ref(), &admin_index_seed(index)]`
 #[account]
 #[derive(Debug)]
 pub struct AdminProfile {
@@ -49,6 +50,38 @@ pub struct AdminProfile {
     /// The fee in lamports that a banned user must pay to request an unban.
     /// This can be configured by the admin.
     pub unban_fee: u64,
+    /// The maximum size in bytes of the `payload` accepted by this service's dispatch
+    /// instructions. Defaults to, and can never exceed, the global `MAX_PAYLOAD_SIZE`.
+    pub max_payload_size: u16,
+    /// The profile index chosen at registration, which lets one `authority` run several
+    /// services. Profiles registered before indices existed read as `0`.
+    pub index: u16,
+    /// How much the unban fee grows with each earlier ban of the same user, in basis points
+    /// of `unban_fee`. `0` keeps the fee flat. See `escalated_unban_fee`.
+    pub unban_fee_multiplier_bps: u16,
+    /// The upper bound of the escalated unban fee, as a multiple of `unban_fee`. `0` means
+    /// the fee is not capped.
+    pub max_unban_fee_multiple: u16,
+    /// Incremented every time `communication_pubkey` is replaced, so that off-chain key
+    /// caches can tell which of two observed keys is newer. Starts at `0`.
+    pub comm_key_version: u32,
+    /// When set, `admin_set_config` refuses any configuration in which the
+    /// `oracle_authority` equals the `authority`, so that a single compromised key cannot
+    /// both move funds and sign prices. Off by default.
+    pub require_distinct_oracle: bool,
+}
+
+/// Returns the seed that encodes an admin profile `index` in its PDA derivation.
+///
+/// Index `0` maps to an empty seed. Because PDA seeds are concatenated before hashing,
+/// `[b"admin", authority, &[]]` yields the same address as the original
+/// `[b"admin", authority]` derivation, so existing profiles keep their addresses.
+pub fn admin_index_seed(index: u16) -> Vec<u8> {
+    if index == 0 {
+        Vec::new()
+    } else {
+        index.to_le_bytes().to_vec()
+    }
 }
 
 /// # User Profile
@@ -80,6 +113,62 @@ pub struct UserProfile {
     /// A flag indicating that the user has paid the `unban_fee` and requested to be unbanned.
     /// This does not automatically lift the ban; it only signals the request to the admin.
     pub unban_requested: bool,
+    /// The `deposit_balance` in lamports below which a paid command emits a `UserLowBalance`
+    /// event, prompting the service to ask for a top-up. `0` disables the notification.
+    pub low_balance_threshold: u64,
+    /// Set once `UserLowBalance` has been emitted, so that it fires only once per crossing.
+    /// Cleared when a deposit lifts the balance back to the threshold or the threshold changes.
+    pub low_balance_notified: bool,
+    /// The number of times the admin has banned this user. Earlier bans raise the fee of
+    /// `user_request_unban` when the admin sets an `unban_fee_multiplier_bps`.
+    pub ban_count: u32,
+    /// The highest oracle price in lamports the user accepts for a single command, set by the
+    /// user to bound what a compromised oracle can charge. `0` means no cap.
+    pub max_price_per_command: u64,
+    /// Incremented every time `communication_pubkey` is replaced, so that off-chain key
+    /// caches can tell which of two observed keys is newer. Starts at `0`.
+    pub comm_key_version: u32,
+}
+
+/// # Allowance
+///
+/// A user's time-boxed authorization for their service to charge a fixed set of commands
+/// without the user signing each one, e.g. for metered APIs. The oracle still signs the
+/// price of every command, which is paid from the user's `deposit_balance` as usual.
+///
+/// A user has at most one allowance per service. Granting a new one replaces it.
+///
+/// - **PDA Seeds:** `[b"allowance", user_profile.key().as_ref()]`
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct Allowance {
+    /// The `UserProfile` PDA the allowance draws from.
+    pub user_profile: Pubkey,
+    /// The commands the service may charge. At most `MAX_ALLOWANCE_COMMANDS`.
+    #[max_len(MAX_ALLOWANCE_COMMANDS)]
+    pub commands: Vec<u16>,
+    /// What the service may still charge in lamports. Decremented by every command.
+    pub remaining_cap: u64,
+    /// The Unix timestamp from which the allowance can no longer be drawn against.
+    pub expires_at: i64,
+}
+
+/// # User Wallet
+///
+/// An opt-in deposit shared by all of a user's `UserProfile`s, so that a user of several
+/// services does not have to fund each profile separately. Paid commands draw from it
+/// through `user_dispatch_command_from_wallet`; the profile of each service still decides
+/// whether the user is banned and how much a single command may cost.
+///
+/// - **PDA Seeds:** `[b"wallet", authority.key().as_ref()]`
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct UserWallet {
+    /// The public key of the user's wallet. This key is the sole `authority` allowed to
+    /// deposit into, withdraw from and spend the shared balance.
+    pub authority: Pubkey,
+    /// The shared prepaid balance in lamports.
+    pub deposit_balance: u64,
 }
 
 // --- Instruction Accounts Structs ---
@@ -90,18 +179,23 @@ pub struct UserProfile {
 ///
 /// Defines the accounts required to initialize a new `AdminProfile` for a service provider.
 #[derive(Accounts)]
+#[instruction(communication_pubkey: Pubkey, index: Option<u16>)]
 pub struct AdminRegisterProfile<'info> {
     /// The `Signer` (the admin's wallet) who will become the owner of the new `AdminProfile`.
     /// This account pays for the creation of the `admin_profile` PDA.
     #[account(mut)]
     pub authority: Signer<'info>,
     /// The new `AdminProfile` account to be initialized. Its address is a PDA
-    /// derived from the `authority`'s key, ensuring one profile per admin.
+    /// derived from the `authority`'s key and the profile `index` (default `0`).
     #[account(
         init,
         payer = authority,
         space = 8 + std::mem::size_of::<AdminProfile>(),
-        seeds = [b"admin", authority.key().as_ref()],
+        seeds = [
+            b"admin",
+            authority.key().as_ref(),
+            &admin_index_seed(index.unwrap_or_default())[..]
+        ],
         bump
     )]
     pub admin_profile: Account<'info, AdminProfile>,
@@ -122,7 +216,7 @@ pub struct AdminWithdraw<'info> {
     /// verify that the `authority` is the legitimate owner and the PDA seeds are correct.
     #[account(
         mut,
-        seeds = [b"admin", authority.key().as_ref()],
+        seeds = [b"admin", authority.key().as_ref(), &admin_index_seed(admin_profile.index)[..]],
         bump,
         constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
@@ -137,6 +231,32 @@ pub struct AdminWithdraw<'info> {
     pub destination: AccountInfo<'info>,
 }
 
+/// # Accounts for `admin_withdraw_all`
+///
+/// Defines the accounts required for an admin to withdraw the entire internal balance
+/// of their `AdminProfile`.
+#[derive(Accounts)]
+pub struct AdminWithdrawAll<'info> {
+    /// The `Signer` (the admin's wallet) who must be the `authority` of the `admin_profile`.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The `AdminProfile` to be emptied. Constraints verify that the `authority` is the
+    /// legitimate owner and the PDA seeds are correct.
+    #[account(
+        mut,
+        seeds = [b"admin", authority.key().as_ref(), &admin_index_seed(admin_profile.index)[..]],
+        bump,
+        constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
+    )]
+    pub admin_profile: Account<'info, AdminProfile>,
+    /// The account that will receive the withdrawn lamports.
+    ///
+    /// **Security:** `CHECK:` is used here because this account is only a destination for a
+    /// lamport transfer from a program-controlled PDA. No data is read from or written to it.
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+}
+
 /// # Accounts for `admin_set_config`
 ///
 /// Defines the accounts required for an admin to update their `AdminProfile`'s configuration,
@@ -150,7 +270,7 @@ pub struct AdminSetConfig<'info> {
     /// and the account's PDA seeds.
     #[account(
         mut,
-        seeds = [b"admin", authority.key().as_ref()],
+        seeds = [b"admin", authority.key().as_ref(), &admin_index_seed(admin_profile.index)[..]],
         bump,
         constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
@@ -169,7 +289,7 @@ pub struct AdminBanUser<'info> {
     /// The `AdminProfile` of the admin initiating the ban. Constraints verify
     /// the `authority` and the PDA seeds.
     #[account(
-        seeds = [b"admin", authority.key().as_ref()],
+        seeds = [b"admin", authority.key().as_ref(), &admin_index_seed(admin_profile.index)[..]],
         bump,
         constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
@@ -195,7 +315,7 @@ pub struct AdminUnbanUser<'info> {
     /// The `AdminProfile` of the admin initiating the unban. Constraints verify
     /// the `authority` and the PDA seeds.
     #[account(
-        seeds = [b"admin", authority.key().as_ref()],
+        seeds = [b"admin", authority.key().as_ref(), &admin_index_seed(admin_profile.index)[..]],
         bump,
         constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
@@ -221,7 +341,7 @@ pub struct UserRequestUnban<'info> {
     /// will be credited with the `unban_fee`.
     #[account(
         mut,
-        seeds = [b"admin", admin_profile.authority.as_ref()],
+        seeds = [b"admin", admin_profile.authority.as_ref(), &admin_index_seed(admin_profile.index)[..]],
         bump
     )]
     pub admin_profile: Account<'info, AdminProfile>,
@@ -239,11 +359,11 @@ pub struct UserRequestUnban<'info> {
 
 /// # Accounts for `admin_close_profile`
 ///
-/// Defines the accounts required to close an `AdminProfile` and reclaim its rent lamports.
+/// Defines the accounts required to close an `AdminProfile` and reclaim its lamports.
 #[derive(Accounts)]
 pub struct AdminCloseProfile<'info> {
     /// The `Signer` (the admin's wallet) who must be the `authority` of the `admin_profile`.
-    /// This account will receive the rent lamports back from the closed account.
+    /// This account receives the rent and any remaining internal balance of the closed account.
     #[account(mut)]
     pub authority: Signer<'info>,
     /// The `AdminProfile` account to be closed. Constraints verify the `authority`
@@ -252,7 +372,7 @@ pub struct AdminCloseProfile<'info> {
     #[account(
         mut,
         close = authority,
-        seeds = [b"admin", authority.key().as_ref()],
+        seeds = [b"admin", authority.key().as_ref(), &admin_index_seed(admin_profile.index)[..]],
         bump,
         constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
@@ -270,7 +390,7 @@ pub struct AdminDispatchCommand<'info> {
     /// The admin's own profile PDA. Constraints ensure that the `admin_authority`
     /// is the legitimate owner of this profile.
     #[account(
-        seeds = [b"admin", admin_authority.key().as_ref()],
+        seeds = [b"admin", admin_authority.key().as_ref(), &admin_index_seed(admin_profile.index)[..]],
         bump,
         constraint = admin_profile.authority == admin_authority.key() @ BridgeError::SignerUnauthorized
     )]
@@ -297,7 +417,10 @@ pub struct UserCreateProfile<'info> {
     pub authority: Signer<'info>,
     /// The `AdminProfile` PDA that this new user profile will be linked to.
     /// This account is read-only but its existence and PDA derivation are verified.
-    #[account(seeds = [b"admin", admin_profile.authority.as_ref()], bump)]
+    #[account(
+        seeds = [b"admin", admin_profile.authority.as_ref(), &admin_index_seed(admin_profile.index)[..]],
+        bump
+    )]
     pub admin_profile: Account<'info, AdminProfile>,
     /// The new `UserProfile` account to be initialized. Its address is a PDA
     /// derived from the user's `authority` key and the `admin_profile`'s PDA key,
@@ -392,6 +515,80 @@ pub struct UserUpdateCommKey<'info> {
     pub user_profile: Account<'info, UserProfile>,
 }
 
+/// # Accounts for `user_set_low_balance_threshold`
+///
+/// Defines the accounts for a user to set the balance below which they want to be notified.
+#[derive(Accounts)]
+pub struct UserSetLowBalanceThreshold<'info> {
+    /// The `Signer` (the user's wallet) who must be the `authority` of the `user_profile`.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The `AdminProfile` associated with the `user_profile`, required to derive the user PDA.
+    pub admin_profile: Account<'info, AdminProfile>,
+    /// The `UserProfile` account whose `low_balance_threshold` will be updated.
+    #[account(
+        mut,
+        seeds = [b"user", authority.key().as_ref(), admin_profile.key().as_ref()],
+        bump,
+        constraint = user_profile.authority == authority.key() @ BridgeError::SignerUnauthorized,
+        constraint = user_profile.admin_profile_on_creation == admin_profile.key() @ BridgeError::AdminMismatch
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// # Accounts for `user_set_spending_cap`
+///
+/// Defines the accounts for a user to cap the price they pay for a single command.
+#[derive(Accounts)]
+pub struct UserSetSpendingCap<'info> {
+    /// The `Signer` (the user's wallet) who must be the `authority` of the `user_profile`.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The `AdminProfile` associated with the `user_profile`, required to derive the user PDA.
+    pub admin_profile: Account<'info, AdminProfile>,
+    /// The `UserProfile` account whose `max_price_per_command` will be updated.
+    #[account(
+        mut,
+        seeds = [b"user", authority.key().as_ref(), admin_profile.key().as_ref()],
+        bump,
+        constraint = user_profile.authority == authority.key() @ BridgeError::SignerUnauthorized,
+        constraint = user_profile.admin_profile_on_creation == admin_profile.key() @ BridgeError::AdminMismatch
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// # Accounts for `user_grant_allowance`
+///
+/// Defines the accounts for a user to grant their service an [`Allowance`].
+#[derive(Accounts)]
+pub struct UserGrantAllowance<'info> {
+    /// The `Signer` (the user's wallet) who must be the `authority` of the `user_profile`.
+    /// This account pays for the creation of the `allowance` PDA.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The `AdminProfile` associated with the `user_profile`, required to derive the user PDA.
+    pub admin_profile: Account<'info, AdminProfile>,
+    /// The `UserProfile` the allowance draws from.
+    #[account(
+        seeds = [b"user", authority.key().as_ref(), admin_profile.key().as_ref()],
+        bump,
+        constraint = user_profile.authority == authority.key() @ BridgeError::SignerUnauthorized,
+        constraint = user_profile.admin_profile_on_creation == admin_profile.key() @ BridgeError::AdminMismatch
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    /// The `Allowance` to create, or to replace if the user already granted one.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Allowance::INIT_SPACE,
+        seeds = [b"allowance", user_profile.key().as_ref()],
+        bump
+    )]
+    pub allowance: Account<'info, Allowance>,
+    /// The Solana System Program, required by Anchor for account creation.
+    pub system_program: Program<'info, System>,
+}
+
 /// # Accounts for `user_close_profile`
 ///
 /// Defines the accounts to close a `UserProfile`, reclaiming its rent and any remaining deposit balance.
@@ -438,7 +635,7 @@ pub struct UserDispatchCommand<'info> {
     /// command `price`. Its seeds are checked to ensure it's a valid profile.
     #[account(
         mut,
-        seeds = [b"admin", admin_profile.authority.as_ref()],
+        seeds = [b"admin", admin_profile.authority.as_ref(), &admin_index_seed(admin_profile.index)[..]],
         bump
     )]
     pub admin_profile: Account<'info, AdminProfile>,
@@ -451,6 +648,46 @@ pub struct UserDispatchCommand<'info> {
     pub instructions: UncheckedAccount<'info>,
 }
 
+/// # Accounts for `dispatch_with_allowance`
+///
+/// Defines the accounts for a service to charge a user command against the user's
+/// [`Allowance`], without the user's signature.
+#[derive(Accounts)]
+pub struct DispatchWithAllowance<'info> {
+    /// The `Signer` of the transaction, which must be the admin's `authority` or
+    /// `oracle_authority`, so that only the service can draw against the allowance.
+    pub charger: Signer<'info>,
+    /// The user's profile PDA. It is debited for the command `price`.
+    #[account(
+        mut,
+        seeds = [b"user", user_profile.authority.as_ref(), admin_profile.key().as_ref()],
+        bump,
+        constraint = user_profile.admin_profile_on_creation == admin_profile.key() @ BridgeError::AdminMismatch
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    /// The `AdminProfile` of the service. It is credited with the command `price`.
+    #[account(
+        mut,
+        seeds = [b"admin", admin_profile.authority.as_ref(), &admin_index_seed(admin_profile.index)[..]],
+        bump,
+        constraint = (admin_profile.authority == charger.key() || admin_profile.oracle_authority == charger.key()) @ BridgeError::SignerUnauthorized
+    )]
+    pub admin_profile: Account<'info, AdminProfile>,
+    /// The user's `Allowance`, debited for the command `price`.
+    #[account(
+        mut,
+        seeds = [b"allowance", user_profile.key().as_ref()],
+        bump
+    )]
+    pub allowance: Account<'info, Allowance>,
+    /// The Instructions sysvar (`ixs`), used to verify the oracle's price signature.
+    ///
+    /// **Security:** `CHECK:` is used as we are only reading instruction data from this
+    /// sysvar account, not deserializing its data.
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
 /// # Accounts for `log_action`
 ///
 /// Defines the accounts for logging a significant off-chain action to the blockchain.
@@ -473,8 +710,119 @@ pub struct LogAction<'info> {
     /// The `AdminProfile` associated with the action being logged.
     #[account(
         mut,
-        seeds = [b"admin", admin_profile.authority.as_ref()],
+        seeds = [b"admin", admin_profile.authority.as_ref(), &admin_index_seed(admin_profile.index)[..]],
+        bump,
+    )]
+    pub admin_profile: Account<'info, AdminProfile>,
+}
+
+// --- Shared Wallet Instructions ---
+
+/// # Accounts for `user_create_wallet`
+///
+/// Defines the accounts for a user to create their shared [`UserWallet`].
+#[derive(Accounts)]
+pub struct UserCreateWallet<'info> {
+    /// The `Signer` (the user's wallet) who will become the owner of the new `UserWallet`.
+    /// This account pays for the creation of the `wallet` PDA.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The new `UserWallet` account to be initialized. A user has at most one.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + UserWallet::INIT_SPACE,
+        seeds = [b"wallet", authority.key().as_ref()],
+        bump
+    )]
+    pub wallet: Account<'info, UserWallet>,
+    /// The Solana System Program, required by Anchor for account creation (`init`).
+    pub system_program: Program<'info, System>,
+}
+
+/// # Accounts for `wallet_deposit`
+///
+/// Defines the accounts for a user to deposit lamports into their shared [`UserWallet`].
+#[derive(Accounts)]
+pub struct WalletDeposit<'info> {
+    /// The `Signer` (the user's wallet) who must be the `authority` of the `wallet`.
+    /// This account is the source of the deposited funds.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The `UserWallet` to receive the deposit.
+    #[account(
+        mut,
+        seeds = [b"wallet", authority.key().as_ref()],
+        bump,
+        constraint = wallet.authority == authority.key() @ BridgeError::SignerUnauthorized
+    )]
+    pub wallet: Account<'info, UserWallet>,
+    /// The System Program, required to perform the lamport transfer from the user's
+    /// wallet to the `wallet` PDA.
+    pub system_program: Program<'info, System>,
+}
+
+/// # Accounts for `wallet_withdraw`
+///
+/// Defines the accounts for a user to withdraw unspent funds from their shared [`UserWallet`].
+#[derive(Accounts)]
+pub struct WalletWithdraw<'info> {
+    /// The `Signer` (the user's wallet) who must be the `authority` of the `wallet`.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The `UserWallet` from which funds will be withdrawn.
+    #[account(
+        mut,
+        seeds = [b"wallet", authority.key().as_ref()],
         bump,
+        constraint = wallet.authority == authority.key() @ BridgeError::SignerUnauthorized
+    )]
+    pub wallet: Account<'info, UserWallet>,
+    /// The account that will receive the withdrawn lamports.
+    ///
+    /// **Security:** `CHECK:` is used here because this account is only a destination for a
+    /// lamport transfer. No data is read from or written to it.
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+}
+
+/// # Accounts for `user_dispatch_command_from_wallet`
+///
+/// The accounts of [`UserDispatchCommand`], plus the user's shared [`UserWallet`], which pays
+/// for the command instead of the `user_profile`.
+#[derive(Accounts)]
+pub struct UserDispatchCommandFromWallet<'info> {
+    /// The `Signer` of the transaction (the user's wallet).
+    pub authority: Signer<'info>,
+    /// The user's profile PDA for the target service. It is not debited, but its ban status
+    /// and spending cap still apply.
+    #[account(
+        seeds = [b"user", authority.key().as_ref(), admin_profile.key().as_ref()],
+        bump,
+        constraint = user_profile.authority == authority.key() @ BridgeError::SignerUnauthorized,
+        constraint = user_profile.admin_profile_on_creation == admin_profile.key() @ BridgeError::AdminMismatch
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    /// The target `AdminProfile` of the service being called. It is credited with the
+    /// command `price`.
+    #[account(
+        mut,
+        seeds = [b"admin", admin_profile.authority.as_ref(), &admin_index_seed(admin_profile.index)[..]],
+        bump
     )]
     pub admin_profile: Account<'info, AdminProfile>,
+    /// The user's shared `UserWallet`. It is debited for the command `price`.
+    #[account(
+        mut,
+        seeds = [b"wallet", authority.key().as_ref()],
+        bump,
+        constraint = wallet.authority == authority.key() @ BridgeError::SignerUnauthorized
+    )]
+    pub wallet: Account<'info, UserWallet>,
+    /// The Instructions sysvar (`ixs`), used to verify the oracle's price signature.
+    ///
+    /// **Security:** `CHECK:` is used as we are only reading instruction data from this
+    /// sysvar account, not deserializing its data.
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
 }
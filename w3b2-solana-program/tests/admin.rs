@@ -13,6 +13,9 @@ use instructions::*;
 use solana_program::native_token::LAMPORTS_PER_SOL;
 use solana_program::sysvar::rent::Rent;
 use solana_sdk::signature::{Keypair, Signer};
+use w3b2_solana_program::events::{
+    AdminFundsWithdrawn, AdminProfileClosed, AdminProfileRegistered,
+};
 use w3b2_solana_program::state::{AdminProfile, UserProfile};
 
 use crate::instructions::user::DispatchCommandArgs;
@@ -29,7 +32,8 @@ fn test_admin_create_profile_success() {
 
     // === 2. Act (Execution) ===
     println!("Attempting to create admin profile...");
-    let admin_pda = admin::create_profile(&mut svm, &authority, comm_key.pubkey());
+    let (admin_pda, logs) =
+        admin::create_profile_with_logs(&mut svm, &authority, comm_key.pubkey());
     println!("Admin profile created successfully at: {admin_pda}");
 
     // === 3. Assert (Verification) ===
@@ -44,6 +48,28 @@ fn test_admin_create_profile_success() {
         "Balance should be 0 on initialization"
     );
 
+    // The event carries the initial configuration of the profile.
+    let registered = &parse_events::<AdminProfileRegistered>(&logs)[0];
+    assert_eq!(registered.admin_pda, admin_pda);
+    assert_eq!(registered.authority, authority.pubkey());
+    assert_eq!(registered.communication_pubkey, comm_key.pubkey());
+    assert_eq!(registered.index, admin_profile.index);
+    assert_eq!(registered.oracle_authority, authority.pubkey());
+    assert_eq!(
+        registered.timestamp_validity_seconds,
+        admin_profile.timestamp_validity_seconds
+    );
+    assert_eq!(registered.max_payload_size, admin_profile.max_payload_size);
+    assert_eq!(registered.unban_fee, admin_profile.unban_fee);
+    assert_eq!(
+        registered.unban_fee_multiplier_bps,
+        admin_profile.unban_fee_multiplier_bps
+    );
+    assert_eq!(
+        registered.max_unban_fee_multiple,
+        admin_profile.max_unban_fee_multiple
+    );
+
     let rent = Rent::default();
     let space = 8 + std::mem::size_of::<AdminProfile>();
     let rent_exempt_minimum = rent.minimum_balance(space);
@@ -89,6 +115,50 @@ fn test_admin_close_profile_success() {
     println!("   -> Authority balance correctly refunded: {authority_balance_before} -> {authority_balance_after}");
 }
 
+/// Tests that one authority can register several `AdminProfile`s under different indices.
+/// Verifies that index `0` keeps the original `[b"admin", authority]` address and that the
+/// profiles are independent: closing one leaves the others untouched.
+#[test]
+fn test_admin_multiple_profiles_per_authority() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let comm_key = create_keypair();
+
+    let (legacy_pda, _) = solana_program::pubkey::Pubkey::find_program_address(
+        &[b"admin", authority.pubkey().as_ref()],
+        &w3b2_solana_program::ID,
+    );
+
+    // === 2. Act ===
+    println!("Registering admin profiles 0, 1 and 2 under one authority...");
+    let pdas: Vec<_> = (0..3)
+        .map(|index| {
+            admin::create_profile_with_index(&mut svm, &authority, comm_key.pubkey(), index)
+        })
+        .collect();
+    admin::close_profile_with_index(&mut svm, &authority, 1);
+
+    // === 3. Assert ===
+    assert_eq!(pdas[0], legacy_pda, "Index 0 must keep the legacy address");
+    assert_ne!(pdas[0], pdas[2]);
+    assert!(
+        svm.get_account(&pdas[1]).is_none(),
+        "Profile 1 was not closed!"
+    );
+
+    for index in [0u16, 2] {
+        let pda = admin::admin_pda(&authority.pubkey(), index);
+        let account = svm.get_account(&pda).unwrap();
+        let profile = AdminProfile::try_deserialize(&mut account.data.as_slice()).unwrap();
+        assert_eq!(profile.index, index);
+        assert_eq!(profile.authority, authority.pubkey());
+    }
+
+    println!("✅ Multiple Profiles Test Passed!");
+    println!("   -> Profiles: {pdas:?}");
+}
+
 /// Tests the successful update of an `AdminProfile`'s configuration.
 #[test]
 fn test_admin_set_config_success() {
@@ -113,6 +183,7 @@ fn test_admin_set_config_success() {
         None, // Do not change validity
         None, // Do not change comm key
         None,
+        None,
     );
 
     let admin_profile_mid = {
@@ -135,6 +206,7 @@ fn test_admin_set_config_success() {
         admin_profile_mid.communication_pubkey,
         admin_profile_before.communication_pubkey
     );
+    assert_eq!(admin_profile_mid.comm_key_version, 0);
     println!("✅ Oracle authority updated successfully.");
 
     // --- Act & Assert: Part 2 (Update other fields) ---
@@ -148,6 +220,7 @@ fn test_admin_set_config_success() {
         Some(new_validity),
         Some(new_comm_key.pubkey()),
         None,
+        None,
     );
 
     let admin_account_data = svm.get_account(&admin_pda).unwrap();
@@ -160,6 +233,7 @@ fn test_admin_set_config_success() {
         admin_profile_after.communication_pubkey,
         new_comm_key.pubkey()
     );
+    assert_eq!(admin_profile_after.comm_key_version, 1);
     // Assert that the oracle from Part 1 is still set
     assert_eq!(admin_profile_after.oracle_authority, new_oracle.pubkey());
     // Assert that non-config fields remain unchanged throughout
@@ -399,3 +473,107 @@ fn test_admin_withdraw_success() {
     );
     println!("   -> Destination wallet received: {destination_balance_after} lamports");
 }
+
+/// Tests the full teardown flow: `admin_withdraw_all` empties the internal balance,
+/// after which `admin_close_profile` succeeds and refunds the rent.
+#[test]
+fn test_admin_withdraw_all_then_close() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, _user_pda) = setup_profiles(&mut svm);
+
+    user::deposit(&mut svm, &user_authority, admin_pda, 2 * LAMPORTS_PER_SOL);
+    let command_price = LAMPORTS_PER_SOL / 3;
+    let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+    user::dispatch_command(
+        &mut svm,
+        &user_authority,
+        admin_pda,
+        &admin_authority,
+        DispatchCommandArgs {
+            command_id: 1,
+            price: command_price,
+            timestamp,
+            payload: vec![],
+        },
+    );
+
+    let pda_account_before = svm.get_account(&admin_pda).unwrap();
+    let rent_exempt_minimum = Rent::default().minimum_balance(pda_account_before.data.len());
+    let destination_wallet = create_keypair();
+
+    // === 2. Act ===
+    let logs = admin::withdraw_all(&mut svm, &admin_authority, destination_wallet.pubkey());
+
+    // === 3. Assert ===
+    let events = parse_events::<AdminFundsWithdrawn>(&logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].amount, command_price);
+    assert_eq!(events[0].destination, destination_wallet.pubkey());
+
+    let pda_account_after = svm.get_account(&admin_pda).unwrap();
+    let admin_profile_after =
+        AdminProfile::try_deserialize(&mut pda_account_after.data.as_slice()).unwrap();
+    assert_eq!(admin_profile_after.balance, 0);
+    assert_eq!(pda_account_after.lamports, rent_exempt_minimum);
+    assert_eq!(
+        svm.get_balance(&destination_wallet.pubkey()).unwrap(),
+        command_price
+    );
+    println!("✅ Admin withdrew the full balance of {command_price} lamports.");
+
+    admin::close_profile(&mut svm, &admin_authority);
+    let closed_account = svm.get_account(&admin_pda);
+    assert!(closed_account.is_none(), "Account was not closed!");
+    println!("✅ Admin profile closed after withdrawing everything.");
+}
+
+/// Tests that closing a profile with a non-zero internal balance sweeps the balance to the
+/// authority along with the rent, and reports it in `AdminProfileClosed`.
+#[test]
+fn test_admin_close_profile_sweeps_the_balance() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, _user_pda) = setup_profiles(&mut svm);
+
+    user::deposit(&mut svm, &user_authority, admin_pda, 2 * LAMPORTS_PER_SOL);
+    let command_price = LAMPORTS_PER_SOL / 3;
+    let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+    user::dispatch_command(
+        &mut svm,
+        &user_authority,
+        admin_pda,
+        &admin_authority,
+        DispatchCommandArgs {
+            command_id: 1,
+            price: command_price,
+            timestamp,
+            payload: vec![],
+        },
+    );
+
+    let pda_lamports = svm.get_balance(&admin_pda).unwrap();
+    let rent_exempt_minimum =
+        Rent::default().minimum_balance(svm.get_account(&admin_pda).unwrap().data.len());
+    assert_eq!(pda_lamports, rent_exempt_minimum + command_price);
+    let authority_balance_before = svm.get_balance(&admin_authority.pubkey()).unwrap();
+
+    // === 2. Act ===
+    let logs = admin::close_profile(&mut svm, &admin_authority);
+
+    // === 3. Assert ===
+    assert!(
+        svm.get_account(&admin_pda).is_none(),
+        "Account was not closed!"
+    );
+    let authority_balance_after = svm.get_balance(&admin_authority.pubkey()).unwrap();
+    assert_eq!(
+        authority_balance_after,
+        authority_balance_before + rent_exempt_minimum + command_price - 5000
+    );
+
+    let events = parse_events::<AdminProfileClosed>(&logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].swept_balance, command_price);
+    println!("✅ Closing swept the balance of {command_price} lamports to the authority.");
+}
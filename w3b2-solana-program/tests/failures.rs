@@ -8,7 +8,12 @@ use anchor_lang::prelude::Clock;
 use instructions::*;
 use solana_program::native_token::LAMPORTS_PER_SOL;
 use solana_sdk::signer::Signer;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
 use w3b2_solana_program::errors::BridgeError;
+use w3b2_solana_program::instructions::{
+    MAX_TIMESTAMP_FUTURE_SKEW_SECONDS, MAX_TIMESTAMP_VALIDITY_SECONDS,
+    MIN_TIMESTAMP_VALIDITY_SECONDS,
+};
 
 /// Converts a program-specific error enum into its on-chain numeric code.
 /// Anchor assigns codes starting from 6000.
@@ -47,7 +52,7 @@ fn test_fail_unauthorized_signer() {
     // The malicious actor calls `log_action`, passing in the legitimate user's and admin's
     // profiles, but signing the transaction with their own key.
     // The program should reject this because the signer is not the authority of either profile.
-    let log_ix = log::ix_log_action(&malicious_actor, user_pda, admin_pda, 999, 999);
+    let log_ix = log::ix_log_action(&malicious_actor, user_pda, admin_pda, 999, 999, 0, None);
     let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
         &[log_ix],
         Some(&malicious_actor.pubkey()),
@@ -179,6 +184,198 @@ fn test_fail_payload_too_large() {
     println!("   -> Correctly failed with error: PayloadTooLarge ({error_code})");
 }
 
+/// Tests that a command fails if its payload exceeds the admin's own `max_payload_size`,
+/// even though it is well within the global `MAX_PAYLOAD_SIZE`.
+#[test]
+fn test_fail_payload_exceeds_admin_limit() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, _, _, user_pda) = setup_profiles(&mut svm);
+    let admin_limit = 64u16;
+    admin::set_config(
+        &mut svm,
+        &admin_authority,
+        None,
+        None,
+        None,
+        None,
+        Some(admin_limit),
+    );
+
+    // A payload exactly at the limit is accepted.
+    admin::dispatch_command(
+        &mut svm,
+        &admin_authority,
+        user_pda,
+        1,
+        vec![0u8; admin_limit as usize],
+    );
+
+    // === 2. Act ===
+    println!("Admin dispatching command one byte over its own limit...");
+    let dispatch_ix = admin::ix_dispatch_command(
+        &admin_authority,
+        user_pda,
+        2,
+        vec![0u8; admin_limit as usize + 1],
+    );
+    let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
+        &[dispatch_ix],
+        Some(&admin_authority.pubkey()),
+    );
+    tx.sign(&[&admin_authority], svm.latest_blockhash());
+    let result = svm.send_transaction(tx);
+
+    // === 3. Assert ===
+    let error_code = get_error_code(result).expect("Failed to extract error code.");
+    assert_eq!(error_code, to_error_code(BridgeError::PayloadTooLarge));
+
+    println!("✅ Per-Admin Payload Limit Test Passed!");
+    println!("   -> Correctly failed with error: PayloadTooLarge ({error_code})");
+}
+
+/// Tests that an admin cannot raise `max_payload_size` above the global `MAX_PAYLOAD_SIZE`.
+#[test]
+fn test_fail_payload_limit_above_global_max() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let admin_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    admin::create_profile(&mut svm, &admin_authority, create_keypair().pubkey());
+    let too_large = w3b2_solana_program::instructions::MAX_PAYLOAD_SIZE as u16 + 1;
+
+    // === 2. Act ===
+    let set_config_ix =
+        admin::ix_set_config(&admin_authority, None, None, None, None, Some(too_large));
+    let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
+        &[set_config_ix],
+        Some(&admin_authority.pubkey()),
+    );
+    tx.sign(&[&admin_authority], svm.latest_blockhash());
+    let result = svm.send_transaction(tx);
+
+    // === 3. Assert ===
+    let error_code = get_error_code(result).expect("Failed to extract error code.");
+    assert_eq!(error_code, to_error_code(BridgeError::PayloadLimitTooLarge));
+
+    println!("✅ Payload Limit Ceiling Test Passed!");
+    println!("   -> Correctly failed with error: PayloadLimitTooLarge ({error_code})");
+}
+
+/// Tests that `timestamp_validity_seconds` can be set to either bound of its allowed range,
+/// but not beyond them.
+#[test]
+fn test_fail_timestamp_validity_out_of_range() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let admin_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    admin::create_profile(&mut svm, &admin_authority, create_keypair().pubkey());
+    let mut set_validity = |validity: i64| {
+        let set_config_ix =
+            admin::ix_set_config(&admin_authority, None, Some(validity), None, None, None);
+        let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
+            &[set_config_ix],
+            Some(&admin_authority.pubkey()),
+        );
+        tx.sign(&[&admin_authority], svm.latest_blockhash());
+        let error = svm.send_transaction(tx).err().map(|failed| failed.err);
+        svm.expire_blockhash();
+        error
+    };
+
+    // === 2. Act ===
+    let at_min = set_validity(MIN_TIMESTAMP_VALIDITY_SECONDS);
+    let at_max = set_validity(MAX_TIMESTAMP_VALIDITY_SECONDS);
+    let below_min = set_validity(MIN_TIMESTAMP_VALIDITY_SECONDS - 1);
+    let above_max = set_validity(MAX_TIMESTAMP_VALIDITY_SECONDS + 1);
+
+    // === 3. Assert ===
+    assert_eq!(at_min, None, "the lower bound is allowed");
+    assert_eq!(at_max, None, "the upper bound is allowed");
+    let expected = Some(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(to_error_code(BridgeError::InvalidTimestampValidity)),
+    ));
+    assert_eq!(below_min, expected);
+    assert_eq!(above_max, expected);
+
+    println!("✅ Timestamp Validity Bounds Test Passed!");
+}
+
+/// Tests that an admin who requires a distinct oracle cannot leave the `oracle_authority`
+/// equal to the `authority`, and that the requirement can be toggled.
+#[test]
+fn test_fail_oracle_equal_to_authority_when_distinct_required() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let admin_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    // A new profile is its own oracle.
+    admin::create_profile(&mut svm, &admin_authority, create_keypair().pubkey());
+    let oracle = create_keypair().pubkey();
+    // Returns the custom error code of the update, or `None` if it succeeded.
+    let mut set_config = |new_oracle, require_distinct| {
+        let ix =
+            admin::ix_set_require_distinct_oracle(&admin_authority, new_oracle, require_distinct);
+        let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
+            &[ix],
+            Some(&admin_authority.pubkey()),
+        );
+        svm.expire_blockhash();
+        tx.sign(&[&admin_authority], svm.latest_blockhash());
+        svm.send_transaction(tx)
+            .err()
+            .map(|failed| get_error_code(Err(failed)).expect("Failed to extract error code."))
+    };
+
+    // === 2. Act ===
+    let enable_alone = set_config(None, Some(true));
+    let enable_with_oracle = set_config(Some(oracle), Some(true));
+    let revert_oracle = set_config(Some(admin_authority.pubkey()), None);
+    let disable_and_revert = set_config(Some(admin_authority.pubkey()), Some(false));
+
+    // === 3. Assert ===
+    let error_code = to_error_code(BridgeError::OracleMustBeDistinct);
+    assert_eq!(enable_alone, Some(error_code));
+    assert_eq!(enable_with_oracle, None);
+    assert_eq!(revert_oracle, Some(error_code));
+    assert_eq!(disable_and_revert, None);
+
+    println!("✅ Distinct Oracle Test Passed!");
+    println!("   -> Correctly failed with error: OracleMustBeDistinct ({error_code})");
+}
+
+/// Tests that `log_action` rejects metadata larger than `MAX_METADATA_SIZE`.
+#[test]
+fn test_fail_log_action_metadata_too_large() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (_, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+    let metadata = vec![0u8; w3b2_solana_program::instructions::MAX_METADATA_SIZE + 1];
+
+    // === 2. Act ===
+    let log_ix = log::ix_log_action(
+        &user_authority,
+        user_pda,
+        admin_pda,
+        1,
+        200,
+        0,
+        Some(metadata),
+    );
+    let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
+        &[log_ix],
+        Some(&user_authority.pubkey()),
+    );
+    tx.sign(&[&user_authority], svm.latest_blockhash());
+    let result = svm.send_transaction(tx);
+
+    // === 3. Assert ===
+    let error_code = get_error_code(result).expect("Failed to extract error code.");
+    assert_eq!(error_code, to_error_code(BridgeError::MetadataTooLarge));
+
+    println!("✅ Metadata Size Limit Test Passed!");
+    println!("   -> Correctly failed with error: MetadataTooLarge ({error_code})");
+}
+
 /// Tests that a user cannot execute a paid command if their `deposit_balance` is insufficient.
 #[test]
 fn test_fail_insufficient_deposit_balance() {
@@ -193,7 +390,7 @@ fn test_fail_insufficient_deposit_balance() {
     // Create a user linked to the admin, but DO NOT deposit any funds.
     // The user profile will have `deposit_balance = 0`.
     let user_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
-    let _ = user::create_profile(
+    let user_pda = user::create_profile(
         &mut svm,
         &user_authority,
         create_keypair().pubkey(),
@@ -208,12 +405,12 @@ fn test_fail_insufficient_deposit_balance() {
     // Even for a failure test, we need to supply the precedent Ed25519 instruction
     // that the on-chain program expects to see. The program should fail on the
     // balance check before it even gets to verifying the signature details.
-    let message = [
-        1u16.to_le_bytes().as_ref(),
-        command_price.to_le_bytes().as_ref(),
-        timestamp.to_le_bytes().as_ref(),
-    ]
-    .concat();
+    let message = w3b2_solana_program::instructions::oracle_price_message(
+        1,
+        command_price,
+        timestamp,
+        user_pda,
+    );
 
     let signature = admin_authority.sign_message(&message);
     let pubkey_bytes = admin_authority.pubkey().to_bytes();
@@ -275,11 +472,12 @@ fn test_fail_invalid_oracle_signer() {
         None,
         None,
         None,
+        None,
     );
 
     // Create a user.
     let user_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
-    let _ = user::create_profile(
+    let user_pda = user::create_profile(
         &mut svm,
         &user_authority,
         create_keypair().pubkey(),
@@ -293,12 +491,9 @@ fn test_fail_invalid_oracle_signer() {
     let price = 1000u64;
     let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
 
-    let message = [
-        command_id.to_le_bytes().as_ref(),
-        price.to_le_bytes().as_ref(),
-        timestamp.to_le_bytes().as_ref(),
-    ]
-    .concat();
+    let message = w3b2_solana_program::instructions::oracle_price_message(
+        command_id, price, timestamp, user_pda,
+    );
 
     // The signature is created by the ROGUE oracle.
     let signature = rogue_oracle.sign_message(&message);
@@ -344,7 +539,7 @@ fn test_fail_invalid_oracle_signer() {
 fn test_fail_timestamp_too_old() {
     // === 1. Arrange ===
     let mut svm = setup_svm();
-    let (admin_authority, admin_pda, user_authority, _) = setup_profiles(&mut svm);
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
 
     let command_id = 1u16;
     let price = 1000u64;
@@ -352,12 +547,12 @@ fn test_fail_timestamp_too_old() {
     // The oracle (the admin in this case) signs a message with a timestamp in the past.
     let old_timestamp = svm.get_sysvar::<Clock>().unix_timestamp - 100; // 100 seconds ago
 
-    let message = [
-        command_id.to_le_bytes().as_ref(),
-        price.to_le_bytes().as_ref(),
-        old_timestamp.to_le_bytes().as_ref(),
-    ]
-    .concat();
+    let message = w3b2_solana_program::instructions::oracle_price_message(
+        command_id,
+        price,
+        old_timestamp,
+        user_pda,
+    );
 
     let signature = admin_authority.sign_message(&message);
     let pubkey_bytes = admin_authority.pubkey().to_bytes();
@@ -399,13 +594,136 @@ fn test_fail_timestamp_too_old() {
     println!("✅ Timestamp Too Old Test Passed!");
 }
 
+/// Tests that `user_dispatch_command` fails with `TimestampInFuture` if the oracle signed a
+/// timestamp too far ahead of the cluster clock, which would extend its validity window.
+#[test]
+fn test_fail_timestamp_in_future() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+    user::deposit(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL);
+
+    let command_id = 1u16;
+    let price = 1000u64;
+    let future_timestamp =
+        svm.get_sysvar::<Clock>().unix_timestamp + MAX_TIMESTAMP_FUTURE_SKEW_SECONDS + 3600;
+
+    let ed25519_ix = user::ix_verify_price(
+        &admin_authority,
+        command_id,
+        price,
+        future_timestamp,
+        user_pda,
+    );
+    let dispatch_ix = user::ix_dispatch_command(
+        &user_authority,
+        admin_pda,
+        command_id,
+        price,
+        future_timestamp,
+        vec![],
+    );
+    let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
+        &[ed25519_ix, dispatch_ix],
+        Some(&user_authority.pubkey()),
+    );
+    tx.sign(&[&user_authority], svm.latest_blockhash());
+
+    // === 2. Act ===
+    let result = svm.send_transaction(tx);
+
+    // === 3. Assert ===
+    assert!(result.is_err(), "Transaction should have failed");
+    let error_code = get_error_code(result).unwrap();
+    assert_eq!(error_code, to_error_code(BridgeError::TimestampInFuture));
+
+    println!("✅ Timestamp In Future Test Passed!");
+}
+
+/// Tests that `user_dispatch_command` against a profile whose admin was closed fails with
+/// an error instead of panicking, and leaves the user's deposit untouched.
+#[test]
+fn test_fail_dispatch_to_closed_admin() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+    user::deposit(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL);
+    admin::close_profile(&mut svm, &admin_authority);
+    assert!(svm
+        .get_account(&admin_pda)
+        .is_none_or(|account| account.lamports == 0));
+    let balance_before = svm.get_account(&user_pda).unwrap().lamports;
+
+    let price = 1000u64;
+    let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+    let ed25519_ix = user::ix_verify_price(&admin_authority, 1, price, timestamp, user_pda);
+    let dispatch_ix =
+        user::ix_dispatch_command(&user_authority, admin_pda, 1, price, timestamp, vec![]);
+    let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
+        &[ed25519_ix, dispatch_ix],
+        Some(&user_authority.pubkey()),
+    );
+    tx.sign(&[&user_authority], svm.latest_blockhash());
+
+    // === 2. Act ===
+    let result = svm.send_transaction(tx);
+
+    // === 3. Assert ===
+    // The closed account no longer deserializes as an `AdminProfile`, so the dangling link
+    // is rejected before the instruction runs.
+    assert!(result.is_err(), "Transaction should have failed");
+    let error_code = get_error_code(result);
+    assert_eq!(
+        error_code,
+        Some(anchor_lang::error::ErrorCode::AccountNotInitialized as u32)
+    );
+    assert_eq!(svm.get_account(&user_pda).unwrap().lamports, balance_before);
+
+    println!("✅ Dispatch To Closed Admin Test Passed!");
+}
+
+/// Tests that `user_dispatch_command` accepts a timestamp slightly ahead of the cluster
+/// clock, as signed by an oracle whose clock runs a little fast.
+#[test]
+fn test_slightly_skewed_timestamp_is_accepted() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+    user::deposit(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL);
+    let balance_before = svm.get_account(&user_pda).unwrap().lamports;
+
+    let price = 1000u64;
+    let skewed_timestamp =
+        svm.get_sysvar::<Clock>().unix_timestamp + MAX_TIMESTAMP_FUTURE_SKEW_SECONDS;
+
+    // === 2. Act ===
+    user::dispatch_command(
+        &mut svm,
+        &user_authority,
+        admin_pda,
+        &admin_authority,
+        user::DispatchCommandArgs {
+            command_id: 1,
+            price,
+            timestamp: skewed_timestamp,
+            payload: vec![],
+        },
+    );
+
+    // === 3. Assert ===
+    let balance_after = svm.get_account(&user_pda).unwrap().lamports;
+    assert_eq!(balance_before - balance_after, price);
+
+    println!("✅ Slightly Skewed Timestamp Test Passed!");
+}
+
 /// Tests that `user_dispatch_command` fails with `SignatureVerificationFailed`
 /// if the arguments passed to the instruction do not match the data signed by the oracle.
 #[test]
 fn test_fail_signature_verification_failed() {
     // === 1. Arrange ===
     let mut svm = setup_svm();
-    let (admin_authority, admin_pda, user_authority, _) = setup_profiles(&mut svm);
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
 
     let command_id = 1u16;
     let signed_price = 1000u64; // The price the oracle actually signed
@@ -413,12 +731,12 @@ fn test_fail_signature_verification_failed() {
     let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
 
     // The oracle signs the message with the CORRECT price.
-    let message = [
-        command_id.to_le_bytes().as_ref(),
-        signed_price.to_le_bytes().as_ref(),
-        timestamp.to_le_bytes().as_ref(),
-    ]
-    .concat();
+    let message = w3b2_solana_program::instructions::oracle_price_message(
+        command_id,
+        signed_price,
+        timestamp,
+        user_pda,
+    );
 
     let signature = admin_authority.sign_message(&message);
     let pubkey_bytes = admin_authority.pubkey().to_bytes();
@@ -459,6 +777,230 @@ fn test_fail_signature_verification_failed() {
     println!("✅ Signature Verification Failed (Price Mismatch) Test Passed!");
 }
 
+/// Tests that a price signed for one user's profile fails with `SignatureVerificationFailed`
+/// when another user of the same admin submits it, while it is accepted from its user.
+#[test]
+fn test_fail_price_signed_for_another_user() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+    let other_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let other_pda = user::create_profile(
+        &mut svm,
+        &other_authority,
+        create_keypair().pubkey(),
+        admin_pda,
+    );
+    user::deposit(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL);
+    user::deposit(&mut svm, &other_authority, admin_pda, LAMPORTS_PER_SOL);
+    let other_balance_before = svm.get_account(&other_pda).unwrap().lamports;
+
+    // The oracle signs a price for the first user only.
+    let price = 1000u64;
+    let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+    let verify = user::ix_verify_price(&admin_authority, 1, price, timestamp, user_pda);
+    let mut send = |authority: &solana_sdk::signature::Keypair| {
+        let dispatch_ix =
+            user::ix_dispatch_command(authority, admin_pda, 1, price, timestamp, vec![]);
+        let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
+            &[verify.clone(), dispatch_ix],
+            Some(&authority.pubkey()),
+        );
+        tx.sign(&[authority], svm.latest_blockhash());
+        // Only the error is kept, as the full failure metadata is too large to pass around.
+        svm.send_transaction(tx)
+            .map(|_| ())
+            .map_err(|failed| failed.err)
+    };
+
+    // === 2. Act ===
+    println!("Another user submitting a price signed for the first user...");
+    let replayed = send(&other_authority);
+    let intended = send(&user_authority);
+
+    // === 3. Assert ===
+    assert_eq!(
+        replayed,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(to_error_code(BridgeError::SignatureVerificationFailed))
+        ))
+    );
+    assert!(
+        intended.is_ok(),
+        "the intended user should be able to use the price"
+    );
+    assert_eq!(
+        svm.get_account(&other_pda).unwrap().lamports,
+        other_balance_before
+    );
+
+    println!("✅ Price Signed For Another User Test Passed!");
+}
+
+/// Tests that `user_dispatch_command` only accepts a single, unambiguous oracle verification.
+/// A transaction without any Ed25519 instruction fails with `InstructionMismatch`, and one
+/// verifying the price message twice fails with `AmbiguousSignatureVerification`.
+#[test]
+fn test_fail_missing_or_ambiguous_signature_verification() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+    user::deposit(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL);
+
+    let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+    let verify = user::ix_verify_price(&admin_authority, 1, 1000, timestamp, user_pda);
+    let dispatch_ix =
+        user::ix_dispatch_command(&user_authority, admin_pda, 1, 1000, timestamp, vec![]);
+    let mut send = |instructions: &[solana_program::instruction::Instruction]| {
+        let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
+            instructions,
+            Some(&user_authority.pubkey()),
+        );
+        tx.sign(&[&user_authority], svm.latest_blockhash());
+        get_error_code(svm.send_transaction(tx))
+    };
+
+    // === 2. Act ===
+    let missing = send(std::slice::from_ref(&dispatch_ix));
+    let ambiguous = send(&[verify.clone(), dispatch_ix, verify]);
+
+    // === 3. Assert ===
+    assert_eq!(
+        missing.unwrap(),
+        to_error_code(BridgeError::InstructionMismatch)
+    );
+    assert_eq!(
+        ambiguous.unwrap(),
+        to_error_code(BridgeError::AmbiguousSignatureVerification)
+    );
+
+    println!("✅ Missing/Ambiguous Signature Verification Test Passed!");
+}
+
+/// Tests that `user_dispatch_command` fails with `PriceExceedsUserCap` when a correctly
+/// signed price is above the user's spending cap, while a price at the cap is accepted.
+#[test]
+fn test_fail_price_exceeds_user_cap() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+    let cap = LAMPORTS_PER_SOL / 10;
+    user::deposit(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL);
+    user::set_spending_cap(&mut svm, &user_authority, admin_pda, cap);
+
+    let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+    user::dispatch_command(
+        &mut svm,
+        &user_authority,
+        admin_pda,
+        &admin_authority,
+        user::DispatchCommandArgs {
+            command_id: 1,
+            price: cap,
+            timestamp,
+            payload: vec![],
+        },
+    );
+
+    // === 2. Act ===
+    println!("Dispatching a command signed above the user's cap...");
+    let price = cap + 1;
+    let message =
+        w3b2_solana_program::instructions::oracle_price_message(1, price, timestamp, user_pda);
+    let signature = admin_authority.sign_message(&message);
+    let ed25519_ix = solana_sdk::ed25519_instruction::new_ed25519_instruction_with_signature(
+        &message,
+        &signature.as_ref().try_into().unwrap(),
+        &admin_authority.pubkey().to_bytes(),
+    );
+    let dispatch_ix =
+        user::ix_dispatch_command(&user_authority, admin_pda, 1, price, timestamp, vec![]);
+    let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
+        &[ed25519_ix, dispatch_ix],
+        Some(&user_authority.pubkey()),
+    );
+    tx.sign(&[&user_authority], svm.latest_blockhash());
+    let result = svm.send_transaction(tx);
+
+    // === 3. Assert ===
+    assert!(result.is_err(), "Transaction should have failed");
+    let error_code = get_error_code(result).unwrap();
+    assert_eq!(error_code, to_error_code(BridgeError::PriceExceedsUserCap));
+
+    println!("✅ Price Exceeds User Cap Test Passed!");
+}
+
+/// Tests that `dispatch_with_allowance` refuses commands outside the allowance: a command it
+/// does not cover, a price above what remains, a signer other than the service, and any
+/// command once it expired.
+#[test]
+fn test_fail_dispatch_outside_allowance() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+    user::deposit(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL);
+
+    let cap = LAMPORTS_PER_SOL / 10;
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let expires_at = now + 60;
+    user::grant_allowance(
+        &mut svm,
+        &user_authority,
+        admin_pda,
+        vec![1],
+        cap,
+        expires_at,
+    );
+
+    let dispatch = |svm: &mut litesvm::LiteSVM, charger, command_id, price| {
+        let args = user::DispatchCommandArgs {
+            command_id,
+            price,
+            timestamp: svm.get_sysvar::<Clock>().unix_timestamp,
+            payload: vec![],
+        };
+        let tx = user::tx_dispatch_with_allowance(
+            svm,
+            charger,
+            user_pda,
+            admin_pda,
+            &admin_authority,
+            args,
+        );
+        get_error_code(svm.send_transaction(tx))
+    };
+
+    // === 2. Act ===
+    println!("Charging commands outside the allowance...");
+    let not_covered = dispatch(&mut svm, &admin_authority, 2, cap);
+    let above_cap = dispatch(&mut svm, &admin_authority, 1, cap + 1);
+    let stranger = create_funded_keypair(&mut svm, LAMPORTS_PER_SOL);
+    let unauthorized = dispatch(&mut svm, &stranger, 1, cap);
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = expires_at;
+    svm.set_sysvar(&clock);
+    let expired = dispatch(&mut svm, &admin_authority, 1, cap);
+
+    // === 3. Assert ===
+    assert_eq!(
+        not_covered,
+        Some(to_error_code(BridgeError::CommandNotInAllowance))
+    );
+    assert_eq!(
+        above_cap,
+        Some(to_error_code(BridgeError::AllowanceExhausted))
+    );
+    assert_eq!(
+        unauthorized,
+        Some(to_error_code(BridgeError::SignerUnauthorized))
+    );
+    assert_eq!(expired, Some(to_error_code(BridgeError::AllowanceExpired)));
+
+    println!("✅ Dispatch Outside Allowance Test Passed!");
+}
+
 /// Tests that a banned user cannot dispatch a command.
 #[test]
 fn test_fail_dispatch_when_banned() {
@@ -472,12 +1014,8 @@ fn test_fail_dispatch_when_banned() {
     // === 2. Act ===
     println!("Banned user attempting to dispatch a command...");
     let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
-    let message = [
-        0u16.to_le_bytes().as_ref(),
-        0u64.to_le_bytes().as_ref(),
-        timestamp.to_le_bytes().as_ref(),
-    ]
-    .concat();
+    let message =
+        w3b2_solana_program::instructions::oracle_price_message(0, 0, timestamp, user_pda);
     let signature = admin_authority.sign_message(&message);
     let ed25519_ix = solana_sdk::ed25519_instruction::new_ed25519_instruction_with_signature(
         &message,
@@ -612,6 +1150,7 @@ fn test_fail_request_unban_insufficient_funds() {
         None,
         None,
         Some(unban_fee),
+        None,
     );
 
     // Ban the user.
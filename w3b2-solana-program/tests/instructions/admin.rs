@@ -1,16 +1,50 @@
 #![allow(dead_code)]
 
 use super::*;
+use w3b2_solana_program::state::admin_index_seed;
+
+/// Derives the address of the `AdminProfile` with the given `index` owned by `authority`.
+pub fn admin_pda(authority: &Pubkey, index: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"admin", authority.as_ref(), &admin_index_seed(index)],
+        &w3b2_solana_program::ID,
+    )
+    .0
+}
 
 pub fn create_profile(svm: &mut LiteSVM, authority: &Keypair, comm_key: Pubkey) -> Pubkey {
-    let (register_ix, admin_pda) = ix_create_profile(authority, comm_key);
+    create_profile_with_index(svm, authority, comm_key, 0)
+}
+
+pub fn create_profile_with_index(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    comm_key: Pubkey,
+    index: u16,
+) -> Pubkey {
+    let (register_ix, admin_pda) = ix_create_profile(authority, comm_key, index);
     build_and_send_tx(svm, vec![register_ix], authority, vec![]);
     admin_pda
 }
 
-pub fn close_profile(svm: &mut LiteSVM, authority: &Keypair) {
-    let close_ix = ix_close_profile(authority);
-    build_and_send_tx(svm, vec![close_ix], authority, vec![]);
+/// Creates the profile at index `0`, returning its PDA and the transaction logs.
+pub fn create_profile_with_logs(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    comm_key: Pubkey,
+) -> (Pubkey, Vec<String>) {
+    let (register_ix, admin_pda) = ix_create_profile(authority, comm_key, 0);
+    let logs = build_and_send_tx(svm, vec![register_ix], authority, vec![]);
+    (admin_pda, logs)
+}
+
+pub fn close_profile(svm: &mut LiteSVM, authority: &Keypair) -> Vec<String> {
+    close_profile_with_index(svm, authority, 0)
+}
+
+pub fn close_profile_with_index(svm: &mut LiteSVM, authority: &Keypair, index: u16) -> Vec<String> {
+    let close_ix = ix_close_profile(authority, index);
+    build_and_send_tx(svm, vec![close_ix], authority, vec![])
 }
 
 pub fn set_config(
@@ -20,6 +54,7 @@ pub fn set_config(
     new_validity: Option<i64>,
     new_comm_key: Option<Pubkey>,
     new_unban_fee: Option<u64>,
+    new_max_payload_size: Option<u16>,
 ) {
     let set_config_ix = ix_set_config(
         authority,
@@ -27,15 +62,31 @@ pub fn set_config(
         new_validity,
         new_comm_key,
         new_unban_fee,
+        new_max_payload_size,
     );
     build_and_send_tx(svm, vec![set_config_ix], authority, vec![]);
 }
 
+pub fn set_unban_fee_escalation(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    multiplier_bps: Option<u16>,
+    max_multiple: Option<u16>,
+) {
+    let set_config_ix = ix_set_unban_fee_escalation(authority, multiplier_bps, max_multiple);
+    build_and_send_tx(svm, vec![set_config_ix], authority, vec![]);
+}
+
 pub fn withdraw(svm: &mut LiteSVM, authority: &Keypair, destination: Pubkey, amount: u64) {
     let withdraw_ix = ix_withdraw(authority, destination, amount);
     build_and_send_tx(svm, vec![withdraw_ix], authority, vec![]);
 }
 
+pub fn withdraw_all(svm: &mut LiteSVM, authority: &Keypair, destination: Pubkey) -> Vec<String> {
+    let withdraw_all_ix = ix_withdraw_all(authority, destination);
+    build_and_send_tx(svm, vec![withdraw_all_ix], authority, vec![])
+}
+
 pub fn dispatch_command(
     svm: &mut LiteSVM,
     authority: &Keypair,
@@ -57,14 +108,16 @@ pub fn unban_user(svm: &mut LiteSVM, authority: &Keypair, user_profile_pda: Pubk
     build_and_send_tx(svm, vec![unban_ix], authority, vec![]);
 }
 
-fn ix_create_profile(authority: &Keypair, communication_pubkey: Pubkey) -> (Instruction, Pubkey) {
-    let (admin_pda, _) = Pubkey::find_program_address(
-        &[b"admin", authority.pubkey().as_ref()],
-        &w3b2_solana_program::ID,
-    );
+fn ix_create_profile(
+    authority: &Keypair,
+    communication_pubkey: Pubkey,
+    index: u16,
+) -> (Instruction, Pubkey) {
+    let admin_pda = admin_pda(&authority.pubkey(), index);
 
     let data = w3b2_instruction::AdminRegisterProfile {
         communication_pubkey,
+        index: Some(index),
     }
     .data();
 
@@ -90,17 +143,19 @@ pub fn ix_set_config(
     new_timestamp_validity: Option<i64>,
     new_communication_pubkey: Option<Pubkey>,
     new_unban_fee: Option<u64>,
+    new_max_payload_size: Option<u16>,
 ) -> Instruction {
-    let (admin_pda, _) = Pubkey::find_program_address(
-        &[b"admin", authority.pubkey().as_ref()],
-        &w3b2_solana_program::ID,
-    );
+    let admin_pda = admin_pda(&authority.pubkey(), 0);
 
     let data = w3b2_instruction::AdminSetConfig {
         new_oracle_authority,
         new_timestamp_validity,
         new_communication_pubkey,
         new_unban_fee,
+        new_max_payload_size,
+        new_unban_fee_multiplier_bps: None,
+        new_max_unban_fee_multiple: None,
+        new_require_distinct_oracle: None,
     }
     .data();
 
@@ -118,10 +173,7 @@ pub fn ix_set_config(
 }
 
 pub fn ix_ban_user(authority: &Keypair, user_profile_pda: Pubkey) -> Instruction {
-    let (admin_pda, _) = Pubkey::find_program_address(
-        &[b"admin", authority.pubkey().as_ref()],
-        &w3b2_solana_program::ID,
-    );
+    let admin_pda = admin_pda(&authority.pubkey(), 0);
 
     let data = w3b2_instruction::AdminBanUser {}.data();
 
@@ -140,10 +192,7 @@ pub fn ix_ban_user(authority: &Keypair, user_profile_pda: Pubkey) -> Instruction
 }
 
 pub fn ix_unban_user(authority: &Keypair, user_profile_pda: Pubkey) -> Instruction {
-    let (admin_pda, _) = Pubkey::find_program_address(
-        &[b"admin", authority.pubkey().as_ref()],
-        &w3b2_solana_program::ID,
-    );
+    let admin_pda = admin_pda(&authority.pubkey(), 0);
 
     let data = w3b2_instruction::AdminUnbanUser {}.data();
 
@@ -161,11 +210,8 @@ pub fn ix_unban_user(authority: &Keypair, user_profile_pda: Pubkey) -> Instructi
     }
 }
 
-pub fn ix_close_profile(authority: &Keypair) -> Instruction {
-    let (admin_pda, _) = Pubkey::find_program_address(
-        &[b"admin", authority.pubkey().as_ref()],
-        &w3b2_solana_program::ID,
-    );
+pub fn ix_close_profile(authority: &Keypair, index: u16) -> Instruction {
+    let admin_pda = admin_pda(&authority.pubkey(), index);
 
     let data = w3b2_instruction::AdminCloseProfile {}.data();
 
@@ -182,11 +228,72 @@ pub fn ix_close_profile(authority: &Keypair) -> Instruction {
     }
 }
 
+pub fn ix_set_unban_fee_escalation(
+    authority: &Keypair,
+    new_unban_fee_multiplier_bps: Option<u16>,
+    new_max_unban_fee_multiple: Option<u16>,
+) -> Instruction {
+    let admin_pda = admin_pda(&authority.pubkey(), 0);
+
+    let data = w3b2_instruction::AdminSetConfig {
+        new_oracle_authority: None,
+        new_timestamp_validity: None,
+        new_communication_pubkey: None,
+        new_unban_fee: None,
+        new_max_payload_size: None,
+        new_unban_fee_multiplier_bps,
+        new_max_unban_fee_multiple,
+        new_require_distinct_oracle: None,
+    }
+    .data();
+
+    let accounts = w3b2_accounts::AdminSetConfig {
+        authority: authority.pubkey(),
+        admin_profile: admin_pda,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_solana_program::ID,
+        accounts,
+        data,
+    }
+}
+
+pub fn ix_set_require_distinct_oracle(
+    authority: &Keypair,
+    new_oracle_authority: Option<Pubkey>,
+    new_require_distinct_oracle: Option<bool>,
+) -> Instruction {
+    let admin_pda = admin_pda(&authority.pubkey(), 0);
+
+    let data = w3b2_instruction::AdminSetConfig {
+        new_oracle_authority,
+        new_timestamp_validity: None,
+        new_communication_pubkey: None,
+        new_unban_fee: None,
+        new_max_payload_size: None,
+        new_unban_fee_multiplier_bps: None,
+        new_max_unban_fee_multiple: None,
+        new_require_distinct_oracle,
+    }
+    .data();
+
+    let accounts = w3b2_accounts::AdminSetConfig {
+        authority: authority.pubkey(),
+        admin_profile: admin_pda,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_solana_program::ID,
+        accounts,
+        data,
+    }
+}
+
 pub fn ix_withdraw(authority: &Keypair, destination: Pubkey, amount: u64) -> Instruction {
-    let (admin_pda, _) = Pubkey::find_program_address(
-        &[b"admin", authority.pubkey().as_ref()],
-        &w3b2_solana_program::ID,
-    );
+    let admin_pda = admin_pda(&authority.pubkey(), 0);
 
     let data = w3b2_instruction::AdminWithdraw { amount }.data();
 
@@ -204,20 +311,37 @@ pub fn ix_withdraw(authority: &Keypair, destination: Pubkey, amount: u64) -> Ins
     }
 }
 
+pub fn ix_withdraw_all(authority: &Keypair, destination: Pubkey) -> Instruction {
+    let admin_pda = admin_pda(&authority.pubkey(), 0);
+
+    let data = w3b2_instruction::AdminWithdrawAll {}.data();
+
+    let accounts = w3b2_accounts::AdminWithdrawAll {
+        authority: authority.pubkey(),
+        admin_profile: admin_pda,
+        destination,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_solana_program::ID,
+        accounts,
+        data,
+    }
+}
+
 pub fn ix_dispatch_command(
     authority: &Keypair,
     user_profile_pda: Pubkey,
     command_id: u64,
     payload: Vec<u8>,
 ) -> Instruction {
-    let (admin_pda, _) = Pubkey::find_program_address(
-        &[b"admin", authority.pubkey().as_ref()],
-        &w3b2_solana_program::ID,
-    );
+    let admin_pda = admin_pda(&authority.pubkey(), 0);
 
     let data = w3b2_instruction::AdminDispatchCommand {
         command_id,
         payload,
+        mode: CommandMode::default(),
     }
     .data();
 
@@ -2,6 +2,7 @@
 
 use super::*;
 
+#[allow(clippy::too_many_arguments)]
 pub fn log_action(
     svm: &mut LiteSVM,
     authority: &Keypair,
@@ -9,6 +10,8 @@ pub fn log_action(
     admin_profile_pda: Pubkey,
     session_id: u64,
     action_code: u16,
+    category: u8,
+    metadata: Option<Vec<u8>>,
 ) -> Vec<String> {
     let log_ix = ix_log_action(
         authority,
@@ -16,6 +19,8 @@ pub fn log_action(
         admin_profile_pda,
         session_id,
         action_code,
+        category,
+        metadata,
     );
     build_and_send_tx(svm, vec![log_ix], authority, vec![])
 }
@@ -26,10 +31,14 @@ pub fn ix_log_action(
     admin_profile_pda: Pubkey,
     session_id: u64,
     action_code: u16,
+    category: u8,
+    metadata: Option<Vec<u8>>,
 ) -> Instruction {
     let data = w3b2_instruction::LogAction {
         session_id,
         action_code,
+        category,
+        metadata,
     }
     .data();
     let accounts = w3b2_accounts::LogAction {
@@ -15,7 +15,9 @@ use solana_sdk::{
     compute_budget::ComputeBudgetInstruction, signature::Keypair, signer::Signer,
     transaction::Transaction,
 };
-use w3b2_solana_program::{accounts as w3b2_accounts, instruction as w3b2_instruction};
+use w3b2_solana_program::{
+    accounts as w3b2_accounts, instruction as w3b2_instruction, protocols::CommandMode,
+};
 
 /// A constant path to the compiled on-chain program binary (`.so` file).
 /// This is used by `setup_svm` to load the program into the test environment.
@@ -23,9 +23,29 @@ pub fn update_comm_key(
     build_and_send_tx(svm, vec![update_ix], authority, vec![]);
 }
 
-pub fn close_profile(svm: &mut LiteSVM, authority: &Keypair, admin_pda: Pubkey) {
+pub fn set_low_balance_threshold(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    admin_pda: Pubkey,
+    threshold: u64,
+) {
+    let set_ix = ix_set_low_balance_threshold(authority, admin_pda, threshold);
+    build_and_send_tx(svm, vec![set_ix], authority, vec![]);
+}
+
+pub fn set_spending_cap(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    admin_pda: Pubkey,
+    max_price_per_command: u64,
+) -> Vec<String> {
+    let set_ix = ix_set_spending_cap(authority, admin_pda, max_price_per_command);
+    build_and_send_tx(svm, vec![set_ix], authority, vec![])
+}
+
+pub fn close_profile(svm: &mut LiteSVM, authority: &Keypair, admin_pda: Pubkey) -> Vec<String> {
     let close_ix = ix_close_profile(authority, admin_pda);
-    build_and_send_tx(svm, vec![close_ix], authority, vec![]);
+    build_and_send_tx(svm, vec![close_ix], authority, vec![])
 }
 
 pub fn deposit(svm: &mut LiteSVM, authority: &Keypair, admin_pda: Pubkey, amount: u64) {
@@ -61,26 +81,17 @@ pub fn dispatch_command(
     admin_pda: Pubkey,
     oracle: &Keypair,
     args: DispatchCommandArgs,
-) {
-    // 1. Construct the message the oracle needs to sign
-    let message = [
-        args.command_id.to_le_bytes().as_ref(),
-        args.price.to_le_bytes().as_ref(),
-        args.timestamp.to_le_bytes().as_ref(),
-    ]
-    .concat();
-
-    // 2. Sign the message and create the Ed25519 signature verification instruction
-    let signature = oracle.sign_message(&message);
-    let pubkey_bytes = oracle.pubkey().to_bytes();
-    let signature_bytes: [u8; 64] = signature.as_ref().try_into().unwrap();
-    let ed25519_ix = solana_sdk::ed25519_instruction::new_ed25519_instruction_with_signature(
-        &message,
-        &signature_bytes,
-        &pubkey_bytes,
+) -> Vec<String> {
+    // 1. Sign the price message and create the Ed25519 signature verification instruction
+    let ed25519_ix = ix_verify_price(
+        oracle,
+        args.command_id,
+        args.price,
+        args.timestamp,
+        user_pda(&authority.pubkey(), admin_pda),
     );
 
-    // 3. Create the actual dispatch command instruction
+    // 2. Create the actual dispatch command instruction
     let dispatch_ix = ix_dispatch_command(
         authority,
         admin_pda,
@@ -90,13 +101,79 @@ pub fn dispatch_command(
         args.payload,
     );
 
-    // 4. Send both instructions in the same transaction
-    build_and_send_tx(svm, vec![ed25519_ix, dispatch_ix], authority, vec![]);
+    // 3. Send both instructions in the same transaction
+    build_and_send_tx(svm, vec![ed25519_ix, dispatch_ix], authority, vec![])
+}
+
+/// Builds the Ed25519 instruction verifying `oracle`'s signature over the price message
+/// of a command, signed for the user profile `user_pda`.
+pub fn ix_verify_price(
+    oracle: &Keypair,
+    command_id: u16,
+    price: u64,
+    timestamp: i64,
+    user_pda: Pubkey,
+) -> Instruction {
+    let message = w3b2_solana_program::instructions::oracle_price_message(
+        command_id, price, timestamp, user_pda,
+    );
+    let signature_bytes: [u8; 64] = oracle.sign_message(&message).as_ref().try_into().unwrap();
+    solana_sdk::ed25519_instruction::new_ed25519_instruction_with_signature(
+        &message,
+        &signature_bytes,
+        &oracle.pubkey().to_bytes(),
+    )
+}
+
+pub fn grant_allowance(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    admin_pda: Pubkey,
+    commands: Vec<u16>,
+    total_cap: u64,
+    expires_at: i64,
+) -> Vec<String> {
+    let ix = ix_grant_allowance(authority, admin_pda, commands, total_cap, expires_at);
+    build_and_send_tx(svm, vec![ix], authority, vec![])
 }
 
-pub fn request_unban(svm: &mut LiteSVM, authority: &Keypair, admin_pda: Pubkey) {
+/// Builds the transaction in which `charger` charges a command against the allowance of
+/// `user_pda`, with `oracle`'s price verification in front of it.
+pub fn tx_dispatch_with_allowance(
+    svm: &LiteSVM,
+    charger: &Keypair,
+    user_pda: Pubkey,
+    admin_pda: Pubkey,
+    oracle: &Keypair,
+    args: DispatchCommandArgs,
+) -> solana_sdk::transaction::Transaction {
+    let ed25519_ix = ix_verify_price(
+        oracle,
+        args.command_id,
+        args.price,
+        args.timestamp,
+        user_pda,
+    );
+    let dispatch_ix = ix_dispatch_with_allowance(
+        charger.pubkey(),
+        user_pda,
+        admin_pda,
+        args.command_id,
+        args.price,
+        args.timestamp,
+        args.payload,
+    );
+    let mut tx = solana_sdk::transaction::Transaction::new_with_payer(
+        &[ed25519_ix, dispatch_ix],
+        Some(&charger.pubkey()),
+    );
+    tx.sign(&[charger], svm.latest_blockhash());
+    tx
+}
+
+pub fn request_unban(svm: &mut LiteSVM, authority: &Keypair, admin_pda: Pubkey) -> Vec<String> {
     let ix = ix_request_unban(authority, admin_pda);
-    build_and_send_tx(svm, vec![ix], authority, vec![]);
+    build_and_send_tx(svm, vec![ix], authority, vec![])
 }
 
 pub fn ix_create_profile(
@@ -159,6 +236,61 @@ pub fn ix_update_comm_key(authority: &Keypair, admin_pda: Pubkey, new_key: Pubke
     }
 }
 
+pub fn ix_set_low_balance_threshold(
+    authority: &Keypair,
+    admin_pda: Pubkey,
+    threshold: u64,
+) -> Instruction {
+    let (user_pda, _) = Pubkey::find_program_address(
+        &[b"user", authority.pubkey().as_ref(), admin_pda.as_ref()],
+        &w3b2_solana_program::ID,
+    );
+
+    let data = w3b2_instruction::UserSetLowBalanceThreshold { threshold }.data();
+
+    let accounts = w3b2_accounts::UserSetLowBalanceThreshold {
+        authority: authority.pubkey(),
+        admin_profile: admin_pda,
+        user_profile: user_pda,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_solana_program::ID,
+        accounts,
+        data,
+    }
+}
+
+pub fn ix_set_spending_cap(
+    authority: &Keypair,
+    admin_pda: Pubkey,
+    max_price_per_command: u64,
+) -> Instruction {
+    let (user_pda, _) = Pubkey::find_program_address(
+        &[b"user", authority.pubkey().as_ref(), admin_pda.as_ref()],
+        &w3b2_solana_program::ID,
+    );
+
+    let data = w3b2_instruction::UserSetSpendingCap {
+        max_price_per_command,
+    }
+    .data();
+
+    let accounts = w3b2_accounts::UserSetSpendingCap {
+        authority: authority.pubkey(),
+        admin_profile: admin_pda,
+        user_profile: user_pda,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_solana_program::ID,
+        accounts,
+        data,
+    }
+}
+
 pub fn ix_close_profile(authority: &Keypair, admin_pda: Pubkey) -> Instruction {
     let (user_pda, _) = Pubkey::find_program_address(
         &[b"user", authority.pubkey().as_ref(), admin_pda.as_ref()],
@@ -250,6 +382,7 @@ pub fn ix_dispatch_command(
         price,
         timestamp,
         payload,
+        mode: CommandMode::default(),
     }
     .data();
 
@@ -306,6 +439,7 @@ pub fn build_dispatch_command_instruction(
         price,
         timestamp,
         payload,
+        mode: CommandMode::default(),
     }
     .data();
 
@@ -323,3 +457,223 @@ pub fn build_dispatch_command_instruction(
         data,
     }
 }
+
+pub fn user_pda(authority: &Pubkey, admin_pda: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"user", authority.as_ref(), admin_pda.as_ref()],
+        &w3b2_solana_program::ID,
+    )
+    .0
+}
+
+pub fn allowance_pda(user_pda: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"allowance", user_pda.as_ref()], &w3b2_solana_program::ID).0
+}
+
+pub fn ix_grant_allowance(
+    authority: &Keypair,
+    admin_pda: Pubkey,
+    commands: Vec<u16>,
+    total_cap: u64,
+    expires_at: i64,
+) -> Instruction {
+    let (user_pda, _) = Pubkey::find_program_address(
+        &[b"user", authority.pubkey().as_ref(), admin_pda.as_ref()],
+        &w3b2_solana_program::ID,
+    );
+
+    let data = w3b2_instruction::UserGrantAllowance {
+        commands,
+        total_cap,
+        expires_at,
+    }
+    .data();
+
+    let accounts = w3b2_accounts::UserGrantAllowance {
+        authority: authority.pubkey(),
+        admin_profile: admin_pda,
+        user_profile: user_pda,
+        allowance: allowance_pda(user_pda),
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_solana_program::ID,
+        accounts,
+        data,
+    }
+}
+
+pub fn ix_dispatch_with_allowance(
+    charger: Pubkey,
+    user_pda: Pubkey,
+    admin_pda: Pubkey,
+    command_id: u16,
+    price: u64,
+    timestamp: i64,
+    payload: Vec<u8>,
+) -> Instruction {
+    let data = w3b2_instruction::DispatchWithAllowance {
+        command_id,
+        price,
+        timestamp,
+        payload,
+        mode: CommandMode::default(),
+    }
+    .data();
+
+    let accounts = w3b2_accounts::DispatchWithAllowance {
+        charger,
+        user_profile: user_pda,
+        admin_profile: admin_pda,
+        allowance: allowance_pda(user_pda),
+        instructions: instructions::ID,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_solana_program::ID,
+        accounts,
+        data,
+    }
+}
+
+pub fn wallet_pda(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"wallet", authority.as_ref()], &w3b2_solana_program::ID).0
+}
+
+pub fn create_wallet(svm: &mut LiteSVM, authority: &Keypair) -> Pubkey {
+    let create_ix = ix_create_wallet(authority);
+    build_and_send_tx(svm, vec![create_ix], authority, vec![]);
+    wallet_pda(&authority.pubkey())
+}
+
+pub fn wallet_deposit(svm: &mut LiteSVM, authority: &Keypair, amount: u64) -> Vec<String> {
+    let deposit_ix = ix_wallet_deposit(authority, amount);
+    build_and_send_tx(svm, vec![deposit_ix], authority, vec![])
+}
+
+pub fn wallet_withdraw(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    destination: Pubkey,
+    amount: u64,
+) -> Vec<String> {
+    let withdraw_ix = ix_wallet_withdraw(authority, destination, amount);
+    build_and_send_tx(svm, vec![withdraw_ix], authority, vec![])
+}
+
+pub fn dispatch_command_from_wallet(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    admin_pda: Pubkey,
+    oracle: &Keypair,
+    args: DispatchCommandArgs,
+) -> Vec<String> {
+    let ed25519_ix = ix_verify_price(
+        oracle,
+        args.command_id,
+        args.price,
+        args.timestamp,
+        user_pda(&authority.pubkey(), admin_pda),
+    );
+    let dispatch_ix = ix_dispatch_command_from_wallet(
+        authority,
+        admin_pda,
+        args.command_id,
+        args.price,
+        args.timestamp,
+        args.payload,
+    );
+    build_and_send_tx(svm, vec![ed25519_ix, dispatch_ix], authority, vec![])
+}
+
+pub fn ix_create_wallet(authority: &Keypair) -> Instruction {
+    let data = w3b2_instruction::UserCreateWallet {}.data();
+
+    let accounts = w3b2_accounts::UserCreateWallet {
+        authority: authority.pubkey(),
+        wallet: wallet_pda(&authority.pubkey()),
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_solana_program::ID,
+        accounts,
+        data,
+    }
+}
+
+pub fn ix_wallet_deposit(authority: &Keypair, amount: u64) -> Instruction {
+    let data = w3b2_instruction::WalletDeposit { amount }.data();
+
+    let accounts = w3b2_accounts::WalletDeposit {
+        authority: authority.pubkey(),
+        wallet: wallet_pda(&authority.pubkey()),
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_solana_program::ID,
+        accounts,
+        data,
+    }
+}
+
+pub fn ix_wallet_withdraw(authority: &Keypair, destination: Pubkey, amount: u64) -> Instruction {
+    let data = w3b2_instruction::WalletWithdraw { amount }.data();
+
+    let accounts = w3b2_accounts::WalletWithdraw {
+        authority: authority.pubkey(),
+        wallet: wallet_pda(&authority.pubkey()),
+        destination,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_solana_program::ID,
+        accounts,
+        data,
+    }
+}
+
+pub fn ix_dispatch_command_from_wallet(
+    authority: &Keypair,
+    admin_pda: Pubkey,
+    command_id: u16,
+    price: u64,
+    timestamp: i64,
+    payload: Vec<u8>,
+) -> Instruction {
+    let (user_pda, _) = Pubkey::find_program_address(
+        &[b"user", authority.pubkey().as_ref(), admin_pda.as_ref()],
+        &w3b2_solana_program::ID,
+    );
+
+    let data = w3b2_instruction::UserDispatchCommandFromWallet {
+        command_id,
+        price,
+        timestamp,
+        payload,
+        mode: CommandMode::default(),
+    }
+    .data();
+
+    let accounts = w3b2_accounts::UserDispatchCommandFromWallet {
+        authority: authority.pubkey(),
+        user_profile: user_pda,
+        admin_profile: admin_pda,
+        wallet: wallet_pda(&authority.pubkey()),
+        instructions: instructions::ID,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_solana_program::ID,
+        accounts,
+        data,
+    }
+}
@@ -2,7 +2,7 @@ mod instructions;
 
 use instructions::*;
 use solana_sdk::signature::Signer;
-use w3b2_solana_program::events::OffChainActionLogged;
+use w3b2_solana_program::{action_categories, events::OffChainActionLogged};
 
 #[test]
 fn test_log_action_by_user_success() {
@@ -22,6 +22,8 @@ fn test_log_action_by_user_success() {
         admin_pda,
         session_id,
         action_code,
+        action_categories::GENERAL,
+        None,
     );
     println!("Action logged by user.");
 
@@ -33,7 +35,9 @@ fn test_log_action_by_user_success() {
     assert_eq!(event.user_profile_pda, user_pda);
     assert_eq!(event.admin_profile_pda, admin_pda);
     assert_eq!(event.session_id, session_id);
+    assert_eq!(event.category, action_categories::GENERAL);
     assert_eq!(event.action_code, action_code);
+    assert!(event.metadata.is_empty());
 
     println!("✅ Log Action by User Test Passed!");
 }
@@ -56,6 +60,8 @@ fn test_log_action_by_admin_success() {
         admin_pda,
         session_id,
         action_code,
+        action_categories::GENERAL,
+        None,
     );
     println!("Action logged by admin.");
 
@@ -71,3 +77,75 @@ fn test_log_action_by_admin_success() {
 
     println!("✅ Log Action by Admin Test Passed!");
 }
+
+#[test]
+fn test_log_action_with_metadata_success() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, _user_authority, user_pda) = setup_profiles(&mut svm);
+
+    let metadata = b"resource:42;status:201".to_vec();
+
+    // === 2. Act ===
+    println!("Admin logging an action with metadata...");
+    let logs = log::log_action(
+        &mut svm,
+        &admin_authority,
+        user_pda,
+        admin_pda,
+        777,
+        201,
+        action_categories::HTTP,
+        Some(metadata.clone()),
+    );
+    println!("Action with metadata logged.");
+
+    // === 3. Assert ===
+    let events = parse_events::<OffChainActionLogged>(&logs);
+    let event = events.last().expect("No events were emitted!");
+
+    assert_eq!(event.category, action_categories::HTTP);
+    assert_eq!(event.action_code, 201);
+    assert_eq!(event.metadata, metadata);
+
+    println!("✅ Log Action with Metadata Test Passed!");
+}
+
+#[test]
+fn test_log_action_category_namespaces_action_code() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (_admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+
+    // The same code means different things in different subsystems.
+    let action_code = 404;
+
+    // === 2. Act ===
+    println!("Logging the same action code under two categories...");
+    let mut emitted = Vec::new();
+    for (session_id, category) in [
+        (1, action_categories::HTTP),
+        (2, action_categories::STORAGE),
+    ] {
+        let logs = log::log_action(
+            &mut svm,
+            &user_authority,
+            user_pda,
+            admin_pda,
+            session_id,
+            action_code,
+            category,
+            None,
+        );
+        let events = parse_events::<OffChainActionLogged>(&logs);
+        emitted.push(events.last().expect("No events were emitted!").clone());
+        svm.expire_blockhash();
+    }
+
+    // === 3. Assert ===
+    assert_eq!(emitted[0].category, action_categories::HTTP);
+    assert_eq!(emitted[1].category, action_categories::STORAGE);
+    assert!(emitted.iter().all(|e| e.action_code == action_code));
+
+    println!("✅ Log Action Category Test Passed!");
+}
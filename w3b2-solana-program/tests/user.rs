@@ -12,8 +12,13 @@ use anchor_lang::AccountDeserialize;
 use instructions::*;
 use solana_program::native_token::LAMPORTS_PER_SOL;
 use solana_program::sysvar::rent::Rent;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
 use solana_sdk::signature::Signer;
-use w3b2_solana_program::state::{AdminProfile, UserProfile};
+use w3b2_solana_program::events::{
+    AllowanceConsumed, AllowanceGranted, UserCommandDispatched, UserFundsDeposited, UserLowBalance,
+    UserProfileClosed, UserSpendingCapUpdated, UserUnbanRequested, WalletCharged,
+};
+use w3b2_solana_program::state::{AdminProfile, Allowance, UserProfile, UserWallet};
 
 /// Tests the successful creation of a `UserProfile` PDA.
 /// Verifies that a user can create a profile linked to a specific admin.
@@ -68,8 +73,8 @@ fn test_user_create_profile_success() {
 }
 
 /// Tests the successful update of a `UserProfile`'s communication key.
-/// Verifies that the `communication_pubkey` field is updated correctly while other
-/// fields in the `UserProfile` remain unchanged.
+/// Verifies that the `communication_pubkey` field is updated correctly and
+/// `comm_key_version` is incremented, while other fields remain unchanged.
 #[test]
 fn test_user_update_comm_key_success() {
     // === 1. Arrange ===
@@ -102,6 +107,21 @@ fn test_user_update_comm_key_success() {
     assert_ne!(user_profile.communication_pubkey, initial_comm_key.pubkey());
     assert_eq!(user_profile.authority, user_authority.pubkey());
     assert_eq!(user_profile.deposit_balance, 0);
+    assert_eq!(user_profile.comm_key_version, 1);
+
+    // Every rotation bumps the version, so caches can order the keys they observe.
+    let newest_comm_key = create_keypair();
+    user::update_comm_key(
+        &mut svm,
+        &user_authority,
+        admin_pda,
+        newest_comm_key.pubkey(),
+    );
+    let user_account_data = svm.get_account(&user_pda).unwrap();
+    let user_profile =
+        UserProfile::try_deserialize(&mut user_account_data.data.as_slice()).unwrap();
+    assert_eq!(user_profile.communication_pubkey, newest_comm_key.pubkey());
+    assert_eq!(user_profile.comm_key_version, 2);
 
     println!("✅ Update User Comm Key Test Passed!");
     println!("   -> Old Key: {}", initial_comm_key.pubkey());
@@ -132,7 +152,7 @@ fn test_user_close_profile_success() {
 
     // === 2. Act ===
     println!("Closing user profile...");
-    user::close_profile(&mut svm, &user_authority, admin_pda);
+    let logs = user::close_profile(&mut svm, &user_authority, admin_pda);
     println!("Profile closed.");
 
     // === 3. Assert ===
@@ -142,6 +162,7 @@ fn test_user_close_profile_success() {
     let authority_balance_after = svm.get_balance(&user_authority.pubkey()).unwrap();
     let expected_balance = authority_balance_before + pda_balance - 5000;
     assert_eq!(authority_balance_after, expected_balance);
+    assert!(!parse_events::<UserProfileClosed>(&logs)[0].was_banned);
 
     println!("✅ Close User Profile Test Passed!");
     println!(
@@ -149,6 +170,46 @@ fn test_user_close_profile_success() {
     );
 }
 
+/// Tests that a banned user can still close their profile, so that the ban never traps
+/// their deposit. The closure event reports that the user was banned.
+#[test]
+fn test_user_close_profile_while_banned() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+
+    let deposit_amount = LAMPORTS_PER_SOL;
+    user::deposit(&mut svm, &user_authority, admin_pda, deposit_amount);
+    admin::ban_user(&mut svm, &admin_authority, user_pda);
+
+    let pda_balance = svm.get_balance(&user_pda).unwrap();
+    let authority_balance_before = svm.get_balance(&user_authority.pubkey()).unwrap();
+
+    // === 2. Act ===
+    println!("Closing the profile of a banned user...");
+    let logs = user::close_profile(&mut svm, &user_authority, admin_pda);
+
+    // === 3. Assert ===
+    assert!(
+        svm.get_account(&user_pda).is_none(),
+        "Account was not closed!"
+    );
+
+    let authority_balance_after = svm.get_balance(&user_authority.pubkey()).unwrap();
+    assert!(pda_balance > deposit_amount);
+    assert_eq!(
+        authority_balance_after,
+        authority_balance_before + pda_balance - 5000
+    );
+
+    let closed = &parse_events::<UserProfileClosed>(&logs)[0];
+    assert!(closed.was_banned);
+    assert_eq!(closed.user_pda, user_pda);
+
+    println!("✅ Close While Banned Test Passed!");
+    println!("   -> Deposit and rent refunded: {pda_balance} lamports");
+}
+
 /// Tests the successful deposit of funds into a `UserProfile`.
 /// Verifies that the internal `deposit_balance` is correctly incremented and that
 /// the on-chain lamport balance of both the user's wallet and the PDA are updated
@@ -206,6 +267,47 @@ fn test_user_deposit_success() {
     );
 }
 
+/// Tests that a deposit reports the user's wallet as its source when a relayer pays the
+/// transaction fees, and that only the deposited amount leaves the user's wallet.
+#[test]
+fn test_user_deposit_reports_source_when_relayer_pays_fees() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+
+    let admin_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let admin_pda = admin::create_profile(&mut svm, &admin_authority, create_keypair().pubkey());
+
+    let user_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let user_pda = user::create_profile(
+        &mut svm,
+        &user_authority,
+        create_keypair().pubkey(),
+        admin_pda,
+    );
+    let relayer = create_funded_keypair(&mut svm, LAMPORTS_PER_SOL);
+
+    let authority_balance_before = svm.get_balance(&user_authority.pubkey()).unwrap();
+    let deposit_amount = LAMPORTS_PER_SOL;
+
+    // === 2. Act ===
+    let deposit_ix = user::ix_deposit(&user_authority, admin_pda, deposit_amount);
+    let logs = build_and_send_tx(&mut svm, vec![deposit_ix], &relayer, vec![&user_authority]);
+
+    // === 3. Assert ===
+    let deposited = &parse_events::<UserFundsDeposited>(&logs)[0];
+    assert_eq!(deposited.source, user_authority.pubkey());
+    assert_ne!(deposited.source, relayer.pubkey());
+    assert_eq!(deposited.authority, user_authority.pubkey());
+    assert_eq!(deposited.user_profile_pda, user_pda);
+    assert_eq!(deposited.amount, deposit_amount);
+
+    let authority_balance_after = svm.get_balance(&user_authority.pubkey()).unwrap();
+    assert_eq!(
+        authority_balance_after,
+        authority_balance_before - deposit_amount
+    );
+}
+
 /// Tests the successful withdrawal of funds from a `UserProfile`.
 /// Verifies that the internal `deposit_balance` is correctly decremented, the PDA's
 /// lamport balance decreases, and the destination wallet's balance increases by the
@@ -364,6 +466,366 @@ fn test_user_dispatch_command_success() {
     );
 }
 
+/// Tests that `UserCommandDispatched` records the oracle key that authorized the price.
+/// Verifies that after the admin delegates pricing to a new oracle via `admin_set_config`,
+/// dispatches carry the new oracle's key instead of the admin's own.
+#[test]
+fn test_user_dispatch_command_records_oracle_authority() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, _user_pda) = setup_profiles(&mut svm);
+    user::deposit(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL);
+
+    let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+    let args = |command_id| user::DispatchCommandArgs {
+        command_id,
+        price: 1_000,
+        timestamp,
+        payload: vec![],
+    };
+    let logs = user::dispatch_command(
+        &mut svm,
+        &user_authority,
+        admin_pda,
+        &admin_authority,
+        args(1),
+    );
+    let before = parse_events::<UserCommandDispatched>(&logs);
+
+    let new_oracle = create_keypair();
+    admin::set_config(
+        &mut svm,
+        &admin_authority,
+        Some(new_oracle.pubkey()),
+        None,
+        None,
+        None,
+        None,
+    );
+
+    // === 2. Act ===
+    let logs = user::dispatch_command(&mut svm, &user_authority, admin_pda, &new_oracle, args(2));
+
+    // === 3. Assert ===
+    let after = parse_events::<UserCommandDispatched>(&logs);
+    assert_eq!(before.len(), 1);
+    assert_eq!(before[0].oracle_authority, admin_authority.pubkey());
+    assert_eq!(after.len(), 1);
+    assert_eq!(after[0].oracle_authority, new_oracle.pubkey());
+
+    println!("✅ Dispatch Oracle Authority Test Passed!");
+}
+
+/// Tests that the oracle's Ed25519 verification may sit anywhere in the transaction.
+/// Verifies that a dispatch succeeds whether the verification immediately precedes it,
+/// is separated from it by a compute budget instruction, or follows it.
+#[test]
+fn test_user_dispatch_command_with_verification_anywhere() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+    let deposit_amount = LAMPORTS_PER_SOL;
+    user::deposit(&mut svm, &user_authority, admin_pda, deposit_amount);
+
+    let price = 1_000u64;
+    let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+    let verify = |command_id| {
+        user::ix_verify_price(&admin_authority, command_id, price, timestamp, user_pda)
+    };
+    let dispatch = |command_id| {
+        user::ix_dispatch_command(
+            &user_authority,
+            admin_pda,
+            command_id,
+            price,
+            timestamp,
+            vec![],
+        )
+    };
+    let priority_fee = ComputeBudgetInstruction::set_compute_unit_price(1);
+    let placements = [
+        vec![verify(1), dispatch(1)],
+        vec![verify(2), priority_fee.clone(), dispatch(2)],
+        vec![dispatch(3), verify(3)],
+    ];
+
+    // === 2. Act ===
+    for instructions in placements {
+        build_and_send_tx(&mut svm, instructions, &user_authority, vec![]);
+    }
+
+    // === 3. Assert ===
+    let user_account = svm.get_account(&user_pda).unwrap();
+    let user_profile = UserProfile::try_deserialize(&mut user_account.data.as_slice()).unwrap();
+    assert_eq!(user_profile.deposit_balance, deposit_amount - 3 * price);
+
+    println!("✅ Verification Placement Test Passed!");
+}
+
+/// Tests the `UserLowBalance` notification of paid commands.
+/// Verifies that the event is emitted when a payment drops the deposit below the user's
+/// threshold, is not repeated while the balance stays below it, and is re-armed by a
+/// deposit that lifts the balance back above the threshold.
+#[test]
+fn test_user_low_balance_event_on_threshold_crossing() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+
+    let threshold = LAMPORTS_PER_SOL / 2;
+    user::deposit(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL);
+    user::set_low_balance_threshold(&mut svm, &user_authority, admin_pda, threshold);
+
+    let timestamp = svm.get_sysvar::<Clock>().unix_timestamp;
+    let dispatch = |svm: &mut litesvm::LiteSVM, price: u64| {
+        let logs = user::dispatch_command(
+            svm,
+            &user_authority,
+            admin_pda,
+            &admin_authority,
+            user::DispatchCommandArgs {
+                command_id: 1,
+                price,
+                timestamp,
+                payload: vec![],
+            },
+        );
+        parse_events::<UserLowBalance>(&logs)
+    };
+
+    // === 2. Act ===
+    println!("Dispatching commands across the low-balance threshold...");
+    let above = dispatch(&mut svm, LAMPORTS_PER_SOL * 3 / 10); // 0.70 SOL left
+    let crossing = dispatch(&mut svm, LAMPORTS_PER_SOL / 4); // 0.45 SOL left
+    let still_below = dispatch(&mut svm, LAMPORTS_PER_SOL / 10); // 0.35 SOL left
+    user::deposit(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL); // 1.35 SOL
+    let crossing_again = dispatch(&mut svm, LAMPORTS_PER_SOL); // 0.35 SOL left
+
+    // === 3. Assert ===
+    assert!(above.is_empty(), "No event while above the threshold");
+    assert_eq!(crossing.len(), 1);
+    assert_eq!(crossing[0].user_profile_pda, user_pda);
+    assert_eq!(crossing[0].admin_pda, admin_pda);
+    assert_eq!(crossing[0].deposit_balance, LAMPORTS_PER_SOL * 45 / 100);
+    assert_eq!(crossing[0].threshold, threshold);
+    assert!(
+        still_below.is_empty(),
+        "The event must fire once per crossing"
+    );
+    assert_eq!(crossing_again.len(), 1, "A deposit must re-arm the event");
+
+    let user_account = svm.get_account(&user_pda).unwrap();
+    let user_profile = UserProfile::try_deserialize(&mut user_account.data.as_slice()).unwrap();
+    assert!(user_profile.low_balance_notified);
+
+    println!("✅ Low Balance Event Test Passed!");
+    println!(
+        "   -> Event emitted at balance: {}",
+        crossing[0].deposit_balance
+    );
+}
+
+/// Tests that a user can set and remove their spending cap.
+/// Verifies that the cap is stored on the profile and announced by `UserSpendingCapUpdated`.
+#[test]
+fn test_user_set_spending_cap_success() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (_, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+    let read_cap = |svm: &litesvm::LiteSVM| {
+        let account = svm.get_account(&user_pda).unwrap();
+        UserProfile::try_deserialize(&mut account.data.as_slice())
+            .unwrap()
+            .max_price_per_command
+    };
+    assert_eq!(read_cap(&svm), 0, "New profiles have no cap");
+
+    // === 2. Act ===
+    let logs = user::set_spending_cap(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL);
+    let capped = read_cap(&svm);
+    svm.expire_blockhash();
+    user::set_spending_cap(&mut svm, &user_authority, admin_pda, 0);
+
+    // === 3. Assert ===
+    assert_eq!(capped, LAMPORTS_PER_SOL);
+    assert_eq!(read_cap(&svm), 0);
+    let events = parse_events::<UserSpendingCapUpdated>(&logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].user_profile_pda, user_pda);
+    assert_eq!(events[0].new_max_price_per_command, LAMPORTS_PER_SOL);
+
+    println!("✅ Set Spending Cap Test Passed!");
+}
+
+/// Tests that a service can charge several commands against a user's allowance without the
+/// user signing them, and that every charge is drawn from both the deposit and the allowance.
+#[test]
+fn test_dispatch_with_allowance_draws_across_calls() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+    let deposit_amount = LAMPORTS_PER_SOL;
+    user::deposit(&mut svm, &user_authority, admin_pda, deposit_amount);
+
+    let price = LAMPORTS_PER_SOL / 10;
+    let total_cap = 5 * price;
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let logs = user::grant_allowance(
+        &mut svm,
+        &user_authority,
+        admin_pda,
+        vec![1, 2],
+        total_cap,
+        now + 3600,
+    );
+    let granted = parse_events::<AllowanceGranted>(&logs);
+    assert_eq!(granted.len(), 1);
+    assert_eq!(granted[0].user_profile_pda, user_pda);
+    assert_eq!(granted[0].commands, vec![1, 2]);
+
+    // === 2. Act ===
+    // The admin is its own oracle, and signs the transactions instead of the user.
+    let mut consumed = Vec::new();
+    for (call, command_id) in [1u16, 2, 1].into_iter().enumerate() {
+        let tx = user::tx_dispatch_with_allowance(
+            &svm,
+            &admin_authority,
+            user_pda,
+            admin_pda,
+            &admin_authority,
+            user::DispatchCommandArgs {
+                command_id,
+                price,
+                timestamp: now,
+                payload: vec![call as u8],
+            },
+        );
+        let logs = svm.send_transaction(tx).expect("dispatch failed").logs;
+
+        let dispatched = parse_events::<UserCommandDispatched>(&logs);
+        assert_eq!(dispatched.len(), 1);
+        assert_eq!(dispatched[0].sender, user_authority.pubkey());
+        consumed.extend(parse_events::<AllowanceConsumed>(&logs));
+    }
+
+    // === 3. Assert ===
+    let remaining: Vec<u64> = consumed.iter().map(|e| e.remaining_cap).collect();
+    assert_eq!(remaining, vec![4 * price, 3 * price, 2 * price]);
+    assert!(consumed
+        .iter()
+        .all(|e| e.charger == admin_authority.pubkey() && e.amount == price));
+
+    let allowance_account = svm.get_account(&user::allowance_pda(user_pda)).unwrap();
+    let allowance = Allowance::try_deserialize(&mut allowance_account.data.as_slice()).unwrap();
+    assert_eq!(allowance.remaining_cap, 2 * price);
+
+    let user_account = svm.get_account(&user_pda).unwrap();
+    let user_profile = UserProfile::try_deserialize(&mut user_account.data.as_slice()).unwrap();
+    assert_eq!(user_profile.deposit_balance, deposit_amount - 3 * price);
+
+    let admin_account = svm.get_account(&admin_pda).unwrap();
+    let admin_profile = AdminProfile::try_deserialize(&mut admin_account.data.as_slice()).unwrap();
+    assert_eq!(admin_profile.balance, 3 * price);
+
+    println!("✅ Dispatch With Allowance Test Passed!");
+}
+
+/// Tests that one shared wallet pays for commands to two different admins, while the
+/// deposits of the user's profiles stay untouched.
+#[test]
+fn test_shared_wallet_pays_commands_to_two_admins() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (first_admin, first_admin_pda, user_authority, first_user_pda) = setup_profiles(&mut svm);
+    let second_admin = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let second_admin_pda =
+        admin::create_profile(&mut svm, &second_admin, create_keypair().pubkey());
+    let second_user_pda = user::create_profile(
+        &mut svm,
+        &user_authority,
+        create_keypair().pubkey(),
+        second_admin_pda,
+    );
+
+    let wallet_pda = user::create_wallet(&mut svm, &user_authority);
+    let deposit_amount = LAMPORTS_PER_SOL;
+    user::wallet_deposit(&mut svm, &user_authority, deposit_amount);
+
+    let first_price = LAMPORTS_PER_SOL / 10;
+    let second_price = LAMPORTS_PER_SOL / 4;
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+
+    // === 2. Act ===
+    // Each admin is its own oracle.
+    let first_logs = user::dispatch_command_from_wallet(
+        &mut svm,
+        &user_authority,
+        first_admin_pda,
+        &first_admin,
+        user::DispatchCommandArgs {
+            command_id: 1,
+            price: first_price,
+            timestamp: now,
+            payload: vec![1],
+        },
+    );
+    let second_logs = user::dispatch_command_from_wallet(
+        &mut svm,
+        &user_authority,
+        second_admin_pda,
+        &second_admin,
+        user::DispatchCommandArgs {
+            command_id: 2,
+            price: second_price,
+            timestamp: now,
+            payload: vec![2],
+        },
+    );
+
+    // === 3. Assert ===
+    let first_charge = parse_events::<WalletCharged>(&first_logs);
+    let second_charge = parse_events::<WalletCharged>(&second_logs);
+    assert_eq!(first_charge.len(), 1);
+    assert_eq!(first_charge[0].user_profile_pda, first_user_pda);
+    assert_eq!(first_charge[0].admin_pda, first_admin_pda);
+    assert_eq!(
+        first_charge[0].new_deposit_balance,
+        deposit_amount - first_price
+    );
+    assert_eq!(second_charge.len(), 1);
+    assert_eq!(second_charge[0].wallet_pda, wallet_pda);
+    assert_eq!(second_charge[0].user_profile_pda, second_user_pda);
+    assert_eq!(second_charge[0].admin_pda, second_admin_pda);
+    assert_eq!(
+        parse_events::<UserCommandDispatched>(&second_logs)[0].sender_user_pda,
+        second_user_pda
+    );
+
+    let wallet_account = svm.get_account(&wallet_pda).unwrap();
+    let wallet = UserWallet::try_deserialize(&mut wallet_account.data.as_slice()).unwrap();
+    assert_eq!(
+        wallet.deposit_balance,
+        deposit_amount - first_price - second_price
+    );
+
+    for (admin_pda, price) in [
+        (first_admin_pda, first_price),
+        (second_admin_pda, second_price),
+    ] {
+        let admin_account = svm.get_account(&admin_pda).unwrap();
+        let admin_profile =
+            AdminProfile::try_deserialize(&mut admin_account.data.as_slice()).unwrap();
+        assert_eq!(admin_profile.balance, price);
+    }
+    for user_pda in [first_user_pda, second_user_pda] {
+        let user_account = svm.get_account(&user_pda).unwrap();
+        let user_profile = UserProfile::try_deserialize(&mut user_account.data.as_slice()).unwrap();
+        assert_eq!(user_profile.deposit_balance, 0);
+    }
+
+    println!("✅ Shared Wallet Test Passed!");
+}
+
 /// Tests the user's ability to request an unban, both with and without a fee.
 #[test]
 fn test_user_request_unban_success() {
@@ -411,6 +873,7 @@ fn test_user_request_unban_success() {
         None,
         None,
         Some(unban_fee),
+        None,
     );
 
     // User deposits funds to pay the fee
@@ -455,3 +918,54 @@ fn test_user_request_unban_success() {
 
     println!("✅ User Request Unban Test Passed!");
 }
+
+/// Tests that the unban fee escalates with the user's earlier bans when the admin sets
+/// an `unban_fee_multiplier_bps`, and that it stops growing at `max_unban_fee_multiple`.
+#[test]
+fn test_user_unban_fee_escalates_with_ban_count() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let (admin_authority, admin_pda, user_authority, user_pda) = setup_profiles(&mut svm);
+
+    let unban_fee = 100_000;
+    admin::set_config(
+        &mut svm,
+        &admin_authority,
+        None,
+        None,
+        None,
+        Some(unban_fee),
+        None,
+    );
+    // Each earlier ban adds 50% of the fee, up to twice the fee.
+    admin::set_unban_fee_escalation(&mut svm, &admin_authority, Some(5_000), Some(2));
+    user::deposit(&mut svm, &user_authority, admin_pda, LAMPORTS_PER_SOL);
+
+    // === 2. Act ===
+    let mut fees = Vec::new();
+    for _ in 0..4 {
+        admin::ban_user(&mut svm, &admin_authority, user_pda);
+        let logs = user::request_unban(&mut svm, &user_authority, admin_pda);
+        fees.push(parse_events::<UserUnbanRequested>(&logs)[0].fee_paid);
+        admin::unban_user(&mut svm, &admin_authority, user_pda);
+        svm.expire_blockhash();
+    }
+
+    // === 3. Assert ===
+    assert!(
+        fees[1] > fees[0],
+        "the second ban should cost more than the first"
+    );
+    assert_eq!(fees, vec![100_000, 150_000, 200_000, 200_000]);
+
+    let user_profile = {
+        let account_data = svm.get_account(&user_pda).unwrap();
+        UserProfile::try_deserialize(&mut account_data.data.as_slice()).unwrap()
+    };
+    assert_eq!(user_profile.ban_count, 4);
+    assert_eq!(
+        user_profile.deposit_balance,
+        LAMPORTS_PER_SOL - fees.iter().sum::<u64>()
+    );
+    println!("✅ Unban fees escalated with each ban: {fees:?}");
+}
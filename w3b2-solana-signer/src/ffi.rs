@@ -6,6 +6,18 @@
 //! ## Thread Safety
 //! - The global key table (`KEY_TABLE`) is backed by `DashMap` and **is safe to access from multiple threads**.
 //! - Each FFI call is atomic with respect to key storage. You can safely call `load_key`, `sign_with_handle`, etc. concurrently.
+//! - `DashMap` splits the table into shards, each guarded by its own read-write lock. Signing only
+//!   takes a shared (read) lock on the shard holding the key, so signs never serialize against each
+//!   other. Loading, unloading or rotating a key takes the exclusive lock of a single shard, which
+//!   briefly waits for in-progress signs with keys from that shard.
+//! - [`w3b2_rotate_key`] swaps the key behind an id in place, so every sign uses either the old
+//!   or the new key in full.
+//!
+//! ## Concurrency Limit and Statistics
+//! - [`w3b2_set_max_concurrent_signs`] bounds how many signs run at once (to cap CPU usage). Callers
+//!   over the limit block until a slot frees up. The limit is off (`0`) by default.
+//! - [`w3b2_get_stats`] reports the total number of signs, the signs currently in flight, and how many
+//!   keys have been loaded and unloaded.
 //!
 //! ## Memory and Lifetime
 //! - All buffers returned from Rust must be freed using [`free_buffer`].
@@ -24,13 +36,22 @@
 use dashmap::DashMap;
 use lazy_static::lazy_static;
 use libc::{mlock, munlock};
-use solana_sdk::{hash::Hash, message::Message, signature::Keypair, transaction::Transaction};
+use solana_sdk::{
+    hash::Hash,
+    message::Message,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
 use std::cell::RefCell;
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
 use std::os::raw::c_char;
 use std::ptr;
 use std::slice;
 use std::sync::atomic::{AtomicU64, Ordering};
-use zeroize::Zeroize;
+use std::sync::{Condvar, Mutex};
+use zeroize::{Zeroize, Zeroizing};
 
 type Handle = u64;
 
@@ -55,8 +76,73 @@ impl Drop for KeyEntry {
 
 static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
 
+static TOTAL_SIGNS: AtomicU64 = AtomicU64::new(0);
+static KEYS_LOADED: AtomicU64 = AtomicU64::new(0);
+static KEYS_UNLOADED: AtomicU64 = AtomicU64::new(0);
+
 lazy_static! {
     static ref KEY_TABLE: DashMap<Handle, KeyEntry> = DashMap::new();
+    static ref SIGN_LIMITER: SignLimiter = SignLimiter::default();
+}
+
+/// A counting semaphore bounding the number of concurrent signs.
+#[derive(Default)]
+struct SignLimiter {
+    /// `(in_flight, max)`, where a `max` of `0` means unlimited.
+    state: Mutex<(u64, u64)>,
+    slot_freed: Condvar,
+}
+
+/// Releases a [`SignLimiter`] slot when dropped.
+struct SignPermit;
+
+impl SignLimiter {
+    /// Blocks until a signing slot is available and takes it.
+    fn acquire(&self) -> SignPermit {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        while state.1 != 0 && state.0 >= state.1 {
+            state = self
+                .slot_freed
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        state.0 += 1;
+        SignPermit
+    }
+
+    fn set_max(&self, max: u64) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).1 = max;
+        self.slot_freed.notify_all();
+    }
+
+    fn in_flight(&self) -> u64 {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).0
+    }
+}
+
+impl Drop for SignPermit {
+    fn drop(&mut self) {
+        SIGN_LIMITER
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .0 -= 1;
+        SIGN_LIMITER.slot_freed.notify_one();
+    }
+}
+
+/// Usage counters reported by [`w3b2_get_stats`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct W3b2SignerStats {
+    /// The number of successful signs since the library was loaded.
+    pub total_signs: u64,
+    /// The number of signs currently in progress.
+    pub in_flight: u64,
+    /// The number of keys loaded with [`load_key`].
+    pub keys_loaded: u64,
+    /// The number of keys removed with [`unload_key`] or [`clear_all_keys`].
+    pub keys_unloaded: u64,
 }
 
 thread_local! {
@@ -159,9 +245,132 @@ pub unsafe extern "C" fn load_key(key_ptr: *const u8, key_len: usize) -> Handle
 
     let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
     KEY_TABLE.insert(handle, KeyEntry { secret, locked });
+    KEYS_LOADED.fetch_add(1, Ordering::Relaxed);
     handle
 }
 
+/// The call succeeded.
+pub const W3B2_OK: i32 = 0;
+/// A pointer argument was null or the path was not valid UTF-8.
+pub const W3B2_ERR_INVALID_ARGUMENT: i32 = -1;
+/// The keypair file does not exist.
+pub const W3B2_ERR_FILE_NOT_FOUND: i32 = -2;
+/// The file could not be read or is not a valid JSON keypair.
+pub const W3B2_ERR_PARSE: i32 = -3;
+/// The key memory could not be locked with `mlock()`. The key is not loaded.
+pub const W3B2_ERR_MLOCK: i32 = -4;
+/// The key handle does not refer to a loaded key.
+pub const W3B2_ERR_INVALID_HANDLE: i32 = -5;
+
+/// Loads a keypair from a file in the standard Solana CLI format (a JSON array of 64 bytes)
+/// and registers it in the key table.
+///
+/// # Safety
+/// - `path_ptr` must be a valid, null-terminated UTF-8 string.
+/// - `out_key_id` must be a valid, non-null pointer to a `Handle`.
+///
+/// # Notes
+/// - The key bytes are parsed straight into `mlock`ed memory; the raw file contents are
+///   zeroized before returning, so the secret never lingers in an unlocked buffer.
+/// - Unlike [`load_key`], a failing `mlock()` is an error and the key is not loaded.
+///
+/// # Returns
+/// - [`W3B2_OK`] on success, with the new handle written to `out_key_id`.
+/// - [`W3B2_ERR_INVALID_ARGUMENT`], [`W3B2_ERR_FILE_NOT_FOUND`], [`W3B2_ERR_PARSE`] or
+///   [`W3B2_ERR_MLOCK`] on failure. The error details are stored via [`get_last_error`].
+#[no_mangle]
+pub unsafe extern "C" fn w3b2_load_from_file(
+    path_ptr: *const c_char,
+    out_key_id: *mut Handle,
+) -> i32 {
+    if path_ptr.is_null() || out_key_id.is_null() {
+        set_last_error("null or invalid argument");
+        return W3B2_ERR_INVALID_ARGUMENT;
+    }
+    let Ok(path) = CStr::from_ptr(path_ptr).to_str() else {
+        set_last_error("path is not valid UTF-8");
+        return W3B2_ERR_INVALID_ARGUMENT;
+    };
+
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            set_last_error(format!("keypair file not found: {}", path));
+            return W3B2_ERR_FILE_NOT_FOUND;
+        }
+        Err(e) => {
+            set_last_error(format!("failed to open keypair file: {}", e));
+            return W3B2_ERR_PARSE;
+        }
+    };
+    // Reserve the full size up front so reading never reallocates (and copies) the contents.
+    let size = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+    let mut contents = Zeroizing::new(Vec::with_capacity(size + 1));
+    if let Err(e) = file.read_to_end(&mut contents) {
+        set_last_error(format!("failed to read keypair file: {}", e));
+        return W3B2_ERR_PARSE;
+    }
+
+    let mut secret = vec![0u8; 64];
+    if !try_mlock(&mut secret) {
+        set_last_error("mlock failed (process may allow swapping)");
+        return W3B2_ERR_MLOCK;
+    }
+    let mut entry = KeyEntry {
+        secret,
+        locked: true,
+    };
+    if let Err(e) = parse_json_keypair(&contents, &mut entry.secret) {
+        set_last_error(format!("invalid keypair file: {}", e));
+        return W3B2_ERR_PARSE;
+    }
+    if let Err(e) = Keypair::try_from(entry.secret.as_slice()) {
+        set_last_error(format!("invalid keypair file: {}", e));
+        return W3B2_ERR_PARSE;
+    }
+
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    KEY_TABLE.insert(handle, entry);
+    KEYS_LOADED.fetch_add(1, Ordering::Relaxed);
+    *out_key_id = handle;
+    W3B2_OK
+}
+
+/// Parses a JSON array of exactly `out.len()` integers in `0..=255` into `out`.
+fn parse_json_keypair(json: &[u8], out: &mut [u8]) -> Result<(), &'static str> {
+    let mut bytes = json
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .peekable();
+    if bytes.next() != Some(b'[') {
+        return Err("expected a JSON array");
+    }
+    for (i, slot) in out.iter_mut().enumerate() {
+        if i > 0 && bytes.next() != Some(b',') {
+            return Err("expected 64 comma-separated bytes");
+        }
+        let mut value: u16 = 0;
+        let mut digits = 0;
+        while let Some(digit) = bytes.peek().filter(|b| b.is_ascii_digit()) {
+            value = value * 10 + u16::from(digit - b'0');
+            digits += 1;
+            bytes.next();
+            if value > 255 {
+                return Err("byte value out of range");
+            }
+        }
+        if digits == 0 {
+            return Err("expected 64 comma-separated bytes");
+        }
+        *slot = value as u8;
+    }
+    if bytes.next() != Some(b']') || bytes.next().is_some() {
+        return Err("expected exactly 64 bytes");
+    }
+    Ok(())
+}
+
 /// Unloads and securely deletes a key associated with a given handle.
 ///
 /// # Notes
@@ -174,7 +383,72 @@ pub extern "C" fn unload_key(handle: Handle) {
     }
     if KEY_TABLE.remove(&handle).is_none() {
         set_last_error("invalid handle");
+        return;
     }
+    KEYS_UNLOADED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Replaces the keypair behind `key_id` with a new one, keeping the id.
+///
+/// Rotating an oracle key this way leaves every reference to `key_id` valid, with no window
+/// in which some callers sign with a different key than others.
+///
+/// # Safety
+/// - `new_key_ptr` must point to 64 bytes (`[secret||public]`).
+/// - `out_new_pubkey` must point to a writable buffer of at least 32 bytes.
+///
+/// # Notes
+/// - The swap is atomic: it takes the exclusive lock of the key's table shard, so it waits
+///   for signs already using the old key, and every later sign uses the new one. No sign
+///   ever observes a partially replaced key.
+/// - The old key is zeroized and `munlock()`ed once the swap is done.
+/// - Like [`w3b2_load_from_file`], a failing `mlock()` is an error and the old key is kept.
+///
+/// # Returns
+/// - [`W3B2_OK`] on success, with the new public key written to `out_new_pubkey`.
+/// - [`W3B2_ERR_INVALID_ARGUMENT`], [`W3B2_ERR_PARSE`], [`W3B2_ERR_MLOCK`] or
+///   [`W3B2_ERR_INVALID_HANDLE`] on failure, leaving the old key in place. The error details
+///   are stored via [`get_last_error`].
+#[no_mangle]
+pub unsafe extern "C" fn w3b2_rotate_key(
+    key_id: Handle,
+    new_key_ptr: *const u8,
+    out_new_pubkey: *mut u8,
+) -> i32 {
+    if key_id == 0 || new_key_ptr.is_null() || out_new_pubkey.is_null() {
+        set_last_error("null or invalid argument");
+        return W3B2_ERR_INVALID_ARGUMENT;
+    }
+
+    let mut secret = vec![0u8; 64];
+    if !try_mlock(&mut secret) {
+        set_last_error("mlock failed (process may allow swapping)");
+        return W3B2_ERR_MLOCK;
+    }
+    let mut entry = KeyEntry {
+        secret,
+        locked: true,
+    };
+    entry
+        .secret
+        .copy_from_slice(slice::from_raw_parts(new_key_ptr, 64));
+    if let Err(e) = Keypair::try_from(entry.secret.as_slice()) {
+        set_last_error(format!("invalid keypair: {}", e));
+        return W3B2_ERR_PARSE;
+    }
+    let new_pubkey: [u8; 32] = entry.secret[32..64].try_into().unwrap();
+
+    let Some(mut current) = KEY_TABLE.get_mut(&key_id) else {
+        set_last_error("invalid handle");
+        return W3B2_ERR_INVALID_HANDLE;
+    };
+    std::mem::swap(&mut *current, &mut entry);
+    drop(current);
+    // `entry` now holds the old key, which is zeroized outside of the shard lock.
+    drop(entry);
+
+    slice::from_raw_parts_mut(out_new_pubkey, 32).copy_from_slice(&new_pubkey);
+    W3B2_OK
 }
 
 /// Retrieves the public key from a loaded keypair.
@@ -275,48 +549,52 @@ pub unsafe extern "C" fn sign_with_key_once(
     let blockhash_bytes = slice::from_raw_parts(blockhash_ptr, 32);
     let key_bytes = slice::from_raw_parts(keypair_ptr, keypair_len);
 
-    let message = match bincode::serde::decode_from_slice::<Message, _>(
-        msg_bytes,
-        bincode::config::standard(),
-    ) {
-        Ok((m, _)) => m,
-        Err(e) => {
-            set_last_error(format!("Message decode failed: {}", e));
-            return ptr::null_mut();
-        }
-    };
-
-    let kp = match Keypair::try_from(key_bytes) {
-        Ok(k) => k,
+    let _permit = SIGN_LIMITER.acquire();
+    match sign_message(msg_bytes, blockhash_bytes, key_bytes) {
+        Ok(encoded) => into_raw_buffer(encoded, out_len),
         Err(e) => {
-            set_last_error(format!("Keypair parse failed: {}", e));
-            return ptr::null_mut();
+            set_last_error(e);
+            ptr::null_mut()
         }
-    };
-
-    let recent_blockhash = Hash::new_from_array(blockhash_bytes.try_into().unwrap());
-    let mut tx = Transaction::new_unsigned(message);
-    if let Err(e) = tx.try_sign(&[&kp], recent_blockhash) {
-        set_last_error(format!("Transaction sign failed: {}", e));
-        return ptr::null_mut();
     }
+}
 
-    let encoded = match bincode::serde::encode_to_vec(&tx, bincode::config::standard()) {
-        Ok(v) => v,
-        Err(e) => {
-            set_last_error(format!("Transaction serialize failed: {}", e));
-            return ptr::null_mut();
-        }
-    };
-
-    let len = encoded.len();
-    let boxed = encoded.into_boxed_slice();
+/// Hands `buf` over to the caller, who must release it with [`free_buffer`].
+unsafe fn into_raw_buffer(buf: Vec<u8>, out_len: *mut usize) -> *mut u8 {
+    let len = buf.len();
+    let boxed = buf.into_boxed_slice();
     let ptr_out = Box::into_raw(boxed) as *mut u8;
 
     *out_len = len;
     ptr_out
 }
 
+/// Decodes a `Message`, signs it with `key_bytes`, and returns the serialized `Transaction`.
+///
+/// Callers must hold a [`SignPermit`].
+fn sign_message(
+    msg_bytes: &[u8],
+    blockhash_bytes: &[u8],
+    key_bytes: &[u8],
+) -> Result<Vec<u8>, String> {
+    let (message, _) =
+        bincode::serde::decode_from_slice::<Message, _>(msg_bytes, bincode::config::standard())
+            .map_err(|e| format!("Message decode failed: {}", e))?;
+
+    let kp = Keypair::try_from(key_bytes).map_err(|e| format!("Keypair parse failed: {}", e))?;
+
+    let recent_blockhash = Hash::new_from_array(blockhash_bytes.try_into().unwrap());
+    let mut tx = Transaction::new_unsigned(message);
+    tx.try_sign(&[&kp], recent_blockhash)
+        .map_err(|e| format!("Transaction sign failed: {}", e))?;
+
+    let encoded = bincode::serde::encode_to_vec(&tx, bincode::config::standard())
+        .map_err(|e| format!("Transaction serialize failed: {}", e))?;
+
+    TOTAL_SIGNS.fetch_add(1, Ordering::Relaxed);
+    Ok(encoded)
+}
+
 /// Signs a message using a preloaded key handle.
 ///
 /// # Safety
@@ -329,7 +607,9 @@ pub unsafe extern "C" fn sign_with_key_once(
 /// - `NULL` on failure.
 ///
 /// # Notes
-/// - Internally calls [`sign_with_key_once`].
+/// - Signs with the stored key while holding a shared lock on its table shard, so the
+///   secret is never copied out of its `mlock`ed buffer and concurrent signs do not block
+///   each other.
 #[no_mangle]
 pub unsafe extern "C" fn sign_with_handle(
     handle: Handle,
@@ -348,22 +628,90 @@ pub unsafe extern "C" fn sign_with_handle(
         return ptr::null_mut();
     }
 
-    let secret = match KEY_TABLE.get(&handle) {
-        Some(entry) => entry.secret.clone(),
-        None => {
-            set_last_error("invalid handle");
-            return ptr::null_mut();
+    // Wait for a slot before locking the shard, so a saturated limit never holds up
+    // loads and unloads.
+    let _permit = SIGN_LIMITER.acquire();
+    let Some(entry) = KEY_TABLE.get(&handle) else {
+        set_last_error("invalid handle");
+        return ptr::null_mut();
+    };
+
+    let msg_bytes = slice::from_raw_parts(msg_ptr, msg_len);
+    let blockhash_bytes = slice::from_raw_parts(blockhash_ptr, 32);
+    let result = sign_message(msg_bytes, blockhash_bytes, &entry.secret);
+    drop(entry);
+
+    match result {
+        Ok(encoded) => into_raw_buffer(encoded, out_len),
+        Err(e) => {
+            set_last_error(e);
+            ptr::null_mut()
         }
+    }
+}
+
+/// Signs a serialized Solana `Message` with a loaded key and returns only the signature.
+///
+/// `message_ptr` holds a `Message` in the same encoding the W3B2 gateway and
+/// `TransactionBuilder` return (bincode, standard configuration). The signature is computed
+/// over the message's wire format, exactly as the Solana runtime verifies it.
+///
+/// # Safety
+/// - `message_ptr` must point to `message_len` bytes.
+/// - `out_sig` must point to a writable buffer of at least 64 bytes.
+///
+/// # Notes
+/// - The message is signed as-is, so its `recent_blockhash` must already be set.
+/// - The caller is responsible for placing the signature at the index of the key in the
+///   message's signer list (`account_keys[..num_required_signatures]`); the fee payer is
+///   always at index `0`.
+///
+/// # Returns
+/// - [`W3B2_OK`] on success, with the 64-byte signature written to `out_sig`.
+/// - [`W3B2_ERR_INVALID_ARGUMENT`], [`W3B2_ERR_INVALID_HANDLE`] or [`W3B2_ERR_PARSE`] on
+///   failure. The error details are stored via [`get_last_error`].
+#[no_mangle]
+pub unsafe extern "C" fn w3b2_sign_transaction_message(
+    key_id: Handle,
+    message_ptr: *const u8,
+    message_len: usize,
+    out_sig: *mut u8,
+) -> i32 {
+    if key_id == 0 || message_ptr.is_null() || message_len == 0 || out_sig.is_null() {
+        set_last_error("null or invalid argument");
+        return W3B2_ERR_INVALID_ARGUMENT;
+    }
+
+    let msg_bytes = slice::from_raw_parts(message_ptr, message_len);
+    let message = match bincode::serde::decode_from_slice::<Message, _>(
+        msg_bytes,
+        bincode::config::standard(),
+    ) {
+        Ok((m, _)) => m,
+        Err(e) => {
+            set_last_error(format!("Message decode failed: {}", e));
+            return W3B2_ERR_PARSE;
+        }
+    };
+
+    let _permit = SIGN_LIMITER.acquire();
+    let Some(entry) = KEY_TABLE.get(&key_id) else {
+        set_last_error("invalid handle");
+        return W3B2_ERR_INVALID_HANDLE;
     };
+    let kp = match Keypair::try_from(entry.secret.as_slice()) {
+        Ok(k) => k,
+        Err(e) => {
+            set_last_error(format!("Keypair parse failed: {}", e));
+            return W3B2_ERR_PARSE;
+        }
+    };
+    drop(entry);
 
-    sign_with_key_once(
-        msg_ptr,
-        msg_len,
-        blockhash_ptr,
-        secret.as_ptr(),
-        secret.len(),
-        out_len,
-    )
+    let signature = kp.sign_message(&message.serialize());
+    slice::from_raw_parts_mut(out_sig, 64).copy_from_slice(signature.as_ref());
+    TOTAL_SIGNS.fetch_add(1, Ordering::Relaxed);
+    W3B2_OK
 }
 
 /// Clears all loaded keys from memory.
@@ -373,6 +721,82 @@ pub unsafe extern "C" fn sign_with_handle(
 /// - Should be called before program exit for security reasons.
 #[no_mangle]
 pub extern "C" fn clear_all_keys() {
+    let count = KEY_TABLE.len() as u64;
     KEY_TABLE.clear();
     KEY_TABLE.shrink_to_fit();
+    KEYS_UNLOADED.fetch_add(count, Ordering::Relaxed);
+}
+
+/// Limits how many signs may run at the same time across all threads.
+///
+/// # Notes
+/// - `0` removes the limit (the default).
+/// - Threads that call a signing function while the limit is reached block until another
+///   sign completes. A low limit bounds CPU usage at the cost of latency under load.
+/// - Lowering the limit does not interrupt signs already in progress.
+#[no_mangle]
+pub extern "C" fn w3b2_set_max_concurrent_signs(n: u64) {
+    SIGN_LIMITER.set_max(n);
+}
+
+/// Writes the current usage counters to `out`.
+///
+/// # Safety
+/// - `out` must be a valid, non-null pointer to a [`W3b2SignerStats`].
+///
+/// # Returns
+/// - `true` on success.
+/// - `false` if `out` is null.
+#[no_mangle]
+pub unsafe extern "C" fn w3b2_get_stats(out: *mut W3b2SignerStats) -> bool {
+    if out.is_null() {
+        set_last_error("null out argument");
+        return false;
+    }
+    *out = W3b2SignerStats {
+        total_signs: TOTAL_SIGNS.load(Ordering::Relaxed),
+        in_flight: SIGN_LIMITER.in_flight(),
+        keys_loaded: KEYS_LOADED.load(Ordering::Relaxed),
+        keys_unloaded: KEYS_UNLOADED.load(Ordering::Relaxed),
+    };
+    true
+}
+
+/// Verifies an ed25519 signature over an arbitrary message.
+///
+/// Lets a host application check the output of the signing functions (e.g. the
+/// signature of a transaction returned by [`sign_with_handle`] over its message bytes)
+/// without implementing ed25519 verification itself.
+///
+/// # Safety
+/// - `pubkey_ptr` must point to 32 bytes.
+/// - `message_ptr` must point to `message_len` bytes. It may be null if `message_len` is `0`.
+/// - `signature_ptr` must point to 64 bytes.
+///
+/// # Returns
+/// - `1` if the signature is valid for the public key and message.
+/// - `0` if it is not.
+/// - `-1` if an argument is null; the error is stored via [`get_last_error`].
+#[no_mangle]
+pub unsafe extern "C" fn w3b2_verify(
+    pubkey_ptr: *const u8,
+    message_ptr: *const u8,
+    message_len: usize,
+    signature_ptr: *const u8,
+) -> i32 {
+    if pubkey_ptr.is_null() || signature_ptr.is_null() || (message_ptr.is_null() && message_len > 0)
+    {
+        set_last_error("null or invalid argument");
+        return -1;
+    }
+
+    let pubkey_bytes = slice::from_raw_parts(pubkey_ptr, 32);
+    let message = if message_len == 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(message_ptr, message_len)
+    };
+    let signature_bytes: [u8; 64] = slice::from_raw_parts(signature_ptr, 64).try_into().unwrap();
+
+    i32::from(Signature::from(signature_bytes).verify(pubkey_bytes, message))
 }
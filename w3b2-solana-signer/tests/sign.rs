@@ -1,20 +1,40 @@
 #![allow(deprecated)]
+#[path = "../../w3b2-solana-connector/tests/mock/svm.rs"]
+mod svm;
+
+use litesvm::LiteSVM;
 use serial_test::serial;
+use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
     hash::Hash,
     message::Message,
+    native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::{Keypair, Signature, Signer},
     system_instruction,
     transaction::Transaction,
 };
-use std::{ffi::CStr, ptr};
+use std::{
+    ffi::{CStr, CString},
+    ptr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+use svm::PATH_SBF;
+use w3b2_solana_connector::client::TransactionBuilder;
 use w3b2_solana_signer::ffi::{
     clear_all_keys, free_buffer, generate_keypair, get_last_error, get_public_key, load_key,
-    sign_with_handle, sign_with_key_once, unload_key,
+    sign_with_handle, sign_with_key_once, unload_key, w3b2_get_stats, w3b2_load_from_file,
+    w3b2_rotate_key, w3b2_set_max_concurrent_signs, w3b2_sign_transaction_message, w3b2_verify,
+    W3b2SignerStats, W3B2_ERR_FILE_NOT_FOUND, W3B2_ERR_INVALID_ARGUMENT, W3B2_ERR_INVALID_HANDLE,
+    W3B2_ERR_PARSE, W3B2_OK,
 };
 
 #[test]
+#[serial]
 fn test_sign_with_key_once_ffi_equivalence() {
     let kp = Keypair::new();
     let to = Pubkey::new_unique();
@@ -240,3 +260,419 @@ fn test_get_public_key_success() {
     }
     clear_all_keys();
 }
+
+fn stats() -> W3b2SignerStats {
+    let mut stats = W3b2SignerStats::default();
+    assert!(unsafe { w3b2_get_stats(&mut stats) });
+    stats
+}
+
+#[test]
+#[serial]
+fn test_concurrent_signing_respects_limit_and_updates_stats() {
+    const THREADS: usize = 32;
+    const SIGNS_PER_THREAD: usize = 50;
+    const MAX_CONCURRENT: u64 = 4;
+
+    clear_all_keys();
+    let before = stats();
+    w3b2_set_max_concurrent_signs(MAX_CONCURRENT);
+
+    let keypairs: Vec<Keypair> = (0..8).map(|_| Keypair::new()).collect();
+    let handles: Vec<u64> = keypairs
+        .iter()
+        .map(|kp| unsafe { load_key(kp.to_bytes().as_ptr(), 64) })
+        .collect();
+    assert!(handles.iter().all(|h| *h != 0));
+
+    // Samples the in-flight counter while the workers run.
+    let done = Arc::new(AtomicBool::new(false));
+    let observer = {
+        let done = done.clone();
+        thread::spawn(move || {
+            let mut peak = 0;
+            while !done.load(Ordering::Relaxed) {
+                peak = peak.max(stats().in_flight);
+                thread::yield_now();
+            }
+            peak
+        })
+    };
+
+    let workers: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let handle = handles[i % handles.len()];
+            let payer = keypairs[i % keypairs.len()].pubkey();
+            thread::spawn(move || {
+                let ix = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+                let msg = Message::new(&[ix], Some(&payer));
+                let msg_bytes =
+                    bincode::serde::encode_to_vec(&msg, bincode::config::standard()).unwrap();
+                let blockhash = Hash::new_unique();
+                for _ in 0..SIGNS_PER_THREAD {
+                    let mut out_len = 0;
+                    let ptr = unsafe {
+                        sign_with_handle(
+                            handle,
+                            msg_bytes.as_ptr(),
+                            msg_bytes.len(),
+                            blockhash.as_ref().as_ptr(),
+                            &mut out_len,
+                        )
+                    };
+                    assert!(!ptr.is_null(), "sign_with_handle returned null");
+                    let tx_bytes = unsafe { std::slice::from_raw_parts(ptr, out_len) };
+                    let tx: Transaction =
+                        bincode::serde::decode_from_slice(tx_bytes, bincode::config::standard())
+                            .unwrap()
+                            .0;
+                    assert!(tx.verify().is_ok());
+                    unsafe { free_buffer(ptr, out_len) };
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    done.store(true, Ordering::Relaxed);
+    let peak_in_flight = observer.join().unwrap();
+
+    for handle in &handles {
+        unload_key(*handle);
+    }
+    w3b2_set_max_concurrent_signs(0);
+    let after = stats();
+
+    assert!(
+        peak_in_flight <= MAX_CONCURRENT,
+        "peak in-flight was {peak_in_flight}"
+    );
+    assert_eq!(
+        after.total_signs - before.total_signs,
+        (THREADS * SIGNS_PER_THREAD) as u64
+    );
+    assert_eq!(after.in_flight, 0);
+    assert_eq!(after.keys_loaded - before.keys_loaded, handles.len() as u64);
+    assert_eq!(
+        after.keys_unloaded - before.keys_unloaded,
+        handles.len() as u64
+    );
+    assert!(!unsafe { w3b2_get_stats(ptr::null_mut()) });
+    clear_all_keys();
+}
+
+#[test]
+#[serial]
+fn test_verify_signature_round_trip() {
+    let kp = Keypair::new();
+    let key_bytes = kp.to_bytes();
+    let handle = unsafe { load_key(key_bytes.as_ptr(), key_bytes.len()) };
+    assert_ne!(handle, 0);
+
+    // Sign a transaction through the FFI and verify its signature over the message bytes.
+    let ix = system_instruction::transfer(&kp.pubkey(), &Pubkey::new_unique(), 7);
+    let msg = Message::new(&[ix], Some(&kp.pubkey()));
+    let msg_bytes = bincode::serde::encode_to_vec(&msg, bincode::config::standard()).unwrap();
+    let blockhash = Hash::new_unique();
+    let mut out_len = 0;
+    let ptr = unsafe {
+        sign_with_handle(
+            handle,
+            msg_bytes.as_ptr(),
+            msg_bytes.len(),
+            blockhash.as_ref().as_ptr(),
+            &mut out_len,
+        )
+    };
+    assert!(!ptr.is_null());
+    let tx: Transaction = bincode::serde::decode_from_slice(
+        unsafe { std::slice::from_raw_parts(ptr, out_len) },
+        bincode::config::standard(),
+    )
+    .unwrap()
+    .0;
+    unsafe { free_buffer(ptr, out_len) };
+
+    let pubkey = kp.pubkey().to_bytes();
+    let signed = tx.message_data();
+    let signature = tx.signatures[0];
+    let verify = |message: &[u8], pubkey: &[u8; 32]| unsafe {
+        w3b2_verify(
+            pubkey.as_ptr(),
+            message.as_ptr(),
+            message.len(),
+            signature.as_ref().as_ptr(),
+        )
+    };
+    assert_eq!(verify(&signed, &pubkey), 1);
+
+    // An oracle price message (command_id | price | timestamp | user profile) signed off-band.
+    let price_message =
+        w3b2_solana_connector::oracle::build_price_message(7, 1_000, 42, Pubkey::new_unique());
+    let price_signature = kp.sign_message(&price_message);
+    let verified = unsafe {
+        w3b2_verify(
+            pubkey.as_ptr(),
+            price_message.as_ptr(),
+            price_message.len(),
+            price_signature.as_ref().as_ptr(),
+        )
+    };
+    assert_eq!(verified, 1);
+
+    // Negative cases: a tampered message and the wrong public key.
+    let mut tampered = signed.clone();
+    tampered[0] ^= 0xff;
+    assert_eq!(verify(&tampered, &pubkey), 0);
+    assert_eq!(verify(&signed, &Keypair::new().pubkey().to_bytes()), 0);
+
+    let result = unsafe { w3b2_verify(ptr::null(), signed.as_ptr(), signed.len(), ptr::null()) };
+    assert_eq!(result, -1);
+    let error_msg = unsafe { CStr::from_ptr(get_last_error()).to_str().unwrap() };
+    assert_eq!(error_msg, "null or invalid argument");
+
+    unload_key(handle);
+    clear_all_keys();
+}
+
+/// Writes `contents` to a unique file in the temp directory and returns its path.
+fn write_temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("w3b2-signer-{}-{}.json", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn load_from_file(path: &std::path::Path) -> (i32, u64) {
+    let c_path = CString::new(path.to_str().unwrap()).unwrap();
+    let mut handle = 0;
+    let code = unsafe { w3b2_load_from_file(c_path.as_ptr(), &mut handle) };
+    (code, handle)
+}
+
+#[test]
+#[serial]
+fn test_load_from_file() {
+    clear_all_keys();
+    let kp = Keypair::new();
+    let json = format!("{:?}", kp.to_bytes().to_vec());
+    let key_path = write_temp_file("valid", &json);
+    let bad_path = write_temp_file("invalid", "[1, 2, 3]");
+
+    // A valid Solana CLI keypair file loads and exposes the expected public key.
+    let (code, handle) = load_from_file(&key_path);
+    assert_eq!(code, W3B2_OK);
+    assert_ne!(handle, 0);
+    let mut out_len = 0;
+    let pubkey_ptr = unsafe { get_public_key(handle, &mut out_len) };
+    assert!(!pubkey_ptr.is_null());
+    assert_eq!(
+        unsafe { std::slice::from_raw_parts(pubkey_ptr, out_len) },
+        kp.pubkey().to_bytes()
+    );
+    unsafe { free_buffer(pubkey_ptr, out_len) };
+
+    // Each failure mode has its own code.
+    let (code, _) = load_from_file(&std::env::temp_dir().join("w3b2-signer-missing.json"));
+    assert_eq!(code, W3B2_ERR_FILE_NOT_FOUND);
+    let (code, _) = load_from_file(&bad_path);
+    assert_eq!(code, W3B2_ERR_PARSE);
+    let error_msg = unsafe { CStr::from_ptr(get_last_error()).to_str().unwrap() };
+    assert!(error_msg.contains("invalid keypair file"));
+    let code = unsafe { w3b2_load_from_file(ptr::null(), ptr::null_mut()) };
+    assert_eq!(code, W3B2_ERR_INVALID_ARGUMENT);
+
+    std::fs::remove_file(key_path).unwrap();
+    std::fs::remove_file(bad_path).unwrap();
+    unload_key(handle);
+    clear_all_keys();
+}
+
+/// A constant path to the compiled on-chain program binary (`.so` file).
+#[test]
+#[serial]
+fn test_rotate_key_swaps_the_key_under_concurrent_signing() {
+    const THREADS: usize = 8;
+    const ROTATIONS: usize = 50;
+
+    clear_all_keys();
+    let keypairs: Arc<Vec<Keypair>> = Arc::new((0..4).map(|_| Keypair::new()).collect());
+    let handle = unsafe { load_key(keypairs[0].to_bytes().as_ptr(), 64) };
+    assert_ne!(handle, 0);
+    let message = Message::new(
+        &[system_instruction::transfer(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            1,
+        )],
+        None,
+    );
+    let message_bytes =
+        bincode::serde::encode_to_vec(&message, bincode::config::standard()).unwrap();
+    let signed_data = message.serialize();
+
+    // Every signature made while the key rotates must verify against exactly one of the keys.
+    let done = Arc::new(AtomicBool::new(false));
+    let workers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let (done, keypairs) = (done.clone(), keypairs.clone());
+            let (message_bytes, signed_data) = (message_bytes.clone(), signed_data.clone());
+            thread::spawn(move || {
+                let mut signs = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let mut signature = [0u8; 64];
+                    let code = unsafe {
+                        w3b2_sign_transaction_message(
+                            handle,
+                            message_bytes.as_ptr(),
+                            message_bytes.len(),
+                            signature.as_mut_ptr(),
+                        )
+                    };
+                    assert_eq!(code, W3B2_OK);
+                    let signature = Signature::from(signature);
+                    let matching = keypairs
+                        .iter()
+                        .filter(|kp| signature.verify(kp.pubkey().as_ref(), &signed_data))
+                        .count();
+                    assert_eq!(matching, 1, "a signature must come from a single whole key");
+                    signs += 1;
+                }
+                signs
+            })
+        })
+        .collect();
+
+    let mut new_pubkey = [0u8; 32];
+    for i in 1..=ROTATIONS {
+        let next = &keypairs[i % keypairs.len()];
+        let code =
+            unsafe { w3b2_rotate_key(handle, next.to_bytes().as_ptr(), new_pubkey.as_mut_ptr()) };
+        assert_eq!(code, W3B2_OK);
+        assert_eq!(new_pubkey, next.pubkey().to_bytes());
+        thread::yield_now();
+    }
+    done.store(true, Ordering::Relaxed);
+    let signs: usize = workers.into_iter().map(|w| w.join().unwrap()).sum();
+    assert!(signs > 0);
+
+    // The id now refers to the last key it was rotated to.
+    let last = &keypairs[ROTATIONS % keypairs.len()];
+    let mut out_len = 0;
+    let pubkey = unsafe { get_public_key(handle, &mut out_len) };
+    assert_eq!(
+        unsafe { std::slice::from_raw_parts(pubkey, out_len) },
+        last.pubkey().as_ref()
+    );
+    unsafe { free_buffer(pubkey, out_len) };
+
+    // A failed rotation leaves the current key in place.
+    let code = unsafe { w3b2_rotate_key(handle, [0u8; 64].as_ptr(), new_pubkey.as_mut_ptr()) };
+    assert_eq!(code, W3B2_ERR_PARSE);
+    let code = unsafe {
+        w3b2_rotate_key(
+            99999,
+            keypairs[0].to_bytes().as_ptr(),
+            new_pubkey.as_mut_ptr(),
+        )
+    };
+    assert_eq!(code, W3B2_ERR_INVALID_HANDLE);
+    let code = unsafe { w3b2_rotate_key(handle, ptr::null(), new_pubkey.as_mut_ptr()) };
+    assert_eq!(code, W3B2_ERR_INVALID_ARGUMENT);
+    let pubkey = unsafe { get_public_key(handle, &mut out_len) };
+    assert_eq!(
+        unsafe { std::slice::from_raw_parts(pubkey, out_len) },
+        last.pubkey().as_ref()
+    );
+    unsafe { free_buffer(pubkey, out_len) };
+
+    unload_key(handle);
+    clear_all_keys();
+}
+
+/// Signs gateway-encoded `message` bytes through `w3b2_sign_transaction_message` and
+/// assembles the transaction, placing the signature at the fee payer's index.
+fn sign_message_via_ffi(handle: u64, message: &Message) -> Transaction {
+    let message_bytes =
+        bincode::serde::encode_to_vec(message, bincode::config::standard()).unwrap();
+    let mut signature = [0u8; 64];
+    let code = unsafe {
+        w3b2_sign_transaction_message(
+            handle,
+            message_bytes.as_ptr(),
+            message_bytes.len(),
+            signature.as_mut_ptr(),
+        )
+    };
+    assert_eq!(code, W3B2_OK);
+
+    let mut tx = Transaction::new_unsigned(message.clone());
+    tx.signatures[0] = Signature::from(signature);
+    tx
+}
+
+#[test]
+#[serial]
+fn test_sign_transaction_message_is_accepted_by_the_runtime() {
+    let mut svm = LiteSVM::new();
+    let payer = Keypair::new();
+    svm.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    let handle = unsafe { load_key(payer.to_bytes().as_ptr(), 64) };
+    assert_ne!(handle, 0);
+
+    let recipient = Pubkey::new_unique();
+    let ix = system_instruction::transfer(&payer.pubkey(), &recipient, 1_000_000);
+    let mut message = Message::new(&[ix], Some(&payer.pubkey()));
+    message.recent_blockhash = svm.latest_blockhash();
+
+    let tx = sign_message_via_ffi(handle, &message);
+    assert!(tx.verify().is_ok());
+    svm.send_transaction(tx)
+        .expect("transaction signed via FFI should be accepted");
+    assert_eq!(svm.get_balance(&recipient), Some(1_000_000));
+
+    let mut signature = [0u8; 64];
+    let code =
+        unsafe { w3b2_sign_transaction_message(99999, [1u8].as_ptr(), 1, signature.as_mut_ptr()) };
+    assert_ne!(code, W3B2_OK);
+    let code = unsafe {
+        let bytes = bincode::serde::encode_to_vec(&message, bincode::config::standard()).unwrap();
+        w3b2_sign_transaction_message(99999, bytes.as_ptr(), bytes.len(), signature.as_mut_ptr())
+    };
+    assert_eq!(code, W3B2_ERR_INVALID_HANDLE);
+
+    unload_key(handle);
+    clear_all_keys();
+}
+
+#[test]
+#[serial]
+#[ignore = "Requires a compiled BPF program"]
+fn test_sign_connector_message_and_submit() {
+    let mut svm = LiteSVM::new();
+    svm.add_program_from_file(w3b2_solana_program::ID, PATH_SBF)
+        .unwrap();
+    let admin = Keypair::new();
+    svm.airdrop(&admin.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    let handle = unsafe { load_key(admin.to_bytes().as_ptr(), 64) };
+    assert_ne!(handle, 0);
+
+    // The connector returns the same bytes as the gateway's `PrepareAdminRegisterProfile`.
+    let builder =
+        TransactionBuilder::new(Arc::new(RpcClient::new("http://127.0.0.1:0".to_string())));
+    let message_bytes =
+        builder.prepare_admin_register_profile(admin.pubkey(), 0, Pubkey::new_unique());
+    let mut message: Message =
+        bincode::serde::decode_from_slice(&message_bytes, bincode::config::standard())
+            .unwrap()
+            .0;
+    message.recent_blockhash = svm.latest_blockhash();
+
+    let tx = sign_message_via_ffi(handle, &message);
+    svm.send_transaction(tx)
+        .expect("admin_register_profile signed via FFI should succeed");
+
+    unload_key(handle);
+    clear_all_keys();
+}